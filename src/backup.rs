@@ -0,0 +1,123 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use log::{debug, warn};
+
+use crate::error::{BasecampError, BasecampResult};
+
+/// Copies of each configuration file kept when `backups` isn't set
+pub const DEFAULT_BACKUPS: usize = 10;
+
+/// Copies kept per file, from `backups` in config.yaml
+static LIMIT: Mutex<Option<usize>> = Mutex::new(None);
+
+/// A copy of config.yaml or codebases.yaml taken before it was rewritten
+///
+/// Stored as `.basecamp/backups/<stem>-<number>.yaml`, numbered across both
+/// files so the highest number is the latest save.
+#[derive(Debug, Clone)]
+pub struct Backup {
+    /// Name shown and accepted by `config restore` (e.g. "codebases-12")
+    pub id: String,
+    /// File the copy belongs to (e.g. "codebases.yaml")
+    pub file: String,
+    pub path: PathBuf,
+    /// Unix time the copy was taken
+    pub saved_at: u64,
+    number: u64,
+}
+
+/// Set how many copies of each file are kept (`None` for the default)
+pub fn set_limit(limit: Option<usize>) {
+    *LIMIT.lock().unwrap() = limit;
+}
+
+/// Get path to the backups directory next to the configuration files
+pub fn backups_dir(basecamp_dir: &Path) -> PathBuf {
+    basecamp_dir.join("backups")
+}
+
+/// Backups in a .basecamp directory, latest first
+pub fn list(basecamp_dir: &Path) -> Vec<Backup> {
+    let Ok(entries) = fs::read_dir(backups_dir(basecamp_dir)) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<Backup> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let id = name.strip_suffix(".yaml")?.to_string();
+            let (stem, number) = id.rsplit_once('-')?;
+            let number = number.parse().ok()?;
+            let saved_at = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|age| age.as_secs())
+                .unwrap_or_default();
+            Some(Backup {
+                file: format!("{}.yaml", stem),
+                id,
+                path: entry.path(),
+                saved_at,
+                number,
+            })
+        })
+        .collect();
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.number));
+    backups
+}
+
+/// Copy a configuration file into the backups before it is rewritten
+///
+/// Older copies of the same file beyond the limit are deleted. Failures are
+/// only logged, so they never prevent the save itself.
+pub fn keep(path: &Path) {
+    let limit = LIMIT.lock().unwrap().unwrap_or(DEFAULT_BACKUPS);
+    let (Some(basecamp_dir), Some(file)) = (path.parent(), path.file_name()) else {
+        return;
+    };
+    if limit == 0 || !path.exists() {
+        return;
+    }
+
+    let file = file.to_string_lossy();
+    let stem = file.strip_suffix(".yaml").unwrap_or(&file);
+    let backups = list(basecamp_dir);
+    let number = backups.first().map_or(1, |latest| latest.number + 1);
+    let target = backups_dir(basecamp_dir).join(format!("{}-{}.yaml", stem, number));
+
+    let copied = fs::create_dir_all(backups_dir(basecamp_dir)).and_then(|_| fs::copy(path, &target));
+    if let Err(e) = copied {
+        warn!("Failed to back up {:?}: {}", path, e);
+        return;
+    }
+    debug!("Backed up {:?} to {:?}", path, target);
+
+    // The new copy isn't listed yet, so `limit - 1` older ones stay
+    for old in backups.iter().filter(|backup| *backup.file == *file).skip(limit - 1) {
+        if let Err(e) = fs::remove_file(&old.path) {
+            warn!("Failed to delete old backup {:?}: {}", old.path, e);
+        }
+    }
+}
+
+/// Write a backup back over its configuration file, keeping the current one first
+pub fn restore(basecamp_dir: &Path, backup: &Backup) -> BasecampResult<()> {
+    let path = basecamp_dir.join(&backup.file);
+    let content = fs::read_to_string(&backup.path)?;
+    if let Err(e) = serde_yaml::from_str::<serde_yaml::Value>(&content) {
+        return Err(BasecampError::InvalidConfig(format!(
+            "Backup {} isn't valid YAML: {}",
+            backup.id, e
+        )));
+    }
+
+    keep(&path);
+    fs::write(&path, content)?;
+    Ok(())
+}
@@ -1,4 +1,9 @@
-use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::core::filter::RepoFilter;
+use crate::runner;
 
 /// BaseCamp: A streamlined tool for managing multiple codebases and repositories
 #[derive(Parser, Debug)]
@@ -6,9 +11,62 @@ use clap::{Parser, Subcommand};
 #[clap(propagate_version = true)]
 pub struct Cli {
     /// Verbosity level (-v, -vv, -vvv)
-    #[clap(short, long, action = clap::ArgAction::Count)]
+    #[clap(short, long, global = true, action = clap::ArgAction::Count)]
     pub verbose: u8,
 
+    /// Only show errors and final results
+    #[clap(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Never prompt; use defaults or command-line values (implied when stdin is not a terminal)
+    #[clap(long, global = true, env = "BASECAMP_NON_INTERACTIVE")]
+    pub non_interactive: bool,
+
+    /// Disable colors and progress bars (also when NO_COLOR is set or stdout is not a terminal)
+    #[clap(long, global = true)]
+    pub no_color: bool,
+
+    /// Answer 'yes' to every confirmation
+    #[clap(short = 'y', long, global = true, env = "BASECAMP_YES")]
+    pub yes: bool,
+
+    /// Workspace directory to operate on instead of the current directory
+    #[clap(long, global = true, env = "BASECAMP_CONFIG_DIR", value_name = "DIR")]
+    pub config_dir: Option<PathBuf>,
+
+    /// Never access the network; commands needing it fail or only report what they would do
+    #[clap(
+        long,
+        global = true,
+        env = "BASECAMP_OFFLINE",
+        value_parser = clap::builder::BoolishValueParser::new()
+    )]
+    pub offline: bool,
+
+    /// Git mode; `fake` clones local stub repositories and skips fetches (for tests and CI)
+    #[clap(
+        long,
+        global = true,
+        env = "BASECAMP_GIT_MODE",
+        value_enum,
+        value_name = "MODE",
+        default_value = "real"
+    )]
+    pub git_mode: GitMode,
+
+    /// Show the changes to config.yaml and codebases.yaml as a diff and ask before saving them
+    #[clap(
+        long,
+        global = true,
+        env = "BASECAMP_DIFF",
+        value_parser = clap::builder::BoolishValueParser::new()
+    )]
+    pub diff: bool,
+
+    /// Use a profile from config.yaml instead of the selected one
+    #[clap(long, global = true, env = "BASECAMP_PROFILE")]
+    pub profile: Option<String>,
+
     /// Subcommands
     #[clap(subcommand)]
     pub command: Commands,
@@ -18,6 +76,7 @@ pub struct Cli {
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Initialize a new BaseCamp configuration
+    #[clap(after_help = INIT_EXAMPLES)]
     Init {
         /// Connection type: 'https' or 'ssh'
         #[clap(long)]
@@ -30,10 +89,18 @@ pub enum Commands {
         /// Organization name or GitHub username
         #[clap(long)]
         name: Option<String>,
-        
-        /// Non-interactive mode
+
+        /// Hosting service: 'github', 'github-enterprise', 'gitlab', 'bitbucket' or 'custom'
         #[clap(long)]
-        non_interactive: bool,
+        provider: Option<String>,
+
+        /// Host of a self-hosted service (e.g. git.example.com)
+        #[clap(long)]
+        host: Option<String>,
+
+        /// Start from a codebases.yaml template: a name in ~/.config/basecamp/templates, a path or a URL
+        #[clap(long)]
+        template: Option<String>,
         
         /// Force overwrite existing configuration
         #[clap(long)]
@@ -41,32 +108,117 @@ pub enum Commands {
     },
 
     /// Install all repositories for all codebases or a specific codebase
+    #[clap(after_help = INSTALL_EXAMPLES)]
     Install {
-        /// Codebase name (if not specified, all codebases will be installed)
+        /// Codebase name (if not specified, `default_codebase` or all codebases will be installed)
         codebase: Option<String>,
 
-        /// Number of parallel clone operations
-        #[clap(short, long, default_value = "4")]
-        parallel: usize,
+        /// Install every codebase, even when `default_codebase` is set
+        #[clap(long, conflicts_with = "codebase")]
+        all: bool,
+
+        /// Number of parallel clone operations [default: `parallel` in config.yaml, or 4]
+        #[clap(short, long)]
+        parallel: Option<usize>,
+
+        /// Print the run summary in a machine-readable format on stdout
+        #[clap(long, value_enum)]
+        report: Option<ReportFormat>,
+
+        /// Stop starting new clones after the first failure
+        #[clap(long, conflicts_with = "keep_going")]
+        fail_fast: bool,
+
+        /// Clone every remaining repository even when some fail (default)
+        #[clap(long)]
+        keep_going: bool,
+
+        /// Fetch and fast-forward (or rebase, per config) repositories that already exist
+        #[clap(short, long)]
+        update: bool,
+
+        /// Move these repositories to the trash and clone them again (comma-separated or repeated)
+        #[clap(long, value_name = "REPO", value_delimiter = ',')]
+        force_reclone: Vec<String>,
+
+        /// Re-clone even when repositories have uncommitted changes or unpushed commits
+        #[clap(short, long, requires = "force_reclone")]
+        force: bool,
+
+        /// Continue the latest interrupted or failed install: skip finished repositories,
+        /// move partial clones to the trash and retry the rest
+        #[clap(long, conflicts_with_all = ["codebase", "all", "force_reclone"])]
+        resume: bool,
+
+        /// Move directories in the way of a clone that aren't git repositories into the trash (see undo)
+        #[clap(long, conflicts_with = "backup_conflicts")]
+        overwrite_non_git: bool,
+
+        /// Move directories in the way of a clone that aren't git repositories into .basecamp/conflicts/
+        #[clap(long)]
+        backup_conflicts: bool,
     },
 
     /// List all codebases or repositories in a specific codebase
+    #[clap(after_help = LIST_EXAMPLES)]
     List {
         /// Codebase name (if not specified, all codebases will be listed)
         codebase: Option<String>,
+
+        /// Include branch and working tree status (from the state cache)
+        #[clap(short, long)]
+        status: bool,
+
+        /// Ignore the state cache when showing status
+        #[clap(long, requires = "status")]
+        no_cache: bool,
+
+        /// Add the default branch's CI state and the open pull requests, asked from the host (needs a token)
+        #[clap(long, requires = "status")]
+        remote: bool,
+
+        /// Only list the repositories that aren't cloned yet
+        #[clap(long)]
+        missing_only: bool,
+
+        /// Show codebases and their repositories as a tree, one repository per line
+        #[clap(long)]
+        tree: bool,
+
+        /// Show the URLs of repositories in the browser instead of their clone URLs
+        #[clap(long)]
+        web_url: bool,
+
+        /// Find repositories by name (substring or regular expression) and the codebases they're in
+        #[clap(long, value_name = "PATTERN", conflicts_with_all = ["tree", "status", "missing_only"])]
+        search: Option<String>,
     },
 
     /// Add repositories to a codebase
+    #[clap(after_help = ADD_EXAMPLES)]
     Add {
         /// Codebase name
         codebase: String,
 
         /// Repository names
-        #[clap(required = true)]
+        #[clap(required_unless_present = "from_file")]
         repositories: Vec<String>,
+
+        /// Read newline-separated repository names from a file (`-` for stdin)
+        #[clap(long, value_name = "FILE")]
+        from_file: Option<PathBuf>,
+
+        /// GitHub URL to use when no configuration exists yet (instead of prompting)
+        #[clap(long, env = "BASECAMP_GITHUB_URL")]
+        github_url: Option<String>,
+
+        /// Save repositories that fail to clone too, to install them later
+        #[clap(long)]
+        keep_failed: bool,
     },
 
     /// Remove repositories from a codebase or remove an entire codebase
+    #[clap(after_help = REMOVE_EXAMPLES)]
     Remove {
         /// Codebase name
         codebase: String,
@@ -77,6 +229,566 @@ pub enum Commands {
         /// Force removal even if there are uncommitted changes
         #[clap(short, long)]
         force: bool,
+
+        /// Show what would be removed and deleted, with sizes and local work, without changing anything
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Move clones into .basecamp/archive and out of the configuration, keeping their work
+    #[clap(after_help = ARCHIVE_EXAMPLES)]
+    Archive {
+        /// Codebase name
+        codebase: String,
+
+        /// Repository names (if not specified, the entire codebase is archived)
+        repositories: Vec<String>,
+    },
+
+    /// Bring archived repositories back into their codebase
+    Unarchive {
+        /// Codebase name
+        codebase: String,
+
+        /// Repository names (if not specified, every archived repository of the codebase)
+        repositories: Vec<String>,
+    },
+
+    /// Run a named task across the repositories of a codebase
+    #[clap(after_help = RUN_EXAMPLES)]
+    Run {
+        /// Task name (as defined under `tasks` in codebases.yaml)
+        task: String,
+
+        /// Codebase name (if not specified, the task runs in all codebases)
+        codebase: Option<String>,
+
+        /// Number of tasks run in parallel [default: `parallel` in config.yaml, or 4]
+        #[clap(short, long)]
+        parallel: Option<usize>,
+
+        /// Only run in repositories that are dirty, clean, ahead, behind, on branch=NAME or at tag[=NAME] (repeatable, all must match)
+        #[clap(long = "filter", value_name = "PREDICATE")]
+        filters: Vec<RepoFilter>,
+
+        /// Kill the task of a repository, with everything it started, after this many seconds
+        #[clap(long, value_name = "SECONDS")]
+        timeout: Option<u64>,
+
+        /// Print output live, each line prefixed with its repository
+        #[clap(long, conflicts_with = "capture")]
+        stream: bool,
+
+        /// Print the output of every repository as a block once its task is done
+        #[clap(long)]
+        capture: bool,
+
+        /// Bytes of output kept per repository, the rest is dropped
+        #[clap(long, value_name = "BYTES", default_value_t = runner::DEFAULT_MAX_OUTPUT)]
+        max_output: usize,
+    },
+
+    /// Merge the compose files of a codebase's repositories into a single stack
+    #[clap(after_help = COMPOSE_EXAMPLES)]
+    Compose {
+        /// Codebase name
+        codebase: String,
+
+        /// Output file (defaults to <codebase>/compose.yaml)
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        /// Keep original service, volume and network names instead of prefixing them with the repository name
+        #[clap(long)]
+        no_prefix: bool,
+
+        /// Run 'docker compose up -d' on the merged stack
+        #[clap(long)]
+        up: bool,
+    },
+
+    /// Print the environment variables of a codebase as export lines for eval
+    #[clap(after_help = ENV_EXAMPLES)]
+    Env {
+        /// Codebase name
+        codebase: String,
+
+        /// Repository the variables are for, filling in their {repo} placeholders
+        repo: Option<String>,
+    },
+
+    /// Open a repository in the browser, or its local checkout in an editor
+    #[clap(after_help = OPEN_EXAMPLES)]
+    Open {
+        /// Repository name
+        repo: String,
+
+        /// Codebase containing the repository (only needed when it belongs to several)
+        #[clap(short, long)]
+        codebase: Option<String>,
+
+        /// Open the local checkout in $VISUAL/$EDITOR (or VS Code) instead of the browser
+        #[clap(short, long)]
+        editor: bool,
+    },
+
+    /// Show the codebases, URL, paths, branch, last commit, remotes, state and size of a repository
+    #[clap(after_help = INFO_EXAMPLES)]
+    Info {
+        /// Repository name
+        repo: String,
+
+        /// Only show the repository in this codebase
+        #[clap(short, long)]
+        codebase: Option<String>,
+    },
+
+    /// Print the absolute path of a repository (exact, substring or close match)
+    #[clap(after_help = PATH_EXAMPLES)]
+    Path {
+        /// Repository name or part of it
+        repo: String,
+
+        /// Codebase containing the repository
+        #[clap(short, long)]
+        codebase: Option<String>,
+    },
+
+    /// Print the absolute paths of installed repositories, for xargs and other tools
+    #[clap(after_help = PATHS_EXAMPLES)]
+    Paths {
+        /// Codebase name (if not specified, repositories of all codebases are listed)
+        codebase: Option<String>,
+
+        /// End each path with a NUL character instead of a newline (for xargs -0)
+        #[clap(short = '0', long)]
+        null: bool,
+
+        /// Only list repositories that are dirty, clean, ahead, behind, on branch=NAME or at tag[=NAME] (repeatable, all must match)
+        #[clap(long = "filter", value_name = "PREDICATE")]
+        filters: Vec<RepoFilter>,
+
+        /// List each repository after those it depends on
+        #[clap(long)]
+        deps: bool,
+    },
+
+    /// Print shell integration defining `bcd <repo>` to cd into repositories
+    #[clap(after_help = SHELL_INIT_EXAMPLES)]
+    ShellInit {
+        /// Target shell
+        #[clap(value_enum)]
+        shell: Shell,
+    },
+
+    /// Print a completion script that asks basecamp for codebase, repository and other names
+    #[clap(after_help = COMPLETIONS_EXAMPLES)]
+    Completions {
+        /// Target shell
+        #[clap(value_enum)]
+        shell: Shell,
+    },
+
+    /// Print completion candidates for the words after `basecamp` (used by completion scripts)
+    #[clap(name = "__complete", hide = true)]
+    Complete {
+        /// Words typed so far, ending with the word being completed
+        #[clap(allow_hyphen_values = true, trailing_var_arg = true)]
+        words: Vec<String>,
+    },
+
+    /// Show branch, ahead/behind and dirty state of repositories (from the state cache)
+    #[clap(after_help = STATUS_EXAMPLES)]
+    Status {
+        /// Codebase name (if not specified, all codebases are shown)
+        codebase: Option<String>,
+
+        /// Ignore the state cache and read every repository
+        #[clap(long)]
+        no_cache: bool,
+
+        /// Fetch remotes first (waiting at most 10 seconds) so ahead/behind counts are current
+        #[clap(long)]
+        fetch: bool,
+
+        /// Number of parallel fetch operations [default: `parallel` in config.yaml, or 4]
+        #[clap(short, long, requires = "fetch")]
+        parallel: Option<usize>,
+
+        /// Add the default branch's CI state and the open pull requests, asked from the host (needs a token)
+        #[clap(long)]
+        remote: bool,
+    },
+
+    /// Fetch all repositories and update the state cache
+    #[clap(after_help = FETCH_EXAMPLES)]
+    Fetch {
+        /// Codebase name (if not specified, all codebases are fetched)
+        codebase: Option<String>,
+
+        /// Number of parallel fetch operations [default: `parallel` in config.yaml, or 4]
+        #[clap(short, long)]
+        parallel: Option<usize>,
+    },
+
+    /// List installed repositories whose branch is behind its remote (fails if any)
+    #[clap(after_help = OUTDATED_EXAMPLES)]
+    Outdated {
+        /// Codebase name (if not specified, all codebases are checked)
+        codebase: Option<String>,
+
+        /// Fetch every repository before checking, instead of using the last fetch
+        #[clap(long)]
+        fetch: bool,
+
+        /// Number of parallel fetch operations [default: `parallel` in config.yaml, or 4]
+        #[clap(short, long, requires = "fetch")]
+        parallel: Option<usize>,
+    },
+
+    /// Periodically fetch all repositories and keep the state cache fresh
+    #[clap(after_help = WATCH_EXAMPLES)]
+    Watch {
+        /// Seconds between refreshes
+        #[clap(short, long, default_value = "300", value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+
+        /// Send a desktop notification when a repository falls behind its remote
+        #[clap(long)]
+        notify: bool,
+
+        /// Refresh once and exit (e.g. from cron)
+        #[clap(long)]
+        once: bool,
+
+        /// Number of parallel fetch operations [default: `parallel` in config.yaml, or 4]
+        #[clap(short, long)]
+        parallel: Option<usize>,
+    },
+
+    /// Check that the remotes and git config of every cloned repository match the configuration
+    #[clap(after_help = VERIFY_EXAMPLES)]
+    Verify {
+        /// Codebase name (if not specified, all codebases are verified)
+        codebase: Option<String>,
+
+        /// Rewrite mismatching remotes and git config values to the configured ones
+        #[clap(long)]
+        fix: bool,
+    },
+
+    /// Find repositories listed in several codebases and keep each in one place
+    #[clap(after_help = DEDUPE_EXAMPLES)]
+    Dedupe {
+        /// Only resolve these repositories (all duplicates if none are given)
+        repositories: Vec<String>,
+
+        /// Keep the repositories only in this codebase
+        #[clap(long, value_name = "CODEBASE", conflicts_with_all = ["share", "allow"])]
+        keep_in: Option<String>,
+
+        /// Clone the repositories once and link them into each codebase (`shared: true`)
+        #[clap(long, conflicts_with = "allow")]
+        share: bool,
+
+        /// Keep the duplicates on purpose, with one clone per codebase
+        #[clap(long)]
+        allow: bool,
+    },
+
+    /// Check config.yaml and codebases.yaml for mistakes
+    #[clap(after_help = VALIDATE_EXAMPLES)]
+    Validate {
+        /// Rewrite codebases.yaml with codebases and repositories sorted by name (drops comments)
+        #[clap(long)]
+        sort: bool,
+    },
+
+    /// Switch config.yaml and every cloned repository between SSH and HTTPS remotes
+    #[clap(after_help = MIGRATE_REMOTES_EXAMPLES)]
+    MigrateRemotes {
+        /// Remote URL style to switch to
+        #[clap(long, value_enum)]
+        to: RemoteProtocol,
+    },
+
+    /// Move config.yaml and every clone's origin to a renamed organization
+    #[clap(after_help = MIGRATE_ORG_EXAMPLES)]
+    MigrateOrg {
+        /// Current organization (or host/organization)
+        old: String,
+
+        /// New organization (or host/organization)
+        new: String,
+
+        /// Show the planned changes without applying them
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Add the repositories of a meta, gita, repo or vcstool file to codebases.yaml
+    #[clap(after_help = IMPORT_EXAMPLES)]
+    Import {
+        /// File to import (`.meta`, gita's `repos.csv`, a repo manifest or a vcstool `.repos` file)
+        file: PathBuf,
+
+        /// Format of the file [default: guessed from its name]
+        #[clap(long, value_enum)]
+        format: Option<ImportFormat>,
+
+        /// Put every repository in this codebase instead of one per parent directory
+        #[clap(long)]
+        codebase: Option<String>,
+
+        /// Show what would be imported without changing the configuration
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Write the repositories as a meta or vcstool file, a URL list or a clone script
+    #[clap(after_help = EXPORT_EXAMPLES)]
+    Export {
+        /// Codebase name (if not specified, every codebase is exported)
+        codebase: Option<String>,
+
+        /// Output format
+        #[clap(long, value_enum)]
+        format: ExportFormat,
+
+        /// Write to a file instead of stdout
+        #[clap(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Render codebases, repositories, branches, last commits and status as Markdown or HTML
+    #[clap(after_help = REPORT_EXAMPLES)]
+    Report {
+        /// Codebase name (if not specified, every codebase is reported)
+        codebase: Option<String>,
+
+        /// Document format [default: from the extension of --output, or markdown]
+        #[clap(long, value_enum)]
+        format: Option<DocumentFormat>,
+
+        /// Write to a file instead of stdout
+        #[clap(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Count commits, authors and changed lines per repository and codebase
+    #[clap(after_help = STATS_EXAMPLES)]
+    Stats {
+        /// Codebase name (if not specified, every codebase is counted)
+        codebase: Option<String>,
+
+        /// Only count commits since this duration ago (12h, 30d, 2w) or date (2024-03-01)
+        #[clap(long, default_value = "30d")]
+        since: String,
+
+        /// Number of repositories read in parallel [default: `parallel` in config.yaml, or 4]
+        #[clap(short, long)]
+        parallel: Option<usize>,
+
+        /// Print the statistics in a machine-readable format on stdout
+        #[clap(long, value_enum)]
+        report: Option<ReportFormat>,
+    },
+
+    /// Push the repositories of a codebase to a mirror host from config.yaml
+    #[clap(after_help = MIRROR_EXAMPLES)]
+    Mirror {
+        /// Codebase name (if not specified, every codebase is mirrored)
+        codebase: Option<String>,
+
+        /// Name of the mirror under 'mirrors' in config.yaml (needed when several are configured)
+        #[clap(long)]
+        to: Option<String>,
+
+        /// Don't create repositories missing on the mirror
+        #[clap(long)]
+        no_create: bool,
+
+        /// Number of parallel push operations [default: `parallel` in config.yaml, or 4]
+        #[clap(short, long)]
+        parallel: Option<usize>,
+    },
+
+    /// Fetch the upstream of forked repositories and fast-forward their default branch
+    #[clap(after_help = SYNC_FORK_EXAMPLES)]
+    SyncFork {
+        /// Codebase name (if not specified, the forks of every codebase are synced)
+        codebase: Option<String>,
+
+        /// Number of parallel fetch operations [default: `parallel` in config.yaml, or 4]
+        #[clap(short, long)]
+        parallel: Option<usize>,
+    },
+
+    /// Move the `rev` pins of codebases.yaml to the latest version tag or commit
+    #[clap(after_help = UPDATE_PINS_EXAMPLES)]
+    UpdatePins {
+        /// Codebase name (if not specified, the pins of every codebase are updated)
+        codebase: Option<String>,
+
+        /// Show the new pins without writing them
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Number of parallel fetch operations [default: `parallel` in config.yaml, or 4]
+        #[clap(short, long)]
+        parallel: Option<usize>,
+    },
+
+    /// Revert the latest add, remove or rename, restoring the configuration and deleted directories
+    #[clap(after_help = UNDO_EXAMPLES)]
+    Undo {
+        /// Undo even when the configuration changed since, or a clone to delete has local work
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Manage the configuration files of the workspace
+    #[clap(after_help = CONFIG_EXAMPLES)]
+    Config {
+        #[clap(subcommand)]
+        command: ConfigCommands,
+    },
+
+    /// Generate or launch development sessions for a codebase
+    #[clap(after_help = WORKSPACE_EXAMPLES)]
+    Workspace {
+        #[clap(subcommand)]
+        command: WorkspaceCommands,
+    },
+
+    /// Manage the registry of BaseCamp workspaces on this machine
+    #[clap(after_help = WORKSPACES_EXAMPLES)]
+    Workspaces {
+        #[clap(subcommand)]
+        command: WorkspacesCommands,
+    },
+
+    /// Save and restore the branches, commits and local changes of every repository
+    #[clap(after_help = SNAPSHOT_EXAMPLES)]
+    Snapshot {
+        #[clap(subcommand)]
+        command: SnapshotCommands,
+    },
+
+    /// Deploy the shared git hooks of `hooks_dir` into the repositories
+    #[clap(after_help = HOOKS_EXAMPLES)]
+    Hooks {
+        #[clap(subcommand)]
+        command: HooksCommands,
+    },
+
+    /// Manage configuration profiles (host URL and SSH key per identity)
+    #[clap(after_help = PROFILE_EXAMPLES)]
+    Profile {
+        #[clap(subcommand)]
+        command: ProfileCommands,
+    },
+
+    /// Write man pages for basecamp and every subcommand to a directory
+    #[clap(after_help = MANPAGES_EXAMPLES)]
+    Manpages {
+        /// Output directory (created if needed)
+        #[clap(default_value = "man")]
+        dir: PathBuf,
+    },
+
+    /// Serve list, status, install, add and remove to editors and other tools
+    #[clap(after_help = SERVE_EXAMPLES)]
+    Serve {
+        /// Speak newline-delimited JSON-RPC 2.0 (the only protocol so far)
+        #[clap(long, required = true)]
+        json_rpc: bool,
+
+        /// Listen on a unix socket instead of stdin/stdout
+        #[clap(long, value_name = "PATH")]
+        socket: Option<PathBuf>,
+    },
+}
+
+/// Shells supported by `shell-init`
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+/// Remote URL styles
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemoteProtocol {
+    Ssh,
+    Https,
+}
+
+/// How git operations are carried out
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GitMode {
+    /// Use the configured backend
+    Real,
+    /// Simulate clones and fetches without network access
+    Fake,
+}
+
+/// Files of other multi-repository tools `import` reads
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ImportFormat {
+    /// meta's `.meta` JSON
+    Meta,
+    /// gita's `repos.csv`
+    Gita,
+    /// A manifest XML of Google's `repo`
+    Repo,
+    /// vcstool's `.repos` YAML
+    Vcstool,
+}
+
+/// Files `export` writes
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ExportFormat {
+    /// meta's `.meta` JSON
+    Meta,
+    /// vcstool's `.repos` YAML
+    Vcstool,
+    /// One clone URL per line
+    Urls,
+    /// A POSIX shell script of `git clone` commands
+    Script,
+}
+
+/// Formats of the documents `report` renders
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum DocumentFormat {
+    Markdown,
+    Html,
+}
+
+/// Machine-readable report formats
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ReportFormat {
+    Json,
+}
+
+/// Workspace subcommands
+#[derive(Subcommand, Debug)]
+pub enum WorkspaceCommands {
+    /// Generate a tmuxinator project (or start a tmux session) with one window per repository
+    Tmux {
+        /// Codebase name
+        codebase: String,
+
+        /// Write the tmuxinator project to this file instead of stdout
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        /// Start a detached tmux session directly instead of generating a project
+        #[clap(long, conflicts_with = "output")]
+        launch: bool,
     },
 }
 
@@ -84,3 +796,494 @@ pub enum Commands {
 pub fn parse_args() -> Cli {
     Cli::parse()
 }
+
+/// Workspace registry subcommands
+#[derive(Subcommand, Debug)]
+pub enum WorkspacesCommands {
+    /// List registered workspaces
+    List,
+
+    /// Register an existing workspace
+    Add {
+        /// Workspace root (defaults to the current workspace)
+        dir: Option<PathBuf>,
+
+        /// Name of the workspace (defaults to the directory name)
+        #[clap(long)]
+        name: Option<String>,
+    },
+
+    /// Select the workspace used by commands run outside of any workspace
+    Switch {
+        /// Workspace name
+        #[clap(required_unless_present = "clear")]
+        name: Option<String>,
+
+        /// Stop using a default workspace
+        #[clap(long, conflicts_with = "name")]
+        clear: bool,
+    },
+
+    /// Forget a workspace without touching its files
+    Remove {
+        /// Workspace name
+        name: String,
+    },
+
+    /// Forget workspaces whose directory no longer exists
+    Prune,
+}
+
+/// Profile subcommands
+#[derive(Subcommand, Debug)]
+pub enum ProfileCommands {
+    /// List the profiles defined in config.yaml
+    List,
+
+    /// Add a profile
+    Add {
+        /// Profile name (e.g. work, oss)
+        name: String,
+
+        /// Base URL for the repositories of this profile
+        #[clap(long)]
+        github_url: Option<String>,
+
+        /// SSH private key used by this profile
+        #[clap(long)]
+        ssh_key: Option<PathBuf>,
+    },
+
+    /// Select the profile used when --profile isn't given
+    Use {
+        /// Profile name
+        name: String,
+    },
+
+    /// Go back to the top-level settings of config.yaml
+    Clear,
+}
+
+/// Config subcommands
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommands {
+    /// Roll config.yaml or codebases.yaml back to a backup taken before it was rewritten
+    Restore {
+        /// Backup to restore (e.g. codebases-12) [default: the latest]
+        backup: Option<String>,
+
+        /// List the backups instead of restoring one
+        #[clap(long)]
+        list: bool,
+    },
+
+    /// Print every message ID with its English text, as a translation file to fill in
+    Messages,
+}
+
+/// Hooks subcommands
+#[derive(Subcommand, Debug)]
+pub enum HooksCommands {
+    /// Deploy the hooks into every cloned repository, refreshing earlier ones
+    Sync {
+        /// Codebase name (if not specified, every codebase is synced)
+        codebase: Option<String>,
+    },
+}
+
+/// Snapshot subcommands
+#[derive(Subcommand, Debug)]
+pub enum SnapshotCommands {
+    /// Record the state of every cloned repository under a name
+    Create {
+        /// Snapshot name
+        name: String,
+
+        /// Codebase name (if not specified, every codebase is recorded)
+        codebase: Option<String>,
+    },
+
+    /// Bring every repository of a snapshot back to its recorded state
+    Restore {
+        /// Snapshot name
+        name: String,
+
+        /// Discard local changes, and commits made since the snapshot, of repositories being restored
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// List the snapshots of the workspace
+    List,
+
+    /// Delete a snapshot
+    Delete {
+        /// Snapshot name
+        name: String,
+    },
+}
+
+// Examples shown after `--help` and in the man pages
+
+const INIT_EXAMPLES: &str = "\
+Examples:
+  # Answer the questions interactively
+  basecamp init
+
+  # Set up an organization over SSH without prompting
+  basecamp init --connection-type ssh --repo-type org --name your-org --force
+
+  # A group on a self-hosted GitLab
+  basecamp init --provider gitlab --host gitlab.example.com --connection-type https --repo-type org --name platform
+
+  # Start with the codebases of the platform team's template
+  basecamp init --template https://example.com/basecamp/codebases.yaml";
+
+const INSTALL_EXAMPLES: &str = "\
+Examples:
+  # Clone every repository of every codebase
+  basecamp install
+
+  # Clone one codebase, 8 repositories at a time
+  basecamp install frontend --parallel 8
+
+  # Also fast-forward repositories that are already cloned
+  basecamp install --update
+
+  # Print the run summary as JSON for scripts
+  basecamp install --report json
+
+  # Continue an install that was interrupted or had failures
+  basecamp install --resume";
+
+const LIST_EXAMPLES: &str = "\
+Examples:
+  # List all codebases
+  basecamp list
+
+  # List the repositories of a codebase with their branch and status
+  basecamp list frontend --status";
+
+const ADD_EXAMPLES: &str = "\
+Examples:
+  # Add and clone repositories
+  basecamp add frontend react-app dashboard
+
+  # Add the repositories listed by the GitHub CLI
+  gh repo list your-org --limit 200 | basecamp add services --from-file -
+
+  # Keep repositories that can't be cloned yet (e.g. waiting for access)
+  basecamp add services billing --keep-failed";
+
+const REMOVE_EXAMPLES: &str = "\
+Examples:
+  # Remove repositories from a codebase and delete their clones
+  basecamp remove frontend settings
+
+  # Remove a whole codebase, even with uncommitted changes
+  basecamp remove frontend --force
+
+  # See what removing a codebase would delete
+  basecamp remove frontend --dry-run";
+
+const ARCHIVE_EXAMPLES: &str = "\
+Examples:
+  # Put a repository you're done with aside, without deleting its branches or stashes
+  basecamp archive backend legacy-billing
+
+  # Archive a whole codebase, and bring it back later
+  basecamp archive frontend
+  basecamp unarchive frontend";
+
+const RUN_EXAMPLES: &str = "\
+Examples:
+  # Run the 'test' task in every repository of a codebase
+  basecamp run test frontend
+
+  # Run the 'build' task in all codebases, 8 at a time
+  basecamp run build --parallel 8
+
+  # Run the 'test' task only in repositories with uncommitted changes
+  basecamp run test --filter dirty
+
+  # Run the 'lint' task in repositories on main that are ahead of their remote
+  basecamp run lint --filter branch=main --filter ahead
+
+  # Follow the output of every repository, giving up on any after 10 minutes
+  basecamp run test --stream --timeout 600";
+
+const COMPOSE_EXAMPLES: &str = "\
+Examples:
+  # Merge every repository's compose file into frontend/compose.yaml
+  basecamp compose frontend
+
+  # Merge and start the whole stack
+  basecamp compose frontend --up";
+
+const ENV_EXAMPLES: &str = "\
+Examples:
+  # Load the environment of a codebase into the current shell
+  eval \"$(basecamp env backend)\"
+
+  # The same, with {repo} filled in for one repository
+  eval \"$(basecamp env backend api-server)\"";
+
+const OPEN_EXAMPLES: &str = "\
+Examples:
+  # Open the repository page in the default browser
+  basecamp open web-client
+
+  # Open the local checkout in $VISUAL/$EDITOR
+  basecamp open web-client --editor";
+
+const INFO_EXAMPLES: &str = "\
+Examples:
+  # Everything about a repository, from the configuration and its clone
+  basecamp info api-server
+
+  # The copy of a repository in one codebase
+  basecamp info api-server --codebase platform";
+
+const PATH_EXAMPLES: &str = "\
+Examples:
+  # Print the absolute path of a repository
+  basecamp path web-client
+
+  # cd into the first repository matching 'web'
+  cd \"$(basecamp path web)\"";
+
+const PATHS_EXAMPLES: &str = "\
+Examples:
+  # Run a script in every frontend repository, 4 at a time
+  basecamp paths -0 frontend | xargs -0 -n1 -P4 some-script
+
+  # Show the changes of the repositories with uncommitted changes
+  basecamp paths --filter dirty | xargs -I{} git -C {} diff --stat";
+
+const SHELL_INIT_EXAMPLES: &str = "\
+Examples:
+  # bash / zsh (e.g. in ~/.bashrc)
+  eval \"$(basecamp shell-init bash)\"
+
+  # fish
+  basecamp shell-init fish | source";
+
+const COMPLETIONS_EXAMPLES: &str = "\
+Examples:
+  # bash (e.g. in ~/.bashrc)
+  source <(basecamp completions bash)
+
+  # zsh (e.g. in ~/.zshrc, after compinit)
+  source <(basecamp completions zsh)
+
+  # fish
+  basecamp completions fish > ~/.config/fish/completions/basecamp.fish";
+
+const STATUS_EXAMPLES: &str = "\
+Examples:
+  # Show the state of every repository from the cache
+  basecamp status
+
+  # Fetch remotes first so ahead/behind counts are current
+  basecamp status frontend --fetch";
+
+const FETCH_EXAMPLES: &str = "\
+Examples:
+  # Fetch every repository and update the state cache
+  basecamp fetch
+
+  # Fetch one codebase, 8 repositories at a time
+  basecamp fetch frontend --parallel 8";
+
+const OUTDATED_EXAMPLES: &str = "\
+Examples:
+  # List repositories behind their remote as of the last fetch
+  basecamp outdated
+
+  # Fetch first, e.g. as a CI gate (exits 1 when anything is behind)
+  basecamp outdated frontend --fetch";
+
+const WATCH_EXAMPLES: &str = "\
+Examples:
+  # Refresh every 10 minutes with desktop notifications
+  basecamp watch --interval 600 --notify
+
+  # Refresh once (e.g. from cron)
+  basecamp watch --once";
+
+const VERIFY_EXAMPLES: &str = "\
+Examples:
+  # Report clones whose remotes or git identity don't match the configuration
+  basecamp verify
+
+  # Rewrite the mismatching remotes
+  basecamp verify frontend --fix";
+
+const DEDUPE_EXAMPLES: &str = "\
+Examples:
+  # Keep 'shared-lib' only in the backend codebase
+  basecamp dedupe shared-lib --keep-in backend
+
+  # Clone every duplicate once and link it into each codebase
+  basecamp dedupe --share";
+
+const VALIDATE_EXAMPLES: &str = "\
+Examples:
+  # Report mistakes with their file:line and a suggested fix
+  basecamp validate
+
+  # Sort codebases and repositories by name (drops comments)
+  basecamp validate --sort";
+
+const MIGRATE_REMOTES_EXAMPLES: &str = "\
+Examples:
+  # Switch every clone to SSH remotes
+  basecamp migrate-remotes --to ssh";
+
+const MIGRATE_ORG_EXAMPLES: &str = "\
+Examples:
+  # Preview moving to a renamed organization
+  basecamp migrate-org old-org new-org --dry-run
+
+  # Move to another host as well
+  basecamp migrate-org github.com/old-org git.example.com/new-org";
+
+const IMPORT_EXAMPLES: &str = "\
+Examples:
+  # Preview the codebases made from a meta repository
+  basecamp import .meta --dry-run
+
+  # Import a repo manifest into a single codebase
+  basecamp import default.xml --codebase android
+
+  # Import a vcstool file with an unusual name
+  basecamp import deps.txt --format vcstool";
+
+const EXPORT_EXAMPLES: &str = "\
+Examples:
+  # Clone the backend codebase in CI without BaseCamp
+  basecamp export backend --format script -o clone.sh
+
+  # Hand every repository to vcstool
+  basecamp export --format vcstool | vcs import
+
+  # List the clone URLs
+  basecamp export --format urls";
+
+const REPORT_EXAMPLES: &str = "\
+Examples:
+  # Onboarding document of the whole workspace
+  basecamp report --output report.md
+
+  # Weekly snapshot of a codebase as a web page
+  basecamp report backend --output backend.html";
+
+const STATS_EXAMPLES: &str = "\
+Examples:
+  # Activity of the last 30 days
+  basecamp stats
+
+  # Activity of a codebase this quarter, as JSON
+  basecamp stats backend --since 2024-04-01 --report json";
+
+const MIRROR_EXAMPLES: &str = "\
+Examples:
+  # Push every cloned repository of a codebase to the only configured mirror
+  basecamp mirror backend
+
+  # Pick a mirror, and only push to repositories that already exist there
+  basecamp mirror backend --to internal --no-create";
+
+const SYNC_FORK_EXAMPLES: &str = "\
+Examples:
+  # Bring the default branch of every fork up to date with its upstream
+  basecamp sync-fork
+
+  # Only the forks of one codebase
+  basecamp sync-fork backend";
+
+const UPDATE_PINS_EXAMPLES: &str = "\
+Examples:
+  # See which pins have a newer release or commit
+  basecamp update-pins --dry-run
+
+  # Bump them in codebases.yaml, then check the new revisions out
+  basecamp update-pins backend
+  basecamp install backend --update";
+
+const UNDO_EXAMPLES: &str = "\
+Examples:
+  # Bring back a repository removed by mistake, with its local directory
+  basecamp remove backend api-server
+  basecamp undo";
+
+const CONFIG_EXAMPLES: &str = "\
+Examples:
+  # See the backups taken before each save
+  basecamp config restore --list
+
+  # Undo the latest save, or go back to a given backup
+  basecamp config restore
+  basecamp config restore codebases-12
+
+  # Start a translation of the messages (then set ui.messages in config.yaml)
+  basecamp config messages > messages-fr.yaml";
+
+const WORKSPACE_EXAMPLES: &str = "\
+Examples:
+  # Print a tmuxinator project with one window per repository
+  basecamp workspace tmux frontend > ~/.config/tmuxinator/frontend.yml
+
+  # Or start a detached tmux session directly
+  basecamp workspace tmux frontend --launch";
+
+const WORKSPACES_EXAMPLES: &str = "\
+Examples:
+  # Register the current workspace and use it from anywhere
+  basecamp workspaces add --name work
+  basecamp workspaces switch work
+
+  # Forget workspaces that were deleted
+  basecamp workspaces prune";
+
+const PROFILE_EXAMPLES: &str = "\
+Examples:
+  # Add a profile with its own host and SSH key, then select it
+  basecamp profile add oss --github-url git@github.com:your-user --ssh-key ~/.ssh/id_oss
+  basecamp profile use oss
+
+  # Use another profile for a single command
+  basecamp --profile work list";
+
+const SNAPSHOT_EXAMPLES: &str = "\
+Examples:
+  # Record every repository before a cross-repository refactoring
+  basecamp snapshot create before-refactor
+
+  # Go back to it, discarding the changes made since
+  basecamp snapshot restore before-refactor --force";
+
+const HOOKS_EXAMPLES: &str = "\
+Examples:
+  # Deploy the hooks after changing hooks_dir
+  basecamp hooks sync
+
+  # Only in the repositories of one codebase
+  basecamp hooks sync backend";
+
+const SERVE_EXAMPLES: &str = "\
+Examples:
+  # Answer requests on stdin/stdout (e.g. spawned by an editor extension)
+  basecamp serve --json-rpc
+
+  # Listen on a unix socket
+  basecamp serve --json-rpc --socket /tmp/basecamp.sock";
+
+const MANPAGES_EXAMPLES: &str = "\
+Examples:
+  # Generate the pages and read one without installing it
+  basecamp manpages target/man
+  man target/man/basecamp-install.1
+
+  # Install them for the current user
+  basecamp manpages ~/.local/share/man/man1";
@@ -0,0 +1,208 @@
+use std::fs;
+use std::path::PathBuf;
+
+use log::{debug, info};
+
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::journal::Operation;
+use crate::ui::UI;
+
+/// Execute the archive command
+///
+/// Moves the clones of repositories, or of the whole codebase when none are
+/// given, into `.basecamp/archive/<codebase>/` and removes them from
+/// codebases.yaml. Nothing is deleted, so local work is kept; `unarchive`
+/// brings them back.
+pub fn execute(codebase: String, repositories: Vec<String>) -> BasecampResult<()> {
+    debug!("Executing archive command for codebase '{}' with repos: {:?}", codebase, repositories);
+
+    let mut config = Config::load(&PathBuf::new())?;
+    let codebase = config.resolve_codebase(&codebase);
+    let whole_codebase = repositories.is_empty();
+    let repos = if whole_codebase {
+        config.get_repositories(&codebase)?.clone()
+    } else {
+        for repo in &repositories {
+            if !config.get_repositories(&codebase)?.contains(repo) {
+                return Err(BasecampError::RepositoryNotFound(repo.clone(), codebase));
+            }
+        }
+        repositories
+    };
+
+    // Only clones can be archived; a configuration entry alone is for `remove`
+    let missing: Vec<&String> = repos
+        .iter()
+        .filter(|repo| !config.workspace.repo_path(&codebase, repo).exists())
+        .collect();
+    if !missing.is_empty() {
+        return Err(BasecampError::Generic(format!(
+            "{} of '{}' aren't installed, so there's nothing to archive. Install or remove them first.",
+            missing.iter().map(|repo| repo.as_str()).collect::<Vec<_>>().join(", "),
+            codebase
+        )));
+    }
+    let archived: Vec<&String> = repos
+        .iter()
+        .filter(|repo| config.workspace.archive_path(&codebase, repo).exists())
+        .collect();
+    if !archived.is_empty() {
+        return Err(BasecampError::Generic(format!(
+            "{} of '{}' are archived already; unarchive or delete them in {} first",
+            archived.iter().map(|repo| repo.as_str()).collect::<Vec<_>>().join(", "),
+            codebase,
+            config.workspace.archive_dir(&codebase).display()
+        )));
+    }
+
+    let message = format!(
+        "This will move {} of codebase '{}' into {} and remove them from the configuration. Continue?",
+        repos.join(", "),
+        codebase,
+        config.workspace.archive_dir(&codebase).display()
+    );
+    if !UI::confirm(&message, true)? {
+        UI::info("Archive cancelled.");
+        return Ok(());
+    }
+
+    // Recorded for `basecamp undo`
+    let journal = Operation::begin(&config.workspace, "archive");
+    let summary = format!("archive {} from '{}'", repos.join(", "), codebase);
+    if whole_codebase {
+        config.remove_codebase(&codebase)?;
+    } else {
+        config.remove_repositories(&codebase, &repos)?;
+    }
+    if let Err(e) = config.save(&PathBuf::new()) {
+        journal.finish(&summary);
+        return Err(e);
+    }
+
+    let mut failed = Vec::new();
+    for repo in &repos {
+        let from = config.workspace.repo_path(&codebase, repo);
+        let to = config.workspace.archive_path(&codebase, repo);
+        let moved = to.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| fs::rename(&from, &to));
+        match moved {
+            Ok(()) => {
+                journal.record_move(&from, &to);
+                info!("Archived {:?} to {:?}", from, to);
+                UI::detail(&format!("Archived {}", from.display()));
+            }
+            Err(e) => {
+                UI::warning(&format!("Failed to archive {}: {}", from.display(), e));
+                failed.push(repo.clone());
+            }
+        }
+    }
+    if whole_codebase {
+        let _ = fs::remove_dir(config.workspace.codebase_path(&codebase));
+    }
+    journal.finish(&summary);
+
+    if !failed.is_empty() {
+        return Err(BasecampError::Generic(format!(
+            "{} were removed from the configuration but couldn't be archived; their clones are where they were",
+            failed.join(", ")
+        )));
+    }
+    UI::success(&format!(
+        "Archived {} of codebase '{}'. Run 'basecamp unarchive {}' to bring them back.",
+        repos.join(", "),
+        codebase,
+        codebase
+    ));
+    Ok(())
+}
+
+/// Execute the unarchive command
+///
+/// Moves archived repositories, or all archived repositories of the codebase
+/// when none are given, back into the codebase and adds them to codebases.yaml
+/// again.
+pub fn unarchive(codebase: String, repositories: Vec<String>) -> BasecampResult<()> {
+    debug!("Executing unarchive command for codebase '{}' with repos: {:?}", codebase, repositories);
+
+    let mut config = Config::load(&PathBuf::new())?;
+    let codebase = config.resolve_codebase(&codebase);
+    let archive_dir = config.workspace.archive_dir(&codebase);
+    let mut available: Vec<String> = fs::read_dir(&archive_dir)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    available.sort();
+    if available.is_empty() {
+        return Err(BasecampError::Generic(format!("Codebase '{}' has no archived repositories", codebase)));
+    }
+
+    let repos = if repositories.is_empty() {
+        available.clone()
+    } else {
+        repositories
+    };
+    let unknown: Vec<&String> = repos.iter().filter(|repo| !available.contains(repo)).collect();
+    if !unknown.is_empty() {
+        return Err(BasecampError::Generic(format!(
+            "{} aren't archived in '{}' (archived: {})",
+            unknown.iter().map(|repo| repo.as_str()).collect::<Vec<_>>().join(", "),
+            codebase,
+            available.join(", ")
+        )));
+    }
+    let occupied: Vec<&String> = repos
+        .iter()
+        .filter(|repo| config.workspace.repo_path(&codebase, repo).exists())
+        .collect();
+    if !occupied.is_empty() {
+        return Err(BasecampError::Generic(format!(
+            "{} of '{}' exist in the workspace again; move them away before unarchiving",
+            occupied.iter().map(|repo| repo.as_str()).collect::<Vec<_>>().join(", "),
+            codebase
+        )));
+    }
+
+    // Recorded for `basecamp undo`
+    let journal = Operation::begin(&config.workspace, "unarchive");
+    let summary = format!("unarchive {} into '{}'", repos.join(", "), codebase);
+    config.add_repositories(&codebase, &repos)?;
+    if let Err(e) = config.save(&PathBuf::new()) {
+        journal.finish(&summary);
+        return Err(e);
+    }
+
+    let mut failed = Vec::new();
+    for repo in &repos {
+        let from = config.workspace.archive_path(&codebase, repo);
+        let to = config.workspace.repo_path(&codebase, repo);
+        let moved = to.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| fs::rename(&from, &to));
+        match moved {
+            Ok(()) => {
+                journal.record_move(&from, &to);
+                info!("Unarchived {:?} to {:?}", from, to);
+                UI::detail(&format!("Restored {}", to.display()));
+            }
+            Err(e) => {
+                UI::warning(&format!("Failed to restore {}: {}", to.display(), e));
+                failed.push(repo.clone());
+            }
+        }
+    }
+    let _ = fs::remove_dir(&archive_dir);
+    journal.finish(&summary);
+
+    if !failed.is_empty() {
+        return Err(BasecampError::Generic(format!(
+            "{} were added to the configuration but are still in {}",
+            failed.join(", "),
+            archive_dir.display()
+        )));
+    }
+    UI::success(&format!("Unarchived {} into codebase '{}'", repos.join(", "), codebase));
+    Ok(())
+}
@@ -0,0 +1,289 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use clap::{Arg, Command, CommandFactory};
+use log::debug;
+
+use crate::cli::{Cli, Shell};
+use crate::config::Config;
+use crate::error::BasecampResult;
+use crate::registry::WorkspaceRegistry;
+use crate::ui::{Reporter, UI, Verbosity};
+
+const BASH_COMPLETION: &str = r#"# BaseCamp completion for bash
+_basecamp() {
+    local IFS=$'\n'
+    COMPREPLY=($(basecamp __complete -- "${COMP_WORDS[@]:1:COMP_CWORD}" 2>/dev/null))
+}
+complete -o default -F _basecamp basecamp
+"#;
+
+const ZSH_COMPLETION: &str = r#"#compdef basecamp
+# BaseCamp completion for zsh
+_basecamp() {
+    local -a candidates
+    candidates=("${(@f)$(basecamp __complete -- "${(@)words[2,CURRENT]}" 2>/dev/null)}")
+    if [[ -n ${candidates[1]} ]]; then
+        compadd -a candidates
+    else
+        _files
+    fi
+}
+compdef _basecamp basecamp
+"#;
+
+const FISH_COMPLETION: &str = r#"# BaseCamp completion for fish
+function __basecamp_complete
+    set -l words (commandline -opc) (commandline -ct)
+    basecamp __complete -- $words[2..-1] 2>/dev/null
+end
+complete -c basecamp -f -a '(__basecamp_complete)'
+"#;
+
+const POWERSHELL_COMPLETION: &str = r#"# BaseCamp completion for PowerShell
+Register-ArgumentCompleter -Native -CommandName basecamp -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+    $words = @($commandAst.CommandElements | Select-Object -Skip 1 | ForEach-Object { $_.ToString() })
+    if ($wordToComplete -eq '') { $words += '""' }
+    basecamp __complete -- @words 2>$null | ForEach-Object {
+        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)
+    }
+}
+"#;
+
+/// Execute the completions command
+pub fn execute(shell: Shell) -> BasecampResult<()> {
+    debug!("Executing completions command for {:?}", shell);
+
+    let script = match shell {
+        Shell::Bash => BASH_COMPLETION,
+        Shell::Zsh => ZSH_COMPLETION,
+        Shell::Fish => FISH_COMPLETION,
+        Shell::Powershell => POWERSHELL_COMPLETION,
+    };
+
+    UI::output(script);
+    Ok(())
+}
+
+/// What has been typed before the word being completed
+struct Parsed<'a> {
+    /// Deepest subcommand typed (the root command when none)
+    cmd: &'a Command,
+    /// Names of the subcommands leading to `cmd`
+    path: Vec<&'a str>,
+    /// Values typed so far, keyed by argument id
+    values: Vec<(&'a str, String)>,
+    /// Number of positional values typed for `cmd`
+    positionals: usize,
+    /// Option waiting for its value
+    pending: Option<&'a Arg>,
+}
+
+/// Execute the hidden `__complete` command used by the completion scripts
+///
+/// `words` are the arguments after `basecamp`, the last one being the
+/// (possibly empty) word under the cursor. Candidates are printed one per
+/// line; nothing is printed when the shell should complete file names.
+pub fn complete(words: Vec<String>) -> BasecampResult<()> {
+    // Completions must never print anything else, even for a broken configuration
+    Reporter::global().set_verbosity(Verbosity::Quiet);
+
+    let mut root = Cli::command();
+    root.build();
+
+    let (current, typed) = match words.split_last() {
+        Some((current, typed)) => (current.as_str(), typed),
+        None => ("", &[][..]),
+    };
+    let parsed = parse(&root, typed);
+
+    if let Some((_, dir)) = parsed.values.iter().find(|(id, _)| *id == "config_dir") {
+        Config::set_workspace_dir(Some(PathBuf::from(dir)));
+    }
+
+    let candidates: Vec<String> = if let Some(arg) = parsed.pending {
+        values_of(arg, &parsed)
+    } else if let Some((name, _)) = current.strip_prefix("--").and_then(|rest| rest.split_once('=')) {
+        find_long(parsed.cmd, name)
+            .map(|arg| values_of(arg, &parsed))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|value| format!("--{}={}", name, value))
+            .collect()
+    } else if current.starts_with('-') {
+        parsed
+            .cmd
+            .get_arguments()
+            .filter(|arg| !arg.is_hide_set())
+            .filter_map(|arg| arg.get_long())
+            .map(|long| format!("--{}", long))
+            .collect()
+    } else {
+        let mut candidates = Vec::new();
+        if parsed.positionals == 0 {
+            candidates.extend(
+                parsed
+                    .cmd
+                    .get_subcommands()
+                    .filter(|sub| !sub.is_hide_set())
+                    .map(|sub| sub.get_name().to_string()),
+            );
+        }
+        if let Some(arg) = positional(parsed.cmd, parsed.positionals) {
+            candidates.extend(values_of(arg, &parsed));
+        }
+        candidates
+    };
+
+    let mut output = String::new();
+    for candidate in candidates.iter().filter(|c| c.starts_with(current)) {
+        output.push_str(candidate);
+        output.push('\n');
+    }
+    UI::output(&output);
+
+    Ok(())
+}
+
+/// Walk the typed words through the command definitions
+fn parse<'a>(root: &'a Command, typed: &'a [String]) -> Parsed<'a> {
+    let mut parsed = Parsed {
+        cmd: root,
+        path: Vec::new(),
+        values: Vec::new(),
+        positionals: 0,
+        pending: None,
+    };
+
+    for word in typed {
+        if let Some(arg) = parsed.pending.take() {
+            parsed.values.push((arg.get_id().as_str(), word.clone()));
+            continue;
+        }
+
+        if let Some(long) = word.strip_prefix("--").filter(|long| !long.is_empty()) {
+            let (name, inline) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (long, None),
+            };
+            if let Some(arg) = find_long(parsed.cmd, name).filter(|arg| arg.get_action().takes_values()) {
+                match inline {
+                    Some(value) => parsed.values.push((arg.get_id().as_str(), value.to_string())),
+                    None => parsed.pending = Some(arg),
+                }
+            }
+            continue;
+        }
+
+        if let Some(shorts) = word.strip_prefix('-').filter(|shorts| !shorts.is_empty() && *word != "--") {
+            // Only the last flag of a cluster like `-fp` can wait for a value
+            let last = shorts.chars().last().unwrap();
+            parsed.pending = parsed
+                .cmd
+                .get_arguments()
+                .find(|arg| arg.get_short() == Some(last))
+                .filter(|arg| arg.get_action().takes_values());
+            continue;
+        }
+
+        if parsed.positionals == 0
+            && let Some(sub) = parsed.cmd.find_subcommand(word)
+        {
+            parsed.path.push(sub.get_name());
+            parsed.cmd = sub;
+            continue;
+        }
+
+        if let Some(arg) = positional(parsed.cmd, parsed.positionals) {
+            parsed.values.push((arg.get_id().as_str(), word.clone()));
+        }
+        parsed.positionals += 1;
+    }
+
+    parsed
+}
+
+/// Option of a command with this long name
+fn find_long<'a>(cmd: &'a Command, name: &str) -> Option<&'a Arg> {
+    cmd.get_arguments().find(|arg| arg.get_long() == Some(name))
+}
+
+/// Positional argument receiving the value at `index` (the last one takes every remaining value)
+fn positional(cmd: &Command, index: usize) -> Option<&Arg> {
+    let positionals: Vec<&Arg> = cmd.get_positionals().collect();
+    positionals.get(index).copied().or_else(|| {
+        positionals
+            .last()
+            .copied()
+            .filter(|arg| arg.get_num_args().is_some_and(|range| range.max_values() > 1))
+    })
+}
+
+/// Values an argument accepts, read from the command definition and the live configuration
+fn values_of(arg: &Arg, parsed: &Parsed) -> Vec<String> {
+    let possible: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_string())
+        .collect();
+    if !possible.is_empty() {
+        return possible;
+    }
+
+    match (parsed.path.as_slice(), arg.get_id().as_str()) {
+        (["workspaces", "switch" | "remove"], "name") => {
+            return WorkspaceRegistry::load().workspaces.into_keys().collect();
+        }
+        (_, "codebase" | "keep_in" | "repositories" | "repo" | "force_reclone" | "task" | "profile")
+        | (["profile", "use"], "name") => {}
+        _ => return Vec::new(),
+    }
+
+    // Anything else comes from the workspace configuration
+    let Ok(config) = Config::load(&PathBuf::new()) else {
+        return Vec::new();
+    };
+
+    match arg.get_id().as_str() {
+        "codebase" | "keep_in" => codebase_names(&config),
+        "repositories" | "repo" | "force_reclone" => {
+            let codebase = parsed
+                .values
+                .iter()
+                .find(|(id, _)| *id == "codebase")
+                .map(|(_, name)| config.resolve_codebase(name));
+            let codebases = config.select_codebases(codebase.as_deref()).unwrap_or_default();
+
+            let typed: Vec<&String> = parsed.values.iter().map(|(_, value)| value).collect();
+            let repos: BTreeSet<String> = codebases
+                .iter()
+                .filter_map(|codebase| config.get_repositories(codebase).ok())
+                .flatten()
+                .filter(|repo| !typed.contains(repo))
+                .cloned()
+                .collect();
+            repos.into_iter().collect()
+        }
+        "task" => config.codebases_config.tasks.into_keys().collect(),
+        _ => config.git_config.profiles.into_keys().collect(),
+    }
+}
+
+/// Codebases, the groups they are nested in and aliases
+fn codebase_names(config: &Config) -> Vec<String> {
+    let mut names = BTreeSet::new();
+    for codebase in config.list_codebases() {
+        let mut group = String::new();
+        for part in codebase.split('/') {
+            if !group.is_empty() {
+                group.push('/');
+            }
+            group.push_str(part);
+            names.insert(group.clone());
+        }
+    }
+    names.extend(config.git_config.aliases.keys().cloned());
+    names.into_iter().collect()
+}
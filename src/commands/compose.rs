@@ -0,0 +1,333 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use log::{debug, info};
+use serde_yaml::{Mapping, Value};
+
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::ui::UI;
+
+/// Compose file names looked up in every repository, in order of preference
+const COMPOSE_FILES: &[&str] = &[
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yaml",
+    "docker-compose.yml",
+];
+
+/// Top-level sections whose entries are namespaced per repository
+const NAMED_SECTIONS: &[&str] = &["services", "volumes", "networks", "configs", "secrets"];
+
+/// Execute the compose command
+pub fn execute(
+    codebase: String,
+    output: Option<PathBuf>,
+    no_prefix: bool,
+    up: bool,
+) -> BasecampResult<()> {
+    debug!("Executing compose command for codebase '{}'", codebase);
+
+    // Load configuration
+    let config = Config::load(&PathBuf::new())?;
+    let codebase = config.resolve_codebase(&codebase);
+    let repos = config.get_repositories(&codebase)?;
+
+    // The stack file lives in the codebase directory so relative paths stay short
+    let output = output.unwrap_or_else(|| config.workspace.codebase_path(&codebase).join("compose.yaml"));
+    let output_dir = output
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+
+    let mut stack = Mapping::new();
+    let mut merged_repos = Vec::new();
+
+    for repo in repos {
+        let repo_path = config.workspace.repo_path(&codebase, repo);
+        let compose_path = match COMPOSE_FILES
+            .iter()
+            .map(|name| repo_path.join(name))
+            .find(|path| path.exists())
+        {
+            Some(path) => path,
+            None => {
+                debug!("No compose file found in {:?}", repo_path);
+                continue;
+            }
+        };
+
+        debug!("Merging compose file {:?}", compose_path);
+        let content = fs::read_to_string(&compose_path)?;
+        let fragment: Value = serde_yaml::from_str(&content)?;
+
+        let prefix = if no_prefix { None } else { Some(repo.as_str()) };
+        let relative_dir = relative_to(&repo_path, &output_dir);
+        let fragment = namespace_fragment(fragment, prefix, &relative_dir);
+
+        merge_fragment(&mut stack, fragment, repo)?;
+        merged_repos.push(repo.clone());
+    }
+
+    if merged_repos.is_empty() {
+        UI::warning(&format!(
+            "No compose files found in the installed repositories of codebase '{}'",
+            codebase
+        ));
+        return Ok(());
+    }
+
+    if let Some(parent) = output.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&output, serde_yaml::to_string(&Value::Mapping(stack))?)?;
+
+    UI::success(&format!(
+        "Merged compose files from [{}] into {}",
+        merged_repos.join(", "),
+        output.display()
+    ));
+    info!("Wrote compose stack for '{}' to {:?}", codebase, output);
+
+    if up {
+        UI::info("Starting the stack with 'docker compose up -d'...");
+        let status = Command::new("docker")
+            .arg("compose")
+            .arg("-f")
+            .arg(&output)
+            .args(["-p", &codebase, "up", "-d"])
+            .status()
+            .map_err(|e| {
+                BasecampError::CommandFailed(format!("Failed to run docker (is it installed?): {}", e))
+            })?;
+
+        if !status.success() {
+            return Err(BasecampError::CommandFailed(
+                "'docker compose up' failed".to_string(),
+            ));
+        }
+
+        UI::success(&format!("Stack for codebase '{}' is up", codebase));
+    }
+
+    Ok(())
+}
+
+/// Merge a namespaced fragment into the stack, rejecting name collisions
+fn merge_fragment(stack: &mut Mapping, fragment: Value, repo: &str) -> BasecampResult<()> {
+    let fragment = match fragment {
+        Value::Mapping(mapping) => mapping,
+        _ => return Ok(()),
+    };
+
+    for (key, value) in fragment {
+        let section = key.as_str().unwrap_or_default();
+
+        if NAMED_SECTIONS.contains(&section) {
+            let target = stack
+                .entry(key.clone())
+                .or_insert_with(|| Value::Mapping(Mapping::new()));
+
+            if let (Value::Mapping(target), Value::Mapping(entries)) = (target, value) {
+                for (name, entry) in entries {
+                    if target.contains_key(&name) {
+                        return Err(BasecampError::Generic(format!(
+                            "Duplicate {} entry '{}' from repository '{}'. Remove --no-prefix to namespace entries per repository.",
+                            section,
+                            name.as_str().unwrap_or_default(),
+                            repo
+                        )));
+                    }
+                    target.insert(name, entry);
+                }
+            }
+        } else if section != "version" && !stack.contains_key(&key) {
+            // Keep the first occurrence of other top-level keys (e.g. extensions)
+            stack.insert(key, value);
+        }
+    }
+
+    Ok(())
+}
+
+/// Prefix named entries and rewrite relative paths of a compose fragment
+fn namespace_fragment(fragment: Value, prefix: Option<&str>, repo_dir: &str) -> Value {
+    let mut fragment = match fragment {
+        Value::Mapping(mapping) => mapping,
+        other => return other,
+    };
+
+    let names = |section: &str, fragment: &Mapping| -> Vec<String> {
+        fragment
+            .get(section)
+            .and_then(Value::as_mapping)
+            .map(|m| m.keys().filter_map(|k| k.as_str().map(String::from)).collect())
+            .unwrap_or_default()
+    };
+    let services = names("services", &fragment);
+    let volumes = names("volumes", &fragment);
+    let networks = names("networks", &fragment);
+
+    let rename = |name: &str| match prefix {
+        Some(prefix) => format!("{}-{}", prefix, name),
+        None => name.to_string(),
+    };
+
+    for section in NAMED_SECTIONS {
+        if let Some(Value::Mapping(entries)) = fragment.remove(*section) {
+            let renamed: Mapping = entries
+                .into_iter()
+                .map(|(name, entry)| {
+                    let name = match name.as_str() {
+                        Some(n) => Value::String(rename(n)),
+                        None => name,
+                    };
+                    (name, entry)
+                })
+                .collect();
+            fragment.insert(Value::String(section.to_string()), Value::Mapping(renamed));
+        }
+    }
+
+    if let Some(Value::Mapping(entries)) = fragment.get_mut("services") {
+        for (_, service) in entries.iter_mut() {
+            let service = match service.as_mapping_mut() {
+                Some(s) => s,
+                None => continue,
+            };
+
+            for key in ["depends_on", "links", "volumes_from"] {
+                rename_references(service.get_mut(key), &services, &rename);
+            }
+            rename_references(service.get_mut("networks"), &networks, &rename);
+
+            // Sharing another service's network stack: "service:<name>"
+            if let Some(Value::String(mode)) = service.get_mut("network_mode")
+                && let Some(target) = mode.strip_prefix("service:")
+                && services.iter().any(|s| s == target)
+            {
+                *mode = format!("service:{}", rename(target));
+            }
+
+            // Build contexts and env files are relative to the repository
+            match service.get_mut("build") {
+                Some(Value::String(context)) => *context = rebase_path(context, repo_dir),
+                Some(Value::Mapping(build)) => {
+                    if let Some(Value::String(context)) = build.get_mut("context") {
+                        *context = rebase_path(context, repo_dir);
+                    }
+                }
+                _ => {}
+            }
+            match service.get_mut("env_file") {
+                Some(Value::String(file)) => *file = rebase_path(file, repo_dir),
+                Some(Value::Sequence(files)) => {
+                    for file in files.iter_mut() {
+                        if let Value::String(file) = file {
+                            *file = rebase_path(file, repo_dir);
+                        }
+                    }
+                }
+                _ => {}
+            }
+
+            // Short volume syntax: "source:target[:mode]"
+            if let Some(Value::Sequence(mounts)) = service.get_mut("volumes") {
+                for mount in mounts.iter_mut() {
+                    if let Value::String(spec) = mount {
+                        *spec = rewrite_volume(spec, &volumes, &rename, repo_dir);
+                    }
+                }
+            }
+        }
+    }
+
+    Value::Mapping(fragment)
+}
+
+/// Rename references to entries declared in the same fragment (list or map form)
+fn rename_references(value: Option<&mut Value>, known: &[String], rename: &dyn Fn(&str) -> String) {
+    match value {
+        Some(Value::Sequence(items)) => {
+            for item in items.iter_mut() {
+                if let Value::String(name) = item {
+                    // links may use the "service:alias" form, volumes_from "service:ro"
+                    let (target, alias) = match name.split_once(':') {
+                        Some((target, alias)) => (target.to_string(), Some(alias.to_string())),
+                        None => (name.clone(), None),
+                    };
+                    if known.contains(&target) {
+                        *name = match alias {
+                            Some(alias) => format!("{}:{}", rename(&target), alias),
+                            None => rename(&target),
+                        };
+                    }
+                }
+            }
+        }
+        Some(Value::Mapping(entries)) => {
+            let renamed: Mapping = std::mem::take(entries)
+                .into_iter()
+                .map(|(name, entry)| match name.as_str() {
+                    Some(n) if known.iter().any(|k| k == n) => (Value::String(rename(n)), entry),
+                    _ => (name, entry),
+                })
+                .collect();
+            *entries = renamed;
+        }
+        _ => {}
+    }
+}
+
+/// Rewrite a short-syntax volume spec for the merged stack
+///
+/// Compose reads a source as a host path only when it starts with `.`, `/` or
+/// `~`; anything else names a volume.
+fn rewrite_volume(
+    spec: &str,
+    volumes: &[String],
+    rename: &dyn Fn(&str) -> String,
+    repo_dir: &str,
+) -> String {
+    match spec.split_once(':') {
+        Some((source, rest)) if volumes.iter().any(|v| v == source) => {
+            format!("{}:{}", rename(source), rest)
+        }
+        Some((source, rest)) if source.starts_with('.') => {
+            format!("{}:{}", rebase_path(source, repo_dir), rest)
+        }
+        _ => spec.to_string(),
+    }
+}
+
+/// Rebase a relative path onto the repository directory
+///
+/// Absolute and home paths, and remote build contexts (URLs), are kept as they are.
+fn rebase_path(path: &str, repo_dir: &str) -> String {
+    if Path::new(path).is_absolute() || path.starts_with(['/', '~']) || path.contains("://") || path.starts_with("git@") {
+        return path.to_string();
+    }
+
+    let base = if Path::new(repo_dir).is_absolute() {
+        repo_dir.to_string()
+    } else {
+        format!("./{}", repo_dir)
+    };
+
+    match path.trim_start_matches("./") {
+        "." | "" => base,
+        rest => format!("{}/{}", base, rest),
+    }
+}
+
+/// Express `path` relative to `base`, or absolute when it does not live below it
+fn relative_to(path: &Path, base: &Path) -> String {
+    let relative = match path.strip_prefix(base) {
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => std::env::current_dir().unwrap_or_default().join(path),
+    };
+    relative.to_string_lossy().replace('\\', "/")
+}
@@ -0,0 +1,70 @@
+use log::debug;
+
+use crate::backup;
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::messages;
+use crate::ui::UI;
+
+/// Execute the config restore command
+///
+/// Writes a backup of config.yaml or codebases.yaml back over the file: the
+/// one named `backup`, or the latest one. The configuration isn't loaded, so
+/// this works on a file that no longer parses. The version being replaced
+/// becomes a backup itself.
+pub fn restore(backup: Option<String>, list: bool) -> BasecampResult<()> {
+    debug!("Executing config restore command");
+
+    let basecamp_dir = Config::get_basecamp_dir();
+    let backups = backup::list(&basecamp_dir);
+    if backups.is_empty() {
+        UI::info("No backups yet; they're taken whenever config.yaml or codebases.yaml is rewritten.");
+        return Ok(());
+    }
+
+    if list {
+        let mut table = UI::create_table(vec!["Backup", "File", "Saved"]);
+        for backup in &backups {
+            UI::add_table_row(
+                &mut table,
+                vec![backup.id.clone(), backup.file.clone(), UI::format_age(Some(backup.saved_at))],
+            );
+        }
+        UI::print_table(&table);
+        return Ok(());
+    }
+
+    let selected = match &backup {
+        Some(id) => backups.iter().find(|backup| backup.id == *id).ok_or_else(|| {
+            BasecampError::Generic(format!("No backup named '{}'; see 'basecamp config restore --list'", id))
+        })?,
+        None => &backups[0],
+    };
+
+    let message = format!(
+        "Replace {} with backup {} from {}?",
+        selected.file,
+        selected.id,
+        UI::format_age(Some(selected.saved_at))
+    );
+    if !UI::confirm(&message, false)? {
+        UI::info("Restore cancelled.");
+        return Ok(());
+    }
+
+    backup::restore(&basecamp_dir, selected)?;
+    UI::success(&format!("Restored {} from backup {}", selected.file, selected.id));
+    UI::detail("The replaced version was kept as a backup too");
+    Ok(())
+}
+
+/// Execute the config messages command
+///
+/// Prints the message catalog as YAML, the starting point of a translation
+/// file for `ui.messages`.
+pub fn messages() -> BasecampResult<()> {
+    debug!("Executing config messages command");
+
+    UI::output(&messages::template()?);
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use std::path::PathBuf;
+
+use log::debug;
+
+use crate::config::Config;
+use crate::error::BasecampResult;
+use crate::ui::UI;
+
+/// What to do with a repository listed in several codebases
+enum Resolution {
+    /// Remove it from every other codebase
+    KeepIn(String),
+    /// Keep every entry, each with its own clone
+    Allow,
+    /// Keep every entry, linked to a single clone
+    Share,
+    Skip,
+}
+
+/// Execute the dedupe command
+///
+/// Lists the repositories that appear in several codebases (and would be
+/// cloned once per codebase), then keeps each in a single codebase, shares a
+/// single clone between the codebases or marks the duplicates as intentional,
+/// as chosen with `keep_in`/`share`/`allow` or a prompt.
+pub fn execute(
+    repositories: Vec<String>,
+    keep_in: Option<String>,
+    share: bool,
+    allow: bool,
+) -> BasecampResult<()> {
+    debug!("Executing dedupe command");
+
+    // Load configuration
+    let mut config = Config::load(&PathBuf::new())?;
+    let keep_in = keep_in.map(|codebase| config.resolve_codebase(&codebase));
+
+    let duplicates: Vec<(String, Vec<String>)> = config
+        .duplicate_repositories()
+        .into_iter()
+        .filter(|(repo, _)| repositories.is_empty() || repositories.contains(repo))
+        .collect();
+
+    if duplicates.is_empty() {
+        UI::success("No repository is listed in more than one codebase");
+        return Ok(());
+    }
+
+    let mut table = UI::create_table(vec!["Repository", "Codebases", "Clones"]);
+    for (repo, codebases) in &duplicates {
+        let clones = codebases
+            .iter()
+            .filter(|codebase| config.workspace.repo_path(codebase, repo).exists())
+            .count();
+        UI::add_table_row(
+            &mut table,
+            vec![repo.clone(), codebases.join(", "), clones.to_string()],
+        );
+    }
+    UI::print_table(&table);
+
+    let mut resolved = 0;
+    let mut left_on_disk = Vec::new();
+
+    for (repo, codebases) in &duplicates {
+        let resolution = if share {
+            Resolution::Share
+        } else if allow {
+            Resolution::Allow
+        } else if let Some(keep_in) = &keep_in {
+            if codebases.contains(keep_in) {
+                Resolution::KeepIn(keep_in.clone())
+            } else {
+                UI::warning(&format!("'{}' is not listed in codebase '{}', skipping it", repo, keep_in));
+                Resolution::Skip
+            }
+        } else {
+            ask(repo, codebases)?
+        };
+
+        match resolution {
+            Resolution::KeepIn(kept) => {
+                for codebase in codebases.iter().filter(|c| **c != kept) {
+                    config.remove_repositories(codebase, std::slice::from_ref(repo))?;
+                    let repo_path = config.workspace.repo_path(codebase, repo);
+                    if repo_path.exists() {
+                        left_on_disk.push(repo_path);
+                    }
+                }
+                UI::detail(&format!("{}: kept in '{}' only", repo, kept));
+            }
+            Resolution::Allow => {
+                config.update_repo_config(repo, |settings| settings.allow_duplicate = true);
+                UI::detail(&format!("{}: kept in {} (separate clones)", repo, codebases.join(", ")));
+            }
+            Resolution::Share => {
+                config.update_repo_config(repo, |settings| settings.shared = true);
+                UI::detail(&format!("{}: shared by {}", repo, codebases.join(", ")));
+                left_on_disk.extend(
+                    codebases
+                        .iter()
+                        .map(|codebase| config.workspace.repo_path(codebase, repo))
+                        .filter(|path| path.symlink_metadata().is_ok_and(|m| m.is_dir())),
+                );
+            }
+            Resolution::Skip => continue,
+        }
+        resolved += 1;
+    }
+
+    if resolved == 0 {
+        UI::info("Nothing was changed. Pass --keep-in <codebase>, --share or --allow, or run interactively to choose.");
+        return Ok(());
+    }
+
+    config.save_codebases()?;
+    UI::success(&format!("Resolved {} of {} duplicated repositories", resolved, duplicates.len()));
+
+    for path in &left_on_disk {
+        UI::info(&format!(
+            "The clone at {} is no longer used; delete it once you don't need it (then run 'basecamp install' for shared repositories)",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Ask how to resolve a duplicated repository
+fn ask(repo: &str, codebases: &[String]) -> BasecampResult<Resolution> {
+    let mut options: Vec<String> = codebases
+        .iter()
+        .map(|codebase| format!("Keep it only in '{}'", codebase))
+        .collect();
+    options.push("Share a single clone between these codebases".to_string());
+    options.push("Keep it in every codebase (separate clones)".to_string());
+    options.push("Skip".to_string());
+
+    let labels: Vec<&str> = options.iter().map(String::as_str).collect();
+    let choice = UI::select(
+        &format!("'{}' is listed in {} codebases", repo, codebases.len()),
+        &labels,
+        Some(labels.len() - 1),
+    )?;
+
+    Ok(match choice {
+        index if index < codebases.len() => Resolution::KeepIn(codebases[index].clone()),
+        index if index == codebases.len() => Resolution::Share,
+        index if index == codebases.len() + 1 => Resolution::Allow,
+        _ => Resolution::Skip,
+    })
+}
@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+use log::debug;
+
+use crate::config::Config;
+use crate::core::env;
+use crate::error::BasecampResult;
+use crate::ui::{Reporter, UI};
+
+/// Execute the env command
+///
+/// Prints the environment of a codebase, its groups' variables and secrets
+/// included, as `export` lines for `eval "$(basecamp env backend)"`. With a
+/// repository, `{repo}` is expanded for it. Messages go to stderr so only the
+/// exports reach the shell.
+pub fn execute(codebase: String, repo: Option<String>) -> BasecampResult<()> {
+    debug!("Executing env command for codebase '{}'", codebase);
+    Reporter::global().set_machine_output(true);
+
+    let config = Config::load(&PathBuf::new())?;
+    let codebase = config.resolve_codebase(&codebase);
+    config.get_repositories(&codebase)?;
+    if let Some(repo) = &repo {
+        config.resolve_repository(repo, Some(&codebase))?;
+    }
+
+    let codebase_env = env::resolve(&config, &codebase)?;
+    if codebase_env.is_empty() {
+        UI::info(&format!(
+            "Codebase '{}' has no environment variables; define them under 'env' in codebases.yaml",
+            codebase
+        ));
+        return Ok(());
+    }
+
+    let variables = match &repo {
+        Some(repo) => env::for_repo(&codebase_env, repo),
+        None => codebase_env,
+    };
+    UI::output(&env::shell_exports(&variables));
+    Ok(())
+}
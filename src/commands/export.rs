@@ -0,0 +1,90 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::debug;
+use serde::Serialize;
+
+use crate::cli::ExportFormat;
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::shell;
+use crate::ui::UI;
+
+/// `repositories` of a vcstool `.repos` file
+#[derive(Serialize)]
+struct VcsFile {
+    repositories: BTreeMap<String, VcsRepository>,
+}
+
+#[derive(Serialize)]
+struct VcsRepository {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    url: String,
+}
+
+/// Execute the export command
+///
+/// Writes the repositories of a codebase (or of every codebase) as a meta
+/// file, a vcstool file, a list of URLs or a shell script cloning them, on
+/// stdout or to `output`. Checkout paths are relative to the workspace root.
+pub fn execute(codebase: Option<String>, format: ExportFormat, output: Option<&Path>) -> BasecampResult<()> {
+    debug!("Executing export command as {:?}", format);
+
+    let config = Config::load(&PathBuf::new())?;
+    if !config.has_github_url() {
+        return Err(BasecampError::GitHubUrlNotConfigured);
+    }
+
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+    let mut checkouts: BTreeMap<String, String> = BTreeMap::new();
+    for codebase in config.select_codebases(codebase.as_deref())? {
+        for repo in config.get_repositories(&codebase)? {
+            checkouts.insert(
+                format!("{}/{}", codebase, config.workspace.repo_dir(repo)),
+                GitRepo::build_repo_url(&config.git_config.github_url, repo),
+            );
+        }
+    }
+
+    let content = match format {
+        ExportFormat::Meta => {
+            let meta = serde_json::json!({ "projects": checkouts });
+            format!("{}\n", serde_json::to_string_pretty(&meta)?)
+        }
+        ExportFormat::Vcstool => {
+            let repositories = checkouts
+                .into_iter()
+                .map(|(path, url)| (path, VcsRepository { kind: "git", url }))
+                .collect();
+            serde_yaml::to_string(&VcsFile { repositories })?
+        }
+        ExportFormat::Urls => {
+            // A repository listed in several codebases is one URL
+            let urls: BTreeSet<String> = checkouts.into_values().collect();
+            urls.into_iter().map(|url| format!("{}\n", url)).collect()
+        }
+        ExportFormat::Script => {
+            let mut script = String::from("#!/bin/sh\n# Clones the BaseCamp workspace; existing checkouts are kept\nset -e\n\n");
+            for (path, url) in checkouts {
+                script.push_str(&format!(
+                    "[ -d {path} ] || git clone {url} {path}\n",
+                    path = shell::quote(&path),
+                    url = shell::quote(&url)
+                ));
+            }
+            script
+        }
+    };
+
+    match output {
+        Some(path) => {
+            fs::write(path, content)?;
+            UI::success(&format!("Exported the configuration to {}", path.display()));
+        }
+        None => UI::output(&content),
+    }
+    Ok(())
+}
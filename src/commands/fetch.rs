@@ -0,0 +1,209 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::{debug, info, warn};
+
+use crate::config::Config;
+use crate::error::BasecampResult;
+use crate::git::{Cancel, GitRepo, RepoStatus};
+use crate::state::WorkspaceState;
+use crate::ui::{Summary, UI};
+use crate::workspace::Workspace;
+
+/// Execute the fetch command
+pub fn execute(codebase: Option<String>, parallel_count: Option<usize>) -> BasecampResult<()> {
+    debug!("Executing fetch command");
+
+    // Load configuration
+    let config = Config::load(&PathBuf::new())?;
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+    refresh(&config, codebase.as_deref(), config.parallel(parallel_count), None)?;
+
+    Ok(())
+}
+
+/// Fetch every installed repository and update the state cache
+///
+/// Returns the repositories that newly fell behind their remote. With a
+/// timeout, repositories not fetched in time keep their last known state.
+pub fn refresh(
+    config: &Config,
+    codebase: Option<&str>,
+    parallel_count: usize,
+    timeout: Option<Duration>,
+) -> BasecampResult<Vec<String>> {
+    GitRepo::ensure_online("fetch remotes")?;
+    let started = Instant::now();
+    let codebases = config.select_codebases(codebase)?;
+
+    let mut targets: Vec<(String, String)> = Vec::new();
+    for codebase in &codebases {
+        for repo in config.get_repositories(codebase)? {
+            if config.workspace.repo_path(codebase, repo).exists() {
+                targets.push((codebase.clone(), repo.clone()));
+            }
+        }
+    }
+    targets.sort();
+
+    let results = fetch_all(&config.workspace, config.remote_name(), &targets, parallel_count, timeout);
+
+    let mut state = WorkspaceState::load(&config.workspace);
+    let mut newly_behind = Vec::new();
+    let mut behind_count = 0;
+    let mut failed_count = 0;
+
+    for ((codebase, repo), (fetched, status)) in targets.iter().zip(results) {
+        if !fetched {
+            failed_count += 1;
+        }
+        let status = match status {
+            Some(status) => status,
+            None => continue,
+        };
+
+        let previously_behind = state
+            .get(codebase, repo)
+            .map(|s| s.behind > 0)
+            .unwrap_or(false);
+        if status.behind > 0 {
+            behind_count += 1;
+            if !previously_behind {
+                newly_behind.push(format!("{} ({} commits)", repo, status.behind));
+            }
+        }
+
+        UI::detail(&format!(
+            "{}/{}: {}{}",
+            codebase,
+            repo,
+            status.branch.as_deref().unwrap_or("(detached)"),
+            if fetched { "" } else { " (fetch failed)" }
+        ));
+        state.update(codebase, repo, &status, fetched);
+    }
+
+    state.save()?;
+
+    Summary::new()
+        .count(targets.len() - failed_count, "fetched")
+        .count(behind_count, "behind")
+        .failed(failed_count, "failed")
+        .print(started.elapsed());
+    info!("Workspace state refreshed");
+
+    Ok(newly_behind)
+}
+
+/// Fetch and compute status of repositories with a pool of worker threads
+///
+/// Returns, per target, whether the fetch succeeded and the computed status.
+/// With a timeout, fetches still running at the deadline are cancelled and
+/// joined, and those repositories get their local status instead.
+fn fetch_all(
+    workspace: &Workspace,
+    remote: &str,
+    targets: &[(String, String)],
+    parallel_count: usize,
+    timeout: Option<Duration>,
+) -> Vec<(bool, Option<RepoStatus>)> {
+    let next = AtomicUsize::new(0);
+    let cancel = Cancel::default();
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut results: Vec<Option<(bool, Option<RepoStatus>)>> = vec![None; targets.len()];
+    let mut timed_out = Vec::new();
+
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..parallel_count.clamp(1, targets.len().max(1)) {
+            let sender = sender.clone();
+            let (next, cancel) = (&next, &cancel);
+            scope.spawn(move || {
+                while !cancel.is_cancelled() {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let Some((codebase, repo)) = targets.get(idx) else {
+                        break;
+                    };
+                    let repo_path = workspace.repo_path(codebase, repo);
+
+                    let fetched = match GitRepo::fetch_cancellable(&repo_path, remote, Some(cancel)) {
+                        Ok(_) => true,
+                        Err(e) => {
+                            warn!("Failed to fetch '{}': {}", repo, e);
+                            false
+                        }
+                    };
+                    let interrupted = !fetched && cancel.is_cancelled();
+                    let _ = sender.send((idx, fetched, interrupted, read_status(&repo_path, remote, repo)));
+                }
+            });
+        }
+        drop(sender);
+
+        // Past the deadline, cancel the running fetches and wait for them to stop
+        loop {
+            let received = match deadline {
+                Some(deadline) if !cancel.is_cancelled() => {
+                    match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                        Ok(received) => Some(received),
+                        Err(mpsc::RecvTimeoutError::Timeout) => {
+                            cancel.cancel();
+                            continue;
+                        }
+                        Err(mpsc::RecvTimeoutError::Disconnected) => None,
+                    }
+                }
+                _ => receiver.recv().ok(),
+            };
+            let Some((idx, fetched, interrupted, status)) = received else {
+                break;
+            };
+            if interrupted {
+                timed_out.push(idx);
+            }
+            results[idx] = Some((fetched, status));
+        }
+    });
+
+    // Repositories never fetched fall back to their local status
+    let results = results
+        .into_iter()
+        .enumerate()
+        .map(|(idx, result)| {
+            result.unwrap_or_else(|| {
+                let (codebase, repo) = &targets[idx];
+                timed_out.push(idx);
+                (false, read_status(&workspace.repo_path(codebase, repo), remote, repo))
+            })
+        })
+        .collect();
+
+    if !timed_out.is_empty() {
+        timed_out.sort();
+        let names: Vec<String> = timed_out
+            .iter()
+            .map(|&idx| format!("{}/{}", targets[idx].0, targets[idx].1))
+            .collect();
+        UI::warning(&format!(
+            "Fetch timed out for {} repositories, showing their last known remote state: {}",
+            names.len(),
+            names.join(", ")
+        ));
+    }
+
+    results
+}
+
+/// Compute the local status of a repository, logging failures
+fn read_status(repo_path: &Path, remote: &str, repo: &str) -> Option<RepoStatus> {
+    match GitRepo::status(repo_path, remote) {
+        Ok(status) => Some(status),
+        Err(e) => {
+            warn!("Failed to read status of '{}': {}", repo, e);
+            None
+        }
+    }
+}
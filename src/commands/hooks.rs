@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use log::{debug, warn};
+
+use crate::config::Config;
+use crate::core::hooks;
+use crate::error::{BasecampError, BasecampResult};
+use crate::ui::UI;
+
+/// Execute the hooks sync command
+///
+/// Deploys the hooks of `hooks_dir` into every cloned repository, refreshing
+/// copies and deleting hooks that were removed from it. Hooks a repository
+/// has of its own are left in place and reported.
+pub fn sync(codebase: Option<String>) -> BasecampResult<()> {
+    debug!("Executing hooks sync command");
+
+    let config = Config::load(&PathBuf::new())?;
+    let Some(shared_dir) = config.hooks_dir()? else {
+        UI::info("No hooks to deploy; set 'hooks_dir' in config.yaml to a directory of git hooks");
+        return Ok(());
+    };
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+
+    let mut table = UI::create_table(vec!["Repository", "Deployed", "Removed", "Kept"]);
+    let mut synced = 0;
+    let mut failed = Vec::new();
+    for codebase in config.select_codebases(codebase.as_deref())? {
+        for repo in config.get_repositories(&codebase)? {
+            let repo_path = config.workspace.repo_path(&codebase, repo);
+            if !repo_path.exists() {
+                continue;
+            }
+
+            let label = format!("{}/{}", codebase, repo);
+            match hooks::sync(&repo_path, &shared_dir, config.git_config.hooks_mode) {
+                Ok(result) => {
+                    synced += 1;
+                    let list = |names: &[String]| if names.is_empty() { "-".to_string() } else { names.join(", ") };
+                    UI::add_table_row(
+                        &mut table,
+                        vec![label, list(&result.installed), list(&result.removed), list(&result.kept)],
+                    );
+                }
+                Err(e) => {
+                    warn!("Failed to deploy the hooks of {}: {}", label, e);
+                    UI::error(&format!("{}: {}", label, e));
+                    failed.push(label);
+                }
+            }
+        }
+    }
+
+    if synced == 0 && failed.is_empty() {
+        UI::info("No cloned repositories to deploy the hooks into");
+        return Ok(());
+    }
+    UI::print_table(&table);
+
+    if !failed.is_empty() {
+        return Err(BasecampError::CommandFailed(format!(
+            "The hooks of {} repositories couldn't be deployed: {}",
+            failed.len(),
+            failed.join(", ")
+        )));
+    }
+    UI::success(&format!("Synced the hooks of {} repositories from {}", synced, shared_dir.display()));
+    UI::detail("Hooks listed as kept are the repositories' own; delete them to use the shared ones");
+    Ok(())
+}
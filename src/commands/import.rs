@@ -0,0 +1,316 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::debug;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::cli::ImportFormat;
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::ui::UI;
+use crate::validate;
+
+/// Codebase of repositories checked out at the root of the imported tool's tree
+const DEFAULT_CODEBASE: &str = "imported";
+
+/// A repository read from another tool's file
+#[derive(Debug)]
+struct Entry {
+    /// Checkout path relative to that tool's root (`services/api`)
+    path: String,
+    /// Repository name on the host
+    name: String,
+    /// Remote URL, when the file gives an absolute one
+    url: Option<String>,
+}
+
+/// `repositories` of a vcstool `.repos` file
+#[derive(Deserialize)]
+struct VcsFile {
+    #[serde(default)]
+    repositories: BTreeMap<String, VcsRepository>,
+}
+
+#[derive(Deserialize)]
+struct VcsRepository {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    url: Option<String>,
+}
+
+/// Execute the import command
+///
+/// Reads the repositories of a meta, gita, repo or vcstool file and adds them
+/// to codebases.yaml. Each checkout's parent directory becomes its codebase
+/// unless `codebase` puts them all in one. Nothing is cloned.
+pub fn execute(file: &Path, format: Option<ImportFormat>, codebase: Option<String>, dry_run: bool) -> BasecampResult<()> {
+    debug!("Executing import command for {:?}", file);
+
+    if !file.exists() {
+        return Err(BasecampError::FileNotFound(file.to_path_buf()));
+    }
+    let format = match format {
+        Some(format) => format,
+        None => detect_format(file)?,
+    };
+    let content = fs::read_to_string(file)?;
+    let entries = match format {
+        ImportFormat::Meta => parse_meta(file, &content)?,
+        ImportFormat::Gita => parse_gita(&content)?,
+        ImportFormat::Repo => parse_repo_manifest(file, &content)?,
+        ImportFormat::Vcstool => parse_vcstool(file, &content)?,
+    };
+
+    let mut imported: BTreeMap<String, Vec<&Entry>> = BTreeMap::new();
+    for entry in &entries {
+        if !validate::is_valid_repo_name(&entry.name) {
+            UI::warning(&format!("Skipping '{}': not a valid repository name", entry.name));
+            continue;
+        }
+        let codebase = codebase.clone().unwrap_or_else(|| codebase_of(&entry.path));
+        imported.entry(codebase).or_default().push(entry);
+    }
+    if imported.is_empty() {
+        return Err(BasecampError::Generic(format!("No repositories found in {}", file.display())));
+    }
+
+    let mut config = match Config::load(&PathBuf::new()) {
+        Ok(config) => config,
+        Err(BasecampError::FileNotFound(_)) => {
+            UI::info("No configuration file found. Creating a new one.");
+            Config::new()
+        }
+        Err(e) => return Err(e),
+    };
+
+    // A new configuration takes the base URL most repositories share
+    if !config.has_github_url() {
+        let base = most_common_base(&entries).ok_or_else(|| {
+            BasecampError::Generic(format!(
+                "{} has no repository URL to take github_url from; run 'basecamp init' first",
+                file.display()
+            ))
+        })?;
+        UI::info(&format!("Using {} as github_url", base));
+        config.set_github_url(base)?;
+    }
+
+    let github_url = config.git_config.github_url.clone();
+    let home = GitRepo::remote_owner(&GitRepo::build_repo_url(&github_url, "repo"));
+    let elsewhere: Vec<&str> = entries
+        .iter()
+        .filter(|entry| {
+            entry
+                .url
+                .as_ref()
+                .is_some_and(|url| GitRepo::remote_owner(url) != home)
+        })
+        .map(|entry| entry.name.as_str())
+        .collect();
+    if !elsewhere.is_empty() {
+        UI::warning(&format!(
+            "{} repositories live outside {} and will be cloned from it instead: {}",
+            elsewhere.len(),
+            github_url,
+            elsewhere.join(", ")
+        ));
+    }
+
+    let mut table = UI::create_table(vec!["Codebase", "Repository", "Source"]);
+    for (codebase, entries) in &imported {
+        for entry in entries {
+            UI::add_table_row(
+                &mut table,
+                vec![
+                    codebase.clone(),
+                    entry.name.clone(),
+                    entry.url.clone().unwrap_or_else(|| entry.path.clone()),
+                ],
+            );
+        }
+    }
+    UI::print_table(&table);
+
+    if dry_run {
+        UI::info("Dry run: nothing was changed. Run again without --dry-run to apply.");
+        return Ok(());
+    }
+
+    let mut added = 0;
+    for (codebase, entries) in &imported {
+        let names: Vec<String> = entries.iter().map(|entry| entry.name.clone()).collect();
+        added += config.add_repositories(codebase, &names)?.len();
+        // Checkouts in a folder named differently keep it as their `dir`
+        for entry in entries {
+            let folder = entry.path.rsplit('/').next().unwrap_or(&entry.path);
+            if folder != entry.name && validate::is_valid_repo_name(folder) {
+                config.update_repo_config(&entry.name, |settings| settings.dir = Some(folder.to_string()));
+            }
+        }
+    }
+    config.save_config()?;
+    config.save_codebases()?;
+
+    UI::success(&format!(
+        "Imported {} repositories into {} codebases from {}",
+        added,
+        imported.len(),
+        file.display()
+    ));
+    UI::detail("Run 'basecamp install' to clone them");
+    Ok(())
+}
+
+/// Guess the format from the file name
+fn detect_format(file: &Path) -> BasecampResult<ImportFormat> {
+    let name = file.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    let extension = file.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+    match (name, extension) {
+        (".meta", _) => Ok(ImportFormat::Meta),
+        (_, "csv") => Ok(ImportFormat::Gita),
+        (_, "xml") => Ok(ImportFormat::Repo),
+        (_, "repos" | "yaml" | "yml") => Ok(ImportFormat::Vcstool),
+        _ => Err(BasecampError::Generic(format!(
+            "Can't tell the format of {}; pass --format meta, gita, repo or vcstool",
+            file.display()
+        ))),
+    }
+}
+
+/// Codebase of a checkout: its parent directory, or the default codebase at the root
+fn codebase_of(path: &str) -> String {
+    match path.trim_matches('/').rsplit_once('/') {
+        Some((parent, _)) => parent.to_string(),
+        None => DEFAULT_CODEBASE.to_string(),
+    }
+}
+
+/// Entry of a checkout, named after its URL when there is one
+fn entry(path: &str, url: Option<String>) -> Entry {
+    let path = path.trim_matches('/').trim_start_matches("./");
+    let name = url
+        .as_deref()
+        .and_then(GitRepo::split_repo_url)
+        .map(|(_, name)| name)
+        .unwrap_or_else(|| path.rsplit('/').next().unwrap_or(path).to_string());
+    Entry {
+        path: path.to_string(),
+        name,
+        url,
+    }
+}
+
+/// Base URL shared by the most repositories
+fn most_common_base(entries: &[Entry]) -> Option<String> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for (base, _) in entries.iter().filter_map(|entry| entry.url.as_deref().and_then(GitRepo::split_repo_url)) {
+        *counts.entry(base).or_default() += 1;
+    }
+    counts.into_iter().max_by_key(|(_, count)| *count).map(|(base, _)| base)
+}
+
+/// meta's `.meta` JSON: `{"projects": {"<path>": "<url>"}}`
+fn parse_meta(file: &Path, content: &str) -> BasecampResult<Vec<Entry>> {
+    let meta: Value = serde_json::from_str(content)?;
+    let projects = meta
+        .get("projects")
+        .and_then(Value::as_object)
+        .ok_or_else(|| BasecampError::InvalidConfig(format!("{}: no 'projects' object", file.display())))?;
+
+    Ok(projects
+        .iter()
+        .map(|(path, url)| entry(path, url.as_str().map(str::to_string)))
+        .collect())
+}
+
+/// gita's `repos.csv`: `<local path>,<name>,...` lines, URLs read from the clones' origin
+fn parse_gita(content: &str) -> BasecampResult<Vec<Entry>> {
+    let mut entries = Vec::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let mut columns = line.split(',');
+        let path = PathBuf::from(columns.next().unwrap_or_default().trim());
+        let name = columns
+            .next()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .or_else(|| path.file_name().map(|name| name.to_string_lossy().into_owned()))
+            .unwrap_or_default();
+
+        let url = match GitRepo::remote_url(&path, "origin") {
+            Ok(url) => url,
+            Err(e) => {
+                UI::warning(&format!("Couldn't read the origin of {}: {}", path.display(), e));
+                None
+            }
+        };
+        // gita repositories aren't nested, they all go to one codebase
+        let mut imported = entry(&name, url);
+        imported.path = imported.name.clone();
+        entries.push(imported);
+    }
+    Ok(entries)
+}
+
+/// A `repo` manifest: `<project name="..." path="..." remote="..."/>` with `<remote fetch="..."/>` bases
+fn parse_repo_manifest(file: &Path, content: &str) -> BasecampResult<Vec<Entry>> {
+    let document = roxmltree::Document::parse(content)
+        .map_err(|e| BasecampError::InvalidConfig(format!("{}: {}", file.display(), e)))?;
+    let manifest = document.root_element();
+
+    let remotes: BTreeMap<&str, &str> = manifest
+        .children()
+        .filter(|node| node.has_tag_name("remote"))
+        .filter_map(|node| Some((node.attribute("name")?, node.attribute("fetch")?)))
+        .collect();
+    let default_remote = manifest
+        .children()
+        .find(|node| node.has_tag_name("default"))
+        .and_then(|node| node.attribute("remote"));
+
+    let includes: BTreeSet<&str> = manifest
+        .children()
+        .filter(|node| node.has_tag_name("include"))
+        .filter_map(|node| node.attribute("name"))
+        .collect();
+    if !includes.is_empty() {
+        UI::warning(&format!(
+            "Included manifests aren't imported: {}",
+            includes.into_iter().collect::<Vec<_>>().join(", ")
+        ));
+    }
+
+    let mut entries = Vec::new();
+    for project in manifest.children().filter(|node| node.has_tag_name("project")) {
+        let Some(name) = project.attribute("name") else {
+            continue;
+        };
+        let path = project.attribute("path").unwrap_or(name);
+        // Relative fetch URLs depend on where the manifest was cloned from
+        let url = project
+            .attribute("remote")
+            .or(default_remote)
+            .and_then(|remote| remotes.get(remote))
+            .filter(|fetch| fetch.contains("://") || fetch.starts_with("git@"))
+            .map(|fetch| format!("{}/{}.git", fetch.trim_end_matches('/'), name.trim_end_matches(".git")));
+        entries.push(entry(path, url));
+    }
+    Ok(entries)
+}
+
+/// vcstool's `.repos` YAML: `repositories: {<path>: {type: git, url: ...}}`
+fn parse_vcstool(file: &Path, content: &str) -> BasecampResult<Vec<Entry>> {
+    let repos: VcsFile = validate::parse(file, content)?;
+
+    let mut entries = Vec::new();
+    for (path, repository) in repos.repositories {
+        match repository.kind.as_deref() {
+            None | Some("git") => entries.push(entry(&path, repository.url)),
+            Some(kind) => UI::warning(&format!("Skipping '{}': {} repositories aren't supported", path, kind)),
+        }
+    }
+    Ok(entries)
+}
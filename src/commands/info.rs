@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use log::debug;
+
+use crate::config::Config;
+use crate::core::dir_size;
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::ui::UI;
+
+/// Execute the info command
+///
+/// Shows what the configuration and the clone know about a repository: its
+/// codebases, clone URL, local paths and, once installed, branch, last
+/// commit, remotes, state and size on disk.
+pub fn execute(repo: String, codebase: Option<String>) -> BasecampResult<()> {
+    debug!("Executing info command for repository '{}'", repo);
+
+    let config = Config::load(&PathBuf::new())?;
+    if !config.has_github_url() {
+        return Err(BasecampError::GitHubUrlNotConfigured);
+    }
+
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+    let codebases: Vec<String> = match codebase {
+        Some(codebase) => vec![config.resolve_repository(&repo, Some(&codebase))?],
+        None => {
+            let found: Vec<String> = config.find_repository(&repo).into_iter().cloned().collect();
+            if found.is_empty() {
+                config.resolve_repository(&repo, None)?;
+            }
+            found
+        }
+    };
+
+    let mut fields = vec![
+        ("Codebases", codebases.join(", ")),
+        ("URL", GitRepo::build_repo_url(&config.git_config.github_url, &repo)),
+        ("Web", GitRepo::build_web_url(&config.git_config.github_url, &repo)),
+    ];
+    let paths: Vec<PathBuf> = codebases.iter().map(|codebase| config.workspace.repo_path(codebase, &repo)).collect();
+    for path in &paths {
+        let installed = if path.exists() { "" } else { " (not installed)" };
+        fields.push(("Path", format!("{}{}", path.display(), installed)));
+    }
+
+    // Git details come from the first clone
+    if let Some(path) = paths.iter().find(|path| path.exists()) {
+        let status = GitRepo::status(path, config.remote_name())?;
+        fields.push(("Branch", status.branch.clone().unwrap_or_else(|| "(detached)".to_string())));
+        if let Some(commit) = GitRepo::head_commit(path)? {
+            fields.push((
+                "Last commit",
+                format!("{} {} ({}, {})", commit.id, commit.summary, commit.author, UI::format_date(commit.time)),
+            ));
+        }
+        for (name, url) in GitRepo::remotes(path)? {
+            fields.push(("Remote", format!("{} {}", name, url)));
+        }
+
+        let mut state = vec![if status.dirty { "dirty" } else { "clean" }.to_string()];
+        if status.ahead > 0 {
+            state.push(format!("{} ahead", status.ahead));
+        }
+        if status.behind > 0 {
+            state.push(format!("{} behind", status.behind));
+        }
+        fields.push(("State", state.join(", ")));
+        fields.push(("Size", UI::format_bytes(dir_size(path))));
+    }
+
+    let width = fields.iter().map(|(name, _)| name.len()).max().unwrap_or(0) + 1;
+    let mut text = format!("{}\n", repo);
+    for (name, value) in fields {
+        text.push_str(&format!("  {:width$} {}\n", format!("{}:", name), value, width = width));
+    }
+    UI::output(&text);
+    Ok(())
+}
@@ -1,25 +1,57 @@
-use log::{debug, info};
-use std::path::PathBuf;
-use std::env;
+use log::{debug, info, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
 
-use crate::config::Config;
+use crate::commands::install::{self, InstallOptions};
+use crate::config::{CodebasesConfig, Config};
 use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::git::auth::TokenScope;
+use crate::git::provider::{self, HostProvider};
+use crate::registry::WorkspaceRegistry;
 use crate::ui::UI;
+use crate::validate::{self, Severity};
+
+/// Options of the init command
+#[derive(Debug, Clone, Default)]
+pub struct InitOptions {
+    /// Connection type: 'https' or 'ssh'
+    pub connection_type: Option<String>,
+    /// Repository type: 'org' or 'personal'
+    pub repo_type: Option<String>,
+    /// Organization name or username
+    pub name: Option<String>,
+    /// Hosting service (e.g. 'gitlab')
+    pub provider: Option<String>,
+    /// Host of self-hosted services
+    pub host: Option<String>,
+    /// Name, path or URL of a codebases.yaml to start from
+    pub template: Option<String>,
+    /// Never prompt, fail on missing values instead
+    pub non_interactive: bool,
+    /// Overwrite an existing configuration
+    pub force: bool,
+}
 
 /// Execute the init command
-pub fn execute(
-    connection_type: Option<String>, 
-    repo_type: Option<String>, 
-    name: Option<String>, 
-    non_interactive: bool, 
-    force: bool
-) -> BasecampResult<()> {
+pub fn execute(options: InitOptions) -> BasecampResult<()> {
     debug!("Executing init command");
-    
+
+    let InitOptions {
+        connection_type,
+        repo_type,
+        name,
+        provider,
+        host,
+        template,
+        non_interactive,
+        force,
+    } = options;
+
     // Get paths to the configuration files
     let config_path = Config::get_config_path();
     let codebases_path = Config::get_codebases_path();
-    
+
     // Create the .basecamp directory if it doesn't exist
     if let Err(e) = Config::ensure_basecamp_dir() {
         return Err(crate::error::BasecampError::Generic(format!(
@@ -27,115 +59,188 @@ pub fn execute(
             e
         )));
     }
-    
+
     // Check if configuration files already exist
     let config_exists = config_path.exists();
     let codebases_exists = codebases_path.exists();
-    
-    // Get current working directory for better messaging
-    let current_dir = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-    
-    if config_exists || codebases_exists {
-        if non_interactive {
-            // In non-interactive mode, we use the force flag
-            if !force {
-                UI::info("Init cancelled. Existing configuration preserved (non-interactive mode).");
-                return Ok(());
-            }
-        } else {
-            let confirm = UI::confirm(
-                &format!(
-                    "Configuration files already exist in {}/.basecamp. Overwrite?",
-                    current_dir.display()
-                ),
-                false,
-            )?;
-
-            if !confirm {
-                UI::info("Init cancelled. Existing configuration preserved.");
-                return Ok(());
-            }
+
+    // Get the workspace root for better messaging
+    let current_dir = Config::get_workspace_root().unwrap_or_else(|_| PathBuf::from("."));
+
+    if (config_exists || codebases_exists) && !force {
+        if non_interactive && !UI::assume_yes() {
+            UI::info(&format!(
+                "Init cancelled. Configuration already exists in {}/.basecamp (pass --force to overwrite).",
+                current_dir.display()
+            ));
+            return Ok(());
+        }
+
+        let confirm = UI::confirm(
+            &format!(
+                "Configuration files already exist in {}/.basecamp. Overwrite?",
+                current_dir.display()
+            ),
+            false,
+        )?;
+
+        if !confirm {
+            UI::info("Init cancelled. Existing configuration preserved.");
+            return Ok(());
         }
     }
 
-    // Create new configuration
-    let mut config = Config::new();
-    
-    // If in non-interactive mode, use command-line parameters
+    // A broken template fails before any question is asked
+    let template = template.as_deref().map(load_template).transpose()?;
+
+    // Inside or next to an existing clone, its origin is the likely answer
+    let nothing_given = provider.is_none() && host.is_none() && connection_type.is_none() && name.is_none();
+    if !non_interactive
+        && nothing_given
+        && let Some(detected) = detect_origin(&current_dir)
+    {
+        UI::info(&format!(
+            "Found a clone at {} with origin {}",
+            detected.path.display(),
+            detected.origin
+        ));
+        let confirm = UI::confirm(
+            &format!(
+                "Use the {} {} '{}' ({})?",
+                detected.provider.label(),
+                detected.provider.organization_noun(),
+                detected.owner,
+                detected.url
+            ),
+            true,
+        )?;
+        if confirm {
+            let seeded = template.is_some();
+            save(detected.url, detected.provider, template, &current_dir)?;
+            return guided_setup(detected.provider, &detected.host, &detected.owner, seeded);
+        }
+    }
+
+    if !non_interactive && (provider.is_none() || connection_type.is_none() || repo_type.is_none() || name.is_none()) {
+        UI::info("Let's set up your Git hosting connection:");
+    }
+
+    // Command-line parameters take precedence, missing ones are prompted for
+    let provider = match provider.as_deref() {
+        Some(name) => name.parse()?,
+        // Scripts written before providers existed keep getting GitHub
+        None if non_interactive => HostProvider::Github,
+        None => {
+            let labels: Vec<&str> = HostProvider::ALL.iter().map(|p| p.label()).collect();
+            HostProvider::ALL[UI::select("Where are your repositories hosted?", &labels, Some(0))?]
+        }
+    };
+
+    // Self-hosted services need their host, the public ones default to theirs
+    let host = match (host, provider.default_host()) {
+        (Some(host), _) => provider::normalize_host(&host).to_string(),
+        (None, Some(default)) => default.to_string(),
+        (None, None) if non_interactive => {
+            return Err(BasecampError::Generic(format!(
+                "In non-interactive mode, host must be provided for provider '{}'",
+                provider
+            )));
+        }
+        (None, None) => {
+            let host: String = UI::input("Enter the host name (e.g. git.example.com)", None)?;
+            provider::normalize_host(&host).to_string()
+        }
+    };
+    if host.is_empty() {
+        return Err(BasecampError::Generic("Host name cannot be empty".to_string()));
+    }
+
+    let is_https = match connection_type.as_deref() {
+        Some("https") => true,
+        Some("ssh") => false,
+        Some(t) => return Err(BasecampError::Generic(format!("Invalid connection type: {}. Use 'https' or 'ssh'", t))),
+        None if non_interactive => return Err(BasecampError::Generic("In non-interactive mode, connection-type must be provided".to_string())),
+        None => {
+            let https = format!("HTTPS (https://{}/...)", host);
+            let ssh = format!("SSH (git@{}:...)", host);
+            UI::select("What type of connection do you want to use?", &[&https, &ssh], Some(0))? == 0
+        }
+    };
+
+    // The repository type only changes the wording of the name prompt
+    let is_org = match repo_type.as_deref() {
+        Some("org") => true,
+        Some("personal") => false,
+        Some(t) => return Err(BasecampError::Generic(format!("Invalid repository type: {}. Use 'org' or 'personal'", t))),
+        None if non_interactive => return Err(BasecampError::Generic("In non-interactive mode, repo-type must be provided".to_string())),
+        None => {
+            let org_option = format!("{} repositories", capitalize(provider.organization_noun()));
+            let repo_options = &[org_option.as_str(), "Personal repositories"];
+            UI::select(
+                &format!("Are you connecting to {} or personal repositories?", provider.organization_noun()),
+                repo_options,
+                Some(0),
+            )? == 0
+        }
+    };
+
+    let username_or_org = match name {
+        Some(n) => n,
+        None if non_interactive => return Err(BasecampError::Generic("In non-interactive mode, name must be provided".to_string())),
+        None => {
+            let prompt = if is_org {
+                format!("Enter your {} name", provider.organization_noun())
+            } else {
+                "Enter your username".to_string()
+            };
+            UI::input(&prompt, None)?
+        }
+    };
+
+    // Build the base URL based on user choices
+    let url = provider.base_url(&host, &username_or_org, !is_https);
+
     if non_interactive {
-        // Build GitHub URL from the individual parameters
-        let conn_type = match connection_type.as_deref() {
-            Some("https") => true,
-            Some("ssh") => false,
-            Some(t) => return Err(BasecampError::Generic(format!("Invalid connection type: {}. Use 'https' or 'ssh'", t))),
-            None => return Err(BasecampError::Generic("In non-interactive mode, connection-type must be provided".to_string())),
-        };
-        
-        // Validate repo_type but we don't actually use it for URL construction
-        match repo_type.as_deref() {
-            Some("org") | Some("personal") => (),
-            Some(t) => return Err(BasecampError::Generic(format!("Invalid repository type: {}. Use 'org' or 'personal'", t))),
-            None => return Err(BasecampError::Generic("In non-interactive mode, repo-type must be provided".to_string())),
-        };
-        
-        let username_or_org = match name {
-            Some(n) => n,
-            None => return Err(BasecampError::Generic("In non-interactive mode, name must be provided".to_string())),
-        };
-        
-        // Build the GitHub URL based on user parameters
-        let url = if conn_type {
-            format!("https://github.com/{}", username_or_org)
-        } else {
-            format!("git@github.com:{}", username_or_org)
-        };
-        
-        config.set_github_url(url)?;
-        UI::info(&format!("Using GitHub URL built from parameters: {}", config.git_config.github_url));
+        UI::info(&format!("Using {} URL built from parameters: {}", provider.label(), url));
     } else {
-        // Interactive flow to build GitHub URL
-        UI::info("Let's set up your GitHub connection:");
-        
-        // Ask about connection type using arrow key selection
-        let connection_options = &["HTTPS (https://github.com/...)", "SSH (git@github.com:...)"];
-        let connection_type_idx = UI::select("What type of connection do you want to use?", connection_options, Some(0))?;
-        let is_https = connection_type_idx == 0;
-        
-        // Ask about repository type using arrow key selection
-        let repo_options = &["Organization repositories", "Personal repositories"];
-        let repo_type_idx = UI::select("Are you connecting to organization or personal repositories?", repo_options, Some(0))?;
-        let is_org = repo_type_idx == 0;
-        
-        // Ask for org name or username
-        let prompt = if is_org {
-            "Enter your organization name"
-        } else {
-            "Enter your GitHub username"
-        };
-        
-        let name_input: String = UI::input(prompt, None)?;
-        
-        // Build the GitHub URL based on user choices
-        let url = if is_https {
-            format!("https://github.com/{}", name_input)
-        } else {
-            format!("git@github.com:{}", name_input)
-        };
-        
-        UI::info(&format!("\nYour GitHub URL will be: {}", url));
-        
+        UI::info(&format!("\nYour {} URL will be: {}", provider.label(), url));
+
         let confirm = UI::confirm("Is this correct?", true)?;
         if !confirm {
             UI::info("Let's try again.");
-            return execute(None, None, None, false, false);
+            return execute(InitOptions {
+                template: template.map(|template| template.source),
+                force: true,
+                ..InitOptions::default()
+            });
         }
-        
-        config.set_github_url(url)?;
     }
 
+    let seeded = template.is_some();
+    save(url, provider, template, &current_dir)?;
+    guided_setup(provider, &host, &username_or_org, seeded)
+}
+
+/// Write the new configuration and register the workspace
+fn save(url: String, provider: HostProvider, template: Option<Template>, current_dir: &Path) -> BasecampResult<()> {
+    let mut config = Config::new();
+    config.set_github_url(url)?;
+    config.git_config.provider = Some(provider);
+
     // Save the configuration (this will save both config.yaml and codebases.yaml)
     config.save_config()?;
-    config.save_codebases()?;
+    match &template {
+        // Written as is, so the template's comments are kept
+        Some(template) => {
+            fs::write(Config::get_codebases_path(), &template.content)?;
+            UI::info(&format!(
+                "Seeded {} codebases from template {}",
+                template.codebases.codebases.len(),
+                template.source
+            ));
+        }
+        None => config.save_codebases()?,
+    }
 
     UI::success(&format!(
         "BaseCamp initialized with configuration in {}/.basecamp",
@@ -143,5 +248,245 @@ pub fn execute(
     ));
     info!("BaseCamp initialized successfully");
 
+    // Remember the workspace for `basecamp workspaces`
+    let mut registry = WorkspaceRegistry::load();
+    match registry
+        .register(current_dir, None)
+        .and_then(|name| registry.save().map(|_| name))
+    {
+        Ok(name) => UI::detail(&format!("Registered workspace '{}'", name)),
+        Err(e) => warn!("Failed to register workspace: {}", e),
+    }
+
     Ok(())
 }
+
+/// Optional wizard after an interactive init: group the owner's repositories into codebases, then install them
+///
+/// With a `seeded` codebases.yaml only the first install is offered.
+fn guided_setup(provider: HostProvider, host: &str, owner: &str, seeded: bool) -> BasecampResult<()> {
+    if !UI::is_interactive() {
+        return Ok(());
+    }
+
+    let mut config = Config::load(&PathBuf::new())?;
+    if !seeded {
+        if !UI::confirm("Set up your codebases now?", true)? {
+            UI::detail("Add repositories later with 'basecamp add <codebase> <repo>...'");
+            return Ok(());
+        }
+
+        let mut available = fetch_repositories(provider, host, owner);
+        loop {
+            let codebase: String = UI::input("Codebase name (e.g. backend)", None)?;
+            let codebase = codebase.trim().to_string();
+            if codebase.is_empty() {
+                UI::warning("Codebase name cannot be empty");
+                continue;
+            }
+
+            let repos = if available.is_empty() {
+                let names: String = UI::input(&format!("Repositories of '{}', separated by spaces", codebase), None)?;
+                names
+                    .split_whitespace()
+                    .filter(|name| {
+                        let valid = validate::is_valid_repo_name(name);
+                        if !valid {
+                            UI::warning(&format!("Skipping '{}': not a valid repository name", name));
+                        }
+                        valid
+                    })
+                    .map(str::to_string)
+                    .collect()
+            } else {
+                let options: Vec<&str> = available.iter().map(String::as_str).collect();
+                let checked = UI::multi_select(
+                    &format!("Repositories of '{}' (space to check, enter to confirm)", codebase),
+                    &options,
+                    false,
+                )?;
+                let repos: Vec<String> = checked.iter().map(|&i| available[i].clone()).collect();
+                available.retain(|repo| !repos.contains(repo));
+                repos
+            };
+
+            let added = config.add_repositories(&codebase, &repos)?;
+            UI::detail(&format!("Added {} repositories to '{}'", added.len(), codebase));
+
+            if !UI::confirm("Add another codebase?", false)? {
+                break;
+            }
+        }
+
+        // Codebases left without repositories aren't worth keeping
+        config.codebases_config.codebases.retain(|_, repos| !repos.is_empty());
+        config.save_codebases()?;
+    }
+
+    let total: usize = config.codebases_config.codebases.values().map(Vec::len).sum();
+    if total == 0 {
+        UI::detail("No repositories to install yet");
+        return Ok(());
+    }
+    if !UI::confirm(&format!("Install {} repositories now?", total), true)? {
+        UI::detail("Clone them later with 'basecamp install'");
+        return Ok(());
+    }
+
+    let outcome = install::execute(
+        None,
+        InstallOptions {
+            all: true,
+            ..InstallOptions::default()
+        },
+    )?;
+    if !outcome.is_success() {
+        return Err(BasecampError::CommandFailed(
+            "The first install is incomplete; run 'basecamp install' to retry".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Repositories offered by the guided setup, empty when they must be typed in
+fn fetch_repositories(provider: HostProvider, host: &str, owner: &str) -> Vec<String> {
+    if provider == HostProvider::Custom || GitRepo::is_offline() {
+        return Vec::new();
+    }
+    let scope = TokenScope {
+        host: host.to_string(),
+        provider,
+    };
+    let Some(token) = scope.env_token() else {
+        UI::info(&format!(
+            "No access token found ({}), type the repository names instead",
+            scope.variables().join(", ")
+        ));
+        return Vec::new();
+    };
+
+    let spinner = UI::spinner(&format!("Fetching the repositories of '{}' from {}", owner, host));
+    let result = provider.list_repositories(host, owner, Some(&token));
+    spinner.finish_and_clear();
+    match result {
+        Ok(repos) => {
+            debug!("Found {} repositories of '{}'", repos.len(), owner);
+            repos
+        }
+        Err(e) => {
+            UI::warning(&format!("Couldn't list the repositories of '{}': {}", owner, e));
+            Vec::new()
+        }
+    }
+}
+
+/// A codebases.yaml new workspaces start from
+struct Template {
+    /// Name, path or URL it was given as
+    source: String,
+    content: String,
+    codebases: CodebasesConfig,
+}
+
+/// Directory of the templates `init --template <name>` looks up
+///
+/// `~/.config/basecamp/templates`, where `<name>.yaml` is used.
+fn templates_dir() -> Option<PathBuf> {
+    Config::get_user_config_dir().map(|dir| dir.join("templates"))
+}
+
+/// Read and check a template given as an http(s) URL, a file path or a template name
+fn load_template(source: &str) -> BasecampResult<Template> {
+    let (file, content) = if source.starts_with("https://") || source.starts_with("http://") {
+        GitRepo::ensure_online("download the init template")?;
+        debug!("Downloading init template from {}", source);
+        let content = ureq::get(source)
+            .call()
+            .map_err(|e| BasecampError::Generic(format!("Failed to download template {}: {}", source, e)))?
+            .into_string()?;
+        (PathBuf::from(source), content)
+    } else {
+        let path = Config::expand_home(Path::new(source));
+        let file = if path.is_file() {
+            path
+        } else {
+            templates_dir()
+                .map(|dir| dir.join(format!("{}.yaml", source)))
+                .filter(|path| path.is_file())
+                .ok_or_else(|| {
+                    BasecampError::Generic(format!(
+                        "Template '{}' not found: it is neither a file, a URL nor a template in {}",
+                        source,
+                        templates_dir().unwrap_or_default().display()
+                    ))
+                })?
+        };
+        debug!("Reading init template {:?}", file);
+        let content = fs::read_to_string(&file)?;
+        (file, content)
+    };
+
+    let codebases: CodebasesConfig = validate::parse::<Option<CodebasesConfig>>(&file, &content)?.unwrap_or_default();
+    if let Some(error) = validate::validate_codebases_file(&file, &content)
+        .into_iter()
+        .find(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        return Err(BasecampError::InvalidConfig(error.to_string()));
+    }
+
+    Ok(Template {
+        source: source.to_string(),
+        content,
+        codebases,
+    })
+}
+
+/// Base URL suggested by the origin of an existing clone
+struct DetectedOrigin {
+    /// Working copy the origin was read from
+    path: PathBuf,
+    /// The origin remote URL
+    origin: String,
+    provider: HostProvider,
+    host: String,
+    owner: String,
+    /// Base URL in the same style (HTTPS or SSH) as the origin
+    url: String,
+}
+
+/// Look for a clone enclosing the workspace, or else directly inside it, and read its origin
+fn detect_origin(root: &Path) -> Option<DetectedOrigin> {
+    let enclosing = root.ancestors().find(|dir| dir.join(".git").exists()).map(Path::to_path_buf);
+    let mut children: Vec<PathBuf> = fs::read_dir(root)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join(".git").exists())
+        .collect();
+    children.sort();
+
+    enclosing.into_iter().chain(children).find_map(|path| {
+        let origin = GitRepo::remote_url(&path, "origin").ok()??;
+        let (host, owner) = GitRepo::remote_owner(&origin)?;
+        let provider = HostProvider::detect(&host);
+        let ssh = !origin.starts_with("https://") && !origin.starts_with("http://");
+        debug!("Detected {} owner '{}' from the origin of {:?}", provider, owner, path);
+        Some(DetectedOrigin {
+            url: provider.base_url(&host, &owner, ssh),
+            path,
+            origin,
+            provider,
+            host,
+            owner,
+        })
+    })
+}
+
+/// Upper-case the first letter of a word
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -1,68 +1,344 @@
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
-use std::thread;
+use std::sync::Arc;
+use std::time::Duration;
 
 use log::{debug, info};
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 
+use crate::cli::ReportFormat;
+use crate::commands::verify;
 use crate::config::Config;
+use crate::core::install::{self, ConflictPolicy, InstallOutcome, InstallSettings, RepoReport, RepoResult, SkipReason};
+use crate::core::resume::Checkpoint;
+use crate::core::{NoProgress, safety};
 use crate::error::{BasecampError, BasecampResult};
 use crate::git::GitRepo;
-use crate::ui::UI;
+use crate::journal::Operation;
+use crate::oplog::OperationLog;
+use crate::ui::{Reporter, ParallelProgress, Summary, UI};
+
+/// Number of slowest repositories shown in the summary
+const SLOWEST_SHOWN: usize = 3;
+
+/// Options of the install command
+#[derive(Debug, Clone, Default)]
+pub struct InstallOptions {
+    /// Install every codebase even when `default_codebase` is set
+    pub all: bool,
+    /// Number of parallel clone operations (`parallel` in config.yaml when unset)
+    pub parallel: Option<usize>,
+    /// Machine-readable summary printed on stdout
+    pub report: Option<ReportFormat>,
+    /// Stop starting new clones after the first failure
+    pub fail_fast: bool,
+    /// Fetch and fast-forward repositories that already exist
+    pub update: bool,
+    /// Repositories deleted and cloned again
+    pub force_reclone: Vec<String>,
+    /// Skip the uncommitted/unpushed checks before deleting repositories
+    pub force: bool,
+    /// Continue the latest interrupted or failed run instead of starting a new one
+    pub resume: bool,
+    /// What to do with directories in the way that aren't git repositories
+    pub conflicts: ConflictPolicy,
+}
 
 /// Execute the install command
-pub fn execute(
-    codebase: Option<String>,
-    parallel_count: usize,
-) -> BasecampResult<()> {
+///
+/// Repositories that fail to clone or update don't make the command fail:
+/// they are reported in the returned outcome, whose `exit_code` the caller
+/// exits with.
+pub fn execute(codebase: Option<String>, options: InstallOptions) -> BasecampResult<InstallOutcome> {
     debug!("Executing install command");
 
+    let InstallOptions {
+        all,
+        parallel: parallel_count,
+        report,
+        fail_fast,
+        update,
+        force_reclone,
+        force,
+        resume,
+        conflicts,
+    } = options;
+
+    // Keep stdout clean for the machine-readable report
+    if report.is_some() {
+        Reporter::global().set_machine_output(true);
+    }
+
     // Load configuration
-    let config = Config::load(&PathBuf::new())?;
+    let mut config = Config::load(&PathBuf::new())?;
+    let codebase = match codebase {
+        None if !all => config.git_config.default_codebase.clone(),
+        codebase => codebase,
+    };
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+    let parallel_count = config.parallel(parallel_count);
 
     // Check if GitHub URL is configured
     if !config.has_github_url() {
         return Err(BasecampError::GitHubUrlNotConfigured);
     }
 
-    // Install specific codebase or all codebases
-    match codebase {
-        Some(codebase_name) => install_codebase(&config, &codebase_name, parallel_count),
-        None => install_all_codebases(&config, parallel_count),
+    // An interrupted run goes on with the repositories it didn't finish
+    let resumed = if resume {
+        Some(resume_checkpoint(&config)?)
+    } else {
+        None
+    };
+
+    // Archived and renamed repositories are reviewed before anything is cloned
+    if report.is_none()
+        && resumed.is_none()
+        && let Ok(codebases) = config.select_codebases(codebase.as_deref())
+    {
+        verify::check_host(&mut config, &codebases)?;
+    }
+
+    // Trash corrupted working copies first, the regular install clones them again
+    if !force_reclone.is_empty() {
+        GitRepo::ensure_online("clone repositories again with --force-reclone")?;
+        remove_for_reclone(&config, codebase.as_deref(), &force_reclone, force)?;
+    }
+
+    // Every clone is recorded in .basecamp/logs/ and in the summary
+    let settings = InstallSettings {
+        parallel: parallel_count,
+        fail_fast,
+        update: update.then_some(config.git_config.update),
+        conflicts,
+    };
+    let log = OperationLog::new(&config.workspace, "install");
+
+    // Install specific codebase, a group of codebases or all codebases
+    let jobs = match (&resumed, codebase) {
+        (Some(checkpoint), _) => Ok(resume_jobs(&config, checkpoint)),
+        (None, Some(codebase_name)) if !config.is_group(&codebase_name) => codebase_jobs(&config, &codebase_name),
+        (None, codebase) => config
+            .select_codebases(codebase.as_deref())
+            .and_then(|codebases| codebases_jobs(&config, &codebases)),
+    };
+    let jobs = match jobs {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            let result = Err(e);
+            log.finish(&result);
+            return result;
+        }
+    };
+
+    // Progress is checkpointed in .basecamp/state/ for `install --resume`
+    let checkpoint = resumed.or_else(|| (!GitRepo::is_offline()).then(|| Checkpoint::begin(&config.workspace, &jobs)));
+    let tracking = checkpoint.as_ref().map(|checkpoint| checkpoint.track());
+    let outcome = install::install(&config, &jobs, settings, &log, &ParallelProgress::new());
+    drop(tracking);
+    let remaining = checkpoint.map_or(0, |checkpoint| {
+        checkpoint.record(&outcome);
+        let remaining = checkpoint.finish();
+        if remaining > 0 {
+            UI::detail(&format!("Install progress saved in {}", checkpoint.path().display()));
+        }
+        remaining
+    });
+    report_install(&jobs, &outcome, settings);
+
+    match report {
+        Some(ReportFormat::Json) => UI::output(&format!("{}\n", serde_json::to_string_pretty(&outcome)?)),
+        None if !outcome.repositories.is_empty() => print_slowest(&outcome),
+        None => {}
+    }
+
+    // Aggregate every failure across codebases in the final message
+    let failed: Vec<String> = outcome
+        .failures()
+        .map(|r| format!("{}/{}", r.codebase, r.repo))
+        .collect();
+    if !failed.is_empty() {
+        UI::error(&format!(
+            "{} repositories failed to clone: {}",
+            failed.len(),
+            failed.join(", ")
+        ));
+    }
+    if remaining > 0 {
+        UI::info(&format!(
+            "{} repositories weren't installed. Run 'basecamp install --resume' to retry them.",
+            remaining
+        ));
+    }
+    if report.is_none() && !outcome.repositories.is_empty() {
+        print_summary(&outcome);
+    }
+
+    log.finish(&outcome.result());
+    Ok(outcome)
+}
+
+/// Checkpoint of the latest unfinished run, with its partial clones moved to the trash
+fn resume_checkpoint(config: &Config) -> BasecampResult<Arc<Checkpoint>> {
+    let checkpoint = Checkpoint::latest(&config.workspace)?
+        .ok_or_else(|| BasecampError::Generic("No interrupted install to resume".to_string()))?;
+
+    // A clone that was cut off or failed halfway would be taken for an installed repository
+    let journal = Operation::begin(&config.workspace, "install");
+    for path in checkpoint.partial_clones() {
+        UI::info(&format!("Moving partial clone {} to the trash", path.display()));
+        journal.trash(&path)?;
+        info!("Trashed partial clone {:?}", path);
+    }
+    journal.finish("delete partial clones before resuming an install");
+    Ok(checkpoint)
+}
+
+/// Repositories of a resumed run that aren't done yet and are still configured
+fn resume_jobs(config: &Config, checkpoint: &Checkpoint) -> Vec<(String, String)> {
+    let configured =
+        |codebase: &str, repo: &String| config.get_repositories(codebase).is_ok_and(|repos| repos.contains(repo));
+    for entry in checkpoint.entries() {
+        if !configured(&entry.codebase, &entry.repo) {
+            UI::detail(&format!("Skipping {}/{}, it was removed from the configuration", entry.codebase, entry.repo));
+        }
+    }
+    checkpoint.retain(|entry| configured(&entry.codebase, &entry.repo));
+
+    let total = checkpoint.entries().len();
+    let jobs: Vec<(String, String)> = checkpoint
+        .remaining()
+        .into_iter()
+        .map(|entry| (entry.codebase, entry.repo))
+        .collect();
+    UI::info(&format!(
+        "Resuming install: {} of {} repositories left",
+        jobs.len(),
+        total
+    ));
+    jobs
+}
+
+/// Move the working copies of repositories selected with --force-reclone to the trash
+///
+/// Every repository is checked before anything is moved, so a dirty one aborts the whole run.
+/// A shared repository loses its shared clone along with its links, so it's cloned again too.
+fn remove_for_reclone(
+    config: &Config,
+    codebase: Option<&str>,
+    repos: &[String],
+    force: bool,
+) -> BasecampResult<()> {
+    let codebases = config.select_codebases(codebase)?;
+
+    let (mut clones, mut links) = (Vec::new(), Vec::new());
+    for repo in repos {
+        let matching: Vec<&String> = codebases
+            .iter()
+            .filter(|c| {
+                config
+                    .get_repositories(c)
+                    .map(|r| r.contains(repo))
+                    .unwrap_or(false)
+            })
+            .collect();
+
+        if matching.is_empty() {
+            return Err(match codebase {
+                Some(name) => BasecampError::RepositoryNotFound(repo.clone(), name.to_string()),
+                None => BasecampError::UnknownRepository(repo.clone()),
+            });
+        }
+
+        let paths = matching.into_iter().map(|c| config.workspace.repo_path(c, repo));
+        if config.repo_config(repo).shared {
+            let shared_path = config.workspace.shared_path(repo);
+            if shared_path.exists() && !clones.contains(&shared_path) {
+                clones.push(shared_path);
+            }
+            links.extend(paths.filter(|path| path.symlink_metadata().is_ok()));
+        } else {
+            clones.extend(paths.filter(|path| path.exists()));
+        }
+    }
+
+    if !force {
+        let clones: Vec<(String, PathBuf)> =
+            clones.iter().map(|path| (path.display().to_string(), path.clone())).collect();
+        safety::check(&clones, config.remote_name(), config.parallel(None), &NoProgress)?;
+    }
+
+    // `basecamp undo` brings them back
+    let journal = Operation::begin(&config.workspace, "install");
+    for path in clones.iter().chain(&links) {
+        UI::info(&format!("Moving {} to the trash for a fresh clone", path.display()));
+        journal.trash(path)?;
+        info!("Trashed {:?} for re-clone", path);
+    }
+    journal.finish("move repositories to the trash for a fresh clone");
+
+    Ok(())
+}
+
+/// Print the repositories that took the longest to install
+fn print_slowest(report: &InstallOutcome) {
+    let mut slowest: Vec<&RepoReport> = report
+        .repositories
+        .iter()
+        .filter(|r| r.result != RepoResult::Skipped)
+        .collect();
+    slowest.sort_by(|a, b| b.seconds.total_cmp(&a.seconds));
+
+    if slowest.len() > 1 {
+        let names: Vec<String> = slowest
+            .iter()
+            .take(SLOWEST_SHOWN)
+            .map(|r| format!("{}/{} ({:.1}s)", r.codebase, r.repo, r.seconds))
+            .collect();
+        UI::info(&format!("Slowest: {}", names.join(", ")));
     }
 }
 
-/// Install a specific codebase
-fn install_codebase(config: &Config, codebase: &str, parallel_count: usize) -> BasecampResult<()> {
+/// Print the outcome line of an install run
+fn print_summary(report: &InstallOutcome) {
+    let mut summary = Summary::new()
+        .count(report.cloned, "cloned")
+        .count(report.updated, "updated")
+        .count(report.skipped, "skipped")
+        .failed(report.failed, "failed")
+        .failed(report.conflicts, "conflicts");
+    if report.bytes > 0 {
+        summary = summary.note(&format!("{} downloaded", UI::format_bytes(report.bytes)));
+    }
+    summary.print(Duration::from_secs_f64(report.seconds));
+}
+
+/// Repositories of a specific codebase
+fn codebase_jobs(config: &Config, codebase: &str) -> BasecampResult<Vec<(String, String)>> {
     info!("Installing codebase: {}", codebase);
 
-    // Get repositories for the codebase
     let repos = config.get_repositories(codebase)?;
 
     if repos.is_empty() {
         UI::info(&format!("No repositories in codebase '{}'", codebase));
-        return Ok(());
     }
 
-    // Clone repositories
-    clone_repositories(config, codebase, repos, parallel_count)
+    Ok(repos
+        .iter()
+        .map(|repo| (codebase.to_string(), repo.clone()))
+        .collect())
 }
 
-/// Install all codebases
-fn install_all_codebases(config: &Config, parallel_count: usize) -> BasecampResult<()> {
-    info!("Installing all codebases");
-
-    let codebases = config.list_codebases();
+/// Repositories of several codebases (all of them, or the codebases of a group)
+///
+/// The repositories of every codebase share one pool of workers.
+fn codebases_jobs(config: &Config, codebases: &[String]) -> BasecampResult<Vec<(String, String)>> {
+    info!("Installing codebases: {}", codebases.join(", "));
 
     if codebases.is_empty() {
         UI::info("No codebases configured yet. Use 'basecamp add <codebase> <repo>' to add one.");
-        return Ok(());
+        return Ok(Vec::new());
     }
 
-    // Install each codebase
+    let mut jobs = Vec::new();
     for codebase in codebases {
-        UI::info(&format!("Installing codebase: {}", codebase));
-
         let repos = config.get_repositories(codebase)?;
 
         if repos.is_empty() {
@@ -70,205 +346,148 @@ fn install_all_codebases(config: &Config, parallel_count: usize) -> BasecampResu
             continue;
         }
 
-        // Clone repositories
-        clone_repositories(config, codebase, repos, parallel_count)?;
+        jobs.extend(repos.iter().map(|repo| (codebase.clone(), repo.clone())));
     }
 
-    Ok(())
+    Ok(jobs)
 }
 
-/// Clone repositories in parallel
-fn clone_repositories(
-    config: &Config,
-    codebase: &str,
-    repos: &[String],
-    parallel_count: usize,
-) -> BasecampResult<()> {
-    if repos.is_empty() {
-        return Ok(());
+/// Report the results of an install run per codebase
+fn report_install(jobs: &[(String, String)], report: &InstallOutcome, settings: InstallSettings) {
+    if jobs.is_empty() {
+        return;
     }
 
-    let total_repos = repos.len();
+    if GitRepo::is_offline() {
+        report_offline_plan(jobs, report, settings);
+        return;
+    }
 
-    // Display what will be installed
-    UI::info(&format!(
-        "Installing {} repositories in codebase '{}'",
-        total_repos, codebase
-    ));
+    let codebases = install::codebases_of(jobs);
+    let single_codebase = codebases.len() == 1;
+    let label = |entry: &RepoReport| install::job_label(&entry.codebase, &entry.repo, single_codebase);
+    let skipped_for = |entry: &RepoReport, reason: SkipReason| entry.reason == Some(reason);
+
+    let cancelled = report
+        .repositories
+        .iter()
+        .filter(|r| skipped_for(r, SkipReason::Cancelled))
+        .count();
+    if cancelled > 0 {
+        UI::warning(&format!(
+            "Skipped {} repositories after the first failure (--fail-fast)",
+            cancelled
+        ));
+    }
 
-    // Adjust parallel count based on available repositories
-    let parallel_count = std::cmp::min(parallel_count, total_repos);
-
-    // Create shared data for threads
-    let github_url = config.git_config.github_url.clone();
-    let repos = Arc::new(repos.to_vec());
-    let codebase = Arc::new(codebase.to_string());
-    let remaining_repos = Arc::new(Mutex::new((0..total_repos).collect::<Vec<_>>()));
-    let errors = Arc::new(Mutex::new(Vec::new()));
-    
-    // Track completed repositories
-    let completed_repos = Arc::new(Mutex::new(0));
-    
-    // Track repositories that were already installed
-    let already_installed_repos = Arc::new(Mutex::new(Vec::new()));
-    
-    // Setup progress bars
-    let multi_progress = MultiProgress::new();
-    let multi_progress_arc = Arc::new(multi_progress);
-    
-    // Create the main progress bar
-    let progress_bar = multi_progress_arc.add(ProgressBar::new(total_repos as u64));
-    progress_bar.set_style(
-        ProgressStyle::default_bar()
-            .template("{msg} [{bar:40.cyan/blue}] {pos}/{len} ({percent}%)")
-            .expect("Failed to create progress bar template")
-            .progress_chars("=> ")
-    );
-    progress_bar.set_message(format!("Installing repositories in '{}'", codebase));
-    
-    // Spinner style for individual repositories
-    let spinner_style = ProgressStyle::default_spinner()
-        .template("{spinner:.green} {wide_msg}")
-        .expect("Failed to create spinner style template");
-
-    // Create a clone of MultiProgress for the worker threads
-    let mp_for_threads = multi_progress_arc.clone();
-    
-    // Spawn worker threads
-    let mut handles = vec![];
-
-    for _ in 0..parallel_count {
-        let repos = Arc::clone(&repos);
-        let codebase = Arc::clone(&codebase);
-        let remaining_repos = Arc::clone(&remaining_repos);
-        let errors = Arc::clone(&errors);
-        let already_installed_repos = Arc::clone(&already_installed_repos);
-        let github_url = github_url.clone();
-        let multi_progress = Arc::clone(&mp_for_threads);
-        let spinner_style = spinner_style.clone();
-        let completed_repos = Arc::clone(&completed_repos);
-        let progress_bar = progress_bar.clone();
-
-        let handle = thread::spawn(move || {
-            loop {
-                // Get next repository to clone
-                let repo_idx = {
-                    let mut remaining = remaining_repos.lock().unwrap();
-                    if remaining.is_empty() {
-                        break;
-                    }
-                    remaining.remove(0)
-                };
-
-                let repo = &repos[repo_idx];
-                
-                // Create a new spinner for this repository
-                let spinner = multi_progress.add(ProgressBar::new_spinner());
-                spinner.set_style(spinner_style.clone());
-                spinner.set_message(format!("Cloning '{}'...", repo));
-                spinner.enable_steady_tick(std::time::Duration::from_millis(100));
-                
-                // Clone repository
-                let repo_path = GitRepo::get_repo_path(&codebase, repo);
-
-                if repo_path.exists() {
-                    // Repository already exists - show a clear already installed message
-                    spinner.finish_with_message(format!("Repository '{}' already installed ✓", repo));
-                    
-                    // Track that this repository was already installed
-                    let mut installed = already_installed_repos.lock().unwrap();
-                    installed.push(repo.clone());
-                } else {
-                    let repo_url = GitRepo::build_repo_url(&github_url, repo);
-
-                    match GitRepo::clone(&repo_url, &repo_path) {
-                        Ok(_) => {
-                            spinner.finish_with_message(format!("Cloned '{}' successfully ✓", repo));
-                        }
-                        Err(e) => {
-                            let error_msg = format!("Failed to clone repository '{}': {}", repo, e);
-                            spinner.finish_with_message(format!("Failed to clone '{}' ✗", repo));
-
-                            // Add error to the list
-                            let mut errors_list = errors.lock().unwrap();
-                            errors_list.push((repo.clone(), error_msg));
-                        }
-                    }
-                }
-                
-                // Update progress
-                {
-                    let mut completed = completed_repos.lock().unwrap();
-                    *completed += 1;
-                    progress_bar.set_position(*completed as u64);
-                }
+    // Report each codebase separately
+    for codebase in &codebases {
+        let entries: Vec<&RepoReport> = report
+            .repositories
+            .iter()
+            .filter(|r| &r.codebase == codebase)
+            .collect();
+        let count = |matches: &dyn Fn(&RepoReport) -> bool| entries.iter().filter(|r| matches(r)).count();
+
+        let total = entries.len();
+        let installed = count(&|r| skipped_for(r, SkipReason::AlreadyInstalled) || skipped_for(r, SkipReason::UpToDate));
+        let updated = count(&|r| r.result == RepoResult::Updated);
+        let failed = count(&|r| r.result == RepoResult::Failed || r.result == RepoResult::Conflict);
+        let skipped = count(&|r| skipped_for(r, SkipReason::Cancelled));
+
+        if failed > 0 || skipped > 0 {
+            UI::warning(&format!(
+                "Codebase '{}' is incomplete: {} failed, {} not attempted",
+                codebase, failed, skipped
+            ));
+        } else if installed == total {
+            UI::success(&format!("Codebase '{}' is already up to date", codebase));
+        } else {
+            if updated > 0 {
+                UI::info(&format!("Updated {} repositories in '{}'", updated, codebase));
+            }
+            if installed > 0 {
+                UI::info(&format!("{} repositories were already installed in '{}'", installed, codebase));
             }
-        });
 
-        handles.push(handle);
+            if installed + updated == total {
+                UI::success(&format!("Codebase '{}' is up to date", codebase));
+            } else {
+                UI::success(&format!("Successfully installed codebase '{}'", codebase));
+            }
+        }
     }
 
-    // Wait for all threads to complete
-    for handle in handles {
-        let _ = handle.join();
-    }
-    
-    // Get the list of repositories that were already installed
-    let already_installed = already_installed_repos.lock().unwrap();
-    let newly_installed = total_repos - already_installed.len() - errors.lock().unwrap().len();
-    
-    // Check for errors before finishing the progress bar
-    let errors_list = errors.lock().unwrap();
-    if !errors_list.is_empty() {
-        // Change progress bar to indicate errors
-        progress_bar.set_style(
-            ProgressStyle::default_bar()
-                .template("{msg} [{bar:40.red/blue}] {pos}/{len} ({percent}%)")
-                .expect("Failed to create progress bar template")
-                .progress_chars("=> ")
-        );
-        progress_bar.finish_with_message(format!("Installation of repositories in '{}' completed with errors", codebase));
-        
+    // Failures in job order: codebases by name, their repositories as listed
+    let failures: Vec<&RepoReport> = jobs
+        .iter()
+        .filter_map(|(codebase, repo)| {
+            report
+                .failures()
+                .find(|r| &r.codebase == codebase && &r.repo == repo)
+        })
+        .collect();
+
+    if !failures.is_empty() {
         UI::warning(&format!(
             "Encountered {} errors during installation:",
-            errors_list.len()
+            failures.len()
         ));
 
-        println!(); // Add padding above errors without the "i" prefix
-        for (repo, error) in errors_list.iter() {
-            UI::error(&format!("  {}: {}", repo, error));
-        }
-        println!(); // Add padding below errors without the "i" prefix
-
-        return Err(BasecampError::CommandFailed(format!(
-            "{} repositories failed to clone",
-            errors_list.len()
-        )));
-    } else if already_installed.len() == total_repos {
-        // All repositories were already installed
-        progress_bar.finish_with_message(format!("Codebase '{}' is already up to date", codebase));
-        UI::success(&format!("Codebase '{}' is already up to date", codebase));
-    } else {
-        // Some repositories were installed and some were already present
-        if newly_installed > 0 {
-            progress_bar.finish_with_message(format!("Successfully installed {} new repositories in '{}'", newly_installed, codebase));
-            
-            if !already_installed.is_empty() {
-                UI::info(&format!("{} repositories were already installed", already_installed.len()));
-            }
-            
-            UI::success(&format!("Successfully installed codebase '{}'", codebase));
-        } else {
-            // This should not happen (would be caught by the already_installed.len() == total_repos check above)
-            progress_bar.finish_with_message(format!("No new repositories were installed in '{}'", codebase));
-            UI::success(&format!("Codebase '{}' is already up to date", codebase));
+        UI::blank_line(); // Add padding above errors without the "i" prefix
+        for entry in &failures {
+            let action = if entry.url.is_some() { "clone" } else { "update" };
+            UI::error(&format!(
+                "  {}: Failed to {} repository '{}': {}",
+                label(entry),
+                action,
+                entry.repo,
+                entry.error.as_deref().unwrap_or_default()
+            ));
         }
+        UI::blank_line(); // Add padding below errors without the "i" prefix
     }
 
-    // Let Arc<MultiProgress> clean up naturally when all references are dropped
-    // The worker threads have all completed, so their references are gone
-    // This is the last reference, ensuring proper cleanup
-    drop(multi_progress_arc);
+    // Directories in the way are left alone unless asked otherwise
+    let conflicts: Vec<String> = report
+        .conflicts()
+        .map(|entry| format!("{}: {}", label(entry), entry.error.as_deref().unwrap_or_default()))
+        .collect();
+    if !conflicts.is_empty() {
+        UI::warning(&format!(
+            "{} repositories weren't cloned because a directory is in the way:",
+            conflicts.len()
+        ));
+        for conflict in &conflicts {
+            UI::error(&format!("  {}", conflict));
+        }
+        UI::info(
+            "Move them away, or run again with --backup-conflicts to keep them in .basecamp/conflicts/ \
+             or --overwrite-non-git to move them to the trash.",
+        );
+    }
+}
 
-    Ok(())
+/// Report what an offline install would clone, without touching the network
+fn report_offline_plan(jobs: &[(String, String)], report: &InstallOutcome, settings: InstallSettings) {
+    let single_codebase = install::codebases_of(jobs).len() == 1;
+    let missing: Vec<String> = report
+        .repositories
+        .iter()
+        .filter(|r| r.reason == Some(SkipReason::Offline))
+        .map(|r| install::job_label(&r.codebase, &r.repo, single_codebase))
+        .collect();
+
+    if missing.is_empty() {
+        UI::success("All repositories are already installed");
+    } else {
+        UI::info(&format!(
+            "Offline mode: would clone {} repositories: {}",
+            missing.len(),
+            missing.join(", ")
+        ));
+    }
+    if settings.update.is_some() {
+        UI::warning("Offline mode: existing repositories were not updated");
+    }
 }
@@ -1,67 +1,336 @@
-use log::{debug, info};
+use std::collections::BTreeMap;
 
+use log::{debug, info, warn};
+use regex::RegexBuilder;
+
+use crate::commands::status::{remote_cells, remote_health};
 use crate::config::Config;
 use crate::error::{BasecampError, BasecampResult};
-use crate::ui::UI;
+use crate::git::GitRepo;
+use crate::git::provider::{CiState, RemoteHealth};
+use crate::state::{RepoState, WorkspaceState};
+use crate::ui::{Glyph, UI};
+
+/// Options of the list command
+pub struct ListOptions {
+    /// Add branch and working tree status columns
+    pub status: bool,
+    pub no_cache: bool,
+    /// Ask the host for the CI state and open pull requests of each repository
+    pub remote: bool,
+    /// Only list repositories that aren't cloned
+    pub missing_only: bool,
+    /// Draw codebases and their repositories as a tree instead of a table
+    pub tree: bool,
+    /// Show the browser URL of repositories instead of their clone URL
+    pub web_url: bool,
+    /// Only list repositories whose name matches this substring or regular expression
+    pub search: Option<String>,
+}
 
 /// Execute the list command
-pub fn execute(codebase: Option<String>) -> BasecampResult<()> {
+///
+/// With `remote`, the status columns include what the host reports about CI and pull requests.
+pub fn execute(codebase: Option<String>, options: ListOptions) -> BasecampResult<()> {
     debug!("Executing list command");
+    let ListOptions {
+        status,
+        no_cache,
+        remote,
+        missing_only,
+        tree,
+        web_url,
+        search,
+    } = options;
 
     // Load configuration
     let config = Config::load(&std::path::PathBuf::new())?;
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
 
     // Check if GitHub URL is configured
     if !config.has_github_url() {
         return Err(BasecampError::GitHubUrlNotConfigured);
     }
 
-    // List specific codebase or all codebases
-    match codebase {
-        Some(codebase_name) => list_repositories(&config, &codebase_name),
-        None => list_codebases(&config),
+    // Status columns are read through the state cache
+    let mut state = if status { Some(WorkspaceState::load(&config.workspace)) } else { None };
+    let ttl = if no_cache { 0 } else { config.git_config.cache.ttl };
+
+    let codebases = match &codebase {
+        Some(codebase_name) if !config.is_group(codebase_name) => vec![codebase_name.clone()],
+        codebase => config.select_codebases(codebase.as_deref())?,
+    };
+    let remote = if remote {
+        let mut repos = Vec::new();
+        for codebase in &codebases {
+            repos.extend(config.get_repositories(codebase)?.iter().cloned());
+        }
+        remote_health(&config, &repos, no_cache)
+    } else {
+        None
+    };
+
+    if let Some(pattern) = &search {
+        return search_repositories(&config, &codebases, pattern);
+    }
+
+    // List specific codebase, or the codebases of a group or of the workspace
+    let result = match codebase {
+        _ if tree => list_tree(&config, &codebases, state.as_mut(), ttl, remote.as_ref(), missing_only),
+        Some(codebase_name) if !config.is_group(&codebase_name) => {
+            list_repositories(&config, &codebase_name, state.as_mut(), ttl, remote.as_ref(), missing_only, web_url)
+        }
+        _ => list_codebases(&config, &codebases, state.as_mut(), ttl, remote.as_ref(), missing_only),
+    };
+
+    if let Some(state) = state {
+        state.save()?;
     }
+
+    result
 }
 
-/// List all codebases
-fn list_codebases(config: &Config) -> BasecampResult<()> {
-    info!("Listing all codebases");
+/// Get the status of a repository through the state cache
+fn repo_state(
+    config: &Config,
+    state: &mut WorkspaceState,
+    codebase: &str,
+    repo: &str,
+    ttl: u64,
+) -> Option<RepoState> {
+    let repo_path = config.workspace.repo_path(codebase, repo);
+    match state.fresh(codebase, repo, &repo_path, config.remote_name(), ttl) {
+        Ok(repo_state) => repo_state.cloned(),
+        Err(e) => {
+            warn!("Failed to read status of '{}': {}", repo, e);
+            None
+        }
+    }
+}
+
+/// Describe a repository state in a few words (e.g. "dirty ↑2")
+fn describe(repo_state: &RepoState) -> String {
+    let mut parts = Vec::new();
+    if repo_state.dirty {
+        parts.push("dirty".to_string());
+    }
+    if repo_state.ahead > 0 {
+        parts.push(format!("{}{}", Glyph::Ahead, repo_state.ahead));
+    }
+    if repo_state.behind > 0 {
+        parts.push(format!("{}{}", Glyph::Behind, repo_state.behind));
+    }
+
+    if parts.is_empty() {
+        "clean".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// List the repositories whose name matches a pattern, with the codebases they belong to
+///
+/// The pattern is a case-insensitive regular expression, or a plain substring
+/// when it isn't a valid one.
+fn search_repositories(config: &Config, codebases: &[String], pattern: &str) -> BasecampResult<()> {
+    let regex = RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .or_else(|_| RegexBuilder::new(&regex::escape(pattern)).case_insensitive(true).build())
+        .map_err(|e| BasecampError::Generic(format!("Invalid search pattern '{}': {}", pattern, e)))?;
+
+    let mut table = UI::create_table(vec!["Repository", "Codebase", "Installed"]);
+    let mut matches = 0;
+    for codebase in codebases {
+        for repo in config.get_repositories(codebase)?.iter().filter(|repo| regex.is_match(repo)) {
+            let installed = config.workspace.repo_path(codebase, repo).exists();
+            UI::add_table_row(
+                &mut table,
+                vec![repo.clone(), codebase.clone(), if installed { "yes" } else { "no" }.to_string()],
+            );
+            matches += 1;
+        }
+    }
+
+    if matches == 0 {
+        UI::info(&format!("No repositories match '{}'", pattern));
+        return Ok(());
+    }
+    UI::print_table(&table);
+    Ok(())
+}
 
-    let codebases = config.list_codebases();
+/// Draw codebases with their repositories as a tree, one repository per line
+///
+/// Each repository gets a glyph (✓ cloned, ○ not cloned) and, with status,
+/// its branch and state.
+fn list_tree(
+    config: &Config,
+    codebases: &[String],
+    mut state: Option<&mut WorkspaceState>,
+    ttl: u64,
+    remote: Option<&BTreeMap<String, RemoteHealth>>,
+    missing_only: bool,
+) -> BasecampResult<()> {
+    let mut lines = Vec::new();
+    for codebase in codebases {
+        let repos = config.get_repositories(codebase)?;
+        let installed: Vec<(&String, bool)> = repos
+            .iter()
+            .map(|repo| (repo, config.workspace.repo_path(codebase, repo).exists()))
+            .collect();
+        let cloned = installed.iter().filter(|(_, installed)| *installed).count();
+        let shown: Vec<&(&String, bool)> = installed.iter().filter(|(_, installed)| !missing_only || !installed).collect();
+        if missing_only && shown.is_empty() {
+            continue;
+        }
+
+        lines.push(format!("{} ({}/{} cloned)", codebase, cloned, repos.len()));
+        let width = shown.iter().map(|(repo, _)| repo.len()).max().unwrap_or(0);
+        for (index, (repo, installed)) in shown.iter().enumerate() {
+            let branch = if index + 1 == shown.len() { Glyph::TreeLast } else { Glyph::TreeBranch };
+            let mut line = format!("{} {} {:width$}", branch, if *installed { Glyph::Installed } else { Glyph::Missing }, repo, width = width);
+            if let Some(state) = state.as_deref_mut() {
+                match repo_state(config, state, codebase, repo, ttl) {
+                    Some(repo_state) => line.push_str(&format!(
+                        "  {} {}",
+                        repo_state.branch.as_deref().unwrap_or("(detached)"),
+                        describe(&repo_state)
+                    )),
+                    None => line.push_str("  not installed"),
+                }
+            }
+            if let Some(remote) = remote {
+                let [ci, prs] = remote_cells(remote.get(*repo));
+                line.push_str(&format!("  CI {}, {} PRs", ci, prs));
+            }
+            lines.push(line.trim_end().to_string());
+        }
+    }
+
+    if lines.is_empty() {
+        UI::success("Every repository is installed");
+        return Ok(());
+    }
+    UI::output(&format!("{}\n", lines.join("\n")));
+    Ok(())
+}
+
+/// List codebases with their repositories
+fn list_codebases(
+    config: &Config,
+    codebases: &[String],
+    mut state: Option<&mut WorkspaceState>,
+    ttl: u64,
+    remote: Option<&BTreeMap<String, RemoteHealth>>,
+    missing_only: bool,
+) -> BasecampResult<()> {
+    info!("Listing codebases: {}", codebases.join(", "));
 
     if codebases.is_empty() {
         UI::info("No codebases configured yet. Use 'basecamp add <codebase> <repo>' to add one.");
         return Ok(());
     }
 
-    let mut table = UI::create_table(vec!["Codebase", "Repositories"]);
+    let mut headers = vec!["Codebase", "Repositories", "Installed"];
+    if state.is_some() {
+        headers.push("Status");
+    }
+    if remote.is_some() {
+        headers.push("Remote");
+    }
+    let mut table = UI::create_table(headers);
 
+    let mut listed = 0;
     for codebase_name in codebases {
         let repos = config.get_repositories(codebase_name)?;
-        
+        let installed = repos
+            .iter()
+            .filter(|repo| config.workspace.repo_path(codebase_name, repo).exists())
+            .count();
+        let shown: Vec<&String> = repos
+            .iter()
+            .filter(|repo| !missing_only || !config.workspace.repo_path(codebase_name, repo).exists())
+            .collect();
+        if missing_only && shown.is_empty() {
+            continue;
+        }
+        listed += 1;
+
         // Format repository names as a simple comma-separated list
-        let repo_names = if !repos.is_empty() {
-            repos.join(", ")
+        let repo_names = if !shown.is_empty() {
+            shown.iter().map(|repo| repo.as_str()).collect::<Vec<_>>().join(", ")
         } else {
             String::from("None")
         };
 
-        UI::add_table_row(
-            &mut table,
-            vec![
-                codebase_name.to_string(),
-                repo_names
-            ],
-        );
+        let mut row = vec![
+            codebase_name.to_string(),
+            repo_names,
+            format!("{}/{} cloned", installed, repos.len()),
+        ];
+
+        if let Some(state) = state.as_deref_mut() {
+            // Summarize the state of the codebase's repositories
+            let (mut dirty, mut ahead, mut behind, mut missing) = (0, 0, 0, 0);
+            for repo in repos {
+                match repo_state(config, state, codebase_name, repo, ttl) {
+                    Some(repo_state) => {
+                        dirty += repo_state.dirty as usize;
+                        ahead += (repo_state.ahead > 0) as usize;
+                        behind += (repo_state.behind > 0) as usize;
+                    }
+                    None => missing += 1,
+                }
+            }
+
+            let summary: Vec<String> = [
+                (dirty, "dirty"),
+                (ahead, "ahead"),
+                (behind, "behind"),
+                (missing, "not installed"),
+            ]
+            .iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, label)| format!("{} {}", count, label))
+            .collect();
+
+            row.push(if summary.is_empty() {
+                "clean".to_string()
+            } else {
+                summary.join(", ")
+            });
+        }
+
+        if let Some(remote) = remote {
+            let health: Vec<&RemoteHealth> = repos.iter().filter_map(|repo| remote.get(repo)).collect();
+            let failing = health.iter().filter(|health| health.ci == Some(CiState::Failure)).count();
+            let open: usize = health.iter().map(|health| health.open_pull_requests).sum();
+            row.push(format!("{} failing CI, {} open PRs", failing, open));
+        }
+
+        UI::add_table_row(&mut table, row);
     }
 
+    if listed == 0 {
+        UI::success("Every repository is installed");
+        return Ok(());
+    }
     UI::print_table(&table);
 
     Ok(())
 }
 
 /// List repositories in a specific codebase
-fn list_repositories(config: &Config, codebase: &str) -> BasecampResult<()> {
+fn list_repositories(
+    config: &Config,
+    codebase: &str,
+    mut state: Option<&mut WorkspaceState>,
+    ttl: u64,
+    remote: Option<&BTreeMap<String, RemoteHealth>>,
+    missing_only: bool,
+    web_url: bool,
+) -> BasecampResult<()> {
     info!("Listing repositories for codebase: {}", codebase);
 
     let repos = config.get_repositories(codebase)?;
@@ -74,15 +343,57 @@ fn list_repositories(config: &Config, codebase: &str) -> BasecampResult<()> {
         return Ok(());
     }
 
-    let mut table = UI::create_table(vec!["Repository", "URL"]);
+    let installed: Vec<bool> = repos
+        .iter()
+        .map(|repo| config.workspace.repo_path(codebase, repo).exists())
+        .collect();
+    let cloned = installed.iter().filter(|installed| **installed).count();
+    if missing_only && cloned == repos.len() {
+        UI::success(&format!("All {} repositories of '{}' are installed", repos.len(), codebase));
+        return Ok(());
+    }
+
+    let mut headers = vec!["Repository", "URL", "Installed"];
+    if state.is_some() {
+        headers.extend(["Branch", "Status"]);
+    }
+    if remote.is_some() {
+        headers.extend(["CI", "PRs"]);
+    }
+    let mut table = UI::create_table(headers);
+
+    for (repo, installed) in repos.iter().zip(installed) {
+        if missing_only && installed {
+            continue;
+        }
+        let url = if web_url {
+            GitRepo::build_web_url(&config.git_config.github_url, repo)
+        } else {
+            GitRepo::build_repo_url(&config.git_config.github_url, repo)
+        };
+        let mut row = vec![repo.to_string(), url, if installed { "yes" } else { "no" }.to_string()];
 
-    for repo in repos {
-        let url = format!("{}/{}.git", config.git_config.github_url, repo);
+        if let Some(state) = state.as_deref_mut() {
+            match repo_state(config, state, codebase, repo, ttl) {
+                Some(repo_state) => {
+                    row.push(repo_state.branch.clone().unwrap_or_else(|| "(detached)".to_string()));
+                    row.push(describe(&repo_state));
+                }
+                None => {
+                    row.push("-".to_string());
+                    row.push("not installed".to_string());
+                }
+            }
+        }
+        if let Some(remote) = remote {
+            row.extend(remote_cells(remote.get(repo)));
+        }
 
-        UI::add_table_row(&mut table, vec![repo.to_string(), url]);
+        UI::add_table_row(&mut table, row);
     }
 
     UI::print_table(&table);
+    UI::info(&format!("{}/{} cloned", cloned, repos.len()));
 
     Ok(())
 }
@@ -0,0 +1,67 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::{Command, CommandFactory};
+use clap_mangen::Man;
+use log::debug;
+
+use crate::cli::Cli;
+use crate::error::BasecampResult;
+use crate::ui::UI;
+
+/// Execute the manpages command
+///
+/// Writes `basecamp.1` and one page per subcommand (e.g. `basecamp-install.1`,
+/// `basecamp-workspaces-add.1`) to `dir`, for packages to install under
+/// `share/man/man1`.
+pub fn execute(dir: PathBuf) -> BasecampResult<()> {
+    debug!("Executing manpages command");
+
+    fs::create_dir_all(&dir)?;
+
+    // Building fills in the display names (`basecamp-install`) and global options of subcommands
+    let mut cmd = Cli::command().disable_help_subcommand(true);
+    cmd.build();
+
+    let mut written = Vec::new();
+    generate(&cmd, &dir, &mut written)?;
+
+    for path in &written {
+        UI::detail(&format!("Wrote {}", path.display()));
+    }
+    UI::success(&format!("Wrote {} man pages to {}", written.len(), dir.display()));
+
+    Ok(())
+}
+
+/// Write the page of a command and of its subcommands
+fn generate(cmd: &Command, dir: &Path, written: &mut Vec<PathBuf>) -> BasecampResult<()> {
+    let man = Man::new(cmd.clone()).source(format!("basecamp {}", env!("CARGO_PKG_VERSION")));
+    let mut page = Vec::new();
+    man.render(&mut page)?;
+
+    let path = dir.join(man.get_filename());
+    fs::write(&path, with_examples_section(&String::from_utf8_lossy(&page)))?;
+    written.push(path);
+
+    for subcommand in cmd.get_subcommands().filter(|sub| !sub.is_hide_set()) {
+        generate(subcommand, dir, written)?;
+    }
+
+    Ok(())
+}
+
+/// Turn the `Examples:` after-help, rendered as an EXTRA section, into an unfilled EXAMPLES section
+///
+/// Without this the example lines would be joined into one paragraph.
+fn with_examples_section(page: &str) -> String {
+    let Some(start) = page.find(".SH EXTRA\n") else {
+        return page.to_string();
+    };
+    let body_start = start + ".SH EXTRA\n".len();
+    let end = page[body_start..].find("\n.SH ").map_or(page.len(), |i| body_start + i + 1);
+
+    let body = page[body_start..end].trim_end();
+    let body = body.strip_prefix("Examples:\n").unwrap_or(body);
+    format!("{}.SH EXAMPLES\n.nf\n{}\n.fi\n{}", &page[..start], body, &page[end..])
+}
@@ -0,0 +1,138 @@
+use std::path::PathBuf;
+
+use log::{debug, info};
+
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::ui::UI;
+
+/// A cloned repository whose `origin` moves to the new organization
+struct RemoteChange {
+    codebase: String,
+    repo: String,
+    current: String,
+    new: String,
+}
+
+/// Execute the migrate-org command
+///
+/// Moves `github_url` and the `origin` remote of every clone pointing at
+/// organization `old` over to `new`. Remotes of other organizations (forks,
+/// mirrors) are left alone. With `dry_run`, only the planned changes are shown.
+pub fn execute(old: &str, new: &str, dry_run: bool) -> BasecampResult<()> {
+    debug!("Executing migrate-org command from {} to {}", old, new);
+
+    // Load configuration
+    let mut config = Config::load(&PathBuf::new())?;
+
+    // Check if GitHub URL is configured
+    if !config.has_github_url() {
+        return Err(BasecampError::GitHubUrlNotConfigured);
+    }
+
+    let current_url = config.git_config.github_url.clone();
+    let new_url = GitRepo::rename_org(&current_url, old, new);
+
+    let mut codebases: Vec<String> = config.list_codebases().into_iter().cloned().collect();
+    codebases.sort();
+
+    let mut changes = Vec::new();
+    for codebase in &codebases {
+        for repo in config.get_repositories(codebase)? {
+            let repo_path = config.workspace.repo_path(codebase, repo);
+            if !repo_path.exists() {
+                continue;
+            }
+
+            let Some(current) = GitRepo::remote_url(&repo_path, config.remote_name())? else {
+                continue;
+            };
+            let Some((base, name)) = GitRepo::split_repo_url(&current) else {
+                continue;
+            };
+            if let Some(new_base) = GitRepo::rename_org(&base, old, new) {
+                changes.push(RemoteChange {
+                    codebase: codebase.clone(),
+                    repo: repo.clone(),
+                    new: GitRepo::build_repo_url(&new_base, &name),
+                    current,
+                });
+            }
+        }
+    }
+
+    if new_url.is_none() && changes.is_empty() {
+        return Err(BasecampError::CommandFailed(format!(
+            "Neither config.yaml ({}) nor any cloned repository uses organization '{}'",
+            current_url, old
+        )));
+    }
+
+    let mut table = UI::create_table(vec!["Target", "Current", "New"]);
+    if let Some(new_url) = &new_url {
+        UI::add_table_row(
+            &mut table,
+            vec!["config.yaml".to_string(), current_url.clone(), new_url.clone()],
+        );
+    }
+    for change in &changes {
+        UI::add_table_row(
+            &mut table,
+            vec![
+                format!("{}/{}", change.codebase, change.repo),
+                change.current.clone(),
+                change.new.clone(),
+            ],
+        );
+    }
+    UI::print_table(&table);
+
+    if dry_run {
+        UI::info("Dry run: nothing was changed. Run again without --dry-run to apply.");
+        return Ok(());
+    }
+
+    if let Some(new_url) = new_url {
+        config.set_github_url(new_url.clone())?;
+        config.save_config()?;
+        UI::success(&format!("Updated github_url to {}", new_url));
+    } else {
+        UI::warning(&format!(
+            "config.yaml ({}) doesn't use organization '{}' and was left unchanged",
+            current_url, old
+        ));
+    }
+
+    let mut errors = Vec::new();
+    for change in &changes {
+        let repo_path = config.workspace.repo_path(&change.codebase, &change.repo);
+        match GitRepo::set_remote_url(&repo_path, config.remote_name(), &change.new) {
+            Ok(()) => info!("Rewrote origin of {:?} to {}", repo_path, change.new),
+            Err(e) => {
+                UI::error(&format!(
+                    "Failed to rewrite origin of {}/{}: {}",
+                    change.codebase, change.repo, e
+                ));
+                errors.push(format!("{}/{}", change.codebase, change.repo));
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(BasecampError::CommandFailed(format!(
+            "{} repositories could not be migrated: {}",
+            errors.len(),
+            errors.join(", ")
+        )));
+    }
+
+    if !changes.is_empty() {
+        UI::success(&format!(
+            "Moved the origin of {} repositories to '{}'",
+            changes.len(),
+            new
+        ));
+    }
+    Ok(())
+}
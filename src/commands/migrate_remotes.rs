@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+
+use log::{debug, info};
+
+use crate::cli::RemoteProtocol;
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::ui::UI;
+
+/// Execute the migrate-remotes command
+///
+/// Rewrites `github_url` in `config.yaml` to the requested URL style, then
+/// points the `origin` remote of every cloned repository at the new URL.
+pub fn execute(to: RemoteProtocol) -> BasecampResult<()> {
+    debug!("Executing migrate-remotes command to {:?}", to);
+
+    // Load configuration
+    let mut config = Config::load(&PathBuf::new())?;
+
+    // Check if GitHub URL is configured
+    if !config.has_github_url() {
+        return Err(BasecampError::GitHubUrlNotConfigured);
+    }
+
+    let current_url = config.git_config.github_url.clone();
+    let new_url = GitRepo::convert_url(&current_url, to == RemoteProtocol::Ssh)
+        .ok_or_else(|| BasecampError::InvalidGitHubUrl(current_url.clone()))?;
+
+    if new_url == current_url {
+        UI::info(&format!("config.yaml already uses {}", new_url));
+    } else {
+        config.set_github_url(new_url.clone())?;
+        config.save_config()?;
+        UI::success(&format!("Updated github_url from {} to {}", current_url, new_url));
+    }
+
+    let mut codebases: Vec<String> = config.list_codebases().into_iter().cloned().collect();
+    codebases.sort();
+
+    let mut rewritten = 0;
+    let mut unchanged = 0;
+    let mut errors = Vec::new();
+
+    for codebase in &codebases {
+        for repo in config.get_repositories(codebase)? {
+            let repo_path = config.workspace.repo_path(codebase, repo);
+            if !repo_path.exists() {
+                continue;
+            }
+
+            let expected = GitRepo::build_repo_url(&new_url, repo);
+            let result = GitRepo::remote_url(&repo_path, config.remote_name()).and_then(|actual| {
+                if actual.is_some_and(|url| GitRepo::same_remote(&url, &expected)) {
+                    return Ok(false);
+                }
+                GitRepo::set_remote_url(&repo_path, config.remote_name(), &expected).map(|_| true)
+            });
+
+            match result {
+                Ok(true) => {
+                    info!("Rewrote origin of {:?} to {}", repo_path, expected);
+                    UI::detail(&format!("{}/{}: {}", codebase, repo, expected));
+                    rewritten += 1;
+                }
+                Ok(false) => unchanged += 1,
+                Err(e) => {
+                    UI::error(&format!("Failed to rewrite origin of {}/{}: {}", codebase, repo, e));
+                    errors.push(format!("{}/{}", codebase, repo));
+                }
+            }
+        }
+    }
+
+    if rewritten > 0 || unchanged > 0 {
+        UI::success(&format!(
+            "Rewrote the origin of {} repositories ({} already up to date)",
+            rewritten, unchanged
+        ));
+    }
+
+    if !errors.is_empty() {
+        return Err(BasecampError::CommandFailed(format!(
+            "{} repositories could not be migrated: {}",
+            errors.len(),
+            errors.join(", ")
+        )));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,201 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use log::{debug, warn};
+
+use crate::config::{Config, MirrorConfig};
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::git::auth::{self, TokenScope};
+use crate::git::provider::HostProvider;
+use crate::ui::UI;
+
+/// Where missing repositories are created on the mirror host
+struct Creator {
+    provider: HostProvider,
+    host: String,
+    owner: String,
+    token: String,
+}
+
+/// Pushed references of a repository, and whether it had to be created first
+type MirrorResult = BasecampResult<(bool, usize)>;
+
+/// Execute the mirror command
+///
+/// Pushes the branches and tags of every cloned repository of a codebase (or
+/// of every codebase) to a host configured under `mirrors` in config.yaml.
+/// Repositories missing on the mirror are created through its API, when the
+/// provider has one and an access token is set, unless `no_create` is given.
+pub fn execute(
+    codebase: Option<String>,
+    to: Option<String>,
+    no_create: bool,
+    parallel: Option<usize>,
+) -> BasecampResult<()> {
+    debug!("Executing mirror command");
+
+    let config = Config::load(&PathBuf::new())?;
+    if !config.has_github_url() {
+        return Err(BasecampError::GitHubUrlNotConfigured);
+    }
+    let (name, mirror) = select_mirror(&config, to.as_deref())?;
+    GitRepo::ensure_online("push to a mirror")?;
+    let creator = if no_create { None } else { creator(name, mirror, config.git_config.token_scope().as_ref()) };
+
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+    let mut jobs = Vec::new();
+    let mut seen = BTreeSet::new();
+    let mut missing = 0;
+    for codebase in config.select_codebases(codebase.as_deref())? {
+        for repo in config.get_repositories(&codebase)? {
+            if !config.workspace.repo_path(&codebase, repo).exists() {
+                missing += 1;
+            } else if seen.insert(repo.clone()) {
+                jobs.push((codebase.clone(), repo.clone()));
+            }
+        }
+    }
+    if jobs.is_empty() {
+        UI::info("No cloned repositories to mirror. Run 'basecamp install' first.");
+        return Ok(());
+    }
+
+    UI::info(&format!("Mirroring {} repositories to '{}' ({})", jobs.len(), name, mirror.url));
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<MirrorResult>>> = Mutex::new((0..jobs.len()).map(|_| None).collect());
+    thread::scope(|scope| {
+        for _ in 0..config.parallel(parallel).min(jobs.len()) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some((codebase, repo)) = jobs.get(index) else {
+                        break;
+                    };
+                    let result = mirror_repository(&config, mirror, creator.as_ref(), codebase, repo);
+                    results.lock().unwrap()[index] = Some(result);
+                }
+            });
+        }
+    });
+
+    let mut table = UI::create_table(vec!["Codebase", "Repository", "Result"]);
+    let mut failed = Vec::new();
+    let mut created = 0;
+    for ((codebase, repo), result) in jobs.iter().zip(results.into_inner().unwrap()) {
+        let cell = match result {
+            Some(Ok((was_created, refs))) => {
+                created += usize::from(was_created);
+                let pushed = format!("pushed {} refs", refs);
+                if was_created { format!("created, {}", pushed) } else { pushed }
+            }
+            Some(Err(e)) => {
+                warn!("Failed to mirror {}/{}: {}", codebase, repo, e);
+                failed.push(repo.clone());
+                format!("failed: {}", e)
+            }
+            None => continue,
+        };
+        UI::add_table_row(&mut table, vec![codebase.clone(), repo.clone(), cell]);
+    }
+    UI::print_table(&table);
+
+    if missing > 0 {
+        UI::detail(&format!("{} repositories aren't cloned and were left out", missing));
+    }
+    if !failed.is_empty() {
+        return Err(BasecampError::CommandFailed(format!(
+            "{} of {} repositories failed to mirror: {}",
+            failed.len(),
+            jobs.len(),
+            failed.join(", ")
+        )));
+    }
+    UI::success(&format!(
+        "Mirrored {} repositories to '{}' ({} created)",
+        jobs.len(),
+        name,
+        created
+    ));
+    Ok(())
+}
+
+/// Mirror named with `--to`, or the only one configured
+fn select_mirror<'a>(config: &'a Config, to: Option<&str>) -> BasecampResult<(&'a str, &'a MirrorConfig)> {
+    let mirrors = &config.git_config.mirrors;
+    let names = || mirrors.keys().cloned().collect::<Vec<_>>().join(", ");
+    match to {
+        Some(to) => mirrors
+            .get_key_value(to)
+            .map(|(name, mirror)| (name.as_str(), mirror))
+            .ok_or_else(|| BasecampError::Generic(format!("No mirror named '{}' in config.yaml (known: {})", to, names()))),
+        None if mirrors.len() == 1 => Ok(mirrors.iter().next().map(|(name, mirror)| (name.as_str(), mirror)).unwrap()),
+        None if mirrors.is_empty() => Err(BasecampError::Generic(
+            "No mirrors configured; add one under 'mirrors' in config.yaml".to_string(),
+        )),
+        None => Err(BasecampError::Generic(format!("Choose a mirror with --to: {}", names()))),
+    }
+}
+
+/// How to create missing repositories on a mirror, `None` when they can't be
+///
+/// Without `token_env`, the environment's tokens are used when they belong to
+/// the mirror host (`scope` being the configured host).
+fn creator(name: &str, mirror: &MirrorConfig, scope: Option<&TokenScope>) -> Option<Creator> {
+    let (host, owner) = GitRepo::remote_owner(&GitRepo::build_repo_url(&mirror.url, "repo"))?;
+    let provider = mirror.provider.unwrap_or_else(|| HostProvider::detect(&host));
+    if provider == HostProvider::Custom {
+        return None;
+    }
+
+    let token = match &mirror.token_env {
+        Some(variable) => env::var(variable).ok().filter(|token| !token.trim().is_empty()),
+        None => auth::env_token(&host, scope),
+    };
+    let Some(token) = token else {
+        let variables = match &mirror.token_env {
+            Some(variable) => variable.clone(),
+            None => auth::token_variables(&host, scope).join(" or "),
+        };
+        UI::warning(&format!(
+            "Set {} to create missing repositories on mirror '{}'",
+            variables, name
+        ));
+        return None;
+    };
+    Some(Creator {
+        provider,
+        host,
+        owner,
+        token,
+    })
+}
+
+/// Create a repository on the mirror when needed and push it there
+fn mirror_repository(
+    config: &Config,
+    mirror: &MirrorConfig,
+    creator: Option<&Creator>,
+    codebase: &str,
+    repo: &str,
+) -> MirrorResult {
+    let created = match creator {
+        Some(creator) => creator
+            .provider
+            .ensure_repository(&creator.host, &creator.owner, repo, &creator.token)?,
+        None => false,
+    };
+    let url = GitRepo::build_repo_url(&mirror.url, repo);
+    let token = mirror.token_env.as_deref().and_then(|variable| env::var(variable).ok());
+    let refs = GitRepo::push_mirror(
+        &config.workspace.repo_path(codebase, repo),
+        config.remote_name(),
+        &url,
+        token.as_deref().filter(|token| !token.trim().is_empty()),
+    )?;
+    Ok((created, refs))
+}
@@ -1,11 +1,75 @@
 pub mod add;
+pub mod archive;
+pub mod completions;
+pub mod compose;
+pub mod config;
+pub mod dedupe;
+pub mod env;
+pub mod export;
+pub mod fetch;
+pub mod hooks;
+pub mod import;
+pub mod info;
 pub mod init;
 pub mod install;
 pub mod list;
+pub mod manpages;
+pub mod migrate_org;
+pub mod migrate_remotes;
+pub mod mirror;
+pub mod open;
+pub mod outdated;
+pub mod path;
+pub mod paths;
+pub mod profile;
 pub mod remove;
+pub mod report;
+pub mod run;
+pub mod serve;
+pub mod shell_init;
+pub mod snapshot;
+pub mod stats;
+pub mod status;
+pub mod sync_fork;
+pub mod undo;
+pub mod update_pins;
+pub mod validate;
+pub mod verify;
+pub mod watch;
+pub mod workspace;
+pub mod workspaces;
 
 pub use add::execute as add;
-pub use init::execute as init;
-pub use install::execute as install;
-pub use list::execute as list;
+pub use archive::{execute as archive, unarchive};
+pub use completions::{complete, execute as completions};
+pub use compose::execute as compose;
+pub use dedupe::execute as dedupe;
+pub use env::execute as env;
+pub use export::execute as export;
+pub use fetch::execute as fetch;
+pub use import::execute as import;
+pub use info::execute as info;
+pub use init::{execute as init, InitOptions};
+pub use install::{execute as install, InstallOptions};
+pub use list::{execute as list, ListOptions};
+pub use manpages::execute as manpages;
+pub use migrate_org::execute as migrate_org;
+pub use migrate_remotes::execute as migrate_remotes;
+pub use mirror::execute as mirror;
+pub use open::execute as open;
+pub use outdated::execute as outdated;
+pub use path::execute as path;
+pub use paths::execute as paths;
 pub use remove::execute as remove;
+pub use report::execute as report;
+pub use run::{execute as run, TaskOptions};
+pub use serve::execute as serve;
+pub use shell_init::execute as shell_init;
+pub use stats::execute as stats;
+pub use status::{execute as status, StatusOptions};
+pub use sync_fork::execute as sync_fork;
+pub use undo::execute as undo;
+pub use update_pins::execute as update_pins;
+pub use validate::execute as validate;
+pub use verify::execute as verify;
+pub use watch::execute as watch;
@@ -0,0 +1,100 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use log::{debug, info};
+
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::ui::UI;
+
+/// Execute the open command
+pub fn execute(repo: String, codebase: Option<String>, editor: bool) -> BasecampResult<()> {
+    debug!("Executing open command for repository '{}'", repo);
+
+    // Load configuration
+    let config = Config::load(&PathBuf::new())?;
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+
+    // Check if GitHub URL is configured
+    if !config.has_github_url() {
+        return Err(BasecampError::GitHubUrlNotConfigured);
+    }
+
+    let codebase = config.resolve_repository(&repo, codebase.as_deref())?;
+
+    if editor {
+        let repo_path = env::current_dir()?.join(config.workspace.repo_path(&codebase, &repo));
+        if !repo_path.exists() {
+            return Err(BasecampError::Generic(format!(
+                "Repository '{}' is not installed. Run 'basecamp install {}' first.",
+                repo, codebase
+            )));
+        }
+
+        open_in_editor(&repo_path)?;
+        info!("Opened {:?} in editor", repo_path);
+    } else {
+        let url = GitRepo::build_web_url(&config.git_config.github_url, &repo);
+        UI::info(&format!("Opening {}", url));
+        open_in_browser(&url)?;
+        info!("Opened {} in browser", url);
+    }
+
+    Ok(())
+}
+
+/// Open a directory with $VISUAL, $EDITOR or VS Code
+fn open_in_editor(path: &Path) -> BasecampResult<()> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "code".to_string());
+
+    // Editors are often configured with arguments, e.g. "code -w"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("code");
+
+    debug!("Opening {:?} with '{}'", path, editor);
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|e| BasecampError::CommandFailed(format!("Failed to start editor '{}': {}", program, e)))?;
+
+    if !status.success() {
+        return Err(BasecampError::CommandFailed(format!(
+            "Editor '{}' exited with {}",
+            program, status
+        )));
+    }
+
+    Ok(())
+}
+
+/// Open a URL with the platform's default browser
+fn open_in_browser(url: &str) -> BasecampResult<()> {
+    let mut cmd = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", "start", ""]);
+        cmd
+    } else {
+        Command::new("xdg-open")
+    };
+
+    let status = cmd
+        .arg(url)
+        .status()
+        .map_err(|e| BasecampError::CommandFailed(format!("Failed to open browser: {}", e)))?;
+
+    if !status.success() {
+        return Err(BasecampError::CommandFailed(format!(
+            "Failed to open {} in the browser",
+            url
+        )));
+    }
+
+    Ok(())
+}
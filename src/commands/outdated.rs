@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+
+use log::{debug, warn};
+
+use crate::commands::fetch;
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::ui::UI;
+
+/// Execute the outdated command
+///
+/// Lists the installed repositories whose branch is behind its remote, as
+/// of the last fetch (or a fresh one with `fetch`). Fails when any is behind,
+/// so it can gate scripts and CI jobs.
+pub fn execute(
+    codebase: Option<String>,
+    fetch: bool,
+    parallel_count: Option<usize>,
+) -> BasecampResult<()> {
+    debug!("Executing outdated command");
+
+    // Load configuration
+    let config = Config::load(&PathBuf::new())?;
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+    let codebases = config.select_codebases(codebase.as_deref())?;
+
+    if fetch {
+        fetch::refresh(&config, codebase.as_deref(), config.parallel(parallel_count), None)?;
+    }
+
+    let mut table = UI::create_table(vec![
+        "Codebase",
+        "Repository",
+        "Branch",
+        "Behind",
+        "Last remote commit",
+    ]);
+    let mut checked = 0;
+    let mut outdated = 0;
+
+    for codebase in &codebases {
+        for repo in config.get_repositories(codebase)? {
+            let repo_path = config.workspace.repo_path(codebase, repo);
+            if !repo_path.exists() {
+                continue;
+            }
+
+            let status = match GitRepo::status(&repo_path, config.remote_name()) {
+                Ok(status) => status,
+                Err(e) => {
+                    warn!("Failed to read status of '{}': {}", repo, e);
+                    UI::warning(&format!("Could not read the status of {}/{}: {}", codebase, repo, e));
+                    continue;
+                }
+            };
+            checked += 1;
+            if status.behind == 0 {
+                continue;
+            }
+
+            outdated += 1;
+            let last_commit = GitRepo::upstream_commit_time(&repo_path, config.remote_name()).unwrap_or_default();
+            UI::add_table_row(
+                &mut table,
+                vec![
+                    codebase.clone(),
+                    repo.clone(),
+                    status.branch.unwrap_or_else(|| "(detached)".to_string()),
+                    status.behind.to_string(),
+                    UI::format_age(last_commit),
+                ],
+            );
+        }
+    }
+
+    if outdated == 0 {
+        UI::success(&format!(
+            "All {} installed repositories are up to date with their remote{}",
+            checked,
+            if fetch { "" } else { " (as of the last fetch; pass --fetch to check now)" }
+        ));
+        return Ok(());
+    }
+
+    UI::print_table(&table);
+    Err(BasecampError::CommandFailed(format!(
+        "{} of {} installed repositories are behind their remote",
+        outdated, checked
+    )))
+}
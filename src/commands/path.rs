@@ -0,0 +1,79 @@
+use std::env;
+use std::path::PathBuf;
+
+use log::debug;
+
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::ui::UI;
+
+/// Execute the path command
+///
+/// Prints the absolute path of a repository on stdout so that it can be
+/// consumed by shell helpers such as `bcd`.
+pub fn execute(query: String, codebase: Option<String>) -> BasecampResult<()> {
+    debug!("Executing path command for '{}'", query);
+
+    // Load configuration
+    let config = Config::load(&PathBuf::new())?;
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+
+    let (codebase, repo) = match codebase {
+        Some(codebase) => {
+            config.resolve_repository(&query, Some(&codebase))?;
+            (codebase, query)
+        }
+        None => fuzzy_find(&config, &query)?,
+    };
+
+    let path = env::current_dir()?.join(config.workspace.repo_path(&codebase, &repo));
+    UI::output(&format!("{}\n", path.display()));
+
+    Ok(())
+}
+
+/// Find a repository by exact name, unique substring or close spelling
+fn fuzzy_find(config: &Config, query: &str) -> BasecampResult<(String, String)> {
+    let mut all: Vec<(String, String)> = Vec::new();
+    for codebase in config.list_codebases() {
+        for repo in config.get_repositories(codebase)? {
+            all.push((codebase.clone(), repo.clone()));
+        }
+    }
+    all.sort();
+
+    let exact: Vec<&(String, String)> = all.iter().filter(|(_, repo)| repo == query).collect();
+    let query_lower = query.to_lowercase();
+    let substring: Vec<&(String, String)> = all
+        .iter()
+        .filter(|(_, repo)| repo.to_lowercase().contains(&query_lower))
+        .collect();
+
+    let candidates = if !exact.is_empty() { exact } else { substring };
+
+    match candidates.as_slice() {
+        [(codebase, repo)] => Ok((codebase.clone(), repo.clone())),
+        [] => {
+            let names: Vec<&str> = all.iter().map(|(_, repo)| repo.as_str()).collect();
+            let suggestions = similar::get_close_matches(query, &names, 3, 0.6);
+            if suggestions.is_empty() {
+                Err(BasecampError::UnknownRepository(query.to_string()))
+            } else {
+                Err(BasecampError::Generic(format!(
+                    "Repository '{}' not found. Did you mean: {}?",
+                    query,
+                    suggestions.join(", ")
+                )))
+            }
+        }
+        matches => Err(BasecampError::Generic(format!(
+            "'{}' matches several repositories: {}",
+            query,
+            matches
+                .iter()
+                .map(|(codebase, repo)| format!("{}/{}", codebase, repo))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
@@ -0,0 +1,40 @@
+use std::env;
+use std::path::PathBuf;
+
+use log::debug;
+
+use crate::config::Config;
+use crate::core::filter::{self, RepoFilter};
+use crate::error::BasecampResult;
+use crate::ui::{ParallelProgress, Reporter, UI};
+
+/// Execute the paths command
+///
+/// Prints the absolute path of every installed repository, one per line or
+/// NUL-terminated with `null`, for `xargs` and other tools. Repositories come
+/// in codebases.yaml order, or after their dependencies with `deps`. Messages
+/// go to stderr so only the paths reach the pipe.
+pub fn execute(codebase: Option<String>, null: bool, filters: Vec<RepoFilter>, deps: bool) -> BasecampResult<()> {
+    debug!("Executing paths command");
+    Reporter::global().set_machine_output(true);
+
+    let config = Config::load(&PathBuf::new())?;
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+    let codebases = config.select_codebases(codebase.as_deref())?;
+
+    let mut selected = filter::select(&config, &codebases, &filters, &ParallelProgress::new())?;
+    if deps {
+        let repos: Vec<String> = selected.iter().map(|(_, repo)| repo.clone()).collect();
+        let order: Vec<String> = config.dependency_levels(&repos)?.into_iter().flatten().collect();
+        selected.sort_by_key(|(_, repo)| order.iter().position(|name| name == repo));
+    }
+
+    let cwd = env::current_dir()?;
+    let terminator = if null { '\0' } else { '\n' };
+    let paths: String = selected
+        .iter()
+        .map(|(codebase, repo)| format!("{}{}", cwd.join(config.workspace.repo_path(codebase, repo)).display(), terminator))
+        .collect();
+    UI::output(&paths);
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use log::debug;
+
+use crate::config::{Config, ProfileConfig};
+use crate::error::{BasecampError, BasecampResult};
+use crate::ui::UI;
+
+/// Execute the profile list command
+pub fn list() -> BasecampResult<()> {
+    debug!("Executing profile list command");
+
+    // Load configuration
+    let config = Config::load(&PathBuf::new())?;
+    let profiles = &config.git_config.profiles;
+
+    if profiles.is_empty() {
+        UI::info("No profiles defined. Add one with 'basecamp profile add <name> --github-url <url>'.");
+        return Ok(());
+    }
+
+    let active = config.git_config.active_profile();
+    let mut table = UI::create_table(vec!["", "Profile", "GitHub URL", "SSH key"]);
+    for (name, profile) in profiles {
+        UI::add_table_row(
+            &mut table,
+            vec![
+                if active == Some(name.as_str()) { "*" } else { "" }.to_string(),
+                name.clone(),
+                profile.github_url.clone().unwrap_or_else(|| "-".to_string()),
+                profile
+                    .ssh_key
+                    .as_ref()
+                    .map(|key| key.display().to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+            ],
+        );
+    }
+    UI::print_table(&table);
+
+    match active {
+        Some(name) => UI::info(&format!("Active profile: {}", name)),
+        None => UI::info("No profile is active; the top-level settings of config.yaml are used"),
+    }
+    Ok(())
+}
+
+/// Execute the profile add command
+pub fn add(name: &str, github_url: Option<String>, ssh_key: Option<PathBuf>) -> BasecampResult<()> {
+    debug!("Executing profile add command for '{}'", name);
+
+    if let Some(url) = &github_url
+        && !url.starts_with("https://")
+        && !url.starts_with("git@")
+    {
+        return Err(BasecampError::InvalidGitHubUrl(url.clone()));
+    }
+
+    // Load configuration
+    let mut config = Config::load(&PathBuf::new())?;
+
+    if config.git_config.profiles.contains_key(name) {
+        return Err(BasecampError::Generic(format!(
+            "Profile '{}' already exists",
+            name
+        )));
+    }
+
+    config.git_config.profiles.insert(
+        name.to_string(),
+        ProfileConfig {
+            github_url,
+            ssh_key,
+            update: None,
+        },
+    );
+    config.save_config()?;
+
+    UI::success(&format!(
+        "Added profile '{}'. Select it with 'basecamp profile use {}' or --profile {}.",
+        name, name, name
+    ));
+    Ok(())
+}
+
+/// Execute the profile use command
+pub fn use_profile(name: &str) -> BasecampResult<()> {
+    debug!("Executing profile use command for '{}'", name);
+
+    // Load configuration
+    let mut config = Config::load(&PathBuf::new())?;
+
+    if !config.git_config.profiles.contains_key(name) {
+        return Err(BasecampError::ProfileNotFound(name.to_string()));
+    }
+
+    config.git_config.profile = Some(name.to_string());
+    config.save_config()?;
+
+    UI::success(&format!("Now using profile '{}'", name));
+    Ok(())
+}
+
+/// Execute the profile clear command
+pub fn clear() -> BasecampResult<()> {
+    debug!("Executing profile clear command");
+
+    // Load configuration
+    let mut config = Config::load(&PathBuf::new())?;
+
+    if config.git_config.profile.take().is_none() {
+        UI::info("No profile was selected");
+        return Ok(());
+    }
+    config.save_config()?;
+
+    UI::success("Profile cleared; the top-level settings of config.yaml are used");
+    Ok(())
+}
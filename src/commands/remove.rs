@@ -1,16 +1,25 @@
-use log::{debug, info};
+use log::debug;
+use std::fs;
 use std::path::PathBuf;
+use std::time::Instant;
 
 use crate::config::Config;
-use crate::error::{BasecampError, BasecampResult};
+use crate::core::remove::{self, RemovePreview};
+use crate::error::{BasecampError, BasecampResult, LocalWork};
 use crate::git::GitRepo;
-use crate::ui::UI;
+use crate::journal::Operation;
+use crate::oplog::OperationLog;
+use crate::ui::{ParallelProgress, Summary, UI};
 
 /// Execute the remove command
+///
+/// A group without repositories removes all of its codebases. With `dry_run`,
+/// what would be removed is shown without asking or changing anything.
 pub fn execute(
     codebase: String,
     repositories: Vec<String>,
     force: bool,
+    dry_run: bool,
 ) -> BasecampResult<()> {
     debug!(
         "Executing remove command for codebase '{}' with repos: {:?}",
@@ -19,210 +28,269 @@ pub fn execute(
 
     // Load configuration
     let mut config = Config::load(&PathBuf::new())?;
+    let group = repositories.is_empty() && config.is_group(&config.resolve_codebase(&codebase));
 
-    // Check if GitHub URL is configured
-    if !config.has_github_url() {
-        return Err(BasecampError::GitHubUrlNotConfigured);
+    if dry_run && group {
+        return show_group_preview(&config, &codebase, force);
+    }
+    if dry_run {
+        return show_preview(&config, &codebase, &repositories, force);
     }
 
-    // If no repositories specified, remove the entire codebase
-    if repositories.is_empty() {
-        return remove_codebase(&mut config, &codebase, force);
+    // Remotes aren't contacted either way, but their state may be old
+    if GitRepo::is_offline() && !force {
+        UI::detail("Offline mode: unpushed commits are checked against the last fetched state of each remote");
     }
 
-    // Otherwise, remove specific repositories
-    remove_repositories(&mut config, &codebase, &repositories, force)
+    // Every deletion is recorded in .basecamp/logs/
+    let log = OperationLog::new(&config.workspace, "remove");
+    let result = if group {
+        confirm_and_remove_group(&mut config, &codebase, force, &log)
+    } else {
+        confirm_and_remove(&mut config, &codebase, &repositories, force, &log)
+    };
+
+    log.finish(&result);
+    result
 }
 
-/// Remove an entire codebase
-fn remove_codebase(
-    config: &mut Config,
-    codebase: &str,
-    force: bool,
-) -> BasecampResult<()> {
-    info!("Removing entire codebase: {}", codebase);
+/// Print the configuration entries, directories and local work a removal would affect
+fn show_preview(config: &Config, codebase: &str, repositories: &[String], force: bool) -> BasecampResult<()> {
+    let preview = remove::preview(config, codebase, repositories, &ParallelProgress::new())?;
+    print_preview(&preview);
+    print_local_work(&preview.local_work, force);
+    Ok(())
+}
 
-    // Get repositories in the codebase
-    let repos = match config.get_repositories(codebase) {
-        Ok(r) => r.clone(),
-        Err(e) => return Err(e),
-    };
+/// Print what removing every codebase of a group would affect
+fn show_group_preview(config: &Config, group: &str, force: bool) -> BasecampResult<()> {
+    let previews = group_previews(config, group)?;
+    for preview in &previews {
+        print_preview(preview);
+    }
+    let local_work: Vec<_> = previews.into_iter().flat_map(|preview| preview.local_work).collect();
+    print_local_work(&local_work, force);
+    Ok(())
+}
 
-    // Check if repositories exist on disk
-    let codebase_path = PathBuf::from(codebase);
-    let codebase_exists_on_disk = codebase_path.exists();
-    
-    if codebase_exists_on_disk {
-        // Check if force is required
-        if !force {
-            for repo in &repos {
-                let repo_path = GitRepo::get_repo_path(codebase, repo);
-
-                // Check for uncommitted changes
-                if repo_path.exists() && GitRepo::has_uncommitted_changes(&repo_path)? {
-                    return Err(BasecampError::UncommittedChanges(repo_path));
-                }
+/// Plans of removing each codebase of a group, with the local work they'd lose
+fn group_previews(config: &Config, group: &str) -> BasecampResult<Vec<RemovePreview>> {
+    config
+        .select_codebases(Some(&config.resolve_codebase(group)))?
+        .iter()
+        .map(|codebase| remove::preview(config, codebase, &[], &ParallelProgress::new()))
+        .collect()
+}
 
-                // Check for unpushed commits
-                if repo_path.exists() && GitRepo::has_unpushed_commits(&repo_path)? {
-                    return Err(BasecampError::UnpushedCommits(repo_path));
-                }
-            }
-        }
+/// Print the configuration entries and directories of a planned removal
+fn print_preview(preview: &RemovePreview) {
+    let plan = &preview.plan;
 
-        // Ask for confirmation
-        let confirm = UI::confirm(
-            &format!(
-                "This will remove codebase '{}' and all of its repositories from the configuration\n\
-                 AND DELETE ALL LOCAL FILES in the '{}' directory. Continue?",
-                codebase, codebase
-            ),
-            false,
-        )?;
-
-        if !confirm {
-            UI::info("Remove cancelled.");
-            return Ok(());
-        }
+    if plan.whole_codebase() {
+        UI::info(&format!("Would remove codebase '{}' from codebases.yaml", plan.codebase));
     } else {
-        // If the codebase doesn't exist on disk, just confirm removal from config
-        let confirm = UI::confirm(
-            &format!(
-                "This will remove codebase '{}' and all of its repositories from the configuration. Continue?",
-                codebase
-            ),
-            false,
-        )?;
-
-        if !confirm {
-            UI::info("Remove cancelled.");
-            return Ok(());
+        UI::info(&format!(
+            "Would remove [{}] from codebase '{}' in codebases.yaml",
+            plan.repositories.join(", "),
+            plan.codebase
+        ));
+    }
+
+    if plan.directories.is_empty() {
+        UI::info("No local directories would be deleted");
+    } else {
+        UI::info("Would delete:");
+        for (path, size) in plan.directories.iter().zip(&preview.sizes) {
+            UI::info(&format!("  - {} ({})", path.display(), UI::format_bytes(*size)));
         }
+        UI::info(&format!("Total: {}", UI::format_bytes(preview.sizes.iter().sum())));
+    }
+}
+
+/// Print the local work a dry run found, and whether it would block the removal
+fn print_local_work(local_work: &[(PathBuf, LocalWork)], force: bool) {
+    for (path, work) in local_work {
+        UI::warning(&format!("'{}' {}", path.display(), work));
+    }
+    match (local_work.is_empty(), force) {
+        (true, _) => UI::success("No uncommitted changes or unpushed commits would be lost"),
+        (false, true) => UI::warning("--force would remove these repositories anyway, losing their local work"),
+        (false, false) => UI::info("The removal would be refused for these repositories without --force"),
     }
 
-    // Remove codebase from configuration
-    config.remove_codebase(codebase)?;
+    UI::info("Dry run: nothing was changed. Run again without --dry-run to apply.");
+}
 
-    // Save the updated configuration
-    config.save(&PathBuf::new())?;
+/// Remove every codebase of a group, after confirmation
+///
+/// All of them are checked before anything changes, and the removal is
+/// recorded as one operation for `undo`.
+fn confirm_and_remove_group(config: &mut Config, group: &str, force: bool, log: &OperationLog) -> BasecampResult<()> {
+    let group = config.resolve_codebase(group);
+    let previews = group_previews(config, &group)?;
+    let local_work: Vec<_> = previews.iter().flat_map(|preview| preview.local_work.clone()).collect();
+    if !force && !local_work.is_empty() {
+        return Err(BasecampError::LocalWork(local_work));
+    }
 
-    UI::success(&format!("Removed codebase '{}' from configuration", codebase));
+    let codebases: Vec<&str> = previews.iter().map(|preview| preview.plan.codebase.as_str()).collect();
+    let size: u64 = previews.iter().flat_map(|preview| &preview.sizes).sum();
+    let description = format!(
+        "This will remove group '{}' and its codebases ({}) from the configuration\n\
+         AND DELETE ALL LOCAL FILES in their directories ({}).",
+        group,
+        codebases.join(", "),
+        UI::format_bytes(size)
+    );
+    let confirmed = match config.confirm_delete_bytes() {
+        Some(limit) if size > limit => UI::confirm_typed(&description, &group)?,
+        _ => UI::confirm(&format!("{} Continue?", description), false)?,
+    };
+    if !confirmed {
+        UI::info("Remove cancelled.");
+        return Ok(());
+    }
 
-    // Delete local files if they exist
-    if codebase_exists_on_disk {
-        UI::info(&format!("Deleting local directory '{}'...", codebase));
-        match std::fs::remove_dir_all(&codebase_path) {
-            Ok(_) => {
-                UI::success(&format!("Successfully deleted local directory '{}'", codebase));
-                info!("Deleted local directory '{}'", codebase);
-            },
-            Err(e) => {
-                UI::warning(&format!("Failed to delete local directory '{}': {}", codebase, e));
-                info!("Failed to delete local directory '{}': {}", codebase, e);
+    // Recorded for `basecamp undo` as a single operation
+    let started = Instant::now();
+    let journal = Operation::begin(&config.workspace, "remove");
+    let summary = format!("remove group '{}'", group);
+    let mut deleted = 0;
+    let mut failed = 0;
+    for preview in &previews {
+        let plan = &preview.plan;
+        if let Err(e) = remove::forget(config, plan) {
+            journal.finish(&summary);
+            return Err(e);
+        }
+        for deletion in remove::delete(plan, config.parallel(None), log, &journal, &ParallelProgress::new()) {
+            match &deletion.error {
+                Some(error) => {
+                    UI::warning(&format!("Failed to delete {}: {}", deletion.path.display(), error));
+                    failed += 1;
+                }
+                None => deleted += 1,
             }
         }
     }
 
+    // The group's directory goes too once nothing else is left in it
+    let _ = fs::remove_dir(config.workspace.codebase_path(&group));
+
+    journal.finish(&summary);
+    UI::success(&format!("Removed group '{}' ({}) from configuration", group, codebases.join(", ")));
+    UI::detail("Run 'basecamp undo' to revert the removal");
+    Summary::new()
+        .count(codebases.len(), "codebases removed")
+        .count(deleted, "deleted")
+        .failed(failed, "failed to delete")
+        .print(started.elapsed());
     Ok(())
 }
 
-/// Remove specific repositories from a codebase
-fn remove_repositories(
+/// Remove repositories, or the entire codebase when none are given, after confirmation
+fn confirm_and_remove(
     config: &mut Config,
     codebase: &str,
     repositories: &[String],
     force: bool,
+    log: &OperationLog,
 ) -> BasecampResult<()> {
-    info!(
-        "Removing repositories {:?} from codebase '{}'",
-        repositories, codebase
-    );
-
-    // Track which repositories exist on disk
-    let mut repos_on_disk = Vec::new();
-    
-    // Check if force is required and collect repositories that exist on disk
-    if !force {
-        for repo in repositories {
-            let repo_path = GitRepo::get_repo_path(codebase, repo);
-            
-            if repo_path.exists() {
-                repos_on_disk.push((repo, repo_path.clone()));
-                
-                // Check for uncommitted changes
-                if GitRepo::has_uncommitted_changes(&repo_path)? {
-                    return Err(BasecampError::UncommittedChanges(repo_path));
-                }
+    let plan = remove::plan(config, codebase, repositories, force, &ParallelProgress::new())?;
+    let codebase = &plan.codebase;
 
-                // Check for unpushed commits
-                if GitRepo::has_unpushed_commits(&repo_path)? {
-                    return Err(BasecampError::UnpushedCommits(repo_path));
-                }
-            }
-        }
-    } else {
-        // If force is enabled, just collect repositories that exist on disk
-        for repo in repositories {
-            let repo_path = GitRepo::get_repo_path(codebase, repo);
-            if repo_path.exists() {
-                repos_on_disk.push((repo, repo_path.clone()));
-            }
-        }
-    }
-    
-    // Create confirmation message based on whether repos exist on disk
-    let confirmation_message = if !repos_on_disk.is_empty() {
-        format!(
+    let size = plan.sizes(config.parallel(None)).iter().sum();
+    let description = match (plan.whole_codebase(), plan.directories.is_empty()) {
+        (true, false) => format!(
+            "This will remove codebase '{}' and all of its repositories from the configuration\n\
+             AND DELETE ALL LOCAL FILES in the '{}' directory ({}).",
+            codebase, codebase, UI::format_bytes(size)
+        ),
+        (true, true) => format!(
+            "This will remove codebase '{}' and all of its repositories from the configuration.",
+            codebase
+        ),
+        (false, false) => format!(
             "This will remove repositories {:?} from codebase '{}'\n\
-             AND DELETE THE FOLLOWING LOCAL DIRECTORIES:\n{}\n\
-             Continue?",
-            repositories, codebase,
-            repos_on_disk.iter().map(|(_, path)| format!("  - {}", path.display())).collect::<Vec<_>>().join("\n")
-        )
-    } else {
-        format!(
-            "This will remove repositories {:?} from codebase '{}' configuration. Continue?",
+             AND DELETE THE FOLLOWING LOCAL DIRECTORIES ({}):\n{}",
+            repositories, codebase, UI::format_bytes(size),
+            plan.directories.iter().map(|path| format!("  - {}", path.display())).collect::<Vec<_>>().join("\n")
+        ),
+        (false, true) => format!(
+            "This will remove repositories {:?} from codebase '{}' configuration.",
             repositories, codebase
-        )
+        ),
     };
 
-    // Ask for confirmation
-    let confirm = UI::confirm(&confirmation_message, false)?;
-
-    if !confirm {
+    // Large deletions are confirmed by typing the codebase name
+    let confirmed = match config.confirm_delete_bytes() {
+        Some(limit) if size > limit => UI::confirm_typed(&description, codebase)?,
+        _ if plan.whole_codebase() || plan.directories.is_empty() => {
+            UI::confirm(&format!("{} Continue?", description), false)?
+        }
+        _ => UI::confirm(&format!("{}\nContinue?", description), false)?,
+    };
+    if !confirmed {
         UI::info("Remove cancelled.");
         return Ok(());
     }
 
-    // Remove repositories from codebase configuration
-    config.remove_repositories(codebase, repositories)?;
-
-    // Save the updated configuration
-    config.save(&PathBuf::new())?;
-
-    let repo_list = repositories.join(", ");
-    UI::success(&format!(
-        "Removed repositories [{}] from codebase '{}' configuration",
-        repo_list, codebase
-    ));
-    
-    // Delete local files for each repository
-    if !repos_on_disk.is_empty() {
-        UI::info("Deleting local repository directories...");
-        
-        for (repo, repo_path) in repos_on_disk {
-            match std::fs::remove_dir_all(&repo_path) {
-                Ok(_) => {
-                    UI::success(&format!("Successfully deleted local directory for '{}'", repo));
-                    info!("Deleted local directory '{}'", repo_path.display());
-                },
-                Err(e) => {
-                    UI::warning(&format!("Failed to delete local directory for '{}': {}", repo, e));
-                    info!("Failed to delete local directory '{}': {}", repo_path.display(), e);
-                }
-            }
+    // Recorded for `basecamp undo`, deleted directories go to the trash
+    let started = Instant::now();
+    let journal = Operation::begin(&config.workspace, "remove");
+    let result = remove::forget(config, &plan);
+    if result.is_err() {
+        journal.finish(&plan.summary());
+        return result;
+    }
+
+    if plan.whole_codebase() {
+        UI::success(&format!("Removed codebase '{}' from configuration", codebase));
+    } else {
+        UI::success(&format!(
+            "Removed repositories [{}] from codebase '{}' configuration",
+            repositories.join(", "),
+            codebase
+        ));
+    }
+
+    // Delete local files if they exist, each reported as it finishes
+    let mut summary = if plan.whole_codebase() {
+        Summary::new().count(1, "codebase removed")
+    } else {
+        Summary::new().count(plan.repositories.len(), "removed")
+    };
+    if !plan.directories.is_empty() {
+        let deletions = remove::delete(&plan, config.parallel(None), log, &journal, &ParallelProgress::new());
+        let failed: Vec<_> = deletions.iter().filter(|deletion| deletion.error.is_some()).collect();
+        for deletion in &failed {
+            UI::warning(&format!(
+                "Failed to delete {}: {}",
+                deletion.path.display(),
+                deletion.error.as_deref().unwrap_or_default()
+            ));
         }
+        if failed.is_empty() && plan.whole_codebase() {
+            UI::success(&format!("Successfully deleted local directory '{}'", codebase));
+        } else if failed.is_empty() {
+            UI::success(&format!("Successfully deleted {} local directories", deletions.len()));
+        }
+        summary = summary
+            .count(deletions.len() - failed.len(), "deleted")
+            .failed(failed.len(), "failed to delete");
+    }
+
+    journal.finish(&plan.summary());
+    UI::detail("Run 'basecamp undo' to revert the removal");
+
+    for (repo, shared_path) in remove::unused_shared(config, &plan) {
+        UI::info(&format!(
+            "No codebase uses the shared clone of '{}' at {} anymore; delete it once you don't need it",
+            repo,
+            shared_path.display()
+        ));
     }
 
+    summary.print(started.elapsed());
     Ok(())
 }
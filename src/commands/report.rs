@@ -0,0 +1,172 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{debug, warn};
+
+use crate::cli::DocumentFormat;
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::ui::{Glyph, UI};
+
+/// A repository as described in the report
+struct Row {
+    repo: String,
+    url: String,
+    /// Branch checked out, `None` when not cloned or detached
+    branch: Option<String>,
+    /// Unix time of the commit checked out
+    last_commit: Option<u64>,
+    status: String,
+}
+
+/// Execute the report command
+///
+/// Renders each codebase with its repositories, clone URLs, current branches,
+/// last commit dates and working tree status as Markdown or HTML, on stdout
+/// or to `output`. The format follows the extension of `output` when not given.
+pub fn execute(codebase: Option<String>, format: Option<DocumentFormat>, output: Option<&Path>) -> BasecampResult<()> {
+    debug!("Executing report command");
+
+    let config = Config::load(&PathBuf::new())?;
+    if !config.has_github_url() {
+        return Err(BasecampError::GitHubUrlNotConfigured);
+    }
+
+    let format = format.unwrap_or_else(|| {
+        match output.and_then(|path| path.extension()).and_then(|ext| ext.to_str()) {
+            Some("html" | "htm") => DocumentFormat::Html,
+            _ => DocumentFormat::Markdown,
+        }
+    });
+
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+    let mut sections = Vec::new();
+    for codebase in config.select_codebases(codebase.as_deref())? {
+        let rows = config
+            .get_repositories(&codebase)?
+            .iter()
+            .map(|repo| describe(&config, &codebase, repo))
+            .collect();
+        sections.push((codebase, rows));
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let title = format!("BaseCamp workspace report ({})", UI::format_date(now));
+    let content = match format {
+        DocumentFormat::Markdown => render_markdown(&title, &config.git_config.github_url, &sections),
+        DocumentFormat::Html => render_html(&title, &config.git_config.github_url, &sections),
+    };
+
+    match output {
+        Some(path) => {
+            fs::write(path, content)?;
+            UI::success(&format!("Wrote the workspace report to {}", path.display()));
+        }
+        None => UI::output(&content),
+    }
+    Ok(())
+}
+
+/// Read the state of a repository's clone
+fn describe(config: &Config, codebase: &str, repo: &str) -> Row {
+    let repo_path = config.workspace.repo_path(codebase, repo);
+    let mut row = Row {
+        repo: repo.to_string(),
+        url: GitRepo::build_repo_url(&config.git_config.github_url, repo),
+        branch: None,
+        last_commit: None,
+        status: "not cloned".to_string(),
+    };
+    if !repo_path.exists() {
+        return row;
+    }
+
+    match GitRepo::status(&repo_path, config.remote_name()) {
+        Ok(status) => {
+            let mut parts = vec![if status.dirty { "dirty" } else { "clean" }.to_string()];
+            if status.ahead > 0 {
+                parts.push(format!("{}{}", Glyph::Ahead, status.ahead));
+            }
+            if status.behind > 0 {
+                parts.push(format!("{}{}", Glyph::Behind, status.behind));
+            }
+            row.status = parts.join(" ");
+            row.branch = status.branch;
+        }
+        Err(e) => {
+            warn!("Failed to read status of '{}': {}", repo, e);
+            row.status = "unreadable".to_string();
+        }
+    }
+    row.last_commit = GitRepo::head_commit_time(&repo_path).ok().flatten();
+    row
+}
+
+/// Branch and last commit date cells
+fn cells(row: &Row) -> (String, String) {
+    (
+        row.branch.clone().unwrap_or_else(|| "-".to_string()),
+        row.last_commit.map(UI::format_date).unwrap_or_else(|| "-".to_string()),
+    )
+}
+
+fn render_markdown(title: &str, github_url: &str, sections: &[(String, Vec<Row>)]) -> String {
+    let mut out = format!("# {}\n\nRepositories of {}\n", title, github_url);
+    for (codebase, rows) in sections {
+        out.push_str(&format!("\n## {}\n\n", codebase));
+        out.push_str("| Repository | URL | Branch | Last commit | Status |\n");
+        out.push_str("|---|---|---|---|---|\n");
+        for row in rows {
+            let (branch, last_commit) = cells(row);
+            let escape = |cell: &str| cell.replace('|', "\\|");
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                escape(&row.repo),
+                escape(&row.url),
+                escape(&branch),
+                last_commit,
+                row.status
+            ));
+        }
+    }
+    out
+}
+
+fn render_html(title: &str, github_url: &str, sections: &[(String, Vec<Row>)]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n</head>\n<body>\n<h1>{title}</h1>\n<p>Repositories of {}</p>\n",
+        html_escape(github_url),
+        title = html_escape(title)
+    );
+    for (codebase, rows) in sections {
+        out.push_str(&format!("<h2>{}</h2>\n<table>\n", html_escape(codebase)));
+        out.push_str("<tr><th>Repository</th><th>URL</th><th>Branch</th><th>Last commit</th><th>Status</th></tr>\n");
+        for row in rows {
+            let (branch, last_commit) = cells(row);
+            out.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                html_escape(&row.repo),
+                html_escape(&row.url),
+                html_escape(&branch),
+                last_commit,
+                html_escape(&row.status)
+            ));
+        }
+        out.push_str("</table>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Escape text for HTML content and attributes
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
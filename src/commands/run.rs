@@ -0,0 +1,353 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::{debug, info};
+
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::core::ItemStatus;
+use crate::core::filter::{self, RepoFilter};
+use crate::runner::{self, Exit, OutputMode, RunOptions};
+use crate::shell;
+use crate::template;
+use crate::ui::{ParallelProgress, Summary, UI};
+
+/// A single task invocation in one repository
+#[derive(Debug, Clone)]
+struct TaskJob {
+    codebase: String,
+    repo: String,
+    command: Option<String>,
+    path: PathBuf,
+}
+
+/// Outcome of a task invocation, failures with the text to report
+#[derive(Debug, Clone)]
+enum TaskStatus {
+    Passed,
+    Failed(String),
+    TimedOut(String),
+    Skipped(String),
+}
+
+impl TaskStatus {
+    fn is_failure(&self) -> bool {
+        matches!(self, Self::Failed(_) | Self::TimedOut(_))
+    }
+}
+
+/// Options of the run command
+pub struct TaskOptions {
+    pub parallel: Option<usize>,
+    /// Only run in installed repositories passing all of these
+    pub filters: Vec<RepoFilter>,
+    /// Seconds after which a task is killed
+    pub timeout: Option<u64>,
+    /// Show the output of every repository, live or in a block each, instead of only that of failures
+    pub output: Option<OutputMode>,
+    /// Bytes of output kept per repository
+    pub max_output: usize,
+}
+
+/// Execute the run command
+pub fn execute(task: String, codebase: Option<String>, options: TaskOptions) -> BasecampResult<()> {
+    debug!("Executing run command for task '{}'", task);
+
+    // Load configuration
+    let config = Config::load(&PathBuf::new())?;
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+    let parallel_count = config.parallel(options.parallel);
+    let filters = options.filters;
+    let run_options = RunOptions {
+        timeout: options.timeout.map(Duration::from_secs),
+        mode: options.output.unwrap_or_default(),
+        max_output: options.max_output,
+    };
+
+    let task_config = config.get_task(&task)?;
+    let started = Instant::now();
+
+    // Collect the repositories the task applies to
+    let codebases = config.select_codebases(codebase.as_deref())?;
+
+    let vars = config.template_vars();
+    let mut jobs = Vec::new();
+    for codebase in &codebases {
+        for repo in config.get_repositories(codebase)? {
+            jobs.push(TaskJob {
+                codebase: codebase.clone(),
+                repo: repo.clone(),
+                command: task_config
+                    .command_for(codebase, repo)
+                    .map(|c| template::expand(c, &vars.with_repo(codebase, repo))),
+                path: config.workspace.repo_path(codebase, repo),
+            });
+        }
+    }
+
+    if jobs.is_empty() {
+        UI::info("No repositories to run the task in.");
+        return Ok(());
+    }
+
+    if !filters.is_empty() {
+        let selected = filter::select(&config, &codebases, &filters, &ParallelProgress::new())?;
+        jobs.retain(|job| selected.iter().any(|(codebase, repo)| *codebase == job.codebase && *repo == job.repo));
+
+        if jobs.is_empty() {
+            let filters: Vec<String> = filters.iter().map(ToString::to_string).collect();
+            UI::info(&format!("No installed repositories match {}", filters.join(" and ")));
+            return Ok(());
+        }
+    }
+
+    info!("Running task '{}' in {} repositories", task, jobs.len());
+    UI::info(&format!("Running task '{}' in {} repositories", task, jobs.len()));
+
+    // Order repositories by their declared dependencies
+    let repo_names: Vec<String> = jobs.iter().map(|job| job.repo.clone()).collect();
+    let levels = config.dependency_levels(&repo_names)?;
+
+    let mut results = Vec::new();
+    // Repositories that failed or were skipped, whose dependents are skipped in turn
+    let mut broken: Vec<String> = Vec::new();
+
+    for (level_idx, level) in levels.iter().enumerate() {
+        let (level_jobs, rest): (Vec<TaskJob>, Vec<TaskJob>) =
+            jobs.into_iter().partition(|job| level.contains(&job.repo));
+        jobs = rest;
+
+        // Never run repositories whose dependencies may be broken; independent ones still run
+        let mut runnable = Vec::new();
+        for job in level_jobs {
+            match config.dependencies_of(&job.repo).iter().find(|dep| broken.contains(dep)) {
+                Some(dep) => {
+                    let reason = format!("'{}' failed", dep);
+                    broken.push(job.repo.clone());
+                    results.push((job, TaskStatus::Skipped(reason), Duration::ZERO));
+                }
+                None => runnable.push(job),
+            }
+        }
+
+        if runnable.is_empty() {
+            continue;
+        }
+        if levels.len() > 1 {
+            debug!("Running dependency level {}: {:?}", level_idx + 1, level);
+        }
+
+        let level_results = run_jobs(&task, runnable, parallel_count, &run_options, options.output.is_some());
+        broken.extend(
+            level_results
+                .iter()
+                .filter(|(_, status, _)| status.is_failure())
+                .map(|(job, _, _)| job.repo.clone()),
+        );
+        results.extend(level_results);
+    }
+
+    print_summary(&results);
+
+    let failed: Vec<&(TaskJob, TaskStatus, Duration)> = results
+        .iter()
+        .filter(|(_, status, _)| status.is_failure())
+        .collect();
+
+    let count = |matches: fn(&TaskStatus) -> bool| results.iter().filter(|(_, status, _)| matches(status)).count();
+    let summary = Summary::new()
+        .count(count(|status| matches!(status, TaskStatus::Passed)), "passed")
+        .count(count(|status| matches!(status, TaskStatus::Skipped(_))), "skipped")
+        .failed(count(|status| matches!(status, TaskStatus::Failed(_))), "failed")
+        .failed(count(|status| matches!(status, TaskStatus::TimedOut(_))), "timed out");
+
+    if !failed.is_empty() {
+        UI::blank_line();
+        for (job, status, _) in &failed {
+            if let TaskStatus::Failed(report) | TaskStatus::TimedOut(report) = status {
+                UI::error(&format!("{}/{}:", job.codebase, job.repo));
+                UI::error_detail(report.trim_end());
+                UI::blank_line();
+            }
+        }
+        summary.print(started.elapsed());
+
+        return Err(BasecampError::CommandFailed(format!(
+            "Task '{}' failed in {} repositories",
+            task,
+            failed.len()
+        )));
+    }
+
+    UI::success(&format!("Task '{}' completed successfully", task));
+    summary.print(started.elapsed());
+    Ok(())
+}
+
+/// Run jobs using a pool of worker threads
+///
+/// With `show_output`, the output of every job is printed, live or once it's
+/// done depending on the mode, rather than only reported for failures.
+fn run_jobs(
+    task: &str,
+    jobs: Vec<TaskJob>,
+    parallel_count: usize,
+    options: &RunOptions,
+    show_output: bool,
+) -> Vec<(TaskJob, TaskStatus, Duration)> {
+    let total = jobs.len();
+    let parallel_count = parallel_count.clamp(1, total);
+
+    let jobs = Arc::new(jobs);
+    let remaining = Arc::new(Mutex::new((0..total).collect::<Vec<_>>()));
+    let results = Arc::new(Mutex::new(Vec::new()));
+
+    let progress = Arc::new(ParallelProgress::with_total(total, &format!("Running '{}'", task)));
+
+    let mut handles = vec![];
+
+    for _ in 0..parallel_count {
+        let jobs = Arc::clone(&jobs);
+        let remaining = Arc::clone(&remaining);
+        let results = Arc::clone(&results);
+        let progress = Arc::clone(&progress);
+        let options = options.clone();
+
+        let handle = thread::spawn(move || {
+            loop {
+                let job_idx = {
+                    let mut remaining = remaining.lock().unwrap();
+                    if remaining.is_empty() {
+                        break;
+                    }
+                    remaining.remove(0)
+                };
+
+                let job = &jobs[job_idx];
+                let label = format!("{}/{}", job.codebase, job.repo);
+
+                progress.start_item(&label, &format!("Running in '{}'...", label));
+
+                let started = Instant::now();
+                let status = run_job(job, &label, &options, show_output, &progress);
+                let elapsed = started.elapsed();
+
+                match &status {
+                    TaskStatus::Passed => {
+                        progress.finish_item(&label, ItemStatus::Succeeded, &format!("'{}' passed", label))
+                    }
+                    TaskStatus::Failed(_) => {
+                        progress.finish_item(&label, ItemStatus::Failed, &format!("'{}' failed", label))
+                    }
+                    TaskStatus::TimedOut(_) => {
+                        progress.finish_item(&label, ItemStatus::Failed, &format!("'{}' timed out", label))
+                    }
+                    TaskStatus::Skipped(reason) => progress.finish_item(
+                        &label,
+                        ItemStatus::Skipped,
+                        &format!("'{}' skipped ({})", label, reason),
+                    ),
+                }
+
+                results
+                    .lock()
+                    .unwrap()
+                    .push((job_idx, job.clone(), status, elapsed));
+            }
+        });
+
+        handles.push(handle);
+    }
+
+    for handle in handles {
+        let _ = handle.join();
+    }
+
+    progress.finish(&format!("Finished running '{}'", task));
+
+    // Report results in configuration order rather than completion order
+    let mut results = std::mem::take(&mut *results.lock().unwrap());
+    results.sort_by_key(|(idx, _, _, _)| *idx);
+    results
+        .into_iter()
+        .map(|(_, job, status, elapsed)| (job, status, elapsed))
+        .collect()
+}
+
+/// Run a single job
+///
+/// Its output is printed as it comes in stream mode, or as a block once it's
+/// done with `show_output`; otherwise it's kept for the report of a failure.
+fn run_job(
+    job: &TaskJob,
+    label: &str,
+    options: &RunOptions,
+    show_output: bool,
+    progress: &ParallelProgress,
+) -> TaskStatus {
+    let command = match &job.command {
+        Some(command) => command,
+        None => return TaskStatus::Skipped("no command defined".to_string()),
+    };
+
+    if !job.path.exists() {
+        return TaskStatus::Skipped("not installed".to_string());
+    }
+
+    debug!("Running '{}' in {:?}", command, job.path);
+
+    let mut process = shell::command(command);
+    process.current_dir(&job.path);
+    let result = runner::run(process, options, &|line| {
+        progress.suspend(|| UI::output(&format!("{} | {}\n", label, line)))
+    });
+
+    let mut output = result.output.clone();
+    if result.truncated {
+        output.push_str(&format!("[output cut after {} bytes]\n", options.max_output));
+    }
+    if show_output && options.mode == OutputMode::Capture && !output.is_empty() {
+        progress.suspend(|| {
+            UI::info(&format!("{}:", label));
+            UI::output(&output);
+        });
+    }
+
+    let Some(reason) = result.reason() else {
+        return TaskStatus::Passed;
+    };
+    let mut report = if show_output { String::new() } else { output };
+    report.push_str(&format!("'{}' {}", command, reason));
+    match result.exit {
+        Exit::TimedOut(_) => TaskStatus::TimedOut(report),
+        _ => TaskStatus::Failed(report),
+    }
+}
+
+/// Print a pass/fail table for all jobs
+fn print_summary(results: &[(TaskJob, TaskStatus, Duration)]) {
+    let mut table = UI::create_table(vec!["Codebase", "Repository", "Result", "Duration"]);
+
+    for (job, status, elapsed) in results {
+        let result = match status {
+            TaskStatus::Passed => "passed".to_string(),
+            TaskStatus::Failed(_) => "FAILED".to_string(),
+            TaskStatus::TimedOut(_) => "TIMED OUT".to_string(),
+            TaskStatus::Skipped(reason) => format!("skipped ({})", reason),
+        };
+
+        UI::add_table_row(
+            &mut table,
+            vec![
+                job.codebase.clone(),
+                job.repo.clone(),
+                result,
+                format!("{:.1}s", elapsed.as_secs_f64()),
+            ],
+        );
+    }
+
+    UI::print_table(&table);
+}
@@ -0,0 +1,370 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use log::{debug, info, warn};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use crate::config::Config;
+use crate::core::events::{self, Event};
+use crate::core::install::{self, ConflictPolicy, InstallSettings};
+use crate::core::{NoProgress, add, list, remove, status};
+use crate::error::{BasecampError, BasecampResult};
+use crate::oplog::OperationLog;
+use crate::ui::Reporter;
+
+/// Invalid JSON was received
+const PARSE_ERROR: i64 = -32700;
+/// The JSON sent is not a valid request object
+const INVALID_REQUEST: i64 = -32600;
+/// The method does not exist
+const METHOD_NOT_FOUND: i64 = -32601;
+/// Invalid method parameters
+const INVALID_PARAMS: i64 = -32602;
+/// The operation failed (the message is the error BaseCamp would print)
+const OPERATION_FAILED: i64 = -32000;
+
+/// Where responses and event notifications of a connection are written
+type Writer = Arc<Mutex<dyn Write + Send>>;
+
+/// Requests of all connections run one at a time, as they share the workspace
+static OPERATION: Mutex<()> = Mutex::new(());
+
+/// Set by a `shutdown` request; the server stops once it's answered
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+
+/// How often the socket server checks for a shutdown between connections
+#[cfg(unix)]
+const ACCEPT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Error returned to the client
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<BasecampError> for RpcError {
+    fn from(err: BasecampError) -> Self {
+        Self::new(OPERATION_FAILED, err.to_string())
+    }
+}
+
+/// Parameters of methods that take none
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NoParams {}
+
+/// Parameters selecting a codebase, a group or the whole workspace
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SelectParams {
+    codebase: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StatusParams {
+    codebase: Option<String>,
+    no_cache: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct InstallParams {
+    codebase: Option<String>,
+    parallel: Option<usize>,
+    fail_fast: bool,
+    update: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AddParams {
+    codebase: String,
+    repositories: Vec<String>,
+    #[serde(default)]
+    keep_failed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RemoveParams {
+    codebase: String,
+    #[serde(default)]
+    repositories: Vec<String>,
+    #[serde(default)]
+    force: bool,
+}
+
+/// Execute the serve command
+///
+/// Reads newline-delimited JSON-RPC 2.0 requests on stdin (or on each
+/// connection to a unix socket) and answers on the same stream. Operation
+/// events are sent as `event` notifications while requests run.
+pub fn execute(socket: Option<PathBuf>) -> BasecampResult<()> {
+    debug!("Executing serve command");
+
+    // Stdout carries the protocol, anything else the commands print goes to stderr
+    Reporter::global().set_machine_output(true);
+
+    match socket {
+        Some(path) => listen(&path),
+        None => {
+            let writer: Writer = Arc::new(Mutex::new(io::stdout()));
+            serve_connection(io::stdin().lock(), writer);
+            Ok(())
+        }
+    }
+}
+
+/// Accept connections on a unix socket, serving each on its own thread
+#[cfg(unix)]
+fn listen(path: &Path) -> BasecampResult<()> {
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::thread;
+
+    // A socket left behind by a server that is gone is replaced
+    if path.exists() && UnixStream::connect(path).is_err() {
+        std::fs::remove_file(path)?;
+    }
+
+    let listener = UnixListener::bind(path)?;
+    let _socket = SocketFile(path.to_path_buf());
+    listener.set_nonblocking(true)?;
+    info!("Listening on {}", path.display());
+
+    while !SHUTDOWN.load(Ordering::Relaxed) {
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(ACCEPT_INTERVAL);
+                continue;
+            }
+            Err(e) => {
+                warn!("Failed to accept connection: {}", e);
+                continue;
+            }
+        };
+        if let Err(e) = stream.set_nonblocking(false) {
+            warn!("Failed to set up connection: {}", e);
+            continue;
+        }
+
+        thread::spawn(move || {
+            let writer: Writer = match stream.try_clone() {
+                Ok(clone) => Arc::new(Mutex::new(clone)),
+                Err(e) => {
+                    warn!("Failed to set up connection: {}", e);
+                    return;
+                }
+            };
+            serve_connection(BufReader::new(stream), writer);
+        });
+    }
+
+    info!("Stopped listening on {}", path.display());
+    Ok(())
+}
+
+/// Socket file of the server, removed when it stops
+#[cfg(unix)]
+struct SocketFile(PathBuf);
+
+#[cfg(unix)]
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.0) {
+            warn!("Failed to remove socket {}: {}", self.0.display(), e);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn listen(_path: &Path) -> BasecampResult<()> {
+    Err(BasecampError::Generic(
+        "--socket is only supported on Unix; use stdio instead".to_string(),
+    ))
+}
+
+/// Answer the requests of one client until it closes the stream
+fn serve_connection(reader: impl BufRead, writer: Writer) {
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Failed to read request: {}", e);
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(response) = handle_message(&line, &writer) {
+            send(&writer, &response);
+        }
+        if SHUTDOWN.load(Ordering::Relaxed) {
+            break;
+        }
+    }
+}
+
+/// Write one message on its own line
+fn send(writer: &Writer, message: &Value) {
+    let mut writer = writer.lock().unwrap();
+    if let Err(e) = writeln!(writer, "{}", message).and_then(|_| writer.flush()) {
+        debug!("Failed to send message: {}", e);
+    }
+}
+
+/// Handle a request line, returning the response (none for notifications)
+///
+/// Events of the request's own run are sent to `writer` while it runs.
+fn handle_message(line: &str, writer: &Writer) -> Option<Value> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, e.to_string()))),
+    };
+
+    let id = request.get("id").cloned();
+    let method = request.get("method").and_then(Value::as_str);
+    let (Some(method), Some("2.0")) = (method, request.get("jsonrpc").and_then(Value::as_str)) else {
+        return Some(error_response(
+            id.unwrap_or(Value::Null),
+            RpcError::new(INVALID_REQUEST, "expected a JSON-RPC 2.0 request with a method"),
+        ));
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    debug!("Handling '{}' request", method);
+    let result = {
+        let _operation = OPERATION.lock().unwrap_or_else(|e| e.into_inner());
+        let run = events::run();
+        let events_writer = Arc::clone(writer);
+        let _subscription = events::subscribe_run(run.id(), move |event: &Event| {
+            send(&events_writer, &json!({ "jsonrpc": "2.0", "method": "event", "params": event }));
+        });
+        dispatch(method, params)
+    };
+
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(err) => error_response(id, err),
+    })
+}
+
+fn error_response(id: Value, err: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": err.code, "message": err.message },
+    })
+}
+
+/// Run a method with its parameters
+fn dispatch(method: &str, params: Value) -> Result<Value, RpcError> {
+    match method {
+        "list" => {
+            let params: SelectParams = parse_params(params)?;
+            let config = Config::load(&PathBuf::new())?;
+            to_value(list::list(&config, params.codebase.as_deref())?)
+        }
+        "status" => {
+            let params: StatusParams = parse_params(params)?;
+            let config = Config::load(&PathBuf::new())?;
+            let codebase = params.codebase.map(|name| config.resolve_codebase(&name));
+            let codebases = config.select_codebases(codebase.as_deref())?;
+            let ttl = if params.no_cache { 0 } else { config.git_config.cache.ttl };
+            to_value(status::status(&config, &codebases, ttl, &NoProgress)?)
+        }
+        "install" => {
+            let params: InstallParams = parse_params(params)?;
+            let config = Config::load(&PathBuf::new())?;
+            let jobs: Vec<(String, String)> = list::list(&config, params.codebase.as_deref())?
+                .into_iter()
+                .flat_map(|codebase| {
+                    let name = codebase.name;
+                    codebase
+                        .repositories
+                        .into_iter()
+                        .map(move |repo| (name.clone(), repo.name))
+                })
+                .collect();
+            let settings = InstallSettings {
+                parallel: config.parallel(params.parallel),
+                fail_fast: params.fail_fast,
+                update: params.update.then_some(config.git_config.update),
+                conflicts: ConflictPolicy::default(),
+            };
+
+            let log = OperationLog::new(&config.workspace, "install");
+            let report = install::install(&config, &jobs, settings, &log, &NoProgress);
+            log.finish(&report.result());
+            to_value(report)
+        }
+        "add" => {
+            let params: AddParams = parse_params(params)?;
+            if params.repositories.is_empty() {
+                return Err(RpcError::new(INVALID_PARAMS, "no repositories specified"));
+            }
+            let mut config = Config::load(&PathBuf::new())?;
+
+            let log = OperationLog::new(&config.workspace, "add");
+            let result = add::add(
+                &mut config,
+                &params.codebase,
+                &params.repositories,
+                params.keep_failed,
+                &log,
+                &NoProgress,
+            );
+            log.finish(&result);
+            to_value(result?)
+        }
+        "remove" => {
+            let params: RemoveParams = parse_params(params)?;
+            let mut config = Config::load(&PathBuf::new())?;
+
+            let log = OperationLog::new(&config.workspace, "remove");
+            let result = remove::remove(
+                &mut config,
+                &params.codebase,
+                &params.repositories,
+                params.force,
+                &log,
+                &NoProgress,
+            );
+            log.finish(&result);
+            to_value(result?)
+        }
+        "shutdown" => {
+            parse_params::<NoParams>(params)?;
+            SHUTDOWN.store(true, Ordering::Relaxed);
+            Ok(Value::Null)
+        }
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("unknown method '{}'", method))),
+    }
+}
+
+/// Read method parameters, where none are the same as `{}`
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+fn to_value(result: impl serde::Serialize) -> Result<Value, RpcError> {
+    serde_json::to_value(result).map_err(|e| RpcError::new(OPERATION_FAILED, e.to_string()))
+}
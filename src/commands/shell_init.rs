@@ -0,0 +1,39 @@
+use log::debug;
+
+use crate::cli::Shell;
+use crate::error::BasecampResult;
+use crate::ui::UI;
+
+const POSIX_INIT: &str = r#"# BaseCamp shell integration
+bcd() {
+    local dir
+    dir="$(basecamp path "$@")" && cd "$dir"
+}
+"#;
+
+const FISH_INIT: &str = r#"# BaseCamp shell integration
+function bcd
+    set -l dir (basecamp path $argv); and cd $dir
+end
+"#;
+
+const POWERSHELL_INIT: &str = r#"# BaseCamp shell integration
+function bcd {
+    $dir = basecamp path @args
+    if ($LASTEXITCODE -eq 0) { Set-Location $dir }
+}
+"#;
+
+/// Execute the shell-init command
+pub fn execute(shell: Shell) -> BasecampResult<()> {
+    debug!("Executing shell-init command for {:?}", shell);
+
+    let script = match shell {
+        Shell::Bash | Shell::Zsh => POSIX_INIT,
+        Shell::Fish => FISH_INIT,
+        Shell::Powershell => POWERSHELL_INIT,
+    };
+
+    UI::output(script);
+    Ok(())
+}
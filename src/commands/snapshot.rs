@@ -0,0 +1,273 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::state;
+use crate::ui::UI;
+use crate::validate;
+use crate::workspace::Workspace;
+
+/// File describing a snapshot, next to its patches
+const SNAPSHOT_FILE: &str = "snapshot.json";
+
+/// State of the workspace's repositories at one point in time
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    /// Unix time the snapshot was taken
+    created_at: u64,
+    repositories: Vec<RepoSnapshot>,
+}
+
+/// Checked out state of one repository
+#[derive(Debug, Serialize, Deserialize)]
+struct RepoSnapshot {
+    codebase: String,
+    repo: String,
+    /// Branch checked out, `None` when HEAD was detached
+    branch: Option<String>,
+    commit: String,
+    /// Patch of the local changes, relative to the snapshot directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    patch: Option<PathBuf>,
+}
+
+/// Execute the snapshot create command
+///
+/// Records the branch, commit and local changes (as a patch, untracked files
+/// included) of every cloned repository of a codebase, or of every codebase,
+/// under `.basecamp/snapshots/<name>/`.
+pub fn create(name: &str, codebase: Option<String>) -> BasecampResult<()> {
+    debug!("Executing snapshot create command for '{}'", name);
+
+    let config = Config::load(&PathBuf::new())?;
+    let dir = snapshot_dir(&config.workspace, name)?;
+    if dir.exists() {
+        return Err(BasecampError::Generic(format!(
+            "Snapshot '{}' already exists; delete it with 'basecamp snapshot delete {}'",
+            name, name
+        )));
+    }
+
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+    let mut repositories = Vec::new();
+    let mut patches = Vec::new();
+    for codebase in config.select_codebases(codebase.as_deref())? {
+        for repo in config.get_repositories(&codebase)? {
+            let repo_path = config.workspace.repo_path(&codebase, repo);
+            if !repo_path.exists() {
+                continue;
+            }
+
+            let recorded = GitRepo::checkout_state(&repo_path)
+                .and_then(|checkout| Ok((checkout, GitRepo::working_tree_patch(&repo_path)?)));
+            let ((branch, commit), patch) = match recorded {
+                Ok(recorded) => recorded,
+                Err(e) => {
+                    warn!("Failed to record {}/{}: {}", codebase, repo, e);
+                    UI::warning(&format!("Skipping '{}/{}': {}", codebase, repo, e));
+                    continue;
+                }
+            };
+            let patch_path = patch.map(|patch| {
+                let path = Path::new(&codebase).join(format!("{}.patch", repo));
+                patches.push((path.clone(), patch));
+                path
+            });
+            repositories.push(RepoSnapshot {
+                codebase: codebase.clone(),
+                repo: repo.clone(),
+                branch,
+                commit,
+                patch: patch_path,
+            });
+        }
+    }
+    if repositories.is_empty() {
+        return Err(BasecampError::Generic("No cloned repositories to snapshot".to_string()));
+    }
+
+    for (path, patch) in &patches {
+        let path = dir.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, patch)?;
+    }
+    let snapshot = Snapshot {
+        created_at: state::now(),
+        repositories,
+    };
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(SNAPSHOT_FILE), serde_json::to_string_pretty(&snapshot)?)?;
+
+    UI::success(&format!(
+        "Saved snapshot '{}' of {} repositories ({} with local changes)",
+        name,
+        snapshot.repositories.len(),
+        patches.len()
+    ));
+    Ok(())
+}
+
+/// Execute the snapshot restore command
+///
+/// Checks out the recorded branch and commit of every repository in the
+/// snapshot and re-applies its local changes, unstaged. Repositories with
+/// local changes or later commits on the branch are refused unless `force`
+/// discards them.
+pub fn restore(name: &str, force: bool) -> BasecampResult<()> {
+    debug!("Executing snapshot restore command for '{}'", name);
+
+    let config = Config::load(&PathBuf::new())?;
+    let dir = snapshot_dir(&config.workspace, name)?;
+    let snapshot = load(&dir, name)?;
+
+    let mut restored = 0;
+    let mut failed = Vec::new();
+    for entry in &snapshot.repositories {
+        let label = format!("{}/{}", entry.codebase, entry.repo);
+        let repo_path = config.workspace.repo_path(&entry.codebase, &entry.repo);
+        if !repo_path.exists() {
+            UI::warning(&format!("Skipping '{}': not cloned", label));
+            continue;
+        }
+
+        let result = entry
+            .patch
+            .as_ref()
+            .map(|patch| fs::read(dir.join(patch)))
+            .transpose()
+            .map_err(BasecampError::from)
+            .and_then(|patch| {
+                GitRepo::restore_checkout(&repo_path, entry.branch.as_deref(), &entry.commit, patch.as_deref(), force)
+            });
+        match result {
+            Ok(()) => {
+                restored += 1;
+                UI::detail(&format!(
+                    "{}: {} at {}{}",
+                    label,
+                    entry.branch.as_deref().unwrap_or("(detached)"),
+                    &entry.commit[..entry.commit.len().min(8)],
+                    if entry.patch.is_some() { " with local changes" } else { "" }
+                ));
+            }
+            Err(e) => {
+                warn!("Failed to restore {}: {}", label, e);
+                UI::error(&format!("{}: {}", label, e));
+                failed.push(label);
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(BasecampError::CommandFailed(format!(
+            "{} repositories couldn't be restored: {}{}",
+            failed.len(),
+            failed.join(", "),
+            if force { "" } else { ". Pass --force to discard their local changes and later commits." }
+        )));
+    }
+    UI::success(&format!("Restored {} repositories from snapshot '{}'", restored, name));
+    Ok(())
+}
+
+/// Execute the snapshot list command
+pub fn list() -> BasecampResult<()> {
+    debug!("Executing snapshot list command");
+
+    let config = Config::load(&PathBuf::new())?;
+    let root = config.workspace.basecamp_dir().join("snapshots");
+    let mut names: Vec<String> = match fs::read_dir(&root) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().join(SNAPSHOT_FILE).exists())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    if names.is_empty() {
+        UI::info("No snapshots yet. Take one with 'basecamp snapshot create <name>'.");
+        return Ok(());
+    }
+    names.sort();
+
+    let mut table = UI::create_table(vec!["Snapshot", "Created", "Repositories", "With local changes"]);
+    for name in &names {
+        let snapshot = load(&root.join(name), name)?;
+        let dirty = snapshot.repositories.iter().filter(|entry| entry.patch.is_some()).count();
+        UI::add_table_row(
+            &mut table,
+            vec![
+                name.clone(),
+                UI::format_date(snapshot.created_at),
+                snapshot.repositories.len().to_string(),
+                dirty.to_string(),
+            ],
+        );
+    }
+    UI::print_table(&table);
+    Ok(())
+}
+
+/// Execute the snapshot delete command
+pub fn delete(name: &str) -> BasecampResult<()> {
+    debug!("Executing snapshot delete command for '{}'", name);
+
+    let config = Config::load(&PathBuf::new())?;
+    let dir = snapshot_dir(&config.workspace, name)?;
+    load(&dir, name)?;
+    fs::remove_dir_all(&dir)?;
+    UI::success(&format!("Deleted snapshot '{}'", name));
+    Ok(())
+}
+
+/// Directory of a snapshot, refusing names that would lead out of the snapshots directory
+fn snapshot_dir(workspace: &Workspace, name: &str) -> BasecampResult<PathBuf> {
+    if !validate::is_valid_repo_name(name) {
+        return Err(BasecampError::Generic(format!(
+            "Invalid snapshot name '{}': use letters, digits, '-', '_' and '.'",
+            name
+        )));
+    }
+    Ok(workspace.basecamp_dir().join("snapshots").join(name))
+}
+
+/// Read a snapshot's description
+///
+/// The file may have been edited or come from elsewhere, so the paths it
+/// names must stay inside the workspace and the snapshot directory.
+fn load(dir: &Path, name: &str) -> BasecampResult<Snapshot> {
+    let path = dir.join(SNAPSHOT_FILE);
+    if !path.exists() {
+        return Err(BasecampError::Generic(format!(
+            "No snapshot named '{}'; see 'basecamp snapshot list'",
+            name
+        )));
+    }
+    let snapshot: Snapshot = serde_json::from_str(&fs::read_to_string(&path)?)?;
+
+    let inside = |patch: &Path| {
+        patch.components().next().is_some() && patch.components().all(|part| matches!(part, Component::Normal(_)))
+    };
+    for entry in &snapshot.repositories {
+        if !validate::is_valid_codebase_name(&entry.codebase)
+            || !validate::is_valid_repo_name(&entry.repo)
+            || entry.patch.as_deref().is_some_and(|patch| !inside(patch))
+        {
+            return Err(BasecampError::Generic(format!(
+                "Snapshot '{}' names a path outside the workspace ({}/{}); see {}",
+                name,
+                entry.codebase,
+                entry.repo,
+                path.display()
+            )));
+        }
+    }
+    Ok(snapshot)
+}
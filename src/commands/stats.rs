@@ -0,0 +1,234 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{debug, warn};
+use prettytable::Table;
+use serde::Serialize;
+
+use crate::cli::ReportFormat;
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::ui::UI;
+
+/// Activity of a repository, or of a codebase
+#[derive(Debug, Default, Serialize)]
+struct Activity {
+    codebase: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    repo: Option<String>,
+    commits: usize,
+    authors: usize,
+    insertions: usize,
+    deletions: usize,
+    #[serde(skip)]
+    author_set: BTreeSet<String>,
+}
+
+impl Activity {
+    fn add(&mut self, other: &Activity) {
+        self.commits += other.commits;
+        self.insertions += other.insertions;
+        self.deletions += other.deletions;
+        self.author_set.extend(other.author_set.iter().cloned());
+        self.authors = self.author_set.len();
+    }
+}
+
+/// Summary printed with `--report json`
+#[derive(Serialize)]
+struct StatsReport<'a> {
+    /// Unix time commits are counted from
+    since: u64,
+    repositories: &'a [Activity],
+    codebases: &'a [Activity],
+    total: &'a Activity,
+}
+
+/// Execute the stats command
+///
+/// Walks the history of every cloned repository back to `since` and sums up
+/// commits, distinct authors and changed lines per repository, per codebase
+/// and overall.
+pub fn execute(
+    codebase: Option<String>,
+    since: &str,
+    parallel: Option<usize>,
+    report: Option<ReportFormat>,
+) -> BasecampResult<()> {
+    debug!("Executing stats command since {}", since);
+
+    let config = Config::load(&PathBuf::new())?;
+    let since = parse_since(since)?;
+
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+    let mut jobs = Vec::new();
+    let mut missing = 0;
+    for codebase in config.select_codebases(codebase.as_deref())? {
+        for repo in config.get_repositories(&codebase)? {
+            if config.workspace.repo_path(&codebase, repo).exists() {
+                jobs.push((codebase.clone(), repo.clone()));
+            } else {
+                missing += 1;
+            }
+        }
+    }
+    jobs.sort();
+
+    // Repositories are walked in parallel, results come back in job order
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<Activity>>> = Mutex::new((0..jobs.len()).map(|_| None).collect());
+    thread::scope(|scope| {
+        for _ in 0..config.parallel(parallel).min(jobs.len()) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some((codebase, repo)) = jobs.get(index) else {
+                        break;
+                    };
+                    let activity = repo_activity(&config, codebase, repo, since);
+                    results.lock().unwrap()[index] = activity;
+                }
+            });
+        }
+    });
+    let repositories: Vec<Activity> = results.into_inner().unwrap().into_iter().flatten().collect();
+
+    let mut codebases: BTreeMap<&str, Activity> = BTreeMap::new();
+    let mut total = Activity::default();
+    for activity in &repositories {
+        codebases
+            .entry(&activity.codebase)
+            .or_insert_with(|| Activity {
+                codebase: activity.codebase.clone(),
+                ..Activity::default()
+            })
+            .add(activity);
+        total.add(activity);
+    }
+    let codebases: Vec<Activity> = codebases.into_values().collect();
+
+    if let Some(ReportFormat::Json) = report {
+        let report = StatsReport {
+            since,
+            repositories: &repositories,
+            codebases: &codebases,
+            total: &total,
+        };
+        UI::output(&format!("{}\n", serde_json::to_string_pretty(&report)?));
+        return Ok(());
+    }
+
+    UI::info(&format!("Activity since {}", UI::format_date(since)));
+    let mut table = UI::create_table(vec!["Codebase", "Repository", "Commits", "Authors", "Added", "Removed"]);
+    for activity in &repositories {
+        let repo = activity.repo.clone().unwrap_or_default();
+        add_row(&mut table, activity.codebase.clone(), repo, activity);
+    }
+    UI::print_table(&table);
+
+    if codebases.len() > 1 {
+        let mut table = UI::create_table(vec!["Codebase", "Repositories", "Commits", "Authors", "Added", "Removed"]);
+        for activity in &codebases {
+            let count = repositories.iter().filter(|r| r.codebase == activity.codebase).count();
+            add_row(&mut table, activity.codebase.clone(), count.to_string(), activity);
+        }
+        add_row(&mut table, "Total".to_string(), repositories.len().to_string(), &total);
+        UI::print_table(&table);
+    }
+
+    if missing > 0 {
+        UI::detail(&format!("{} repositories aren't cloned and were left out", missing));
+    }
+    Ok(())
+}
+
+/// Count the commits of a repository since a Unix time
+fn repo_activity(config: &Config, codebase: &str, repo: &str, since: u64) -> Option<Activity> {
+    let repo_path = config.workspace.repo_path(codebase, repo);
+    let commits = match GitRepo::commits_since(&repo_path, since) {
+        Ok(commits) => commits,
+        Err(e) => {
+            warn!("Failed to read the history of '{}': {}", repo, e);
+            UI::warning(&format!("Skipping '{}': {}", repo, e));
+            return None;
+        }
+    };
+
+    let author_set: BTreeSet<String> = commits.iter().map(|commit| commit.author.clone()).collect();
+    Some(Activity {
+        codebase: codebase.to_string(),
+        repo: Some(repo.to_string()),
+        commits: commits.len(),
+        authors: author_set.len(),
+        insertions: commits.iter().map(|commit| commit.insertions).sum(),
+        deletions: commits.iter().map(|commit| commit.deletions).sum(),
+        author_set,
+    })
+}
+
+/// Add a row of counts after two label cells
+fn add_row(table: &mut Table, first: String, second: String, activity: &Activity) {
+    UI::add_table_row(
+        table,
+        vec![
+            first,
+            second,
+            activity.commits.to_string(),
+            activity.authors.to_string(),
+            format!("+{}", activity.insertions),
+            format!("-{}", activity.deletions),
+        ],
+    );
+}
+
+/// Parse `--since`: a duration back from now (`12h`, `30d`, `2w`) or a date (`2024-03-01`)
+fn parse_since(since: &str) -> BasecampResult<u64> {
+    let invalid = || {
+        BasecampError::Generic(format!(
+            "Invalid --since '{}': use a duration such as 12h, 30d or 2w, or a date such as 2024-03-01",
+            since
+        ))
+    };
+
+    if let Some((year, rest)) = since.split_once('-') {
+        let (month, day) = rest.split_once('-').ok_or_else(invalid)?;
+        let (year, month, day): (i64, i64, i64) = (
+            year.parse().map_err(|_| invalid())?,
+            month.parse().map_err(|_| invalid())?,
+            day.parse().map_err(|_| invalid())?,
+        );
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return Err(invalid());
+        }
+        return u64::try_from(days_from_civil(year, month, day) * 86400).map_err(|_| invalid());
+    }
+
+    let unit = since.chars().last().ok_or_else(invalid)?;
+    let amount: u64 = since[..since.len() - unit.len_utf8()].parse().map_err(|_| invalid())?;
+    let seconds = match unit {
+        'h' => 3600,
+        'd' => 86400,
+        'w' => 7 * 86400,
+        _ => return Err(invalid()),
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    Ok(now.saturating_sub(amount.saturating_mul(seconds)))
+}
+
+/// Days since 1970-01-01 of a civil date, after Howard Hinnant's `days_from_civil`
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
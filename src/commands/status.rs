@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use indicatif::ProgressBar;
+use log::{debug, info, warn};
+
+use crate::commands::fetch;
+use crate::config::Config;
+use crate::core::signing::SigningCheck;
+use crate::core::{Notice, ProgressSink, status};
+use crate::error::BasecampResult;
+use crate::git::GitRepo;
+use crate::git::provider::RemoteHealth;
+use crate::state;
+use crate::ui::{Glyph, ParallelProgress, UI};
+use crate::workspace::Workspace;
+
+/// How long `status --fetch` waits for remotes before showing what it has
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Options of the status command
+pub struct StatusOptions {
+    pub no_cache: bool,
+    pub fetch: bool,
+    pub parallel: Option<usize>,
+    /// Ask the host for the CI state and open pull requests of each repository
+    pub remote: bool,
+}
+
+/// Execute the status command
+///
+/// Reads repository state from the `.basecamp/state.json` cache maintained by
+/// `watch`, `fetch` and `install`, recomputing (and caching) local status for
+/// entries older than the configured TTL. With `fetch`, remotes are fetched
+/// first; otherwise stale entries may be refetched in the background when
+/// `cache.prefetch` is set. Offline mode skips both. With `signing` set,
+/// clones whose commits wouldn't be signed are reported.
+pub fn execute(codebase: Option<String>, options: StatusOptions) -> BasecampResult<()> {
+    debug!("Executing status command");
+
+    // Load configuration
+    let config = Config::load(&PathBuf::new())?;
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+
+    let codebases = config.select_codebases(codebase.as_deref())?;
+
+    if codebases.is_empty() {
+        UI::info("No codebases configured yet. Use 'basecamp add <codebase> <repo>' to add one.");
+        return Ok(());
+    }
+
+    let offline = GitRepo::is_offline();
+    if options.fetch && offline {
+        UI::warning("Offline mode: not fetching, ahead/behind counts are as of the last fetch");
+    } else if options.fetch {
+        fetch::refresh(
+            &config,
+            codebase.as_deref(),
+            config.parallel(options.parallel),
+            Some(FETCH_TIMEOUT),
+        )?;
+    }
+
+    let ttl = if options.no_cache { 0 } else { config.git_config.cache.ttl };
+    let entries = status::status(&config, &codebases, ttl, &ParallelProgress::new())?;
+
+    let remote = if options.remote {
+        let repos: Vec<String> = entries.iter().map(|entry| entry.repo.clone()).collect();
+        remote_health(&config, &repos, options.no_cache)
+    } else {
+        None
+    };
+
+    let pinned = entries.iter().any(|entry| entry.rev.is_some());
+    let mut headers = vec!["Codebase", "Repository", "Branch", "Ahead/Behind", "Changes", "Last fetch"];
+    if pinned {
+        headers.push("Pin");
+    }
+    if remote.is_some() {
+        headers.extend(["CI", "PRs"]);
+    }
+    let mut table = UI::create_table(headers);
+
+    for entry in &entries {
+        let mut row = match &entry.state {
+            Some(repo_state) => vec![
+                entry.codebase.clone(),
+                entry.repo.clone(),
+                repo_state.branch.clone().unwrap_or_else(|| "(detached)".to_string()),
+                format!("{}{} {}{}", Glyph::Ahead, repo_state.ahead, Glyph::Behind, repo_state.behind),
+                if repo_state.dirty { "dirty" } else { "clean" }.to_string(),
+                UI::format_age(repo_state.last_fetch),
+            ],
+            None => vec![
+                entry.codebase.clone(),
+                entry.repo.clone(),
+                "-".to_string(),
+                "-".to_string(),
+                "not installed".to_string(),
+                "-".to_string(),
+            ],
+        };
+        if pinned {
+            row.push(match &entry.rev {
+                Some(rev) if entry.drifted => format!("{} (drifted)", rev),
+                Some(rev) => rev.clone(),
+                None => "-".to_string(),
+            });
+        }
+        if let Some(remote) = &remote {
+            row.extend(remote_cells(remote.get(&entry.repo)));
+        }
+        UI::add_table_row(&mut table, row);
+    }
+
+    UI::print_table(&table);
+
+    let drifted = entries.iter().filter(|entry| entry.drifted).count();
+    if drifted > 0 {
+        UI::warning(&format!(
+            "{} repositories are off their pinned revision; 'basecamp install --update' checks the pins out again",
+            drifted
+        ));
+    }
+
+    if config.git_config.signing.is_some() {
+        let mut check = SigningCheck::default();
+        let mut unsigned = Vec::new();
+        for entry in entries.iter().filter(|entry| entry.state.is_some()) {
+            let repo_path = config.workspace.repo_path(&entry.codebase, &entry.repo);
+            match check.problem(&repo_path) {
+                Ok(Some(problem)) => unsigned.push(format!("{}/{}: {}", entry.codebase, entry.repo, problem)),
+                Ok(None) => {}
+                Err(e) => warn!("Couldn't check commit signing in {:?}: {}", repo_path, e),
+            }
+        }
+        if !unsigned.is_empty() {
+            UI::warning(&format!("Commits wouldn't be signed in {} repositories", unsigned.len()));
+            for line in &unsigned {
+                UI::detail(line);
+            }
+        }
+    }
+
+    let interval = config.git_config.cache.prefetch;
+    let stale = interval > 0
+        && entries.iter().filter_map(|entry| entry.state.as_ref()).any(|repo_state| {
+            repo_state.last_fetch.is_none_or(|at| state::now().saturating_sub(at) >= interval)
+        });
+
+    if stale && !options.fetch && !offline {
+        prefetch(&config.workspace, codebase.as_deref(), interval);
+    }
+
+    info!("Status displayed for {} codebases", codebases.len());
+    Ok(())
+}
+
+/// Spinner shown while the host is asked, printing warnings above it
+struct RemoteProgress(ProgressBar);
+
+impl ProgressSink for RemoteProgress {
+    fn notice(&self, notice: Notice, message: &str) {
+        self.0.suspend(|| match notice {
+            Notice::Info => UI::info(message),
+            Notice::Warning => UI::warning(message),
+        });
+    }
+}
+
+/// What the host reports about repositories (CI state, open pull requests,
+/// archived or renamed), `None` when it can't be asked
+///
+/// Needs an access token; answers are cached for `cache.remote_ttl` seconds
+/// unless `no_cache` is set.
+pub fn remote_health(config: &Config, repos: &[String], no_cache: bool) -> Option<BTreeMap<String, RemoteHealth>> {
+    if GitRepo::is_offline() {
+        UI::warning("Offline mode: not asking the host about CI and pull requests");
+        return None;
+    }
+    let scope = config.git_config.token_scope()?;
+    let Some(token) = scope.env_token() else {
+        UI::warning(&format!(
+            "Set {} to show CI states and pull requests",
+            scope.variables().join(" or ")
+        ));
+        return None;
+    };
+
+    let ttl = if no_cache { 0 } else { config.git_config.cache.remote_ttl };
+    let spinner = UI::spinner("Asking the host about the repositories");
+    let result = status::remote_health(config, repos, &token, ttl, config.parallel(None), &RemoteProgress(spinner.clone()));
+    spinner.finish_and_clear();
+
+    match result {
+        Ok(health) => Some(health),
+        Err(e) => {
+            UI::warning(&format!("Couldn't ask the host about the repositories: {}", e));
+            None
+        }
+    }
+}
+
+/// CI and PRs cells of a repository
+pub fn remote_cells(health: Option<&RemoteHealth>) -> [String; 2] {
+    match health {
+        Some(health) => [
+            health.ci.map_or_else(|| "-".to_string(), |ci| ci.to_string()),
+            health.open_pull_requests.to_string(),
+        ],
+        None => ["-".to_string(), "-".to_string()],
+    }
+}
+
+/// Start a detached `basecamp fetch`, at most once per prefetch interval
+///
+/// The next `status` then shows up-to-date ahead/behind counts without
+/// waiting for the network.
+fn prefetch(workspace: &Workspace, codebase: Option<&str>, interval: u64) {
+    let stamp = workspace.basecamp_dir().join("prefetch");
+    let started = fs::read_to_string(&stamp)
+        .ok()
+        .and_then(|content| content.trim().parse::<u64>().ok());
+    if started.is_some_and(|at| state::now().saturating_sub(at) < interval) {
+        debug!("Background fetch already started recently");
+        return;
+    }
+
+    let spawned = env::current_exe().and_then(|exe| {
+        let mut command = Command::new(exe);
+        command.args(["--quiet", "--non-interactive", "--no-color"]);
+        command.arg("--config-dir").arg(env::current_dir()?.join(workspace.root()));
+        command.arg("fetch").args(codebase);
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+    });
+
+    match spawned {
+        Ok(_) => {
+            if let Err(e) = fs::write(&stamp, state::now().to_string()) {
+                warn!("Failed to record background fetch: {}", e);
+            }
+            UI::detail("Fetching remotes in the background; run 'basecamp status' again for fresh numbers");
+        }
+        Err(e) => warn!("Failed to start background fetch: {}", e),
+    }
+}
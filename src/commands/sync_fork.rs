@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use log::{debug, warn};
+
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::{GitRepo, UpdateResult};
+use crate::ui::UI;
+
+/// Synced branch of a fork and what happened to it
+type SyncResult = BasecampResult<(String, UpdateResult)>;
+
+/// Execute the sync-fork command
+///
+/// For every cloned repository with an `upstream` setting, points the
+/// `upstream` remote at it, fetches it and fast-forwards the default branch.
+/// Nothing is pushed to the fork.
+pub fn execute(codebase: Option<String>, parallel: Option<usize>) -> BasecampResult<()> {
+    debug!("Executing sync-fork command");
+
+    let config = Config::load(&PathBuf::new())?;
+    if !config.has_github_url() {
+        return Err(BasecampError::GitHubUrlNotConfigured);
+    }
+    GitRepo::ensure_online("fetch the upstream of forks")?;
+
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+    let mut forks = Vec::new();
+    for codebase in config.select_codebases(codebase.as_deref())? {
+        for repo in config.get_repositories(&codebase)? {
+            let Some(upstream) = config.repo_config(repo).upstream else {
+                continue;
+            };
+            if config.workspace.repo_path(&codebase, repo).exists() {
+                let url = GitRepo::expand_remote_url(&config.git_config.github_url, &upstream);
+                forks.push((codebase.clone(), repo.clone(), url));
+            }
+        }
+    }
+    if forks.is_empty() {
+        UI::info("No cloned repository has an 'upstream' setting under 'repositories' in codebases.yaml");
+        return Ok(());
+    }
+
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<SyncResult>>> = Mutex::new((0..forks.len()).map(|_| None).collect());
+    thread::scope(|scope| {
+        for _ in 0..config.parallel(parallel).min(forks.len()) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some((codebase, repo, url)) = forks.get(index) else {
+                        break;
+                    };
+                    let result = GitRepo::sync_fork(&config.workspace.repo_path(codebase, repo), config.remote_name(), url);
+                    results.lock().unwrap()[index] = Some(result);
+                }
+            });
+        }
+    });
+
+    let mut failed = Vec::new();
+    let mut synced = 0;
+    for ((codebase, repo, _), result) in forks.iter().zip(results.into_inner().unwrap()) {
+        match result {
+            Some(Ok((branch, UpdateResult::UpToDate))) => {
+                UI::detail(&format!("{}/{}: '{}' already up to date", codebase, repo, branch));
+            }
+            Some(Ok((branch, result))) => {
+                synced += 1;
+                UI::success(&format!("{}/{}: '{}' {}", codebase, repo, branch, result));
+            }
+            Some(Err(e)) => {
+                warn!("Failed to sync {}/{}: {}", codebase, repo, e);
+                UI::error(&format!("{}/{}: {}", codebase, repo, e));
+                failed.push(format!("{}/{}", codebase, repo));
+            }
+            None => {}
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(BasecampError::CommandFailed(format!(
+            "{} of {} forks failed to sync: {}",
+            failed.len(),
+            forks.len(),
+            failed.join(", ")
+        )));
+    }
+    UI::success(&format!("Synced {} forks, {} were already up to date", synced, forks.len() - synced));
+    Ok(())
+}
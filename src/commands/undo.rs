@@ -0,0 +1,103 @@
+use std::fs;
+use std::path::PathBuf;
+
+use log::{debug, info};
+
+use crate::config::Config;
+use crate::core::{NoProgress, safety};
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::DEFAULT_REMOTE;
+use crate::journal::{ConfigFiles, Journal};
+use crate::ui::UI;
+use crate::workspace::Workspace;
+
+/// Execute the undo command
+///
+/// Reverts the latest journaled operation (`add`, `remove`, `archive`, or the renames
+/// and removals of `verify`): config.yaml and codebases.yaml are written back
+/// as they were, directories moved into the trash or renamed are moved back,
+/// and clones made by `add` are deleted. Unless `force` is set, the undo is
+/// refused when the configuration changed since, or when a clone to delete
+/// has local work.
+pub fn execute(force: bool) -> BasecampResult<()> {
+    debug!("Executing undo command");
+
+    let workspace = Workspace::current();
+    let mut journal = Journal::load(&workspace);
+    let Some(entry) = journal.entries.last().cloned() else {
+        UI::info("Nothing to undo");
+        return Ok(());
+    };
+    let label = format!("{} ({})", entry.summary, UI::format_date(entry.recorded_at));
+    let root = workspace.root();
+
+    if !force && ConfigFiles::read(&workspace) != entry.after {
+        return Err(BasecampError::Generic(format!(
+            "The configuration changed since '{}'; undoing it would discard those changes. Pass --force to undo anyway.",
+            label
+        )));
+    }
+
+    let created: Vec<_> = entry.created.iter().map(|path| root.join(path)).filter(|path| path.exists()).collect();
+    if !force {
+        let clones: Vec<(String, PathBuf)> =
+            created.iter().map(|path| (path.display().to_string(), path.clone())).collect();
+        // Clones were made with the configuration as it is now; a broken one means the default remote
+        let remote = Config::load_workspace(workspace.clone())
+            .ok()
+            .and_then(|config| config.git_config.remote_name)
+            .unwrap_or_else(|| DEFAULT_REMOTE.to_string());
+        safety::check(&clones, &remote, clones.len(), &NoProgress)?;
+    }
+    // A clone made in place of a trashed directory is deleted before it comes back
+    for moved in &entry.moved {
+        let from = root.join(&moved.from);
+        if from.exists() && !created.contains(&from) && root.join(&moved.to).exists() {
+            return Err(BasecampError::Generic(format!(
+                "'{}' exists again; move it away before undoing '{}'",
+                from.display(),
+                label
+            )));
+        }
+    }
+
+    let mut message = format!("Undo '{}'?", label);
+    if !created.is_empty() {
+        message.push_str(&format!(
+            "\nThis DELETES the clones it made:\n{}",
+            created.iter().map(|path| format!("  - {}", path.display())).collect::<Vec<_>>().join("\n")
+        ));
+    }
+    if !UI::confirm(&message, false)? {
+        UI::info("Undo cancelled.");
+        return Ok(());
+    }
+
+    for path in &created {
+        fs::remove_dir_all(path)?;
+        info!("Deleted {:?}", path);
+        UI::detail(&format!("Deleted {}", path.display()));
+    }
+    for moved in entry.moved.iter().rev() {
+        let (from, to) = (root.join(&moved.from), root.join(&moved.to));
+        if !to.exists() {
+            UI::warning(&format!("Can't restore {}: {} is gone", from.display(), to.display()));
+            continue;
+        }
+        if let Some(parent) = from.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(&to, &from)?;
+        info!("Moved {:?} back to {:?}", to, from);
+        UI::detail(&format!("Restored {}", from.display()));
+    }
+    entry.before.write(&workspace)?;
+    if let Some(trash) = &entry.trash {
+        let _ = fs::remove_dir_all(root.join(trash));
+    }
+
+    journal.entries.pop();
+    journal.save(&workspace)?;
+    UI::success(&format!("Undid '{}'", entry.summary));
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use log::{debug, warn};
+
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::ui::UI;
+
+/// Newer revision of a pin, `None` when it's current
+type BumpResult = BasecampResult<Option<String>>;
+
+/// Execute the update-pins command
+///
+/// Fetches every cloned repository with a `rev` setting and moves the pin
+/// forward: a version tag to the highest tag of its series, a commit SHA to
+/// the tip of the default branch. Branch pins are left as they are. Clones
+/// aren't checked out; `install --update` does that.
+pub fn execute(codebase: Option<String>, dry_run: bool, parallel: Option<usize>) -> BasecampResult<()> {
+    debug!("Executing update-pins command");
+
+    let mut config = Config::load(&PathBuf::new())?;
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+
+    // Pins are settings of the repository, so one clone of each is enough
+    let mut pins: Vec<(String, String, PathBuf)> = Vec::new();
+    for codebase in config.select_codebases(codebase.as_deref())? {
+        for repo in config.get_repositories(&codebase)? {
+            let Some(rev) = config.repo_config(repo).rev else {
+                continue;
+            };
+            let repo_path = config.workspace.repo_path(&codebase, repo);
+            if repo_path.exists() && !pins.iter().any(|(name, _, _)| name == repo) {
+                pins.push((repo.clone(), rev, repo_path));
+            }
+        }
+    }
+    if pins.is_empty() {
+        UI::info("No cloned repository has a 'rev' setting under 'repositories' in codebases.yaml");
+        return Ok(());
+    }
+
+    let offline = GitRepo::is_offline();
+    if offline {
+        UI::warning("Offline mode: not fetching, pins are compared with the last fetched tags and branches");
+    }
+
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<BumpResult>>> = Mutex::new((0..pins.len()).map(|_| None).collect());
+    thread::scope(|scope| {
+        for _ in 0..config.parallel(parallel).min(pins.len()) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some((_, rev, repo_path)) = pins.get(index) else {
+                        break;
+                    };
+                    let fetched = if offline { Ok(()) } else { GitRepo::fetch(repo_path, config.remote_name()) };
+                    let result = fetched.and_then(|_| GitRepo::bumped_rev(repo_path, config.remote_name(), rev));
+                    results.lock().unwrap()[index] = Some(result);
+                }
+            });
+        }
+    });
+
+    let mut table = UI::create_table(vec!["Repository", "Pin", "New pin"]);
+    let mut bumped = 0;
+    let mut failed = Vec::new();
+    for ((repo, rev, _), result) in pins.iter().zip(results.into_inner().unwrap()) {
+        let new_pin = match result {
+            Some(Ok(Some(new_rev))) => {
+                config.set_rev(repo, &new_rev);
+                bumped += 1;
+                new_rev
+            }
+            Some(Ok(None)) => "(current)".to_string(),
+            Some(Err(e)) => {
+                warn!("Failed to check the pin of {}: {}", repo, e);
+                UI::error(&format!("{}: {}", repo, e));
+                failed.push(repo.clone());
+                "(failed)".to_string()
+            }
+            None => continue,
+        };
+        UI::add_table_row(&mut table, vec![repo.clone(), rev.clone(), new_pin]);
+    }
+    UI::print_table(&table);
+
+    if bumped > 0 && dry_run {
+        UI::info(&format!("Dry run: {} pins would be updated in codebases.yaml", bumped));
+    } else if bumped > 0 {
+        config.save_codebases()?;
+        UI::success(&format!("Updated {} pins in codebases.yaml", bumped));
+        UI::detail("Run 'basecamp install --update' to check the new revisions out");
+    }
+
+    if !failed.is_empty() {
+        return Err(BasecampError::CommandFailed(format!(
+            "{} of {} pins couldn't be checked: {}",
+            failed.len(),
+            pins.len(),
+            failed.join(", ")
+        )));
+    }
+    if bumped == 0 {
+        UI::success("Every pin is current");
+    }
+    Ok(())
+}
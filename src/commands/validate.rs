@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use log::debug;
+
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::ui::UI;
+use crate::validate::{self, Severity};
+use crate::workspace::Workspace;
+
+/// Execute the validate command
+///
+/// Reports every problem of the configuration files with its location and a
+/// suggested fix. Fails when at least one problem is an error. With `sort`,
+/// a valid codebases.yaml is then rewritten in sorted order.
+pub fn execute(sort: bool) -> BasecampResult<()> {
+    debug!("Executing validate command");
+
+    let diagnostics = validate::validate_workspace(&Workspace::current())?;
+
+    let mut errors = 0;
+    let mut warnings = 0;
+    // The help stays with its problem, errors going to stderr
+    for diagnostic in &diagnostics {
+        match diagnostic.severity {
+            Severity::Error => {
+                errors += 1;
+                UI::error(&diagnostic.to_string());
+            }
+            Severity::Warning => {
+                warnings += 1;
+                UI::warning(&diagnostic.to_string());
+            }
+        }
+    }
+
+    if errors > 0 {
+        return Err(BasecampError::CommandFailed(format!(
+            "Configuration has {} errors and {} warnings",
+            errors, warnings
+        )));
+    }
+
+    if warnings > 0 {
+        UI::success(&format!("Configuration is usable, with {} warnings", warnings));
+    } else {
+        UI::success("Configuration is valid");
+    }
+
+    if sort {
+        let mut config = Config::load(&PathBuf::new())?;
+        if config.sort_codebases()? {
+            UI::success("Sorted codebases and repositories in codebases.yaml");
+        } else {
+            UI::info("codebases.yaml is already sorted");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,320 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use log::{debug, info};
+
+use crate::commands::status::remote_health;
+use crate::config::Config;
+use crate::core::signing::SigningCheck;
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::git::provider::RemoteHealth;
+use crate::journal::Operation;
+use crate::ui::UI;
+use crate::validate;
+
+/// A remote or git config value of a cloned repository that doesn't match the configuration
+struct Mismatch {
+    codebase: String,
+    repo: String,
+    /// Name of the remote, or the git config key
+    remote: String,
+    actual: Option<String>,
+    expected: String,
+}
+
+/// Execute the verify command
+///
+/// Compares the clone remote (`origin` by default) of every cloned repository
+/// with the URL built from `config.yaml`, and its `upstream` and `remotes`
+/// with their settings in codebases.yaml, and the `git` settings with the
+/// local git config. `fix` rewrites mismatching remotes and values and adds
+/// missing ones. With `signing` set, commits must be signable in every clone.
+/// With an access token, repositories archived, renamed or moved on the host are
+/// reported too.
+pub fn execute(codebase: Option<String>, fix: bool) -> BasecampResult<()> {
+    debug!("Executing verify command");
+
+    // Load configuration
+    let mut config = Config::load(&PathBuf::new())?;
+    let codebase = codebase.map(|name| config.resolve_codebase(&name));
+
+    // Check if GitHub URL is configured
+    if !config.has_github_url() {
+        return Err(BasecampError::GitHubUrlNotConfigured);
+    }
+
+    let codebases = config.select_codebases(codebase.as_deref())?;
+
+    // Renamed repositories are updated before their remotes are compared
+    if let Some(scope) = config.git_config.token_scope()
+        && !GitRepo::is_offline()
+        && scope.env_token().is_none()
+    {
+        UI::detail(&format!(
+            "Set {} to also check for archived and renamed repositories",
+            scope.variables().join(" or ")
+        ));
+    }
+    let stale = check_host(&mut config, &codebases)?;
+
+    let mut checked = 0;
+    let mut mismatches = Vec::new();
+    let mut settings = Vec::new();
+    let mut cloned = Vec::new();
+
+    for codebase in &codebases {
+        for repo in config.get_repositories(codebase)? {
+            let repo_path = config.workspace.repo_path(codebase, repo);
+            if !repo_path.exists() {
+                continue;
+            }
+
+            checked += 1;
+            let clone_remote = (
+                config.remote_name().to_string(),
+                GitRepo::build_repo_url(&config.git_config.github_url, repo),
+            );
+            for (remote, expected) in std::iter::once(clone_remote).chain(config.extra_remotes(repo)) {
+                let actual = GitRepo::remote_url(&repo_path, &remote)?;
+
+                // git reports remotes with `insteadOf` rewrites applied
+                let rewritten = GitRepo::rewrite_url(&expected);
+                let matches = |url: &str| GitRepo::same_remote(url, &expected) || GitRepo::same_remote(url, &rewritten);
+                if !actual.as_deref().is_some_and(matches) {
+                    mismatches.push(Mismatch {
+                        codebase: codebase.clone(),
+                        repo: repo.clone(),
+                        remote,
+                        actual,
+                        expected,
+                    });
+                }
+            }
+
+            for (key, expected) in config.git_settings(codebase, repo)? {
+                let actual = GitRepo::local_config(&repo_path, &key)?;
+                if actual.as_deref() != Some(expected.as_str()) {
+                    settings.push(Mismatch {
+                        codebase: codebase.clone(),
+                        repo: repo.clone(),
+                        remote: key,
+                        actual,
+                        expected,
+                    });
+                }
+            }
+            cloned.push((format!("{}/{}", codebase, repo), repo_path));
+        }
+    }
+
+    if mismatches.is_empty() && settings.is_empty() {
+        UI::success(&format!(
+            "All {} cloned repositories point at their configured remotes",
+            checked
+        ));
+        stale_result(stale)?;
+        return signing_result(&config, &cloned);
+    }
+
+    print_mismatches("Remote", "URL", &mismatches);
+    print_mismatches("Git config", "Value", &settings);
+
+    let counts = match (mismatches.len(), settings.len()) {
+        (remotes, 0) => format!("{} remotes", remotes),
+        (0, values) => format!("{} git config values", values),
+        (remotes, values) => format!("{} remotes and {} git config values", remotes, values),
+    };
+    if !fix {
+        return Err(BasecampError::CommandFailed(format!(
+            "{} of {} cloned repositories don't match the configuration. Run 'basecamp verify --fix' to rewrite them.",
+            counts, checked
+        )));
+    }
+
+    for mismatch in &mismatches {
+        let repo_path = config.workspace.repo_path(&mismatch.codebase, &mismatch.repo);
+        GitRepo::set_remote_url(&repo_path, &mismatch.remote, &mismatch.expected)?;
+        info!("Set {} of {:?} to {}", mismatch.remote, repo_path, mismatch.expected);
+    }
+    for setting in &settings {
+        let repo_path = config.workspace.repo_path(&setting.codebase, &setting.repo);
+        GitRepo::set_local_config(&repo_path, &setting.remote, &setting.expected)?;
+        info!("Set {} of {:?} to {}", setting.remote, repo_path, setting.expected);
+    }
+
+    UI::success(&format!("Rewrote {}", counts));
+    stale_result(stale)?;
+    signing_result(&config, &cloned)
+}
+
+/// Fail when `signing` is set but commits wouldn't be signed in some clones
+///
+/// Checked once the git config matches, since `--fix` may have been all it took.
+fn signing_result(config: &Config, cloned: &[(String, PathBuf)]) -> BasecampResult<()> {
+    if config.git_config.signing.is_none() {
+        return Ok(());
+    }
+
+    let mut check = SigningCheck::default();
+    let mut unsigned = 0;
+    for (label, repo_path) in cloned {
+        if let Some(problem) = check.problem(repo_path)? {
+            UI::error(&format!("{}: {}", label, problem));
+            unsigned += 1;
+        }
+    }
+    if unsigned == 0 {
+        return Ok(());
+    }
+    Err(BasecampError::CommandFailed(format!(
+        "Commits wouldn't be signed in {} repositories, although 'signing' is set in config.yaml",
+        unsigned
+    )))
+}
+
+/// Print a table of mismatches, if any, naming what was compared
+fn print_mismatches(what: &str, value: &str, mismatches: &[Mismatch]) {
+    if mismatches.is_empty() {
+        return;
+    }
+    let mut table = UI::create_table(vec!["Codebase", "Repository", what, value, "Expected"]);
+    for mismatch in mismatches {
+        UI::add_table_row(
+            &mut table,
+            vec![
+                mismatch.codebase.clone(),
+                mismatch.repo.clone(),
+                mismatch.remote.clone(),
+                mismatch.actual.clone().unwrap_or_else(|| "(missing)".to_string()),
+                mismatch.expected.clone(),
+            ],
+        );
+    }
+    UI::print_table(&table);
+}
+
+/// Fail when stale entries are left in codebases.yaml
+fn stale_result(stale: usize) -> BasecampResult<()> {
+    if stale == 0 {
+        return Ok(());
+    }
+    Err(BasecampError::CommandFailed(format!(
+        "{} repositories are archived, renamed or moved on the host. Run 'basecamp verify' again to update them.",
+        stale
+    )))
+}
+
+/// Ask the host about the repositories of `codebases` and review the stale ones
+///
+/// Does nothing offline or without an access token. Returns how many stale
+/// entries are left in codebases.yaml.
+pub fn check_host(config: &mut Config, codebases: &[String]) -> BasecampResult<usize> {
+    if GitRepo::is_offline() || config.git_config.token_scope().and_then(|scope| scope.env_token()).is_none() {
+        return Ok(0);
+    }
+
+    let mut repos = Vec::new();
+    for codebase in codebases {
+        repos.extend(config.get_repositories(codebase)?.iter().cloned());
+    }
+    repos.sort();
+    repos.dedup();
+
+    match remote_health(config, &repos, false) {
+        Some(health) => review_stale(config, &health),
+        None => Ok(0),
+    }
+}
+
+/// Warn about repositories the host reports as archived, renamed or moved,
+/// and offer to update codebases.yaml
+///
+/// A renamed repository keeps its place in every codebase, and its clone is
+/// moved and pointed at the new URL. Archived repositories and those moved
+/// to another owner can be removed; their clones are left in place. Returns
+/// how many stale entries are left.
+pub fn review_stale(config: &mut Config, health: &BTreeMap<String, RemoteHealth>) -> BasecampResult<usize> {
+    let github_url = config.git_config.github_url.clone();
+    let owner = GitRepo::remote_owner(&GitRepo::build_repo_url(&github_url, "repo"))
+        .map(|(_, owner)| owner)
+        .unwrap_or_default();
+
+    // Recorded for `basecamp undo`, with the clones moved
+    let journal = Operation::begin(&config.workspace, "verify");
+    let mut fixes = Vec::new();
+    let mut left = 0;
+    for (repo, health) in health {
+        let fix = match (health.renamed_within(&owner), &health.moved_to) {
+            (Some(new_name), _) => {
+                UI::warning(&format!("'{}' was renamed to '{}' on the host", repo, new_name));
+                let rename = UI::confirm(&format!("Rename '{}' to '{}' in codebases.yaml?", repo, new_name), false)?;
+                if rename {
+                    rename_repository(config, repo, new_name, &journal)?;
+                }
+                rename.then(|| format!("rename {} to {}", repo, new_name))
+            }
+            (None, Some(moved_to)) => {
+                UI::warning(&format!("'{}' moved to {}, outside {}", repo, moved_to, github_url));
+                offer_removal(config, repo)?.then(|| format!("remove {}", repo))
+            }
+            (None, None) if health.archived => {
+                UI::warning(&format!("'{}' is archived on the host", repo));
+                offer_removal(config, repo)?.then(|| format!("remove {}", repo))
+            }
+            (None, None) => continue,
+        };
+
+        match fix {
+            Some(fix) => fixes.push(fix),
+            None => left += 1,
+        }
+    }
+
+    if !fixes.is_empty() {
+        config.save_codebases()?;
+        journal.finish(&fixes.join(", "));
+    }
+    Ok(left)
+}
+
+/// Rename a repository in codebases.yaml, and move its clones
+fn rename_repository(config: &mut Config, old: &str, new: &str, journal: &Operation) -> BasecampResult<()> {
+    if !validate::is_valid_repo_name(new) {
+        return Err(BasecampError::Generic(format!(
+            "The host reports '{}' was renamed to '{}', which is not a valid repository name",
+            old,
+            new.escape_debug()
+        )));
+    }
+    let url = GitRepo::build_repo_url(&config.git_config.github_url, new);
+    for codebase in config.rename_repository(old, new) {
+        let old_path = config.workspace.repo_path(&codebase, old);
+        let new_path = config.workspace.repo_path(&codebase, new);
+        if !old_path.exists() || new_path.exists() {
+            continue;
+        }
+        fs::rename(&old_path, &new_path)?;
+        journal.record_move(&old_path, &new_path);
+        GitRepo::set_remote_url(&new_path, config.remote_name(), &url)?;
+        info!("Moved {:?} to {:?}", old_path, new_path);
+    }
+    UI::success(&format!("Renamed '{}' to '{}'", old, new));
+    Ok(())
+}
+
+/// Offer to remove a repository from every codebase listing it
+fn offer_removal(config: &mut Config, repo: &str) -> BasecampResult<bool> {
+    if !UI::confirm(&format!("Remove '{}' from codebases.yaml?", repo), false)? {
+        return Ok(false);
+    }
+
+    let codebases: Vec<String> = config.find_repository(repo).into_iter().cloned().collect();
+    for codebase in &codebases {
+        config.remove_repositories(codebase, &[repo.to_string()])?;
+    }
+    UI::success(&format!("Removed '{}' from {}", repo, codebases.join(", ")));
+    UI::detail("Its clones were left in place; delete them when they're no longer needed");
+    Ok(true)
+}
@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+use log::{debug, warn};
+
+use crate::commands::fetch;
+use crate::config::Config;
+use crate::error::BasecampResult;
+use crate::ui::UI;
+
+/// Execute the watch command
+pub fn execute(interval: u64, notify: bool, once: bool, parallel_count: Option<usize>) -> BasecampResult<()> {
+    debug!("Executing watch command");
+
+    if !once {
+        UI::info(&format!(
+            "Watching workspace, refreshing every {}s (Ctrl+C to stop)",
+            interval
+        ));
+    }
+
+    loop {
+        // Reload the configuration each cycle to pick up added or removed repositories
+        let refreshed = Config::load(&PathBuf::new())
+            .and_then(|config| fetch::refresh(&config, None, config.parallel(parallel_count), None));
+        let newly_behind = match refreshed {
+            Ok(newly_behind) => newly_behind,
+            Err(e) if once => return Err(e),
+            // A lost connection or a config file being edited shouldn't end the watch
+            Err(e) => {
+                warn!("Refresh failed: {}", e);
+                UI::warning(&format!("Refresh failed, trying again in {}s: {}", interval, e));
+                Vec::new()
+            }
+        };
+
+        if notify && !newly_behind.is_empty() {
+            send_notification(
+                "BaseCamp: repositories behind remote",
+                &newly_behind.join(", "),
+            );
+        }
+
+        if once {
+            break;
+        }
+
+        thread::sleep(Duration::from_secs(interval));
+    }
+
+    Ok(())
+}
+
+/// Show a desktop notification using the platform's notifier
+fn send_notification(title: &str, body: &str) {
+    let mut cmd = if cfg!(target_os = "macos") {
+        let mut cmd = Command::new("osascript");
+        cmd.arg("-e").arg(format!(
+            "display notification {:?} with title {:?}",
+            body, title
+        ));
+        cmd
+    } else if cfg!(windows) {
+        debug!("Desktop notifications are not supported on Windows");
+        return;
+    } else {
+        let mut cmd = Command::new("notify-send");
+        cmd.arg(title).arg(body);
+        cmd
+    };
+
+    if let Err(e) = cmd.status() {
+        warn!("Failed to send desktop notification: {}", e);
+    }
+}
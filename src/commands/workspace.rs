@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use log::{debug, info};
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::ui::UI;
+use crate::workspace::Workspace;
+
+/// Tmuxinator project file layout
+#[derive(Debug, Serialize)]
+struct TmuxinatorProject {
+    name: String,
+    root: String,
+    windows: Vec<BTreeMap<String, String>>,
+}
+
+/// Execute the workspace tmux command
+pub fn tmux(codebase: String, output: Option<PathBuf>, launch: bool) -> BasecampResult<()> {
+    debug!("Executing workspace tmux command for codebase '{}'", codebase);
+
+    // Load configuration
+    let config = Config::load(&PathBuf::new())?;
+    let codebase = config.resolve_codebase(&codebase);
+    let repos = config.get_repositories(&codebase)?;
+
+    let current_dir = config.workspace.absolute_root()?;
+    let root = current_dir.join(&codebase);
+    let session = session_name(&codebase);
+
+    // Only open windows for repositories that are actually cloned
+    let installed: Vec<&String> = repos
+        .iter()
+        .filter(|repo| config.workspace.repo_path(&codebase, repo).exists())
+        .collect();
+
+    if installed.is_empty() {
+        UI::warning(&format!(
+            "No installed repositories in codebase '{}'. Run 'basecamp install {}' first.",
+            codebase, codebase
+        ));
+        return Ok(());
+    }
+
+    if launch {
+        return launch_session(&session, &config.workspace, &current_dir, &codebase, &installed);
+    }
+
+    let project = TmuxinatorProject {
+        name: session,
+        root: root.display().to_string(),
+        windows: installed
+            .iter()
+            .map(|repo| {
+                let mut window = BTreeMap::new();
+                window.insert(repo.to_string(), format!("cd {}", config.workspace.repo_dir(repo)));
+                window
+            })
+            .collect(),
+    };
+
+    let yaml = serde_yaml::to_string(&project)?;
+
+    match output {
+        Some(path) => {
+            fs::write(&path, yaml)?;
+            UI::success(&format!("Wrote tmuxinator project to {}", path.display()));
+            info!("Wrote tmuxinator project for '{}' to {:?}", codebase, path);
+        }
+        None => UI::output(&yaml),
+    }
+
+    Ok(())
+}
+
+/// Spawn a detached tmux session with one window per repository
+fn launch_session(
+    session: &str,
+    workspace: &Workspace,
+    current_dir: &Path,
+    codebase: &str,
+    repos: &[&String],
+) -> BasecampResult<()> {
+    for (idx, repo) in repos.iter().enumerate() {
+        let repo_dir = current_dir.join(codebase).join(workspace.repo_dir(repo));
+        let repo_dir = repo_dir.to_string_lossy();
+
+        let mut cmd = Command::new("tmux");
+        if idx == 0 {
+            cmd.args(["new-session", "-d", "-s", session, "-n", repo, "-c", &repo_dir]);
+        } else {
+            cmd.args(["new-window", "-t", session, "-n", repo, "-c", &repo_dir]);
+        }
+
+        run_tmux(&mut cmd)?;
+    }
+
+    UI::success(&format!(
+        "Started tmux session '{}' with {} windows. Attach with: tmux attach -t {}",
+        session,
+        repos.len(),
+        session
+    ));
+    info!("Started tmux session '{}'", session);
+
+    Ok(())
+}
+
+/// Run a tmux command and turn failures into errors
+fn run_tmux(cmd: &mut Command) -> BasecampResult<()> {
+    debug!("Running {:?}", cmd);
+
+    let output = cmd.output().map_err(|e| {
+        BasecampError::CommandFailed(format!("Failed to run tmux (is it installed?): {}", e))
+    })?;
+
+    if !output.status.success() {
+        return Err(BasecampError::CommandFailed(format!(
+            "tmux failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Build a tmux-safe session name (tmux rejects '.' and ':')
+fn session_name(codebase: &str) -> String {
+    codebase.replace(['.', ':'], "-")
+}
@@ -0,0 +1,150 @@
+use std::path::PathBuf;
+
+use log::debug;
+
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::registry::WorkspaceRegistry;
+use crate::ui::UI;
+
+/// Execute the workspaces list command
+pub fn list() -> BasecampResult<()> {
+    debug!("Executing workspaces list command");
+
+    let registry = WorkspaceRegistry::load();
+    if registry.workspaces.is_empty() {
+        UI::info("No workspaces registered. Run 'basecamp init' or 'basecamp workspaces add <dir>'.");
+        return Ok(());
+    }
+
+    let mut stale = 0;
+    let mut table = UI::create_table(vec!["", "Workspace", "Path", "Status"]);
+    for (name, path) in &registry.workspaces {
+        let status = if WorkspaceRegistry::is_stale(path) {
+            stale += 1;
+            "missing"
+        } else {
+            "ok"
+        };
+        UI::add_table_row(
+            &mut table,
+            vec![
+                if registry.current.as_ref() == Some(name) { "*" } else { "" }.to_string(),
+                name.clone(),
+                path.display().to_string(),
+                status.to_string(),
+            ],
+        );
+    }
+    UI::print_table(&table);
+
+    if stale > 0 {
+        UI::info(&format!(
+            "{} workspaces no longer exist. Run 'basecamp workspaces prune' to forget them.",
+            stale
+        ));
+    }
+    Ok(())
+}
+
+/// Execute the workspaces add command
+pub fn add(dir: Option<PathBuf>, name: Option<String>) -> BasecampResult<()> {
+    debug!("Executing workspaces add command");
+
+    let root = match dir {
+        Some(dir) => dir,
+        None => Config::get_workspace_root()?,
+    };
+    if WorkspaceRegistry::is_stale(&root) {
+        return Err(BasecampError::FileNotFound(
+            root.join(".basecamp").join("config.yaml"),
+        ));
+    }
+
+    let mut registry = WorkspaceRegistry::load();
+    let name = registry.register(&root, name.as_deref())?;
+    registry.save()?;
+
+    UI::success(&format!("Registered workspace '{}' ({})", name, root.display()));
+    Ok(())
+}
+
+/// Execute the workspaces switch command
+///
+/// Commands run outside of any workspace (and without `--config-dir`) then
+/// operate on the selected one.
+pub fn switch(name: Option<String>) -> BasecampResult<()> {
+    debug!("Executing workspaces switch command");
+
+    let mut registry = WorkspaceRegistry::load();
+
+    let Some(name) = name else {
+        registry.current = None;
+        registry.save()?;
+        UI::success("Commands run outside a workspace no longer use a default workspace");
+        return Ok(());
+    };
+
+    let path = registry.workspaces.get(&name).cloned().ok_or_else(|| {
+        BasecampError::Generic(format!(
+            "Workspace '{}' is not registered. Use 'basecamp workspaces list' to see registered workspaces.",
+            name
+        ))
+    })?;
+    if WorkspaceRegistry::is_stale(&path) {
+        return Err(BasecampError::Generic(format!(
+            "Workspace '{}' no longer exists at {}. Run 'basecamp workspaces prune' to forget it.",
+            name,
+            path.display()
+        )));
+    }
+
+    registry.current = Some(name.clone());
+    registry.save()?;
+
+    UI::success(&format!(
+        "Commands run outside a workspace now operate on '{}' ({})",
+        name,
+        path.display()
+    ));
+    Ok(())
+}
+
+/// Execute the workspaces remove command
+pub fn remove(name: &str) -> BasecampResult<()> {
+    debug!("Executing workspaces remove command for '{}'", name);
+
+    let mut registry = WorkspaceRegistry::load();
+    if registry.workspaces.remove(name).is_none() {
+        return Err(BasecampError::Generic(format!(
+            "Workspace '{}' is not registered",
+            name
+        )));
+    }
+    if registry.current.as_deref() == Some(name) {
+        registry.current = None;
+    }
+    registry.save()?;
+
+    UI::success(&format!("Forgot workspace '{}'; its files were left untouched", name));
+    Ok(())
+}
+
+/// Execute the workspaces prune command
+pub fn prune() -> BasecampResult<()> {
+    debug!("Executing workspaces prune command");
+
+    let mut registry = WorkspaceRegistry::load();
+    let pruned = registry.prune();
+    if pruned.is_empty() {
+        UI::info("Every registered workspace still exists");
+        return Ok(());
+    }
+    registry.save()?;
+
+    for (name, path) in &pruned {
+        UI::detail(&format!("{} ({})", name, path.display()));
+    }
+    UI::success(&format!("Forgot {} missing workspaces", pruned.len()));
+    Ok(())
+}
@@ -1,12 +1,402 @@
-use std::collections::HashMap;
-use std::fs::{self, File, create_dir_all};
-use std::io::Write;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::mem;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use log::{debug, info};
 use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
 
+use crate::backup;
+use crate::core::events::{self, Event};
 use crate::error::{BasecampError, BasecampResult};
+use crate::git::backend::{self, BackendKind};
+use crate::git::auth::{self, ProviderKind, TokenScope};
+use crate::git::provider::HostProvider;
+use crate::git::{DEFAULT_REMOTE, GitRepo, NetworkTimeouts};
+use crate::messages;
+use crate::template::{self, Expanded, Vars};
+use crate::ui::{Theme, UI};
+use crate::validate;
+use crate::workspace::Workspace;
+use crate::yaml_edit;
+
+/// Parallel git operations when neither `-p` nor `parallel` is set
+const DEFAULT_PARALLEL: usize = 4;
+
+/// Size of deletions (in MiB) that need the codebase name typed when unset
+const DEFAULT_CONFIRM_DELETE_MIB: u64 = 1024;
+
+/// Profile selected with `--profile`, taking precedence over `profile:` in config.yaml
+static PROFILE_OVERRIDE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Workspace root selected with `--config-dir` (the current directory when unset)
+///
+/// The one process-wide path left, read through `Workspace::current()` as the
+/// default of commands that aren't given a workspace. Library code takes a
+/// `Workspace` (or the root passed to `Config::load`) instead.
+static WORKSPACE_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Whether changes to the configuration files are shown and confirmed before saving (`--diff`)
+static PREVIEW_CHANGES: AtomicBool = AtomicBool::new(false);
+
+/// Repository state cache settings
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// Seconds a cached repository state is considered fresh (0 disables the cache)
+    #[serde(default = "CacheConfig::default_ttl")]
+    pub ttl: u64,
+
+    /// Seconds after which `status` fetches again in the background (0 disables prefetching)
+    #[serde(default, skip_serializing_if = "CacheConfig::is_disabled")]
+    pub prefetch: u64,
+
+    /// Seconds CI states and pull request counts from the host are reused (0 always asks)
+    #[serde(default = "CacheConfig::default_remote_ttl")]
+    pub remote_ttl: u64,
+}
+
+impl CacheConfig {
+    fn default_ttl() -> u64 {
+        60
+    }
+
+    fn default_remote_ttl() -> u64 {
+        300
+    }
+
+    fn is_disabled(seconds: &u64) -> bool {
+        *seconds == 0
+    }
+
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Self::default_ttl(),
+            prefetch: 0,
+            remote_ttl: Self::default_remote_ttl(),
+        }
+    }
+}
+
+/// Limits of clone and fetch operations, in seconds (0 disables a limit)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TimeoutConfig {
+    /// Time allowed until the remote first responds
+    #[serde(default = "TimeoutConfig::default_connect")]
+    pub connect: u64,
+
+    /// Time allowed for a whole clone or fetch
+    #[serde(default = "TimeoutConfig::default_total")]
+    pub total: u64,
+}
+
+impl TimeoutConfig {
+    fn default_connect() -> u64 {
+        30
+    }
+
+    fn default_total() -> u64 {
+        1800
+    }
+
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Limits used by the git operations
+    pub fn to_network_timeouts(&self) -> NetworkTimeouts {
+        let limit = |seconds: u64| (seconds > 0).then(|| Duration::from_secs(seconds));
+        NetworkTimeouts {
+            connect: limit(self.connect),
+            total: limit(self.total),
+        }
+    }
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            connect: Self::default_connect(),
+            total: Self::default_total(),
+        }
+    }
+}
+
+/// Look of the output: theme colors and ASCII-only symbols
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct UiConfig {
+    /// Plain ASCII instead of Unicode symbols (✓ ✗ ↑ ↓ and the spinner)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ascii: bool,
+
+    #[serde(default, skip_serializing_if = "ThemeConfig::is_default")]
+    pub theme: ThemeConfig,
+
+    /// Translation file of the messages (see `basecamp config messages`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub messages: Option<PathBuf>,
+}
+
+/// Colors (names like `green` or 256-color indexes) and spinner characters
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ThemeConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// Spinner frames, one character each (e.g. "|/-\\")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spinner: Option<String>,
+}
+
+impl ThemeConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl UiConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Use the theme and translations for the output of this run
+    ///
+    /// A relative `messages` path is resolved against the workspace root.
+    fn apply(&self, workspace: &Workspace) -> BasecampResult<()> {
+        let mut theme = Theme::default();
+        for (name, value, color) in [
+            ("success", &self.theme.success, &mut theme.success),
+            ("warning", &self.theme.warning, &mut theme.warning),
+            ("error", &self.theme.error, &mut theme.error),
+        ] {
+            if let Some(value) = value {
+                *color = Theme::parse_color(value).ok_or_else(|| {
+                    BasecampError::InvalidConfig(format!(
+                        "ui.theme.{}: unknown color '{}' (use black, red, green, yellow, blue, magenta, cyan, white or 0-255)",
+                        name, value
+                    ))
+                })?;
+            }
+        }
+        if let Some(spinner) = &self.theme.spinner {
+            if spinner.chars().count() < 2 {
+                return Err(BasecampError::InvalidConfig(
+                    "ui.theme.spinner: give at least two characters to animate".to_string(),
+                ));
+            }
+            theme.spinner = Some(spinner.clone());
+        }
+        theme.ascii = self.ascii;
+        UI::set_theme(theme);
+
+        match &self.messages {
+            Some(path) => {
+                let path = workspace.root().join(Config::expand_home(path));
+                let unknown = messages::load(&path)
+                    .map_err(|e| BasecampError::InvalidConfig(format!("ui.messages: {}", e)))?;
+                if !unknown.is_empty() {
+                    UI::warning(&format!("Unknown message IDs in {}: {}", path.display(), unknown.join(", ")));
+                }
+            }
+            None => messages::reset(),
+        }
+        Ok(())
+    }
+}
+
+/// TLS settings of HTTPS remotes
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct TlsConfig {
+    /// PEM bundle of additional certificate authorities (e.g. a company CA)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<PathBuf>,
+
+    /// Accept any server certificate; insecure, prefer `ca_bundle`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub insecure_skip_verify: bool,
+}
+
+impl TlsConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Trust the CA bundle and apply the verification setting to git operations
+    ///
+    /// A relative `ca_bundle` is resolved against the workspace root.
+    fn apply(&self, workspace: &Workspace) -> BasecampResult<()> {
+        if let Some(ca_bundle) = &self.ca_bundle {
+            let path = workspace.root().join(Config::expand_home(ca_bundle));
+            if !path.is_file() {
+                return Err(BasecampError::InvalidConfig(format!(
+                    "tls.ca_bundle: '{}' does not exist",
+                    path.display()
+                )));
+            }
+            GitRepo::set_ca_bundle(&path)?;
+        }
+
+        // Once per run, the configuration may be loaded again
+        if self.insecure_skip_verify && !GitRepo::is_insecure_skip_verify() {
+            UI::warning("TLS certificate verification is disabled (tls.insecure_skip_verify in config.yaml)");
+        }
+        GitRepo::set_insecure_skip_verify(self.insecure_skip_verify);
+        Ok(())
+    }
+}
+
+/// How `install --update` brings existing repositories up to date
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UpdateStrategy {
+    /// Only fast-forward; diverged branches are reported as errors
+    #[default]
+    #[serde(rename = "ff-only")]
+    FastForward,
+    /// Rebase local commits onto the upstream branch
+    #[serde(rename = "rebase")]
+    Rebase,
+}
+
+impl UpdateStrategy {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// When `install` clones a repository, relative to the others
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    /// Cloned before all others
+    High,
+    #[default]
+    Normal,
+    /// Cloned after all others
+    Low,
+}
+
+impl Priority {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// How the hooks of `hooks_dir` are deployed into clones
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum HooksMode {
+    /// Symlink each hook into `.git/hooks` (copied where symlinks aren't available)
+    #[default]
+    Link,
+    /// Copy each hook into `.git/hooks`
+    Copy,
+    /// Point `core.hooksPath` at the directory, disabling the clone's own hooks
+    HooksPath,
+}
+
+impl HooksMode {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Kind of signature of signed commits (git's `gpg.format`)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SigningFormat {
+    /// GnuPG signatures
+    #[default]
+    Openpgp,
+    /// S/MIME signatures made with gpgsm
+    X509,
+    /// SSH signatures made with ssh-keygen
+    Ssh,
+}
+
+impl SigningFormat {
+    /// Value of `gpg.format`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Openpgp => "openpgp",
+            Self::X509 => "x509",
+            Self::Ssh => "ssh",
+        }
+    }
+}
+
+/// Commit signing set up in every clone
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SigningConfig {
+    /// Kind of signature (`openpgp`, `x509` or `ssh`)
+    #[serde(default)]
+    pub format: SigningFormat,
+
+    /// Signing key: a GnuPG key ID, or the path of an SSH key (the committer's email when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+
+    /// Sign annotated tags too
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub tags: bool,
+}
+
+/// Second host a codebase can be replicated to with `basecamp mirror`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct MirrorConfig {
+    /// Base URL of the repositories on the mirror host, like `github_url`
+    pub url: String,
+
+    /// Hosting service of `url` (detected from its host when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<HostProvider>,
+
+    /// Environment variable holding an access token for the mirror host
+    /// (`GITHUB_TOKEN`, `GH_TOKEN` or `GITLAB_TOKEN` when unset and the mirror is on their host)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+}
+
+/// Named profile overriding the top-level settings of config.yaml
+///
+/// Only the settings a profile defines are overridden.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ProfileConfig {
+    /// Base GitHub URL for repositories
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github_url: Option<String>,
+
+    /// SSH private key used for this profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_key: Option<PathBuf>,
+
+    /// Update strategy of `install --update`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update: Option<UpdateStrategy>,
+}
+
+/// Top-level settings replaced by the active profile, restored on save
+#[derive(Debug, Clone, Default)]
+struct Replaced {
+    github_url: Option<String>,
+    ssh_key: Option<Option<PathBuf>>,
+    update: Option<UpdateStrategy>,
+}
 
 /// Git configuration structure
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -14,14 +404,339 @@ pub struct GitConfig {
     /// Base GitHub URL for repositories
     #[serde(default)]
     pub github_url: String,
+
+    /// Hosting service of `github_url` (github, github_enterprise, gitlab, bitbucket or custom)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<HostProvider>,
+
+    /// SSH private key tried before the SSH agent and the keys in ~/.ssh
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_key: Option<PathBuf>,
+
+    /// State cache settings
+    #[serde(default, skip_serializing_if = "CacheConfig::is_default")]
+    pub cache: CacheConfig,
+
+    /// Update strategy of `install --update`
+    #[serde(default, skip_serializing_if = "UpdateStrategy::is_default")]
+    pub update: UpdateStrategy,
+
+    /// Default number of parallel git operations (`-p` overrides it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parallel: Option<usize>,
+
+    /// Copies of config.yaml and codebases.yaml kept in `.basecamp/backups/` (0 disables them)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backups: Option<usize>,
+
+    /// Set to false to disable colors and progress bars
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<bool>,
+
+    /// Theme colors and ASCII symbols of the output
+    #[serde(default, skip_serializing_if = "UiConfig::is_default")]
+    pub ui: UiConfig,
+
+    /// Deletions larger than this many MiB need the codebase name typed to confirm (0 disables it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm_delete_mib: Option<u64>,
+
+    /// Credential providers tried in order (agent, key_file, token, helper, keychain when empty)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub auth: Vec<ProviderKind>,
+
+    /// Proxy for HTTP(S) remotes (`https_proxy`/`http_proxy` are used otherwise)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+
+    /// Limits of clone and fetch operations
+    #[serde(default, skip_serializing_if = "TimeoutConfig::is_default")]
+    pub timeouts: TimeoutConfig,
+
+    /// Git implementation used for status (`git2` or `gix`); clones and fetches always use git2
+    #[serde(default, skip_serializing_if = "BackendKind::is_default")]
+    pub backend: BackendKind,
+
+    /// Certificate authorities and verification of HTTPS remotes
+    #[serde(default, skip_serializing_if = "TlsConfig::is_default")]
+    pub tls: TlsConfig,
+
+    /// Never access the network (like `--offline`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub offline: bool,
+
+    /// Name of the remote repositories are cloned from (`origin` when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_name: Option<String>,
+
+    /// Short names for codebases or groups (e.g. `fe: frontend`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+
+    /// Codebase (or group) `install` uses when none is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_codebase: Option<String>,
+
+    /// Profile used when `--profile` isn't given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
+    /// Named profiles (e.g. `work`, `oss`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+
+    /// Hosts codebases can be replicated to, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mirrors: BTreeMap<String, MirrorConfig>,
+
+    /// Files the environment of a codebase is written to on install (`[dotenv]` when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_files: Option<Vec<EnvFile>>,
+
+    /// Directory of git hooks deployed into every clone, relative to the workspace root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks_dir: Option<PathBuf>,
+
+    /// How the hooks of `hooks_dir` are deployed (`link`, `copy` or `hooks_path`)
+    #[serde(default, skip_serializing_if = "HooksMode::is_default")]
+    pub hooks_mode: HooksMode,
+
+    /// Git config set in every clone (e.g. `user.email`), nested keys joined with dots
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub git: BTreeMap<String, Value>,
+
+    /// Commit signing set up in every clone, through the git config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing: Option<SigningConfig>,
+
+    /// Name of the applied profile and the settings it replaced
+    #[serde(skip)]
+    applied: Option<(String, Replaced)>,
+
+    /// Settings inherited from the user-level config.yaml, not saved to the project
+    #[serde(skip)]
+    inherited: Mapping,
+
+    /// Values whose placeholders were expanded on load, written back on save
+    #[serde(skip)]
+    expanded: Vec<Expanded>,
+}
+
+impl GitConfig {
+    /// Host of `github_url` and its hosting service, which access tokens are scoped to
+    pub fn token_scope(&self) -> Option<TokenScope> {
+        let (host, _) = GitRepo::remote_owner(&GitRepo::build_repo_url(&self.github_url, "repo"))?;
+        let provider = self.provider.unwrap_or_else(|| HostProvider::detect(&host));
+        Some(TokenScope { host, provider })
+    }
+
+    /// Name of the profile applied to these settings, if any
+    pub fn active_profile(&self) -> Option<&str> {
+        self.applied.as_ref().map(|(name, _)| name.as_str())
+    }
+
+    /// Override the top-level settings with those of a profile
+    pub fn apply_profile(&mut self, name: &str) -> BasecampResult<()> {
+        let profile = self
+            .profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| BasecampError::ProfileNotFound(name.to_string()))?;
+
+        let mut replaced = Replaced::default();
+        if let Some(url) = profile.github_url {
+            replaced.github_url = Some(mem::replace(&mut self.github_url, url));
+        }
+        if let Some(key) = profile.ssh_key {
+            replaced.ssh_key = Some(self.ssh_key.replace(key));
+        }
+        if let Some(update) = profile.update {
+            replaced.update = Some(mem::replace(&mut self.update, update));
+        }
+
+        debug!("Applied profile '{}'", name);
+        self.applied = Some((name.to_string(), replaced));
+        Ok(())
+    }
+
+    /// Project-level settings as written to config.yaml
+    ///
+    /// Values overridden by the applied profile are saved back into that
+    /// profile, and unchanged user-level defaults are left out.
+    fn to_yaml(&self) -> BasecampResult<Value> {
+        let mut value = serde_yaml::to_value(self.stored())?;
+        template::restore(&mut value, &self.expanded);
+        if let Value::Mapping(mapping) = &mut value {
+            strip_defaults(mapping, &self.inherited);
+        }
+        Ok(value)
+    }
+
+    /// Settings with the top-level values the applied profile replaced
+    fn stored(&self) -> GitConfig {
+        let mut stored = self.clone();
+        if let Some((name, replaced)) = stored.applied.take() {
+            let profile = stored.profiles.entry(name).or_default();
+            if let Some(url) = replaced.github_url {
+                profile.github_url = Some(mem::replace(&mut stored.github_url, url));
+            }
+            if let Some(key) = replaced.ssh_key {
+                profile.ssh_key = mem::replace(&mut stored.ssh_key, key);
+            }
+            if let Some(update) = replaced.update {
+                profile.update = Some(mem::replace(&mut stored.update, update));
+            }
+        }
+        stored
+    }
+}
+
+/// Named task definition (e.g. `build`, `test`, `start`)
+///
+/// The command run in a repository is the most specific one available:
+/// repository override, then codebase override (or the override of a group
+/// containing the codebase), then the default command.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TaskConfig {
+    /// Default command for every repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+
+    /// Per-codebase command overrides
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub codebases: BTreeMap<String, String>,
+
+    /// Per-repository command overrides
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub repos: BTreeMap<String, String>,
+}
+
+impl TaskConfig {
+    /// Resolve the command to run for a repository of a codebase
+    pub fn command_for(&self, codebase: &str, repo: &str) -> Option<&str> {
+        let mut groups = std::iter::successors(Some(codebase), |name| {
+            name.rsplit_once('/').map(|(group, _)| group)
+        });
+        self.repos
+            .get(repo)
+            .or_else(|| groups.find_map(|name| self.codebases.get(name)))
+            .or(self.command.as_ref())
+            .map(|c| c.as_str())
+    }
+}
+
+/// Value of an environment variable of a codebase
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum EnvValue {
+    /// Secret printed by a command (e.g. `pass show api/token`), run when the value is needed
+    Command { command: String },
+    /// Plain value: a string, number or boolean
+    Value(Value),
+}
+
+/// Files `install` writes the environment of a codebase into, in each of its repositories
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvFile {
+    /// `.env`, read by dotenv libraries and docker compose
+    Dotenv,
+    /// `.envrc`, loaded by direnv once allowed
+    Direnv,
+}
+
+impl EnvFile {
+    /// Name of the file in a repository
+    pub fn file_name(self) -> &'static str {
+        match self {
+            EnvFile::Dotenv => ".env",
+            EnvFile::Direnv => ".envrc",
+        }
+    }
+}
+
+/// Settings of a repository, shared by every codebase listing it
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct RepoConfig {
+    /// Keep separate clones when the repository is listed in several codebases
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_duplicate: bool,
+
+    /// Clone once under `.basecamp/shared/` and link it into every codebase listing it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shared: bool,
+
+    /// Repository this one is a fork of (`owner/name` on the same host, or a URL),
+    /// added to clones as the `upstream` remote
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<String>,
+
+    /// Additional remotes of clones (mirrors, backups) by name, as `owner/name` or URLs
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub remotes: BTreeMap<String, String>,
+
+    /// Revision clones are pinned to (a tag, a commit SHA or a branch), checked out detached
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+
+    /// Directories of a monorepo checked out in clones (cone-mode sparse checkout)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sparse: Vec<String>,
+
+    /// Name of the directory clones land in, when it differs from the repository name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+
+    /// Git config of clones, over the `git` settings of config.yaml
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub git: BTreeMap<String, Value>,
+
+    /// Clone before (`high`) or after (`low`) the other repositories
+    #[serde(default, skip_serializing_if = "Priority::is_default")]
+    pub priority: Priority,
+}
+
+impl RepoConfig {
+    /// Whether no setting differs from the default
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 /// Codebases configuration structure
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct CodebasesConfig {
     /// Map of codebase names to repository lists
-    #[serde(default)]
-    pub codebases: HashMap<String, Vec<String>>,
+    ///
+    /// Codebases nested in groups are named `group/codebase`.
+    #[serde(default, with = "codebase_tree")]
+    pub codebases: BTreeMap<String, Vec<String>>,
+
+    /// Map of task names to their definitions
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tasks: BTreeMap<String, TaskConfig>,
+
+    /// Environment variables of codebases (or groups), by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, BTreeMap<String, EnvValue>>,
+
+    /// Map of repository names to the repositories they depend on
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub depends_on: BTreeMap<String, Vec<String>>,
+
+    /// Map of repository names to their settings
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub repositories: BTreeMap<String, RepoConfig>,
+}
+
+impl CodebasesConfig {
+    /// Directory names of repositories with a `dir` setting
+    pub fn repo_dirs(&self) -> BTreeMap<String, String> {
+        self.repositories
+            .iter()
+            .filter_map(|(repo, settings)| Some((repo.clone(), settings.dir.clone()?)))
+            .collect()
+    }
 }
 
 /// Configuration structure for BaseCamp
@@ -31,122 +746,355 @@ pub struct Config {
     pub git_config: GitConfig,
     /// Codebases configuration
     pub codebases_config: CodebasesConfig,
+    /// Workspace the configuration is loaded from and saved to
+    pub workspace: Workspace,
 }
 
 impl Config {
-    /// Create a new empty configuration
+    /// Create a new empty configuration for the default workspace
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            workspace: Workspace::current(),
+            ..Self::default()
+        }
     }
 
-    /// Get path to .basecamp directory
+    /// Operate on the workspace in `dir` instead of the current directory
+    ///
+    /// `dir` may also point at its `.basecamp` directory.
+    pub fn set_workspace_dir(dir: Option<PathBuf>) {
+        *WORKSPACE_DIR.lock().unwrap() = dir.map(|dir| Workspace::new(dir).root().to_path_buf());
+    }
+
+    /// Get path to the workspace root, relative to the current directory by default
+    pub fn get_workspace_dir() -> PathBuf {
+        WORKSPACE_DIR.lock().unwrap().clone().unwrap_or_default()
+    }
+
+    /// Get the absolute path to the workspace root
+    pub fn get_workspace_root() -> BasecampResult<PathBuf> {
+        Workspace::current().absolute_root()
+    }
+
+    /// Get path to .basecamp directory of the default workspace
     pub fn get_basecamp_dir() -> PathBuf {
-        PathBuf::from(".basecamp")
+        Workspace::current().basecamp_dir()
     }
 
-    /// Get path to config.yaml file
+    /// Get path to config.yaml file of the default workspace
     pub fn get_config_path() -> PathBuf {
-        Self::get_basecamp_dir().join("config.yaml")
+        Workspace::current().config_path()
     }
 
-    /// Get path to codebases.yaml file
+    /// Get path to codebases.yaml file of the default workspace
     pub fn get_codebases_path() -> PathBuf {
-        Self::get_basecamp_dir().join("codebases.yaml")
+        Workspace::current().codebases_path()
     }
 
-    /// Ensure the .basecamp directory exists
-    pub fn ensure_basecamp_dir() -> BasecampResult<()> {
-        let dir = Self::get_basecamp_dir();
-        if !dir.exists() {
-            debug!("Creating .basecamp directory at {:?}", dir);
-            create_dir_all(&dir)?;
+    /// Get path to the user-level BaseCamp directory
+    ///
+    /// `$XDG_CONFIG_HOME/basecamp`, or `~/.config/basecamp`.
+    pub fn get_user_config_dir() -> Option<PathBuf> {
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| Self::home_dir().map(|home| home.join(".config")))?;
+        Some(config_home.join("basecamp"))
+    }
+
+    /// Get the home directory of the current user
+    ///
+    /// `$HOME` when set (also by Git Bash on Windows), then `%USERPROFILE%`, then
+    /// the platform's own lookup.
+    pub fn home_dir() -> Option<PathBuf> {
+        ["HOME", "USERPROFILE"]
+            .into_iter()
+            .filter_map(env::var_os)
+            .find(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(dirs::home_dir)
+    }
+
+    /// Expand a leading `~/` (or `~\`) to the home directory
+    pub fn expand_home(path: &Path) -> PathBuf {
+        let rest = path.to_str().and_then(|text| match text {
+            "~" => Some(""),
+            _ => text.strip_prefix("~/").or_else(|| text.strip_prefix("~\\")),
+        });
+        match (rest, Self::home_dir()) {
+            (Some(rest), Some(home)) => home.join(rest),
+            _ => path.to_path_buf(),
         }
-        Ok(())
     }
 
-    /// Load configuration from the .basecamp directory files
-    pub fn load(_: &Path) -> BasecampResult<Self> {
+    /// Get path to the user-level config.yaml holding defaults for every project
+    pub fn get_user_config_path() -> Option<PathBuf> {
+        Self::get_user_config_dir().map(|dir| dir.join("config.yaml"))
+    }
+
+    /// Ensure the .basecamp directory of the default workspace exists
+    pub fn ensure_basecamp_dir() -> BasecampResult<()> {
+        Workspace::current().ensure_basecamp_dir()
+    }
+
+    /// Show changes to the configuration files before saving them, only
+    /// saving once they're confirmed (`--diff`)
+    pub fn set_preview_changes(enabled: bool) {
+        PREVIEW_CHANGES.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Select the profile to apply on load (from `--profile` or `BASECAMP_PROFILE`)
+    pub fn set_profile_override(profile: Option<String>) {
+        *PROFILE_OVERRIDE.lock().unwrap() = profile;
+    }
+
+    /// Load configuration from the .basecamp directory of the workspace at `root`
+    ///
+    /// An empty `root` loads the default workspace (`--config-dir`, or else the
+    /// current directory). The profile given with `--profile`, or else the one
+    /// selected in config.yaml, is applied on top of the top-level settings.
+    pub fn load(root: &Path) -> BasecampResult<Self> {
+        let workspace = if root.as_os_str().is_empty() {
+            Workspace::current()
+        } else {
+            Workspace::new(root)
+        };
+        Self::load_workspace(workspace)
+    }
+
+    /// Load the configuration of a workspace
+    pub fn load_workspace(mut workspace: Workspace) -> BasecampResult<Self> {
         // Try to load from the configuration files
-        debug!("Loading configuration from .basecamp directory");
+        debug!("Loading configuration from {:?}", workspace.basecamp_dir());
         
-        // Load git config
-        let git_config = if Self::get_config_path().exists() {
-            let content = fs::read_to_string(Self::get_config_path())?;
-            serde_yaml::from_str(&content)?
+        // Load git config, reporting invalid values at the line they occur
+        let config_path = workspace.config_path();
+        let mut project: Mapping = if config_path.exists() {
+            let content = fs::read_to_string(&config_path)?;
+            validate::parse::<Option<GitConfig>>(&config_path, &content)?;
+            validate::parse::<Option<Mapping>>(&config_path, &content)?.unwrap_or_default()
         } else {
-            return Err(BasecampError::FileNotFound(Self::get_config_path()));
+            return Err(BasecampError::FileNotFound(config_path));
         };
+
+        // Fill in settings the project doesn't define from the user-level config
+        let mut inherited = Mapping::new();
+        if let Some(path) = Self::get_user_config_path().filter(|path| path.exists()) {
+            debug!("Loading user defaults from {:?}", path);
+            let content = fs::read_to_string(&path)?;
+            validate::parse::<Option<GitConfig>>(&path, &content)?;
+            if let Some(defaults) = validate::parse::<Option<Mapping>>(&path, &content)? {
+                inherited = merge_defaults(&mut project, defaults);
+            }
+        }
+
+        // Expand placeholders, `{org}` being the owner of the github_url in use
+        let profile = PROFILE_OVERRIDE
+            .lock()
+            .unwrap()
+            .clone()
+            .or_else(|| project.get("profile").and_then(Value::as_str).map(str::to_string));
+        let github_url = profile
+            .as_deref()
+            .and_then(|name| project.get("profiles")?.get(name)?.get("github_url"))
+            .or_else(|| project.get("github_url"))
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let vars = Vars {
+            org: org_of(&template::expand(github_url, &Vars::default())),
+            ..Vars::default()
+        };
+        let mut project = Value::Mapping(project);
+        let expanded = template::expand_value(&mut project, &vars);
+
+        let mut git_config: GitConfig = serde_yaml::from_value(project)?;
+        git_config.inherited = inherited;
+        git_config.expanded = expanded;
         
         // Load codebases config
-        let codebases_config = if Self::get_codebases_path().exists() {
-            let content = fs::read_to_string(Self::get_codebases_path())?;
-            serde_yaml::from_str(&content)?
+        let codebases_path = workspace.codebases_path();
+        let codebases_config = if codebases_path.exists() {
+            let content = fs::read_to_string(&codebases_path)?;
+            validate::parse(&codebases_path, &content)?
         } else {
             CodebasesConfig::default()
         };
+        validate::check_paths(&codebases_path, &codebases_config)?;
+
+        // Apply the selected profile
+        if let Some(profile) = profile {
+            git_config.apply_profile(&profile)?;
+        }
+        // Process-wide settings are all set from every loaded workspace, so none is left
+        // over from a workspace loaded before (`serve`, library callers)
+        GitRepo::set_ssh_key(git_config.ssh_key.clone());
+        auth::set_providers(git_config.auth.clone());
+        auth::set_token_scope(git_config.token_scope());
+        GitRepo::set_timeouts(git_config.timeouts.to_network_timeouts());
+        GitRepo::set_proxy(git_config.proxy.clone());
+        backup::set_limit(git_config.backups);
+        git_config.tls.apply(&workspace)?;
+        backend::set_backend(git_config.backend)?;
+        // Combined with --offline and --no-color, which the workspace can't turn off
+        GitRepo::set_workspace_offline(git_config.offline);
+        UI::set_workspace_color(git_config.color != Some(false));
+        git_config.ui.apply(&workspace)?;
         
+        workspace.set_dirs(codebases_config.repo_dirs());
         let config = Self {
             git_config,
             codebases_config,
+            workspace,
         };
         
         info!("Configuration loaded successfully");
         Ok(config)
     }
 
-    /// Save configuration to the .basecamp directory files
-    pub fn save(&self, _: &Path) -> BasecampResult<()> {
+    /// Save configuration to the .basecamp directory of the workspace at `root`
+    ///
+    /// An empty `root` saves to the workspace the configuration was loaded from.
+    pub fn save(&self, root: &Path) -> BasecampResult<()> {
+        let workspace = if root.as_os_str().is_empty() {
+            self.workspace.clone()
+        } else {
+            Workspace::new(root)
+        };
+
         // Ensure the directory exists
-        Self::ensure_basecamp_dir()?;
-        
-        // Save each file independently
-        let config_result = self.save_config();
-        let codebases_result = self.save_codebases();
+        workspace.ensure_basecamp_dir()?;
+
+        // Both files are previewed together, then saved independently
+        let config_value = self.git_config.to_yaml()?;
+        let codebases_value = serde_yaml::to_value(&self.codebases_config)?;
+        Self::confirm_changes(&[
+            (workspace.config_path(), yaml_edit::render(&workspace.config_path(), &config_value)?),
+            (workspace.codebases_path(), yaml_edit::render(&workspace.codebases_path(), &codebases_value)?),
+        ])?;
+        let config_result = Self::write_yaml(workspace.config_path(), &config_value);
+        let codebases_result = Self::write_yaml(workspace.codebases_path(), &codebases_value);
         
         // Return any error that occurred
         config_result?;
         codebases_result?;
         
         // Verify files exist after saving
-        if !Self::get_config_path().exists() {
-            return Err(BasecampError::FileNotFound(Self::get_config_path()));
+        if !workspace.config_path().exists() {
+            return Err(BasecampError::FileNotFound(workspace.config_path()));
         }
         
-        if !Self::get_codebases_path().exists() {
-            return Err(BasecampError::FileNotFound(Self::get_codebases_path()));
+        if !workspace.codebases_path().exists() {
+            return Err(BasecampError::FileNotFound(workspace.codebases_path()));
         }
         
         Ok(())
     }
     
     /// Save git configuration to config.yaml
+    ///
+    /// Only the changed lines are rewritten, so comments and key order survive.
     pub fn save_config(&self) -> BasecampResult<()> {
-        Self::ensure_basecamp_dir()?;
-        let config_path = Self::get_config_path();
-        debug!("Saving git configuration to {:?}", config_path);
-        
-        let yaml = serde_yaml::to_string(&self.git_config)?;
-        let mut file = File::create(config_path)?;
-        file.write_all(yaml.as_bytes())?;
-        
-        info!("Git configuration saved successfully");
-        Ok(())
+        self.workspace.ensure_basecamp_dir()?;
+        let value = self.git_config.to_yaml()?;
+        let path = self.workspace.config_path();
+        Self::confirm_changes(&[(path.clone(), yaml_edit::render(&path, &value)?)])?;
+        Self::write_yaml(path, &value)
     }
-    
+
     /// Save codebases configuration to codebases.yaml
+    ///
+    /// Only the changed lines are rewritten, so comments and key order survive.
     pub fn save_codebases(&self) -> BasecampResult<()> {
-        Self::ensure_basecamp_dir()?;
-        let codebases_path = Self::get_codebases_path();
-        debug!("Saving codebases configuration to {:?}", codebases_path);
-        
-        let yaml = serde_yaml::to_string(&self.codebases_config)?;
-        let mut file = File::create(codebases_path)?;
-        file.write_all(yaml.as_bytes())?;
-        
-        info!("Codebases configuration saved successfully");
+        self.workspace.ensure_basecamp_dir()?;
+        let value = serde_yaml::to_value(&self.codebases_config)?;
+        let path = self.workspace.codebases_path();
+        Self::confirm_changes(&[(path.clone(), yaml_edit::render(&path, &value)?)])?;
+        Self::write_yaml(path, &value)
+    }
+
+    fn write_yaml(path: PathBuf, value: &Value) -> BasecampResult<()> {
+        debug!("Saving configuration to {:?}", path);
+        yaml_edit::write(&path, value)?;
+        events::emit(Event::ConfigSaved { path: path.clone() });
+        info!("Saved {:?}", path);
         Ok(())
     }
 
+    /// With `--diff`, print the changes about to be written and ask to go ahead
+    ///
+    /// `files` holds the new content of each file, `None` when it doesn't change.
+    /// Declining fails, leaving every file as it was.
+    fn confirm_changes(files: &[(PathBuf, Option<String>)]) -> BasecampResult<()> {
+        if !PREVIEW_CHANGES.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let mut changed = false;
+        for (path, content) in files {
+            let Some(content) = content else {
+                continue;
+            };
+            let label = path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned());
+            UI::diff(&label, &fs::read_to_string(path).unwrap_or_default(), content);
+            changed = true;
+        }
+
+        if changed && !UI::confirm("Save these changes?", false)? {
+            return Err(BasecampError::CommandFailed(
+                "Changes to the configuration were not saved".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Rewrite codebases.yaml with codebases and repositories sorted by name
+    ///
+    /// Comments move with the codebases and repositories they describe.
+    /// Returns whether the file changed.
+    pub fn sort_codebases(&mut self) -> BasecampResult<bool> {
+        for repos in self.codebases_config.codebases.values_mut() {
+            repos.sort();
+        }
+        for dependencies in self.codebases_config.depends_on.values_mut() {
+            dependencies.sort();
+        }
+
+        self.workspace.ensure_basecamp_dir()?;
+        let codebases_path = self.workspace.codebases_path();
+        let value = serde_yaml::to_value(&self.codebases_config)?;
+        let Some(yaml) = yaml_edit::render_sorted(&codebases_path, &value, &["codebases", "depends_on"])? else {
+            return Ok(false);
+        };
+        Self::confirm_changes(&[(codebases_path.clone(), Some(yaml.clone()))])?;
+
+        debug!("Writing sorted codebases configuration to {:?}", codebases_path);
+        backup::keep(&codebases_path);
+        fs::write(&codebases_path, yaml)?;
+        events::emit(Event::ConfigSaved { path: codebases_path });
+        Ok(true)
+    }
+
+    /// Number of parallel git operations: `-p`, then `parallel` in config.yaml
+    pub fn parallel(&self, requested: Option<usize>) -> usize {
+        requested
+            .or(self.git_config.parallel)
+            .unwrap_or(DEFAULT_PARALLEL)
+            .max(1)
+    }
+
+    /// Size in bytes above which deleting needs a typed confirmation, `None` when disabled
+    pub fn confirm_delete_bytes(&self) -> Option<u64> {
+        match self.git_config.confirm_delete_mib.unwrap_or(DEFAULT_CONFIRM_DELETE_MIB) {
+            0 => None,
+            mib => Some(mib * 1024 * 1024),
+        }
+    }
+
+    /// Name of the remote repositories are cloned from: `remote_name` in config.yaml, `origin` otherwise
+    pub fn remote_name(&self) -> &str {
+        self.git_config.remote_name.as_deref().unwrap_or(DEFAULT_REMOTE)
+    }
+
     /// Check if GitHub URL is configured
     pub fn has_github_url(&self) -> bool {
         !self.git_config.github_url.is_empty()
@@ -175,6 +1123,20 @@ impl Config {
 
     /// Add repositories to a codebase
     pub fn add_repositories(&mut self, codebase: &str, repos: &[String]) -> BasecampResult<Vec<String>> {
+        // Names become paths in the workspace, so they can't lead out of it
+        if !validate::is_valid_codebase_name(codebase) {
+            return Err(BasecampError::Generic(format!(
+                "'{}' is not a valid codebase name; use directory names separated by '/', without '..'",
+                codebase.escape_debug()
+            )));
+        }
+        if let Some(repo) = repos.iter().find(|repo| !validate::is_valid_repo_name(repo)) {
+            return Err(BasecampError::Generic(format!(
+                "'{}' is not a valid repository name; use letters, digits, '-', '_' and '.'",
+                repo.escape_debug()
+            )));
+        }
+
         let codebase_repos = self.codebases_config.codebases.entry(codebase.to_string()).or_default();
         let mut added_repos = Vec::new();
         let mut skipped_repos = Vec::new();
@@ -214,6 +1176,109 @@ impl Config {
         Ok(())
     }
 
+    /// Rename a repository in every codebase listing it, keeping its position and settings
+    ///
+    /// Returns the codebases that listed it.
+    pub fn rename_repository(&mut self, old: &str, new: &str) -> Vec<String> {
+        let mut renamed = Vec::new();
+        for (codebase, repos) in self.codebases_config.codebases.iter_mut() {
+            let Some(position) = repos.iter().position(|repo| repo == old) else {
+                continue;
+            };
+            if repos.iter().any(|repo| repo == new) {
+                repos.remove(position);
+            } else {
+                repos[position] = new.to_string();
+            }
+            renamed.push(codebase.clone());
+        }
+
+        if let Some(settings) = self.codebases_config.repositories.remove(old) {
+            self.codebases_config.repositories.entry(new.to_string()).or_insert(settings);
+            self.workspace.set_dirs(self.codebases_config.repo_dirs());
+        }
+        renamed
+    }
+
+    /// Settings of a repository (the defaults when it has none)
+    pub fn repo_config(&self, repo: &str) -> RepoConfig {
+        self.codebases_config
+            .repositories
+            .get(repo)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Pin a repository to a revision
+    pub fn set_rev(&mut self, repo: &str, rev: &str) {
+        self.codebases_config
+            .repositories
+            .entry(repo.to_string())
+            .or_default()
+            .rev = Some(rev.to_string());
+    }
+
+    /// Values of the placeholders of configuration values used outside of a repository
+    pub fn template_vars(&self) -> Vars {
+        Vars {
+            org: org_of(&self.git_config.github_url),
+            ..Vars::default()
+        }
+    }
+
+    /// Remotes a clone of a repository has besides the clone remote: `upstream`
+    /// and its `remotes`, with their URLs
+    ///
+    /// Remotes aren't tied to a codebase, so `{codebase}` isn't expanded in them.
+    pub fn extra_remotes(&self, repo: &str) -> Vec<(String, String)> {
+        let settings = self.repo_config(repo);
+        let github_url = &self.git_config.github_url;
+        let vars = Vars {
+            repo: Some(repo.to_string()),
+            ..self.template_vars()
+        };
+        settings
+            .upstream
+            .iter()
+            .map(|upstream| ("upstream".to_string(), upstream))
+            .chain(settings.remotes.iter().map(|(name, url)| (name.clone(), url)))
+            .map(|(name, url)| (name, GitRepo::expand_remote_url(github_url, &template::expand(url, &vars))))
+            .collect()
+    }
+
+    /// Change the settings of a repository, dropping entries left at their defaults
+    pub fn update_repo_config(&mut self, repo: &str, update: impl FnOnce(&mut RepoConfig)) {
+        let repositories = &mut self.codebases_config.repositories;
+        let settings = repositories.entry(repo.to_string()).or_default();
+        update(settings);
+        if settings.is_default() {
+            repositories.remove(repo);
+        }
+    }
+
+    /// Repositories listed in several codebases, neither shared nor with
+    /// `allow_duplicate`, with those codebases
+    pub fn duplicate_repositories(&self) -> Vec<(String, Vec<String>)> {
+        let mut listed: BTreeMap<&String, Vec<String>> = BTreeMap::new();
+        for (codebase, repos) in &self.codebases_config.codebases {
+            for repo in repos {
+                let codebases = listed.entry(repo).or_default();
+                if !codebases.contains(codebase) {
+                    codebases.push(codebase.clone());
+                }
+            }
+        }
+
+        listed
+            .into_iter()
+            .filter(|(repo, codebases)| {
+                let settings = self.repo_config(repo);
+                codebases.len() > 1 && !settings.allow_duplicate && !settings.shared
+            })
+            .map(|(repo, codebases)| (repo.clone(), codebases))
+            .collect()
+    }
+
     /// Get all repositories for a specific codebase
     pub fn get_repositories(&self, codebase: &str) -> BasecampResult<&Vec<String>> {
         match self.codebases_config.codebases.get(codebase) {
@@ -226,4 +1291,379 @@ impl Config {
     pub fn list_codebases(&self) -> Vec<&String> {
         self.codebases_config.codebases.keys().collect()
     }
+
+    /// Resolve a codebase name given on the command line, following aliases
+    ///
+    /// Codebases and groups take precedence over an alias of the same name.
+    pub fn resolve_codebase(&self, name: &str) -> String {
+        let known = self.codebases_config.codebases.contains_key(name) || self.is_group(name);
+        match self.git_config.aliases.get(name) {
+            Some(target) if !known => {
+                debug!("Codebase alias '{}' resolves to '{}'", name, target);
+                target.clone()
+            }
+            _ => name.to_string(),
+        }
+    }
+
+    /// Whether `name` is a group of codebases rather than a codebase
+    pub fn is_group(&self, name: &str) -> bool {
+        let prefix = format!("{}/", name.trim_end_matches('/'));
+        !self.codebases_config.codebases.contains_key(name)
+            && self.list_codebases().iter().any(|c| c.starts_with(&prefix))
+    }
+
+    /// Codebases selected by a codebase or group name, or every codebase
+    ///
+    /// A group selects all of the codebases nested under it.
+    pub fn select_codebases(&self, name: Option<&str>) -> BasecampResult<Vec<String>> {
+        let Some(name) = name else {
+            return Ok(self.list_codebases().into_iter().cloned().collect());
+        };
+
+        let name = name.trim_end_matches('/');
+        if self.codebases_config.codebases.contains_key(name) {
+            return Ok(vec![name.to_string()]);
+        }
+
+        let prefix = format!("{}/", name);
+        let members: Vec<String> = self
+            .list_codebases()
+            .into_iter()
+            .filter(|c| c.starts_with(&prefix))
+            .cloned()
+            .collect();
+        if members.is_empty() {
+            return Err(BasecampError::CodebaseNotFound(name.to_string()));
+        }
+        Ok(members)
+    }
+
+    /// Find the codebases containing a repository
+    pub fn find_repository(&self, repo: &str) -> Vec<&String> {
+        let mut codebases: Vec<&String> = self
+            .codebases_config
+            .codebases
+            .iter()
+            .filter(|(_, repos)| repos.iter().any(|r| r == repo))
+            .map(|(name, _)| name)
+            .collect();
+        codebases.sort();
+        codebases
+    }
+
+    /// Resolve the single codebase a repository belongs to
+    ///
+    /// When the repository is part of several codebases, `codebase` must be
+    /// given to disambiguate.
+    pub fn resolve_repository(&self, repo: &str, codebase: Option<&str>) -> BasecampResult<String> {
+        if let Some(codebase) = codebase {
+            let repos = self.get_repositories(codebase)?;
+            if !repos.iter().any(|r| r == repo) {
+                return Err(BasecampError::RepositoryNotFound(
+                    repo.to_string(),
+                    codebase.to_string(),
+                ));
+            }
+            return Ok(codebase.to_string());
+        }
+
+        match self.find_repository(repo).as_slice() {
+            [] => Err(BasecampError::UnknownRepository(repo.to_string())),
+            [codebase] => Ok(codebase.to_string()),
+            codebases => Err(BasecampError::Generic(format!(
+                "Repository '{}' belongs to several codebases ({}). Use --codebase to pick one.",
+                repo,
+                codebases
+                    .iter()
+                    .map(|c| c.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))),
+        }
+    }
+
+    /// Repositories a repository depends on (`depends_on` in codebases.yaml)
+    pub fn dependencies_of(&self, repo: &str) -> &[String] {
+        self.codebases_config.depends_on.get(repo).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Group repositories into dependency levels
+    ///
+    /// Every repository only depends on repositories from earlier levels, so the
+    /// repositories of a level can be processed in parallel. Dependencies on
+    /// repositories outside of `repos` are ignored.
+    pub fn dependency_levels(&self, repos: &[String]) -> BasecampResult<Vec<Vec<String>>> {
+        let mut pending: Vec<String> = Vec::new();
+        for repo in repos {
+            if !pending.contains(repo) {
+                pending.push(repo.clone());
+            }
+        }
+
+        let mut levels: Vec<Vec<String>> = Vec::new();
+        let mut done: Vec<String> = Vec::new();
+
+        while !pending.is_empty() {
+            let (ready, blocked): (Vec<String>, Vec<String>) =
+                pending.into_iter().partition(|repo| {
+                    self.codebases_config
+                        .depends_on
+                        .get(repo)
+                        .map(|deps| {
+                            deps.iter()
+                                .all(|dep| done.contains(dep) || !repos.contains(dep) || dep == repo)
+                        })
+                        .unwrap_or(true)
+                });
+
+            if ready.is_empty() {
+                return Err(BasecampError::DependencyCycle(blocked.join(", ")));
+            }
+
+            done.extend(ready.iter().cloned());
+            levels.push(ready);
+            pending = blocked;
+        }
+
+        Ok(levels)
+    }
+
+    /// Environment variables of a codebase, those of its groups included
+    ///
+    /// A codebase overrides the variables of its groups, and an inner group those of an outer one.
+    pub fn env_for(&self, codebase: &str) -> BTreeMap<String, EnvValue> {
+        let scopes: Vec<&str> = std::iter::successors(Some(codebase), |name| {
+            name.rsplit_once('/').map(|(group, _)| group)
+        })
+        .collect();
+
+        let mut env = BTreeMap::new();
+        for scope in scopes.into_iter().rev() {
+            if let Some(variables) = self.codebases_config.env.get(scope) {
+                env.extend(variables.clone());
+            }
+        }
+        env
+    }
+
+    /// Files the environment of a codebase is written to on install
+    pub fn env_files(&self) -> Vec<EnvFile> {
+        self.git_config.env_files.clone().unwrap_or_else(|| vec![EnvFile::Dotenv])
+    }
+
+    /// Absolute path of `hooks_dir`, `None` when it isn't set
+    pub fn hooks_dir(&self) -> BasecampResult<Option<PathBuf>> {
+        let Some(dir) = &self.git_config.hooks_dir else {
+            return Ok(None);
+        };
+        let path = self.workspace.absolute_root()?.join(Self::expand_home(dir));
+        if !path.is_dir() {
+            return Err(BasecampError::InvalidConfig(format!(
+                "hooks_dir: '{}' is not a directory",
+                path.display()
+            )));
+        }
+        Ok(Some(path))
+    }
+
+    /// Git config keys and values of the clone of a repository in a codebase
+    ///
+    /// The `signing` settings come first, then the `git` settings of
+    /// config.yaml and those of the repository, each overriding the previous
+    /// ones. `{codebase}` and `{repo}` are expanded in the values.
+    pub fn git_settings(&self, codebase: &str, repo: &str) -> BasecampResult<BTreeMap<String, String>> {
+        let mut settings = BTreeMap::new();
+        if let Some(signing) = &self.git_config.signing {
+            settings.insert("commit.gpgsign".to_string(), "true".to_string());
+            settings.insert("gpg.format".to_string(), signing.format.as_str().to_string());
+            if let Some(key) = &signing.key {
+                let key = match signing.format {
+                    SigningFormat::Ssh if !key.starts_with("key::") => {
+                        Self::expand_home(Path::new(key)).to_string_lossy().into_owned()
+                    }
+                    _ => key.clone(),
+                };
+                settings.insert("user.signingkey".to_string(), key);
+            }
+            if signing.tags {
+                settings.insert("tag.gpgsign".to_string(), "true".to_string());
+            }
+        }
+        flatten_git_settings("", &self.git_config.git, &mut settings)?;
+        flatten_git_settings("", &self.repo_config(repo).git, &mut settings)?;
+
+        let vars = self.template_vars().with_repo(codebase, repo);
+        Ok(settings
+            .into_iter()
+            .map(|(key, value)| (key, template::expand(&value, &vars)))
+            .collect())
+    }
+
+    /// Get a task definition by name
+    pub fn get_task(&self, name: &str) -> BasecampResult<&TaskConfig> {
+        match self.codebases_config.tasks.get(name) {
+            Some(task) => Ok(task),
+            None => Err(BasecampError::TaskNotFound(name.to_string())),
+        }
+    }
+}
+
+/// Collect git config values by their dotted key (`user: {email: ...}` is `user.email`)
+fn flatten_git_settings(
+    prefix: &str,
+    values: &BTreeMap<String, Value>,
+    settings: &mut BTreeMap<String, String>,
+) -> BasecampResult<()> {
+    for (name, value) in values {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        let value = match value {
+            Value::String(text) => text.clone(),
+            Value::Bool(flag) => flag.to_string(),
+            Value::Number(number) => number.to_string(),
+            Value::Mapping(mapping) => {
+                let nested: BTreeMap<String, Value> = serde_yaml::from_value(Value::Mapping(mapping.clone()))?;
+                flatten_git_settings(&key, &nested, settings)?;
+                continue;
+            }
+            _ => {
+                return Err(BasecampError::InvalidConfig(format!(
+                    "git: '{}' must be a string, a number or a boolean",
+                    key
+                )));
+            }
+        };
+        if !key.contains('.') {
+            return Err(BasecampError::InvalidConfig(format!(
+                "git: '{}' is not a git config key, which look like 'section.name'",
+                key
+            )));
+        }
+        settings.insert(key, value);
+    }
+    Ok(())
+}
+
+/// Add the settings of `defaults` missing from `project`, returning the added ones
+///
+/// Nested sections (e.g. `cache`, `profiles`) are merged key by key.
+fn merge_defaults(project: &mut Mapping, defaults: Mapping) -> Mapping {
+    let mut inherited = Mapping::new();
+    for (key, value) in defaults {
+        match (project.get_mut(&key), value) {
+            (None, value) => {
+                project.insert(key.clone(), value.clone());
+                inherited.insert(key, value);
+            }
+            (Some(Value::Mapping(section)), Value::Mapping(nested)) => {
+                let nested = merge_defaults(section, nested);
+                if !nested.is_empty() {
+                    inherited.insert(key, Value::Mapping(nested));
+                }
+            }
+            (Some(_), _) => {}
+        }
+    }
+    inherited
+}
+
+/// Owner of the repositories under a base URL, the value of `{org}`
+fn org_of(github_url: &str) -> Option<String> {
+    GitRepo::remote_owner(&GitRepo::build_repo_url(github_url, "repo")).map(|(_, owner)| owner)
+}
+
+/// Remove the inherited settings that still have their user-level value
+fn strip_defaults(stored: &mut Mapping, inherited: &Mapping) {
+    for (key, value) in inherited {
+        match (stored.get_mut(key), value) {
+            (Some(Value::Mapping(section)), Value::Mapping(nested)) => {
+                strip_defaults(section, nested);
+                if section.is_empty() {
+                    stored.shift_remove(key);
+                }
+            }
+            (Some(current), _) if current == value => {
+                stored.shift_remove(key);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Codebase groups in codebases.yaml
+///
+/// Groups are nested mappings in the file and flattened into `group/codebase`
+/// names in memory, which is also where their repositories are cloned.
+mod codebase_tree {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de, ser};
+    use serde_yaml::{Mapping, Value};
+
+    pub fn serialize<S: Serializer>(
+        codebases: &BTreeMap<String, Vec<String>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut tree = Mapping::new();
+        for (name, repos) in codebases {
+            let mut parts: Vec<&str> = name.split('/').collect();
+            let leaf = parts.pop().unwrap_or_default();
+
+            let mut group = &mut tree;
+            for part in parts {
+                let Value::Mapping(next) = group
+                    .entry(Value::from(part))
+                    .or_insert_with(|| Value::Mapping(Mapping::new()))
+                else {
+                    return Err(ser::Error::custom(format!(
+                        "codebase '{}' is nested in '{}', which is a codebase",
+                        name, part
+                    )));
+                };
+                group = next;
+            }
+            group.insert(Value::from(leaf), Value::from(repos.clone()));
+        }
+        tree.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<String, Vec<String>>, D::Error> {
+        let mut codebases = BTreeMap::new();
+        if let Some(tree) = Option::<Mapping>::deserialize(deserializer)? {
+            flatten("", tree, &mut codebases).map_err(de::Error::custom)?;
+        }
+        Ok(codebases)
+    }
+
+    fn flatten(
+        prefix: &str,
+        group: Mapping,
+        codebases: &mut BTreeMap<String, Vec<String>>,
+    ) -> Result<(), String> {
+        for (key, value) in group {
+            let Value::String(name) = key else {
+                return Err(format!("codebase names must be strings, found {:?}", key));
+            };
+            let name = if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) };
+
+            match value {
+                Value::Mapping(group) => flatten(&name, group, codebases)?,
+                Value::Null => {
+                    codebases.insert(name, Vec::new());
+                }
+                value => {
+                    let repos = Vec::<String>::deserialize(value)
+                        .map_err(|e| format!("codebase '{}': {}", name, e))?;
+                    codebases.insert(name, repos);
+                }
+            }
+        }
+        Ok(())
+    }
 }
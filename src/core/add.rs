@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+
+use log::info;
+use serde::Serialize;
+
+use super::ProgressSink;
+use super::install::{self, ConflictPolicy, InstallOutcome, InstallSettings, RepoResult};
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::journal::Operation;
+use crate::oplog::OperationLog;
+
+/// Result of adding repositories to a codebase
+#[derive(Debug, Serialize)]
+pub struct AddReport {
+    /// Codebase the repositories were added to (aliases resolved)
+    pub codebase: String,
+    /// Repositories newly added to the configuration
+    pub added: Vec<String>,
+    /// Repositories the codebase already had
+    pub existing: Vec<String>,
+    /// Added repositories left out of the configuration because they failed to clone
+    pub rolled_back: Vec<String>,
+    /// Clones of the added repositories
+    pub install: InstallOutcome,
+}
+
+/// Add repositories to a codebase, clone them and save the configuration
+///
+/// The additions are staged in memory while cloning, then only those that
+/// cloned are saved (all of them with `keep_failed`), so a failed clone never
+/// leaves an entry behind. The files are read again just before saving and
+/// the additions applied to them, keeping changes another basecamp made in the
+/// meantime. In offline mode the configuration is updated and cloning is left
+/// to a later `install`.
+pub fn add(
+    config: &mut Config,
+    codebase: &str,
+    repositories: &[String],
+    keep_failed: bool,
+    log: &OperationLog,
+    progress: &dyn ProgressSink,
+) -> BasecampResult<AddReport> {
+    if !config.has_github_url() {
+        return Err(BasecampError::GitHubUrlNotConfigured);
+    }
+
+    let codebase = config.resolve_codebase(codebase);
+    if config.is_group(&codebase) {
+        return Err(BasecampError::Generic(format!(
+            "'{}' is a group of codebases. Add repositories to one of its codebases (e.g. '{}/<name>').",
+            codebase, codebase
+        )));
+    }
+
+    // Recorded for `basecamp undo`, with the clones made
+    let journal = Operation::begin(&config.workspace, "add");
+    let added = config.add_repositories(&codebase, repositories)?;
+
+    let existing = repositories
+        .iter()
+        .filter(|repo| !added.contains(repo))
+        .cloned()
+        .collect();
+
+    // Install only the new repositories, with the parallelism of install without -p
+    let jobs: Vec<(String, String)> = added
+        .iter()
+        .map(|repo| (codebase.clone(), repo.clone()))
+        .collect();
+    let settings = InstallSettings {
+        parallel: config.parallel(None),
+        fail_fast: false,
+        update: None,
+        conflicts: ConflictPolicy::default(),
+    };
+    let report = install::install(config, &jobs, settings, log, progress);
+
+    for cloned in report.repositories.iter().filter(|r| r.result == RepoResult::Cloned) {
+        journal.record_created(&config.workspace.repo_path(&cloned.codebase, &cloned.repo));
+    }
+
+    let rolled_back: Vec<String> = if keep_failed {
+        Vec::new()
+    } else {
+        report.failures().map(|r| r.repo.clone()).collect()
+    };
+    let kept: Vec<String> = added.iter().filter(|repo| !rolled_back.contains(repo)).cloned().collect();
+    let saved = persist(config, &codebase, &kept, &rolled_back);
+    journal.finish(&format!("add {} to '{}'", kept.join(", "), codebase));
+    saved?;
+    info!("Added repositories {:?} to codebase '{}'", kept, codebase);
+
+    Ok(AddReport {
+        codebase,
+        added,
+        existing,
+        rolled_back,
+        install: report,
+    })
+}
+
+/// Save the repositories `kept` as added to a codebase
+///
+/// The additions are applied to the files as they are now. A configuration
+/// that wasn't saved yet is saved as staged, without the `rolled_back` ones.
+fn persist(config: &mut Config, codebase: &str, kept: &[String], rolled_back: &[String]) -> BasecampResult<()> {
+    match Config::load_workspace(config.workspace.clone()) {
+        Ok(mut latest) => {
+            if kept.is_empty() {
+                return Ok(());
+            }
+            latest.add_repositories(codebase, kept)?;
+            latest.save(&PathBuf::new())?;
+            *config = latest;
+        }
+        Err(BasecampError::FileNotFound(_)) => {
+            config.remove_repositories(codebase, rolled_back)?;
+            config.save(&PathBuf::new())?;
+        }
+        Err(e) => return Err(e),
+    }
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use std::fs;
+use std::path::Path;
+
+use log::debug;
+use serde_yaml::Value;
+
+use crate::config::{Config, EnvFile, EnvValue};
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::shell;
+use crate::template::{self, Vars};
+
+/// First line of the files `install` writes; files without it are never overwritten
+const GENERATED_HEADER: &str = "# Generated by basecamp from codebases.yaml, changes are overwritten by install";
+
+/// An environment variable of a codebase, ready to be exported
+#[derive(Debug, Clone)]
+pub struct Variable {
+    pub name: String,
+    pub value: String,
+    /// Read from a command, so its value is never expanded
+    secret: bool,
+}
+
+/// Environment variables of a codebase, with secrets read from their commands
+///
+/// Placeholders are expanded, except `{repo}`, which [`for_repo`] fills in for
+/// each repository of the codebase.
+pub fn resolve(config: &Config, codebase: &str) -> BasecampResult<Vec<Variable>> {
+    let vars = Vars {
+        codebase: Some(codebase.to_string()),
+        ..config.template_vars()
+    };
+
+    config
+        .env_for(codebase)
+        .into_iter()
+        .map(|(name, value)| {
+            let (value, secret) = match value {
+                EnvValue::Command { command } => (read_secret(&name, &template::expand(&command, &vars))?, true),
+                EnvValue::Value(Value::String(text)) => (template::expand(&text, &vars), false),
+                EnvValue::Value(Value::Number(number)) => (number.to_string(), false),
+                EnvValue::Value(Value::Bool(flag)) => (flag.to_string(), false),
+                EnvValue::Value(Value::Null) => (String::new(), false),
+                EnvValue::Value(_) => {
+                    return Err(BasecampError::InvalidConfig(format!(
+                        "env variable '{}' of '{}' must be a value or '{{command: ...}}'",
+                        name, codebase
+                    )));
+                }
+            };
+            Ok(Variable { name, value, secret })
+        })
+        .collect()
+}
+
+/// The environment of a codebase as seen by one of its repositories
+pub fn for_repo(env: &[Variable], repo: &str) -> Vec<Variable> {
+    let vars = Vars {
+        repo: Some(repo.to_string()),
+        ..Vars::default()
+    };
+    env.iter()
+        .map(|variable| Variable {
+            value: if variable.secret {
+                variable.value.clone()
+            } else {
+                template::expand(&variable.value, &vars)
+            },
+            ..variable.clone()
+        })
+        .collect()
+}
+
+/// Run the command of a secret, taking its output without the final newline
+fn read_secret(name: &str, command: &str) -> BasecampResult<String> {
+    debug!("Reading {} from '{}'", name, command);
+    let output = shell::command(command)
+        .output()
+        .map_err(|e| BasecampError::Generic(format!("Failed to start the command of {}: {}", name, e)))?;
+    if !output.status.success() {
+        return Err(BasecampError::Generic(format!(
+            "The command of {} failed: {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim_end_matches(['\n', '\r']).to_string())
+}
+
+/// `export` lines for POSIX shells, to `eval` or source
+pub fn shell_exports(env: &[Variable]) -> String {
+    env.iter()
+        .map(|variable| format!("export {}={}\n", variable.name, shell::quote(&variable.value)))
+        .collect()
+}
+
+/// Lines of a `.env` file, values double-quoted with `\`, `"`, `$` and newlines escaped
+fn dotenv_lines(env: &[Variable]) -> String {
+    env.iter()
+        .map(|variable| {
+            let value = variable
+                .value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('$', "\\$")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"\n", variable.name, value)
+        })
+        .collect()
+}
+
+/// Write the environment files of a repository and keep them out of git status
+///
+/// Files that exist without the generated header are left alone and reported
+/// in the error, after the other files were written.
+pub fn write_files(repo_path: &Path, files: &[EnvFile], env: &[Variable]) -> BasecampResult<()> {
+    let mut kept = Vec::new();
+    for file in files {
+        let path = repo_path.join(file.file_name());
+        if fs::read_to_string(&path).is_ok_and(|content| !content.starts_with(GENERATED_HEADER)) {
+            kept.push(file.file_name());
+            continue;
+        }
+
+        let body = match file {
+            EnvFile::Dotenv => dotenv_lines(env),
+            EnvFile::Direnv => shell_exports(env),
+        };
+        fs::write(&path, format!("{}\n{}", GENERATED_HEADER, body))?;
+        GitRepo::exclude(repo_path, &format!("/{}", file.file_name()))?;
+        debug!("Wrote {:?}", path);
+    }
+
+    if !kept.is_empty() {
+        return Err(BasecampError::Generic(format!(
+            "{} of {} weren't written by basecamp, so they were left as they are",
+            kept.join(" and "),
+            repo_path.display()
+        )));
+    }
+    Ok(())
+}
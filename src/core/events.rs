@@ -0,0 +1,184 @@
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+use super::install::SkipReason;
+
+/// Callback receiving every event
+type Listener = Arc<dyn Fn(&Event) + Send + Sync>;
+
+/// Subscribed listeners with their ids, and the run they are limited to
+static LISTENERS: Mutex<Vec<(usize, Option<RunId>, Listener)>> = Mutex::new(Vec::new());
+
+/// Id of the next subscription
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Id of the next run
+static NEXT_RUN: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Run the events emitted on this thread belong to
+    static CURRENT_RUN: Cell<Option<RunId>> = const { Cell::new(None) };
+}
+
+/// Identifies the events of one operation among those of concurrent operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunId(usize);
+
+/// Events emitted on this thread belong to a run until this is dropped
+#[must_use = "the run ends when the guard is dropped"]
+pub struct Run {
+    id: RunId,
+    previous: Option<RunId>,
+}
+
+impl Run {
+    pub fn id(&self) -> RunId {
+        self.id
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        CURRENT_RUN.with(|current| current.set(self.previous));
+    }
+}
+
+/// Tag the events emitted on this thread with the current run, or a new one
+///
+/// Worker threads started during the run join it with [`in_run`].
+pub fn run() -> Run {
+    let previous = current_run();
+    let id = previous.unwrap_or_else(|| RunId(NEXT_RUN.fetch_add(1, Ordering::Relaxed)));
+    CURRENT_RUN.with(|current| current.set(Some(id)));
+    Run { id, previous }
+}
+
+/// The run of the events emitted on this thread, if any
+pub fn current_run() -> Option<RunId> {
+    CURRENT_RUN.with(Cell::get)
+}
+
+/// Call `work` with the events it emits on this thread tagged with `run`
+pub fn in_run<R>(run: Option<RunId>, work: impl FnOnce() -> R) -> R {
+    let previous = CURRENT_RUN.with(|current| current.replace(run));
+    let result = work();
+    CURRENT_RUN.with(|current| current.set(previous));
+    result
+}
+
+/// Something that happened during an operation
+///
+/// Repositories are identified by codebase and name, except in clone progress
+/// which only knows the destination `path` (the same as in `CloneStarted`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// A repository is about to be cloned
+    CloneStarted {
+        codebase: String,
+        repo: String,
+        url: String,
+        path: PathBuf,
+    },
+    /// Objects were received for a clone (at most every 100ms)
+    CloneProgress {
+        path: PathBuf,
+        received_objects: usize,
+        total_objects: usize,
+        received_bytes: u64,
+    },
+    /// A clone ended, with the error when it failed
+    CloneFinished {
+        codebase: String,
+        repo: String,
+        bytes: u64,
+        seconds: f64,
+        error: Option<String>,
+    },
+    /// Nothing was done for a repository
+    RepoSkipped {
+        codebase: String,
+        repo: String,
+        reason: SkipReason,
+    },
+    /// config.yaml or codebases.yaml was written
+    ConfigSaved { path: PathBuf },
+}
+
+/// Handle of a listener, removing it when dropped
+#[must_use = "the listener is removed when the subscription is dropped"]
+pub struct Subscription(usize);
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        LISTENERS.lock().unwrap().retain(|(id, _, _)| *id != self.0);
+    }
+}
+
+/// Call `listener` with every event until the subscription is dropped
+///
+/// Listeners are called on the thread doing the work (often a worker
+/// thread), so they should return quickly.
+pub fn subscribe(listener: impl Fn(&Event) + Send + Sync + 'static) -> Subscription {
+    add_listener(None, Arc::new(listener))
+}
+
+/// Call `listener` with the events of one run until the subscription is dropped
+pub fn subscribe_run(run: RunId, listener: impl Fn(&Event) + Send + Sync + 'static) -> Subscription {
+    add_listener(Some(run), Arc::new(listener))
+}
+
+/// Receive every event on a channel until the subscription is dropped
+///
+/// For library users; the CLI only listens to the events of its runs.
+#[allow(dead_code)]
+pub fn channel() -> (Subscription, Receiver<Event>) {
+    let (sender, receiver) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    let subscription = subscribe(move |event| {
+        let _ = sender.lock().unwrap().send(event.clone());
+    });
+    (subscription, receiver)
+}
+
+/// Receive the events of one run on a channel until the subscription is dropped
+pub fn run_channel(run: RunId) -> (Subscription, Receiver<Event>) {
+    let (sender, receiver) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    let subscription = subscribe_run(run, move |event| {
+        let _ = sender.lock().unwrap().send(event.clone());
+    });
+    (subscription, receiver)
+}
+
+fn add_listener(run: Option<RunId>, listener: Listener) -> Subscription {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    LISTENERS.lock().unwrap().push((id, run, listener));
+    Subscription(id)
+}
+
+/// Whether anybody listens, to skip building events nobody receives
+pub(crate) fn has_listeners() -> bool {
+    !LISTENERS.lock().unwrap().is_empty()
+}
+
+/// Send an event to every listener of all events or of the current run
+pub(crate) fn emit(event: Event) {
+    // Listeners may subscribe or emit themselves, so they're called without the lock
+    let run = current_run();
+    let listeners: Vec<Listener> = LISTENERS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, only, _)| only.is_none() || *only == run)
+        .map(|(_, _, listener)| Arc::clone(listener))
+        .collect();
+    for listener in listeners {
+        listener(&event);
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::{ProgressSink, status};
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::state::RepoState;
+
+/// Condition on the state of an installed repository (`--filter`)
+///
+/// Ahead and behind counts are those of the state cache, as of the last fetch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepoFilter {
+    /// Uncommitted changes in the working tree
+    Dirty,
+    /// No uncommitted changes
+    Clean,
+    /// Commits not pushed to the upstream branch
+    Ahead,
+    /// Commits of the upstream branch not pulled
+    Behind,
+    /// On the named branch
+    Branch(String),
+    /// HEAD tagged (with the given tag, if any)
+    Tag(Option<String>),
+}
+
+impl RepoFilter {
+    /// Whether a repository in `state`, cloned at `repo_path`, passes the filter
+    pub fn matches(&self, repo_path: &Path, state: &RepoState) -> bool {
+        match self {
+            Self::Dirty => state.dirty,
+            Self::Clean => !state.dirty,
+            Self::Ahead => state.ahead > 0,
+            Self::Behind => state.behind > 0,
+            Self::Branch(name) => state.branch.as_deref() == Some(name.as_str()),
+            Self::Tag(name) => GitRepo::tags_at_head(repo_path)
+                .is_ok_and(|tags| name.as_ref().map_or(!tags.is_empty(), |name| tags.contains(name))),
+        }
+    }
+}
+
+/// Installed repositories of `codebases` passing every filter, as `(codebase, repository)`
+///
+/// Their local state is read afresh; ahead and behind counts are as of the
+/// last fetch. The order is that of codebases.yaml.
+pub fn select(
+    config: &Config,
+    codebases: &[String],
+    filters: &[RepoFilter],
+    progress: &dyn ProgressSink,
+) -> BasecampResult<Vec<(String, String)>> {
+    if filters.is_empty() {
+        let mut installed = Vec::new();
+        for codebase in codebases {
+            for repo in config.get_repositories(codebase)? {
+                if config.workspace.repo_path(codebase, repo).exists() {
+                    installed.push((codebase.clone(), repo.clone()));
+                }
+            }
+        }
+        return Ok(installed);
+    }
+
+    Ok(status::status(config, codebases, 0, progress)?
+        .into_iter()
+        .filter(|entry| {
+            let repo_path = config.workspace.repo_path(&entry.codebase, &entry.repo);
+            entry
+                .state
+                .as_ref()
+                .is_some_and(|state| filters.iter().all(|filter| filter.matches(&repo_path, state)))
+        })
+        .map(|entry| (entry.codebase, entry.repo))
+        .collect())
+}
+
+impl fmt::Display for RepoFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dirty => f.write_str("dirty"),
+            Self::Clean => f.write_str("clean"),
+            Self::Ahead => f.write_str("ahead"),
+            Self::Behind => f.write_str("behind"),
+            Self::Branch(name) => write!(f, "branch={}", name),
+            Self::Tag(None) => f.write_str("tag"),
+            Self::Tag(Some(name)) => write!(f, "tag={}", name),
+        }
+    }
+}
+
+impl FromStr for RepoFilter {
+    type Err = BasecampError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (name, value) = match text.split_once('=') {
+            Some((name, value)) => (name, Some(value.to_string()).filter(|value| !value.is_empty())),
+            None => (text, None),
+        };
+        match (name, value) {
+            ("dirty", None) => Ok(Self::Dirty),
+            ("clean", None) => Ok(Self::Clean),
+            ("ahead", None) => Ok(Self::Ahead),
+            ("behind", None) => Ok(Self::Behind),
+            ("branch", Some(branch)) => Ok(Self::Branch(branch)),
+            ("tag", tag) => Ok(Self::Tag(tag)),
+            _ => Err(BasecampError::Generic(format!(
+                "Invalid filter: {}. Use 'dirty', 'clean', 'ahead', 'behind', 'branch=NAME', 'tag' or 'tag=NAME'",
+                text
+            ))),
+        }
+    }
+}
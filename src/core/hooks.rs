@@ -0,0 +1,106 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use log::debug;
+
+use crate::config::HooksMode;
+use crate::error::BasecampResult;
+use crate::git::GitRepo;
+
+/// List of the hooks basecamp deployed, in the hooks directory of a clone
+///
+/// Hooks not listed belong to the clone (e.g. installed by a framework) and
+/// are never replaced.
+const MANIFEST: &str = ".basecamp-hooks";
+
+/// What syncing the hooks of a repository did
+#[derive(Debug, Default)]
+pub struct HookSync {
+    /// Hooks linked, copied or refreshed
+    pub installed: Vec<String>,
+    /// Hooks deleted because they're no longer in `hooks_dir`
+    pub removed: Vec<String>,
+    /// Hooks of the clone's own, left in place of the shared ones
+    pub kept: Vec<String>,
+}
+
+/// Hooks in the shared hooks directory, by name
+///
+/// Hidden files and git's `.sample` hooks are left out.
+pub fn shared_hooks(dir: &Path) -> BasecampResult<Vec<(String, PathBuf)>> {
+    let mut hooks: Vec<(String, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .map(|entry| (entry.file_name().to_string_lossy().into_owned(), entry.path()))
+        .filter(|(name, path)| !name.starts_with('.') && !name.ends_with(".sample") && path.is_file())
+        .collect();
+    hooks.sort();
+    Ok(hooks)
+}
+
+/// Deploy the hooks of `shared_dir` into a clone
+pub fn sync(repo_path: &Path, shared_dir: &Path, mode: HooksMode) -> BasecampResult<HookSync> {
+    let hooks = shared_hooks(shared_dir)?;
+    let mut result = HookSync::default();
+
+    if mode == HooksMode::HooksPath {
+        GitRepo::set_hooks_path(repo_path, Some(shared_dir))?;
+        result.installed = hooks.into_iter().map(|(name, _)| name).collect();
+        return Ok(result);
+    }
+
+    // A `core.hooksPath` left by the `hooks_path` mode would hide `.git/hooks`
+    if GitRepo::hooks_path(repo_path)?.as_deref() == Some(shared_dir) {
+        GitRepo::set_hooks_path(repo_path, None)?;
+    }
+
+    let hooks_dir = GitRepo::hooks_dir(repo_path)?;
+    fs::create_dir_all(&hooks_dir)?;
+    let manifest_path = hooks_dir.join(MANIFEST);
+    let managed: Vec<String> = fs::read_to_string(&manifest_path)
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect();
+
+    for (name, source) in &hooks {
+        let target = hooks_dir.join(name);
+        let exists = target.symlink_metadata().is_ok();
+        if exists && !managed.contains(name) {
+            result.kept.push(name.clone());
+            continue;
+        }
+        if exists {
+            fs::remove_file(&target)?;
+        }
+        install_hook(source, &target, mode)?;
+        debug!("Deployed hook {:?} to {:?}", source, target);
+        result.installed.push(name.clone());
+    }
+
+    for name in managed.iter().filter(|name| !hooks.iter().any(|(hook, _)| hook == *name)) {
+        let target = hooks_dir.join(name);
+        if target.symlink_metadata().is_ok() {
+            fs::remove_file(&target)?;
+            result.removed.push(name.clone());
+        }
+    }
+
+    if result.installed.is_empty() {
+        let _ = fs::remove_file(&manifest_path);
+    } else {
+        fs::write(&manifest_path, format!("{}\n", result.installed.join("\n")))?;
+    }
+    Ok(result)
+}
+
+/// Link or copy a hook, copies keeping its permissions
+fn install_hook(source: &Path, target: &Path, mode: HooksMode) -> io::Result<()> {
+    #[cfg(unix)]
+    if mode == HooksMode::Link {
+        return std::os::unix::fs::symlink(source, target);
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+    fs::copy(source, target).map(|_| ())
+}
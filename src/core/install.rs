@@ -0,0 +1,704 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::Instant;
+
+use log::debug;
+use serde::Serialize;
+
+use super::env;
+use super::events::{self, Event};
+use super::hooks;
+use super::{ItemStatus, Notice, ProgressSink, dir_size, parallel_map};
+use crate::config::{Config, UpdateStrategy};
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::{GitRepo, UpdateResult};
+use crate::journal::Operation;
+use crate::oplog::{OperationLog, Outcome};
+use crate::state::{self, WorkspaceState};
+use crate::workspace::Workspace;
+
+/// Result of installing a single repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RepoResult {
+    Cloned,
+    Updated,
+    Skipped,
+    Failed,
+    /// Not cloned because a directory that isn't a git repository is in the way
+    Conflict,
+}
+
+/// Why a repository was skipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The repository exists and wasn't updated
+    AlreadyInstalled,
+    /// `update` found nothing to pull
+    UpToDate,
+    /// Not started after an earlier failure (fail-fast)
+    Cancelled,
+    /// Not cloned in offline mode
+    Offline,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::AlreadyInstalled => write!(f, "already installed"),
+            SkipReason::UpToDate => write!(f, "already up to date"),
+            SkipReason::Cancelled => write!(f, "cancelled after an earlier failure"),
+            SkipReason::Offline => write!(f, "offline"),
+        }
+    }
+}
+
+/// Report entry of a single repository
+#[derive(Debug, Clone, Serialize)]
+pub struct RepoReport {
+    pub codebase: String,
+    pub repo: String,
+    pub result: RepoResult,
+    pub seconds: f64,
+    pub bytes: u64,
+    /// URL the repository was cloned from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<SkipReason>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Exit code of an install run where some repositories failed
+///
+/// Errors that stop the run before anything is installed exit with 1.
+pub const EXIT_REPOSITORIES_FAILED: i32 = 2;
+
+/// Outcome of an install run: what happened to each repository, with durations and errors
+#[derive(Debug, Serialize)]
+pub struct InstallOutcome {
+    pub cloned: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub conflicts: usize,
+    pub bytes: u64,
+    pub seconds: f64,
+    pub repositories: Vec<RepoReport>,
+}
+
+impl InstallOutcome {
+    /// Entries of the repositories that failed
+    pub fn failures(&self) -> impl Iterator<Item = &RepoReport> {
+        self.repositories.iter().filter(|r| r.result == RepoResult::Failed)
+    }
+
+    /// Entries of the repositories with a directory in the way
+    pub fn conflicts(&self) -> impl Iterator<Item = &RepoReport> {
+        self.repositories.iter().filter(|r| r.result == RepoResult::Conflict)
+    }
+
+    /// Whether every repository was installed, updated or skipped as up to date
+    pub fn is_success(&self) -> bool {
+        self.failed == 0 && self.conflicts == 0
+    }
+
+    /// The run as a result, failed when a repository wasn't installed, e.g. for the operation log
+    pub fn result(&self) -> BasecampResult<()> {
+        if self.is_success() {
+            return Ok(());
+        }
+        let repos: Vec<String> = self
+            .failures()
+            .chain(self.conflicts())
+            .map(|r| format!("{}/{}", r.codebase, r.repo))
+            .collect();
+        Err(BasecampError::CommandFailed(format!(
+            "{} repositories weren't installed: {}",
+            repos.len(),
+            repos.join(", ")
+        )))
+    }
+
+    /// Process exit code for the run: 0 on success, `EXIT_REPOSITORIES_FAILED` otherwise
+    pub fn exit_code(&self) -> i32 {
+        if self.is_success() { 0 } else { EXIT_REPOSITORIES_FAILED }
+    }
+}
+
+/// Settings of an install run
+#[derive(Debug, Clone, Copy)]
+pub struct InstallSettings {
+    /// Number of parallel clone operations
+    pub parallel: usize,
+    /// Stop starting new clones after the first failure
+    pub fail_fast: bool,
+    /// Bring existing repositories up to date instead of skipping them
+    pub update: Option<UpdateStrategy>,
+    /// What to do with directories in the way that aren't git repositories
+    pub conflicts: ConflictPolicy,
+}
+
+/// What to do when a repository's directory exists but isn't a git repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictPolicy {
+    /// Leave the directory alone and report the conflict
+    #[default]
+    Report,
+    /// Move the directory into the trash, where `undo` finds it, and clone
+    Overwrite,
+    /// Move the directory into `.basecamp/conflicts/<codebase>/` and clone
+    Backup,
+}
+
+/// Bookkeeping shared by the workers of an install run
+struct InstallRun<'a> {
+    log: &'a OperationLog,
+    repos: Mutex<Vec<RepoReport>>,
+    started: Instant,
+}
+
+impl InstallRun<'_> {
+    /// Record a repository in the operation log and the summary report
+    fn record(
+        &self,
+        (codebase, repo): &(String, String),
+        url: Option<&str>,
+        started: Instant,
+        bytes: u64,
+        result: Result<RepoResult, SkipReason>,
+        error: Option<String>,
+    ) {
+        let duration = started.elapsed();
+        let (result, reason, outcome) = match result {
+            Err(reason) => (RepoResult::Skipped, Some(reason), Outcome::Skipped(reason.to_string())),
+            Ok(result @ (RepoResult::Failed | RepoResult::Conflict)) => {
+                (result, None, Outcome::Failed(error.clone().unwrap_or_default()))
+            }
+            Ok(result) => (result, None, Outcome::Succeeded),
+        };
+
+        self.log.record(&format!("{}/{}", codebase, repo), url, duration, outcome);
+        if let Some(reason) = reason {
+            events::emit(Event::RepoSkipped {
+                codebase: codebase.clone(),
+                repo: repo.clone(),
+                reason,
+            });
+        } else if url.is_some() {
+            events::emit(Event::CloneFinished {
+                codebase: codebase.clone(),
+                repo: repo.clone(),
+                bytes,
+                seconds: duration.as_secs_f64(),
+                error: error.clone(),
+            });
+        }
+        self.repos.lock().unwrap().push(RepoReport {
+            codebase: codebase.to_string(),
+            repo: repo.to_string(),
+            result,
+            seconds: duration.as_secs_f64(),
+            bytes,
+            url: url.map(String::from),
+            reason,
+            error,
+        });
+    }
+
+    /// Build the summary report
+    fn report(self) -> InstallOutcome {
+        let mut repositories = self.repos.into_inner().unwrap();
+        repositories.sort_by(|a, b| (&a.codebase, &a.repo).cmp(&(&b.codebase, &b.repo)));
+
+        let count = |result: RepoResult| repositories.iter().filter(|r| r.result == result).count();
+
+        InstallOutcome {
+            cloned: count(RepoResult::Cloned),
+            updated: count(RepoResult::Updated),
+            skipped: count(RepoResult::Skipped),
+            failed: count(RepoResult::Failed),
+            conflicts: count(RepoResult::Conflict),
+            bytes: repositories.iter().map(|r| r.bytes).sum(),
+            seconds: self.started.elapsed().as_secs_f64(),
+            repositories,
+        }
+    }
+}
+
+/// Clone `(codebase, repository)` pairs in parallel, updating or skipping installed ones
+///
+/// Every repository is recorded in `log` and in the returned report. In
+/// offline mode nothing is cloned and missing repositories are skipped.
+pub fn install(
+    config: &Config,
+    jobs: &[(String, String)],
+    settings: InstallSettings,
+    log: &OperationLog,
+    progress: &dyn ProgressSink,
+) -> InstallOutcome {
+    let run = InstallRun {
+        log,
+        repos: Mutex::new(Vec::new()),
+        started: Instant::now(),
+    };
+
+    if jobs.is_empty() {
+        return run.report();
+    }
+
+    if GitRepo::is_offline() {
+        for job in jobs {
+            let (codebase, repo) = job;
+            let reason = if config.workspace.repo_path(codebase, repo).exists() {
+                SkipReason::AlreadyInstalled
+            } else {
+                SkipReason::Offline
+            };
+            run.record(job, None, Instant::now(), 0, Err(reason), None);
+        }
+        return run.report();
+    }
+
+    let total_repos = jobs.len();
+    let codebases = codebases_of(jobs);
+    let single_codebase = codebases.len() == 1;
+
+    // Display what will be installed
+    progress.notice(
+        Notice::Info,
+        &if single_codebase {
+            format!("Installing {} repositories in codebase '{}'", total_repos, codebases[0])
+        } else {
+            format!("Installing {} repositories across {} codebases", total_repos, codebases.len())
+        },
+    );
+    progress.start(
+        total_repos,
+        &if single_codebase {
+            format!("Installing repositories in '{}'", codebases[0])
+        } else {
+            "Installing repositories".to_string()
+        },
+    );
+
+    // A bar per codebase keeps installs of many codebases readable
+    if !single_codebase {
+        let groups: Vec<(String, usize)> = codebases
+            .iter()
+            .map(|codebase| (codebase.clone(), jobs.iter().filter(|(c, _)| c == codebase).count()))
+            .collect();
+        progress.start_groups(&groups);
+    }
+
+    // Shared repositories are handled one codebase at a time, so only the first clones them
+    let shared_locks: HashMap<&String, Mutex<()>> = jobs
+        .iter()
+        .filter(|(_, repo)| config.repo_config(repo).shared)
+        .map(|(_, repo)| (repo, Mutex::new(())))
+        .collect();
+
+    let github_url = &config.git_config.github_url;
+    let remaining = Mutex::new(clone_order(config, jobs));
+    // Overwritten directories are recorded for `basecamp undo`
+    let journal = Operation::begin(&config.workspace, "install");
+    let failed = Mutex::new(false);
+    let parallel_count = settings.parallel.clamp(1, total_repos);
+
+    // Events of the workers belong to this install, for listeners of its run
+    let events_run = events::run();
+    let run_id = Some(events_run.id());
+
+    // Clone progress events of this install tell how much was received so far, for the transfer rate
+    let (transfer, transfer_events) = events::run_channel(events_run.id());
+
+    thread::scope(|scope| {
+        scope.spawn(move || report_transfer(transfer_events, progress));
+
+        let mut workers = Vec::with_capacity(parallel_count);
+        for _ in 0..parallel_count {
+            workers.push(scope.spawn(|| events::in_run(run_id, || {
+                loop {
+                    // Get next repository to clone
+                    let job_idx = {
+                        let mut remaining = remaining.lock().unwrap();
+                        if remaining.is_empty() || (settings.fail_fast && *failed.lock().unwrap()) {
+                            break;
+                        }
+                        remaining.remove(0)
+                    };
+
+                    let job = &jobs[job_idx];
+                    let (codebase, repo) = job;
+                    let label = job_label(codebase, repo, single_codebase);
+                    let shared_guard = shared_locks.get(repo).map(|lock| lock.lock().unwrap());
+                    let shared = shared_guard.is_some();
+
+                    progress.item_started(&label, &format!("Cloning '{}'...", label));
+
+                    let repo_path = config.workspace.repo_path(codebase, repo);
+                    let started = Instant::now();
+                    let installed = repo_path.join(".git").exists();
+
+                    let status = if installed && let Some(strategy) = settings.update {
+                        progress.item_started(&label, &format!("Updating '{}'...", label));
+
+                        // Pinned repositories go back to their revision instead of following a branch
+                        let settings = config.repo_config(repo);
+                        let updated = match &settings.rev {
+                            Some(rev) => GitRepo::fetch(&repo_path, config.remote_name())
+                                .and_then(|_| GitRepo::checkout_rev(&repo_path, config.remote_name(), rev))
+                                .map(|moved| moved.then(|| format!("checked out {}", rev))),
+                            None => GitRepo::update(&repo_path, config.remote_name(), strategy == UpdateStrategy::Rebase)
+                                .map(|result| (result != UpdateResult::UpToDate).then(|| result.to_string())),
+                        };
+                        // Sparse paths may have changed, and the update may have brought files outside them
+                        let updated = match updated {
+                            Ok(result) if !settings.sparse.is_empty() => {
+                                GitRepo::apply_sparse(&repo_path, &settings.sparse).map(|_| result)
+                            }
+                            updated => updated,
+                        };
+
+                        match updated {
+                            Ok(None) => {
+                                run.record(job, None, started, 0, Err(SkipReason::UpToDate), None);
+                                progress.item_finished(&label, ItemStatus::Skipped, &format!("Repository '{}' already up to date", label));
+                                ItemStatus::Skipped
+                            }
+                            Ok(Some(result)) => {
+                                run.record(job, None, started, 0, Ok(RepoResult::Updated), None);
+                                progress.item_finished(&label, ItemStatus::Succeeded, &format!("Updated '{}' ({})", label, result));
+                                ItemStatus::Succeeded
+                            }
+                            Err(e) => {
+                                run.record(job, None, started, 0, Ok(RepoResult::Failed), Some(e.to_string()));
+                                progress.item_finished(&label, ItemStatus::Failed, &format!("Failed to update '{}'", label));
+                                ItemStatus::Failed
+                            }
+                        }
+                    } else if installed {
+                        run.record(job, None, started, 0, Err(SkipReason::AlreadyInstalled), None);
+                        progress.item_finished(&label, ItemStatus::Skipped, &format!("Repository '{}' already installed", label));
+                        ItemStatus::Skipped
+                    } else if let Err(conflict) = make_way(&config.workspace, job, settings.conflicts, &journal, progress) {
+                        run.record(job, None, started, 0, Ok(RepoResult::Conflict), Some(conflict));
+                        progress.item_finished(&label, ItemStatus::Failed, &format!("A directory is in the way of '{}'", label));
+                        ItemStatus::Failed
+                    } else {
+                        let overwritten = journal.has_trashed(&repo_path);
+                        let repo_url = GitRepo::build_repo_url(github_url, repo);
+                        let linked = shared && config.workspace.shared_path(repo).exists();
+                        events::emit(Event::CloneStarted {
+                            codebase: codebase.clone(),
+                            repo: repo.clone(),
+                            url: repo_url.clone(),
+                            path: if shared { config.workspace.shared_path(repo) } else { repo_path.clone() },
+                        });
+
+                        // Pinned repositories are checked out at their revision, monorepos sparsely
+                        let remote = config.remote_name();
+                        let cloned = GitRepo::clone_into(&config.workspace, &repo_url, remote, codebase, repo, shared).and_then(|bytes| {
+                            let settings = config.repo_config(repo);
+                            if let Some(rev) = &settings.rev {
+                                GitRepo::checkout_rev(&repo_path, remote, rev).map_err(|e| {
+                                    BasecampError::Generic(format!("Cloned, but couldn't check out '{}': {}", rev, e))
+                                })?;
+                            }
+                            if !settings.sparse.is_empty() {
+                                GitRepo::apply_sparse(&repo_path, &settings.sparse).map_err(|e| {
+                                    BasecampError::Generic(format!("Cloned, but couldn't set up the sparse checkout: {}", e))
+                                })?;
+                            }
+                            Ok(bytes)
+                        });
+
+                        match cloned {
+                            Ok(bytes) => {
+                                // Undoing deletes the clone and brings the directory back
+                                if overwritten {
+                                    journal.record_created(&repo_path);
+                                }
+                                // Forks track their upstream, mirrors and backups get their own remotes
+                                for (name, url) in config.extra_remotes(repo) {
+                                    if let Err(e) = GitRepo::set_remote_url(&repo_path, &name, &url) {
+                                        progress.notice(Notice::Warning, &format!("Couldn't add the {} remote of '{}': {}", name, label, e));
+                                    }
+                                }
+                                run.record(job, Some(&repo_url), started, bytes, Ok(RepoResult::Cloned), None);
+                                let message = if linked {
+                                    format!("Linked '{}' to its shared clone", label)
+                                } else {
+                                    format!("Cloned '{}' successfully", label)
+                                };
+                                progress.item_finished(&label, ItemStatus::Succeeded, &message);
+                                ItemStatus::Succeeded
+                            }
+                            Err(e) => {
+                                run.record(job, Some(&repo_url), started, 0, Ok(RepoResult::Failed), Some(e.to_string()));
+                                progress.item_finished(&label, ItemStatus::Failed, &format!("Failed to clone '{}'", label));
+                                ItemStatus::Failed
+                            }
+                        }
+                    };
+
+                    if status == ItemStatus::Failed {
+                        *failed.lock().unwrap() = true;
+                    }
+                }
+            })));
+        }
+
+        for worker in workers {
+            if let Err(panic) = worker.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+        // Ends `report_transfer`
+        drop(transfer);
+    });
+
+    // With fail-fast, repositories not started yet are skipped
+    for idx in remaining.into_inner().unwrap() {
+        run.record(&jobs[idx], None, Instant::now(), 0, Err(SkipReason::Cancelled), None);
+    }
+    journal.finish("install over directories that weren't git repositories");
+
+    let report = run.report();
+
+    // Record fresh clones and updates in the state cache so status display is instant
+    record_installed_state(config, &report);
+    write_env_files(config, &report, progress);
+    deploy_hooks(config, &report, progress);
+    apply_git_settings(config, &report, progress);
+
+    progress.finish(&if report.failed > 0 {
+        "Installation completed with errors".to_string()
+    } else {
+        format!("Installed {} new repositories", report.cloned)
+    });
+
+    report
+}
+
+/// Clear the directory of a repository that isn't a git repository, as `policy` allows
+///
+/// Missing and empty directories aren't in the way. The error describes a
+/// conflict that was left alone or couldn't be cleared.
+fn make_way(
+    workspace: &Workspace,
+    (codebase, repo): &(String, String),
+    policy: ConflictPolicy,
+    journal: &Operation,
+    progress: &dyn ProgressSink,
+) -> Result<(), String> {
+    let path = workspace.repo_path(codebase, repo);
+    if !path.exists() {
+        return Ok(());
+    }
+    if fs::read_dir(&path).is_ok_and(|mut entries| entries.next().is_none()) {
+        return fs::remove_dir(&path).map_err(|e| format!("Couldn't remove empty directory {}: {}", path.display(), e));
+    }
+
+    match policy {
+        ConflictPolicy::Report => Err(format!("{} exists but isn't a git repository", path.display())),
+        ConflictPolicy::Overwrite => {
+            journal.trash(&path).map_err(|e| format!("Couldn't delete {}: {}", path.display(), e))?;
+            progress.notice(
+                Notice::Warning,
+                &format!("Moved {}, which wasn't a git repository, to the trash (basecamp undo restores it)", path.display()),
+            );
+            Ok(())
+        }
+        ConflictPolicy::Backup => {
+            let backup = workspace.conflict_path(codebase, repo, state::now());
+            backup
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::rename(&path, &backup))
+                .map_err(|e| format!("Couldn't move {} to {}: {}", path.display(), backup.display(), e))?;
+            progress.notice(
+                Notice::Info,
+                &format!("Moved {}, which wasn't a git repository, to {}", path.display(), backup.display()),
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Indexes of the jobs in the order they're started: by priority, then smallest first
+///
+/// Sizes are the last known ones from the state cache; repositories never
+/// installed come after the others of their priority, in configuration order.
+fn clone_order(config: &Config, jobs: &[(String, String)]) -> Vec<usize> {
+    let state = WorkspaceState::load(&config.workspace);
+    let mut order: Vec<usize> = (0..jobs.len()).collect();
+    order.sort_by_key(|&idx| {
+        let repo = &jobs[idx].1;
+        (config.repo_config(repo).priority, state.size_of(repo).unwrap_or(u64::MAX))
+    });
+    order
+}
+
+/// Pass the total of bytes received by the clones of a run to `progress` until the events stop
+fn report_transfer(events: Receiver<Event>, progress: &dyn ProgressSink) {
+    let mut received: HashMap<PathBuf, u64> = HashMap::new();
+    for event in events {
+        if let Event::CloneProgress { path, received_bytes, .. } = event {
+            received.insert(path, received_bytes);
+            progress.transferred(received.values().sum());
+        }
+    }
+}
+
+/// Codebases of `(codebase, repository)` pairs, in the order they appear
+pub fn codebases_of(jobs: &[(String, String)]) -> Vec<String> {
+    let mut codebases: Vec<String> = Vec::new();
+    for (codebase, _) in jobs {
+        if !codebases.contains(codebase) {
+            codebases.push(codebase.clone());
+        }
+    }
+    codebases
+}
+
+/// Label of a repository in progress output, qualified when several codebases are installed
+pub fn job_label(codebase: &str, repo: &str, single_codebase: bool) -> String {
+    if single_codebase {
+        repo.to_string()
+    } else {
+        format!("{}/{}", codebase, repo)
+    }
+}
+
+/// Write the environment of each codebase with `env` settings into its installed repositories
+///
+/// Repositories that were already installed get it too, so `install` picks up changes.
+fn write_env_files(config: &Config, report: &InstallOutcome, progress: &dyn ProgressSink) {
+    let files = config.env_files();
+    if files.is_empty() {
+        return;
+    }
+
+    let jobs: Vec<(String, String)> = report
+        .repositories
+        .iter()
+        .filter(|r| r.result != RepoResult::Failed)
+        .map(|r| (r.codebase.clone(), r.repo.clone()))
+        .collect();
+
+    for codebase in codebases_of(&jobs) {
+        if config.env_for(&codebase).is_empty() {
+            continue;
+        }
+        let codebase_env = match env::resolve(config, &codebase) {
+            Ok(codebase_env) => codebase_env,
+            Err(e) => {
+                progress.notice(Notice::Warning, &format!("Couldn't write the environment files of '{}': {}", codebase, e));
+                continue;
+            }
+        };
+
+        for (_, repo) in jobs.iter().filter(|(name, _)| *name == codebase) {
+            let repo_path = config.workspace.repo_path(&codebase, repo);
+            if !repo_path.exists() {
+                continue;
+            }
+            if let Err(e) = env::write_files(&repo_path, &files, &env::for_repo(&codebase_env, repo)) {
+                progress.notice(Notice::Warning, &format!("{}/{}: {}", codebase, repo, e));
+            }
+        }
+    }
+}
+
+/// Deploy the shared hooks of `hooks_dir` into every installed repository
+fn deploy_hooks(config: &Config, report: &InstallOutcome, progress: &dyn ProgressSink) {
+    let shared_dir = match config.hooks_dir() {
+        Ok(Some(dir)) => dir,
+        Ok(None) => return,
+        Err(e) => {
+            progress.notice(Notice::Warning, &format!("Couldn't deploy the hooks: {}", e));
+            return;
+        }
+    };
+
+    for entry in report.repositories.iter().filter(|r| r.result != RepoResult::Failed) {
+        let repo_path = config.workspace.repo_path(&entry.codebase, &entry.repo);
+        if !repo_path.exists() {
+            continue;
+        }
+        let label = format!("{}/{}", entry.codebase, entry.repo);
+        match hooks::sync(&repo_path, &shared_dir, config.git_config.hooks_mode) {
+            Ok(synced) if !synced.kept.is_empty() => progress.notice(
+                Notice::Warning,
+                &format!("{} has its own {} hooks, so the shared ones weren't deployed there", label, synced.kept.join(", ")),
+            ),
+            Ok(_) => {}
+            Err(e) => progress.notice(Notice::Warning, &format!("Couldn't deploy the hooks of {}: {}", label, e)),
+        }
+    }
+}
+
+/// Set the `git` settings in the local git config of every installed repository
+fn apply_git_settings(config: &Config, report: &InstallOutcome, progress: &dyn ProgressSink) {
+    for entry in report.repositories.iter().filter(|r| r.result != RepoResult::Failed) {
+        let repo_path = config.workspace.repo_path(&entry.codebase, &entry.repo);
+        if !repo_path.exists() {
+            continue;
+        }
+        let applied = config
+            .git_settings(&entry.codebase, &entry.repo)
+            .and_then(|settings| {
+                settings
+                    .iter()
+                    .try_for_each(|(key, value)| GitRepo::set_local_config(&repo_path, key, value))
+            });
+        if let Err(e) = applied {
+            progress.notice(
+                Notice::Warning,
+                &format!("Couldn't set the git config of {}/{}: {}", entry.codebase, entry.repo, e),
+            );
+        }
+    }
+}
+
+/// Record the state of freshly cloned or updated repositories in the state cache
+fn record_installed_state(config: &Config, report: &InstallOutcome) {
+    let mut installed = report
+        .repositories
+        .iter()
+        .filter(|r| matches!(r.result, RepoResult::Cloned | RepoResult::Updated))
+        .peekable();
+    if installed.peek().is_none() {
+        return;
+    }
+
+    // Sizes order the next installs; shared clones are measured where they are
+    let installed: Vec<&RepoReport> = installed.collect();
+    let sizes = parallel_map(&installed, config.parallel(None), |entry| {
+        let repo_path = config.workspace.repo_path(&entry.codebase, &entry.repo);
+        dir_size(&fs::canonicalize(&repo_path).unwrap_or(repo_path))
+    });
+
+    let mut state = WorkspaceState::load(&config.workspace);
+    for (entry, size) in installed.into_iter().zip(sizes) {
+        let repo_path = config.workspace.repo_path(&entry.codebase, &entry.repo);
+        match GitRepo::status(&repo_path, config.remote_name()) {
+            // A clone is as fresh as a fetch
+            Ok(status) => state.update(&entry.codebase, &entry.repo, &status, true),
+            Err(e) => debug!("Failed to read status of '{}': {}", entry.repo, e),
+        }
+        state.set_size(&entry.codebase, &entry.repo, size);
+    }
+
+    if let Err(e) = state.save() {
+        debug!("Failed to save state cache: {}", e);
+    }
+}
@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+
+/// A configured codebase with its repositories
+#[derive(Debug, Clone, Serialize)]
+pub struct CodebaseEntry {
+    pub name: String,
+    pub repositories: Vec<RepositoryEntry>,
+}
+
+/// A configured repository
+#[derive(Debug, Clone, Serialize)]
+pub struct RepositoryEntry {
+    pub name: String,
+    pub url: String,
+    pub path: PathBuf,
+    pub installed: bool,
+}
+
+/// The codebases of the workspace, or of a codebase or group, with their repositories
+pub fn list(config: &Config, codebase: Option<&str>) -> BasecampResult<Vec<CodebaseEntry>> {
+    if !config.has_github_url() {
+        return Err(BasecampError::GitHubUrlNotConfigured);
+    }
+
+    let codebase = codebase.map(|name| config.resolve_codebase(name));
+    let codebases = match codebase {
+        Some(name) if !config.is_group(&name) => {
+            config.get_repositories(&name)?;
+            vec![name]
+        }
+        codebase => config.select_codebases(codebase.as_deref())?,
+    };
+
+    codebases
+        .into_iter()
+        .map(|name| {
+            let repositories = config
+                .get_repositories(&name)?
+                .iter()
+                .map(|repo| {
+                    let path = config.workspace.repo_path(&name, repo);
+                    RepositoryEntry {
+                        name: repo.clone(),
+                        url: GitRepo::build_repo_url(&config.git_config.github_url, repo),
+                        installed: path.exists(),
+                        path,
+                    }
+                })
+                .collect();
+            Ok(CodebaseEntry { name, repositories })
+        })
+        .collect()
+}
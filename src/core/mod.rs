@@ -0,0 +1,114 @@
+pub mod add;
+pub mod env;
+pub mod events;
+pub mod filter;
+pub mod hooks;
+pub mod install;
+pub mod list;
+pub mod remove;
+pub mod resume;
+pub mod safety;
+pub mod signing;
+pub mod status;
+
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Result of an operation on a single item, as reported to a [`ProgressSink`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemStatus {
+    /// The operation completed
+    Succeeded,
+    /// Nothing had to be done
+    Skipped,
+    /// The operation failed
+    Failed,
+}
+
+/// Kind of a message sent to a [`ProgressSink`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Notice {
+    /// Informational message
+    Info,
+    /// Recoverable problem
+    Warning,
+}
+
+/// Receives the progress of core operations
+///
+/// Operations return structured results; the sink only tells a user interface
+/// what is happening meanwhile. Items are labels like `api-server` or
+/// `backend/api-server`, and methods are called from worker threads.
+pub trait ProgressSink: Sync {
+    /// Work on `total` items starts
+    fn start(&self, _total: usize, _message: &str) {}
+
+    /// Items are divided in groups (e.g. codebases) with the given number of items each
+    ///
+    /// Called after `start`; items of a group are labelled `<group>/<item>`.
+    fn start_groups(&self, _groups: &[(String, usize)]) {}
+
+    /// Work on an item starts, or moves to another step
+    fn item_started(&self, _item: &str, _message: &str) {}
+
+    /// Work on an item finished
+    fn item_finished(&self, _item: &str, _status: ItemStatus, _message: &str) {}
+
+    /// Bytes received over the network so far, by all items together
+    fn transferred(&self, _bytes: u64) {}
+
+    /// A message not tied to the progress of an item
+    fn notice(&self, _notice: Notice, _message: &str) {}
+
+    /// Work on all items finished
+    fn finish(&self, _message: &str) {}
+}
+
+/// Sink ignoring all progress
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl ProgressSink for NoProgress {}
+
+/// Apply `work` to every item, `parallel` at a time, keeping the order of the items
+pub fn parallel_map<T: Sync, R: Send>(items: &[T], parallel: usize, work: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(items.len()));
+    let run = events::current_run();
+    thread::scope(|scope| {
+        for _ in 0..parallel.clamp(1, items.len().max(1)) {
+            scope.spawn(|| {
+                events::in_run(run, || loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+                    let result = work(item);
+                    results.lock().unwrap().push((index, result));
+                })
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Size in bytes of the files under a directory, without following symbolic links
+///
+/// Links to shared clones count for nothing, as they aren't the clone itself.
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(metadata) = path.symlink_metadata() else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| entries.filter_map(Result::ok).map(|entry| dir_size(&entry.path())).sum())
+        .unwrap_or(0)
+}
@@ -0,0 +1,289 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use log::info;
+use serde::Serialize;
+
+use super::{ItemStatus, ProgressSink, dir_size, parallel_map, safety};
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult, LocalWork};
+use crate::journal::Operation;
+use crate::oplog::{OperationLog, Outcome};
+
+/// What removing repositories or a whole codebase will do, checked but not applied
+#[derive(Debug, Clone, Serialize)]
+pub struct RemovePlan {
+    /// Codebase to remove from (aliases resolved)
+    pub codebase: String,
+    /// Repositories to remove, empty when the whole codebase is removed
+    pub repositories: Vec<String>,
+    /// Local directories that will be deleted
+    pub directories: Vec<PathBuf>,
+}
+
+impl RemovePlan {
+    /// Whether the whole codebase is removed
+    pub fn whole_codebase(&self) -> bool {
+        self.repositories.is_empty()
+    }
+
+    /// Size in bytes of each planned directory, measured `parallel` at a time
+    pub fn sizes(&self, parallel: usize) -> Vec<u64> {
+        parallel_map(&self.directories, parallel, |path| dir_size(path))
+    }
+
+    /// What the removal does, as shown by `undo`
+    pub fn summary(&self) -> String {
+        if self.whole_codebase() {
+            format!("remove codebase '{}'", self.codebase)
+        } else {
+            format!("remove {} from '{}'", self.repositories.join(", "), self.codebase)
+        }
+    }
+}
+
+/// A deleted (or not) local directory
+#[derive(Debug, Clone, Serialize)]
+pub struct Deletion {
+    /// Repository, or the codebase when it's removed whole
+    pub name: String,
+    pub path: PathBuf,
+    /// Why the directory couldn't be deleted
+    pub error: Option<String>,
+}
+
+/// Result of a removal
+#[derive(Debug, Serialize)]
+pub struct RemoveReport {
+    #[serde(flatten)]
+    pub plan: RemovePlan,
+    pub deleted: Vec<Deletion>,
+}
+
+/// Check that repositories, or the whole codebase when `repositories` is empty, can be removed
+///
+/// Unless `force` is set, installed repositories with uncommitted changes or
+/// unpushed commits are refused. They are checked in parallel, reporting to
+/// `progress`, and every refused repository is named in the error.
+pub fn plan(
+    config: &Config,
+    codebase: &str,
+    repositories: &[String],
+    force: bool,
+    progress: &dyn ProgressSink,
+) -> BasecampResult<RemovePlan> {
+    let (plan, installed) = prepare(config, codebase, repositories)?;
+    if !force {
+        safety::check(&installed, config.remote_name(), config.parallel(None), progress)?;
+    }
+    Ok(plan)
+}
+
+/// What a removal would do, without changing anything
+#[derive(Debug)]
+pub struct RemovePreview {
+    pub plan: RemovePlan,
+    /// Size in bytes of each planned directory
+    pub sizes: Vec<u64>,
+    /// Installed repositories whose local work the removal would lose
+    pub local_work: Vec<(PathBuf, LocalWork)>,
+}
+
+/// Plan a removal and check every installed repository, reporting the local work instead of refusing
+pub fn preview(
+    config: &Config,
+    codebase: &str,
+    repositories: &[String],
+    progress: &dyn ProgressSink,
+) -> BasecampResult<RemovePreview> {
+    let (plan, installed) = prepare(config, codebase, repositories)?;
+    let local_work = match safety::check(&installed, config.remote_name(), config.parallel(None), progress) {
+        Ok(()) => Vec::new(),
+        Err(BasecampError::LocalWork(blocked)) => blocked,
+        Err(e) => return Err(e),
+    };
+    let sizes = plan.sizes(config.parallel(None));
+    Ok(RemovePreview {
+        plan,
+        sizes,
+        local_work,
+    })
+}
+
+/// The plan of a removal, with the installed repositories it deletes
+fn prepare(
+    config: &Config,
+    codebase: &str,
+    repositories: &[String],
+) -> BasecampResult<(RemovePlan, Vec<(String, PathBuf)>)> {
+    if !config.has_github_url() {
+        return Err(BasecampError::GitHubUrlNotConfigured);
+    }
+
+    let codebase = config.resolve_codebase(codebase);
+    let whole_codebase = repositories.is_empty();
+
+    // A group is planned one codebase at a time (the CLI expands it), each with its own checks
+    if whole_codebase && config.is_group(&codebase) {
+        return Err(BasecampError::Generic(format!(
+            "'{}' is a group of codebases ({}). Remove its codebases one at a time.",
+            codebase,
+            config.select_codebases(Some(&codebase))?.join(", ")
+        )));
+    }
+
+    let checked = if whole_codebase {
+        config.get_repositories(&codebase)?.clone()
+    } else {
+        repositories.to_vec()
+    };
+    let installed: Vec<(String, PathBuf)> = checked
+        .iter()
+        .map(|repo| (repo.clone(), config.workspace.repo_path(&codebase, repo)))
+        .filter(|(_, repo_path)| repo_path.exists())
+        .collect();
+
+    let mut directories = Vec::new();
+    let codebase_path = config.workspace.codebase_path(&codebase);
+    if whole_codebase && codebase_path.exists() {
+        directories.push(codebase_path);
+    }
+    if !whole_codebase {
+        directories.extend(installed.iter().map(|(_, repo_path)| repo_path.clone()));
+    }
+
+    let plan = RemovePlan {
+        codebase,
+        repositories: repositories.to_vec(),
+        directories,
+    };
+    Ok((plan, installed))
+}
+
+/// Remove the planned repositories or codebase from the configuration and save it
+pub fn forget(config: &mut Config, plan: &RemovePlan) -> BasecampResult<()> {
+    if plan.whole_codebase() {
+        info!("Removing entire codebase: {}", plan.codebase);
+        config.remove_codebase(&plan.codebase)?;
+    } else {
+        info!("Removing repositories {:?} from codebase '{}'", plan.repositories, plan.codebase);
+        config.remove_repositories(&plan.codebase, &plan.repositories)?;
+    }
+    config.save(&PathBuf::new())
+}
+
+/// Delete the planned local directories, `parallel` at a time, recording each in `log`
+///
+/// Directories are moved into the trash of `journal`, so the removal can be
+/// undone. A whole codebase is deleted one repository directory at a time,
+/// then the codebase directory with whatever is left in it.
+pub fn delete(
+    plan: &RemovePlan,
+    parallel: usize,
+    log: &OperationLog,
+    journal: &Operation,
+    progress: &dyn ProgressSink,
+) -> Vec<Deletion> {
+    let (directories, codebase_dir) = if plan.whole_codebase() {
+        let children = plan
+            .directories
+            .iter()
+            .filter_map(|path| fs::read_dir(path).ok())
+            .flatten()
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        (children, plan.directories.first())
+    } else {
+        (plan.directories.clone(), None)
+    };
+
+    let total = directories.len() + usize::from(codebase_dir.is_some());
+    progress.start(total, &format!("Deleting directories of '{}'", plan.codebase));
+    let mut deleted = parallel_map(&directories, parallel, |path| {
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        delete_one(plan, name, path, log, journal, progress)
+    });
+    if let Some(path) = codebase_dir {
+        deleted.push(delete_one(plan, plan.codebase.clone(), path, log, journal, progress));
+    }
+    progress.finish(&format!("Deleted directories of '{}'", plan.codebase));
+    deleted
+}
+
+/// Move one directory into the trash and record it
+fn delete_one(
+    plan: &RemovePlan,
+    name: String,
+    path: &Path,
+    log: &OperationLog,
+    journal: &Operation,
+    progress: &dyn ProgressSink,
+) -> Deletion {
+    let target = if name == plan.codebase && plan.whole_codebase() {
+        plan.codebase.clone()
+    } else {
+        format!("{}/{}", plan.codebase, name)
+    };
+    progress.item_started(&target, &format!("Deleting '{}'...", target));
+
+    let started = Instant::now();
+    let result = journal.trash(path);
+    let outcome = match &result {
+        Ok(_) => Outcome::Succeeded,
+        Err(e) => Outcome::Failed(format!("Failed to delete {}: {}", path.display(), e)),
+    };
+    log.record(&target, None, started.elapsed(), outcome);
+
+    match &result {
+        Ok(_) => {
+            info!("Deleted local directory '{}'", path.display());
+            progress.item_finished(&target, ItemStatus::Succeeded, &format!("Deleted '{}'", target));
+        }
+        Err(e) => {
+            info!("Failed to delete local directory '{}': {}", path.display(), e);
+            progress.item_finished(&target, ItemStatus::Failed, &format!("Failed to delete '{}': {}", target, e));
+        }
+    }
+
+    Deletion {
+        name,
+        path: path.to_path_buf(),
+        error: result.err().map(|e| e.to_string()),
+    }
+}
+
+/// Shared clones of removed repositories that no codebase uses anymore
+///
+/// Only the link is deleted for shared repositories, the clone itself is kept.
+pub fn unused_shared(config: &Config, plan: &RemovePlan) -> Vec<(String, PathBuf)> {
+    plan.repositories
+        .iter()
+        .filter(|repo| config.repo_config(repo).shared && config.find_repository(repo).is_empty())
+        .map(|repo| (repo.clone(), config.workspace.shared_path(repo)))
+        .filter(|(_, path)| path.exists())
+        .collect()
+}
+
+/// Check, forget and delete in one go, without asking for confirmation
+pub fn remove(
+    config: &mut Config,
+    codebase: &str,
+    repositories: &[String],
+    force: bool,
+    log: &OperationLog,
+    progress: &dyn ProgressSink,
+) -> BasecampResult<RemoveReport> {
+    let plan = plan(config, codebase, repositories, force, progress)?;
+    let journal = Operation::begin(&config.workspace, "remove");
+    let result = forget(config, &plan);
+    let deleted = match result {
+        Ok(()) => delete(&plan, config.parallel(None), log, &journal, progress),
+        Err(_) => Vec::new(),
+    };
+    journal.finish(&plan.summary());
+    result?;
+    Ok(RemoveReport { plan, deleted })
+}
@@ -0,0 +1,284 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+
+use super::events::{self, Event, Subscription};
+use super::install::{InstallOutcome, RepoResult, SkipReason};
+use crate::error::BasecampResult;
+use crate::state;
+use crate::workspace::Workspace;
+
+/// How far an install run got with a repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckpointStatus {
+    /// Not started yet
+    Pending,
+    /// Being cloned; a directory left behind is a partial clone
+    Cloning,
+    /// Cloned, updated or found installed
+    Done,
+    /// The clone or update failed
+    Failed,
+}
+
+/// A repository of an install run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointEntry {
+    pub codebase: String,
+    pub repo: String,
+    pub status: CheckpointStatus,
+    /// Directory the repository is cloned into, relative to the workspace root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+/// Contents of a checkpoint file
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct CheckpointFile {
+    /// Unix time the run started
+    started_at: u64,
+    repositories: Vec<CheckpointEntry>,
+}
+
+/// Progress of an install run, stored in `.basecamp/state/install-<id>.json`
+///
+/// The file is written whenever a repository changes status, so it is
+/// accurate when the run is interrupted, and deleted once every repository
+/// is done. `basecamp install --resume` continues from the latest one.
+#[derive(Debug)]
+pub struct Checkpoint {
+    root: PathBuf,
+    path: PathBuf,
+    file: Mutex<CheckpointFile>,
+}
+
+impl Checkpoint {
+    /// Start the checkpoint of a run installing `(codebase, repository)` pairs
+    pub fn begin(workspace: &Workspace, jobs: &[(String, String)]) -> Arc<Self> {
+        let started_at = state::now();
+        let dir = workspace.state_dir();
+        let mut path = dir.join(format!("install-{}.json", started_at));
+        let mut attempt = 1;
+        while path.exists() {
+            attempt += 1;
+            path = dir.join(format!("install-{}-{}.json", started_at, attempt));
+        }
+
+        let repositories = jobs
+            .iter()
+            .map(|(codebase, repo)| CheckpointEntry {
+                codebase: codebase.clone(),
+                repo: repo.clone(),
+                status: CheckpointStatus::Pending,
+                path: None,
+            })
+            .collect();
+        let checkpoint = Self {
+            root: workspace.root().to_path_buf(),
+            path,
+            file: Mutex::new(CheckpointFile {
+                started_at,
+                repositories,
+            }),
+        };
+        checkpoint.save(&checkpoint.file.lock().unwrap());
+        Arc::new(checkpoint)
+    }
+
+    /// The checkpoint of the latest unfinished install run, if any
+    pub fn latest(workspace: &Workspace) -> BasecampResult<Option<Arc<Self>>> {
+        let entries = match fs::read_dir(workspace.state_dir()) {
+            Ok(entries) => entries,
+            Err(_) => return Ok(None),
+        };
+
+        let mut latest: Option<(u64, PathBuf, CheckpointFile)> = None;
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.starts_with("install-") || !name.ends_with(".json") {
+                continue;
+            }
+            let file: CheckpointFile = match serde_json::from_str(&fs::read_to_string(&path)?) {
+                Ok(file) => file,
+                Err(e) => {
+                    warn!("Ignoring unreadable install checkpoint {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            if latest.as_ref().is_none_or(|(started_at, latest, _)| (file.started_at, &path) > (*started_at, latest)) {
+                latest = Some((file.started_at, path, file));
+            }
+        }
+
+        Ok(latest.map(|(_, path, file)| {
+            debug!("Resuming install checkpoint {:?}", path);
+            Arc::new(Self {
+                root: workspace.root().to_path_buf(),
+                path,
+                file: Mutex::new(file),
+            })
+        }))
+    }
+
+    /// Path of the checkpoint file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Repositories of the run with their status
+    pub fn entries(&self) -> Vec<CheckpointEntry> {
+        self.file.lock().unwrap().repositories.clone()
+    }
+
+    /// Repositories that still need to be installed
+    pub fn remaining(&self) -> Vec<CheckpointEntry> {
+        self.entries()
+            .into_iter()
+            .filter(|entry| entry.status != CheckpointStatus::Done)
+            .collect()
+    }
+
+    /// Keep only the repositories `keep` returns true for, e.g. those still configured
+    pub fn retain(&self, keep: impl Fn(&CheckpointEntry) -> bool) {
+        let mut file = self.file.lock().unwrap();
+        file.repositories.retain(|entry| keep(entry));
+        self.save(&file);
+    }
+
+    /// Directories left behind by clones that were interrupted or failed
+    ///
+    /// Only directories the run created are recorded. A directory that a
+    /// finished repository uses too, such as a shared clone, is complete and
+    /// isn't included, and neither is a path leading out of the workspace.
+    pub fn partial_clones(&self) -> Vec<PathBuf> {
+        let entries = self.entries();
+        let mut partial: Vec<PathBuf> = Vec::new();
+        for entry in &entries {
+            let Some(path) = &entry.path else {
+                continue;
+            };
+            if !path.components().all(|component| matches!(component, Component::Normal(_))) {
+                warn!("Ignoring the path {:?} of '{}' in install checkpoint {:?}", path, entry.repo, self.path);
+                continue;
+            }
+            let unfinished = matches!(entry.status, CheckpointStatus::Cloning | CheckpointStatus::Failed);
+            let in_use = entries
+                .iter()
+                .any(|other| other.status == CheckpointStatus::Done && other.path.as_ref() == Some(path));
+            let path = self.root.join(path);
+            if unfinished && !in_use && path.exists() && !partial.contains(&path) {
+                partial.push(path);
+            }
+        }
+        partial
+    }
+
+    /// Follow the repositories of the running install until the subscription is dropped
+    pub fn track(self: &Arc<Self>) -> Subscription {
+        let checkpoint = Arc::clone(self);
+        events::subscribe(move |event| match event {
+            Event::CloneStarted {
+                codebase, repo, path, ..
+            } => {
+                // A directory that's already there, like a shared clone being linked, isn't the run's to delete
+                let created = path.symlink_metadata().is_err().then_some(path);
+                checkpoint.set(codebase, repo, CheckpointStatus::Cloning, created)
+            }
+            Event::CloneFinished {
+                codebase, repo, error, ..
+            } => {
+                let status = if error.is_some() {
+                    CheckpointStatus::Failed
+                } else {
+                    CheckpointStatus::Done
+                };
+                checkpoint.set(codebase, repo, status, None);
+            }
+            Event::RepoSkipped {
+                codebase, repo, reason, ..
+            } => {
+                // Repositories skipped without being installed are tried again
+                let status = match reason {
+                    SkipReason::Cancelled | SkipReason::Offline => CheckpointStatus::Pending,
+                    SkipReason::AlreadyInstalled | SkipReason::UpToDate => CheckpointStatus::Done,
+                };
+                checkpoint.set(codebase, repo, status, None);
+            }
+            _ => {}
+        })
+    }
+
+    /// Record the outcome of the finished run, which also covers updates
+    pub fn record(&self, outcome: &InstallOutcome) {
+        let mut file = self.file.lock().unwrap();
+        for report in &outcome.repositories {
+            let status = match (report.result, report.reason) {
+                (RepoResult::Failed | RepoResult::Conflict, _) => CheckpointStatus::Failed,
+                (RepoResult::Skipped, Some(SkipReason::Cancelled | SkipReason::Offline)) => CheckpointStatus::Pending,
+                _ => CheckpointStatus::Done,
+            };
+            if let Some(entry) = file
+                .repositories
+                .iter_mut()
+                .find(|entry| entry.codebase == report.codebase && entry.repo == report.repo)
+            {
+                entry.status = status;
+            }
+        }
+        self.save(&file);
+    }
+
+    /// Delete the checkpoint file once every repository is done
+    ///
+    /// Returns the number of repositories left for `--resume`.
+    pub fn finish(&self) -> usize {
+        let remaining = self.remaining().len();
+        if remaining == 0 {
+            match fs::remove_file(&self.path) {
+                Ok(()) => info!("Install complete, deleted checkpoint {:?}", self.path),
+                Err(e) => warn!("Couldn't delete install checkpoint {:?}: {}", self.path, e),
+            }
+            // Only removed when no other checkpoint is left
+            if let Some(dir) = self.path.parent() {
+                let _ = fs::remove_dir(dir);
+            }
+        }
+        remaining
+    }
+
+    /// Change the status of a repository and write the file
+    fn set(&self, codebase: &str, repo: &str, status: CheckpointStatus, path: Option<&PathBuf>) {
+        let mut file = self.file.lock().unwrap();
+        let Some(entry) = file
+            .repositories
+            .iter_mut()
+            .find(|entry| entry.codebase == codebase && entry.repo == repo)
+        else {
+            return;
+        };
+        entry.status = status;
+        if let Some(path) = path {
+            entry.path = Some(path.strip_prefix(&self.root).unwrap_or(path).to_path_buf());
+        }
+        self.save(&file);
+    }
+
+    /// Write the checkpoint file; an install goes on without it when that fails
+    fn save(&self, file: &CheckpointFile) {
+        let written = self
+            .path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .map_err(|e| e.to_string())
+            .and_then(|_| serde_json::to_string_pretty(file).map_err(|e| e.to_string()))
+            .and_then(|content| fs::write(&self.path, content).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            warn!("Couldn't write install checkpoint {:?}: {}", self.path, e);
+        }
+    }
+}
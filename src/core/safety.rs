@@ -0,0 +1,62 @@
+use std::path::{Path, PathBuf};
+
+use log::debug;
+
+use super::{ItemStatus, ProgressSink, parallel_map};
+use crate::error::{BasecampError, BasecampResult, LocalWork};
+use crate::git::GitRepo;
+
+/// Local work of a clone that deleting or replacing it would lose
+///
+/// Commits count as pushed once the branch of the same name on `remote` has them.
+pub fn local_work(repo_path: &Path, remote: &str) -> Option<LocalWork> {
+    let work = GitRepo::has_uncommitted_changes(repo_path).and_then(|uncommitted| {
+        Ok(if uncommitted {
+            Some(LocalWork::UncommittedChanges)
+        } else if GitRepo::has_unpushed_commits(repo_path, remote)? {
+            Some(LocalWork::UnpushedCommits)
+        } else {
+            None
+        })
+    });
+    work.unwrap_or_else(|e| Some(LocalWork::Unknown(e.to_string())))
+}
+
+/// Check clones, labelled for `progress`, for local work against `remote`, `parallel` at a time
+///
+/// Every clone is checked, and all that have local work are named in one
+/// [`BasecampError::LocalWork`].
+pub fn check(
+    clones: &[(String, PathBuf)],
+    remote: &str,
+    parallel: usize,
+    progress: &dyn ProgressSink,
+) -> BasecampResult<()> {
+    if clones.is_empty() {
+        return Ok(());
+    }
+
+    progress.start(clones.len(), "Checking for local work");
+    let blocked: Vec<(PathBuf, LocalWork)> = parallel_map(clones, parallel, |(label, repo_path)| {
+        progress.item_started(label, &format!("Checking '{}'...", label));
+        let work = local_work(repo_path, remote);
+        match &work {
+            None => progress.item_finished(label, ItemStatus::Succeeded, label),
+            Some(work) => {
+                debug!("{:?} {}", repo_path, work);
+                progress.item_finished(label, ItemStatus::Failed, &format!("{} {}", label, work));
+            }
+        }
+        work.map(|work| (repo_path.clone(), work))
+    })
+    .into_iter()
+    .flatten()
+    .collect();
+    progress.finish("Checked for local work");
+
+    if blocked.is_empty() {
+        Ok(())
+    } else {
+        Err(BasecampError::LocalWork(blocked))
+    }
+}
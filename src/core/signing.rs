@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use log::debug;
+
+use crate::error::BasecampResult;
+use crate::git::GitRepo;
+
+/// Checks that commits can be signed in clones
+///
+/// Clones sharing a signing program and key are checked once.
+#[derive(Debug, Default)]
+pub struct SigningCheck {
+    checked: HashMap<(String, String, String), Option<String>>,
+}
+
+impl SigningCheck {
+    /// Why commits made in a clone wouldn't be signed, `None` when they would be
+    ///
+    /// Reads the git config in effect in the clone, then asks the signing
+    /// program whether the key is there, without signing anything (which
+    /// could prompt for a passphrase).
+    pub fn problem(&mut self, repo_path: &Path) -> BasecampResult<Option<String>> {
+        let value = |key: &str| GitRepo::config_string(repo_path, key);
+
+        let enabled = value("commit.gpgsign")?
+            .is_some_and(|flag| matches!(flag.to_lowercase().as_str(), "true" | "yes" | "on" | "1"));
+        if !enabled {
+            return Ok(Some("commit.gpgsign isn't enabled".to_string()));
+        }
+
+        let format = value("gpg.format")?.unwrap_or_else(|| "openpgp".to_string());
+        let program = match format.as_str() {
+            "openpgp" => value("gpg.openpgp.program")?.or(value("gpg.program")?),
+            _ => value(&format!("gpg.{}.program", format))?,
+        };
+        let program = match (program, format.as_str()) {
+            (Some(program), _) => program,
+            (None, "openpgp") => "gpg".to_string(),
+            (None, "x509") => "gpgsm".to_string(),
+            (None, "ssh") => "ssh-keygen".to_string(),
+            (None, _) => return Ok(Some(format!("gpg.format '{}' isn't one git knows", format))),
+        };
+
+        // GnuPG falls back to the committer's key, SSH signing needs one set
+        let key = match (value("user.signingkey")?, format.as_str()) {
+            (Some(key), _) => key,
+            (None, "ssh") => return Ok(Some("user.signingkey isn't set".to_string())),
+            (None, _) => match value("user.email")? {
+                Some(email) => email,
+                None => return Ok(Some("neither user.signingkey nor user.email is set".to_string())),
+            },
+        };
+
+        let id = (format, program, key);
+        if let Some(problem) = self.checked.get(&id) {
+            return Ok(problem.clone());
+        }
+        let problem = check_key(&id.0, &id.1, &id.2);
+        debug!("Signing with {} key {}: {:?}", id.0, id.2, problem);
+        self.checked.insert(id, problem.clone());
+        Ok(problem)
+    }
+}
+
+/// Ask the signing program about a key
+fn check_key(format: &str, program: &str, key: &str) -> Option<String> {
+    let mut command = Command::new(program);
+    let literal = key.strip_prefix("key::");
+    match (format, literal) {
+        ("ssh", Some(_)) => command.args(["-l", "-f", "-"]),
+        ("ssh", None) => command.args(["-l", "-f", key]),
+        _ => command.args(["--batch", "--list-secret-keys", key]),
+    };
+    command
+        .stdin(if literal.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => return Some(format!("{} couldn't be started: {}", program, e)),
+    };
+    if let (Some(literal), Some(mut stdin)) = (literal, child.stdin.take()) {
+        let _ = writeln!(stdin, "{}", literal);
+    }
+    match child.wait() {
+        Ok(status) if status.success() => None,
+        Ok(_) if format == "ssh" => Some(format!("{} isn't a readable SSH key", key)),
+        Ok(_) => Some(format!("{} has no secret key for {}", program, key)),
+        Err(e) => Some(format!("{} failed: {}", program, e)),
+    }
+}
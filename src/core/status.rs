@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use log::debug;
+use serde::Serialize;
+
+use super::{Notice, ProgressSink};
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::git::provider::{HostProvider, RemoteHealth};
+use crate::state::{RepoState, WorkspaceState};
+
+/// State of a configured repository
+#[derive(Debug, Clone, Serialize)]
+pub struct RepoEntry {
+    pub codebase: String,
+    pub repo: String,
+    /// Cached or recomputed state (`None` when the repository isn't installed)
+    pub state: Option<RepoState>,
+    /// Revision the repository is pinned to in codebases.yaml
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// Whether an installed pinned repository is off its revision
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub drifted: bool,
+}
+
+/// Read the state of the repositories of `codebases` through the state cache
+///
+/// Entries older than `ttl` seconds are recomputed locally (0 always
+/// recomputes) and the cache is saved. Pinned repositories are always
+/// compared with their revision. Repositories whose status can't be read are
+/// reported to `progress` and left out.
+pub fn status(
+    config: &Config,
+    codebases: &[String],
+    ttl: u64,
+    progress: &dyn ProgressSink,
+) -> BasecampResult<Vec<RepoEntry>> {
+    let mut state = WorkspaceState::load(&config.workspace);
+    let mut entries = Vec::new();
+
+    for codebase in codebases {
+        for repo in config.get_repositories(codebase)? {
+            let repo_path = config.workspace.repo_path(codebase, repo);
+
+            let rev = config.repo_config(repo).rev;
+            match state.fresh(codebase, repo, &repo_path, config.remote_name(), ttl) {
+                Ok(repo_state) => entries.push(RepoEntry {
+                    codebase: codebase.clone(),
+                    repo: repo.clone(),
+                    state: repo_state.cloned(),
+                    drifted: match (&rev, repo_path.exists()) {
+                        (Some(rev), true) => GitRepo::drifted_from(&repo_path, config.remote_name(), rev).unwrap_or(true),
+                        _ => false,
+                    },
+                    rev,
+                }),
+                Err(e) => {
+                    debug!("Failed to read status of {}/{}: {}", codebase, repo, e);
+                    progress.notice(Notice::Warning, &format!("Failed to read status of '{}': {}", repo, e));
+                }
+            }
+        }
+    }
+
+    state.save()?;
+    Ok(entries)
+}
+
+/// Ask the host about repositories (CI state, open pull requests, archived or moved), through the state cache
+///
+/// Answers younger than `ttl` seconds are reused (0 always asks); the others
+/// are requested `parallel` at a time with `token`, and the cache is saved.
+/// Repositories the host can't answer for are reported to `progress` and left out.
+pub fn remote_health(
+    config: &Config,
+    repos: &[String],
+    token: &str,
+    ttl: u64,
+    parallel: usize,
+    progress: &dyn ProgressSink,
+) -> BasecampResult<BTreeMap<String, RemoteHealth>> {
+    let github_url = &config.git_config.github_url;
+    let (host, owner) = GitRepo::remote_owner(&GitRepo::build_repo_url(github_url, "repo"))
+        .ok_or_else(|| BasecampError::InvalidGitHubUrl(github_url.clone()))?;
+    let provider = config.git_config.provider.unwrap_or_else(|| HostProvider::detect(&host));
+
+    let mut state = WorkspaceState::load(&config.workspace);
+    let mut health = BTreeMap::new();
+    let mut missing = Vec::new();
+    for repo in repos {
+        match state.fresh_remote(repo, ttl) {
+            Some(cached) => {
+                health.insert(repo.clone(), cached.clone());
+            }
+            None if !missing.contains(repo) => missing.push(repo.clone()),
+            None => {}
+        }
+    }
+    if missing.is_empty() {
+        return Ok(health);
+    }
+
+    let next = AtomicUsize::new(0);
+    let answers = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..parallel.max(1).min(missing.len()) {
+            scope.spawn(|| {
+                while let Some(repo) = missing.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    match provider.remote_health(&host, &owner, repo, Some(token)) {
+                        Ok(answer) => answers.lock().unwrap().push((repo.clone(), answer)),
+                        Err(e) => {
+                            debug!("Failed to read the remote state of {}: {}", repo, e);
+                            progress.notice(Notice::Warning, &format!("No answer from the host for '{}': {}", repo, e));
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    for (repo, answer) in answers.into_inner().unwrap() {
+        state.update_remote(&repo, answer.clone());
+        health.insert(repo, answer);
+    }
+    state.save()?;
+    Ok(health)
+}
@@ -1,45 +1,115 @@
+use std::fmt;
 use std::path::PathBuf;
 use thiserror::Error;
 
+use crate::messages;
+
 /// Custom error types for the BaseCamp application
+///
+/// Their messages come from the [`messages`] catalog, so they can be translated.
 #[derive(Error, Debug)]
 pub enum BasecampError {
-    #[error("Git error: {0}")]
     GitError(#[from] git2::Error),
-
-    #[error("IO error: {0}")]
+    #[cfg(feature = "gix")]
+    GixError(String),
     IoError(#[from] std::io::Error),
-
-    #[error("YAML serialization/deserialization error: {0}")]
     YamlError(#[from] serde_yaml::Error),
-
-    #[error("Repository '{0}' not found in codebase '{1}'")]
+    JsonError(#[from] serde_json::Error),
     RepositoryNotFound(String, String),
-
-    #[error("Codebase '{0}' not found")]
+    UnknownRepository(String),
     CodebaseNotFound(String),
-
-    #[error("File not found: {0}")]
+    ProfileNotFound(String),
+    TaskNotFound(String),
+    DependencyCycle(String),
     FileNotFound(PathBuf),
-
-    #[error("Repository at '{0}' has uncommitted changes")]
     UncommittedChanges(PathBuf),
-
-    #[error("Repository at '{0}' has unpushed commits")]
-    UnpushedCommits(PathBuf),
-
-    #[error("GitHub URL not configured")]
+    LocalWork(Vec<(PathBuf, LocalWork)>),
     GitHubUrlNotConfigured,
-
-    #[error("Invalid GitHub URL: {0}")]
+    InvalidConfig(String),
     InvalidGitHubUrl(String),
-
-    #[error("Command failed: {0}")]
+    Timeout(String, String),
+    OfflineMode(String),
     CommandFailed(String),
-
-    #[error("{0}")]
     Generic(String),
 }
 
+impl fmt::Display for BasecampError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            BasecampError::GitError(e) => messages::format("error.git", &[("error", e)]),
+            #[cfg(feature = "gix")]
+            BasecampError::GixError(e) => messages::format("error.git", &[("error", e)]),
+            BasecampError::IoError(e) => messages::format("error.io", &[("error", e)]),
+            BasecampError::YamlError(e) => messages::format("error.yaml", &[("error", e)]),
+            BasecampError::JsonError(e) => messages::format("error.json", &[("error", e)]),
+            BasecampError::RepositoryNotFound(repo, codebase) => {
+                messages::format("error.repository_not_found", &[("repo", repo), ("codebase", codebase)])
+            }
+            BasecampError::UnknownRepository(repo) => messages::format("error.unknown_repository", &[("repo", repo)]),
+            BasecampError::CodebaseNotFound(codebase) => {
+                messages::format("error.codebase_not_found", &[("codebase", codebase)])
+            }
+            BasecampError::ProfileNotFound(profile) => {
+                messages::format("error.profile_not_found", &[("profile", profile)])
+            }
+            BasecampError::TaskNotFound(task) => messages::format("error.task_not_found", &[("task", task)]),
+            BasecampError::DependencyCycle(cycle) => messages::format("error.dependency_cycle", &[("cycle", cycle)]),
+            BasecampError::FileNotFound(path) => {
+                messages::format("error.file_not_found", &[("path", &path.display())])
+            }
+            BasecampError::UncommittedChanges(path) => {
+                messages::format("error.uncommitted_changes", &[("path", &path.display())])
+            }
+            BasecampError::LocalWork(blocked) => blocked_message(blocked),
+            BasecampError::GitHubUrlNotConfigured => messages::text("error.github_url_not_configured"),
+            BasecampError::InvalidConfig(problem) => messages::format("error.invalid_config", &[("problem", problem)]),
+            BasecampError::InvalidGitHubUrl(url) => messages::format("error.invalid_github_url", &[("url", url)]),
+            BasecampError::Timeout(repo, reason) => {
+                messages::format("error.timeout", &[("repo", repo), ("reason", reason)])
+            }
+            BasecampError::OfflineMode(operation) => messages::format("error.offline", &[("operation", operation)]),
+            BasecampError::CommandFailed(error) => messages::format("error.command_failed", &[("error", error)]),
+            BasecampError::Generic(message) => message.clone(),
+        };
+        f.write_str(&text)
+    }
+}
+
+/// Local work that would be lost by deleting or replacing a repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalWork {
+    UncommittedChanges,
+    UnpushedCommits,
+    /// The repository couldn't be checked, so it's treated as having some
+    Unknown(String),
+}
+
+impl fmt::Display for LocalWork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            LocalWork::UncommittedChanges => messages::text("local_work.uncommitted"),
+            LocalWork::UnpushedCommits => messages::text("local_work.unpushed"),
+            LocalWork::Unknown(e) => messages::format("local_work.unknown", &[("error", e)]),
+        };
+        f.write_str(&text)
+    }
+}
+
+/// One line per blocked repository, or a sentence for a single one
+fn blocked_message(blocked: &[(PathBuf, LocalWork)]) -> String {
+    match blocked {
+        [(path, work)] => messages::format("error.local_work", &[("path", &path.display()), ("work", work)]),
+        _ => format!(
+            "{}\n{}",
+            messages::format("error.local_work_many", &[("count", &blocked.len())]),
+            blocked
+                .iter()
+                .map(|(path, work)| format!("  - '{}' {}", path.display(), work))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ),
+    }
+}
+
 /// Result type for BaseCamp operations
 pub type BasecampResult<T> = std::result::Result<T, BasecampError>;
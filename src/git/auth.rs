@@ -0,0 +1,511 @@
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+use git2::{Cred, CredentialType};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::git::provider::{self, HostProvider};
+
+/// Attempts after which authentication is given up (servers may ban clients
+/// offering too many keys)
+const MAX_ATTEMPTS: usize = 6;
+
+/// Environment variables holding an access token for HTTPS remotes, in order
+pub const TOKEN_VARIABLES: &[&str] = &["BASECAMP_TOKEN", "GITHUB_TOKEN", "GH_TOKEN", "GITLAB_TOKEN"];
+
+/// SSH key configured in config.yaml (or the active profile)
+static SSH_KEY: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Providers configured with `auth` in config.yaml (the default order when empty)
+static PROVIDERS: Mutex<Vec<ProviderKind>> = Mutex::new(Vec::new());
+
+/// Host of `github_url`, which access tokens of the environment may be sent to
+static TOKEN_SCOPE: Mutex<Option<TokenScope>> = Mutex::new(None);
+
+/// Set the SSH key tried before the keys found in ~/.ssh (and instead of the SSH agent)
+pub fn set_ssh_key(key: Option<PathBuf>) {
+    *SSH_KEY.lock().unwrap() = key;
+}
+
+/// Set the providers tried, in order (an empty list restores the default order)
+pub fn set_providers(providers: Vec<ProviderKind>) {
+    *PROVIDERS.lock().unwrap() = providers;
+}
+
+/// Set the configured host access tokens are scoped to (only public hosts when `None`)
+pub fn set_token_scope(scope: Option<TokenScope>) {
+    *TOKEN_SCOPE.lock().unwrap() = scope;
+}
+
+/// Credential providers, as named in the `auth` list of config.yaml
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderKind {
+    /// Keys of the running SSH agent
+    Agent,
+    /// The configured `ssh_key`, then private keys found in ~/.ssh
+    KeyFile,
+    /// An access token from `BASECAMP_TOKEN`, `GITHUB_TOKEN`, `GH_TOKEN` or
+    /// `GITLAB_TOKEN`, whichever belongs to the host
+    Token,
+    /// The credential helpers of the git configuration (`credential.helper`)
+    Helper,
+    /// The operating system's keychain, through git's platform credential helper
+    Keychain,
+}
+
+impl ProviderKind {
+    /// Order used when `auth` isn't configured
+    pub const DEFAULT_ORDER: [ProviderKind; 5] = [
+        ProviderKind::Agent,
+        ProviderKind::KeyFile,
+        ProviderKind::Token,
+        ProviderKind::Helper,
+        ProviderKind::Keychain,
+    ];
+
+    /// Create a provider of this kind with the current settings, `token`
+    /// replacing the environment's access tokens
+    fn provider(self, token: Option<&str>) -> Box<dyn CredentialProvider> {
+        let configured_key = SSH_KEY.lock().unwrap().as_deref().map(Config::expand_home);
+        match self {
+            ProviderKind::Agent => Box::new(SshAgent::new(configured_key.is_none())),
+            ProviderKind::KeyFile => Box::new(KeyFile::new(configured_key)),
+            ProviderKind::Token => Box::new(Token::new(TOKEN_SCOPE.lock().unwrap().clone(), token)),
+            ProviderKind::Helper => Box::new(Helper::default()),
+            ProviderKind::Keychain => Box::new(Keychain::default()),
+        }
+    }
+}
+
+/// What a remote asks credentials for
+#[derive(Debug, Clone, Copy)]
+pub struct CredentialRequest<'a> {
+    /// URL of the remote
+    pub url: &'a str,
+    /// User name from the URL, or `git`
+    pub username: &'a str,
+    /// Kinds of credentials the remote accepts
+    pub allowed: CredentialType,
+}
+
+impl CredentialRequest<'_> {
+    fn wants_ssh_key(&self) -> bool {
+        self.allowed.contains(CredentialType::SSH_KEY)
+    }
+
+    fn wants_password(&self) -> bool {
+        self.allowed.contains(CredentialType::USER_PASS_PLAINTEXT)
+    }
+}
+
+/// A source of credentials, asked in turn by a [`CredentialChain`]
+///
+/// A remote asks again after rejecting credentials, so a provider hands out
+/// its candidates one at a time and returns `None` once it has nothing (more)
+/// to offer for the request.
+pub trait CredentialProvider: Send {
+    /// Name shown in logs
+    fn name(&self) -> &'static str;
+
+    /// Next credentials to try
+    fn next(&mut self, request: &CredentialRequest<'_>) -> Option<Cred>;
+}
+
+/// Ordered providers asked for credentials until one has something to offer
+pub struct CredentialChain {
+    providers: Vec<Box<dyn CredentialProvider>>,
+    current: usize,
+    attempts: usize,
+}
+
+impl CredentialChain {
+    /// Create a chain trying the providers in order
+    pub fn new(providers: Vec<Box<dyn CredentialProvider>>) -> Self {
+        Self {
+            providers,
+            current: 0,
+            attempts: 0,
+        }
+    }
+
+    /// Create the chain configured with `auth` in config.yaml, offering
+    /// `token` (e.g. a mirror's `token_env`) instead of the environment's tokens
+    pub fn configured(token: Option<&str>) -> Self {
+        let kinds = PROVIDERS.lock().unwrap().clone();
+        let kinds = if kinds.is_empty() {
+            ProviderKind::DEFAULT_ORDER.to_vec()
+        } else {
+            kinds
+        };
+        Self::new(kinds.into_iter().map(|kind| kind.provider(token)).collect())
+    }
+
+    /// Credentials for the next authentication attempt
+    pub fn next(&mut self, request: &CredentialRequest<'_>) -> Result<Cred, git2::Error> {
+        // SSH first asks for the user name alone when the URL has none
+        if request.allowed == CredentialType::USERNAME {
+            return Cred::username(request.username);
+        }
+
+        self.attempts += 1;
+        if self.attempts > MAX_ATTEMPTS {
+            warn!("Too many authentication attempts, giving up");
+            return Err(git2::Error::from_str("Too many authentication attempts"));
+        }
+
+        while let Some(provider) = self.providers.get_mut(self.current) {
+            if let Some(cred) = provider.next(request) {
+                debug!(
+                    "Authentication attempt #{} for {} using {}",
+                    self.attempts,
+                    request.username,
+                    provider.name()
+                );
+                return Ok(cred);
+            }
+            self.current += 1;
+        }
+
+        // Integrated Windows authentication (NTLM/Negotiate) needs no secret
+        if request.allowed.contains(CredentialType::DEFAULT) {
+            return Cred::default();
+        }
+
+        warn!("Couldn't authenticate with any credential provider. Ensure your SSH keys or tokens are set up correctly.");
+        Err(git2::Error::from_str("No credentials accepted by the remote"))
+    }
+}
+
+/// Keys of the running SSH agent
+struct SshAgent {
+    enabled: bool,
+}
+
+impl SshAgent {
+    /// A configured key replaces the agent, which may offer another identity
+    fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+}
+
+impl CredentialProvider for SshAgent {
+    fn name(&self) -> &'static str {
+        "SSH agent"
+    }
+
+    fn next(&mut self, request: &CredentialRequest<'_>) -> Option<Cred> {
+        // Only once, to avoid prompting several times
+        if !self.enabled || !request.wants_ssh_key() {
+            return None;
+        }
+        self.enabled = false;
+        Cred::ssh_key_from_agent(request.username).ok()
+    }
+}
+
+/// The configured key, then the private keys found in ~/.ssh
+struct KeyFile {
+    configured: Option<PathBuf>,
+    candidates: Option<Vec<PathBuf>>,
+}
+
+impl KeyFile {
+    fn new(configured: Option<PathBuf>) -> Self {
+        Self {
+            configured,
+            candidates: None,
+        }
+    }
+}
+
+impl CredentialProvider for KeyFile {
+    fn name(&self) -> &'static str {
+        "SSH key file"
+    }
+
+    fn next(&mut self, request: &CredentialRequest<'_>) -> Option<Cred> {
+        if !request.wants_ssh_key() {
+            return None;
+        }
+
+        let candidates = self.candidates.get_or_insert_with(|| {
+            let ssh_dir = Config::home_dir().unwrap_or_default().join(".ssh");
+            let mut keys = key_candidates(self.configured.as_deref(), &ssh_dir);
+            keys.reverse();
+            keys
+        });
+
+        while let Some(key) = candidates.pop() {
+            if !key.is_file() {
+                continue;
+            }
+            debug!("Trying key {:?}", key);
+            let public_key = public_key_path(&key);
+            let public_key = public_key.is_file().then_some(public_key.as_path());
+            match Cred::ssh_key(request.username, public_key, &key, None) {
+                Ok(cred) => return Some(cred),
+                Err(e) => warn!(
+                    "Key {:?} can't be used ({}); if it needs a passphrase, add it to your SSH agent with: ssh-add {:?}",
+                    key, e, key
+                ),
+            }
+        }
+        None
+    }
+}
+
+/// Private keys to try, in order: the configured key, the standard key names,
+/// the `IdentityFile`s of `ssh_dir/config`, then other keys with a `.pub` file
+pub fn key_candidates(configured: Option<&Path>, ssh_dir: &Path) -> Vec<PathBuf> {
+    let mut keys: Vec<PathBuf> = configured.map(Path::to_path_buf).into_iter().collect();
+
+    keys.extend(
+        ["id_ed25519", "id_rsa", "id_ecdsa", "id_dsa", "github_rsa", "github_ed25519"]
+            .iter()
+            .map(|name| ssh_dir.join(name)),
+    );
+
+    if let Ok(config) = fs::read_to_string(ssh_dir.join("config")) {
+        for line in config.lines() {
+            let mut parts = line.split_whitespace();
+            if parts.next().is_some_and(|key| key.eq_ignore_ascii_case("IdentityFile"))
+                && let Some(path) = parts.next()
+            {
+                keys.push(Config::expand_home(Path::new(path.trim_matches('"'))));
+            }
+        }
+    }
+
+    if let Ok(entries) = fs::read_dir(ssh_dir) {
+        let mut found: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                path.is_file()
+                    && !name.starts_with('.')
+                    && !name.ends_with(".pub")
+                    && !name.contains("known_hosts")
+                    && !name.contains("config")
+                    && public_key_path(path).is_file()
+            })
+            .collect();
+        found.sort();
+        keys.extend(found);
+    }
+
+    let mut seen = HashSet::new();
+    keys.retain(|key| seen.insert(key.clone()));
+    keys
+}
+
+/// Public key next to a private key (`id_ed25519.pub` for `id_ed25519`)
+fn public_key_path(key: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.pub", key.display()))
+}
+
+/// Configured host, with its hosting service, that access tokens of the
+/// environment belong to besides github.com and gitlab.com
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenScope {
+    pub host: String,
+    pub provider: HostProvider,
+}
+
+impl TokenScope {
+    /// Access token of the environment for the configured host itself
+    pub fn env_token(&self) -> Option<String> {
+        env_token(&self.host, Some(self))
+    }
+
+    /// Variables read for a token of the configured host, e.g. for messages
+    pub fn variables(&self) -> Vec<&'static str> {
+        token_variables(&self.host, Some(self))
+    }
+}
+
+/// Variables of `TOKEN_VARIABLES` whose token may be sent to `host`, in order
+///
+/// `BASECAMP_TOKEN` belongs to the configured host only, `GITHUB_TOKEN` and
+/// `GH_TOKEN` to github.com and a configured GitHub Enterprise host, and
+/// `GITLAB_TOKEN` to gitlab.com and a configured GitLab host.
+pub fn token_variables(host: &str, scope: Option<&TokenScope>) -> Vec<&'static str> {
+    let host = bare_host(host);
+    let configured = scope.filter(|scope| bare_host(&scope.host) == host).map(|scope| scope.provider);
+    let github = matches!(configured, Some(HostProvider::Github | HostProvider::GithubEnterprise));
+    TOKEN_VARIABLES
+        .iter()
+        .copied()
+        .filter(|name| match *name {
+            "BASECAMP_TOKEN" => configured.is_some(),
+            "GITHUB_TOKEN" | "GH_TOKEN" => host == "github.com" || github,
+            "GITLAB_TOKEN" => host == "gitlab.com" || configured == Some(HostProvider::Gitlab),
+            _ => false,
+        })
+        .collect()
+}
+
+/// The first access token `lookup` finds among the variables scoped to `host`
+pub fn token_for(host: &str, scope: Option<&TokenScope>, lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+    token_variables(host, scope)
+        .into_iter()
+        .filter_map(lookup)
+        .map(|value| value.trim().to_string())
+        .find(|value| !value.is_empty())
+}
+
+/// Access token for a remote URL, only ever sent over HTTPS
+pub fn token_for_url(url: &str, scope: Option<&TokenScope>, lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let Some(rest) = url.strip_prefix("https://") else {
+        debug!("Not offering an access token to {}, which isn't an HTTPS URL", url);
+        return None;
+    };
+    token_for(rest.split('/').next().unwrap_or(rest), scope, lookup)
+}
+
+/// [`token_for`] reading the process environment
+pub fn env_token(host: &str, scope: Option<&TokenScope>) -> Option<String> {
+    token_for(host, scope, |name| env::var(name).ok())
+}
+
+/// Host name without credentials or port, in lower case
+fn bare_host(host: &str) -> String {
+    let host = provider::normalize_host(host);
+    let host = host.split('/').next().unwrap_or(host);
+    host.split(':').next().unwrap_or(host).to_ascii_lowercase()
+}
+
+/// An access token for HTTPS remotes: the one given, or one from the
+/// environment for the host it belongs to
+struct Token {
+    scope: Option<TokenScope>,
+    given: Option<String>,
+    used: bool,
+}
+
+impl Token {
+    fn new(scope: Option<TokenScope>, given: Option<&str>) -> Self {
+        Self {
+            scope,
+            given: given.map(str::to_string),
+            used: false,
+        }
+    }
+}
+
+impl CredentialProvider for Token {
+    fn name(&self) -> &'static str {
+        "access token"
+    }
+
+    fn next(&mut self, request: &CredentialRequest<'_>) -> Option<Cred> {
+        if self.used || !request.wants_password() {
+            return None;
+        }
+        self.used = true;
+        // Hosts ignore the user name of token logins, but it can't be empty
+        let username = match request.username {
+            "" | "git" => "x-access-token",
+            username => username,
+        };
+        let token = match &self.given {
+            Some(token) if request.url.starts_with("https://") => token.trim().to_string(),
+            Some(_) => return None,
+            None => token_for_url(request.url, self.scope.as_ref(), |name| env::var(name).ok())?,
+        };
+        Cred::userpass_plaintext(username, &token).ok()
+    }
+}
+
+/// The credential helpers of the git configuration
+#[derive(Default)]
+struct Helper {
+    used: bool,
+}
+
+impl CredentialProvider for Helper {
+    fn name(&self) -> &'static str {
+        "git credential helper"
+    }
+
+    fn next(&mut self, request: &CredentialRequest<'_>) -> Option<Cred> {
+        if self.used || !request.wants_password() {
+            return None;
+        }
+        self.used = true;
+        let config = git2::Config::open_default().ok()?;
+        Cred::credential_helper(&config, request.url, Some(request.username)).ok()
+    }
+}
+
+/// The operating system's keychain, through git's platform credential helper
+#[derive(Default)]
+struct Keychain {
+    used: bool,
+}
+
+impl Keychain {
+    /// git credential helper storing secrets in the platform keychain
+    fn helper() -> &'static str {
+        if cfg!(target_os = "macos") {
+            "osxkeychain"
+        } else if cfg!(windows) {
+            "manager"
+        } else {
+            "libsecret"
+        }
+    }
+}
+
+impl CredentialProvider for Keychain {
+    fn name(&self) -> &'static str {
+        "keychain"
+    }
+
+    fn next(&mut self, request: &CredentialRequest<'_>) -> Option<Cred> {
+        if self.used || !request.wants_password() {
+            return None;
+        }
+        self.used = true;
+
+        let (protocol, rest) = request.url.split_once("://")?;
+        let host = rest.split('/').next()?.rsplit('@').next()?;
+
+        let mut child = Command::new("git")
+            .args(["credential-".to_string() + Self::helper(), "get".to_string()])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let query = format!("protocol={}\nhost={}\n\n", protocol, host);
+        child.stdin.take()?.write_all(query.as_bytes()).ok()?;
+        let output = child.wait_with_output().ok()?;
+        if !output.status.success() {
+            debug!("No {} credentials for {}", Self::helper(), host);
+            return None;
+        }
+
+        let (username, password) = parse_helper_output(&String::from_utf8_lossy(&output.stdout))?;
+        Cred::userpass_plaintext(&username, &password).ok()
+    }
+}
+
+/// User name and password from the output of `git credential-<helper> get`
+pub fn parse_helper_output(output: &str) -> Option<(String, String)> {
+    let value = |key: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .map(str::to_string)
+    };
+    Some((value("username")?, value("password")?))
+}
@@ -0,0 +1,342 @@
+use git2::{ErrorCode, FetchOptions, PushOptions, Repository, RepositoryInitOptions, Signature};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::{Cancel, GitRepo, RepoStatus};
+use crate::error::{BasecampError, BasecampResult};
+
+/// Backend configured with `backend` in config.yaml
+static BACKEND: Mutex<BackendKind> = Mutex::new(BackendKind::Git2);
+
+/// Whether git operations are simulated (`--git-mode fake`), whatever the configured backend
+static FAKE: AtomicBool = AtomicBool::new(false);
+
+/// Repositories whose simulated clone fails (comma-separated names)
+pub const FAKE_FAIL_ENV: &str = "BASECAMP_FAKE_FAIL";
+
+/// Git implementations, as named by `backend` in config.yaml
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    /// libgit2, through the `git2` crate
+    #[default]
+    Git2,
+    /// gitoxide for status, libgit2 for network operations (needs a build
+    /// with the `gix` feature)
+    Gix,
+}
+
+impl BackendKind {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether this backend was compiled in
+    pub fn is_available(self) -> bool {
+        match self {
+            BackendKind::Git2 => true,
+            BackendKind::Gix => cfg!(feature = "gix"),
+        }
+    }
+}
+
+/// Select the backend used for the rest of the run
+pub fn set_backend(kind: BackendKind) -> BasecampResult<()> {
+    if !kind.is_available() {
+        return Err(BasecampError::InvalidConfig(
+            "backend 'gix' needs basecamp to be built with `--features gix`".to_string(),
+        ));
+    }
+    *BACKEND.lock().unwrap() = kind;
+    Ok(())
+}
+
+/// The backend selected for this run
+pub fn backend() -> BackendKind {
+    *BACKEND.lock().unwrap()
+}
+
+/// Simulate git operations instead of using the configured backend
+pub fn set_fake(fake: bool) {
+    FAKE.store(fake, Ordering::Relaxed);
+}
+
+/// Backend selected for this run
+pub fn current() -> &'static dyn Backend {
+    if FAKE.load(Ordering::Relaxed) {
+        return &FakeBackend;
+    }
+
+    match backend() {
+        #[cfg(feature = "gix")]
+        BackendKind::Gix => &GixBackend,
+        _ => &Git2Backend,
+    }
+}
+
+/// Git operations that differ between implementations
+///
+/// Offline mode, URL rewrites and progress reporting are handled by
+/// [`GitRepo`] around these calls.
+pub trait Backend: Send + Sync {
+    /// Clone a repository, naming its remote `remote`, and return the number of bytes received
+    fn clone(&self, url: &str, path: &Path, remote: &str) -> BasecampResult<u64>;
+
+    /// Fetch a remote of a repository, stopping early once `cancel` is cancelled
+    fn fetch(&self, repo_path: &Path, remote: &str, cancel: Option<&Cancel>) -> BasecampResult<()>;
+
+    /// Push refspecs of a repository to a URL, failing when the remote rejects any
+    ///
+    /// A `token` is offered instead of the environment's access tokens.
+    fn push(&self, repo_path: &Path, url: &str, refspecs: &[String], token: Option<&str>) -> BasecampResult<()>;
+
+    /// Get the branch, ahead/behind counts and dirty flag of a repository,
+    /// falling back to the branch of the same name on `remote` as upstream
+    fn status(&self, repo_path: &Path, remote: &str) -> BasecampResult<RepoStatus>;
+}
+
+/// libgit2 implementation
+pub struct Git2Backend;
+
+impl Backend for Git2Backend {
+    fn clone(&self, url: &str, path: &Path, remote: &str) -> BasecampResult<u64> {
+        GitRepo::clone_with_stats(url, path, remote).map(|(_, bytes)| bytes)
+    }
+
+    fn fetch(&self, repo_path: &Path, remote: &str, cancel: Option<&Cancel>) -> BasecampResult<()> {
+        let owned_path = repo_path.to_path_buf();
+        let remote_name = remote.to_string();
+        GitRepo::with_watchdog(repo_path, "fetching", cancel, move |watch| {
+            let repo = Repository::open(&owned_path)?;
+            let mut remote = repo.find_remote(&remote_name)?;
+            let url = remote.url().unwrap_or_default().to_string();
+
+            let mut fetch_options = FetchOptions::new();
+            fetch_options.remote_callbacks(GitRepo::remote_callbacks(&url, None, &watch));
+            fetch_options.proxy_options(GitRepo::proxy_options(&url));
+
+            // An empty refspec list uses the refspecs configured for the remote
+            remote.fetch(&[] as &[&str], Some(&mut fetch_options), None)?;
+            Ok(())
+        })
+    }
+
+    fn push(&self, repo_path: &Path, url: &str, refspecs: &[String], token: Option<&str>) -> BasecampResult<()> {
+        let owned_path = repo_path.to_path_buf();
+        let owned_url = url.to_string();
+        let refspecs = refspecs.to_vec();
+        let token = token.map(str::to_string);
+        GitRepo::with_watchdog(repo_path, "pushing", None, move |watch| {
+            let repo = Repository::open(&owned_path)?;
+            let mut remote = repo.remote_anonymous(&owned_url)?;
+
+            let rejected = Arc::new(Mutex::new(Vec::new()));
+            let mut callbacks = GitRepo::remote_callbacks(&owned_url, token.as_deref(), &watch);
+            let rejections = Arc::clone(&rejected);
+            callbacks.push_update_reference(move |refname, status| {
+                if let Some(status) = status {
+                    rejections.lock().unwrap().push(format!("{} ({})", refname, status));
+                }
+                Ok(())
+            });
+            let mut push_options = PushOptions::new();
+            push_options.remote_callbacks(callbacks);
+            push_options.proxy_options(GitRepo::proxy_options(&owned_url));
+
+            remote.push(&refspecs, Some(&mut push_options))?;
+            let rejected = rejected.lock().unwrap();
+            if !rejected.is_empty() {
+                return Err(BasecampError::Generic(format!("Rejected: {}", rejected.join(", "))));
+            }
+            Ok(())
+        })
+    }
+
+    fn status(&self, repo_path: &Path, remote: &str) -> BasecampResult<RepoStatus> {
+        let repo = Repository::open(repo_path)?;
+        let dirty = GitRepo::has_uncommitted_changes(repo_path)?;
+
+        let head = match repo.head() {
+            Ok(head) => head,
+            // Freshly initialized repository without commits
+            Err(e) if e.code() == ErrorCode::UnbornBranch => {
+                return Ok(RepoStatus {
+                    dirty,
+                    ..RepoStatus::default()
+                });
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        if !head.is_branch() {
+            return Ok(RepoStatus {
+                dirty,
+                ..RepoStatus::default()
+            });
+        }
+
+        let branch_name = head.shorthand().unwrap_or("HEAD").to_string();
+        let local_oid = head.peel_to_commit()?.id();
+
+        let (ahead, behind) = match GitRepo::upstream_oid(&repo, remote, &branch_name) {
+            Some(upstream_oid) => repo.graph_ahead_behind(local_oid, upstream_oid)?,
+            None => (0, 0),
+        };
+
+        Ok(RepoStatus {
+            branch: Some(branch_name),
+            ahead,
+            behind,
+            dirty,
+        })
+    }
+}
+
+/// Simulated implementation for tests and CI, never touching the network
+///
+/// A clone creates a local repository with one empty commit on `main`, a
+/// remote pointing to the URL and a matching remote-tracking `main`, so the
+/// clone is clean and up to date. Repositories named in `BASECAMP_FAKE_FAIL`
+/// fail to clone instead. Fetches and pushes do nothing and status is read locally.
+pub struct FakeBackend;
+
+impl Backend for FakeBackend {
+    fn clone(&self, url: &str, path: &Path, remote: &str) -> BasecampResult<u64> {
+        let name = url.rsplit(['/', ':']).next().unwrap_or(url).trim_end_matches(".git");
+        let failing = env::var(FAKE_FAIL_ENV).unwrap_or_default();
+        if failing.split(',').any(|repo| repo.trim() == name) {
+            return Err(BasecampError::Generic(format!("Simulated clone failure for {}", url)));
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let repo = Repository::init_opts(path, RepositoryInitOptions::new().initial_head("main"))?;
+
+        let signature = Signature::now("BaseCamp", "basecamp@localhost")?;
+        let tree = repo.find_tree(repo.index()?.write_tree()?)?;
+        let commit = repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &format!("Simulated clone of {}", url),
+            &tree,
+            &[],
+        )?;
+
+        repo.remote(remote, url)?;
+        repo.reference(&format!("refs/remotes/{}/main", remote), commit, true, "basecamp: simulated clone")?;
+        let mut config = repo.config()?;
+        config.set_str("branch.main.remote", remote)?;
+        config.set_str("branch.main.merge", "refs/heads/main")?;
+
+        Ok(0)
+    }
+
+    fn fetch(&self, repo_path: &Path, _remote: &str, _cancel: Option<&Cancel>) -> BasecampResult<()> {
+        Repository::open(repo_path)?;
+        Ok(())
+    }
+
+    fn push(&self, repo_path: &Path, _url: &str, _refspecs: &[String], _token: Option<&str>) -> BasecampResult<()> {
+        Repository::open(repo_path)?;
+        Ok(())
+    }
+
+    fn status(&self, repo_path: &Path, remote: &str) -> BasecampResult<RepoStatus> {
+        Git2Backend.status(repo_path, remote)
+    }
+}
+
+/// Status-only gitoxide implementation
+///
+/// Only `status` uses gix. gix is built without its network client, so
+/// clones, fetches and pushes are those of [`Git2Backend`] (with its
+/// credential, proxy and TLS settings), and libgit2 stays a dependency.
+#[cfg(feature = "gix")]
+pub struct GixBackend;
+
+#[cfg(feature = "gix")]
+impl Backend for GixBackend {
+    fn clone(&self, url: &str, path: &Path, remote: &str) -> BasecampResult<u64> {
+        Git2Backend.clone(url, path, remote)
+    }
+
+    fn fetch(&self, repo_path: &Path, remote: &str, cancel: Option<&Cancel>) -> BasecampResult<()> {
+        Git2Backend.fetch(repo_path, remote, cancel)
+    }
+
+    fn push(&self, repo_path: &Path, url: &str, refspecs: &[String], token: Option<&str>) -> BasecampResult<()> {
+        Git2Backend.push(repo_path, url, refspecs, token)
+    }
+
+    fn status(&self, repo_path: &Path, remote: &str) -> BasecampResult<RepoStatus> {
+        let repo = gix::open(repo_path).map_err(gix_error)?;
+
+        // Like `git status`, untracked files make the working tree dirty
+        let dirty = repo
+            .status(gix::progress::Discard)
+            .map_err(gix_error)?
+            .untracked_files(gix::status::UntrackedFiles::Files)
+            .into_iter(Vec::new())
+            .map_err(gix_error)?
+            .next()
+            .transpose()
+            .map_err(gix_error)?
+            .is_some();
+
+        let mut head = repo.head().map_err(gix_error)?;
+        let Some(branch) = head.referent_name().map(|name| name.to_owned()) else {
+            return Ok(RepoStatus {
+                dirty,
+                ..RepoStatus::default()
+            });
+        };
+        // Freshly initialized repository without commits
+        let Some(local) = head.try_peel_to_id().map_err(gix_error)? else {
+            return Ok(RepoStatus {
+                dirty,
+                ..RepoStatus::default()
+            });
+        };
+        let branch_name = branch.shorten().to_string();
+
+        // The configured upstream, then the branch of the same name on the clone remote
+        let upstream = match repo.branch_remote_tracking_ref_name(branch.as_ref(), gix::remote::Direction::Fetch) {
+            Some(Ok(tracking)) => Some(tracking.into_owned()),
+            _ => format!("refs/remotes/{}/{}", remote, branch_name).try_into().ok(),
+        };
+        let upstream = upstream
+            .and_then(|name: gix::refs::FullName| repo.try_find_reference(name.as_ref()).ok().flatten())
+            .and_then(|mut reference| reference.peel_to_id().ok())
+            .map(|id| id.detach());
+
+        let (ahead, behind) = match upstream {
+            Some(upstream) => {
+                let local = local.detach();
+                let count = |tip, hidden| -> BasecampResult<usize> {
+                    let walk = repo.rev_walk([tip]).with_hidden([hidden]).all().map_err(gix_error)?;
+                    walk.map(|info| info.map_err(gix_error)).try_fold(0, |n, info| info.map(|_| n + 1))
+                };
+                (count(local, upstream)?, count(upstream, local)?)
+            }
+            None => (0, 0),
+        };
+
+        Ok(RepoStatus {
+            branch: Some(branch_name),
+            ahead,
+            behind,
+            dirty,
+        })
+    }
+}
+
+#[cfg(feature = "gix")]
+fn gix_error(e: impl std::fmt::Display) -> BasecampError {
+    BasecampError::GixError(e.to_string())
+}
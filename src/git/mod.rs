@@ -0,0 +1,1669 @@
+pub mod auth;
+pub mod backend;
+pub mod provider;
+
+use git2::{Repository, StatusOptions, RemoteCallbacks, FetchOptions, ProxyOptions, build::RepoBuilder, CertificateCheckStatus, ErrorCode};
+use log::{debug, info, warn};
+use std::path::{Path, PathBuf};
+use std::env;
+use std::sync::{Arc, Mutex, mpsc};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::core::events::{self, Event};
+use crate::error::{BasecampError, BasecampResult};
+use crate::workspace::Workspace;
+use auth::{CredentialChain, CredentialRequest};
+
+/// Minimum time between two clone progress events
+const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Proxy configured with `proxy` in config.yaml (environment variables otherwise)
+static PROXY: Mutex<Option<String>> = Mutex::new(None);
+
+/// CA bundle trusted for the rest of the process (`tls.ca_bundle` in config.yaml)
+static CA_BUNDLE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Accept any TLS certificate (`tls.insecure_skip_verify` in config.yaml)
+static INSECURE_SKIP_VERIFY: AtomicBool = AtomicBool::new(false);
+
+/// Name of the clone remote when `remote_name` isn't set
+pub const DEFAULT_REMOTE: &str = "origin";
+
+/// Refuse network operations (`--offline`)
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Refuse network operations of the loaded workspace (`offline: true` in config.yaml)
+static WORKSPACE_OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Limits of clone and fetch operations (`timeouts` in config.yaml)
+static TIMEOUTS: Mutex<NetworkTimeouts> = Mutex::new(NetworkTimeouts {
+    connect: None,
+    total: None,
+});
+
+/// How often the watchdog of a network operation checks its deadlines
+const WATCHDOG_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Time a cancelled network operation gets to stop before it is left behind
+const CANCEL_GRACE: Duration = Duration::from_secs(5);
+
+/// Repositories whose cancelled operation is still stuck in a network call
+/// no callback interrupts; they get no new operation until it returns
+static HUNG: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Limits of a network operation (None means no limit)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetworkTimeouts {
+    /// Time allowed until the remote first responds
+    pub connect: Option<Duration>,
+    /// Time allowed for the whole operation
+    pub total: Option<Duration>,
+}
+
+/// Progress of a network operation, shared between its thread and its watchdog
+#[derive(Debug, Default)]
+struct NetworkWatch {
+    /// The remote responded (authentication or transfer)
+    connected: AtomicBool,
+    /// The watchdog gave up; callbacks abort the operation when it resumes
+    cancelled: AtomicBool,
+}
+
+impl NetworkWatch {
+    /// Record remote activity, returning whether the operation should go on
+    fn alive(&self) -> bool {
+        self.connected.store(true, Ordering::Relaxed);
+        !self.is_cancelled()
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Cancels, from another thread, the network operations it is passed to
+///
+/// A cancelled operation fails with [`BasecampError::Timeout`], like one
+/// exceeding a limit.
+#[derive(Debug, Clone, Default)]
+pub struct Cancel(Arc<AtomicBool>);
+
+impl Cancel {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Local state of a repository's current branch
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoStatus {
+    /// Current branch name (None when HEAD is detached or unborn)
+    pub branch: Option<String>,
+    /// Number of local commits not on the upstream branch
+    pub ahead: usize,
+    /// Number of upstream commits not on the local branch
+    pub behind: usize,
+    /// Whether the working tree has uncommitted or untracked changes
+    pub dirty: bool,
+}
+
+/// A commit as counted by `stats`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitStats {
+    /// Author email, lowercased
+    pub author: String,
+    /// Lines added (0 for merges)
+    pub insertions: usize,
+    /// Lines removed (0 for merges)
+    pub deletions: usize,
+}
+
+/// The commit checked out in a repository, as shown by `info`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitInfo {
+    /// Abbreviated commit id
+    pub id: String,
+    pub author: String,
+    /// Unix time of the commit
+    pub time: u64,
+    /// First line of the message
+    pub summary: String,
+}
+
+/// Result of updating a repository from its upstream branch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateResult {
+    /// Nothing to pull from the upstream branch
+    UpToDate,
+    /// Fast-forwarded by the given number of commits
+    FastForwarded(usize),
+    /// Local commits were rebased onto the given number of upstream commits
+    Rebased(usize),
+}
+
+impl std::fmt::Display for UpdateResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpdateResult::UpToDate => write!(f, "up to date"),
+            UpdateResult::FastForwarded(n) => write!(f, "fast-forwarded {} commits", n),
+            UpdateResult::Rebased(n) => write!(f, "rebased onto {} new commits", n),
+        }
+    }
+}
+
+/// Git repository operations
+pub struct GitRepo;
+
+impl GitRepo {
+    /// Clone a Git repository, naming its remote `remote`, and return it with
+    /// the number of bytes received
+    pub fn clone_with_stats(url: &str, path: &Path, remote: &str) -> BasecampResult<(Repository, u64)> {
+        debug!("Cloning repository {} to {:?}", url, path);
+        Self::ensure_online(&format!("clone {}", url))?;
+
+        // Create parent directory if it doesn't exist
+        if let Some(parent) = path.parent()
+            && !parent.exists()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // Clone from the rewritten URL but keep the configured one as origin, like git
+        let effective_url = Self::rewrite_url(url);
+        if effective_url != url {
+            debug!("Rewrote {} to {} (insteadOf)", url, effective_url);
+        }
+
+        let existed = path.exists();
+        let (owned_url, owned_path, owned_remote) = (effective_url, path.to_path_buf(), remote.to_string());
+        let result = Self::with_watchdog(path, "cloning", None, move |watch| {
+            let result = Self::clone_watched(&owned_url, &owned_path, &owned_remote, Arc::clone(&watch));
+            // The cancelled clone would otherwise pass for an installed repository
+            if result.is_err() && watch.is_cancelled() && !existed {
+                let _ = std::fs::remove_dir_all(&owned_path);
+            }
+            result
+        });
+        if let Ok((repo, _)) = &result
+            && repo.find_remote(remote)?.url() != Some(url)
+        {
+            repo.remote_set_url(remote, url)?;
+        }
+        result
+    }
+
+    /// Clone a Git repository, reporting remote activity to its watchdog
+    fn clone_watched(url: &str, path: &Path, remote: &str, watch: Arc<NetworkWatch>) -> BasecampResult<(Repository, u64)> {
+        // Determine if this is an SSH URL
+        let is_ssh_url = url.starts_with("git@");
+
+        // Set up fetch options with callbacks
+        let received_bytes = Arc::new(AtomicU64::new(0));
+        let mut callbacks = Self::remote_callbacks(url, None, &watch);
+        let counter = Arc::clone(&received_bytes);
+        let mut last_event: Option<Instant> = None;
+        callbacks.transfer_progress(move |progress| {
+            counter.store(progress.received_bytes() as u64, Ordering::Relaxed);
+
+            let done = progress.received_objects() == progress.total_objects();
+            if events::has_listeners() && (done || last_event.is_none_or(|at| at.elapsed() >= PROGRESS_EVENT_INTERVAL)) {
+                last_event = Some(Instant::now());
+                events::emit(Event::CloneProgress {
+                    path: path.to_path_buf(),
+                    received_objects: progress.received_objects(),
+                    total_objects: progress.total_objects(),
+                    received_bytes: progress.received_bytes() as u64,
+                });
+            }
+            watch.alive()
+        });
+
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.remote_callbacks(callbacks);
+        fetch_options.proxy_options(Self::proxy_options(url));
+
+        // Use RepoBuilder with fetch options
+        let mut builder = RepoBuilder::new();
+        builder.fetch_options(fetch_options);
+        let remote_name = remote.to_string();
+        builder.remote_create(move |repo, _, url| repo.remote(&remote_name, url));
+
+        // Clone the repository with auth settings
+        let repo = match builder.clone(url, path) {
+            Ok(repo) => repo,
+            Err(e) => {
+                warn!("Failed to clone repository: {}", e);
+                
+                // Provide more helpful error messages for SSH issues
+                if is_ssh_url && (e.code() == ErrorCode::Auth || e.class() == git2::ErrorClass::Ssh) {
+                    warn!("SSH authentication failed. Here are some troubleshooting steps:");
+                    warn!("1. Check if your SSH key is set up correctly: ssh -T git@github.com");
+                    warn!("2. Try adding your key to the SSH agent: ssh-add ~/.ssh/id_ed25519");
+                    warn!("3. Verify your GitHub URL format is correct: git@github.com:username/repo.git");
+                    
+                    if e.message().contains("passphrase") {
+                        warn!("4. Your SSH key appears to be protected with a passphrase.");
+                        warn!("   Please add it to your SSH agent first: ssh-add ~/.ssh/id_ed25519");
+                    }
+                }
+                
+                return Err(BasecampError::GitError(e));
+            }
+        };
+
+        info!("Repository cloned successfully to {:?}", path);
+        Ok((repo, received_bytes.load(Ordering::Relaxed)))
+    }
+
+    /// Forbid (or allow again) clones and fetches for the rest of the run
+    pub fn set_offline(offline: bool) {
+        OFFLINE.store(offline, Ordering::Relaxed);
+    }
+
+    /// Apply the `offline` setting of a loaded workspace, replacing that of the previous one
+    pub fn set_workspace_offline(offline: bool) {
+        WORKSPACE_OFFLINE.store(offline, Ordering::Relaxed);
+    }
+
+    /// Whether network operations are forbidden
+    pub fn is_offline() -> bool {
+        OFFLINE.load(Ordering::Relaxed) || WORKSPACE_OFFLINE.load(Ordering::Relaxed)
+    }
+
+    /// Fail with `OfflineMode` when network operations are forbidden
+    pub fn ensure_online(operation: &str) -> BasecampResult<()> {
+        if Self::is_offline() {
+            return Err(BasecampError::OfflineMode(operation.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Set the connect and overall limits of clones and fetches
+    pub fn set_timeouts(timeouts: NetworkTimeouts) {
+        *TIMEOUTS.lock().unwrap() = timeouts;
+    }
+
+    /// Connect and overall limits of clones and fetches
+    pub fn timeouts() -> NetworkTimeouts {
+        *TIMEOUTS.lock().unwrap()
+    }
+
+    /// Run a network operation on its own thread, cancelling it once a limit
+    /// is exceeded or `cancel` is
+    ///
+    /// A cancelled operation stops at its next callback, and is waited for
+    /// (`CANCEL_GRACE` at most) so it can clean up. One stuck in a network
+    /// call that no callback interrupts is left to finish on its own, and
+    /// its repository gets no other operation until it does.
+    fn with_watchdog<T: Send + 'static>(
+        repo_path: &Path,
+        action: &str,
+        cancel: Option<&Cancel>,
+        operation: impl FnOnce(Arc<NetworkWatch>) -> BasecampResult<T> + Send + 'static,
+    ) -> BasecampResult<T> {
+        let timeouts = Self::timeouts();
+        let watch = Arc::new(NetworkWatch::default());
+        if timeouts.connect.is_none() && timeouts.total.is_none() && cancel.is_none() {
+            return operation(watch);
+        }
+
+        let repo = repo_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| repo_path.display().to_string());
+        if HUNG.lock().unwrap().iter().any(|path| path == repo_path) {
+            return Err(BasecampError::Timeout(repo, "as its previous operation is still hung".to_string()));
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        let operation_watch = Arc::clone(&watch);
+        let owned_path = repo_path.to_path_buf();
+        let run = events::current_run();
+        let worker = thread::spawn(move || {
+            let result = events::in_run(run, || operation(operation_watch));
+            // Sent under the lock, so the watchdog either gets the result or marks the repository hung first
+            let mut hung = HUNG.lock().unwrap();
+            hung.retain(|path| *path != owned_path);
+            let _ = sender.send(result);
+        });
+
+        let started = Instant::now();
+        let reason = loop {
+            match receiver.recv_timeout(WATCHDOG_INTERVAL) {
+                Ok(result) => {
+                    let _ = worker.join();
+                    return result;
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    return Err(BasecampError::Generic(format!("{} stopped unexpectedly", action)));
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+            }
+
+            let elapsed = started.elapsed();
+            let exceeded = match (timeouts.connect, timeouts.total) {
+                _ if cancel.is_some_and(Cancel::is_cancelled) => Some(format!("while {} (cancelled)", action)),
+                (Some(limit), _) if !watch.connected.load(Ordering::Relaxed) && elapsed >= limit => {
+                    Some(format!("while connecting to the remote (limit: {}s)", limit.as_secs()))
+                }
+                (_, Some(limit)) if elapsed >= limit => {
+                    Some(format!("while {} (limit: {}s)", action, limit.as_secs()))
+                }
+                _ => None,
+            };
+            if let Some(reason) = exceeded {
+                break reason;
+            }
+        };
+
+        // The callbacks abort the operation as soon as they run again
+        watch.cancelled.store(true, Ordering::Relaxed);
+        warn!("Gave up on '{}' {}", repo, reason);
+        match receiver.recv_timeout(CANCEL_GRACE) {
+            // It finished just before noticing
+            Ok(Ok(value)) => {
+                let _ = worker.join();
+                return Ok(value);
+            }
+            Ok(Err(_)) | Err(mpsc::RecvTimeoutError::Disconnected) => {
+                let _ = worker.join();
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                let mut hung = HUNG.lock().unwrap();
+                if receiver.try_recv().is_err() {
+                    warn!("'{}' is stuck in a network call, leaving it to finish in the background", repo);
+                    hung.push(repo_path.to_path_buf());
+                }
+            }
+        }
+        Err(BasecampError::Timeout(repo, reason))
+    }
+
+    /// Trust the certificate authorities of a PEM bundle for HTTPS remotes
+    ///
+    /// libgit2 keeps the bundle for the whole process, so it is only set by
+    /// the first configuration load; later loads must name the same bundle.
+    pub fn set_ca_bundle(path: &Path) -> BasecampResult<()> {
+        let mut trusted = CA_BUNDLE.lock().unwrap();
+        match trusted.as_deref() {
+            Some(trusted) if trusted == path => return Ok(()),
+            Some(trusted) => {
+                return Err(BasecampError::InvalidConfig(format!(
+                    "tls.ca_bundle: '{}' can't replace '{}', already trusted by this process",
+                    path.display(),
+                    trusted.display()
+                )));
+            }
+            None => {}
+        }
+
+        debug!("Trusting certificate authorities from {:?}", path);
+        // SAFETY: once per process and under the lock, while loading the
+        // configuration, before any network operation
+        unsafe { git2::opts::set_ssl_cert_file(path)? };
+        *trusted = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Accept (or verify again) any TLS certificate of HTTPS remotes
+    pub fn set_insecure_skip_verify(insecure: bool) {
+        INSECURE_SKIP_VERIFY.store(insecure, Ordering::Relaxed);
+    }
+
+    /// Whether TLS certificates of HTTPS remotes are accepted without verification
+    pub fn is_insecure_skip_verify() -> bool {
+        INSECURE_SKIP_VERIFY.load(Ordering::Relaxed)
+    }
+
+    /// Whether the certificate check accepts a certificate without verifying it
+    ///
+    /// Only TLS certificates are skipped; SSH host keys are always left to libgit2.
+    pub fn skips_certificate_check(x509: bool) -> bool {
+        x509 && Self::is_insecure_skip_verify()
+    }
+
+    /// Set the proxy used for HTTP(S) remotes instead of the environment variables
+    pub fn set_proxy(proxy: Option<String>) {
+        *PROXY.lock().unwrap() = proxy;
+    }
+
+    /// Proxy options of a remote: the resolved proxy, or libgit2's own detection
+    /// (`http.proxy` in the git config)
+    fn proxy_options(url: &str) -> ProxyOptions<'static> {
+        let mut options = ProxyOptions::new();
+        let configured = PROXY.lock().unwrap().clone();
+        match Self::resolve_proxy(url, configured.as_deref(), |name| env::var(name).ok()) {
+            Some(proxy) => {
+                debug!("Using proxy {} for {}", proxy, url);
+                options.url(&proxy);
+            }
+            None => {
+                options.auto();
+            }
+        }
+        options
+    }
+
+    /// Proxy to use for a remote URL
+    ///
+    /// Only HTTP(S) remotes go through a proxy. The configured proxy wins over
+    /// `https_proxy`/`http_proxy` (and their upper-case forms), and hosts matching
+    /// `no_proxy` are reached directly. `env` looks up environment variables.
+    pub fn resolve_proxy(
+        url: &str,
+        configured: Option<&str>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Option<String> {
+        let (scheme, rest) = url.split_once("://")?;
+        let variables: &[&str] = match scheme {
+            "https" => &["https_proxy", "HTTPS_PROXY", "all_proxy", "ALL_PROXY"],
+            "http" => &["http_proxy", "HTTP_PROXY", "all_proxy", "ALL_PROXY"],
+            _ => return None,
+        };
+
+        let lookup = |names: &[&str]| {
+            names
+                .iter()
+                .filter_map(|name| env(name))
+                .find(|value| !value.trim().is_empty())
+        };
+
+        let authority = rest.split('/').next().unwrap_or_default();
+        let host = authority.rsplit('@').next().unwrap_or_default();
+        let host = host.split(':').next().unwrap_or_default().to_lowercase();
+        let bypassed = lookup(&["no_proxy", "NO_PROXY"]).is_some_and(|no_proxy| {
+            no_proxy.split(',').map(str::trim).any(|entry| {
+                let entry = entry.trim_start_matches('.').to_lowercase();
+                entry == "*" || (!entry.is_empty() && (host == entry || host.ends_with(&format!(".{}", entry))))
+            })
+        });
+        if bypassed {
+            return None;
+        }
+
+        configured
+            .filter(|proxy| !proxy.trim().is_empty())
+            .map(str::to_string)
+            .or_else(|| lookup(variables))
+    }
+
+    /// Set the SSH key tried before the SSH agent and the keys found in ~/.ssh
+    pub fn set_ssh_key(key: Option<PathBuf>) {
+        auth::set_ssh_key(key);
+    }
+
+    /// Build remote callbacks authenticating with the configured credential providers
+    ///
+    /// Remote activity is reported to `watch`, and the transfer is aborted once
+    /// its watchdog gives up.
+    fn remote_callbacks(url: &str, token: Option<&str>, watch: &Arc<NetworkWatch>) -> RemoteCallbacks<'static> {
+        let mut callbacks = RemoteCallbacks::new();
+
+        let sideband_watch = Arc::clone(watch);
+        callbacks.sideband_progress(move |_| sideband_watch.alive());
+        let transfer_watch = Arc::clone(watch);
+        callbacks.transfer_progress(move |_| transfer_watch.alive());
+
+        let certificate_watch = Arc::clone(watch);
+        callbacks.certificate_check(move |cert, host| {
+            if !certificate_watch.alive() {
+                return Err(git2::Error::from_str("Operation timed out"));
+            }
+            if Self::skips_certificate_check(cert.as_x509().is_some()) {
+                debug!("Skipping certificate verification for {}", host);
+                return Ok(CertificateCheckStatus::CertificateOk);
+            }
+            Ok(CertificateCheckStatus::CertificatePassthrough)
+        });
+
+        // Each rejected attempt asks the chain again, which moves on to the next candidate
+        let credentials_watch = Arc::clone(watch);
+        let mut chain = CredentialChain::configured(token);
+        let fallback_url = url.to_string();
+        callbacks.credentials(move |url, username_from_url, allowed_types| {
+            if !credentials_watch.alive() {
+                return Err(git2::Error::from_str("Operation timed out"));
+            }
+            chain.next(&CredentialRequest {
+                url: if url.is_empty() { &fallback_url } else { url },
+                username: username_from_url.unwrap_or("git"),
+                allowed: allowed_types,
+            })
+        });
+
+        callbacks
+    }
+
+    /// Directory git looks for the hooks of a repository in, unless `core.hooksPath` is set
+    pub fn hooks_dir(repo_path: &Path) -> BasecampResult<PathBuf> {
+        Ok(Repository::open(repo_path)?.path().join("hooks"))
+    }
+
+    /// The `core.hooksPath` of a repository, if set
+    pub fn hooks_path(repo_path: &Path) -> BasecampResult<Option<PathBuf>> {
+        let config = Repository::open(repo_path)?.config()?;
+        match config.get_path("core.hooksPath") {
+            Ok(path) => Ok(Some(path)),
+            Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Set `core.hooksPath` of a repository, or remove it with `None`
+    pub fn set_hooks_path(repo_path: &Path, path: Option<&Path>) -> BasecampResult<()> {
+        let mut config = Repository::open(repo_path)?.config()?.open_level(git2::ConfigLevel::Local)?;
+        match path {
+            Some(path) => config.set_str("core.hooksPath", &path.to_string_lossy())?,
+            None => {
+                if let Err(e) = config.remove("core.hooksPath")
+                    && e.code() != ErrorCode::NotFound
+                {
+                    return Err(e.into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// A value of the local git config of a repository (`.git/config`), if set
+    pub fn local_config(repo_path: &Path, key: &str) -> BasecampResult<Option<String>> {
+        let config = Repository::open(repo_path)?.config()?.open_level(git2::ConfigLevel::Local)?;
+        match config.get_string(key) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// A value of the git config in effect in a repository (local, global or system), if set
+    pub fn config_string(repo_path: &Path, key: &str) -> BasecampResult<Option<String>> {
+        let config = Repository::open(repo_path)?.config()?.snapshot()?;
+        match config.get_string(key) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Set a value of the local git config of a repository
+    pub fn set_local_config(repo_path: &Path, key: &str, value: &str) -> BasecampResult<()> {
+        let mut config = Repository::open(repo_path)?.config()?.open_level(git2::ConfigLevel::Local)?;
+        config.set_str(key, value)?;
+        Ok(())
+    }
+
+    /// Add a pattern to the local excludes of a repository (`.git/info/exclude`), unless it is there
+    pub fn exclude(repo_path: &Path, pattern: &str) -> BasecampResult<()> {
+        let repo = Repository::open(repo_path)?;
+        let path = repo.path().join("info").join("exclude");
+        let mut content = std::fs::read_to_string(&path).unwrap_or_default();
+        if content.lines().any(|line| line.trim() == pattern) {
+            return Ok(());
+        }
+
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(pattern);
+        content.push('\n');
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, content)?;
+        Ok(())
+    }
+
+    /// Check if a repository has uncommitted changes
+    pub fn has_uncommitted_changes(repo_path: &Path) -> BasecampResult<bool> {
+        debug!("Checking for uncommitted changes in {:?}", repo_path);
+
+        let repo = Repository::open(repo_path)?;
+        let mut status_opts = StatusOptions::new();
+        status_opts.include_untracked(true);
+
+        let statuses = repo.statuses(Some(&mut status_opts))?;
+
+        // Files left out by a sparse checkout show up as deleted
+        let index = repo.index()?;
+        let changes = statuses
+            .iter()
+            .filter(|entry| {
+                entry.status() != git2::Status::WT_DELETED
+                    || !entry.path().and_then(|path| index.get_path(Path::new(path), 0)).is_some_and(|entry| {
+                        entry.flags_extended & git2::IndexEntryExtendedFlag::SKIP_WORKTREE.bits() != 0
+                    })
+            })
+            .count();
+
+        if changes > 0 {
+            debug!("Found {} uncommitted changes", changes);
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    /// Branch (`None` when detached) and commit checked out in a repository
+    pub fn checkout_state(repo_path: &Path) -> BasecampResult<(Option<String>, String)> {
+        let repo = Repository::open(repo_path)?;
+        let head = repo.head()?;
+        let branch = head.is_branch().then(|| head.shorthand().map(str::to_string)).flatten();
+        Ok((branch, head.peel_to_commit()?.id().to_string()))
+    }
+
+    /// Patch of every change to the checked out commit, untracked files
+    /// included, `None` for a clean working tree
+    pub fn working_tree_patch(repo_path: &Path) -> BasecampResult<Option<Vec<u8>>> {
+        let repo = Repository::open(repo_path)?;
+        let head_tree = repo.head()?.peel_to_tree()?;
+        let mut options = git2::DiffOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .show_untracked_content(true)
+            .show_binary(true);
+        let diff = repo.diff_tree_to_workdir_with_index(Some(&head_tree), Some(&mut options))?;
+        if diff.deltas().len() == 0 {
+            return Ok(None);
+        }
+
+        let mut patch = Vec::new();
+        diff.print(git2::DiffFormat::Patch, |_, _, line| {
+            // Content lines come without their origin
+            if matches!(line.origin(), '+' | '-' | ' ') {
+                patch.push(line.origin() as u8);
+            }
+            patch.extend_from_slice(line.content());
+            true
+        })?;
+        Ok(Some(patch))
+    }
+
+    /// Check out a branch (or a detached commit) at `commit` and apply a
+    /// working tree patch on top
+    ///
+    /// The branch is created when missing and moved to `commit` otherwise. A
+    /// repository with local changes, or a branch with commits `commit`
+    /// doesn't contain, is refused unless `force` is set, which discards them
+    /// along with untracked files.
+    pub fn restore_checkout(
+        repo_path: &Path,
+        branch: Option<&str>,
+        commit: &str,
+        patch: Option<&[u8]>,
+        force: bool,
+    ) -> BasecampResult<()> {
+        debug!("Restoring {:?} to {} ({:?})", repo_path, commit, branch);
+
+        if !force && Self::has_uncommitted_changes(repo_path)? {
+            return Err(BasecampError::UncommittedChanges(repo_path.to_path_buf()));
+        }
+        let repo = Repository::open(repo_path)?;
+        let target = git2::Oid::from_str(commit)
+            .and_then(|oid| repo.find_commit(oid))
+            .map_err(|_| BasecampError::Generic(format!("Commit {} isn't in {}; fetch it first", commit, repo_path.display())))?;
+
+        match branch {
+            Some(branch) => {
+                match repo.find_branch(branch, git2::BranchType::Local) {
+                    Ok(existing) => {
+                        // Moving the branch back would orphan the commits made since
+                        let tip = existing.get().peel_to_commit()?.id();
+                        let (ahead, _) = repo.graph_ahead_behind(tip, target.id())?;
+                        if ahead > 0 && !force {
+                            return Err(BasecampError::Generic(format!(
+                                "Branch '{}' in {} has {} commits that aren't in the snapshot",
+                                branch,
+                                repo_path.display(),
+                                ahead
+                            )));
+                        }
+                    }
+                    Err(_) => {
+                        repo.branch(branch, &target, false)?;
+                    }
+                }
+                repo.set_head(&format!("refs/heads/{}", branch))?;
+            }
+            None => repo.set_head_detached(target.id())?,
+        }
+        repo.reset(target.as_object(), git2::ResetType::Hard, None)?;
+        if force {
+            // A hard reset leaves untracked files behind
+            let mut checkout = git2::build::CheckoutBuilder::new();
+            checkout.force().remove_untracked(true);
+            repo.checkout_head(Some(&mut checkout))?;
+        }
+
+        if let Some(patch) = patch {
+            let diff = git2::Diff::from_buffer(patch)?;
+            repo.apply(&diff, git2::ApplyLocation::WorkDir, None)?;
+        }
+        Ok(())
+    }
+
+    /// Check if a repository has unpushed commits
+    pub fn has_unpushed_commits(repo_path: &Path, remote: &str) -> BasecampResult<bool> {
+        debug!("Checking for unpushed commits in {:?}", repo_path);
+
+        let repo = Repository::open(repo_path)?;
+
+        // Get the current branch
+        let head = repo.head()?;
+        let branch_name = head.shorthand().unwrap_or("HEAD");
+
+        // Find remote tracking branch
+        let remote_branch =
+            match repo.find_branch(&format!("{}/{}", remote, branch_name), git2::BranchType::Remote) {
+                Ok(branch) => branch,
+                Err(_) => {
+                    debug!("No remote tracking branch found for {}", branch_name);
+                    return Ok(false); // No remote branch to compare with
+                }
+            };
+
+        // Get commits for comparison
+        let local_commit = head.peel_to_commit()?;
+        let remote_commit = remote_branch.get().peel_to_commit()?;
+
+        // Check if local is ahead of remote
+        let local_id = local_commit.id();
+        let remote_id = remote_commit.id();
+
+        if local_id != remote_id {
+            // Additional check could be done here with git2::graph_ahead_behind
+            // to count exactly how many commits ahead/behind
+            debug!("Local branch is different from remote branch");
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    /// Get the URL of a remote (None when the remote doesn't exist)
+    pub fn remote_url(repo_path: &Path, remote: &str) -> BasecampResult<Option<String>> {
+        let repo = Repository::open(repo_path)?;
+        match repo.find_remote(remote) {
+            Ok(remote) => Ok(remote.url().map(String::from)),
+            Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Point a remote at a new URL, creating the remote when it doesn't exist
+    pub fn set_remote_url(repo_path: &Path, remote: &str, url: &str) -> BasecampResult<()> {
+        debug!("Setting remote '{}' of {:?} to {}", remote, repo_path, url);
+
+        let repo = Repository::open(repo_path)?;
+        match repo.find_remote(remote) {
+            Ok(_) => repo.remote_set_url(remote, url)?,
+            Err(e) if e.code() == ErrorCode::NotFound => {
+                repo.remote(remote, url)?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        Ok(())
+    }
+
+    /// Whether two remote URLs point at the same repository (ignoring `.git` and trailing slashes)
+    pub fn same_remote(a: &str, b: &str) -> bool {
+        let normalize = |url: &str| {
+            let url = url.trim().trim_end_matches('/');
+            url.strip_suffix(".git").unwrap_or(url).to_string()
+        };
+        normalize(a) == normalize(b)
+    }
+
+    /// Fetch the clone remote (`origin` by default) of a repository
+    pub fn fetch(repo_path: &Path, remote: &str) -> BasecampResult<()> {
+        Self::fetch_cancellable(repo_path, remote, None)
+    }
+
+    /// Fetch the clone remote of a repository until done or `cancel` is cancelled
+    pub fn fetch_cancellable(repo_path: &Path, remote: &str, cancel: Option<&Cancel>) -> BasecampResult<()> {
+        debug!("Fetching {} in {:?}", remote, repo_path);
+
+        Self::ensure_online(&format!("fetch {}", repo_path.display()))?;
+        backend::current().fetch(repo_path, remote, cancel)?;
+
+        info!("Fetched {} in {:?}", remote, repo_path);
+        Ok(())
+    }
+
+    /// Get the branch, ahead/behind counts and dirty flag of a repository
+    ///
+    /// Ahead and behind are counted against the branch of the same name on `remote`.
+    pub fn status(repo_path: &Path, remote: &str) -> BasecampResult<RepoStatus> {
+        debug!("Computing status of {:?}", repo_path);
+        backend::current().status(repo_path, remote)
+    }
+
+    /// Fetch the clone remote and bring the current branch up to date with its upstream
+    ///
+    /// Fast-forwards when possible. Diverged branches are rebased when `rebase` is set
+    /// and rejected otherwise.
+    pub fn update(repo_path: &Path, remote: &str, rebase: bool) -> BasecampResult<UpdateResult> {
+        Self::fetch(repo_path, remote)?;
+
+        let repo = Repository::open(repo_path)?;
+        let head = match repo.head() {
+            Ok(head) => head,
+            Err(e) if e.code() == ErrorCode::UnbornBranch => return Ok(UpdateResult::UpToDate),
+            Err(e) => return Err(e.into()),
+        };
+
+        if !head.is_branch() {
+            return Err(BasecampError::Generic(format!(
+                "HEAD is detached in {}; check out a branch to update it",
+                repo_path.display()
+            )));
+        }
+
+        let branch_name = head.shorthand().unwrap_or("HEAD").to_string();
+        let local_oid = head.peel_to_commit()?.id();
+        let upstream_oid = match Self::upstream_oid(&repo, remote, &branch_name) {
+            Some(oid) => oid,
+            None => return Ok(UpdateResult::UpToDate),
+        };
+
+        let (ahead, behind) = repo.graph_ahead_behind(local_oid, upstream_oid)?;
+        if behind == 0 {
+            return Ok(UpdateResult::UpToDate);
+        }
+
+        if Self::has_uncommitted_changes(repo_path)? {
+            return Err(BasecampError::UncommittedChanges(repo_path.to_path_buf()));
+        }
+
+        if ahead == 0 {
+            debug!("Fast-forwarding '{}' in {:?}", branch_name, repo_path);
+            let target = repo.find_object(upstream_oid, None)?;
+            repo.checkout_tree(&target, Some(git2::build::CheckoutBuilder::new().safe()))?;
+            head.resolve()?
+                .set_target(upstream_oid, "basecamp: fast-forward")?;
+            return Ok(UpdateResult::FastForwarded(behind));
+        }
+
+        if !rebase {
+            return Err(BasecampError::Generic(format!(
+                "Branch '{}' in {} has diverged from its upstream ({} ahead, {} behind). Set 'update: rebase' in config.yaml or merge manually.",
+                branch_name,
+                repo_path.display(),
+                ahead,
+                behind
+            )));
+        }
+
+        Self::rebase_onto(&repo, upstream_oid)?;
+        Ok(UpdateResult::Rebased(behind))
+    }
+
+    /// Point the `upstream` remote of a fork at `upstream_url`, fetch it and
+    /// fast-forward the default branch to it
+    ///
+    /// The default branch is the one `HEAD` of the clone remote points at, `main` or
+    /// `master` otherwise. When it is checked out the working tree must be
+    /// clean; a branch with commits of its own is left alone. Returns the
+    /// branch and what happened to it.
+    pub fn sync_fork(repo_path: &Path, remote: &str, upstream_url: &str) -> BasecampResult<(String, UpdateResult)> {
+        Self::set_remote_url(repo_path, "upstream", upstream_url)?;
+        Self::ensure_online(&format!("fetch upstream of {}", repo_path.display()))?;
+        backend::current().fetch(repo_path, "upstream", None)?;
+
+        let repo = Repository::open(repo_path)?;
+        let branch_name = Self::default_branch(&repo, remote).ok_or_else(|| {
+            BasecampError::Generic(format!("Can't tell the default branch of {}", repo_path.display()))
+        })?;
+        let upstream_oid = repo
+            .refname_to_id(&format!("refs/remotes/upstream/{}", branch_name))
+            .map_err(|_| BasecampError::Generic(format!("Upstream has no branch '{}'", branch_name)))?;
+        let mut branch = repo.find_reference(&format!("refs/heads/{}", branch_name))?;
+        let local_oid = branch.peel_to_commit()?.id();
+
+        let (ahead, behind) = repo.graph_ahead_behind(local_oid, upstream_oid)?;
+        if behind == 0 {
+            return Ok((branch_name, UpdateResult::UpToDate));
+        }
+        if ahead > 0 {
+            return Err(BasecampError::Generic(format!(
+                "Branch '{}' in {} has diverged from upstream ({} ahead, {} behind); merge or rebase it manually",
+                branch_name,
+                repo_path.display(),
+                ahead,
+                behind
+            )));
+        }
+
+        let checked_out = repo.head().ok().and_then(|head| head.name().map(str::to_string)) == branch.name().map(str::to_string);
+        if checked_out {
+            if Self::has_uncommitted_changes(repo_path)? {
+                return Err(BasecampError::UncommittedChanges(repo_path.to_path_buf()));
+            }
+            let target = repo.find_object(upstream_oid, None)?;
+            repo.checkout_tree(&target, Some(git2::build::CheckoutBuilder::new().safe()))?;
+        }
+        debug!("Fast-forwarding '{}' in {:?} to upstream", branch_name, repo_path);
+        branch.set_target(upstream_oid, "basecamp: sync fork")?;
+        Ok((branch_name, UpdateResult::FastForwarded(behind)))
+    }
+
+    /// Push the branches of the clone remote and every tag of a repository to `url`
+    ///
+    /// The clone remote is fetched first, so the branches pushed, overwriting
+    /// the mirror's, are current. `token` authenticates with the mirror host
+    /// instead of the environment's tokens. Returns the number of references pushed.
+    pub fn push_mirror(repo_path: &Path, remote: &str, url: &str, token: Option<&str>) -> BasecampResult<usize> {
+        debug!("Pushing {:?} to {}", repo_path, url);
+        Self::fetch(repo_path, remote)?;
+
+        let repo = Repository::open(repo_path)?;
+        let remotes = format!("refs/remotes/{}/", remote);
+        let mut refspecs = Vec::new();
+        for reference in repo.references()? {
+            let reference = reference?;
+            let Some(name) = reference.name() else {
+                continue;
+            };
+            if let Some(branch) = name.strip_prefix(&remotes) {
+                if branch != "HEAD" {
+                    refspecs.push(format!("+{}:refs/heads/{}", name, branch));
+                }
+            } else if name.starts_with("refs/tags/") {
+                refspecs.push(format!("+{}:{}", name, name));
+            }
+        }
+        if refspecs.is_empty() {
+            return Ok(0);
+        }
+
+        backend::current().push(repo_path, url, &refspecs, token)?;
+        info!("Pushed {} references of {:?} to {}", refspecs.len(), repo_path, url);
+        Ok(refspecs.len())
+    }
+
+    /// Commit a pinned revision resolves to: a tag, a commit SHA, or a branch
+    /// of the clone remote
+    pub fn resolve_rev(repo_path: &Path, remote: &str, rev: &str) -> BasecampResult<git2::Oid> {
+        let repo = Repository::open(repo_path)?;
+        Self::find_rev(&repo, remote, rev).ok_or_else(|| {
+            BasecampError::Generic(format!("Revision '{}' isn't in {}; fetch it first", rev, repo_path.display()))
+        })
+    }
+
+    fn find_rev(repo: &Repository, remote: &str, rev: &str) -> Option<git2::Oid> {
+        [
+            format!("refs/tags/{}", rev),
+            rev.to_string(),
+            format!("refs/remotes/{}/{}", remote, rev),
+        ]
+        .iter()
+        .find_map(|spec| repo.revparse_single(spec).ok()?.peel_to_commit().ok())
+        .map(|commit| commit.id())
+    }
+
+    /// Check out the commit a pinned revision resolves to, with HEAD detached
+    ///
+    /// The working tree must be clean unless HEAD is already there. Returns
+    /// whether HEAD moved.
+    pub fn checkout_rev(repo_path: &Path, remote: &str, rev: &str) -> BasecampResult<bool> {
+        let target = Self::resolve_rev(repo_path, remote, rev)?;
+        let repo = Repository::open(repo_path)?;
+        let head = repo.head().ok().and_then(|head| head.target());
+        if repo.head_detached().unwrap_or(false) && head == Some(target) {
+            return Ok(false);
+        }
+        if Self::has_uncommitted_changes(repo_path)? {
+            return Err(BasecampError::UncommittedChanges(repo_path.to_path_buf()));
+        }
+
+        debug!("Checking out {} ({}) in {:?}", rev, target, repo_path);
+        let commit = repo.find_commit(target)?;
+        repo.checkout_tree(commit.as_object(), Some(git2::build::CheckoutBuilder::new().safe()))?;
+        repo.set_head_detached(target)?;
+        Ok(true)
+    }
+
+    /// Whether HEAD is anywhere but on the commit of a pinned revision
+    ///
+    /// A revision that isn't fetched counts as drifted.
+    pub fn drifted_from(repo_path: &Path, remote: &str, rev: &str) -> BasecampResult<bool> {
+        let repo = Repository::open(repo_path)?;
+        let head = repo.head().ok().and_then(|head| head.target());
+        Ok(head.is_none() || Self::find_rev(&repo, remote, rev) != head)
+    }
+
+    /// Tags pointing at the commit checked out in a repository
+    pub fn tags_at_head(repo_path: &Path) -> BasecampResult<Vec<String>> {
+        let repo = Repository::open(repo_path)?;
+        let Some(head) = repo.head().ok().and_then(|head| head.target()) else {
+            return Ok(Vec::new());
+        };
+        Ok(repo
+            .tag_names(None)?
+            .iter()
+            .flatten()
+            .filter(|tag| {
+                repo.revparse_single(&format!("refs/tags/{}", tag))
+                    .and_then(|object| object.peel_to_commit())
+                    .is_ok_and(|commit| commit.id() == head)
+            })
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Newer revision for a pin, as fetched: the highest version tag of the
+    /// same series for a tag (`v1.4.2` to `v1.5.0`, pre-releases excluded),
+    /// or the tip of the remote's default branch for a commit SHA
+    ///
+    /// `None` when the pin is current, or names a branch and follows it anyway.
+    pub fn bumped_rev(repo_path: &Path, remote: &str, rev: &str) -> BasecampResult<Option<String>> {
+        let repo = Repository::open(repo_path)?;
+        let current = Self::find_rev(&repo, remote, rev).ok_or_else(|| {
+            BasecampError::Generic(format!("Revision '{}' isn't in {}; fetch it first", rev, repo_path.display()))
+        })?;
+
+        if repo.find_reference(&format!("refs/tags/{}", rev)).is_ok() {
+            let prefix: String = rev.chars().take_while(|c| !c.is_ascii_digit()).collect();
+            let Some(pinned) = version_key(rev, &prefix) else {
+                return Ok(None);
+            };
+            let latest = repo
+                .tag_names(None)?
+                .iter()
+                .flatten()
+                .filter_map(|tag| Some((version_key(tag, &prefix)?, tag.to_string())))
+                .filter(|(version, _)| *version > pinned)
+                .max();
+            return Ok(latest.map(|(_, tag)| tag));
+        }
+
+        let is_sha = rev.len() >= 7 && rev.chars().all(|c| c.is_ascii_hexdigit());
+        if !is_sha {
+            return Ok(None);
+        }
+        let branch = Self::default_branch(&repo, remote).ok_or_else(|| {
+            BasecampError::Generic(format!("Can't tell the default branch of {}", repo_path.display()))
+        })?;
+        let tip = repo.refname_to_id(&format!("refs/remotes/{}/{}", remote, branch))?;
+        if tip == current {
+            return Ok(None);
+        }
+        let tip = tip.to_string();
+        Ok(Some(tip[..rev.len().clamp(7, tip.len())].to_string()))
+    }
+
+    /// Restrict the working tree of a clone to `paths` (cone-mode sparse checkout)
+    ///
+    /// Like `git sparse-checkout set --cone`: files at the top level, files
+    /// directly in the parents of `paths` and everything under `paths` are
+    /// kept. The patterns and `core.sparseCheckout` are written for git, the
+    /// other files are marked skip-worktree and deleted, and files brought
+    /// back in by new paths are restored. Returns the number of files left out.
+    ///
+    /// Like git, it refuses to leave out files with uncommitted changes.
+    pub fn apply_sparse(repo_path: &Path, paths: &[String]) -> BasecampResult<usize> {
+        debug!("Applying sparse checkout {:?} to {:?}", paths, repo_path);
+
+        let repo = Repository::open(repo_path)?;
+        let workdir = repo.workdir().ok_or_else(|| {
+            BasecampError::Generic(format!("{} has no working tree", repo_path.display()))
+        })?.to_path_buf();
+        let dirs: Vec<String> = paths.iter().map(|path| path.trim_matches('/').to_string()).filter(|path| !path.is_empty()).collect();
+
+        let mut index = repo.index()?;
+        let entries: Vec<git2::IndexEntry> = index.iter().collect();
+        let is_skipped = |entry: &git2::IndexEntry| {
+            entry.flags_extended & git2::IndexEntryExtendedFlag::SKIP_WORKTREE.bits() != 0
+        };
+
+        // Leaving out a modified or staged file would delete the change
+        // (files already left out show up as deleted, so they're not asked about)
+        let mut options = StatusOptions::new();
+        options.include_untracked(false).include_ignored(false);
+        let statuses = repo.statuses(Some(&mut options))?;
+        let changed: std::collections::HashSet<String> = statuses
+            .iter()
+            .filter(|status| status.status() != git2::Status::CURRENT)
+            .filter_map(|status| status.path().map(str::to_string))
+            .collect();
+        let leaves_out_changes = entries.iter().any(|entry| {
+            let path = String::from_utf8_lossy(&entry.path);
+            !is_skipped(entry) && !in_cone(&path, &dirs) && changed.contains(path.as_ref())
+        });
+        if leaves_out_changes {
+            return Err(BasecampError::UncommittedChanges(repo_path.to_path_buf()));
+        }
+
+        let info = repo.path().join("info");
+        std::fs::create_dir_all(&info)?;
+        std::fs::write(info.join("sparse-checkout"), sparse_patterns(&dirs))?;
+        let mut config = repo.config()?;
+        config.set_bool("core.sparseCheckout", true)?;
+        config.set_bool("core.sparseCheckoutCone", true)?;
+
+        let (mut left_out, mut restored) = (0, Vec::new());
+        for mut entry in entries {
+            let path = String::from_utf8_lossy(&entry.path).into_owned();
+            let skipped = is_skipped(&entry);
+            let wanted = in_cone(&path, &dirs);
+            if wanted && skipped {
+                entry.flags_extended &= !git2::IndexEntryExtendedFlag::SKIP_WORKTREE.bits();
+                index.add(&entry)?;
+                restored.push(path);
+            } else if !wanted {
+                left_out += 1;
+                if !skipped {
+                    entry.flags |= git2::IndexEntryFlag::EXTENDED.bits();
+                    entry.flags_extended |= git2::IndexEntryExtendedFlag::SKIP_WORKTREE.bits();
+                    index.add(&entry)?;
+                }
+                let file = workdir.join(&path);
+                if file.symlink_metadata().is_ok() {
+                    std::fs::remove_file(&file)?;
+                    remove_empty_parents(&workdir, &file);
+                }
+            }
+        }
+        index.write()?;
+
+        if !restored.is_empty() {
+            let mut checkout = git2::build::CheckoutBuilder::new();
+            checkout.force();
+            for path in &restored {
+                checkout.path(path);
+            }
+            repo.checkout_index(Some(&mut index), Some(&mut checkout))?;
+        }
+
+        info!("Sparse checkout of {:?} leaves out {} files", repo_path, left_out);
+        Ok(left_out)
+    }
+
+    /// Branch `HEAD` of `remote` points at, or the first of `main` and `master` that exists
+    fn default_branch(repo: &Repository, remote: &str) -> Option<String> {
+        let remotes = format!("refs/remotes/{}/", remote);
+        let origin_head = repo
+            .find_reference(&format!("{}HEAD", remotes))
+            .ok()
+            .and_then(|head| head.symbolic_target().map(str::to_string))
+            .and_then(|target| target.strip_prefix(&remotes).map(str::to_string));
+        origin_head.or_else(|| {
+            ["main", "master"]
+                .into_iter()
+                .find(|name| repo.find_branch(name, git2::BranchType::Local).is_ok())
+                .map(str::to_string)
+        })
+    }
+
+    /// Rebase the current branch onto a commit, aborting on conflicts
+    fn rebase_onto(repo: &Repository, upstream_oid: git2::Oid) -> BasecampResult<()> {
+        debug!("Rebasing onto {}", upstream_oid);
+
+        let upstream = repo.find_annotated_commit(upstream_oid)?;
+        // Without a configured identity, reuse the committer of the current HEAD
+        let signature = match repo.signature() {
+            Ok(signature) => signature,
+            Err(_) => {
+                let head = repo.head()?.peel_to_commit()?;
+                let committer = head.committer();
+                git2::Signature::now(
+                    committer.name().unwrap_or("basecamp"),
+                    committer.email().unwrap_or_default(),
+                )?
+            }
+        };
+        let mut rebase = repo.rebase(None, Some(&upstream), None, None)?;
+
+        while let Some(operation) = rebase.next() {
+            operation?;
+
+            if repo.index()?.has_conflicts() {
+                rebase.abort()?;
+                return Err(BasecampError::Generic(format!(
+                    "Rebase of {} stopped on conflicts and was aborted; rebase manually",
+                    repo.workdir().unwrap_or_else(|| repo.path()).display()
+                )));
+            }
+
+            match rebase.commit(None, &signature, None) {
+                Ok(_) => {}
+                // The change is already upstream
+                Err(e) if e.code() == ErrorCode::Applied => {}
+                Err(e) => {
+                    rebase.abort()?;
+                    return Err(e.into());
+                }
+            }
+        }
+
+        rebase.finish(Some(&signature))?;
+        Ok(())
+    }
+
+    /// Unix time of the latest commit on the upstream of the current branch
+    ///
+    /// `None` when HEAD is detached or the branch has no upstream.
+    pub fn upstream_commit_time(repo_path: &Path, remote: &str) -> BasecampResult<Option<u64>> {
+        let repo = Repository::open(repo_path)?;
+        let Ok(head) = repo.head() else {
+            return Ok(None);
+        };
+        if !head.is_branch() {
+            return Ok(None);
+        }
+
+        let branch_name = head.shorthand().unwrap_or("HEAD");
+        let Some(upstream_oid) = Self::upstream_oid(&repo, remote, branch_name) else {
+            return Ok(None);
+        };
+        let seconds = repo.find_commit(upstream_oid)?.time().seconds();
+        Ok(u64::try_from(seconds).ok())
+    }
+
+    /// Commits reachable from HEAD authored since a Unix time, newest first
+    ///
+    /// Churn is measured against the first parent; merges count as commits without churn.
+    pub fn commits_since(repo_path: &Path, since: u64) -> BasecampResult<Vec<CommitStats>> {
+        let repo = Repository::open(repo_path)?;
+        let mut walk = repo.revwalk()?;
+        if walk.push_head().is_err() {
+            // Nothing committed yet
+            return Ok(Vec::new());
+        }
+        walk.set_sorting(git2::Sort::TIME)?;
+
+        let mut commits = Vec::new();
+        for oid in walk {
+            let commit = repo.find_commit(oid?)?;
+            if u64::try_from(commit.time().seconds()).unwrap_or_default() < since {
+                break;
+            }
+
+            let (insertions, deletions) = if commit.parent_count() > 1 {
+                (0, 0)
+            } else {
+                let parent_tree = match commit.parent(0) {
+                    Ok(parent) => Some(parent.tree()?),
+                    Err(_) => None,
+                };
+                let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+                let stats = diff.stats()?;
+                (stats.insertions(), stats.deletions())
+            };
+            commits.push(CommitStats {
+                author: commit.author().email().unwrap_or_default().to_lowercase(),
+                insertions,
+                deletions,
+            });
+        }
+        Ok(commits)
+    }
+
+    /// Unix time of the commit checked out in a repository, `None` before the first commit
+    pub fn head_commit_time(repo_path: &Path) -> BasecampResult<Option<u64>> {
+        let repo = Repository::open(repo_path)?;
+        let Ok(commit) = repo.head().and_then(|head| head.peel_to_commit()) else {
+            return Ok(None);
+        };
+        Ok(u64::try_from(commit.time().seconds()).ok())
+    }
+
+    /// The commit checked out in a repository, `None` before the first commit
+    pub fn head_commit(repo_path: &Path) -> BasecampResult<Option<CommitInfo>> {
+        let repo = Repository::open(repo_path)?;
+        let Ok(commit) = repo.head().and_then(|head| head.peel_to_commit()) else {
+            return Ok(None);
+        };
+        let id = commit.id().to_string();
+        Ok(Some(CommitInfo {
+            id: id[..id.len().min(7)].to_string(),
+            author: commit.author().name().unwrap_or_default().to_string(),
+            time: u64::try_from(commit.time().seconds()).unwrap_or_default(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+        }))
+    }
+
+    /// Remotes of a repository with their URLs, by name
+    pub fn remotes(repo_path: &Path) -> BasecampResult<Vec<(String, String)>> {
+        let repo = Repository::open(repo_path)?;
+        let mut remotes = Vec::new();
+        for name in repo.remotes()?.iter().flatten() {
+            let url = repo.find_remote(name)?.url().unwrap_or_default().to_string();
+            remotes.push((name.to_string(), url));
+        }
+        remotes.sort();
+        Ok(remotes)
+    }
+
+    /// Get the upstream commit of a branch: its configured upstream, or `<remote>/<branch>`
+    fn upstream_oid(repo: &Repository, remote: &str, branch_name: &str) -> Option<git2::Oid> {
+        repo.find_branch(branch_name, git2::BranchType::Local)
+            .and_then(|branch| branch.upstream())
+            .or_else(|_| repo.find_branch(&format!("{}/{}", remote, branch_name), git2::BranchType::Remote))
+            .ok()
+            .and_then(|branch| branch.get().target())
+    }
+
+    /// Apply the `url.<base>.insteadOf` rewrites of the git configuration to a URL
+    ///
+    /// This is what plain `git clone` does, e.g. to route a host through another
+    /// protocol or account.
+    pub fn rewrite_url(url: &str) -> String {
+        let mut rewrites = Vec::new();
+        if let Ok(config) = git2::Config::open_default()
+            && let Ok(entries) = config.entries(Some(r"url\..*\.insteadof"))
+        {
+            let _ = entries.for_each(|entry| {
+                let base = entry
+                    .name()
+                    .and_then(|name| name.strip_prefix("url."))
+                    .and_then(|name| name.get(..name.len().saturating_sub(".insteadof".len())));
+                if let (Some(base), Some(prefix)) = (base, entry.value()) {
+                    rewrites.push((base.to_string(), prefix.to_string()));
+                }
+            });
+        }
+        Self::apply_url_rewrites(url, &rewrites)
+    }
+
+    /// Rewrite a URL with `(base, prefix)` pairs: the longest `prefix` the URL
+    /// starts with is replaced by its `base`
+    pub fn apply_url_rewrites(url: &str, rewrites: &[(String, String)]) -> String {
+        rewrites
+            .iter()
+            .filter(|(_, prefix)| !prefix.is_empty() && url.starts_with(prefix.as_str()))
+            .max_by_key(|(_, prefix)| prefix.len())
+            .map(|(base, prefix)| format!("{}{}", base, &url[prefix.len()..]))
+            .unwrap_or_else(|| url.to_string())
+    }
+
+    /// Build a repository URL from the GitHub base URL and repository name
+    pub fn build_repo_url(github_url: &str, repo_name: &str) -> String {
+        // Handle both https and git@ URL formats
+        if github_url.starts_with("https://") {
+            // Ensure URL ends with a slash
+            let base_url = if github_url.ends_with('/') {
+                github_url.to_string()
+            } else {
+                format!("{}/", github_url)
+            };
+
+            format!("{}{}.git", base_url, repo_name)
+        } else if github_url.starts_with("git@") {
+            // Handle SSH format
+            let parts: Vec<&str> = github_url.split(':').collect();
+            if parts.len() == 2 {
+                let host = parts[0];
+                let path = if parts[1].ends_with('/') {
+                    parts[1]
+                } else {
+                    &format!("{}/", parts[1])
+                };
+                format!("{}:{}{}.git", host, path, repo_name)
+            } else {
+                // Fallback for malformed URLs
+                format!("{}/{}.git", github_url, repo_name)
+            }
+        } else {
+            // Fallback for other formats
+            format!("{}/{}.git", github_url, repo_name)
+        }
+    }
+
+    /// Build the browser URL of a repository from the configured base URL
+    ///
+    /// Handles HTTP(S) (`https://host/org`), SCP-like SSH (`git@host:org`) and
+    /// `ssh://` URLs, including enterprise hosts and custom SSH ports. SSH
+    /// remotes are browsed over HTTPS, HTTP ones keep their scheme.
+    pub fn build_web_url(github_url: &str, repo_name: &str) -> String {
+        let Some((host, path)) = split_host_path(github_url) else {
+            let base = github_url.trim_end_matches('/').trim_end_matches(".git");
+            return format!("{}/{}", base, repo_name);
+        };
+
+        let scheme = if github_url.starts_with("http://") { "http" } else { "https" };
+        if path.is_empty() {
+            format!("{}://{}/{}", scheme, host, repo_name)
+        } else {
+            format!("{}://{}/{}/{}", scheme, host, path, repo_name)
+        }
+    }
+
+    /// URL of an `upstream` or `remotes` setting: a URL as given, or `owner/name` on the host of `github_url`
+    pub fn expand_remote_url(github_url: &str, remote: &str) -> String {
+        let is_url = remote.contains("://") || remote.starts_with("git@") || Path::new(remote).is_absolute();
+        let (Some((owner, name)), false) = (remote.trim_matches('/').rsplit_once('/'), is_url) else {
+            return remote.to_string();
+        };
+        match split_host_path(github_url) {
+            Some((host, _)) if github_url.starts_with("https://") || github_url.starts_with("http://") => {
+                Self::build_repo_url(&format!("https://{}/{}", host, owner), name.trim_end_matches(".git"))
+            }
+            Some((host, _)) => Self::build_repo_url(&format!("git@{}:{}", host, owner), name.trim_end_matches(".git")),
+            None => remote.to_string(),
+        }
+    }
+
+    /// Rewrite a `github_url` to use SSH (`git@host:org`) or HTTPS (`https://host/org`)
+    ///
+    /// The port of an `ssh://` URL is kept when staying on SSH.
+    /// Returns `None` when the host can't be determined from the URL.
+    pub fn convert_url(github_url: &str, ssh: bool) -> Option<String> {
+        let (host, path) = split_host_path(github_url)?;
+        Some(if ssh {
+            ssh_url(&host, ssh_port(github_url), &path)
+        } else {
+            format!("https://{}/{}", host, path)
+        })
+    }
+
+    /// Move a `github_url` from organization `old` to `new`, keeping its URL style
+    ///
+    /// `old` and `new` are either an organization name or `host/organization`.
+    /// Returns `None` when the URL doesn't belong to `old`.
+    pub fn rename_org(github_url: &str, old: &str, new: &str) -> Option<String> {
+        let (host, path) = split_host_path(github_url)?;
+
+        let matches = match old.split_once('/') {
+            Some((old_host, old_path)) => {
+                host.eq_ignore_ascii_case(old_host) && path.eq_ignore_ascii_case(old_path)
+            }
+            None => path.eq_ignore_ascii_case(old),
+        };
+        if !matches {
+            return None;
+        }
+
+        let (new_host, new_path) = new.split_once('/').unwrap_or((&host, new));
+        let ssh = !github_url.starts_with("https://") && !github_url.starts_with("http://");
+        Some(if ssh {
+            // The SSH port belongs to the host, so it only survives staying on it
+            let port = ssh_port(github_url).filter(|_| new_host.eq_ignore_ascii_case(&host));
+            ssh_url(new_host, port, new_path)
+        } else {
+            format!("https://{}/{}", new_host, new_path)
+        })
+    }
+
+    /// Host and owner (organization, group or user) of a repository remote URL
+    ///
+    /// `git@github.com:acme/web.git` gives `("github.com", "acme")`.
+    pub fn remote_owner(url: &str) -> Option<(String, String)> {
+        let (base, _) = Self::split_repo_url(url)?;
+        split_host_path(&base).filter(|(host, owner)| !host.is_empty() && !owner.is_empty())
+    }
+
+    /// Split a repository remote URL into its base URL and repository name
+    pub fn split_repo_url(url: &str) -> Option<(String, String)> {
+        let url = url.trim_end_matches('/').trim_end_matches(".git");
+        let (base, name) = url.rsplit_once('/')?;
+        if name.is_empty() || base.ends_with(':') || base.ends_with('/') {
+            return None;
+        }
+        Some((base.to_string(), name.to_string()))
+    }
+
+    /// Clone a repository into a codebase, returning the number of bytes received
+    ///
+    /// A `shared` repository is cloned once under `.basecamp/shared/` (unless
+    /// another codebase already did) and linked into the codebase.
+    pub fn clone_into(
+        workspace: &Workspace,
+        url: &str,
+        remote: &str,
+        codebase: &str,
+        repo_name: &str,
+        shared: bool,
+    ) -> BasecampResult<u64> {
+        if !shared {
+            return backend::current().clone(url, &workspace.repo_path(codebase, repo_name), remote);
+        }
+
+        let shared_path = workspace.shared_path(repo_name);
+        let bytes = if shared_path.exists() {
+            0
+        } else {
+            backend::current().clone(url, &shared_path, remote)?
+        };
+        Self::link_shared(workspace, codebase, repo_name)?;
+        Ok(bytes)
+    }
+
+    /// Link a repository of a codebase to its shared clone
+    ///
+    /// The link is relative, so the workspace can be moved.
+    pub fn link_shared(workspace: &Workspace, codebase: &str, repo_name: &str) -> BasecampResult<()> {
+        let path = workspace.repo_path(codebase, repo_name);
+
+        let mut target = PathBuf::new();
+        for _ in codebase.split('/') {
+            target.push("..");
+        }
+        target.push(".basecamp");
+        target.push("shared");
+        target.push(repo_name);
+
+        // Replace a dangling link left by a deleted shared clone
+        if path.symlink_metadata().is_ok_and(|m| m.file_type().is_symlink()) {
+            std::fs::remove_file(&path)?;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        debug!("Linking {:?} to {:?}", path, target);
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&target, &path)?;
+        #[cfg(windows)]
+        std::os::windows::fs::symlink_dir(&target, &path)?;
+        Ok(())
+    }
+}
+
+/// Split a remote base URL into its host and path, dropping users, ports,
+/// credentials and a trailing `.git`
+fn split_host_path(github_url: &str) -> Option<(String, String)> {
+    let base = github_url.trim_end_matches('/').trim_end_matches(".git");
+
+    let (host, path) = if let Some(rest) = base.strip_prefix("ssh://") {
+        // ssh://[user@]host[:port]/path
+        let rest = rest.split_once('@').map(|(_, r)| r).unwrap_or(rest);
+        let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+        (host.split(':').next().unwrap_or(host), path)
+    } else if let Some(rest) = base
+        .strip_prefix("https://")
+        .or_else(|| base.strip_prefix("http://"))
+    {
+        // Drop credentials embedded in the URL
+        let rest = rest.split_once('@').map(|(_, r)| r).unwrap_or(rest);
+        rest.split_once('/').unwrap_or((rest, ""))
+    } else if let Some((user_host, path)) = base.split_once(':') {
+        // git@host:path
+        let host = user_host.split_once('@').map(|(_, h)| h).unwrap_or(user_host);
+        (host, path)
+    } else {
+        return None;
+    };
+
+    Some((host.to_string(), path.trim_matches('/').to_string()))
+}
+
+/// Port of an `ssh://[user@]host:port/path` URL
+fn ssh_port(github_url: &str) -> Option<&str> {
+    let authority = github_url.strip_prefix("ssh://")?.split('/').next()?;
+    let host = authority.rsplit_once('@').map(|(_, h)| h).unwrap_or(authority);
+    host.split_once(':').map(|(_, port)| port).filter(|port| !port.is_empty())
+}
+
+/// SSH URL of a path on a host, SCP-like unless a port has to be given
+///
+/// An HTTPS port in `host` doesn't apply to SSH and is dropped.
+fn ssh_url(host: &str, port: Option<&str>, path: &str) -> String {
+    let host = host.split(':').next().unwrap_or(host);
+    match port {
+        Some(port) => format!("ssh://git@{}:{}/{}", host, port, path),
+        None => format!("git@{}:{}", host, path),
+    }
+}
+
+/// Numeric parts of a version tag after `prefix` (`v1.4.2` is `[1, 4, 2]` for `v`)
+fn version_key(tag: &str, prefix: &str) -> Option<Vec<u64>> {
+    tag.strip_prefix(prefix)?
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect()
+}
+
+/// Whether a file is in the cone of sparse directories: at the top level,
+/// directly in a parent of one of them, or under one of them
+fn in_cone(path: &str, dirs: &[String]) -> bool {
+    let parent = path.rsplit_once('/').map_or("", |(parent, _)| parent);
+    parent.is_empty()
+        || dirs.iter().any(|dir| {
+            path.starts_with(&format!("{}/", dir)) || dir.starts_with(&format!("{}/", parent))
+        })
+}
+
+/// Cone-mode sparse-checkout patterns, as `git sparse-checkout set --cone` writes them
+fn sparse_patterns(dirs: &[String]) -> String {
+    let mut parents: Vec<String> = Vec::new();
+    for dir in dirs {
+        let mut parent = String::new();
+        for part in dir.split('/').collect::<Vec<_>>().split_last().map_or(&[][..], |(_, rest)| rest) {
+            parent = if parent.is_empty() { part.to_string() } else { format!("{}/{}", parent, part) };
+            if !parents.contains(&parent) && !dirs.contains(&parent) {
+                parents.push(parent.clone());
+            }
+        }
+    }
+    parents.sort();
+
+    let mut patterns = String::from("/*\n!/*/\n");
+    for parent in &parents {
+        patterns.push_str(&format!("/{}/\n!/{}/*/\n", parent, parent));
+    }
+    let mut dirs = dirs.to_vec();
+    dirs.sort();
+    for dir in &dirs {
+        patterns.push_str(&format!("/{}/\n", dir));
+    }
+    patterns
+}
+
+/// Remove the directories left empty above a deleted file, up to `root`
+fn remove_empty_parents(root: &Path, file: &Path) {
+    let mut dir = file.parent();
+    while let Some(path) = dir {
+        if path == root || std::fs::remove_dir(path).is_err() {
+            break;
+        }
+        dir = path.parent();
+    }
+}
@@ -0,0 +1,502 @@
+use std::fmt;
+use std::str::FromStr;
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{BasecampError, BasecampResult};
+
+/// Repositories requested per page from hosting APIs
+const PAGE_SIZE: usize = 100;
+
+/// Git hosting services, as named by `provider` in config.yaml
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HostProvider {
+    /// github.com
+    Github,
+    /// A self-hosted GitHub Enterprise Server
+    GithubEnterprise,
+    /// gitlab.com, or a self-hosted GitLab
+    Gitlab,
+    /// bitbucket.org
+    Bitbucket,
+    /// Any other host serving `https://<host>/<owner>/<repo>.git` and `git@<host>:<owner>/<repo>.git`
+    Custom,
+}
+
+impl HostProvider {
+    /// Providers in the order `init` offers them
+    pub const ALL: [HostProvider; 5] = [
+        HostProvider::Github,
+        HostProvider::GithubEnterprise,
+        HostProvider::Gitlab,
+        HostProvider::Bitbucket,
+        HostProvider::Custom,
+    ];
+
+    /// Name used in config.yaml and on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            HostProvider::Github => "github",
+            HostProvider::GithubEnterprise => "github_enterprise",
+            HostProvider::Gitlab => "gitlab",
+            HostProvider::Bitbucket => "bitbucket",
+            HostProvider::Custom => "custom",
+        }
+    }
+
+    /// Name shown to users
+    pub fn label(self) -> &'static str {
+        match self {
+            HostProvider::Github => "GitHub",
+            HostProvider::GithubEnterprise => "GitHub Enterprise",
+            HostProvider::Gitlab => "GitLab",
+            HostProvider::Bitbucket => "Bitbucket",
+            HostProvider::Custom => "Other (custom host)",
+        }
+    }
+
+    /// Host of the public service, `None` for providers that are always self-hosted
+    pub fn default_host(self) -> Option<&'static str> {
+        match self {
+            HostProvider::Github => Some("github.com"),
+            HostProvider::Gitlab => Some("gitlab.com"),
+            HostProvider::Bitbucket => Some("bitbucket.org"),
+            HostProvider::GithubEnterprise | HostProvider::Custom => None,
+        }
+    }
+
+    /// What the provider calls a shared account owning repositories
+    pub fn organization_noun(self) -> &'static str {
+        match self {
+            HostProvider::Gitlab => "group",
+            HostProvider::Bitbucket => "workspace",
+            _ => "organization",
+        }
+    }
+
+    /// Guess the provider serving a host
+    pub fn detect(host: &str) -> Self {
+        let host = normalize_host(host).to_ascii_lowercase();
+        match host.as_str() {
+            "github.com" => HostProvider::Github,
+            "gitlab.com" => HostProvider::Gitlab,
+            "bitbucket.org" => HostProvider::Bitbucket,
+            _ if host.contains("github") => HostProvider::GithubEnterprise,
+            _ if host.contains("gitlab") => HostProvider::Gitlab,
+            _ => HostProvider::Custom,
+        }
+    }
+
+    /// Base URL of the repositories of `owner` on `host` (`https://host/owner` or `git@host:owner`)
+    pub fn base_url(self, host: &str, owner: &str, ssh: bool) -> String {
+        let host = normalize_host(host);
+        let owner = owner.trim_matches('/');
+        if ssh {
+            format!("git@{}:{}", host, owner)
+        } else {
+            format!("https://{}/{}", host, owner)
+        }
+    }
+
+    /// Root of the host's REST API, `None` for custom hosts
+    fn api_root(self, host: &str) -> Option<String> {
+        match self {
+            HostProvider::Github => Some("https://api.github.com".to_string()),
+            HostProvider::GithubEnterprise => Some(format!("https://{}/api/v3", host)),
+            HostProvider::Gitlab => Some(format!("https://{}/api/v4", host)),
+            HostProvider::Bitbucket => Some("https://api.bitbucket.org/2.0".to_string()),
+            HostProvider::Custom => None,
+        }
+    }
+
+    /// Error for hosts without a known API
+    fn no_api(host: &str) -> BasecampError {
+        BasecampError::Generic(format!("Custom host {} has no known API", host))
+    }
+
+    /// Names of the repositories owned by `owner`, read from the host's API
+    ///
+    /// `owner` is tried as an organization (group) first, then as a user.
+    /// Private repositories are only listed with a `token`; archived ones are
+    /// left out. Custom hosts have no known API.
+    pub fn list_repositories(self, host: &str, owner: &str, token: Option<&str>) -> BasecampResult<Vec<String>> {
+        let host = normalize_host(host);
+        let owner = owner.trim_matches('/');
+        let api = self.api_root(host).ok_or_else(|| Self::no_api(host))?;
+        let (endpoints, name_field) = match self {
+            HostProvider::Gitlab => (
+                vec![
+                    // Subgroups are addressed by their encoded full path
+                    format!("{}/groups/{}/projects", api, owner.replace('/', "%2F")),
+                    format!("{}/users/{}/projects", api, owner),
+                ],
+                "path",
+            ),
+            HostProvider::Bitbucket => (vec![format!("{}/repositories/{}", api, owner)], "slug"),
+            _ => (
+                vec![format!("{}/orgs/{}/repos", api, owner), format!("{}/users/{}/repos", api, owner)],
+                "name",
+            ),
+        };
+
+        for endpoint in &endpoints {
+            let mut names = Vec::new();
+            let found = self.for_each_page(endpoint, token, |items| {
+                names.extend(
+                    items
+                        .iter()
+                        .filter(|item| item.get("archived").and_then(Value::as_bool) != Some(true))
+                        .filter_map(|item| item.get(name_field).and_then(Value::as_str))
+                        .map(str::to_string),
+                );
+            })?;
+            if found {
+                names.sort();
+                return Ok(names);
+            }
+        }
+        Err(BasecampError::Generic(format!(
+            "{} has no {} or user named '{}'",
+            host,
+            self.organization_noun(),
+            owner
+        )))
+    }
+
+    /// CI state of the default branch and number of open pull requests of `owner/repo`
+    ///
+    /// Also tells whether the repository is archived, and its new `owner/name`
+    /// when the host redirects a renamed or transferred repository.
+    pub fn remote_health(self, host: &str, owner: &str, repo: &str, token: Option<&str>) -> BasecampResult<RemoteHealth> {
+        let host = normalize_host(host);
+        let owner = owner.trim_matches('/');
+        let api = self.api_root(host).ok_or_else(|| Self::no_api(host))?;
+        let not_found = || BasecampError::Generic(format!("{}/{} not found on {}", owner, repo, host));
+
+        let mut answers = HealthAnswers::default();
+        match self {
+            HostProvider::Gitlab => {
+                let project = format!("{}/projects/{}%2F{}", api, owner.replace('/', "%2F"), repo);
+                answers.repository = self.api_get(&project, token)?.ok_or_else(not_found)?;
+                if let Some(branch) = self.default_branch_of(&answers.repository) {
+                    let pipelines = self.api_get(&format!("{}/pipelines?ref={}&per_page=1", project, branch), token)?;
+                    answers.runs = pipelines.and_then(|pipelines| pipelines.as_array().cloned()).unwrap_or_default();
+                }
+                self.for_each_page(&format!("{}/merge_requests?state=opened", project), token, |items| {
+                    answers.open_pull_requests += items.len();
+                })?;
+            }
+            HostProvider::Bitbucket => {
+                let repository = format!("{}/repositories/{}/{}", api, owner, repo);
+                answers.repository = self.api_get(&repository, token)?.ok_or_else(not_found)?;
+                if let Some(branch) = self.default_branch_of(&answers.repository) {
+                    self.for_each_page(&format!("{}/commit/{}/statuses", repository, branch), token, |items| {
+                        answers.runs.extend_from_slice(items);
+                    })?;
+                }
+                let open = self.api_get(&format!("{}/pullrequests?state=OPEN&pagelen=1", repository), token)?;
+                answers.open_pull_requests = open
+                    .and_then(|open| open.get("size")?.as_u64())
+                    .unwrap_or_default() as usize;
+            }
+            _ => {
+                let repository = format!("{}/repos/{}/{}", api, owner, repo);
+                answers.repository = self.api_get(&repository, token)?.ok_or_else(not_found)?;
+                if let Some(branch) = self.default_branch_of(&answers.repository) {
+                    // Actions report check runs, other services commit statuses
+                    let check_runs = format!("{}/commits/{}/check-runs?per_page=100", repository, branch);
+                    if let Some(checks) = self.api_get(&check_runs, token)? {
+                        answers.runs = checks.get("check_runs").and_then(Value::as_array).cloned().unwrap_or_default();
+                    }
+                    answers.combined_status = self.api_get(&format!("{}/commits/{}/status", repository, branch), token)?;
+                }
+                self.for_each_page(&format!("{}/pulls?state=open", repository), token, |items| {
+                    answers.open_pull_requests += items.len();
+                })?;
+            }
+        }
+        Ok(self.health_from(owner, repo, &answers))
+    }
+
+    /// What the API answers of [`HostProvider::remote_health`] tell about `owner/repo`
+    pub fn health_from(self, owner: &str, repo: &str, answers: &HealthAnswers) -> RemoteHealth {
+        let name_field = match self {
+            HostProvider::Gitlab => "path_with_namespace",
+            _ => "full_name",
+        };
+        let mut health = RemoteHealth {
+            default_branch: self.default_branch_of(&answers.repository),
+            open_pull_requests: answers.open_pull_requests,
+            ..RemoteHealth::default()
+        };
+        health.note_identity(&answers.repository, name_field, owner, repo);
+        if health.default_branch.is_none() {
+            return health;
+        }
+
+        fn state<'a>(run: &'a Value, field: &str) -> Option<&'a str> {
+            run.get(field).and_then(Value::as_str)
+        }
+        health.ci = match self {
+            // Only the latest pipeline counts
+            HostProvider::Gitlab => answers
+                .runs
+                .first()
+                .and_then(|pipeline| state(pipeline, "status"))
+                .and_then(|status| match status {
+                    "success" => Some(CiState::Success),
+                    "failed" | "canceled" => Some(CiState::Failure),
+                    "skipped" | "manual" => None,
+                    _ => Some(CiState::Pending),
+                }),
+            HostProvider::Bitbucket => answers
+                .runs
+                .iter()
+                .filter_map(|status| match state(status, "state")? {
+                    "SUCCESSFUL" => Some(CiState::Success),
+                    "FAILED" | "STOPPED" => Some(CiState::Failure),
+                    _ => Some(CiState::Pending),
+                })
+                .max(),
+            _ => {
+                let mut states: Vec<CiState> = answers
+                    .runs
+                    .iter()
+                    .map(|run| match (state(run, "status"), state(run, "conclusion")) {
+                        (Some("completed"), Some("success" | "neutral" | "skipped")) => CiState::Success,
+                        (Some("completed"), _) => CiState::Failure,
+                        _ => CiState::Pending,
+                    })
+                    .collect();
+                if let Some(status) = &answers.combined_status
+                    && status.get("total_count").and_then(Value::as_u64).unwrap_or_default() > 0
+                {
+                    states.push(match state(status, "state") {
+                        Some("success") => CiState::Success,
+                        Some("pending") => CiState::Pending,
+                        _ => CiState::Failure,
+                    });
+                }
+                states.into_iter().max()
+            }
+        };
+        health
+    }
+
+    /// Default branch named in the API's description of a repository
+    fn default_branch_of(self, repository: &Value) -> Option<String> {
+        let branch = match self {
+            HostProvider::Bitbucket => repository.pointer("/mainbranch/name"),
+            _ => repository.get("default_branch"),
+        };
+        branch.and_then(Value::as_str).map(str::to_string)
+    }
+
+    /// Create `owner/repo` on the host unless it exists, `true` when it was created
+    ///
+    /// New repositories are private. `owner` is tried as an organization
+    /// (group, workspace) first, then as the user of `token`.
+    pub fn ensure_repository(self, host: &str, owner: &str, repo: &str, token: &str) -> BasecampResult<bool> {
+        let host = normalize_host(host);
+        let owner = owner.trim_matches('/');
+        let api = self.api_root(host).ok_or_else(|| Self::no_api(host))?;
+        let token = Some(token);
+        let failed = || BasecampError::Generic(format!("{} refused to create {}/{}", self.label(), owner, repo));
+
+        match self {
+            HostProvider::Gitlab => {
+                let project = format!("{}/projects/{}%2F{}", api, owner.replace('/', "%2F"), repo);
+                if self.api_get(&project, token)?.is_some() {
+                    return Ok(false);
+                }
+                let namespace = self
+                    .api_get(&format!("{}/namespaces/{}", api, owner.replace('/', "%2F")), token)?
+                    .and_then(|namespace| namespace.get("id")?.as_u64())
+                    .ok_or_else(|| BasecampError::Generic(format!("{} has no group or user named '{}'", host, owner)))?;
+                let body = serde_json::json!({ "path": repo, "name": repo, "namespace_id": namespace, "visibility": "private" });
+                self.api_post(&format!("{}/projects", api), token, &body)?.ok_or_else(failed)?;
+            }
+            HostProvider::Bitbucket => {
+                let repository = format!("{}/repositories/{}/{}", api, owner, repo);
+                if self.api_get(&repository, token)?.is_some() {
+                    return Ok(false);
+                }
+                let body = serde_json::json!({ "scm": "git", "is_private": true });
+                self.api_post(&repository, token, &body)?.ok_or_else(failed)?;
+            }
+            _ => {
+                if self.api_get(&format!("{}/repos/{}/{}", api, owner, repo), token)?.is_some() {
+                    return Ok(false);
+                }
+                let body = serde_json::json!({ "name": repo, "private": true });
+                if self.api_post(&format!("{}/orgs/{}/repos", api, owner), token, &body)?.is_none() {
+                    self.api_post(&format!("{}/user/repos", api), token, &body)?.ok_or_else(failed)?;
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Request to the API, authenticated with `token`
+    fn api_request(self, method: &str, url: &str, token: Option<&str>) -> ureq::Request {
+        debug!("Requesting {} {}", method, url);
+        let agent = ureq::AgentBuilder::new().try_proxy_from_env(true).build();
+        let request = agent.request(method, url).set("User-Agent", "basecamp");
+        match (token, self) {
+            (Some(token), HostProvider::Gitlab) => request.set("PRIVATE-TOKEN", token),
+            (Some(token), _) => request.set("Authorization", &format!("Bearer {}", token)),
+            (None, _) => request,
+        }
+    }
+
+    /// POST a JSON body to an API URL, `None` when the URL doesn't exist
+    fn api_post(self, url: &str, token: Option<&str>, body: &Value) -> BasecampResult<Option<Value>> {
+        match self.api_request("POST", url, token).send_json(body) {
+            Ok(response) => Ok(Some(response.into_json()?)),
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(e) => Err(BasecampError::Generic(format!("Request to {} failed: {}", self.label(), e))),
+        }
+    }
+
+    /// GET an API URL, `None` when it doesn't exist
+    fn api_get(self, url: &str, token: Option<&str>) -> BasecampResult<Option<Value>> {
+        match self.api_request("GET", url, token).call() {
+            Ok(response) => Ok(Some(response.into_json()?)),
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(e) => Err(BasecampError::Generic(format!("Request to {} failed: {}", self.label(), e))),
+        }
+    }
+
+    /// Pass the items of every page of a listing to `f`, `false` when the listing doesn't exist
+    fn for_each_page(self, endpoint: &str, token: Option<&str>, mut f: impl FnMut(&[Value])) -> BasecampResult<bool> {
+        let page_size_param = match self {
+            HostProvider::Bitbucket => "pagelen",
+            _ => "per_page",
+        };
+        let separator = if endpoint.contains('?') { '&' } else { '?' };
+
+        for page in 1.. {
+            let url = format!("{}{}{}={}&page={}", endpoint, separator, page_size_param, PAGE_SIZE, page);
+            let Some(body) = self.api_get(&url, token)? else {
+                return Ok(false);
+            };
+
+            // Bitbucket wraps its pages in `values`
+            let items = body.get("values").unwrap_or(&body).as_array().cloned().unwrap_or_default();
+            f(&items);
+            if items.len() < PAGE_SIZE {
+                break;
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// State of the CI of a branch, ordered from best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CiState {
+    Success,
+    Pending,
+    Failure,
+}
+
+impl fmt::Display for CiState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CiState::Success => "success",
+            CiState::Pending => "pending",
+            CiState::Failure => "failure",
+        })
+    }
+}
+
+/// API answers about a repository, which [`HostProvider::health_from`] reads
+#[derive(Debug, Clone, Default)]
+pub struct HealthAnswers {
+    /// The repository (GitLab project) itself
+    pub repository: Value,
+    /// CI runs of the default branch: the latest GitLab pipeline, Bitbucket
+    /// commit statuses or GitHub check runs
+    pub runs: Vec<Value>,
+    /// Combined commit status of the default branch (GitHub)
+    pub combined_status: Option<Value>,
+    /// Open pull (merge) requests
+    pub open_pull_requests: usize,
+}
+
+/// What the host reports about a repository
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteHealth {
+    /// Default branch on the host
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    /// Worst CI state of the default branch, `None` without CI
+    #[serde(default)]
+    pub ci: Option<CiState>,
+    /// Open pull (merge) requests
+    #[serde(default)]
+    pub open_pull_requests: usize,
+    /// The repository is archived (read-only) on the host
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+    /// `owner/name` the host answers with when the repository was renamed or moved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moved_to: Option<String>,
+}
+
+impl RemoteHealth {
+    /// Read `archived` and the `owner/name` in `name_field` of a repository's API answer
+    fn note_identity(&mut self, info: &Value, name_field: &str, owner: &str, repo: &str) {
+        self.archived = info.get("archived").and_then(Value::as_bool).unwrap_or_default();
+        self.moved_to = info
+            .get(name_field)
+            .and_then(Value::as_str)
+            .filter(|name| !name.eq_ignore_ascii_case(&format!("{}/{}", owner, repo)))
+            .map(str::to_string);
+    }
+
+    /// New name of a repository renamed without leaving `owner`
+    pub fn renamed_within(&self, owner: &str) -> Option<&str> {
+        let (new_owner, name) = self.moved_to.as_deref()?.rsplit_once('/')?;
+        new_owner.eq_ignore_ascii_case(owner.trim_matches('/')).then_some(name)
+    }
+}
+
+impl fmt::Display for HostProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for HostProvider {
+    type Err = BasecampError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let name = name.replace('-', "_");
+        Self::ALL
+            .into_iter()
+            .find(|provider| provider.name() == name)
+            .ok_or_else(|| {
+                BasecampError::Generic(format!(
+                    "Invalid provider: {}. Use 'github', 'github-enterprise', 'gitlab', 'bitbucket' or 'custom'",
+                    name
+                ))
+            })
+    }
+}
+
+/// Host name without a scheme, user or trailing slash (`https://git.example.com/` → `git.example.com`)
+pub fn normalize_host(host: &str) -> &str {
+    let host = host.trim();
+    let host = host
+        .strip_prefix("https://")
+        .or_else(|| host.strip_prefix("http://"))
+        .or_else(|| host.strip_prefix("ssh://"))
+        .unwrap_or(host);
+    let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+    host.trim_end_matches('/')
+}
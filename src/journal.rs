@@ -0,0 +1,259 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::backup;
+use crate::error::BasecampResult;
+use crate::state;
+use crate::workspace::Workspace;
+
+/// Number of operations kept in the journal, older ones lose their trash
+const JOURNAL_LIMIT: usize = 20;
+
+/// Contents of config.yaml and codebases.yaml, `None` for a missing file
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ConfigFiles {
+    #[serde(default)]
+    pub config: Option<String>,
+    #[serde(default)]
+    pub codebases: Option<String>,
+}
+
+impl ConfigFiles {
+    /// Read the configuration files of a workspace
+    pub fn read(workspace: &Workspace) -> Self {
+        Self {
+            config: fs::read_to_string(workspace.config_path()).ok(),
+            codebases: fs::read_to_string(workspace.codebases_path()).ok(),
+        }
+    }
+
+    /// Write the configuration files back, deleting those that were missing
+    pub fn write(&self, workspace: &Workspace) -> BasecampResult<()> {
+        workspace.ensure_basecamp_dir()?;
+        for (path, content) in [
+            (workspace.config_path(), &self.config),
+            (workspace.codebases_path(), &self.codebases),
+        ] {
+            backup::keep(&path);
+            match content {
+                Some(content) => fs::write(&path, content)?,
+                None if path.exists() => fs::remove_file(&path)?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A directory moved by an operation, relative to the workspace root
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Move {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// A recorded operation, with what it takes to revert it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JournalEntry {
+    /// Command that ran the operation (e.g. "remove")
+    pub command: String,
+    /// What the operation did, for humans
+    pub summary: String,
+    /// Unix time the operation started
+    pub recorded_at: u64,
+    /// Configuration files before the operation
+    pub before: ConfigFiles,
+    /// Configuration files after the operation
+    pub after: ConfigFiles,
+    /// Directories moved, into the trash or to a new name
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub moved: Vec<Move>,
+    /// Directories created, such as new clones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub created: Vec<PathBuf>,
+    /// Trash directory of the operation, relative to the workspace root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trash: Option<PathBuf>,
+}
+
+/// Journal of the latest mutating operations, stored in `.basecamp/journal.json`
+///
+/// Directories deleted by a journaled operation are moved into
+/// `.basecamp/trash/` instead, so `basecamp undo` can bring them back.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct Journal {
+    #[serde(default)]
+    pub entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    /// Get path to the journal file of a workspace
+    pub fn get_journal_path(workspace: &Workspace) -> PathBuf {
+        workspace.basecamp_dir().join("journal.json")
+    }
+
+    /// Load the journal of a workspace, starting from an empty one when it is missing or unreadable
+    pub fn load(workspace: &Workspace) -> Self {
+        let path = Self::get_journal_path(workspace);
+        if !path.exists() {
+            return Self::default();
+        }
+        match fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+        {
+            Ok(journal) => journal,
+            Err(e) => {
+                warn!("Ignoring unreadable journal {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Save the journal of a workspace
+    pub fn save(&self, workspace: &Workspace) -> BasecampResult<()> {
+        workspace.ensure_basecamp_dir()?;
+        fs::write(Self::get_journal_path(workspace), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Append an entry, dropping the oldest ones and their trash beyond the limit
+    fn push(&mut self, workspace: &Workspace, entry: JournalEntry) {
+        self.entries.push(entry);
+        let excess = self.entries.len().saturating_sub(JOURNAL_LIMIT);
+        for dropped in self.entries.drain(..excess) {
+            if let Some(trash) = dropped.trash {
+                let _ = fs::remove_dir_all(workspace.root().join(trash));
+            }
+        }
+    }
+}
+
+/// A mutating operation being recorded into the journal
+///
+/// Shared between worker threads, so changes are recorded through `&self`.
+#[derive(Debug)]
+pub struct Operation {
+    workspace: Workspace,
+    command: String,
+    started_at: u64,
+    before: ConfigFiles,
+    trash: PathBuf,
+    moved: Mutex<Vec<Move>>,
+    created: Mutex<Vec<PathBuf>>,
+}
+
+impl Operation {
+    /// Start recording an operation, before it touches the configuration
+    pub fn begin(workspace: &Workspace, command: &str) -> Self {
+        let started_at = state::now();
+        let trash_root = Path::new(".basecamp").join("trash");
+        let mut trash = trash_root.join(format!("{}-{}", started_at, command));
+        let mut attempt = 1;
+        while workspace.root().join(&trash).exists() {
+            attempt += 1;
+            trash = trash_root.join(format!("{}-{}-{}", started_at, command, attempt));
+        }
+
+        Self {
+            workspace: workspace.clone(),
+            command: command.to_string(),
+            started_at,
+            before: ConfigFiles::read(workspace),
+            trash,
+            moved: Mutex::new(Vec::new()),
+            created: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Move a directory into the trash instead of deleting it
+    ///
+    /// Falls back to deleting it when it can't be moved, which can't be undone.
+    /// Directories can be trashed from several threads at once.
+    pub fn trash(&self, path: &Path) -> io::Result<()> {
+        let renamed = {
+            let mut moved = self.moved.lock().unwrap();
+            let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+            let to = self.trash.join(format!("{}-{}", moved.len(), name));
+            let target = self.workspace.root().join(&to);
+
+            let renamed =
+                fs::create_dir_all(self.workspace.root().join(&self.trash)).and_then(|_| fs::rename(path, &target));
+            if renamed.is_ok() {
+                moved.push(Move {
+                    from: self.relative(path),
+                    to,
+                });
+            }
+            renamed
+        };
+
+        // Deleting can take long, so it happens without holding the lock
+        renamed.or_else(|e| {
+            debug!("Couldn't move {:?} to the trash, deleting it: {}", path, e);
+            if path.is_dir() {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            }
+        })
+    }
+
+    /// Whether the operation moved a path into the trash
+    pub fn has_trashed(&self, path: &Path) -> bool {
+        let path = self.relative(path);
+        self.moved.lock().unwrap().iter().any(|m| m.from == path && m.to.starts_with(&self.trash))
+    }
+
+    /// Record a directory the operation moved
+    pub fn record_move(&self, from: &Path, to: &Path) {
+        self.moved.lock().unwrap().push(Move {
+            from: self.relative(from),
+            to: self.relative(to),
+        });
+    }
+
+    /// Record a directory the operation created
+    pub fn record_created(&self, path: &Path) {
+        self.created.lock().unwrap().push(self.relative(path));
+    }
+
+    /// Add the operation to the journal, unless it changed nothing
+    pub fn finish(self, summary: &str) {
+        let after = ConfigFiles::read(&self.workspace);
+        let moved = self.moved.into_inner().unwrap();
+        let created = self.created.into_inner().unwrap();
+        if after == self.before && moved.is_empty() && created.is_empty() {
+            return;
+        }
+
+        let trash = moved.iter().any(|m| m.to.starts_with(&self.trash)).then_some(self.trash);
+        let mut journal = Journal::load(&self.workspace);
+        journal.push(
+            &self.workspace,
+            JournalEntry {
+                command: self.command,
+                summary: summary.to_string(),
+                recorded_at: self.started_at,
+                before: self.before,
+                after,
+                moved,
+                created,
+                trash,
+            },
+        );
+        if let Err(e) = journal.save(&self.workspace) {
+            warn!("Failed to write the journal: {}", e);
+        }
+    }
+
+    /// Path relative to the workspace root, as stored in the journal
+    fn relative(&self, path: &Path) -> PathBuf {
+        path.strip_prefix(self.workspace.root()).unwrap_or(path).to_path_buf()
+    }
+}
@@ -40,19 +40,46 @@ basecamp list
 
 The crate is organized into several modules:
 
+- [`backup`]: Copies of the configuration files kept before each save (`.basecamp/backups/`)
 - [`cli`]: Command-line interface and argument parsing
 - [`commands`]: Implementation of the main commands
 - [`config`]: Configuration loading, saving, and manipulation
+- [`core`]: Workspace operations returning structured results, with progress reported through [`core::ProgressSink`]
 - [`error`]: Error handling types
-- [`git`]: Git operations including cloning and status checks
+- [`git`]: Git operations including cloning and status checks, with credential providers in [`git::auth`],
+  interchangeable implementations in [`git::backend`] and hosting services in [`git::provider`]
+- [`journal`]: Journal of mutating operations and their trash, for `undo` (`.basecamp/journal.json`)
 - [`logger`]: Logging setup
+- [`messages`]: Catalog of the common error, hint and prompt messages by ID, with translations loaded at runtime
+- [`oplog`]: Per-run operation logs (`.basecamp/logs/`)
+- [`registry`]: User-level registry of workspaces (`~/.config/basecamp/workspaces.yaml`)
+- [`runner`]: Commands run with a timeout, their output streamed or captured up to a limit
+- [`shell`]: Commands run through the platform shell, and quoting for it
+- [`state`]: Cached repository state (`.basecamp/state.json`)
+- [`template`]: Placeholders (`{org}`, `{repo}`, `{codebase}`, `{home}`, `${NAME}`) in configuration values
 - [`ui`]: Terminal UI utilities including progress bars and colored output
+- [`validate`]: Configuration validation with located diagnostics
+- [`workspace`]: Paths of a workspace, derived from an explicit root rather than the current directory
+- [`yaml_edit`]: Comment-preserving rewrites of the YAML configuration files
 */
 
+pub mod backup;
 pub mod cli;
 pub mod commands;
 pub mod config;
+pub mod core;
 pub mod error;
 pub mod git;
+pub mod journal;
 pub mod logger;
+pub mod messages;
+pub mod oplog;
+pub mod registry;
+pub mod runner;
+pub mod shell;
+pub mod state;
+pub mod template;
 pub mod ui;
+pub mod validate;
+pub mod workspace;
+pub mod yaml_edit;
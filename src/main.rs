@@ -1,18 +1,36 @@
+mod backup;
 mod cli;
 mod commands;
 mod config;
+mod core;
 mod error;
 mod git;
+mod journal;
 mod logger;
+mod messages;
+mod oplog;
+mod registry;
+mod runner;
+mod shell;
+mod state;
+mod template;
 mod ui;
+mod validate;
+mod workspace;
+mod yaml_edit;
 
+use std::path::Path;
 use std::process;
 
 use log::{debug, error};
 
-use crate::cli::Commands;
+use crate::cli::{Commands, ConfigCommands, GitMode, HooksCommands, ProfileCommands, SnapshotCommands, WorkspaceCommands, WorkspacesCommands};
+use crate::config::Config;
+use crate::core::install::ConflictPolicy;
 use crate::error::BasecampError;
-use crate::ui::UI;
+use crate::git::{GitRepo, backend};
+use crate::registry::WorkspaceRegistry;
+use crate::ui::{Reporter, UI, Verbosity};
 
 fn main() {
     // Parse command-line arguments
@@ -23,23 +41,322 @@ fn main() {
 
     debug!("Starting BaseCamp");
 
+    // All user-facing output goes through the reporter
+    Reporter::global().set_verbosity(match (args.quiet, args.verbose) {
+        (true, _) => Verbosity::Quiet,
+        (false, 0) => Verbosity::Normal,
+        (false, _) => Verbosity::Verbose,
+    });
+
+    // Plain output for NO_COLOR, --no-color and non-terminal stdout
+    UI::set_styling(!args.no_color);
+
+    // Translated messages from BASECAMP_MESSAGES, unless the configuration names a file
+    if let Some(path) = std::env::var_os("BASECAMP_MESSAGES") {
+        match messages::load_default(Path::new(&path)) {
+            Ok(unknown) if !unknown.is_empty() => {
+                UI::warning(&format!("Unknown message IDs in the translation file: {}", unknown.join(", ")));
+            }
+            Ok(_) => {}
+            Err(e) => UI::warning(&e.to_string()),
+        }
+    }
+
+    // Prompts fall back to defaults or flags when disabled or without a terminal
+    UI::set_interaction(args.non_interactive, args.yes);
+
+    // Clones and fetches fail fast instead of reaching for the network
+    GitRepo::set_offline(args.offline);
+
+    // Stub clones and no-op fetches, so flows can be exercised without remotes
+    backend::set_fake(args.git_mode == GitMode::Fake);
+
+    // Profile applied whenever the configuration is loaded
+    Config::set_profile_override(args.profile.clone());
+
+    // Configuration changes are shown and confirmed before they're saved
+    Config::set_preview_changes(args.diff);
+
+    // Resolve .basecamp and repository paths against --config-dir
+    if let Some(dir) = &args.config_dir
+        && !dir.is_dir()
+    {
+        UI::error(&format!("Workspace directory '{}' does not exist", dir.display()));
+        process::exit(1);
+    }
+    Config::set_workspace_dir(args.config_dir.clone());
+
+    // Outside of any workspace, fall back to the one selected with `workspaces switch`
+    let uses_workspace = !matches!(args.command, Commands::Init { .. } | Commands::Workspaces { .. } | Commands::Manpages { .. } | Commands::Completions { .. });
+    if args.config_dir.is_none() && uses_workspace && !Config::get_basecamp_dir().exists() {
+        let registry = WorkspaceRegistry::load();
+        if let Some((name, root)) = registry.current_root() {
+            UI::detail(&format!("Using workspace '{}' ({})", name, root.display()));
+            Config::set_workspace_dir(Some(root.to_path_buf()));
+        }
+    }
+
+    // Commands reporting partial failures set their own exit code
+    let mut exit_code = 0;
+
     // Execute the requested command
     let result = match &args.command {
-        Commands::Init { connection_type, repo_type, name, non_interactive, force } => 
-            commands::init(connection_type.clone(), repo_type.clone(), name.clone(), *non_interactive, *force),
-        Commands::Install { codebase, parallel } => {
-            commands::install(codebase.clone(), *parallel)
-        }
-        Commands::List { codebase } => commands::list(codebase.clone()),
+        Commands::Init {
+            connection_type,
+            repo_type,
+            name,
+            provider,
+            host,
+            template,
+            force,
+        } => commands::init(commands::InitOptions {
+            connection_type: connection_type.clone(),
+            repo_type: repo_type.clone(),
+            name: name.clone(),
+            provider: provider.clone(),
+            host: host.clone(),
+            template: template.clone(),
+            non_interactive: !UI::is_interactive(),
+            force: *force,
+        }),
+        Commands::Install {
+            codebase,
+            all,
+            parallel,
+            report,
+            fail_fast,
+            keep_going: _,
+            update,
+            force_reclone,
+            force,
+            resume,
+            overwrite_non_git,
+            backup_conflicts,
+        } => commands::install(
+            codebase.clone(),
+            commands::InstallOptions {
+                all: *all,
+                parallel: *parallel,
+                report: *report,
+                fail_fast: *fail_fast,
+                update: *update,
+                force_reclone: force_reclone.clone(),
+                force: *force,
+                resume: *resume,
+                conflicts: if *overwrite_non_git {
+                    ConflictPolicy::Overwrite
+                } else if *backup_conflicts {
+                    ConflictPolicy::Backup
+                } else {
+                    ConflictPolicy::Report
+                },
+            },
+        )
+        .map(|outcome| exit_code = outcome.exit_code()),
+        Commands::List {
+            codebase,
+            status,
+            no_cache,
+            remote,
+            missing_only,
+            tree,
+            web_url,
+            search,
+        } => commands::list(
+            codebase.clone(),
+            commands::ListOptions {
+                status: *status,
+                no_cache: *no_cache,
+                remote: *remote,
+                missing_only: *missing_only,
+                tree: *tree,
+                web_url: *web_url,
+                search: search.clone(),
+            },
+        ),
         Commands::Add {
             codebase,
             repositories,
-        } => commands::add(codebase.clone(), repositories.clone()),
+            from_file,
+            github_url,
+            keep_failed,
+        } => commands::add(
+            codebase.clone(),
+            repositories.clone(),
+            from_file.as_deref(),
+            github_url.clone(),
+            *keep_failed,
+        ),
         Commands::Remove {
             codebase,
             repositories,
             force,
-        } => commands::remove(codebase.clone(), repositories.clone(), *force),
+            dry_run,
+        } => commands::remove(codebase.clone(), repositories.clone(), *force, *dry_run),
+        Commands::Archive { codebase, repositories } => commands::archive(codebase.clone(), repositories.clone()),
+        Commands::Unarchive { codebase, repositories } => {
+            commands::unarchive(codebase.clone(), repositories.clone())
+        }
+        Commands::Run {
+            task,
+            codebase,
+            parallel,
+            filters,
+            timeout,
+            stream,
+            capture,
+            max_output,
+        } => commands::run(
+            task.clone(),
+            codebase.clone(),
+            commands::TaskOptions {
+                parallel: *parallel,
+                filters: filters.clone(),
+                timeout: *timeout,
+                output: if *stream {
+                    Some(runner::OutputMode::Stream)
+                } else if *capture {
+                    Some(runner::OutputMode::Capture)
+                } else {
+                    None
+                },
+                max_output: *max_output,
+            },
+        ),
+        Commands::Compose {
+            codebase,
+            output,
+            no_prefix,
+            up,
+        } => commands::compose(codebase.clone(), output.clone(), *no_prefix, *up),
+        Commands::Env { codebase, repo } => commands::env(codebase.clone(), repo.clone()),
+        Commands::Open {
+            repo,
+            codebase,
+            editor,
+        } => commands::open(repo.clone(), codebase.clone(), *editor),
+        Commands::Info { repo, codebase } => commands::info(repo.clone(), codebase.clone()),
+        Commands::Path { repo, codebase } => commands::path(repo.clone(), codebase.clone()),
+        Commands::Paths {
+            codebase,
+            null,
+            filters,
+            deps,
+        } => commands::paths(codebase.clone(), *null, filters.clone(), *deps),
+        Commands::ShellInit { shell } => commands::shell_init(*shell),
+        Commands::Completions { shell } => commands::completions(*shell),
+        Commands::Complete { words } => commands::complete(words.clone()),
+        Commands::Status {
+            codebase,
+            no_cache,
+            fetch,
+            parallel,
+            remote,
+        } => commands::status(
+            codebase.clone(),
+            commands::StatusOptions {
+                no_cache: *no_cache,
+                fetch: *fetch,
+                parallel: *parallel,
+                remote: *remote,
+            },
+        ),
+        Commands::Fetch { codebase, parallel } => commands::fetch(codebase.clone(), *parallel),
+        Commands::Outdated {
+            codebase,
+            fetch,
+            parallel,
+        } => commands::outdated(codebase.clone(), *fetch, *parallel),
+        Commands::Watch {
+            interval,
+            notify,
+            once,
+            parallel,
+        } => commands::watch(*interval, *notify, *once, *parallel),
+        Commands::Verify { codebase, fix } => commands::verify(codebase.clone(), *fix),
+        Commands::Dedupe {
+            repositories,
+            keep_in,
+            share,
+            allow,
+        } => commands::dedupe(repositories.clone(), keep_in.clone(), *share, *allow),
+        Commands::Validate { sort } => commands::validate(*sort),
+        Commands::MigrateRemotes { to } => commands::migrate_remotes(*to),
+        Commands::MigrateOrg { old, new, dry_run } => commands::migrate_org(old, new, *dry_run),
+        Commands::Import {
+            file,
+            format,
+            codebase,
+            dry_run,
+        } => commands::import(file, *format, codebase.clone(), *dry_run),
+        Commands::Export {
+            codebase,
+            format,
+            output,
+        } => commands::export(codebase.clone(), *format, output.as_deref()),
+        Commands::Report {
+            codebase,
+            format,
+            output,
+        } => commands::report(codebase.clone(), *format, output.as_deref()),
+        Commands::Stats {
+            codebase,
+            since,
+            parallel,
+            report,
+        } => commands::stats(codebase.clone(), since, *parallel, *report),
+        Commands::Mirror {
+            codebase,
+            to,
+            no_create,
+            parallel,
+        } => commands::mirror(codebase.clone(), to.clone(), *no_create, *parallel),
+        Commands::SyncFork { codebase, parallel } => commands::sync_fork(codebase.clone(), *parallel),
+        Commands::UpdatePins {
+            codebase,
+            dry_run,
+            parallel,
+        } => commands::update_pins(codebase.clone(), *dry_run, *parallel),
+        Commands::Undo { force } => commands::undo(*force),
+        Commands::Config { command } => match command {
+            ConfigCommands::Restore { backup, list } => commands::config::restore(backup.clone(), *list),
+            ConfigCommands::Messages => commands::config::messages(),
+        },
+        Commands::Workspace { command } => match command {
+            WorkspaceCommands::Tmux {
+                codebase,
+                output,
+                launch,
+            } => commands::workspace::tmux(codebase.clone(), output.clone(), *launch),
+        },
+        Commands::Workspaces { command } => match command {
+            WorkspacesCommands::List => commands::workspaces::list(),
+            WorkspacesCommands::Add { dir, name } => commands::workspaces::add(dir.clone(), name.clone()),
+            WorkspacesCommands::Switch { name, clear: _ } => commands::workspaces::switch(name.clone()),
+            WorkspacesCommands::Remove { name } => commands::workspaces::remove(name),
+            WorkspacesCommands::Prune => commands::workspaces::prune(),
+        },
+        Commands::Snapshot { command } => match command {
+            SnapshotCommands::Create { name, codebase } => commands::snapshot::create(name, codebase.clone()),
+            SnapshotCommands::Restore { name, force } => commands::snapshot::restore(name, *force),
+            SnapshotCommands::List => commands::snapshot::list(),
+            SnapshotCommands::Delete { name } => commands::snapshot::delete(name),
+        },
+        Commands::Hooks { command } => match command {
+            HooksCommands::Sync { codebase } => commands::hooks::sync(codebase.clone()),
+        },
+        Commands::Profile { command } => match command {
+            ProfileCommands::List => commands::profile::list(),
+            ProfileCommands::Add {
+                name,
+                github_url,
+                ssh_key,
+            } => commands::profile::add(name, github_url.clone(), ssh_key.clone()),
+            ProfileCommands::Use { name } => commands::profile::use_profile(name),
+            ProfileCommands::Clear => commands::profile::clear(),
+        },
+        Commands::Manpages { dir } => commands::manpages(dir.clone()),
+        Commands::Serve { json_rpc: _, socket } => commands::serve(socket.clone()),
     };
 
     // Handle command result
@@ -47,6 +364,9 @@ fn main() {
         handle_error(err);
         process::exit(1);
     }
+    if exit_code != 0 {
+        process::exit(exit_code);
+    }
 
     debug!("BaseCamp completed successfully");
 }
@@ -55,50 +375,65 @@ fn main() {
 fn handle_error(err: BasecampError) {
     match err {
         BasecampError::GitHubUrlNotConfigured => {
-            UI::error("GitHub URL not configured. Run 'basecamp init' first.");
+            UI::error(&messages::text("report.github_url_not_configured"));
             error!("GitHub URL not configured");
         }
         BasecampError::UncommittedChanges(path) => {
-            UI::error(&format!(
-                "Repository '{}' has uncommitted changes. Commit or stash your changes, or use --force to override.",
-                path.display()
-            ));
+            UI::error(&messages::format("report.uncommitted_changes", &[("path", &path.display())]));
             error!("Uncommitted changes detected in {}", path.display());
         }
-        BasecampError::UnpushedCommits(path) => {
-            UI::error(&format!(
-                "Repository '{}' has unpushed commits. Push your commits, or use --force to override.",
-                path.display()
-            ));
-            error!("Unpushed commits detected in {}", path.display());
+        BasecampError::LocalWork(blocked) => {
+            UI::error(&BasecampError::LocalWork(blocked.clone()).to_string());
+            UI::info(&messages::text("hint.local_work"));
+            for (path, work) in &blocked {
+                error!("{} {}", path.display(), work);
+            }
         }
         BasecampError::FileNotFound(path) => {
-            UI::error(&format!(
-                "File not found: {}. Run 'basecamp init' to create a new configuration.",
-                path.display()
-            ));
+            UI::error(&messages::format("report.file_not_found", &[("path", &path.display())]));
             error!("File not found: {}", path.display());
         }
-        BasecampError::CodebaseNotFound(name) => {
-            UI::error(&format!("Codebase '{}' not found", name));
+        BasecampError::CodebaseNotFound(ref name) => {
+            UI::error(&err.to_string());
             error!("Codebase not found: {}", name);
         }
-        BasecampError::RepositoryNotFound(repo, codebase) => {
-            UI::error(&format!(
-                "Repository '{}' not found in codebase '{}'",
-                repo, codebase
-            ));
+        BasecampError::RepositoryNotFound(ref repo, ref codebase) => {
+            UI::error(&err.to_string());
             error!("Repository not found: {} in {}", repo, codebase);
         }
+        BasecampError::UnknownRepository(repo) => {
+            UI::error(&messages::format("report.unknown_repository", &[("repo", &repo)]));
+            error!("Repository not found: {}", repo);
+        }
+        BasecampError::TaskNotFound(name) => {
+            UI::error(&messages::format("report.task_not_found", &[("task", &name)]));
+            error!("Task not found: {}", name);
+        }
+        BasecampError::ProfileNotFound(name) => {
+            UI::error(&messages::format("report.profile_not_found", &[("profile", &name)]));
+            error!("Profile not found: {}", name);
+        }
+        BasecampError::InvalidConfig(ref diagnostic) => {
+            UI::error(&err.to_string());
+            UI::info(&messages::text("hint.validate"));
+            error!("Invalid configuration: {}", diagnostic);
+        }
         BasecampError::InvalidGitHubUrl(url) => {
-            UI::error(&format!(
-                "Invalid GitHub URL: {}. It should start with 'https://' or 'git@'.",
-                url
-            ));
+            UI::error(&messages::format("report.invalid_github_url", &[("url", &url)]));
             error!("Invalid GitHub URL: {}", url);
         }
+        BasecampError::Timeout(ref repo, ref reason) => {
+            UI::error(&err.to_string());
+            UI::info(&messages::text("hint.timeout"));
+            error!("Timed out: {} {}", repo, reason);
+        }
+        BasecampError::OfflineMode(operation) => {
+            UI::error(&messages::format("report.offline", &[("operation", &operation)]));
+            UI::info(&messages::text("hint.offline"));
+            error!("Network access refused in offline mode: {}", operation);
+        }
         _ => {
-            UI::error(&format!("Error: {}", err));
+            UI::error(&messages::format("report.error", &[("error", &err)]));
             error!("{}", err);
         }
     }
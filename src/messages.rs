@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use log::{debug, info};
+use serde_yaml::{Mapping, Value};
+
+use crate::error::{BasecampError, BasecampResult};
+
+/// Texts of the loaded translation file, by message ID, and the file they came from
+static TRANSLATIONS: RwLock<Option<(PathBuf, HashMap<String, String>)>> = RwLock::new(None);
+
+/// Translations used while the loaded workspace names no file (`BASECAMP_MESSAGES`)
+static DEFAULT_TRANSLATIONS: RwLock<Option<(PathBuf, HashMap<String, String>)>> = RwLock::new(None);
+
+/// Every translatable message with its English text
+///
+/// `{name}` placeholders are filled in by `format`; a translation keeps the
+/// same placeholders, in any order.
+pub const CATALOG: &[(&str, &str)] = &[
+    // Errors
+    ("error.git", "Git error: {error}"),
+    ("error.io", "IO error: {error}"),
+    ("error.yaml", "YAML serialization/deserialization error: {error}"),
+    ("error.json", "JSON serialization/deserialization error: {error}"),
+    ("error.repository_not_found", "Repository '{repo}' not found in codebase '{codebase}'"),
+    ("error.unknown_repository", "Repository '{repo}' not found in any codebase"),
+    ("error.codebase_not_found", "Codebase '{codebase}' not found"),
+    ("error.profile_not_found", "Profile '{profile}' not found in config.yaml"),
+    ("error.task_not_found", "Task '{task}' not found"),
+    ("error.dependency_cycle", "Dependency cycle detected between repositories: {cycle}"),
+    ("error.file_not_found", "File not found: {path}"),
+    ("error.uncommitted_changes", "Repository at '{path}' has uncommitted changes"),
+    ("error.local_work", "Repository at '{path}' {work}"),
+    ("error.local_work_many", "{count} repositories have local work:"),
+    ("error.github_url_not_configured", "GitHub URL not configured"),
+    ("error.invalid_config", "Invalid configuration: {problem}"),
+    ("error.invalid_github_url", "Invalid GitHub URL: {url}"),
+    ("error.timeout", "Repository '{repo}' timed out {reason}"),
+    ("error.offline", "Offline mode: network access needed to {operation}"),
+    ("error.command_failed", "Command failed: {error}"),
+    // Local work found before deleting or replacing a repository
+    ("local_work.uncommitted", "has uncommitted changes"),
+    ("local_work.unpushed", "has unpushed commits"),
+    ("local_work.unknown", "couldn't be checked for local work: {error}"),
+    // Errors as reported when a command fails, with what to do about them
+    ("report.error", "Error: {error}"),
+    ("report.github_url_not_configured", "GitHub URL not configured. Run 'basecamp init' first."),
+    (
+        "report.uncommitted_changes",
+        "Repository '{path}' has uncommitted changes. Commit or stash your changes, or use --force to override.",
+    ),
+    ("report.file_not_found", "File not found: {path}. Run 'basecamp init' to create a new configuration."),
+    (
+        "report.unknown_repository",
+        "Repository '{repo}' not found in any codebase. Use 'basecamp list' to see configured repositories.",
+    ),
+    ("report.task_not_found", "Task '{task}' not found. Define it under 'tasks' in .basecamp/codebases.yaml."),
+    (
+        "report.profile_not_found",
+        "Profile '{profile}' not found. Use 'basecamp profile list' to see the defined profiles.",
+    ),
+    ("report.invalid_github_url", "Invalid GitHub URL: {url}. It should start with 'https://' or 'git@'."),
+    ("report.offline", "Offline mode is enabled, but BaseCamp needs the network to {operation}."),
+    ("hint.local_work", "Commit and push the work, or use --force to override."),
+    ("hint.validate", "Run 'basecamp validate' to check the whole configuration."),
+    ("hint.timeout", "Check your connection, or raise `timeouts` in config.yaml (0 disables a limit)."),
+    ("hint.offline", "Run without --offline (and remove `offline: true` from config.yaml) to allow it."),
+    // Prompts
+    ("prompt.yes", "yes"),
+    ("prompt.no", "no"),
+    ("prompt.assumed_yes", "{message} (assuming 'yes')"),
+    ("prompt.non_interactive", "{message} (non-interactive, assuming '{answer}'; pass --yes to confirm)"),
+    ("prompt.no_answer", "{message} (no answer possible, assuming '{answer}')"),
+    ("prompt.non_interactive_refused", "{message} (non-interactive, pass --yes to confirm)"),
+    ("prompt.type_to_confirm", "{message}\nType '{expected}' to confirm"),
+    // Outcome line at the end of a command
+    ("summary.nothing_to_do", "nothing to do"),
+];
+
+/// Text of a message: its translation when one is loaded, the English text otherwise
+pub fn text(id: &str) -> String {
+    if let Some((_, translations)) = TRANSLATIONS.read().unwrap().as_ref()
+        && let Some(text) = translations.get(id)
+    {
+        return text.clone();
+    }
+
+    match CATALOG.iter().find(|(key, _)| *key == id) {
+        Some((_, text)) => text.to_string(),
+        None => {
+            debug!("Message '{}' is not in the catalog", id);
+            id.to_string()
+        }
+    }
+}
+
+/// Text of a message with its `{name}` placeholders filled in
+///
+/// Placeholders without a value are kept as written.
+pub fn format(id: &str, args: &[(&str, &dyn Display)]) -> String {
+    let text = text(id);
+    let mut formatted = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start + 1..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 1..start + 1 + len];
+        formatted.push_str(&rest[..start]);
+        match args.iter().find(|(key, _)| *key == name) {
+            Some((_, value)) => formatted.push_str(&value.to_string()),
+            None => formatted.push_str(&rest[start..start + len + 2]),
+        }
+        rest = &rest[start + len + 2..];
+    }
+    formatted.push_str(rest);
+    formatted
+}
+
+/// Use the translations of a YAML file mapping message IDs to texts
+///
+/// Messages missing from the file keep their English text. Returns the IDs
+/// of the file that aren't in the catalog, e.g. from an outdated translation.
+/// Loading the same file again (the configuration is loaded several times
+/// per run) does nothing.
+pub fn load(path: &Path) -> BasecampResult<Vec<String>> {
+    if TRANSLATIONS.read().unwrap().as_ref().is_some_and(|(loaded, _)| loaded == path) {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| {
+        BasecampError::Generic(format!("Failed to read translation file {}: {}", path.display(), e))
+    })?;
+    let translations: HashMap<String, String> = serde_yaml::from_str(&content).map_err(|e| {
+        BasecampError::Generic(format!("Invalid translation file {}: {}", path.display(), e))
+    })?;
+
+    let mut unknown: Vec<String> = translations
+        .keys()
+        .filter(|id| !CATALOG.iter().any(|(key, _)| key == id))
+        .cloned()
+        .collect();
+    unknown.sort();
+
+    info!("Loaded {} translated messages from {:?}", translations.len(), path);
+    *TRANSLATIONS.write().unwrap() = Some((path.to_path_buf(), translations));
+    Ok(unknown)
+}
+
+/// Load a translation file used whenever a workspace doesn't name its own
+pub fn load_default(path: &Path) -> BasecampResult<Vec<String>> {
+    let unknown = load(path)?;
+    *DEFAULT_TRANSLATIONS.write().unwrap() = TRANSLATIONS.read().unwrap().clone();
+    Ok(unknown)
+}
+
+/// Go back to the default translations, or to the English texts without any
+pub fn reset() {
+    *TRANSLATIONS.write().unwrap() = DEFAULT_TRANSLATIONS.read().unwrap().clone();
+}
+
+/// The catalog as a YAML translation file, with the English texts to translate
+pub fn template() -> BasecampResult<String> {
+    let mut mapping = Mapping::new();
+    for (id, text) in CATALOG {
+        mapping.insert(Value::from(*id), Value::from(*text));
+    }
+    Ok(serde_yaml::to_string(&mapping)?)
+}
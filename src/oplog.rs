@@ -0,0 +1,195 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use log::{debug, warn};
+
+use crate::error::BasecampResult;
+use crate::state;
+use crate::ui::UI;
+use crate::workspace::Workspace;
+
+/// Outcome of an operation on a single repository
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    /// The operation completed
+    Succeeded,
+    /// Nothing had to be done, with the reason
+    Skipped(String),
+    /// The operation failed, with the full error text
+    Failed(String),
+}
+
+/// A logged repository operation
+#[derive(Debug, Clone)]
+struct Entry {
+    target: String,
+    url: Option<String>,
+    duration: Duration,
+    outcome: Outcome,
+}
+
+/// Per-run log of repository operations, written to `.basecamp/logs/`
+///
+/// Shared between worker threads, so entries are recorded through `&self`.
+#[derive(Debug)]
+pub struct OperationLog {
+    logs_dir: PathBuf,
+    command: String,
+    started_at: u64,
+    started: Instant,
+    entries: Mutex<Vec<Entry>>,
+}
+
+impl OperationLog {
+    /// Start a log for a command (e.g. "install") run in `workspace`
+    pub fn new(workspace: &Workspace, command: &str) -> Self {
+        Self {
+            logs_dir: workspace.logs_dir(),
+            command: command.to_string(),
+            started_at: state::now(),
+            started: Instant::now(),
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Record the outcome of an operation on `<codebase>/<repo>`
+    pub fn record(&self, target: &str, url: Option<&str>, duration: Duration, outcome: Outcome) {
+        self.entries.lock().unwrap().push(Entry {
+            target: target.to_string(),
+            url: url.map(String::from),
+            duration,
+            outcome,
+        });
+    }
+
+    /// Whether any recorded operation failed
+    pub fn has_failures(&self) -> bool {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|e| matches!(e.outcome, Outcome::Failed(_)))
+    }
+
+    /// Write the log file and return its path
+    pub fn write(&self, error: Option<&str>) -> BasecampResult<PathBuf> {
+        fs::create_dir_all(&self.logs_dir)?;
+
+        // Names have second resolution, so runs started in the same second get a suffix
+        let stamp = format_timestamp(self.started_at, true);
+        let mut path = self.logs_dir.join(format!("{}-{}.log", stamp, self.command));
+        let mut attempt = 1;
+        while path.exists() {
+            attempt += 1;
+            path = self.logs_dir.join(format!("{}-{}-{}.log", stamp, self.command, attempt));
+        }
+        debug!("Writing operation log to {:?}", path);
+
+        let mut entries = self.entries.lock().unwrap().clone();
+        entries.sort_by(|a, b| a.target.cmp(&b.target));
+
+        let (mut succeeded, mut skipped, mut failed) = (0, 0, 0);
+        let mut content = format!(
+            "basecamp {} started at {}\n\n",
+            self.command,
+            format_timestamp(self.started_at, false)
+        );
+
+        for entry in &entries {
+            let (label, detail) = match &entry.outcome {
+                Outcome::Succeeded => {
+                    succeeded += 1;
+                    ("ok", None)
+                }
+                Outcome::Skipped(reason) => {
+                    skipped += 1;
+                    ("skipped", Some(reason))
+                }
+                Outcome::Failed(error) => {
+                    failed += 1;
+                    ("failed", Some(error))
+                }
+            };
+
+            let _ = writeln!(
+                content,
+                "[{}] {} ({:.2}s)",
+                label,
+                entry.target,
+                entry.duration.as_secs_f64()
+            );
+            if let Some(url) = &entry.url {
+                let _ = writeln!(content, "    url: {}", url);
+            }
+            if let Some(detail) = detail {
+                for line in detail.lines() {
+                    let _ = writeln!(content, "    {}", line);
+                }
+            }
+        }
+
+        if let Some(error) = error {
+            let _ = writeln!(content, "\nerror: {}", error);
+        }
+        let _ = writeln!(
+            content,
+            "\nfinished in {:.2}s: {} succeeded, {} skipped, {} failed",
+            self.started.elapsed().as_secs_f64(),
+            succeeded,
+            skipped,
+            failed
+        );
+
+        fs::write(&path, content)?;
+        Ok(path)
+    }
+
+    /// Write the log at the end of a run and point to it when something failed
+    pub fn finish<T>(&self, result: &BasecampResult<T>) {
+        let error = result.as_ref().err().map(|e| e.to_string());
+        if self.entries.lock().unwrap().is_empty() && error.is_none() {
+            return;
+        }
+
+        match self.write(error.as_deref()) {
+            Ok(path) if error.is_some() || self.has_failures() => {
+                UI::info(&format!("Full details were written to {}", path.display()));
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to write operation log: {}", e),
+        }
+    }
+}
+
+/// Format a Unix timestamp in UTC, either for file names or for humans
+fn format_timestamp(timestamp: u64, compact: bool) -> String {
+    let days = (timestamp / 86400) as i64;
+    let secs = timestamp % 86400;
+    let (hour, minute, second) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    if compact {
+        format!(
+            "{:04}{:02}{:02}-{:02}{:02}{:02}",
+            year, month, day, hour, minute, second
+        )
+    } else {
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+            year, month, day, hour, minute, second
+        )
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::error::{BasecampError, BasecampResult};
+
+/// User-level registry of BaseCamp workspaces (`~/.config/basecamp/workspaces.yaml`)
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WorkspaceRegistry {
+    /// Workspace used by commands run outside of any workspace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current: Option<String>,
+
+    /// Workspace roots keyed by name
+    #[serde(default)]
+    pub workspaces: BTreeMap<String, PathBuf>,
+}
+
+impl WorkspaceRegistry {
+    /// Get path to workspaces.yaml file
+    pub fn get_registry_path() -> Option<PathBuf> {
+        Config::get_user_config_dir().map(|dir| dir.join("workspaces.yaml"))
+    }
+
+    /// Load the registry, starting from an empty one when it is missing or unreadable
+    pub fn load() -> Self {
+        let Some(path) = Self::get_registry_path().filter(|path| path.exists()) else {
+            return Self::default();
+        };
+
+        match fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_yaml::from_str(&content).map_err(|e| e.to_string()))
+        {
+            Ok(registry) => registry,
+            Err(e) => {
+                warn!("Ignoring unreadable workspace registry {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Save the registry
+    pub fn save(&self) -> BasecampResult<()> {
+        let path = Self::get_registry_path().ok_or_else(|| {
+            BasecampError::Generic(
+                "Cannot locate the user configuration directory (no home directory found)".to_string(),
+            )
+        })?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        debug!("Saving workspace registry to {:?}", path);
+
+        fs::write(&path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Register a workspace root and return its name
+    ///
+    /// Roots that are already registered keep their name unless `name` is
+    /// given. Otherwise the name defaults to the directory name, with a numeric
+    /// suffix when it is already taken.
+    pub fn register(&mut self, root: &Path, name: Option<&str>) -> BasecampResult<String> {
+        let root = fs::canonicalize(root)?;
+        let existing = self.find(&root).map(String::from);
+
+        if let Some(name) = name {
+            if self.workspaces.get(name).is_some_and(|path| *path != root) {
+                return Err(BasecampError::Generic(format!(
+                    "Workspace '{}' is already registered for {}",
+                    name,
+                    self.workspaces[name].display()
+                )));
+            }
+            if let Some(existing) = existing.filter(|existing| existing != name) {
+                self.workspaces.remove(&existing);
+                if self.current.as_deref() == Some(existing.as_str()) {
+                    self.current = Some(name.to_string());
+                }
+            }
+            self.workspaces.insert(name.to_string(), root);
+            return Ok(name.to_string());
+        }
+
+        if let Some(existing) = existing {
+            return Ok(existing);
+        }
+
+        let base = root
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "workspace".to_string());
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while self.workspaces.contains_key(&name) {
+            name = format!("{}-{}", base, suffix);
+            suffix += 1;
+        }
+
+        self.workspaces.insert(name.clone(), root);
+        Ok(name)
+    }
+
+    /// Find the name of a registered root
+    pub fn find(&self, root: &Path) -> Option<&str> {
+        self.workspaces
+            .iter()
+            .find(|(_, path)| path.as_path() == root)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Whether a registered root no longer holds a BaseCamp configuration
+    pub fn is_stale(root: &Path) -> bool {
+        !root.join(".basecamp").join("config.yaml").exists()
+    }
+
+    /// Forget every stale workspace and return the removed entries
+    pub fn prune(&mut self) -> Vec<(String, PathBuf)> {
+        let stale: Vec<(String, PathBuf)> = self
+            .workspaces
+            .iter()
+            .filter(|(_, path)| Self::is_stale(path))
+            .map(|(name, path)| (name.clone(), path.clone()))
+            .collect();
+
+        for (name, _) in &stale {
+            self.workspaces.remove(name);
+        }
+        if self.current.as_ref().is_some_and(|current| !self.workspaces.contains_key(current)) {
+            self.current = None;
+        }
+        stale
+    }
+
+    /// Root of the workspace selected with `workspaces switch`, if it still exists
+    pub fn current_root(&self) -> Option<(&str, &Path)> {
+        let name = self.current.as_deref()?;
+        let path = self.workspaces.get(name)?;
+        (!Self::is_stale(path)).then_some((name, path.as_path()))
+    }
+}
@@ -0,0 +1,209 @@
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::debug;
+
+/// Output kept of a command when no limit is given (1 MiB)
+pub const DEFAULT_MAX_OUTPUT: usize = 1024 * 1024;
+
+/// How often a running command is checked for its exit and timeout
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// How the output of commands is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputMode {
+    /// Buffered per command and handed back when it ends
+    #[default]
+    Capture,
+    /// Handed line by line to the caller while the command runs
+    Stream,
+}
+
+/// Limits and output handling of commands
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    /// Time after which the command and its children are killed
+    pub timeout: Option<Duration>,
+    pub mode: OutputMode,
+    /// Bytes of output kept or streamed, the rest is dropped
+    pub max_output: usize,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            mode: OutputMode::default(),
+            max_output: DEFAULT_MAX_OUTPUT,
+        }
+    }
+}
+
+/// How a command ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Exit {
+    Success,
+    /// Exited with a failure code (`None` when killed by a signal)
+    Failed(Option<i32>),
+    /// Killed after running for the timeout
+    TimedOut(Duration),
+    /// Couldn't be started
+    NotStarted(String),
+}
+
+/// Outcome of a command
+#[derive(Debug, Clone)]
+pub struct RunResult {
+    pub exit: Exit,
+    /// Standard output and error as they came, empty when streamed
+    pub output: String,
+    /// Whether output beyond `max_output` was dropped
+    pub truncated: bool,
+}
+
+impl RunResult {
+    /// Why the command didn't succeed, for a report
+    pub fn reason(&self) -> Option<String> {
+        match &self.exit {
+            Exit::Success => None,
+            Exit::Failed(Some(code)) => Some(format!("exited with code {}", code)),
+            Exit::Failed(None) => Some("was killed".to_string()),
+            Exit::TimedOut(after) => Some(format!("timed out after {}s and was killed", after.as_secs())),
+            Exit::NotStarted(error) => Some(format!("couldn't be started: {}", error)),
+        }
+    }
+}
+
+/// Output read so far, up to the limit
+struct Collected {
+    text: String,
+    bytes: usize,
+    truncated: bool,
+}
+
+/// Run a command with its output piped, killing it and its children on timeout
+///
+/// Standard input is closed. In stream mode every line is passed to `on_line`
+/// as it arrives, from reader threads; in capture mode the lines are kept in
+/// the result. Either way output stops after `max_output` bytes.
+pub fn run(mut command: Command, options: &RunOptions, on_line: &(dyn Fn(&str) + Sync)) -> RunResult {
+    command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            return RunResult {
+                exit: Exit::NotStarted(e.to_string()),
+                output: String::new(),
+                truncated: false,
+            };
+        }
+    };
+
+    let collected = Mutex::new(Collected {
+        text: String::new(),
+        bytes: 0,
+        truncated: false,
+    });
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    let exit = thread::scope(|scope| {
+        for pipe in [stdout.map(|p| Box::new(p) as Box<dyn Read + Send>), stderr.map(|p| Box::new(p) as _)]
+            .into_iter()
+            .flatten()
+        {
+            let collected = &collected;
+            scope.spawn(move || read_lines(pipe, options, collected, on_line));
+        }
+        wait(&mut child, options.timeout)
+    });
+
+    let collected = collected.into_inner().unwrap();
+    RunResult {
+        exit,
+        output: collected.text,
+        truncated: collected.truncated,
+    }
+}
+
+/// Read the lines of a pipe until it closes
+fn read_lines(
+    pipe: Box<dyn Read + Send>,
+    options: &RunOptions,
+    collected: &Mutex<Collected>,
+    on_line: &(dyn Fn(&str) + Sync),
+) {
+    let mut reader = BufReader::new(pipe);
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line).is_ok_and(|read| read > 0) {
+        let text = String::from_utf8_lossy(&line);
+        let mut collected = collected.lock().unwrap();
+        if collected.bytes + line.len() > options.max_output {
+            // Keep draining so the command doesn't block on a full pipe
+            collected.truncated = true;
+        } else {
+            collected.bytes += line.len();
+            match options.mode {
+                OutputMode::Capture => collected.text.push_str(&text),
+                OutputMode::Stream => on_line(text.trim_end_matches(['\n', '\r'])),
+            }
+        }
+        line.clear();
+    }
+}
+
+/// Wait for a command to exit, killing it once the timeout is reached
+fn wait(child: &mut Child, timeout: Option<Duration>) -> Exit {
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return exit_of(status),
+            Ok(None) => {}
+            Err(e) => return Exit::NotStarted(e.to_string()),
+        }
+        if let Some(timeout) = timeout
+            && started.elapsed() >= timeout
+        {
+            debug!("Killing process {} after {:?}", child.id(), timeout);
+            kill(child);
+            let _ = child.wait();
+            return Exit::TimedOut(timeout);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Kill a command with every process it started, which would keep its pipes open
+fn kill(child: &mut Child) {
+    #[cfg(unix)]
+    let killed = Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", child.id())])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    #[cfg(windows)]
+    let killed = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &child.id().to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    if !killed {
+        let _ = child.kill();
+    }
+}
+
+fn exit_of(status: ExitStatus) -> Exit {
+    if status.success() {
+        Exit::Success
+    } else {
+        Exit::Failed(status.code())
+    }
+}
@@ -0,0 +1,19 @@
+use std::process::Command;
+
+/// Build a command that runs through the platform shell
+pub fn command(text: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", text]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", text]);
+        cmd
+    }
+}
+
+/// Quote a word for POSIX shells
+pub fn quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
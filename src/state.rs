@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::error::BasecampResult;
+use crate::git::provider::RemoteHealth;
+use crate::git::{GitRepo, RepoStatus};
+use crate::workspace::Workspace;
+
+/// Cached state of a single repository
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RepoState {
+    /// Current branch name
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// Commits ahead of the upstream branch
+    #[serde(default)]
+    pub ahead: usize,
+    /// Commits behind the upstream branch
+    #[serde(default)]
+    pub behind: usize,
+    /// Whether the working tree has uncommitted changes
+    #[serde(default)]
+    pub dirty: bool,
+    /// Unix timestamp of the last successful fetch
+    #[serde(default)]
+    pub last_fetch: Option<u64>,
+    /// Unix timestamp of the last update of this entry
+    #[serde(default)]
+    pub updated_at: u64,
+    /// Size of the clone on disk in bytes, measured when it was installed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+/// Cached answer of the host about a repository
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RemoteState {
+    #[serde(flatten)]
+    pub health: RemoteHealth,
+    /// Unix timestamp of the request
+    #[serde(default)]
+    pub checked_at: u64,
+}
+
+/// Workspace state cache stored in `.basecamp/state.json`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WorkspaceState {
+    /// Repository states keyed by `<codebase>/<repo>`
+    #[serde(default)]
+    pub repos: BTreeMap<String, RepoState>,
+
+    /// CI state and open pull requests reported by the host, keyed by repository name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub remote: BTreeMap<String, RemoteState>,
+
+    /// Workspace the cache belongs to
+    #[serde(skip)]
+    workspace: Workspace,
+}
+
+impl WorkspaceState {
+    /// Get path to the state.json file of a workspace
+    pub fn get_state_path(workspace: &Workspace) -> PathBuf {
+        workspace.basecamp_dir().join("state.json")
+    }
+
+    /// Load the state cache of a workspace, starting from an empty cache when it is missing or unreadable
+    pub fn load(workspace: &Workspace) -> Self {
+        let path = Self::get_state_path(workspace);
+        let state = if !path.exists() {
+            Self::default()
+        } else {
+            match fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+            {
+                Ok(state) => state,
+                Err(e) => {
+                    warn!("Ignoring unreadable state cache {:?}: {}", path, e);
+                    Self::default()
+                }
+            }
+        };
+
+        Self {
+            workspace: workspace.clone(),
+            ..state
+        }
+    }
+
+    /// Save the state cache to the workspace it was loaded from
+    pub fn save(&self) -> BasecampResult<()> {
+        self.workspace.ensure_basecamp_dir()?;
+        let path = Self::get_state_path(&self.workspace);
+        debug!("Saving workspace state to {:?}", path);
+
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Get the cached state of a repository
+    pub fn get(&self, codebase: &str, repo: &str) -> Option<&RepoState> {
+        self.repos.get(&Self::key(codebase, repo))
+    }
+
+    /// Record a freshly computed status, optionally marking it as fetched
+    pub fn update(&mut self, codebase: &str, repo: &str, status: &RepoStatus, fetched: bool) {
+        let now = now();
+        let entry = self.repos.entry(Self::key(codebase, repo)).or_default();
+
+        entry.branch = status.branch.clone();
+        entry.ahead = status.ahead;
+        entry.behind = status.behind;
+        entry.dirty = status.dirty;
+        entry.updated_at = now;
+        if fetched {
+            entry.last_fetch = Some(now);
+        }
+    }
+
+    /// Record the size of an installed repository
+    pub fn set_size(&mut self, codebase: &str, repo: &str, size: u64) {
+        self.repos.entry(Self::key(codebase, repo)).or_default().size = Some(size);
+    }
+
+    /// Last known size of a repository, from any codebase it was installed in
+    pub fn size_of(&self, repo: &str) -> Option<u64> {
+        self.repos
+            .iter()
+            .filter(|(key, _)| key.rsplit_once('/').is_some_and(|(_, name)| name == repo))
+            .find_map(|(_, state)| state.size)
+    }
+
+    /// Get the state of a repository, recomputing it locally when the cached entry is stale
+    ///
+    /// A `ttl` of 0 always recomputes, against the clone remote `remote`.
+    /// Returns `None` when the repository isn't installed.
+    pub fn fresh(
+        &mut self,
+        codebase: &str,
+        repo: &str,
+        repo_path: &Path,
+        remote: &str,
+        ttl: u64,
+    ) -> BasecampResult<Option<&RepoState>> {
+        if !repo_path.exists() {
+            return Ok(None);
+        }
+
+        let is_fresh = self
+            .get(codebase, repo)
+            .map(|s| ttl > 0 && now().saturating_sub(s.updated_at) < ttl)
+            .unwrap_or(false);
+
+        if !is_fresh {
+            debug!("Refreshing cached state of {}/{}", codebase, repo);
+            let status = GitRepo::status(repo_path, remote)?;
+            self.update(codebase, repo, &status, false);
+        }
+
+        Ok(self.get(codebase, repo))
+    }
+
+    /// Get what the host reported about a repository less than `ttl` seconds ago
+    pub fn fresh_remote(&self, repo: &str, ttl: u64) -> Option<&RemoteHealth> {
+        self.remote
+            .get(repo)
+            .filter(|remote| ttl > 0 && now().saturating_sub(remote.checked_at) < ttl)
+            .map(|remote| &remote.health)
+    }
+
+    /// Record what the host reported about a repository
+    pub fn update_remote(&mut self, repo: &str, health: RemoteHealth) {
+        self.remote.insert(
+            repo.to_string(),
+            RemoteState {
+                health,
+                checked_at: now(),
+            },
+        );
+    }
+
+    /// Build the cache key of a repository
+    fn key(codebase: &str, repo: &str) -> String {
+        format!("{}/{}", codebase, repo)
+    }
+}
+
+/// Current time as a Unix timestamp
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
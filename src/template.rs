@@ -0,0 +1,147 @@
+use std::env;
+
+use serde_yaml::Value;
+
+use crate::config::Config;
+
+/// Values of the placeholders in configuration values
+///
+/// `{home}` is always known; the others only where the value is used for a
+/// repository or codebase. Placeholders without a value are left as written.
+#[derive(Debug, Clone, Default)]
+pub struct Vars {
+    /// Owner of `github_url` (`{org}`)
+    pub org: Option<String>,
+    /// Codebase the value is used for (`{codebase}`)
+    pub codebase: Option<String>,
+    /// Repository the value is used for (`{repo}`)
+    pub repo: Option<String>,
+}
+
+impl Vars {
+    /// Same values, for a repository of a codebase
+    pub fn with_repo(&self, codebase: &str, repo: &str) -> Self {
+        Self {
+            org: self.org.clone(),
+            codebase: Some(codebase.to_string()),
+            repo: Some(repo.to_string()),
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<String> {
+        match name {
+            "org" => self.org.clone(),
+            "codebase" => self.codebase.clone(),
+            "repo" => self.repo.clone(),
+            "home" => Config::home_dir().map(|home| home.to_string_lossy().into_owned()),
+            _ => None,
+        }
+    }
+}
+
+/// Replace the `{org}`, `{codebase}`, `{repo}` and `{home}` placeholders and
+/// `${NAME}` environment variables of a configuration value
+///
+/// Unknown placeholders and unset variables are kept, so braces meant for a
+/// shell (`${PATH}` of a task command, `awk '{print}'`) still reach it.
+pub fn expand(text: &str, vars: &Vars) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start + 1..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 1..start + 1 + len];
+        let variable = rest[..start].ends_with('$');
+        let value = if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            None
+        } else if variable {
+            env::var(name).ok()
+        } else {
+            vars.get(name)
+        };
+
+        match value {
+            Some(value) => {
+                expanded.push_str(&rest[..start - usize::from(variable)]);
+                expanded.push_str(&value);
+            }
+            None => expanded.push_str(&rest[..=start + len + 1]),
+        }
+        rest = &rest[start + len + 2..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Step from a YAML value to one of its children
+#[derive(Debug, Clone)]
+enum Step {
+    Key(Value),
+    Index(usize),
+}
+
+/// A string of a configuration file whose placeholders were expanded
+#[derive(Debug, Clone)]
+pub struct Expanded {
+    path: Vec<Step>,
+    raw: String,
+    value: String,
+}
+
+/// Expand the placeholders of every string in a YAML value, returning what
+/// changed so [`restore`] can write the placeholders back
+pub fn expand_value(value: &mut Value, vars: &Vars) -> Vec<Expanded> {
+    let mut expanded = Vec::new();
+    expand_at(value, vars, &mut Vec::new(), &mut expanded);
+    expanded
+}
+
+fn expand_at(value: &mut Value, vars: &Vars, path: &mut Vec<Step>, expanded: &mut Vec<Expanded>) {
+    match value {
+        Value::String(text) => {
+            let new = expand(text, vars);
+            if new != *text {
+                expanded.push(Expanded {
+                    path: path.clone(),
+                    raw: std::mem::replace(text, new.clone()),
+                    value: new,
+                });
+            }
+        }
+        Value::Mapping(mapping) => {
+            for (key, child) in mapping.iter_mut() {
+                path.push(Step::Key(key.clone()));
+                expand_at(child, vars, path, expanded);
+                path.pop();
+            }
+        }
+        Value::Sequence(items) => {
+            for (index, child) in items.iter_mut().enumerate() {
+                path.push(Step::Index(index));
+                expand_at(child, vars, path, expanded);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Put the placeholders back into the strings that still have their expanded value
+pub fn restore(value: &mut Value, expanded: &[Expanded]) {
+    for entry in expanded {
+        let mut current = Some(&mut *value);
+        for step in &entry.path {
+            current = match (current, step) {
+                (Some(Value::Mapping(mapping)), Step::Key(key)) => mapping.get_mut(key),
+                (Some(Value::Sequence(items)), Step::Index(index)) => items.get_mut(*index),
+                _ => None,
+            };
+        }
+        if let Some(Value::String(text)) = current
+            && *text == entry.value
+        {
+            *text = entry.raw.clone();
+        }
+    }
+}
@@ -1,37 +1,393 @@
-use console::style;
-use dialoguer::{Confirm, Input, Select};
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::collections::HashMap;
+use std::io::{self, IsTerminal};
+use std::sync::{Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::{Duration, Instant};
+
+use console::{Color, style};
+use dialoguer::{Confirm, Input, MultiSelect, Select};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
 use log::error;
 use prettytable::{Cell, Row, Table};
+use similar::TextDiff;
+
+use crate::core::{ItemStatus, Notice, ProgressSink};
+use crate::error::{BasecampError, BasecampResult};
+use crate::messages;
+
+/// Whether prompts were disabled with --non-interactive
+static NON_INTERACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Whether confirmations are answered with 'yes' (--yes)
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+/// Whether output uses colors and progress bars (see `UI::set_styling`)
+static STYLED: AtomicBool = AtomicBool::new(true);
+
+/// Colors allowed on the command line (no --no-color)
+static COLOR: AtomicBool = AtomicBool::new(true);
+
+/// Colors allowed by the loaded workspace (no `color: false` in config.yaml)
+static WORKSPACE_COLOR: AtomicBool = AtomicBool::new(true);
+
+/// Colors and characters of the output (see `UI::set_theme`)
+static THEME: RwLock<Theme> = RwLock::new(Theme::DEFAULT);
+
+/// Colors and characters used in the output, from the `ui` section of config.yaml
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Color of success marks and summaries
+    pub success: Color,
+    /// Color of warning marks
+    pub warning: Color,
+    /// Color of errors and failures
+    pub error: Color,
+    /// Spinner frames, one character each (indicatif's default when unset)
+    pub spinner: Option<String>,
+    /// Plain ASCII instead of Unicode symbols
+    pub ascii: bool,
+}
+
+impl Theme {
+    const DEFAULT: Theme = Theme {
+        success: Color::Green,
+        warning: Color::Yellow,
+        error: Color::Red,
+        spinner: None,
+        ascii: false,
+    };
+
+    /// Spinner frames used in ASCII mode
+    const ASCII_SPINNER: &'static str = "|/-\\";
+
+    /// Parse a color name (`red`, `green`, ...) or a 256-color palette index
+    pub fn parse_color(name: &str) -> Option<Color> {
+        match name.to_lowercase().as_str() {
+            "black" => Some(Color::Black),
+            "red" => Some(Color::Red),
+            "green" => Some(Color::Green),
+            "yellow" => Some(Color::Yellow),
+            "blue" => Some(Color::Blue),
+            "magenta" => Some(Color::Magenta),
+            "cyan" => Some(Color::Cyan),
+            "white" => Some(Color::White),
+            index => index.parse().ok().map(Color::Color256),
+        }
+    }
+
+    /// Frames of the spinner, one string per tick with the final frame last
+    fn spinner_frames(&self) -> Option<Vec<String>> {
+        let chars = match (&self.spinner, self.ascii) {
+            (Some(chars), _) => chars.as_str(),
+            (None, true) => Self::ASCII_SPINNER,
+            (None, false) => return None,
+        };
+        Some(chars.chars().map(String::from).chain([" ".to_string()]).collect())
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Symbols of the output, with a plain ASCII form for terminals and fonts lacking them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Glyph {
+    Success,
+    Failure,
+    Ahead,
+    Behind,
+    Separator,
+    Installed,
+    Missing,
+    TreeBranch,
+    TreeLast,
+}
+
+impl Glyph {
+    /// The symbol in the configured character set
+    pub fn as_str(self) -> &'static str {
+        let ascii = THEME.read().unwrap().ascii;
+        match (self, ascii) {
+            (Self::Success, false) => "✓",
+            (Self::Success, true) => "+",
+            (Self::Failure, false) => "✗",
+            (Self::Failure, true) => "x",
+            (Self::Ahead, false) => "↑",
+            (Self::Ahead, true) => "^",
+            (Self::Behind, false) => "↓",
+            (Self::Behind, true) => "v",
+            (Self::Separator, false) => " · ",
+            (Self::Separator, true) => " | ",
+            (Self::Installed, false) => "✓",
+            (Self::Installed, true) => "+",
+            (Self::Missing, false) => "○",
+            (Self::Missing, true) => "-",
+            (Self::TreeBranch, false) => "├──",
+            (Self::TreeBranch, true) => "|--",
+            (Self::TreeLast, false) => "└──",
+            (Self::TreeLast, true) => "`--",
+        }
+    }
+}
+
+impl std::fmt::Display for Glyph {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Amount of output shown to the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only errors and final results (--quiet)
+    Quiet,
+    /// Regular progress and informational messages
+    Normal,
+    /// Additional per-repository details (-v)
+    Verbose,
+}
+
+/// Kind of a message sent to the reporter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKind {
+    /// Final results: success summaries, tables and command output
+    Result,
+    /// Errors, always written to stderr
+    Error,
+    /// Warnings about recoverable problems
+    Warning,
+    /// Informational messages
+    Info,
+    /// Progress lines and layout padding
+    Progress,
+    /// Details only shown in verbose mode
+    Detail,
+}
+
+/// Central sink for all user-facing output
+///
+/// Every `UI` message goes through the global reporter, which decides what is shown.
+pub struct Reporter {
+    verbosity: AtomicU8,
+    machine_output: AtomicBool,
+}
+
+static REPORTER: Reporter = Reporter {
+    verbosity: AtomicU8::new(Verbosity::Normal as u8),
+    machine_output: AtomicBool::new(false),
+};
+
+impl Reporter {
+    /// Get the global reporter
+    pub fn global() -> &'static Reporter {
+        &REPORTER
+    }
+
+    /// Set the amount of output shown
+    pub fn set_verbosity(&self, verbosity: Verbosity) {
+        self.verbosity.store(verbosity as u8, Ordering::Relaxed);
+    }
+
+    /// Get the amount of output shown
+    pub fn verbosity(&self) -> Verbosity {
+        match self.verbosity.load(Ordering::Relaxed) {
+            0 => Verbosity::Quiet,
+            1 => Verbosity::Normal,
+            _ => Verbosity::Verbose,
+        }
+    }
+
+    /// Send human-readable messages to stderr so stdout only carries raw output (e.g. JSON reports)
+    pub fn set_machine_output(&self, enabled: bool) {
+        self.machine_output.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Whether messages of a kind are shown
+    pub fn shows(&self, kind: MessageKind) -> bool {
+        match kind {
+            MessageKind::Result | MessageKind::Error => true,
+            MessageKind::Warning | MessageKind::Info | MessageKind::Progress => {
+                self.verbosity() >= Verbosity::Normal
+            }
+            MessageKind::Detail => self.verbosity() == Verbosity::Verbose,
+        }
+    }
+
+    /// Write a line of output
+    pub fn report(&self, kind: MessageKind, line: &str) {
+        if !self.shows(kind) {
+            return;
+        }
+
+        if kind == MessageKind::Error || self.machine_output.load(Ordering::Relaxed) {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    }
 
-use crate::error::BasecampResult;
+    /// Write raw output that is not terminated by a newline (e.g. generated files)
+    pub fn write_raw(&self, text: &str) {
+        print!("{}", text);
+    }
+}
 
 /// Terminal UI utilities
 pub struct UI;
 
 impl UI {
-    /// Print a success message
+    /// Configure colors and progress bars for the rest of the run
+    ///
+    /// Styling is off without `color` (--no-color), with `color: false` in the
+    /// loaded workspace, when `NO_COLOR` is set, or when stdout is not a terminal.
+    pub fn set_styling(color: bool) {
+        COLOR.store(color, Ordering::Relaxed);
+        Self::restyle();
+    }
+
+    /// Apply the `color` setting of a loaded workspace, replacing that of the previous one
+    pub fn set_workspace_color(color: bool) {
+        WORKSPACE_COLOR.store(color, Ordering::Relaxed);
+        Self::restyle();
+    }
+
+    fn restyle() {
+        let color = COLOR.load(Ordering::Relaxed) && WORKSPACE_COLOR.load(Ordering::Relaxed);
+        let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let styled = color && !no_color_env && io::stdout().is_terminal();
+
+        STYLED.store(styled, Ordering::Relaxed);
+        console::set_colors_enabled(styled);
+        console::set_colors_enabled_stderr(styled);
+    }
+
+    /// Whether output uses colors and progress bars
+    pub fn is_styled() -> bool {
+        STYLED.load(Ordering::Relaxed)
+    }
+
+    /// Use the colors and characters of a theme for the rest of the run
+    pub fn set_theme(theme: Theme) {
+        *THEME.write().unwrap() = theme;
+    }
+
+    /// Colors and characters in use
+    pub fn theme() -> Theme {
+        THEME.read().unwrap().clone()
+    }
+
+    /// Configure how prompts behave for the rest of the run
+    pub fn set_interaction(non_interactive: bool, assume_yes: bool) {
+        NON_INTERACTIVE.store(non_interactive, Ordering::Relaxed);
+        ASSUME_YES.store(assume_yes, Ordering::Relaxed);
+    }
+
+    /// Whether prompts can be shown (not disabled and stdin is a terminal)
+    pub fn is_interactive() -> bool {
+        !NON_INTERACTIVE.load(Ordering::Relaxed) && std::io::stdin().is_terminal()
+    }
+
+    /// Whether confirmations are answered with 'yes' without asking
+    pub fn assume_yes() -> bool {
+        ASSUME_YES.load(Ordering::Relaxed)
+    }
+
+    /// Print a success message (kept in quiet mode as part of the final summary)
     pub fn success(message: &str) {
-        println!("{} {}", style("✓").green().bold(), message);
+        Reporter::global().report(
+            MessageKind::Result,
+            &format!("{} {}", style(Glyph::Success).fg(Self::theme().success).bold(), message),
+        );
     }
 
     /// Print an error message
     pub fn error(message: &str) {
-        eprintln!("{} {}", style("✗").red().bold(), style(message).red());
+        let theme = Self::theme();
+        Reporter::global().report(
+            MessageKind::Error,
+            &format!("{} {}", style(Glyph::Failure).fg(theme.error).bold(), style(message).fg(theme.error)),
+        );
     }
 
     /// Print a warning message
     pub fn warning(message: &str) {
-        println!("{} {}", style("!").yellow().bold(), message);
+        Reporter::global().report(
+            MessageKind::Warning,
+            &format!("{} {}", style("!").fg(Self::theme().warning).bold(), message),
+        );
     }
 
     /// Print an info message
     pub fn info(message: &str) {
-        println!("{} {}", style("i").blue().bold(), message);
+        Reporter::global().report(
+            MessageKind::Info,
+            &format!("{} {}", style("i").blue().bold(), message),
+        );
+    }
+
+    /// Print a detail message, only shown in verbose mode
+    pub fn detail(message: &str) {
+        Reporter::global().report(MessageKind::Detail, &format!("  {}", style(message).dim()));
+    }
+
+    /// Print plain command output (paths, generated files) that is always shown
+    pub fn output(text: &str) {
+        Reporter::global().write_raw(text);
+    }
+
+    /// Print a plain line belonging to an error report (e.g. captured command output)
+    pub fn error_detail(text: &str) {
+        Reporter::global().report(MessageKind::Result, text);
+    }
+
+    /// Print the changes between two versions of a file as a unified diff
+    pub fn diff(label: &str, old: &str, new: &str) {
+        let diff = TextDiff::from_lines(old, new)
+            .unified_diff()
+            .header(&format!("a/{}", label), &format!("b/{}", label))
+            .to_string();
+        for line in diff.lines() {
+            let line = if line.starts_with("+++") || line.starts_with("---") {
+                style(line).bold()
+            } else if line.starts_with('+') {
+                style(line).green()
+            } else if line.starts_with('-') {
+                style(line).red()
+            } else if line.starts_with("@@") {
+                style(line).cyan()
+            } else {
+                style(line)
+            };
+            Reporter::global().report(MessageKind::Result, &line.to_string());
+        }
+    }
+
+    /// Print an empty line to separate blocks of output
+    pub fn blank_line() {
+        Reporter::global().report(MessageKind::Progress, "");
     }
 
     /// Ask for user confirmation
+    ///
+    /// Answers 'yes' with --yes; without a terminal the default answer is used.
     pub fn confirm(message: &str, default: bool) -> BasecampResult<bool> {
+        if Self::assume_yes() {
+            Self::info(&messages::format("prompt.assumed_yes", &[("message", &message)]));
+            return Ok(true);
+        }
+
+        let answer = messages::text(if default { "prompt.yes" } else { "prompt.no" });
+        if !Self::is_interactive() {
+            Self::warning(&messages::format(
+                "prompt.non_interactive",
+                &[("message", &message), ("answer", &answer)],
+            ));
+            return Ok(default);
+        }
+
         match Confirm::new()
             .with_prompt(message)
             .default(default)
@@ -41,17 +397,46 @@ impl UI {
             Ok(confirmed) => Ok(confirmed),
             Err(err) => {
                 error!("Failed to get user confirmation: {}", err);
-                Ok(default) // Fallback to default on error
+                // Like a missing terminal: show the question so the default isn't taken silently
+                Self::warning(&messages::format("prompt.no_answer", &[("message", &message), ("answer", &answer)]));
+                Ok(default)
             }
         }
     }
 
+    /// Ask for a word to be typed back before something that can't be taken back
+    ///
+    /// `--yes` answers it; without a terminal it's refused.
+    pub fn confirm_typed(message: &str, expected: &str) -> BasecampResult<bool> {
+        if Self::assume_yes() {
+            Self::info(&messages::format("prompt.assumed_yes", &[("message", &message)]));
+            return Ok(true);
+        }
+        if !Self::is_interactive() {
+            Self::warning(&messages::format("prompt.non_interactive_refused", &[("message", &message)]));
+            return Ok(false);
+        }
+
+        let typed: String = Input::new()
+            .with_prompt(messages::format("prompt.type_to_confirm", &[("message", &message), ("expected", &expected)]))
+            .allow_empty(true)
+            .interact_text()
+            .map_err(|err| BasecampError::Generic(format!("Failed to get user input: {}", err)))?;
+        Ok(typed.trim() == expected)
+    }
+
     /// Ask for user input with an optional default value
+    ///
+    /// Without a terminal the default is used, or an error names the missing value.
     pub fn input<T>(message: &str, default: Option<T>) -> BasecampResult<T>
     where
         T: std::str::FromStr + std::fmt::Display + Clone,
         <T as std::str::FromStr>::Err: std::fmt::Debug + std::fmt::Display,
     {
+        if !Self::is_interactive() {
+            return default.ok_or_else(|| Self::no_input_error(message));
+        }
+
         let input = Input::new().with_prompt(message);
 
         let input = if let Some(default_value) = default {
@@ -64,7 +449,7 @@ impl UI {
             Ok(value) => Ok(value),
             Err(err) => {
                 error!("Failed to get user input: {}", err);
-                Err(crate::error::BasecampError::Generic(format!(
+                Err(BasecampError::Generic(format!(
                     "Failed to get user input: {}",
                     err
                 )))
@@ -74,6 +459,10 @@ impl UI {
 
     /// Display a selection menu with arrow key navigation
     pub fn select(message: &str, options: &[&str], default_index: Option<usize>) -> BasecampResult<usize> {
+        if !Self::is_interactive() {
+            return default_index.ok_or_else(|| Self::no_input_error(message));
+        }
+
         let mut select = Select::new()
             .with_prompt(message)
             .items(options);
@@ -86,7 +475,31 @@ impl UI {
             Ok(selection) => Ok(selection),
             Err(err) => {
                 error!("Failed to get user selection: {}", err);
-                Err(crate::error::BasecampError::Generic(format!(
+                Err(BasecampError::Generic(format!(
+                    "Failed to get user selection: {}",
+                    err
+                )))
+            }
+        }
+    }
+
+    /// Display a checklist and return the indices of the checked options
+    pub fn multi_select(message: &str, options: &[&str], checked: bool) -> BasecampResult<Vec<usize>> {
+        if !Self::is_interactive() {
+            return Err(Self::no_input_error(message));
+        }
+
+        let defaults = vec![checked; options.len()];
+        match MultiSelect::new()
+            .with_prompt(message)
+            .items(options)
+            .defaults(&defaults)
+            .interact()
+        {
+            Ok(selection) => Ok(selection),
+            Err(err) => {
+                error!("Failed to get user selection: {}", err);
+                Err(BasecampError::Generic(format!(
                     "Failed to get user selection: {}",
                     err
                 )))
@@ -94,6 +507,14 @@ impl UI {
         }
     }
 
+    /// Error for a prompt that cannot be answered without a terminal
+    fn no_input_error(message: &str) -> BasecampError {
+        BasecampError::Generic(format!(
+            "'{}' needs an answer but prompts are disabled (non-interactive mode or no terminal). Pass the value as a command-line option.",
+            message
+        ))
+    }
+
     /// Create a progress bar
     #[allow(dead_code)]
     pub fn progress_bar(len: u64, message: &str) -> ProgressBar {
@@ -109,23 +530,110 @@ impl UI {
     }
 
     /// Create a spinner
-    #[allow(dead_code)]
     pub fn spinner(message: &str) -> ProgressBar {
         let pb = ProgressBar::new_spinner();
-        pb.set_style(
-            ProgressStyle::default_spinner()
-                .template("{spinner:.green} {msg}")
-                .unwrap(),
-        );
+        pb.set_style(Self::spinner_style("{spinner:.green} {msg}"));
         pb.set_message(message.to_string());
         pb.enable_steady_tick(std::time::Duration::from_millis(100));
         pb
     }
 
+    /// Style of a spinner with the given template, using the spinner characters of the theme
+    fn spinner_style(template: &str) -> ProgressStyle {
+        let style = ProgressStyle::default_spinner()
+            .template(template)
+            .expect("Failed to create spinner style template");
+        match Self::theme().spinner_frames() {
+            Some(frames) => style.tick_strings(&frames.iter().map(String::as_str).collect::<Vec<_>>()),
+            None => style,
+        }
+    }
+
     /// Create a multi-progress bar for parallel operations
-    #[allow(dead_code)]
+    ///
+    /// Hidden when styling is off or in quiet mode; use `UI::finish_progress` so results
+    /// still show up as plain lines.
     pub fn multi_progress() -> MultiProgress {
-        MultiProgress::new()
+        if Self::is_styled() && Reporter::global().shows(MessageKind::Progress) {
+            MultiProgress::new()
+        } else {
+            MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
+        }
+    }
+
+    /// Finish a progress bar or spinner, printing the message as a line when bars are hidden
+    pub fn finish_progress(pb: &ProgressBar, message: String) {
+        if pb.is_hidden() {
+            pb.finish_and_clear();
+            Reporter::global().report(MessageKind::Progress, &message);
+        } else {
+            pb.finish_with_message(message);
+        }
+    }
+
+    /// Format the UTC date of a Unix timestamp (e.g. "2024-03-09")
+    pub fn format_date(timestamp: u64) -> String {
+        // Days to civil date, after Howard Hinnant's `civil_from_days`
+        let days = (timestamp / 86400) as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+
+    /// Format the age of a Unix timestamp (e.g. "5m ago")
+    pub fn format_age(timestamp: Option<u64>) -> String {
+        let timestamp = match timestamp {
+            Some(t) => t,
+            None => return "never".to_string(),
+        };
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let secs = now.saturating_sub(timestamp);
+
+        match secs {
+            0..=59 => format!("{}s ago", secs),
+            60..=3599 => format!("{}m ago", secs / 60),
+            3600..=86399 => format!("{}h ago", secs / 3600),
+            _ => format!("{}d ago", secs / 86400),
+        }
+    }
+
+    /// Format a byte count for humans (e.g. "1.5 MiB")
+    pub fn format_bytes(bytes: u64) -> String {
+        const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB"];
+
+        let mut value = bytes as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+
+        if unit == 0 {
+            format!("{} B", bytes)
+        } else {
+            format!("{:.1} {}", value, UNITS[unit])
+        }
+    }
+
+    /// Format an elapsed time for humans (e.g. "4.2s", "42s", "3m 05s")
+    pub fn format_duration(elapsed: Duration) -> String {
+        let secs = elapsed.as_secs();
+        match secs {
+            0..=9 => format!("{:.1}s", elapsed.as_secs_f64()),
+            10..=59 => format!("{}s", secs),
+            60..=3599 => format!("{}m {:02}s", secs / 60, secs % 60),
+            _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+        }
     }
 
     /// Create a table for displaying data
@@ -159,6 +667,349 @@ impl UI {
 
     /// Display a table
     pub fn print_table(table: &Table) {
-        table.printstd();
+        if Self::is_styled() {
+            table.printstd();
+        } else if let Err(e) = table.print(&mut io::stdout()) {
+            error!("Failed to print table: {}", e);
+        }
+    }
+}
+
+/// End-of-command outcome line, e.g. `✓ 12 cloned · 3 skipped · 1 failed · 42s`
+///
+/// Every command that processes repositories ends with one, so outcomes read
+/// the same everywhere. Zero counts are left out and any failure turns the
+/// line red. It's a final result, shown in quiet mode too.
+#[derive(Debug, Default)]
+pub struct Summary {
+    parts: Vec<String>,
+    failed: bool,
+}
+
+impl Summary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a count (e.g. "12 cloned"), left out when zero
+    pub fn count(mut self, count: usize, label: &str) -> Self {
+        if count > 0 {
+            self.parts.push(format!("{} {}", count, label));
+        }
+        self
+    }
+
+    /// Add a count of failures, which marks the whole outcome as failed
+    pub fn failed(mut self, count: usize, label: &str) -> Self {
+        if count > 0 {
+            self.parts.push(style(format!("{} {}", count, label)).fg(UI::theme().error).bold().to_string());
+            self.failed = true;
+        }
+        self
+    }
+
+    /// Add a free-form part (e.g. the amount transferred)
+    pub fn note(mut self, text: &str) -> Self {
+        self.parts.push(text.to_string());
+        self
+    }
+
+    /// Print the line, ending with the time the command took
+    pub fn print(self, elapsed: Duration) {
+        let theme = UI::theme();
+        let mark = if self.failed {
+            style(Glyph::Failure).fg(theme.error).bold()
+        } else {
+            style(Glyph::Success).fg(theme.success).bold()
+        };
+        let mut parts = self.parts;
+        if parts.is_empty() {
+            parts.push(messages::text("summary.nothing_to_do"));
+        }
+        parts.push(style(UI::format_duration(elapsed)).dim().to_string());
+
+        let separator = style(Glyph::Separator).dim().to_string();
+        Reporter::global().report(MessageKind::Result, &format!("{} {}", mark, parts.join(&separator)));
+    }
+}
+
+/// Progress of items processed in parallel: an overall bar with a spinner per item
+///
+/// Shared by the worker threads through `&self`. When output is not a
+/// terminal (or styling is off) nothing is drawn and finished items are
+/// printed as plain lines instead (see `UI::multi_progress`). Core operations
+/// report through it as a [`ProgressSink`].
+pub struct ParallelProgress {
+    multi: MultiProgress,
+    bar: Mutex<Option<ProgressBar>>,
+    groups: Mutex<Vec<ProgressGroup>>,
+    items: Mutex<HashMap<String, ProgressBar>>,
+    transfer: Mutex<TransferRate>,
+    failed: AtomicBool,
+}
+
+/// Download rate, measured over the last second or more
+pub struct TransferRate {
+    sampled_at: Instant,
+    sampled_bytes: u64,
+    bytes_per_sec: u64,
+}
+
+impl TransferRate {
+    /// A rate measured from `started`, unknown until a second has passed
+    pub fn new(started: Instant) -> Self {
+        Self {
+            sampled_at: started,
+            sampled_bytes: 0,
+            bytes_per_sec: 0,
+        }
+    }
+
+    /// Take `bytes` received in total by `now` into account
+    pub fn update(&mut self, bytes: u64, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.sampled_at);
+        if elapsed >= Duration::from_secs(1) {
+            self.bytes_per_sec = (bytes.saturating_sub(self.sampled_bytes) as f64 / elapsed.as_secs_f64()) as u64;
+            self.sampled_at = now;
+            self.sampled_bytes = bytes;
+        }
+    }
+
+    /// Bytes received per second over the last sample, 0 until known
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.bytes_per_sec
+    }
+}
+
+/// Bar of a group of items (e.g. a codebase), with the spinners of its items under it
+struct ProgressGroup {
+    name: String,
+    bar: ProgressBar,
+    failed: usize,
+}
+
+impl ProgressGroup {
+    /// Index of the group of an item labelled `<group>/<item>`
+    ///
+    /// Groups can be nested (`platform`, `platform/web`), so the longest name wins.
+    fn find(groups: &[ProgressGroup], item: &str) -> Option<usize> {
+        groups
+            .iter()
+            .enumerate()
+            .filter(|(_, group)| item.strip_prefix(group.name.as_str()).is_some_and(|rest| rest.starts_with('/')))
+            .max_by_key(|(_, group)| group.name.len())
+            .map(|(index, _)| index)
+    }
+
+    /// Finish the bar, printing the outcome of the group as a line when bars are hidden
+    fn finish(&self) {
+        if !self.bar.is_hidden() {
+            self.bar.finish();
+            return;
+        }
+        let done = format!("{}/{}", self.bar.position(), self.bar.length().unwrap_or(0));
+        let message = if self.failed > 0 {
+            format!("'{}' finished with {} failed ({})", self.name, self.failed, done)
+        } else {
+            format!("'{}' finished ({})", self.name, done)
+        };
+        UI::finish_progress(&self.bar, message);
+    }
+}
+
+impl ParallelProgress {
+    /// Progress without an overall bar until `start` is called
+    pub fn new() -> Self {
+        Self {
+            multi: UI::multi_progress(),
+            bar: Mutex::new(None),
+            groups: Mutex::new(Vec::new()),
+            items: Mutex::new(HashMap::new()),
+            transfer: Mutex::new(TransferRate::new(Instant::now())),
+            failed: AtomicBool::new(false),
+        }
+    }
+
+    /// Progress over `total` items, with an overall bar labelled `message`
+    pub fn with_total(total: usize, message: &str) -> Self {
+        let progress = Self::new();
+        progress.start(total, message);
+        progress
+    }
+
+    /// Show the overall bar for `total` items
+    pub fn start(&self, total: usize, message: &str) {
+        let bar = self.multi.add(ProgressBar::new(total as u64));
+        bar.set_style(Self::bar_style("cyan/blue"));
+        bar.set_message(message.to_string());
+        *self.bar.lock().unwrap() = Some(bar);
+        *self.transfer.lock().unwrap() = TransferRate::new(Instant::now());
+    }
+
+    /// Show the bytes received so far and the download rate after the overall bar
+    pub fn transferred(&self, bytes: u64) {
+        let mut transfer = self.transfer.lock().unwrap();
+        transfer.update(bytes, Instant::now());
+        if let Some(bar) = self.bar.lock().unwrap().as_ref() {
+            let mut text = format!("{}{}", Glyph::Separator, UI::format_bytes(bytes));
+            if transfer.bytes_per_sec() > 0 {
+                text.push_str(&format!("{}{}/s", Glyph::Separator, UI::format_bytes(transfer.bytes_per_sec())));
+            }
+            bar.set_prefix(text);
+        }
+    }
+
+    /// Show a bar per group of items under the overall bar, with the number of items of each
+    ///
+    /// Items labelled `<group>/<item>` are then shown under their group's bar
+    /// and counted in it.
+    pub fn start_groups(&self, groups: &[(String, usize)]) {
+        let width = groups.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+        let mut bars = self.groups.lock().unwrap();
+        for (name, total) in groups {
+            let bar = self.multi.add(ProgressBar::new(*total as u64));
+            bar.set_style(Self::group_style("green/blue"));
+            bar.set_message(format!("{:width$}", name, width = width));
+            bars.push(ProgressGroup {
+                name: name.clone(),
+                bar,
+                failed: 0,
+            });
+        }
+    }
+
+    /// Show a spinner for an item, or update the message of its spinner
+    pub fn start_item(&self, item: &str, message: &str) {
+        let mut items = self.items.lock().unwrap();
+        let spinner = items.entry(item.to_string()).or_insert_with(|| {
+            let groups = self.groups.lock().unwrap();
+            let spinner = match ProgressGroup::find(&groups, item) {
+                Some(index) => {
+                    let spinner = self.multi.insert_after(&groups[index].bar, ProgressBar::new_spinner());
+                    spinner.set_style(UI::spinner_style("  {spinner:.green} {wide_msg}"));
+                    spinner
+                }
+                None => {
+                    let spinner = self.multi.add(ProgressBar::new_spinner());
+                    spinner.set_style(UI::spinner_style("{spinner:.green} {wide_msg}"));
+                    spinner
+                }
+            };
+            spinner.enable_steady_tick(std::time::Duration::from_millis(100));
+            spinner
+        });
+        spinner.set_message(message.to_string());
+    }
+
+    /// Replace the spinner of an item with its result and advance the overall bar
+    ///
+    /// Succeeded items are marked with ✓ and failed ones with ✗ (+ and x in ASCII mode).
+    pub fn finish_item(&self, item: &str, status: ItemStatus, message: &str) {
+        let message = match status {
+            ItemStatus::Succeeded => format!("{} {}", message, Glyph::Success),
+            ItemStatus::Skipped => message.to_string(),
+            ItemStatus::Failed => {
+                self.failed.store(true, Ordering::Relaxed);
+                format!("{} {}", message, Glyph::Failure)
+            }
+        };
+
+        match self.items.lock().unwrap().remove(item) {
+            Some(spinner) => UI::finish_progress(&spinner, message),
+            None => Reporter::global().report(MessageKind::Progress, &message),
+        }
+        if let Some(bar) = self.bar.lock().unwrap().as_ref() {
+            bar.inc(1);
+        }
+
+        let mut groups = self.groups.lock().unwrap();
+        if let Some(index) = ProgressGroup::find(&groups, item) {
+            let group = &mut groups[index];
+            if status == ItemStatus::Failed {
+                group.failed += 1;
+                group.bar.set_style(Self::group_style("red/blue"));
+            }
+            group.bar.inc(1);
+            if Some(group.bar.position()) == group.bar.length() {
+                group.finish();
+            }
+        }
+    }
+
+    /// Print messages without them being overwritten by the bars
+    pub fn suspend<R>(&self, print: impl FnOnce() -> R) -> R {
+        self.multi.suspend(print)
+    }
+
+    /// Finish the overall bar, turning it red when an item failed, and the bars of groups
+    ///
+    /// Groups with items that never ran (e.g. after --fail-fast) finish short of their total.
+    pub fn finish(&self, message: &str) {
+        for group in self.groups.lock().unwrap().drain(..) {
+            if !group.bar.is_finished() {
+                group.finish();
+            }
+        }
+        if let Some(bar) = self.bar.lock().unwrap().take() {
+            if self.failed.load(Ordering::Relaxed) {
+                bar.set_style(Self::bar_style("red/blue"));
+            }
+            UI::finish_progress(&bar, message.to_string());
+        }
+    }
+
+    /// Style of the bar of a group, labelled with its padded name
+    fn group_style(colors: &str) -> ProgressStyle {
+        ProgressStyle::default_bar()
+            .template(&format!("{{msg}} [{{bar:30.{}}}] {{pos}}/{{len}}", colors))
+            .expect("Failed to create progress bar template")
+            .progress_chars("=> ")
+    }
+
+    /// Style of the overall progress bar, followed by the transfer (kept in its prefix)
+    fn bar_style(colors: &str) -> ProgressStyle {
+        ProgressStyle::default_bar()
+            .template(&format!("{{msg}} [{{bar:40.{}}}] {{pos}}/{{len}} ({{percent}}%){{prefix}}", colors))
+            .expect("Failed to create progress bar template")
+            .progress_chars("=> ")
+    }
+}
+
+impl Default for ParallelProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressSink for ParallelProgress {
+    fn start(&self, total: usize, message: &str) {
+        ParallelProgress::start(self, total, message);
+    }
+
+    fn start_groups(&self, groups: &[(String, usize)]) {
+        ParallelProgress::start_groups(self, groups);
+    }
+
+    fn item_started(&self, item: &str, message: &str) {
+        self.start_item(item, message);
+    }
+
+    fn item_finished(&self, item: &str, status: ItemStatus, message: &str) {
+        self.finish_item(item, status, message);
+    }
+
+    fn transferred(&self, bytes: u64) {
+        ParallelProgress::transferred(self, bytes);
+    }
+
+    fn notice(&self, notice: Notice, message: &str) {
+        self.suspend(|| match notice {
+            Notice::Info => UI::info(message),
+            Notice::Warning => UI::warning(message),
+        });
+    }
+
+    fn finish(&self, message: &str) {
+        ParallelProgress::finish(self, message);
     }
 }
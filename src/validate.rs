@@ -0,0 +1,823 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde_yaml::{Mapping, Value};
+
+use crate::config::{CodebasesConfig, Config, EnvValue, GitConfig};
+use crate::error::{BasecampError, BasecampResult};
+use crate::git::GitRepo;
+use crate::template::{self, Vars};
+use crate::workspace::Workspace;
+
+/// Settings known in config.yaml
+const CONFIG_KEYS: &[&str] = &[
+    "github_url",
+    "provider",
+    "ssh_key",
+    "cache",
+    "update",
+    "parallel",
+    "backups",
+    "color",
+    "ui",
+    "confirm_delete_mib",
+    "offline",
+    "remote_name",
+    "timeouts",
+    "proxy",
+    "auth",
+    "backend",
+    "tls",
+    "aliases",
+    "default_codebase",
+    "profile",
+    "profiles",
+    "mirrors",
+    "env_files",
+    "hooks_dir",
+    "hooks_mode",
+    "git",
+    "signing",
+];
+
+/// Settings known in the `signing` section of config.yaml
+const SIGNING_KEYS: &[&str] = &["format", "key", "tags"];
+
+/// Settings known in the `cache` section of config.yaml
+const CACHE_KEYS: &[&str] = &["ttl", "prefetch", "remote_ttl"];
+
+/// Settings known in the `timeouts` section of config.yaml
+const TIMEOUT_KEYS: &[&str] = &["connect", "total"];
+
+/// Settings known in the `tls` section of config.yaml
+const TLS_KEYS: &[&str] = &["ca_bundle", "insecure_skip_verify"];
+
+/// Settings known in the `ui` section of config.yaml
+const UI_KEYS: &[&str] = &["ascii", "theme", "messages"];
+
+/// Settings known in the `ui.theme` section of config.yaml
+const THEME_KEYS: &[&str] = &["success", "warning", "error", "spinner"];
+
+/// Settings a profile can override
+const PROFILE_KEYS: &[&str] = &["github_url", "ssh_key", "update"];
+
+/// Settings known in a mirror definition
+const MIRROR_KEYS: &[&str] = &["url", "provider", "token_env"];
+
+/// Sections known in codebases.yaml
+const CODEBASES_KEYS: &[&str] = &["codebases", "tasks", "env", "depends_on", "repositories"];
+
+/// Settings known for a repository under `repositories`
+const REPO_KEYS: &[&str] = &["allow_duplicate", "shared", "upstream", "remotes", "rev", "sparse", "dir", "git", "priority"];
+
+/// Settings known in a task definition
+const TASK_KEYS: &[&str] = &["command", "codebases", "repos"];
+
+/// How serious a configuration problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The configuration can't be used as is
+    Error,
+    /// The configuration works but probably not as intended
+    Warning,
+}
+
+/// A configuration problem with its location and a suggested fix
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub file: PathBuf,
+    /// 1-based line number, when it could be determined
+    pub line: Option<usize>,
+    pub message: String,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    fn new(severity: Severity, file: &Path, line: Option<usize>, message: String) -> Self {
+        Self {
+            severity,
+            file: file.to_path_buf(),
+            line,
+            message,
+            help: None,
+        }
+    }
+
+    fn help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    /// `file:line` of the problem
+    pub fn location(&self) -> String {
+        match self.line {
+            Some(line) => format!("{}:{}", self.file.display(), line),
+            None => self.file.display().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location(), self.message)?;
+        if let Some(help) = &self.help {
+            write!(f, "\n  help: {}", help)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a repository name is valid on GitHub (letters, digits, `-`, `_` and `.`)
+pub fn is_valid_repo_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 100
+        && name != "."
+        && name != ".."
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Whether a codebase name is a relative path that stays inside the workspace
+///
+/// Nested codebases are named `group/codebase`. No part may be empty, `.` or
+/// `..`, or contain `\`, `:` or control characters, and `.basecamp` is taken.
+pub fn is_valid_codebase_name(name: &str) -> bool {
+    name.split('/').all(|part| {
+        !part.is_empty()
+            && part != "."
+            && part != ".."
+            && !part.chars().any(|c| matches!(c, '\\' | ':') || c.is_control())
+    }) && name.split('/').next() != Some(".basecamp")
+}
+
+/// Whether a `dir` setting is a single directory name inside the codebase
+pub fn is_valid_dir_name(dir: &str) -> bool {
+    !dir.is_empty()
+        && dir != "."
+        && dir != ".."
+        && !dir.chars().any(|c| matches!(c, '/' | '\\' | ':') || c.is_control())
+}
+
+/// Reject names in codebases.yaml that would put clones outside the workspace
+///
+/// Runs on every load, so a malicious or mistyped entry fails before anything
+/// is cloned, moved or deleted. `basecamp validate` lists every problem.
+pub fn check_paths(file: &Path, config: &CodebasesConfig) -> BasecampResult<()> {
+    let unsafe_name = |problem: String| {
+        BasecampError::InvalidConfig(format!("{}: {}", file.display(), problem))
+    };
+
+    for (codebase, repos) in &config.codebases {
+        if !is_valid_codebase_name(codebase) {
+            return Err(unsafe_name(format!("'{}' is not a valid codebase name", codebase.escape_debug())));
+        }
+        if let Some(repo) = repos.iter().find(|repo| !is_valid_repo_name(repo)) {
+            return Err(unsafe_name(format!(
+                "'{}' in codebase '{}' is not a valid repository name",
+                repo.escape_debug(),
+                codebase
+            )));
+        }
+    }
+    for (repo, settings) in &config.repositories {
+        if let Some(dir) = settings.dir.as_deref().filter(|dir| !is_valid_dir_name(dir)) {
+            return Err(unsafe_name(format!(
+                "'{}' isn't a usable directory name of '{}'",
+                dir.escape_debug(),
+                repo.escape_debug()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Parse a configuration file, turning YAML errors into a located diagnostic
+///
+/// Used by [`Config::load`] so a broken file points at the offending line.
+pub fn parse<T: DeserializeOwned>(file: &Path, content: &str) -> BasecampResult<T> {
+    serde_yaml::from_str(content)
+        .map_err(|e| BasecampError::InvalidConfig(parse_error(file, &e).to_string()))
+}
+
+/// Validate the configuration files of `workspace` and the user-level defaults
+pub fn validate_workspace(workspace: &Workspace) -> BasecampResult<Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+
+    let config_path = workspace.config_path();
+    if !config_path.exists() {
+        return Err(BasecampError::FileNotFound(config_path));
+    }
+    let content = fs::read_to_string(&config_path)?;
+    diagnostics.extend(validate_config_file(&config_path, &content));
+
+    let mut profiles: Vec<String> = Vec::new();
+    let mut selected = None;
+    let mut parsed = Vec::new();
+    if let Ok(Some(git_config)) = serde_yaml::from_str::<Option<GitConfig>>(&content) {
+        profiles.extend(git_config.profiles.keys().cloned());
+        selected = git_config.profile.clone().map(|name| (name, find_line(&content, &["profile"])));
+        parsed.push((config_path.clone(), content, git_config));
+    }
+
+    if let Some(user_path) = Config::get_user_config_path().filter(|path| path.exists()) {
+        let user_content = fs::read_to_string(&user_path)?;
+        diagnostics.extend(validate_config_file(&user_path, &user_content));
+        if let Ok(Some(git_config)) = serde_yaml::from_str::<Option<GitConfig>>(&user_content) {
+            profiles.extend(git_config.profiles.keys().cloned());
+            parsed.push((user_path, user_content, git_config));
+        }
+    }
+
+    if let Some((name, line)) = selected
+        && !profiles.contains(&name)
+    {
+        diagnostics.push(
+            Diagnostic::new(
+                Severity::Error,
+                &config_path,
+                line,
+                format!("selected profile '{}' is not defined", name),
+            )
+            .help(suggest(&name, profiles.iter().map(String::as_str)).unwrap_or_else(|| {
+                "Define it under 'profiles', or run 'basecamp profile clear'".to_string()
+            })),
+        );
+    }
+
+    let codebases_path = workspace.codebases_path();
+    if codebases_path.exists() {
+        let content = fs::read_to_string(&codebases_path)?;
+        diagnostics.extend(validate_codebases_file(&codebases_path, &content));
+
+        if let Ok(codebases) = serde_yaml::from_str::<CodebasesConfig>(&content) {
+            let names: Vec<&str> = codebases.codebases.keys().map(String::as_str).collect();
+            for (file, content, git_config) in &parsed {
+                diagnostics.extend(validate_codebase_references(file, content, git_config, &names));
+            }
+        }
+    }
+
+    Ok(diagnostics)
+}
+
+/// Check that the aliases and `default_codebase` of a config.yaml name existing codebases
+fn validate_codebase_references(
+    file: &Path,
+    content: &str,
+    git_config: &GitConfig,
+    codebases: &[&str],
+) -> Vec<Diagnostic> {
+    // Groups can be referenced too
+    let mut known: Vec<&str> = codebases.to_vec();
+    for codebase in codebases {
+        known.extend(codebase.match_indices('/').map(|(index, _)| &codebase[..index]));
+    }
+
+    let mut diagnostics = Vec::new();
+    let mut check = |name: &str, path: &[&str], setting: String| {
+        if !known.contains(&name) {
+            diagnostics.push(
+                Diagnostic::new(
+                    Severity::Warning,
+                    file,
+                    find_line(content, path),
+                    format!("{} refers to unknown codebase '{}'", setting, name),
+                )
+                .help(suggest(name, known.iter().copied()).unwrap_or_else(|| {
+                    "Use 'basecamp list' to see the configured codebases".to_string()
+                })),
+            );
+        }
+    };
+
+    for (alias, target) in &git_config.aliases {
+        check(target, &["aliases", alias], format!("alias '{}'", alias));
+    }
+    if let Some(default) = &git_config.default_codebase {
+        let target = git_config.aliases.get(default).unwrap_or(default);
+        check(target, &["default_codebase"], "'default_codebase'".to_string());
+    }
+    diagnostics
+}
+
+/// Validate the content of a config.yaml file
+pub fn validate_config_file(file: &Path, content: &str) -> Vec<Diagnostic> {
+    let mapping = match serde_yaml::from_str::<Option<Mapping>>(content) {
+        Ok(mapping) => mapping.unwrap_or_default(),
+        Err(e) => return vec![parse_error(file, &e)],
+    };
+
+    let mut diagnostics = Vec::new();
+    unknown_keys(file, content, &mapping, &[], CONFIG_KEYS, &mut diagnostics);
+
+    if let Some(Value::Mapping(cache)) = mapping.get("cache") {
+        unknown_keys(file, content, cache, &["cache"], CACHE_KEYS, &mut diagnostics);
+    }
+
+    if let Some(Value::Mapping(timeouts)) = mapping.get("timeouts") {
+        unknown_keys(file, content, timeouts, &["timeouts"], TIMEOUT_KEYS, &mut diagnostics);
+    }
+
+    if let Some(Value::Mapping(signing)) = mapping.get("signing") {
+        unknown_keys(file, content, signing, &["signing"], SIGNING_KEYS, &mut diagnostics);
+    }
+
+    if let Some(Value::Mapping(ui)) = mapping.get("ui") {
+        unknown_keys(file, content, ui, &["ui"], UI_KEYS, &mut diagnostics);
+        if let Some(Value::Mapping(theme)) = ui.get("theme") {
+            unknown_keys(file, content, theme, &["ui", "theme"], THEME_KEYS, &mut diagnostics);
+        }
+    }
+
+    if let Some(Value::Mapping(tls)) = mapping.get("tls") {
+        unknown_keys(file, content, tls, &["tls"], TLS_KEYS, &mut diagnostics);
+        if tls.get("insecure_skip_verify").and_then(Value::as_bool) == Some(true) {
+            diagnostics.push(
+                Diagnostic::new(
+                    Severity::Warning,
+                    file,
+                    find_line(content, &["tls", "insecure_skip_verify"]),
+                    "TLS certificate verification is disabled for every HTTPS remote".to_string(),
+                )
+                .help("Trust your server's CA with 'tls.ca_bundle' instead"),
+            );
+        }
+    }
+
+    if let Some(url) = mapping.get("github_url").and_then(Value::as_str) {
+        check_url(file, content, url, &["github_url"], &mut diagnostics);
+    }
+
+    if let Some(Value::Mapping(profiles)) = mapping.get("profiles") {
+        for (name, profile) in profiles {
+            let (Some(name), Value::Mapping(profile)) = (name.as_str(), profile) else {
+                continue;
+            };
+            let path = ["profiles", name];
+            unknown_keys(file, content, profile, &path, PROFILE_KEYS, &mut diagnostics);
+            if let Some(url) = profile.get("github_url").and_then(Value::as_str) {
+                check_url(file, content, url, &["profiles", name, "github_url"], &mut diagnostics);
+            }
+        }
+    }
+
+    if let Some(Value::Mapping(mirrors)) = mapping.get("mirrors") {
+        for (name, mirror) in mirrors {
+            let (Some(name), Value::Mapping(mirror)) = (name.as_str(), mirror) else {
+                continue;
+            };
+            unknown_keys(file, content, mirror, &["mirrors", name], MIRROR_KEYS, &mut diagnostics);
+            if let Some(url) = mirror.get("url").and_then(Value::as_str) {
+                check_url(file, content, url, &["mirrors", name, "url"], &mut diagnostics);
+            }
+        }
+    }
+
+    // Type errors (e.g. an unknown update strategy) only show up when deserializing
+    if let Err(e) = serde_yaml::from_str::<Option<GitConfig>>(content) {
+        diagnostics.push(parse_error(file, &e));
+    }
+
+    diagnostics.sort_by_key(|d| d.line);
+    diagnostics
+}
+
+/// Validate the content of a codebases.yaml file
+pub fn validate_codebases_file(file: &Path, content: &str) -> Vec<Diagnostic> {
+    let mapping = match serde_yaml::from_str::<Option<Mapping>>(content) {
+        Ok(mapping) => mapping.unwrap_or_default(),
+        Err(e) => return vec![parse_error(file, &e)],
+    };
+
+    let mut diagnostics = Vec::new();
+    unknown_keys(file, content, &mapping, &[], CODEBASES_KEYS, &mut diagnostics);
+
+    if let Some(Value::Mapping(repositories)) = mapping.get("repositories") {
+        for (name, settings) in repositories {
+            if let (Some(name), Value::Mapping(settings)) = (name.as_str(), settings) {
+                unknown_keys(file, content, settings, &["repositories", name], REPO_KEYS, &mut diagnostics);
+            }
+        }
+    }
+
+    if let Some(Value::Mapping(tasks)) = mapping.get("tasks") {
+        for (name, task) in tasks {
+            if let (Some(name), Value::Mapping(task)) = (name.as_str(), task) {
+                unknown_keys(file, content, task, &["tasks", name], TASK_KEYS, &mut diagnostics);
+            }
+        }
+    }
+
+    let config: CodebasesConfig = match serde_yaml::from_str(content) {
+        Ok(config) => config,
+        Err(e) => {
+            diagnostics.push(parse_error(file, &e));
+            return diagnostics;
+        }
+    };
+
+    let mut codebases: Vec<&String> = config.codebases.keys().collect();
+    codebases.sort();
+    let mut all_repos: HashSet<&str> = HashSet::new();
+    let mut listed_in: HashMap<&str, &str> = HashMap::new();
+
+    for codebase in &codebases {
+        let repos = &config.codebases[*codebase];
+        let codebase_path: Vec<&str> =
+            std::iter::once("codebases").chain(codebase.split('/')).collect();
+        let codebase_line = find_line(content, &codebase_path);
+
+        if !is_valid_codebase_name(codebase) {
+            diagnostics.push(
+                Diagnostic::new(
+                    Severity::Error,
+                    file,
+                    codebase_line,
+                    format!("'{}' is not a valid codebase name", codebase.escape_debug()),
+                )
+                .help("Codebase names are directories inside the workspace: no '..', absolute paths, '\\', ':' or control characters"),
+            );
+        }
+
+        if repos.is_empty() {
+            diagnostics.push(
+                Diagnostic::new(
+                    Severity::Warning,
+                    file,
+                    codebase_line,
+                    format!("codebase '{}' has no repositories", codebase),
+                )
+                .help(format!(
+                    "Add repositories with 'basecamp add {} <repo>', or remove the codebase",
+                    codebase
+                )),
+            );
+        }
+
+        let mut seen: Vec<&str> = Vec::new();
+        let mut dirs: HashMap<&str, &str> = HashMap::new();
+        for repo in repos {
+            let occurrence = seen.iter().filter(|r| **r == repo).count();
+            let line = codebase_line.and_then(|start| find_item(content, start, repo, occurrence));
+
+            let dir = config.repositories.get(repo).and_then(|r| r.dir.as_deref()).unwrap_or(repo);
+            if let Some(other) = dirs.insert(dir, repo).filter(|other| *other != repo) {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Error,
+                        file,
+                        line,
+                        format!(
+                            "repositories '{}' and '{}' of codebase '{}' are both cloned into '{}'",
+                            other, repo, codebase, dir
+                        ),
+                    )
+                    .help("Set 'dir' under 'repositories' to clone one of them into another directory"),
+                );
+            }
+
+            if occurrence > 0 {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Error,
+                        file,
+                        line,
+                        format!("repository '{}' is listed twice in codebase '{}'", repo, codebase),
+                    )
+                    .help("Remove the duplicate entry"),
+                );
+            } else if !is_valid_repo_name(repo) {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Error,
+                        file,
+                        line,
+                        format!("'{}' is not a valid repository name", repo),
+                    )
+                    .help("Repository names may only contain letters, digits, '-', '_' and '.'"),
+                );
+            }
+
+            if occurrence == 0
+                && let Some(other) = listed_in.get(repo.as_str())
+                && !config.repositories.get(repo).is_some_and(|r| r.allow_duplicate || r.shared)
+            {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Warning,
+                        file,
+                        line,
+                        format!(
+                            "repository '{}' is also listed in codebase '{}' and will be cloned twice",
+                            repo, other
+                        ),
+                    )
+                    .help(format!(
+                        "Run 'basecamp dedupe {}' to keep it in one codebase or share a single clone, or set 'allow_duplicate: true' for it under 'repositories'",
+                        repo
+                    )),
+                );
+            }
+
+            seen.push(repo);
+            all_repos.insert(repo);
+            listed_in.entry(repo).or_insert(codebase.as_str());
+        }
+    }
+
+    for repo in config.repositories.keys() {
+        if !all_repos.contains(repo.as_str()) {
+            diagnostics.push(
+                Diagnostic::new(
+                    Severity::Warning,
+                    file,
+                    find_line(content, &["repositories", repo]),
+                    format!("settings for unknown repository '{}'", repo),
+                )
+                .help(suggest(repo, all_repos.iter().copied()).unwrap_or_else(|| {
+                    "Add the repository to a codebase, or remove its settings".to_string()
+                })),
+            );
+        }
+    }
+
+    for (repo, settings) in &config.repositories {
+        for name in settings.remotes.keys() {
+            let reserved = name == "upstream" && settings.upstream.is_some();
+            if reserved || !git2::Remote::is_valid_name(name) {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Error,
+                        file,
+                        find_line(content, &["repositories", repo, "remotes", name]),
+                        format!("'{}' can't be used as a remote name of '{}'", name, repo),
+                    )
+                    .help(if reserved {
+                        "The 'upstream' remote is set by the 'upstream' setting; rename this one"
+                    } else {
+                        "Remote names may not contain spaces, '..', '~', '^', ':' or end with '.lock'"
+                    }),
+                );
+            }
+        }
+
+        if let Some(dir) = &settings.dir
+            && !is_valid_dir_name(dir)
+        {
+            diagnostics.push(
+                Diagnostic::new(
+                    Severity::Error,
+                    file,
+                    find_line(content, &["repositories", repo, "dir"]),
+                    format!("'{}' isn't a usable directory name of '{}'", dir, repo),
+                )
+                .help("'dir' is a single directory name inside the codebase, such as 'legacy-api'"),
+            );
+        }
+
+        for path in &settings.sparse {
+            let trimmed = path.trim_matches('/');
+            if trimmed.is_empty() || trimmed.split('/').any(|part| part.is_empty() || part == "." || part == "..") {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Error,
+                        file,
+                        find_line(content, &["repositories", repo, "sparse"]),
+                        format!("'{}' isn't a usable sparse path of '{}'", path, repo),
+                    )
+                    .help("Sparse paths are directories relative to the repository root, such as 'services/payments'"),
+                );
+            }
+        }
+    }
+
+    for (repo, deps) in &config.depends_on {
+        let line = find_line(content, &["depends_on", repo]);
+        for name in std::iter::once(repo).chain(deps) {
+            if !all_repos.contains(name.as_str()) {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Warning,
+                        file,
+                        line,
+                        format!("'depends_on' refers to unknown repository '{}'", name),
+                    )
+                    .help(suggest(name, all_repos.iter().copied()).unwrap_or_else(|| {
+                        "Add the repository to a codebase, or remove it from 'depends_on'".to_string()
+                    })),
+                );
+            }
+        }
+    }
+
+    for (task_name, task) in &config.tasks {
+        for codebase in task.codebases.keys() {
+            let group = format!("{}/", codebase);
+            if !codebases.iter().any(|c| *c == codebase || c.starts_with(&group)) {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Warning,
+                        file,
+                        find_line(content, &["tasks", task_name, "codebases", codebase]),
+                        format!("task '{}' overrides unknown codebase '{}'", task_name, codebase),
+                    )
+                    .help(suggest(codebase, codebases.iter().map(|c| c.as_str())).unwrap_or_else(
+                        || "Remove the override, or add the codebase".to_string(),
+                    )),
+                );
+            }
+        }
+        for repo in task.repos.keys() {
+            if !all_repos.contains(repo.as_str()) {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Warning,
+                        file,
+                        find_line(content, &["tasks", task_name, "repos", repo]),
+                        format!("task '{}' overrides unknown repository '{}'", task_name, repo),
+                    )
+                    .help(suggest(repo, all_repos.iter().copied()).unwrap_or_else(|| {
+                        "Remove the override, or add the repository to a codebase".to_string()
+                    })),
+                );
+            }
+        }
+    }
+
+    for (scope, variables) in &config.env {
+        let group = format!("{}/", scope);
+        if !codebases.iter().any(|c| *c == scope || c.starts_with(&group)) {
+            diagnostics.push(
+                Diagnostic::new(
+                    Severity::Warning,
+                    file,
+                    find_line(content, &["env", scope]),
+                    format!("environment of unknown codebase '{}'", scope),
+                )
+                .help(suggest(scope, codebases.iter().map(|c| c.as_str())).unwrap_or_else(|| {
+                    "Remove the environment, or add the codebase".to_string()
+                })),
+            );
+        }
+
+        for (name, value) in variables {
+            let valid_name = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            let line = find_line(content, &["env", scope, name]);
+            if !valid_name {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Error,
+                        file,
+                        line,
+                        format!("'{}' can't be used as an environment variable name", name),
+                    )
+                    .help("Variable names may only contain letters, digits and '_', and don't start with a digit"),
+                );
+            }
+            if let EnvValue::Value(Value::Mapping(_) | Value::Sequence(_) | Value::Tagged(_)) = value {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Error,
+                        file,
+                        line,
+                        format!("environment variable '{}' of '{}' has no usable value", name, scope),
+                    )
+                    .help("Use a plain value, or '{command: ...}' to read a secret from a command"),
+                );
+            }
+        }
+    }
+
+    diagnostics.sort_by_key(|d| d.line);
+    diagnostics
+}
+
+/// Report keys of `mapping` that aren't in `known`
+fn unknown_keys(
+    file: &Path,
+    content: &str,
+    mapping: &Mapping,
+    path: &[&str],
+    known: &[&str],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for key in mapping.keys() {
+        let Some(key) = key.as_str() else { continue };
+        if known.contains(&key) {
+            continue;
+        }
+
+        let mut key_path = path.to_vec();
+        key_path.push(key);
+        let section = if path.is_empty() {
+            String::new()
+        } else {
+            format!(" in '{}'", path.join("."))
+        };
+
+        diagnostics.push(
+            Diagnostic::new(
+                Severity::Warning,
+                file,
+                find_line(content, &key_path),
+                format!("unknown key '{}'{} is ignored", key, section),
+            )
+            .help(suggest(key, known.iter().copied()).unwrap_or_else(|| {
+                format!("Known keys are: {}", known.join(", "))
+            })),
+        );
+    }
+}
+
+/// Report a URL repositories can't be cloned from
+fn check_url(file: &Path, content: &str, url: &str, path: &[&str], diagnostics: &mut Vec<Diagnostic>) {
+    let expanded = template::expand(url, &Vars::default());
+    let valid = (expanded.starts_with("https://") || expanded.starts_with("git@"))
+        && GitRepo::convert_url(&expanded, true).is_some();
+    if !valid {
+        diagnostics.push(
+            Diagnostic::new(
+                Severity::Error,
+                file,
+                find_line(content, path),
+                format!("'{}' is not a valid repository URL", url),
+            )
+            .help("Use 'https://github.com/<org>' or 'git@github.com:<org>'"),
+        );
+    }
+}
+
+/// Turn a YAML error into a diagnostic
+fn parse_error(file: &Path, error: &serde_yaml::Error) -> Diagnostic {
+    let message = error.to_string();
+    // The location is part of the diagnostic already
+    let message = match message.rsplit_once(" at line ") {
+        Some((message, _)) => message.to_string(),
+        None => message,
+    };
+
+    Diagnostic::new(
+        Severity::Error,
+        file,
+        error.location().map(|location| location.line()),
+        message,
+    )
+    .help("Fix the YAML syntax or the value type at this line")
+}
+
+/// "Did you mean" hint for a misspelled name
+fn suggest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let candidates: Vec<&str> = candidates.into_iter().collect();
+    let matches = similar::get_close_matches(name, &candidates, 1, 0.6);
+    matches.first().map(|m| format!("Did you mean '{}'?", m))
+}
+
+/// Find the line of a nested key, following each segment of `path` in order
+fn find_line(content: &str, path: &[&str]) -> Option<usize> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut start = 0;
+    let mut found = None;
+
+    for segment in path {
+        let index = (start..lines.len()).find(|&i| {
+            let line = lines[i].trim_start().trim_start_matches("- ");
+            let line = line.trim_start_matches(['"', '\'']);
+            line.strip_prefix(*segment)
+                .is_some_and(|rest| rest.trim_start_matches(['"', '\'']).starts_with(':'))
+        })?;
+        found = Some(index + 1);
+        start = index + 1;
+    }
+
+    found
+}
+
+/// Find the line of the `occurrence`-th (0-based) list item `item`, starting at line `start`
+fn find_item(content: &str, start: usize, item: &str, occurrence: usize) -> Option<usize> {
+    content
+        .lines()
+        .enumerate()
+        .skip(start.saturating_sub(1))
+        .flat_map(|(index, line)| {
+            let count = line
+                .match_indices(item)
+                .filter(|(pos, _)| is_word(line, *pos, item.len()))
+                .count();
+            std::iter::repeat_n(index + 1, count)
+        })
+        .nth(occurrence)
+}
+
+/// Whether `line[pos..pos + len]` isn't part of a longer name
+fn is_word(line: &str, pos: usize, len: usize) -> bool {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.');
+    let before = line[..pos].chars().next_back();
+    let after = line[pos + len..].chars().next();
+    !before.is_some_and(is_name_char) && !after.is_some_and(is_name_char)
+}
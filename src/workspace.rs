@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+
+use log::debug;
+
+use crate::config::Config;
+use crate::error::BasecampResult;
+
+/// A BaseCamp workspace: the directory holding `.basecamp` and the codebases
+///
+/// Every path of the workspace is derived from its root, so operations don't
+/// depend on the process's current directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Workspace {
+    root: PathBuf,
+    /// Directory names of repositories cloned under another name (`dir` settings)
+    dirs: BTreeMap<String, String>,
+}
+
+impl Workspace {
+    /// Workspace rooted at `root`, which may also point at its `.basecamp` directory
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        let root = match root.file_name() {
+            Some(name) if name == ".basecamp" => root.parent().map(Path::to_path_buf).unwrap_or_default(),
+            _ => root,
+        };
+        Self {
+            root,
+            dirs: BTreeMap::new(),
+        }
+    }
+
+    /// Clone repositories into the given directory names instead of their own
+    pub fn set_dirs(&mut self, dirs: BTreeMap<String, String>) {
+        self.dirs = dirs;
+    }
+
+    /// The default workspace: the one selected with `--config-dir`, or else the current directory
+    pub fn current() -> Self {
+        Self::new(Config::get_workspace_dir())
+    }
+
+    /// Root of the workspace, empty for the current directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Absolute path to the root of the workspace
+    pub fn absolute_root(&self) -> BasecampResult<PathBuf> {
+        Ok(env::current_dir()?.join(&self.root))
+    }
+
+    /// Path to the .basecamp directory
+    pub fn basecamp_dir(&self) -> PathBuf {
+        self.root.join(".basecamp")
+    }
+
+    /// Path to config.yaml
+    pub fn config_path(&self) -> PathBuf {
+        self.basecamp_dir().join("config.yaml")
+    }
+
+    /// Path to codebases.yaml
+    pub fn codebases_path(&self) -> PathBuf {
+        self.basecamp_dir().join("codebases.yaml")
+    }
+
+    /// Path to the directory of a codebase
+    pub fn codebase_path(&self, codebase: &str) -> PathBuf {
+        self.root.join(codebase)
+    }
+
+    /// Name of the directory a repository is cloned into, its `dir` if set
+    pub fn repo_dir<'a>(&'a self, repo_name: &'a str) -> &'a str {
+        self.dirs.get(repo_name).map_or(repo_name, String::as_str)
+    }
+
+    /// Path to a repository in a codebase
+    pub fn repo_path(&self, codebase: &str, repo_name: &str) -> PathBuf {
+        self.codebase_path(codebase).join(self.repo_dir(repo_name))
+    }
+
+    /// Path of the single clone of a shared repository (`.basecamp/shared/<repo>`)
+    pub fn shared_path(&self, repo_name: &str) -> PathBuf {
+        self.basecamp_dir().join("shared").join(repo_name)
+    }
+
+    /// Directory archived repositories of a codebase are kept in (`.basecamp/archive/<codebase>`)
+    pub fn archive_dir(&self, codebase: &str) -> PathBuf {
+        self.basecamp_dir().join("archive").join(codebase)
+    }
+
+    /// Path of an archived repository
+    pub fn archive_path(&self, codebase: &str, repo_name: &str) -> PathBuf {
+        self.archive_dir(codebase).join(repo_name)
+    }
+
+    /// Directory directories in the way of a codebase's clones are moved to (`.basecamp/conflicts/<codebase>`)
+    pub fn conflicts_dir(&self, codebase: &str) -> PathBuf {
+        self.basecamp_dir().join("conflicts").join(codebase)
+    }
+
+    /// Path a directory in the way of a clone is moved to, named after the repository and the time
+    pub fn conflict_path(&self, codebase: &str, repo_name: &str, moved_at: u64) -> PathBuf {
+        self.conflicts_dir(codebase).join(format!("{}-{}", self.repo_dir(repo_name), moved_at))
+    }
+
+    /// Directory of the operation logs (`.basecamp/logs`)
+    pub fn logs_dir(&self) -> PathBuf {
+        self.basecamp_dir().join("logs")
+    }
+
+    /// Directory of the checkpoints of interrupted runs (`.basecamp/state`)
+    pub fn state_dir(&self) -> PathBuf {
+        self.basecamp_dir().join("state")
+    }
+
+    /// Ensure the .basecamp directory exists
+    pub fn ensure_basecamp_dir(&self) -> BasecampResult<()> {
+        let dir = self.basecamp_dir();
+        if !dir.exists() {
+            debug!("Creating .basecamp directory at {:?}", dir);
+            create_dir_all(&dir)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,462 @@
+use std::fs;
+use std::path::Path;
+
+use log::debug;
+use serde_yaml::{Mapping, Value};
+
+use crate::backup;
+use crate::error::BasecampResult;
+
+/// Write `value` to a YAML file, only touching the lines that change
+///
+/// Comments, blank lines and key order of the existing file are kept. When
+/// the file can't be edited in place (unexpected layout), it is rewritten
+/// from scratch instead. The previous version is kept in the backups.
+pub fn write(path: &Path, value: &Value) -> BasecampResult<()> {
+    let Some(content) = render(path, value)? else {
+        debug!("{:?} is unchanged", path);
+        return Ok(());
+    };
+
+    if path.exists() {
+        backup::keep(path);
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// Content [`write`] would give a YAML file holding `value`, `None` when it already does
+pub fn render(path: &Path, value: &Value) -> BasecampResult<Option<String>> {
+    if path.exists() {
+        let content = fs::read_to_string(path)?;
+        if let Ok(old) = serde_yaml::from_str::<Value>(&content) {
+            if old == *value {
+                return Ok(None);
+            }
+            if let Some(edited) = edit(&content, &old, value) {
+                return Ok(Some(edited));
+            }
+            debug!("Could not edit {:?} in place, rewriting it", path);
+        }
+    }
+
+    Ok(Some(serde_yaml::to_string(value)?))
+}
+
+/// Like [`render`], with the entries below each of the top-level `sections` sorted
+///
+/// Comments move with the entries they describe.
+pub fn render_sorted(path: &Path, value: &Value, sections: &[&str]) -> BasecampResult<Option<String>> {
+    let content = if path.exists() { Some(fs::read_to_string(path)?) } else { None };
+
+    let sorted = content.as_deref().and_then(|content| {
+        let sorted = sort(content, sections)?;
+        let old = serde_yaml::from_str::<Value>(&sorted).ok()?;
+        if old == *value { Some(sorted) } else { edit(&sorted, &old, value) }
+    });
+    let rendered = match sorted {
+        Some(sorted) => sorted,
+        None => {
+            debug!("Could not sort {:?} in place, rewriting it", path);
+            serde_yaml::to_string(value)?
+        }
+    };
+
+    Ok((content.as_deref() != Some(rendered.as_str())).then_some(rendered))
+}
+
+/// Sort the entries below each of the top-level `sections` of `content`
+///
+/// Mapping keys and lists of scalars are sorted at every depth. Returns `None`
+/// when the layout isn't understood.
+pub fn sort(content: &str, sections: &[&str]) -> Option<String> {
+    let Value::Mapping(root) = serde_yaml::from_str::<Value>(content).ok()? else {
+        return None;
+    };
+
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    let indent = (0..lines.len())
+        .find(|&i| is_content(&lines[i]))
+        .map(|i| indent_of(&lines[i]))
+        .unwrap_or(0);
+    let end = lines.len();
+    for entry in mapping_entries(&lines, 0, end, indent).iter().rev() {
+        if !sections.contains(&entry.key.as_str()) {
+            continue;
+        }
+        let key = Value::String(entry.key.clone());
+        let block = sort_entry(&lines, entry, indent, root.get(&key)?)?;
+        lines.splice(entry.start..entry.end, block);
+    }
+
+    let mut sorted = lines.join("\n");
+    sorted.push('\n');
+
+    // Never write a file that doesn't say what it should
+    let mut expected = root;
+    for section in sections {
+        if let Some(value) = expected.get_mut(*section) {
+            *value = sorted_value(value);
+        }
+    }
+    (serde_yaml::from_str::<Value>(&sorted).ok()? == Value::Mapping(expected)).then_some(sorted)
+}
+
+/// Lines of a mapping entry holding `value`, with everything below it sorted
+fn sort_entry(lines: &[String], entry: &Entry, indent: usize, value: &Value) -> Option<Vec<String>> {
+    let body = &lines[entry.start + 1..entry.end];
+    if !entry.inline
+        && let Some(child) = child_indent(body, 0, body.len())
+    {
+        let mut block = vec![lines[entry.start].clone()];
+        block.extend(sort_block(body, child, value)?);
+        return Some(block);
+    }
+
+    // Flow collections are rewritten as blocks, unless already in order
+    let sorted = sorted_value(value);
+    if entry.inline && serde_yaml::to_string(value).ok()? != serde_yaml::to_string(&sorted).ok()? {
+        return render_entry(&Value::String(entry.key.clone()), &sorted, indent);
+    }
+    Some(lines[entry.start..entry.end].to_vec())
+}
+
+/// Sort the block mapping or sequence at `indent` in `lines`, which holds `value`
+fn sort_block(lines: &[String], indent: usize, value: &Value) -> Option<Vec<String>> {
+    let mut items = Vec::new();
+    match value {
+        Value::Mapping(mapping) => {
+            let entries = mapping_entries(lines, 0, lines.len(), indent);
+            if entries.len() != mapping.len() {
+                return None;
+            }
+            for entry in &entries {
+                let value = mapping.get(entry.key.as_str())?;
+                let block = sort_entry(lines, entry, indent, value)?;
+                items.push(Item { start: entry.start, end: entry.end, key: entry.key.clone(), lines: block });
+            }
+        }
+        Value::Sequence(values) if values.iter().all(is_scalar) => {
+            let ranges = sequence_items(lines, 0, lines.len());
+            if ranges.len() != values.len() {
+                return None;
+            }
+            for ((start, end), value) in ranges.into_iter().zip(values) {
+                let lines = lines[start..end].to_vec();
+                items.push(Item { start, end, key: sort_key(value), lines });
+            }
+        }
+        _ => return Some(lines.to_vec()),
+    }
+    Some(reorder(lines, items))
+}
+
+/// An entry or item of a block being sorted, spanning lines `start..end`
+struct Item {
+    start: usize,
+    end: usize,
+    key: String,
+    /// Its lines once sorted itself
+    lines: Vec<String>,
+}
+
+/// Put the items of a block in order, each with the comments right above it
+///
+/// Blank lines, and comments separated from the next item by one, stay in place.
+fn reorder(lines: &[String], mut items: Vec<Item>) -> Vec<String> {
+    let mut separators = Vec::new();
+    let mut gap_start = 0;
+    for item in &mut items {
+        let gap = &lines[gap_start..item.start];
+        let attached = gap.iter().rposition(|line| line.trim().is_empty()).map_or(0, |i| i + 1);
+        separators.push(gap[..attached].to_vec());
+        item.lines.splice(0..0, gap[attached..].iter().cloned());
+        gap_start = item.end;
+    }
+    let tail = &lines[gap_start..];
+
+    items.sort_by(|a, b| a.key.cmp(&b.key));
+    separators
+        .into_iter()
+        .zip(items)
+        .flat_map(|(separator, item)| separator.into_iter().chain(item.lines))
+        .chain(tail.iter().cloned())
+        .collect()
+}
+
+/// `value` with mapping keys and lists of scalars sorted at every depth
+fn sorted_value(value: &Value) -> Value {
+    match value {
+        Value::Mapping(mapping) => {
+            let mut entries: Vec<(&Value, &Value)> = mapping.iter().collect();
+            entries.sort_by_key(|(key, _)| sort_key(key));
+            Value::Mapping(entries.into_iter().map(|(key, value)| (key.clone(), sorted_value(value))).collect())
+        }
+        Value::Sequence(values) if values.iter().all(is_scalar) => {
+            let mut values = values.clone();
+            values.sort_by_key(sort_key);
+            Value::Sequence(values)
+        }
+        other => other.clone(),
+    }
+}
+
+fn sort_key(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => serde_yaml::to_string(other).unwrap_or_default(),
+    }
+}
+
+fn is_scalar(value: &Value) -> bool {
+    !matches!(value, Value::Mapping(_) | Value::Sequence(_) | Value::Tagged(_))
+}
+
+/// Edit `content` (which parses to `old`) so that it parses to `new`
+///
+/// Returns `None` when the edit isn't possible or doesn't produce `new`.
+pub fn edit(content: &str, old: &Value, new: &Value) -> Option<String> {
+    let (Value::Mapping(old), Value::Mapping(new)) = (old, new) else {
+        return None;
+    };
+
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    let indent = (0..lines.len())
+        .find(|&i| is_content(&lines[i]))
+        .map(|i| indent_of(&lines[i]))
+        .unwrap_or(0);
+    let end = lines.len();
+    edit_mapping(&mut lines, 0, end, indent, old, new)?;
+
+    let mut edited = lines.join("\n");
+    edited.push('\n');
+
+    // Never write a file that doesn't say what it should
+    (serde_yaml::from_str::<Value>(&edited).ok()? == Value::Mapping(new.clone())).then_some(edited)
+}
+
+/// A `key: value` entry of a block mapping, spanning lines `start..end`
+struct Entry {
+    key: String,
+    start: usize,
+    end: usize,
+    /// Whether the value is on the key line (scalar or flow collection)
+    inline: bool,
+}
+
+/// Edit the block mapping at `indent` within lines `start..end`
+fn edit_mapping(
+    lines: &mut Vec<String>,
+    start: usize,
+    end: usize,
+    indent: usize,
+    old: &Mapping,
+    new: &Mapping,
+) -> Option<()> {
+    let entries = mapping_entries(lines, start, end, indent);
+    if entries.len() != old.len() {
+        return None;
+    }
+
+    // New keys go after the last entry, so they are added before touching anything above
+    let added: Vec<(&Value, &Value)> = new.iter().filter(|(key, _)| !old.contains_key(*key)).collect();
+    if !added.is_empty() {
+        let at = entries.last().map(|e| e.end).unwrap_or(end);
+        let mut block = Vec::new();
+        for (key, value) in added {
+            block.extend(render_entry(key, value, indent)?);
+        }
+        lines.splice(at..at, block);
+    }
+
+    for entry in entries.iter().rev() {
+        let key = Value::String(entry.key.clone());
+        let old_value = old.get(&key)?;
+
+        let Some(new_value) = new.get(&key) else {
+            lines.drain(entry.start..entry.end);
+            continue;
+        };
+        if old_value == new_value {
+            continue;
+        }
+
+        let nested = !entry.inline && entry.end > entry.start + 1;
+        let edited = match (old_value, new_value) {
+            (Value::Mapping(old), Value::Mapping(new)) if nested => {
+                let child_indent = child_indent(lines, entry.start + 1, entry.end)?;
+                (child_indent > indent)
+                    .then(|| edit_mapping(lines, entry.start + 1, entry.end, child_indent, old, new))
+                    .flatten()
+            }
+            (Value::Sequence(old), Value::Sequence(new)) if nested && !new.is_empty() => {
+                edit_sequence(lines, entry.start + 1, entry.end, old, new)
+            }
+            _ => None,
+        };
+
+        // Replace the whole entry when it can't be edited line by line
+        if edited.is_none() {
+            let block = render_entry(&key, new_value, indent)?;
+            lines.splice(entry.start..entry.end, block);
+        }
+    }
+
+    Some(())
+}
+
+/// Edit a block sequence of scalars within lines `start..end`
+///
+/// Removed items are deleted and new items appended after the last one.
+fn edit_sequence(
+    lines: &mut Vec<String>,
+    start: usize,
+    end: usize,
+    old: &[Value],
+    new: &[Value],
+) -> Option<()> {
+    let items: Vec<(usize, usize)> = sequence_items(lines, start, end);
+    if items.len() != old.len() || items.is_empty() {
+        return None;
+    }
+    let indent = indent_of(&lines[items[0].0]);
+
+    // Keep the items that are still in order, remove the others
+    let mut removed = Vec::new();
+    let mut next = 0;
+    for (index, item) in old.iter().enumerate() {
+        if next < new.len() && *item == new[next] {
+            next += 1;
+        } else {
+            removed.push(index);
+        }
+    }
+
+    let at = items.last()?.1;
+    let mut block = Vec::new();
+    for item in &new[next..] {
+        block.extend(indent_lines(&serde_yaml::to_string(&vec![item]).ok()?, indent));
+    }
+    lines.splice(at..at, block);
+
+    for index in removed.into_iter().rev() {
+        let (item_start, item_end) = items[index];
+        lines.drain(item_start..item_end);
+    }
+
+    Some(())
+}
+
+/// Find the entries of the block mapping at `indent` within lines `start..end`
+fn mapping_entries(lines: &[String], start: usize, end: usize, indent: usize) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = Vec::new();
+
+    for i in start..end {
+        let line = &lines[i];
+        if !is_content(line) || indent_of(line) != indent || line.trim_start().starts_with('-') {
+            continue;
+        }
+        let Some((key, rest)) = split_key(line.trim_start()) else {
+            continue;
+        };
+
+        if let Some(previous) = entries.last_mut() {
+            previous.end = block_end(lines, previous.start, i);
+        }
+        entries.push(Entry {
+            key,
+            start: i,
+            end,
+            inline: !rest.trim().is_empty() && !rest.trim().starts_with('#'),
+        });
+    }
+
+    if let Some(last) = entries.last_mut() {
+        last.end = block_end(lines, last.start, end);
+    }
+    entries
+}
+
+/// Find the `(start, end)` lines of each item of a block sequence
+fn sequence_items(lines: &[String], start: usize, end: usize) -> Vec<(usize, usize)> {
+    let Some(indent) = child_indent(lines, start, end) else {
+        return Vec::new();
+    };
+
+    let mut items: Vec<(usize, usize)> = Vec::new();
+    for i in start..end {
+        let line = &lines[i];
+        if !is_content(line) || indent_of(line) != indent {
+            continue;
+        }
+        if !line.trim_start().starts_with('-') {
+            return Vec::new();
+        }
+        if let Some(previous) = items.last_mut() {
+            previous.1 = block_end(lines, previous.0, i);
+        }
+        items.push((i, end));
+    }
+
+    if let Some(last) = items.last_mut() {
+        last.1 = block_end(lines, last.0, end);
+    }
+    items
+}
+
+/// End of a block starting at `start`, leaving trailing blank lines and
+/// comments (which describe what follows) before `limit` out
+fn block_end(lines: &[String], start: usize, limit: usize) -> usize {
+    let mut end = limit;
+    while end > start + 1 && !is_content(&lines[end - 1]) {
+        end -= 1;
+    }
+    end
+}
+
+/// Indentation of the first content line within `start..end`
+fn child_indent(lines: &[String], start: usize, end: usize) -> Option<usize> {
+    (start..end)
+        .find(|&i| is_content(&lines[i]))
+        .map(|i| indent_of(&lines[i]))
+}
+
+/// Render a `key: value` entry at `indent`
+fn render_entry(key: &Value, value: &Value, indent: usize) -> Option<Vec<String>> {
+    let mut entry = Mapping::new();
+    entry.insert(key.clone(), value.clone());
+    Some(indent_lines(&serde_yaml::to_string(&entry).ok()?, indent))
+}
+
+fn indent_lines(text: &str, indent: usize) -> Vec<String> {
+    text.lines()
+        .map(|line| format!("{}{}", " ".repeat(indent), line))
+        .collect()
+}
+
+/// Split `key: rest` into the unquoted key and the rest of the line
+fn split_key(line: &str) -> Option<(String, &str)> {
+    let (key, rest) = match line.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let close = line[1..].find(quote)? + 1;
+            (&line[1..close], line[close + 1..].strip_prefix(':')?)
+        }
+        _ => {
+            let colon = line
+                .match_indices(':')
+                .map(|(i, _)| i)
+                .find(|&i| line[i + 1..].is_empty() || line[i + 1..].starts_with(' '))?;
+            (line[..colon].trim_end(), &line[colon + 1..])
+        }
+    };
+    Some((key.to_string(), rest))
+}
+
+fn is_content(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty() && !trimmed.starts_with('#')
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
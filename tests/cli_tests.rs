@@ -3,6 +3,8 @@ mod common;
 use assert_cmd::Command;
 use predicates::prelude::*;
 
+use common::commit_empty;
+
 #[test]
 fn test_help_command() {
     let mut cmd = Command::cargo_bin("basecamp").unwrap();
@@ -88,6 +90,7 @@ fn test_init_command_no_config() {
         .arg("--connection-type").arg("https")
         .arg("--repo-type").arg("org")
         .arg("--name").arg("test-org")
+        .env("XDG_CONFIG_HOME", temp_path.join("xdg"))
         .current_dir(&temp_path);
     
     // Attempt to run the command - it should succeed with non-interactive mode
@@ -109,6 +112,130 @@ fn test_init_command_no_config() {
     common::teardown(temp_dir);
 }
 
+#[test]
+fn test_init_providers() {
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let init = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.args(["init", "--non-interactive", "--force", "--repo-type", "org", "--name", "platform"])
+            .args(args)
+            .env("XDG_CONFIG_HOME", temp_path.join("xdg"))
+            .current_dir(&temp_path);
+        cmd
+    };
+    let config = || std::fs::read_to_string(temp_path.join(".basecamp/config.yaml")).unwrap();
+
+    // Public services use their own host
+    init(&["--provider", "bitbucket", "--connection-type", "ssh"]).assert().success();
+    assert!(config().contains("github_url: git@bitbucket.org:platform"));
+    assert!(config().contains("provider: bitbucket"));
+
+    // Self-hosted ones take --host, with or without a scheme
+    init(&["--provider", "gitlab", "--host", "https://gitlab.example.com/", "--connection-type", "https"])
+        .assert()
+        .success();
+    assert!(config().contains("github_url: https://gitlab.example.com/platform"));
+    assert!(config().contains("provider: gitlab"));
+
+    // GitHub Enterprise has no default host
+    init(&["--provider", "github-enterprise", "--connection-type", "https"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("host must be provided"));
+
+    init(&["--provider", "sourceforge", "--connection-type", "https"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid provider"));
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_init_template() {
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let init = |template: &str| {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.args(["init", "--non-interactive", "--force", "--connection-type", "https"])
+            .args(["--repo-type", "org", "--name", "platform", "--template", template])
+            .env("XDG_CONFIG_HOME", temp_path.join("xdg"))
+            .current_dir(&temp_path);
+        cmd
+    };
+    let codebases = || std::fs::read_to_string(temp_path.join(".basecamp/codebases.yaml")).unwrap();
+
+    // A template file is copied as is, comments included
+    let template = temp_path.join("standard.yaml");
+    std::fs::write(&template, "# Platform layout\ncodebases:\n  backend:\n    - api\n    - worker\n").unwrap();
+    init(template.to_str().unwrap())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Seeded 1 codebases"));
+    assert!(codebases().starts_with("# Platform layout"));
+
+    // Names are looked up in ~/.config/basecamp/templates
+    let templates = temp_path.join("xdg/basecamp/templates");
+    std::fs::create_dir_all(&templates).unwrap();
+    std::fs::write(templates.join("web.yaml"), "codebases:\n  frontend:\n    - app\n").unwrap();
+    init("web").assert().success();
+    assert!(codebases().contains("frontend"));
+
+    // Broken templates fail before anything is written
+    std::fs::write(&template, "codebases:\n  backend: api\n").unwrap();
+    init(template.to_str().unwrap()).assert().failure();
+    assert!(codebases().contains("frontend"));
+
+    init("missing")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Template 'missing' not found"));
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_init_without_terminal() {
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    // No --non-interactive: a piped stdin is enough to skip the prompts
+    let init = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.arg("init")
+            .args(args)
+            .env("XDG_CONFIG_HOME", temp_path.join("xdg"))
+            .current_dir(&temp_path)
+            .write_stdin("");
+        cmd
+    };
+
+    // Missing answers are errors instead of questions
+    init(&["--connection-type", "ssh", "--repo-type", "org"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("name must be provided"));
+    assert!(!temp_path.join(".basecamp/config.yaml").exists());
+
+    init(&["--connection-type", "ssh", "--repo-type", "org", "--name", "acme"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Using GitHub URL built from parameters: git@github.com:acme"))
+        .stdout(predicate::str::contains("Set up your codebases now?").not());
+    let config = std::fs::read_to_string(temp_path.join(".basecamp/config.yaml")).unwrap();
+    assert!(config.contains("github_url: git@github.com:acme"));
+    assert!(config.contains("provider: github"));
+    let codebases = std::fs::read_to_string(temp_path.join(".basecamp/codebases.yaml")).unwrap();
+    assert!(codebases.contains("codebases: {}"), "{}", codebases);
+
+    // An existing configuration is kept without --force
+    init(&["--connection-type", "https", "--repo-type", "org", "--name", "other"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Init cancelled"));
+    let config = std::fs::read_to_string(temp_path.join(".basecamp/config.yaml")).unwrap();
+    assert!(config.contains("github_url: git@github.com:acme"));
+
+    common::teardown(temp_dir);
+}
+
 #[test]
 fn test_list_without_config() {
     // Setup
@@ -149,6 +276,3461 @@ fn test_list_with_config() {
         .stdout(predicate::str::contains("frontend"))
         .stdout(predicate::str::contains("backend"));
 
+    // Search repositories across codebases, by substring or regular expression
+    let search = |pattern: &str| {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.args(["list", "--search", pattern]).current_dir(&temp_path);
+        cmd.assert().success()
+    };
+    search("API")
+        .stdout(predicate::str::is_match(r"api-server[^\n]*backend").unwrap())
+        .stdout(predicate::str::contains("web-client").not());
+    search("^(ui|web)-")
+        .stdout(predicate::str::contains("ui-component"))
+        .stdout(predicate::str::contains("web-client"))
+        .stdout(predicate::str::contains("database").not());
+    search("(missing").stdout(predicate::str::contains("No repositories match '(missing'"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_list_urls_of_ssh_hosts() {
+    // Setup - repositories cloned over SSH
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let basecamp_dir = common::create_test_config(&temp_path);
+    std::fs::write(basecamp_dir.join("config.yaml"), "github_url: git@github.com:test-org").unwrap();
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&temp_path);
+        cmd
+    };
+    basecamp()
+        .args(["list", "backend"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("git@github.com:test-org/api-server.git"));
+    basecamp()
+        .args(["list", "backend", "--web-url"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("https://github.com/test-org/api-server"))
+        .stdout(predicate::str::contains("git@").not());
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+// `script` runs the command on a pseudo-terminal, where output is colored by default
+#[cfg(target_os = "linux")]
+#[test]
+fn test_no_color() {
+    if std::process::Command::new("script").arg("--version").output().is_err() {
+        return;
+    }
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+    let basecamp = assert_cmd::cargo::cargo_bin("basecamp");
+    let on_terminal = |args: &str, no_color: Option<&str>| {
+        let mut cmd = std::process::Command::new("script");
+        cmd.args(["-qec", &format!("'{}' {}", basecamp.display(), args), "/dev/null"])
+            .env_remove("NO_COLOR")
+            .current_dir(&temp_path);
+        if let Some(value) = no_color {
+            cmd.env("NO_COLOR", value);
+        }
+        let output = cmd.output().unwrap();
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let output = on_terminal("list", None);
+    assert!(output.contains("api-server") && output.contains('\x1b'), "{}", output);
+
+    // NO_COLOR and --no-color turn the styling off, an empty NO_COLOR doesn't
+    for (args, no_color) in [("list", Some("1")), ("--no-color list", None)] {
+        let output = on_terminal(args, no_color);
+        assert!(output.contains("api-server") && !output.contains('\x1b'), "{}", output);
+    }
+    assert!(on_terminal("list", Some("")).contains('\x1b'));
+
+    // So does output that isn't a terminal
+    Command::cargo_bin("basecamp")
+        .unwrap()
+        .arg("list")
+        .env_remove("NO_COLOR")
+        .current_dir(&temp_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("api-server"))
+        .stdout(predicate::str::contains("\x1b").not());
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_run_task() {
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let basecamp_dir = common::create_test_config(&temp_path);
+
+    // Define a task that fails in a single repository
+    let mut codebases = std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap();
+    codebases.push_str("\ntasks:\n  check:\n    command: echo ok\n    repos:\n      web-client: exit 3\n");
+    std::fs::write(basecamp_dir.join("codebases.yaml"), codebases).unwrap();
+    std::fs::create_dir_all(temp_path.join("frontend/ui-component")).unwrap();
+    std::fs::create_dir_all(temp_path.join("frontend/web-client")).unwrap();
+
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["run", "check", "frontend"]).current_dir(&temp_path);
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("passed"))
+        .stdout(predicate::str::contains("FAILED"))
+        .stdout(predicate::str::contains("✗ 1 passed · 1 failed · "))
+        .stderr(predicate::str::contains("failed in 1 repositories"));
+
+    // A failure only skips the repositories depending on it, not an independent chain
+    let codebases = "codebases:\n  frontend: [ui-component, web-client]\n  backend: [api-server, database, cache]\n\
+        depends_on:\n  web-client: [ui-component]\n  database: [api-server]\n  cache: [database]\n\
+        tasks:\n  check:\n    command: echo ok\n    repos:\n      api-server: exit 3\n";
+    std::fs::write(basecamp_dir.join("codebases.yaml"), codebases).unwrap();
+    for repo in ["backend/api-server", "backend/database", "backend/cache"] {
+        std::fs::create_dir_all(temp_path.join(repo)).unwrap();
+    }
+
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["run", "check"]).current_dir(&temp_path);
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("'api-server' failed"))
+        .stdout(predicate::str::contains("'database' failed"))
+        .stdout(predicate::str::contains("✗ 2 passed · 2 skipped · 1 failed · "))
+        .stderr(predicate::str::contains("failed in 1 repositories"));
+
+    // Unknown tasks are reported clearly
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["run", "deploy"]).current_dir(&temp_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Task 'deploy' not found"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_path_command() {
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+
+    // Exact and partial names resolve to the repository directory
+    for query in ["api-server", "api"] {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.args(["path", query]).current_dir(&temp_path);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::contains("api-server"));
+    }
+
+    // Typos get suggestions
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["path", "databse"]).current_dir(&temp_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Did you mean: database"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_shell_init() {
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+    let shell_init = |shell: &str| {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.args(["shell-init", shell]);
+        cmd.assert().success()
+    };
+
+    // Each shell gets a bcd function around `basecamp path`
+    shell_init("bash")
+        .stdout(predicate::str::contains("bcd() {"))
+        .stdout(predicate::str::contains(r#"dir="$(basecamp path "$@")" && cd "$dir""#));
+    shell_init("fish").stdout(predicate::str::contains("function bcd\n"));
+    shell_init("powershell").stdout(predicate::str::contains("function bcd {"));
+
+    // Sourced by a shell, bcd changes to the repository directory
+    #[cfg(unix)]
+    {
+        std::fs::create_dir_all(temp_path.join("backend/api-server")).unwrap();
+        let bin_dir = assert_cmd::cargo::cargo_bin("basecamp").parent().unwrap().to_path_buf();
+        let path = format!("{}:{}", bin_dir.display(), std::env::var("PATH").unwrap_or_default());
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", r#"eval "$(basecamp shell-init bash)" && ! bcd databse && bcd api && pwd"#])
+            .env("PATH", path)
+            .current_dir(&temp_path);
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::ends_with("backend/api-server\n"))
+            .stderr(predicate::str::contains("Did you mean: database"));
+    }
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_open_in_editor() {
+    use std::os::unix::fs::PermissionsExt;
+
+    // Setup - a stub editor logging its arguments
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+    let editor_log = temp_path.join("editor.log");
+    let editor = temp_path.join("stub-editor");
+    std::fs::write(&editor, format!("#!/bin/sh\necho \"$@\" >> '{}'\n", editor_log.display())).unwrap();
+    std::fs::set_permissions(&editor, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let open = |vars: &[(&str, &str)]| {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.args(["open", "api-server", "--editor"])
+            .env_remove("VISUAL")
+            .env_remove("EDITOR")
+            .envs(vars.iter().copied())
+            .current_dir(&temp_path);
+        cmd.assert()
+    };
+
+    // Repositories have to be installed first
+    open(&[("EDITOR", editor.to_str().unwrap())])
+        .failure()
+        .stderr(predicate::str::contains("Repository 'api-server' is not installed"));
+    assert!(!editor_log.exists());
+
+    // The editor command may carry arguments, and $VISUAL wins over $EDITOR
+    std::fs::create_dir_all(temp_path.join("backend/api-server")).unwrap();
+    open(&[("EDITOR", &format!("{} -w", editor.display()))]).success();
+    let log = std::fs::read_to_string(&editor_log).unwrap();
+    assert_eq!(log, format!("-w {}\n", temp_path.join("backend/api-server").display()));
+
+    open(&[("VISUAL", editor.to_str().unwrap()), ("EDITOR", "false")]).success();
+    let log = std::fs::read_to_string(&editor_log).unwrap();
+    assert_eq!(log.lines().count(), 2, "{}", log);
+
+    // Editors that can't start or that fail are reported
+    open(&[("EDITOR", "/nonexistent/editor")])
+        .failure()
+        .stderr(predicate::str::contains("Failed to start editor '/nonexistent/editor'"));
+    open(&[("EDITOR", "false")])
+        .failure()
+        .stderr(predicate::str::contains("Editor 'false' exited with"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_add_without_config_non_interactive() {
+    // Setup - no configuration, so add needs a GitHub URL
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+
+    // Without a terminal the URL prompt can't be answered
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["add", "frontend", "web-client"])
+        .env_remove("BASECAMP_GITHUB_URL")
+        .current_dir(&temp_path);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--github-url"));
+
+    // Passing the URL as an option works without prompting (offline, so nothing is cloned)
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["add", "frontend", "web-client", "--github-url", "https://github.com/test-org", "--offline"])
+        .current_dir(&temp_path);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No configuration file found"));
+    assert!(!temp_path.join("frontend").exists());
+
+    let config = std::fs::read_to_string(temp_path.join(".basecamp/config.yaml")).unwrap();
+    assert!(config.contains("github_url: https://github.com/test-org"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_install_writes_operation_log() {
+    // Setup - point the GitHub URL at a local directory without repositories
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let basecamp_dir = common::create_test_config(&temp_path);
+    let missing_remote = temp_path.join("missing-remote");
+    std::fs::write(
+        basecamp_dir.join("config.yaml"),
+        format!("github_url: {}", missing_remote.display()),
+    )
+    .unwrap();
+
+    // Run install - every clone fails
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["install", "frontend"]).current_dir(&temp_path);
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains(".basecamp/logs/"));
+
+    // Verify the log records each repository with its URL
+    let logs: Vec<_> = std::fs::read_dir(basecamp_dir.join("logs"))
+        .unwrap()
+        .flatten()
+        .collect();
+    assert_eq!(logs.len(), 1);
+
+    let log = std::fs::read_to_string(logs[0].path()).unwrap();
+    assert!(log.contains("[failed] frontend/web-client"));
+    assert!(log.contains("missing-remote/ui-component.git"));
+    assert!(log.contains("\nerror: "));
+
+    // Installs requested through `serve` are logged as failed too
+    std::fs::remove_dir_all(basecamp_dir.join("logs")).unwrap();
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["serve", "--json-rpc"])
+        .write_stdin(r#"{"jsonrpc":"2.0","id":1,"method":"install","params":{"codebase":"frontend"}}"#)
+        .current_dir(&temp_path);
+    cmd.assert().success();
+    let logs: Vec<_> = std::fs::read_dir(basecamp_dir.join("logs")).unwrap().flatten().collect();
+    let log = std::fs::read_to_string(logs[0].path()).unwrap();
+    assert!(log.contains("error: Command failed: 2 repositories weren't installed"), "{}", log);
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_install_json_report() {
+    // Setup - point the GitHub URL at a local directory without repositories
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let basecamp_dir = common::create_test_config(&temp_path);
+    std::fs::write(
+        basecamp_dir.join("config.yaml"),
+        format!("github_url: {}", temp_path.join("missing-remote").display()),
+    )
+    .unwrap();
+
+    // Run install with a JSON report
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["install", "backend", "--report", "json"])
+        .current_dir(&temp_path);
+
+    let output = cmd.assert().failure().get_output().stdout.clone();
+
+    // Verify stdout only contains the report
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["failed"], 2);
+    assert_eq!(report["cloned"], 0);
+    assert_eq!(report["repositories"][0]["repo"], "api-server");
+    assert_eq!(report["repositories"][0]["result"], "failed");
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_install_fail_fast() {
+    // Setup - point the GitHub URL at a local directory without repositories
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let basecamp_dir = common::create_test_config(&temp_path);
+    std::fs::write(
+        basecamp_dir.join("config.yaml"),
+        format!("github_url: {}", temp_path.join("missing-remote").display()),
+    )
+    .unwrap();
+
+    // With one worker, the first failure stops the remaining clones
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["install", "--fail-fast", "-p", "1", "--report", "json"])
+        .current_dir(&temp_path);
+
+    let output = cmd.assert().failure().get_output().clone();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["failed"], 1);
+    assert_eq!(report["skipped"], 3);
+
+    // Each codebase reports how far it got
+    let messages = String::from_utf8_lossy(&output.stderr);
+    assert!(messages.contains("finished with 1 failed (1/2)"), "{}", messages);
+    assert!(messages.contains("finished (0/2)"), "{}", messages);
+
+    // By default, as with --keep-going, every codebase is still attempted and all failures are reported
+    for args in [&["install", "-p", "1"][..], &["install", "-p", "1", "--keep-going"]] {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.args(args).current_dir(&temp_path);
+
+        // Repository failures exit with their own code, distinct from command errors
+        cmd.assert()
+            .code(2)
+            .stdout(predicate::str::contains("'frontend' finished with 2 failed (2/2)"))
+            .stdout(predicate::str::contains("'backend' finished with 2 failed (2/2)"))
+            .stdout(predicate::str::contains("✗ 4 failed · "))
+            .stderr(predicate::str::contains("4 repositories failed to clone"));
+    }
+
+    // The two policies can't be combined
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["install", "--fail-fast", "--keep-going"]).current_dir(&temp_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_quiet_and_verbose_output() {
+    // Setup - a local "organization" missing one of the configured repositories
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server", "cache"],
+        "",
+        "codebases:\n  backend: [api-server, cache, missing]\n",
+    );
+    let install = |flags: &[&str]| {
+        let output = Command::cargo_bin("basecamp")
+            .unwrap()
+            .args(flags)
+            .arg("install")
+            .current_dir(&workspace)
+            .assert()
+            .failure()
+            .get_output()
+            .clone();
+        std::fs::remove_dir_all(workspace.join("backend")).unwrap();
+        (
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        )
+    };
+
+    // --quiet keeps the errors and the final summary only
+    let (stdout, stderr) = install(&["--quiet"]);
+    assert!(stdout.contains("2 cloned · 1 failed"), "{}", stdout);
+    assert!(!stdout.contains("Installing 3 repositories"), "{}", stdout);
+    assert!(!stdout.contains("Cloned 'cache'"), "{}", stdout);
+    assert!(!stdout.contains("Full details were written"), "{}", stdout);
+    assert!(stderr.contains("Failed to clone repository 'missing'"), "{}", stderr);
+
+    let (stdout, _) = install(&[]);
+    assert!(stdout.contains("Installing 3 repositories"), "{}", stdout);
+    assert!(stdout.contains("Cloned 'cache'"), "{}", stdout);
+    assert!(!stdout.contains("Install progress saved"), "{}", stdout);
+
+    // --verbose adds the details
+    let (stdout, _) = install(&["--verbose"]);
+    assert!(stdout.contains("Install progress saved"), "{}", stdout);
+
+    // Results are still shown in quiet mode
+    Command::cargo_bin("basecamp")
+        .unwrap()
+        .args(["--quiet", "list"])
+        .current_dir(&workspace)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("api-server"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_user_config_defaults() {
+    // Setup - a user-level config with a profile and a default
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+    let user_dir = temp_path.join("xdg").join("basecamp");
+    std::fs::create_dir_all(&user_dir).unwrap();
+    std::fs::write(
+        user_dir.join("config.yaml"),
+        "parallel: 2\nprofiles:\n  oss:\n    github_url: https://github.com/oss-org\n",
+    )
+    .unwrap();
+
+    // The user-level profile is available in the project
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["profile", "use", "oss"])
+        .env("XDG_CONFIG_HOME", temp_path.join("xdg"))
+        .current_dir(&temp_path);
+    cmd.assert().success();
+
+    // Saving the project config doesn't copy the user-level defaults
+    let content = std::fs::read_to_string(temp_path.join(".basecamp/config.yaml")).unwrap();
+    assert!(content.contains("profile: oss"));
+    assert!(!content.contains("parallel"));
+    assert!(!content.contains("oss-org"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_config_dir_option() {
+    // Setup - run from a directory outside of the workspace
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let workspace = temp_path.join("workspace");
+    let elsewhere = temp_path.join("elsewhere");
+    std::fs::create_dir_all(&elsewhere).unwrap();
+    common::create_test_config(&workspace);
+
+    // Paths resolve against the workspace, not the current directory
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.arg("--config-dir")
+        .arg(&workspace)
+        .args(["path", "api-server"])
+        .current_dir(&elsewhere);
+    cmd.assert().success().stdout(predicate::str::contains(
+        workspace.join("backend").join("api-server").display().to_string(),
+    ));
+
+    // Missing directories are rejected
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.arg("--config-dir")
+        .arg(temp_path.join("missing"))
+        .arg("list")
+        .current_dir(&elsewhere);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("does not exist"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_workspaces_registry() {
+    // Setup - a workspace and an unrelated directory
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let workspace = temp_path.join("platform");
+    let elsewhere = temp_path.join("elsewhere");
+    std::fs::create_dir_all(&elsewhere).unwrap();
+    common::create_test_config(&workspace);
+    let basecamp = |args: &[&str], dir: &std::path::Path| {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.args(args)
+            .env("XDG_CONFIG_HOME", temp_path.join("xdg"))
+            .current_dir(dir);
+        cmd.assert()
+    };
+
+    // Register and select the workspace
+    basecamp(&["workspaces", "add"], &workspace).success();
+    basecamp(&["workspaces", "switch", "platform"], &elsewhere).success();
+    basecamp(&["workspaces", "list"], &elsewhere)
+        .success()
+        .stdout(predicate::str::contains("platform"));
+
+    // Commands run outside of any workspace use the selected one
+    basecamp(&["path", "api-server"], &elsewhere)
+        .success()
+        .stdout(predicate::str::contains("api-server"));
+
+    // Deleted workspaces are pruned
+    std::fs::remove_dir_all(&workspace).unwrap();
+    basecamp(&["workspaces", "prune"], &elsewhere)
+        .success()
+        .stdout(predicate::str::contains("Forgot 1 missing workspaces"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_validate_sort() {
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let basecamp_dir = common::create_test_config(&temp_path);
+    std::fs::write(
+        basecamp_dir.join("codebases.yaml"),
+        "codebases:\n  frontend: [web-client, ui-component]\n  backend: [database, api-server]\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["validate", "--sort"]).current_dir(&temp_path);
+    cmd.assert().success();
+
+    // Codebases and repositories are written in name order
+    let content = std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap();
+    assert_eq!(
+        content,
+        "codebases:\n  backend:\n  - api-server\n  - database\n  frontend:\n  - ui-component\n  - web-client\n"
+    );
+
+    // Sorting again leaves the file alone
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["validate", "--sort"]).current_dir(&temp_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("already sorted"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_validate_reports_problems() {
+    // Setup - a misspelled key and a repository listed twice
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let basecamp_dir = common::create_test_config(&temp_path);
+    std::fs::write(
+        basecamp_dir.join("config.yaml"),
+        "github_url: https://github.com/test-org\nparalel: 8\n",
+    )
+    .unwrap();
+    std::fs::write(
+        basecamp_dir.join("codebases.yaml"),
+        "codebases:\n  frontend:\n    - web-client\n    - web-client\n",
+    )
+    .unwrap();
+
+    // Each problem is reported at its line with a fix, and errors fail the command
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.arg("validate").current_dir(&temp_path);
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("config.yaml:2: unknown key 'paralel' is ignored"))
+        .stdout(predicate::str::contains("help: Did you mean 'parallel'?"))
+        .stderr(predicate::str::contains("codebases.yaml:4: repository 'web-client' is listed twice in codebase 'frontend'"))
+        .stderr(predicate::str::contains("help: Remove the duplicate entry"))
+        .stderr(predicate::str::contains("Configuration has 1 errors and 1 warnings"));
+
+    // Warnings alone still pass
+    std::fs::write(basecamp_dir.join("codebases.yaml"), "codebases:\n  frontend: [web-client]\n").unwrap();
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.arg("validate").current_dir(&temp_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Configuration is usable, with 1 warnings"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_add_from_stdin() {
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+
+    // `gh repo list` style lines are reduced to repository names, without cloning them
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["add", "tools", "--from-file", "-", "--offline"])
+        .write_stdin("test-org/cli\tCommand line\tpublic\n# skipped\n\ntest-org/docs.git\n")
+        .current_dir(&temp_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Added repositories [cli, docs] to codebase 'tools'"));
+    assert!(!temp_path.join("tools").exists());
+
+    // Invalid names are reported with their line
+    let list = temp_path.join("repos.txt");
+    std::fs::write(&list, "cli\nbad:name\n").unwrap();
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["add", "tools", "--from-file"])
+        .arg(&list)
+        .current_dir(&temp_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("repos.txt:2: 'bad:name' is not a valid repository name"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_dedupe_keep_in() {
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let basecamp_dir = common::create_test_config(&temp_path);
+    std::fs::write(
+        basecamp_dir.join("codebases.yaml"),
+        "codebases:\n  frontend:\n    - web-client\n    - shared-ui\n  mobile:\n    - app\n    - shared-ui\n",
+    )
+    .unwrap();
+
+    // Validation warns about the duplicate
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.arg("validate").current_dir(&temp_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("also listed in codebase 'frontend'"));
+
+    // Keeping it in one codebase removes the other entry
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["dedupe", "--keep-in", "mobile"]).current_dir(&temp_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Resolved 1 of 1 duplicated repositories"));
+
+    let content = std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap();
+    assert_eq!(
+        content,
+        "codebases:\n  frontend:\n    - web-client\n  mobile:\n    - app\n    - shared-ui\n"
+    );
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_install_shared_repository() {
+    // Setup - a local "organization" with one repository
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let org = temp_path.join("org");
+    git2::Repository::init_bare(org.join("shared-ui.git")).unwrap();
+
+    let workspace = temp_path.join("workspace");
+    let basecamp_dir = common::create_test_config(&workspace);
+    std::fs::write(
+        basecamp_dir.join("config.yaml"),
+        format!("github_url: {}", org.display()),
+    )
+    .unwrap();
+    std::fs::write(
+        basecamp_dir.join("codebases.yaml"),
+        "codebases:\n  frontend: [shared-ui]\n  mobile: [shared-ui]\nrepositories:\n  shared-ui:\n    shared: true\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["install", "-p", "2"]).current_dir(&workspace);
+    cmd.assert().success();
+
+    // One clone, linked into both codebases
+    assert!(basecamp_dir.join("shared/shared-ui/.git").is_dir());
+    for codebase in ["frontend", "mobile"] {
+        let link = workspace.join(codebase).join("shared-ui");
+        assert!(link.symlink_metadata().unwrap().file_type().is_symlink());
+        assert!(link.join(".git").is_dir());
+    }
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_outdated() {
+    // Setup - a local "organization" with one repository
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (org, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server"],
+        "",
+        "codebases:\n  backend: [api-server]\n",
+    );
+    let origin = git2::Repository::open(org.join("api-server.git")).unwrap();
+
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["install", "backend"]).current_dir(&workspace);
+    cmd.assert().success();
+
+    // Freshly cloned
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["outdated", "--fetch"]).current_dir(&workspace);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("up to date"));
+
+    // The remote moves ahead
+    commit_empty(&origin, "Second commit");
+    commit_empty(&origin, "Third commit");
+
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["outdated", "--fetch"]).current_dir(&workspace);
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("api-server"))
+        .stderr(predicate::str::contains("1 of 1 installed repositories are behind"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_status_fetch() {
+    // Setup - a workspace with one cloned repository
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (org, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server"],
+        "cache:\n  ttl: 0",
+        "codebases:\n  backend: [api-server]\n",
+    );
+    let origin = git2::Repository::open(org.join("api-server.git")).unwrap();
+
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["install"]).current_dir(&workspace);
+    cmd.assert().success();
+
+    commit_empty(&origin, "Second commit");
+
+    // Without fetching, the new commit isn't known yet
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["status", "--offline"]).current_dir(&workspace);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("↑0 ↓0"));
+
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["status", "--fetch"]).current_dir(&workspace);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("↑0 ↓1"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_offline_mode() {
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+
+    // install only reports what it would clone
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["install", "frontend", "--offline"]).current_dir(&temp_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("would clone 2 repositories"));
+    assert!(!temp_path.join("frontend").exists());
+
+    // Commands that can't work without the network fail instead of hanging
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["fetch"])
+        .env("BASECAMP_OFFLINE", "1")
+        .current_dir(&temp_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Offline mode is enabled"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_install_with_instead_of() {
+    // Setup - the configured host is rewritten to a local directory by the git config
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let org = temp_path.join("org");
+    let origin = git2::Repository::init(org.join("api-server.git")).unwrap();
+    commit_empty(&origin, "Initial commit");
+
+    let home = temp_path.join("home");
+    std::fs::create_dir_all(&home).unwrap();
+    std::fs::write(
+        home.join(".gitconfig"),
+        format!(
+            "[url \"{}/\"]\n\tinsteadOf = https://git.example.invalid/org/\n",
+            org.display()
+        ),
+    )
+    .unwrap();
+
+    let workspace = temp_path.join("workspace");
+    let basecamp_dir = common::create_test_config(&workspace);
+    std::fs::write(
+        basecamp_dir.join("config.yaml"),
+        "github_url: https://git.example.invalid/org",
+    )
+    .unwrap();
+    std::fs::write(
+        basecamp_dir.join("codebases.yaml"),
+        "codebases:\n  backend: [api-server]\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["install"]).env("HOME", &home).current_dir(&workspace);
+    cmd.assert().success();
+
+    // Origin keeps the configured URL, as with plain git
+    let clone = git2::Repository::open(workspace.join("backend/api-server")).unwrap();
+    let config = clone.config().unwrap().open_level(git2::ConfigLevel::Local).unwrap();
+    assert_eq!(
+        config.get_string("remote.origin.url").unwrap(),
+        "https://git.example.invalid/org/api-server.git"
+    );
+
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["verify"]).env("HOME", &home).current_dir(&workspace);
+    cmd.assert().success();
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_serve_json_rpc() {
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+
+    let requests = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"list","params":{"codebase":"backend"}}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"remove","params":{"codebase":"frontend"}}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"list"}"#,
+        r#"{"jsonrpc":"2.0","id":4,"method":"frobnicate"}"#,
+        "not json",
+    ];
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["serve", "--json-rpc"])
+        .write_stdin(requests.join("\n"))
+        .current_dir(&temp_path);
+    let output = cmd.assert().success().get_output().stdout.clone();
+
+    // Responses come in order, interleaved with event notifications
+    let messages: Vec<serde_json::Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let responses: Vec<&serde_json::Value> = messages.iter().filter(|m| m.get("id").is_some()).collect();
+    assert_eq!(responses.len(), 5);
+
+    let repos = &responses[0]["result"][0]["repositories"];
+    assert_eq!(repos[0]["name"], "api-server");
+    assert_eq!(repos[0]["installed"], false);
+
+    // Removal doesn't prompt and is reported as an event
+    assert_eq!(responses[1]["result"]["codebase"], "frontend");
+    assert!(messages.iter().any(|m| m["method"] == "event" && m["params"]["event"] == "config_saved"));
+    assert_eq!(responses[2]["result"].as_array().unwrap().len(), 1);
+
+    assert_eq!(responses[3]["error"]["code"], -32601);
+    assert_eq!(responses[4]["error"]["code"], -32700);
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_serve_socket_shutdown() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::time::{Duration, Instant};
+
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+    let socket = temp_path.join("basecamp.sock");
+
+    let mut server = std::process::Command::new(assert_cmd::cargo::cargo_bin("basecamp"))
+        .args(["serve", "--json-rpc", "--socket"])
+        .arg(&socket)
+        .current_dir(&temp_path)
+        .spawn()
+        .unwrap();
+    let started = Instant::now();
+    let mut stream = loop {
+        match UnixStream::connect(&socket) {
+            Ok(stream) => break stream,
+            Err(_) if started.elapsed() < Duration::from_secs(10) => std::thread::sleep(Duration::from_millis(50)),
+            Err(e) => panic!("server didn't start listening: {}", e),
+        }
+    };
+
+    // The request is answered before the server stops
+    writeln!(stream, r#"{{"jsonrpc":"2.0","id":1,"method":"shutdown"}}"#).unwrap();
+    let mut response = String::new();
+    BufReader::new(&stream).read_line(&mut response).unwrap();
+    let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["id"], 1);
+    assert!(response["result"].is_null());
+
+    // and the socket file goes with it
+    assert!(server.wait().unwrap().success());
+    assert!(!socket.exists());
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_watch_once() {
+    // Setup - a workspace with one cloned repository
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server"],
+        "",
+        "codebases:\n  backend: [api-server]\n",
+    );
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install", "backend"]).assert().success();
+    std::fs::remove_file(workspace.join(".basecamp/state.json")).unwrap();
+
+    // Nothing is behind, so there is nothing to notify about
+    basecamp().args(["watch", "--once", "--notify"]).assert().success();
+    let state = std::fs::read_to_string(workspace.join(".basecamp/state.json")).unwrap();
+    assert!(state.contains("api-server"));
+
+    // A single refresh reports its failure
+    basecamp()
+        .args(["watch", "--once", "--offline"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("offline"));
+
+    // A running watch reports the failure and goes on with the next cycle
+    let mut watch = std::process::Command::new(assert_cmd::cargo::cargo_bin("basecamp"))
+        .args(["watch", "--interval", "1", "--offline"])
+        .current_dir(&workspace)
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(2500));
+    assert!(watch.try_wait().unwrap().is_none(), "watch stopped after a failed refresh");
+    watch.kill().unwrap();
+    let output = watch.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.matches("Refresh failed, trying again in 1s").count() >= 2, "{}", stdout);
+
+    // Refreshing without a pause is refused
+    basecamp()
+        .args(["watch", "--interval", "0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--interval"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_workspace_tmux() {
+    use std::os::unix::fs::PermissionsExt;
+
+    // Setup - two cloned repositories, and a PATH with a stub tmux logging its arguments
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+    for repo in ["ui-component", "web-client"] {
+        std::fs::create_dir_all(temp_path.join("frontend").join(repo)).unwrap();
+    }
+    let empty_bin = temp_path.join("empty-bin");
+    std::fs::create_dir_all(&empty_bin).unwrap();
+    let stub_bin = temp_path.join("stub-bin");
+    std::fs::create_dir_all(&stub_bin).unwrap();
+    let tmux_log = temp_path.join("tmux.log");
+    let tmux = stub_bin.join("tmux");
+    std::fs::write(&tmux, format!("#!/bin/sh\necho \"$@\" >> '{}'\n", tmux_log.display())).unwrap();
+    std::fs::set_permissions(&tmux, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    // The tmuxinator project has a window per repository
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["workspace", "tmux", "frontend"]).current_dir(&temp_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("name: frontend"))
+        .stdout(predicate::str::contains("- ui-component: cd ui-component\n- web-client: cd web-client"));
+
+    // Without tmux installed the session can't be started
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["workspace", "tmux", "frontend", "--launch"])
+        .env("PATH", &empty_bin)
+        .current_dir(&temp_path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to run tmux (is it installed?)"));
+
+    // A detached session opens with the first repository, then a window for each other one
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["workspace", "tmux", "frontend", "--launch"])
+        .env("PATH", &stub_bin)
+        .current_dir(&temp_path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Started tmux session 'frontend' with 2 windows"));
+    let root = temp_path.canonicalize().unwrap().join("frontend");
+    assert_eq!(
+        std::fs::read_to_string(&tmux_log).unwrap(),
+        format!(
+            "new-session -d -s frontend -n ui-component -c {}\nnew-window -t frontend -n web-client -c {}\n",
+            root.join("ui-component").display(),
+            root.join("web-client").display()
+        )
+    );
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_manpages() {
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let dir = temp_path.join("man");
+
+    // No workspace is needed
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.arg("manpages").arg(&dir).current_dir(&temp_path);
+    cmd.assert().success();
+
+    assert!(dir.join("basecamp.1").exists());
+    assert!(dir.join("basecamp-workspaces-add.1").exists());
+    let page = std::fs::read_to_string(dir.join("basecamp-install.1")).unwrap();
+    assert!(page.contains(".SH EXAMPLES\n.nf\n  # Clone every repository of every codebase\n"));
+
+    // The same examples end `--help`
+    let mut cmd = Command::cargo_bin("basecamp").unwrap();
+    cmd.args(["install", "--help"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Examples:\n  # Clone every repository of every codebase"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_dynamic_completion() {
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+
+    let complete = |words: &[&str]| {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.args(["__complete", "--"]).args(words).current_dir(&temp_path);
+        let output = cmd.assert().success().get_output().stdout.clone();
+        String::from_utf8(output).unwrap()
+    };
+
+    // Subcommands, then names from the live configuration
+    assert_eq!(complete(&["ins"]), "install\n");
+    assert_eq!(complete(&["install", ""]), "backend\nfrontend\n");
+    assert_eq!(complete(&["remove", "frontend", "ui-component", ""]), "web-client\n");
+    assert_eq!(complete(&["list", "--st"]), "--status\n");
+    assert_eq!(complete(&["migrate-remotes", "--to", ""]), "ssh\nhttps\n");
+
+    // File arguments are left to the shell
+    assert_eq!(complete(&["add", "tools", "--from-file", ""]), "");
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_fake_git_mode() {
+    // Setup - the configured host doesn't exist, nothing may reach it
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.env("BASECAMP_GIT_MODE", "fake").current_dir(&temp_path);
+        cmd
+    };
+
+    basecamp().args(["install", "backend"]).assert().success();
+    let clone = git2::Repository::open(temp_path.join("backend/api-server")).unwrap();
+    assert_eq!(
+        clone.find_remote("origin").unwrap().url(),
+        Some("https://github.com/test-org/api-server.git")
+    );
+
+    // Simulated failures are left out by add, unless kept
+    basecamp()
+        .args(["add", "backend", "cache", "queue"])
+        .env("BASECAMP_FAKE_FAIL", "queue")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Left failed repositories [queue] out of codebase 'backend'"));
+    let codebases = std::fs::read_to_string(temp_path.join(".basecamp/codebases.yaml")).unwrap();
+    assert!(codebases.contains("cache") && !codebases.contains("queue"));
+
+    basecamp()
+        .args(["add", "backend", "queue", "--keep-failed"])
+        .env("BASECAMP_FAKE_FAIL", "queue")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Kept the failed repositories in codebase 'backend'"));
+    let codebases = std::fs::read_to_string(temp_path.join(".basecamp/codebases.yaml")).unwrap();
+    assert!(codebases.contains("queue"));
+
+    // Stub clones are clean and up to date, so they can be updated and removed without --force
+    basecamp().args(["install", "backend", "--update"]).assert().success();
+    basecamp().args(["remove", "backend", "cache", "--yes"]).assert().success();
+    assert!(!temp_path.join("backend/cache").exists());
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_import() {
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let import = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.arg("import").args(args).current_dir(&temp_path);
+        cmd
+    };
+    let codebases = || std::fs::read_to_string(temp_path.join(".basecamp/codebases.yaml")).unwrap();
+
+    // meta: parent directories become codebases, github_url comes from the URLs
+    std::fs::write(
+        temp_path.join(".meta"),
+        r#"{"projects": {"services/api": "git@github.com:acme/api.git", "web": "git@github.com:acme/web-app.git"}}"#,
+    )
+    .unwrap();
+    import(&[".meta", "--dry-run"]).assert().success().stdout(predicate::str::contains("web-app"));
+    assert!(!temp_path.join(".basecamp/codebases.yaml").exists());
+
+    import(&[".meta"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Imported 2 repositories into 2 codebases"));
+    let config = std::fs::read_to_string(temp_path.join(".basecamp/config.yaml")).unwrap();
+    assert!(config.contains("github_url: git@github.com:acme"));
+    assert!(codebases().contains("services:\n  - api"));
+    assert!(codebases().contains("imported:\n  - web-app"));
+
+    // repo manifest: project URLs are built from their remote
+    std::fs::write(
+        temp_path.join("default.xml"),
+        r#"<manifest>
+  <remote name="origin" fetch="https://github.com/acme"/>
+  <remote name="other" fetch="https://gitlab.com/elsewhere"/>
+  <default remote="origin" revision="main"/>
+  <project name="build-tools" path="tools/build"/>
+  <project name="vendored" path="tools/vendored" remote="other"/>
+</manifest>"#,
+    )
+    .unwrap();
+    import(&["default.xml", "--codebase", "platform"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("outside git@github.com:acme"));
+    assert!(codebases().contains("platform:\n  - build-tools\n  - vendored"));
+
+    // vcstool: other version control systems are skipped
+    std::fs::write(
+        temp_path.join("deps.txt"),
+        "repositories:\n  libs/core:\n    type: git\n    url: https://github.com/acme/core.git\n  libs/old:\n    type: svn\n    url: https://svn.example.com/old\n",
+    )
+    .unwrap();
+    import(&["deps.txt"]).assert().failure().stderr(predicate::str::contains("--format"));
+    import(&["deps.txt", "--format", "vcstool"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("svn repositories aren't supported"));
+    assert!(codebases().contains("core"));
+    assert!(!codebases().contains("old"));
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_export() {
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+    let export = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.arg("export").args(args).current_dir(&temp_path);
+        cmd
+    };
+
+    export(&["backend", "--format", "urls"])
+        .assert()
+        .success()
+        .stdout("https://github.com/test-org/api-server.git\nhttps://github.com/test-org/database.git\n");
+
+    export(&["--format", "script"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "[ -d 'frontend/web-client' ] || git clone 'https://github.com/test-org/web-client.git' 'frontend/web-client'",
+        ));
+
+    export(&["--format", "vcstool"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("  backend/database:\n    type: git\n    url: https://github.com/test-org/database.git"));
+
+    // A meta file imports back into the same codebases
+    export(&["--format", "meta", "-o", ".meta"]).assert().success();
+    let copy = temp_path.join("copy");
+    std::fs::create_dir(&copy).unwrap();
+    let mut import = Command::cargo_bin("basecamp").unwrap();
+    import.args(["import", "../.meta"]).current_dir(&copy).assert().success();
+    let codebases = std::fs::read_to_string(copy.join(".basecamp/codebases.yaml")).unwrap();
+    assert!(codebases.contains("backend:\n  - api-server\n  - database"));
+    assert!(codebases.contains("frontend:\n  - ui-component\n  - web-client"));
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_report() {
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+
+    // A dirty clone whose last commit is from 2023-11-14
+    let mut options = git2::RepositoryInitOptions::new();
+    options.initial_head("main");
+    let repo = git2::Repository::init_opts(temp_path.join("backend/api-server"), &options).unwrap();
+    let signature = git2::Signature::new("Test", "test@example.com", &git2::Time::new(1_700_000_000, 0)).unwrap();
+    let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap()).unwrap();
+    repo.commit(Some("HEAD"), &signature, &signature, "Initial commit", &tree, &[])
+        .unwrap();
+    std::fs::write(temp_path.join("backend/api-server/notes.txt"), "wip").unwrap();
+
+    let report = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.arg("report").args(args).current_dir(&temp_path);
+        cmd
+    };
+
+    report(&["backend"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("## backend"))
+        .stdout(predicate::str::contains(
+            "| api-server | https://github.com/test-org/api-server.git | main | 2023-11-14 | dirty |",
+        ))
+        .stdout(predicate::str::contains("| database | https://github.com/test-org/database.git | - | - | not cloned |"))
+        .stdout(predicate::str::contains("## frontend").not());
+
+    // The format follows the extension of the output file
+    report(&["--output", "report.html"]).assert().success();
+    let html = std::fs::read_to_string(temp_path.join("report.html")).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<h2>frontend</h2>"));
+    assert!(html.contains("<td>api-server</td><td>https://github.com/test-org/api-server.git</td><td>main</td><td>2023-11-14</td><td>dirty</td>"));
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_stats() {
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+
+    // Three commits by two authors, the first one before --since
+    let repo = git2::Repository::init(temp_path.join("backend/api-server")).unwrap();
+    let commits = [
+        ("alice@example.com", 1_600_000_000, "one\n"),
+        ("Alice@example.com", 1_700_000_000, "one\ntwo\nthree\n"),
+        ("bob@example.com", 1_700_000_100, "three\n"),
+    ];
+    for (email, time, content) in commits {
+        std::fs::write(temp_path.join("backend/api-server/file.txt"), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new("file.txt")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        index.write().unwrap();
+        let signature = git2::Signature::new("Dev", email, &git2::Time::new(time, 0)).unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, "Change", &tree, &parents)
+            .unwrap();
+    }
+
+    let output = Command::cargo_bin("basecamp")
+        .unwrap()
+        .args(["stats", "--since", "2023-01-01", "--report", "json"])
+        .current_dir(&temp_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let api = &stats["repositories"][0];
+    assert_eq!(api["repo"], "api-server");
+    assert_eq!(api["commits"], 2);
+    assert_eq!(api["authors"], 2);
+    assert_eq!(api["insertions"], 2);
+    assert_eq!(api["deletions"], 2);
+    assert_eq!(stats["codebases"][0]["codebase"], "backend");
+    assert_eq!(stats["total"]["commits"], 2);
+
+    Command::cargo_bin("basecamp")
+        .unwrap()
+        .args(["stats", "--since", "last-week"])
+        .current_dir(&temp_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid --since"));
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_status_remote_columns() {
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        for variable in ["BASECAMP_TOKEN", "GITHUB_TOKEN", "GH_TOKEN", "GITLAB_TOKEN"] {
+            cmd.env_remove(variable);
+        }
+        cmd.current_dir(&temp_path);
+        cmd
+    };
+
+    // Without a token the columns are left out
+    basecamp()
+        .args(["status", "--remote"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("to show CI states and pull requests"))
+        .stdout(predicate::str::contains("PRs").not());
+
+    // Fresh answers of the host are read from the state cache
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    std::fs::write(
+        temp_path.join(".basecamp/state.json"),
+        format!(
+            r#"{{"repos": {{}}, "remote": {{
+                "api-server": {{"default_branch": "main", "ci": "failure", "open_pull_requests": 3, "checked_at": {now}}},
+                "database": {{"default_branch": "main", "ci": null, "open_pull_requests": 0, "checked_at": {now}}},
+                "ui-component": {{"default_branch": "main", "ci": "success", "open_pull_requests": 1, "checked_at": {now}}},
+                "web-client": {{"default_branch": "main", "ci": "pending", "open_pull_requests": 0, "checked_at": {now}}}
+            }}}}"#
+        ),
+    )
+    .unwrap();
+    basecamp()
+        .args(["list", "backend", "--status", "--remote"])
+        .env("BASECAMP_TOKEN", "cached")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"api-server .*failure .*3").unwrap());
+    basecamp()
+        .args(["list", "--status", "--remote"])
+        .env("BASECAMP_TOKEN", "cached")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 failing CI, 3 open PRs"))
+        .stdout(predicate::str::contains("0 failing CI, 1 open PRs"));
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_verify_stale_repositories() {
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    common::create_test_config(&temp_path);
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.env("BASECAMP_TOKEN", "cached");
+        cmd.current_dir(&temp_path);
+        cmd
+    };
+
+    // A clone of the renamed repository, moved along with its entry
+    let clone = git2::Repository::init(temp_path.join("backend/database")).unwrap();
+    clone.remote("origin", "https://github.com/test-org/database.git").unwrap();
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    std::fs::write(
+        temp_path.join(".basecamp/state.json"),
+        format!(
+            r#"{{"repos": {{}}, "remote": {{
+                "api-server": {{"default_branch": "main", "archived": true, "checked_at": {now}}},
+                "database": {{"default_branch": "main", "moved_to": "test-org/db", "checked_at": {now}}},
+                "ui-component": {{"default_branch": "main", "checked_at": {now}}},
+                "web-client": {{"default_branch": "main", "moved_to": "other-org/web-client", "checked_at": {now}}}
+            }}}}"#
+        ),
+    )
+    .unwrap();
+
+    // Without confirmation the stale entries are only reported
+    basecamp()
+        .arg("verify")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("'api-server' is archived on the host"))
+        .stdout(predicate::str::contains("'database' was renamed to 'db' on the host"))
+        .stdout(predicate::str::contains("'web-client' moved to other-org/web-client"))
+        .stderr(predicate::str::contains("3 repositories are archived, renamed or moved"));
+
+    basecamp().args(["--yes", "verify", "backend"]).assert().success();
+    let codebases = std::fs::read_to_string(temp_path.join(".basecamp/codebases.yaml")).unwrap();
+    assert!(codebases.contains("- db"));
+    assert!(!codebases.contains("api-server"));
+    assert!(codebases.contains("web-client"));
+    assert!(temp_path.join("backend/db/.git").exists());
+    let moved = git2::Repository::open(temp_path.join("backend/db")).unwrap();
+    assert_eq!(
+        moved.find_remote("origin").unwrap().url(),
+        Some("https://github.com/test-org/db.git")
+    );
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_sync_fork() {
+    // Setup - a fork in a local "organization" of a repository elsewhere
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let upstream_path = temp_path.join("upstream/api-server.git");
+    let upstream = git2::Repository::init(&upstream_path).unwrap();
+    commit_empty(&upstream, "Initial commit");
+    let org = temp_path.join("org");
+    git2::Repository::clone(upstream_path.to_str().unwrap(), org.join("api-server.git")).unwrap();
+
+    let workspace = temp_path.join("workspace");
+    let basecamp_dir = common::create_test_config(&workspace);
+    std::fs::write(basecamp_dir.join("config.yaml"), format!("github_url: {}", org.display())).unwrap();
+    std::fs::write(
+        basecamp_dir.join("codebases.yaml"),
+        format!(
+            "codebases:\n  backend: [api-server]\nrepositories:\n  api-server:\n    upstream: {}\n",
+            upstream_path.display()
+        ),
+    )
+    .unwrap();
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install", "backend"]).assert().success();
+    let clone = git2::Repository::open(workspace.join("backend/api-server")).unwrap();
+    assert_eq!(
+        clone.find_remote("upstream").unwrap().url(),
+        upstream_path.to_str()
+    );
+
+    basecamp()
+        .arg("sync-fork")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already up to date"));
+
+    // Upstream moves ahead, the fork's default branch follows
+    commit_empty(&upstream, "Second commit");
+    commit_empty(&upstream, "Third commit");
+    basecamp()
+        .args(["sync-fork", "backend"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("fast-forwarded 2 commits"));
+    assert_eq!(
+        clone.head().unwrap().peel_to_commit().unwrap().id(),
+        upstream.head().unwrap().peel_to_commit().unwrap().id()
+    );
+    assert!(!clone.statuses(None).unwrap().iter().any(|s| s.status() != git2::Status::CURRENT));
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_remote_name_and_extra_remotes() {
+    // Setup - a local "organization" with one repository and a mirror of it
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let mirror = temp_path.join("mirror/api-server.git");
+    let moved = temp_path.join("backup/api-server.git");
+    let codebases = |mirror: &std::path::Path| {
+        format!(
+            "codebases:\n  backend: [api-server]\nrepositories:\n  api-server:\n    remotes:\n      mirror: {}\n",
+            mirror.display()
+        )
+    };
+    let (_, workspace) =
+        common::local_org_workspace(&temp_path, &["api-server"], "remote_name: github\n", &codebases(&mirror));
+    let basecamp_dir = workspace.join(".basecamp");
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install", "backend"]).assert().success();
+    let clone = git2::Repository::open(workspace.join("backend/api-server")).unwrap();
+    assert!(clone.find_remote("origin").is_err());
+    assert!(clone.find_remote("github").is_ok());
+    assert_eq!(clone.find_remote("mirror").unwrap().url(), mirror.to_str());
+    basecamp().arg("verify").assert().success();
+
+    // The mirror moves, verify --fix follows it
+    std::fs::write(basecamp_dir.join("codebases.yaml"), codebases(&moved)).unwrap();
+    basecamp()
+        .arg("verify")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("mirror"))
+        .stderr(predicate::str::contains("1 remotes of 1 cloned repositories don't match"));
+    basecamp().args(["verify", "--fix"]).assert().success();
+    let clone = git2::Repository::open(workspace.join("backend/api-server")).unwrap();
+    assert_eq!(clone.find_remote("mirror").unwrap().url(), moved.to_str());
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_mirror() {
+    // Setup - a local "organization" and an empty mirror of it
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let mirror_dir = temp_path.join("mirror");
+    let mirror = git2::Repository::init_bare(mirror_dir.join("api-server.git")).unwrap();
+    let (org, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server"],
+        &format!("mirrors:\n  backup:\n    url: {}\n", mirror_dir.display()),
+        "codebases:\n  backend: [api-server]\n",
+    );
+    let origin = git2::Repository::open(org.join("api-server.git")).unwrap();
+    let head = origin.head().unwrap().peel_to_commit().unwrap();
+    origin.tag_lightweight("v1.0", head.as_object(), false).unwrap();
+    let branch = origin.head().unwrap().shorthand().unwrap().to_string();
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install", "backend"]).assert().success();
+
+    // Commits made upstream since the clone are fetched before pushing
+    commit_empty(&origin, "Upstream change");
+    let latest = origin.head().unwrap().peel_to_commit().unwrap();
+
+    basecamp()
+        .args(["mirror", "backend", "--to", "elsewhere"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No mirror named 'elsewhere'"));
+    basecamp()
+        .args(["mirror", "backend"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pushed 2 refs"));
+    assert_eq!(
+        mirror.refname_to_id(&format!("refs/heads/{}", branch)).unwrap(),
+        latest.id()
+    );
+    assert_eq!(mirror.refname_to_id("refs/tags/v1.0").unwrap(), head.id());
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_snapshot_create_and_restore() {
+    // Setup - a local "organization" with one repository, cloned with local changes
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server"],
+        "",
+        "codebases:\n  backend: [api-server]\n",
+    );
+    let basecamp_dir = workspace.join(".basecamp");
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install", "backend"]).assert().success();
+
+    let clone_path = workspace.join("backend/api-server");
+    let clone = git2::Repository::open(&clone_path).unwrap();
+    std::fs::write(clone_path.join("README.md"), "first\n").unwrap();
+    let mut index = clone.index().unwrap();
+    index.add_path(std::path::Path::new("README.md")).unwrap();
+    index.write().unwrap();
+    commit_empty(&clone, "Add README");
+    let recorded = clone.head().unwrap().peel_to_commit().unwrap().id();
+    std::fs::write(clone_path.join("README.md"), "first\nwork in progress\n").unwrap();
+    std::fs::write(clone_path.join("notes.txt"), "untracked\n").unwrap();
+
+    basecamp()
+        .args(["snapshot", "create", "before-refactor"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 repositories (1 with local changes)"));
+    assert!(basecamp_dir.join("snapshots/before-refactor/backend/api-server.patch").exists());
+    basecamp()
+        .args(["snapshot", "create", "before-refactor"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists"));
+    basecamp()
+        .args(["snapshot", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("before-refactor"));
+
+    // The refactoring moves the repository on
+    std::fs::write(clone_path.join("README.md"), "rewritten\n").unwrap();
+    std::fs::remove_file(clone_path.join("notes.txt")).unwrap();
+    let mut index = clone.index().unwrap();
+    index.add_path(std::path::Path::new("README.md")).unwrap();
+    index.write().unwrap();
+    commit_empty(&clone, "Refactor");
+    let refactored = clone.head().unwrap().peel_to_commit().unwrap().id();
+
+    // Moving the branch back would lose the new commit
+    basecamp()
+        .args(["snapshot", "restore", "before-refactor"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("has 1 commits that aren't in the snapshot"));
+    assert_eq!(clone.head().unwrap().peel_to_commit().unwrap().id(), refactored);
+
+    std::fs::write(clone_path.join("scratch.txt"), "scratch\n").unwrap();
+
+    basecamp()
+        .args(["snapshot", "restore", "before-refactor"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+    basecamp()
+        .args(["snapshot", "restore", "before-refactor", "--force"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored 1 repositories"));
+
+    assert_eq!(clone.head().unwrap().peel_to_commit().unwrap().id(), recorded);
+    assert_eq!(
+        std::fs::read_to_string(clone_path.join("README.md")).unwrap(),
+        "first\nwork in progress\n"
+    );
+    assert_eq!(std::fs::read_to_string(clone_path.join("notes.txt")).unwrap(), "untracked\n");
+    assert!(!clone_path.join("scratch.txt").exists());
+
+    // Names and recorded paths can't lead out of the workspace
+    for args in [["delete", ".."], ["restore", ".."]] {
+        basecamp()
+            .arg("snapshot")
+            .args(args)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Invalid snapshot name"));
+    }
+    assert!(basecamp_dir.exists());
+    let snapshot_file = basecamp_dir.join("snapshots/before-refactor/snapshot.json");
+    let recorded_snapshot = std::fs::read_to_string(&snapshot_file).unwrap();
+    std::fs::write(&snapshot_file, recorded_snapshot.replace("backend/api-server.patch", "../../../secret.patch")).unwrap();
+    basecamp()
+        .args(["snapshot", "restore", "before-refactor"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("outside the workspace"));
+    std::fs::write(&snapshot_file, recorded_snapshot.replace("\"backend\"", "\"../outside\"")).unwrap();
+    basecamp()
+        .args(["snapshot", "restore", "before-refactor"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("outside the workspace"));
+    std::fs::write(&snapshot_file, recorded_snapshot).unwrap();
+
+    basecamp().args(["snapshot", "delete", "before-refactor"]).assert().success();
+    assert!(!basecamp_dir.join("snapshots/before-refactor").exists());
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_undo() {
+    // Setup - a local "organization" with two repositories, one of them installed
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let codebases = "# Team repositories\ncodebases:\n  backend: [api-server]\n";
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server", "cache"],
+        "",
+        codebases,
+    );
+    let basecamp_dir = workspace.join(".basecamp");
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().arg("undo").assert().success().stdout(predicate::str::contains("Nothing to undo"));
+    basecamp().args(["install", "backend"]).assert().success();
+    let clone = workspace.join("backend/api-server");
+    std::fs::write(clone.join("notes.txt"), "local notes\n").unwrap();
+
+    // A removal comes back with the local directory, untracked files included
+    basecamp().args(["remove", "backend", "api-server", "--force", "--yes"]).assert().success();
+    assert!(!clone.exists());
+    basecamp()
+        .args(["undo", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Undid 'remove api-server from 'backend''"));
+    assert_eq!(std::fs::read_to_string(clone.join("notes.txt")).unwrap(), "local notes\n");
+    assert_eq!(std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap(), codebases);
+    assert!(!basecamp_dir.join("trash").read_dir().unwrap().any(|_| true));
+
+    // An addition is reverted with its clone
+    basecamp().args(["add", "backend", "cache"]).assert().success();
+    assert!(workspace.join("backend/cache").exists());
+    basecamp().args(["undo", "--yes"]).assert().success();
+    assert!(!workspace.join("backend/cache").exists());
+    assert_eq!(std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap(), codebases);
+
+    // Configuration edited since the operation is protected
+    basecamp().args(["remove", "backend", "api-server", "--force", "--yes"]).assert().success();
+    std::fs::write(basecamp_dir.join("codebases.yaml"), "codebases:\n  frontend: []\n").unwrap();
+    basecamp()
+        .args(["undo", "--yes"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+    basecamp().args(["undo", "--yes", "--force"]).assert().success();
+    assert!(clone.join("notes.txt").exists());
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_config_backups_and_restore() {
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let basecamp_dir = common::create_test_config(&temp_path);
+    std::fs::write(
+        basecamp_dir.join("config.yaml"),
+        "github_url: https://github.com/example-org\nbackups: 2\n",
+    )
+    .unwrap();
+    let original = "# Team repositories\ncodebases:\n  backend: [api-server]\n";
+    std::fs::write(basecamp_dir.join("codebases.yaml"), original).unwrap();
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&temp_path);
+        cmd
+    };
+    basecamp()
+        .args(["config", "restore"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No backups yet"));
+
+    // Every save keeps the previous version, up to `backups` copies
+    for repo in ["cache", "worker", "scheduler"] {
+        basecamp().args(["--offline", "add", "backend", repo]).assert().success();
+    }
+    let backups_dir = basecamp_dir.join("backups");
+    assert!(!backups_dir.join("codebases-1.yaml").exists());
+    assert!(backups_dir.join("codebases-2.yaml").exists());
+    assert!(backups_dir.join("codebases-3.yaml").exists());
+    basecamp()
+        .args(["config", "restore", "--list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("codebases-3").and(predicate::str::contains("codebases.yaml")));
+
+    // A corrupted file is rolled back without being loaded
+    std::fs::write(basecamp_dir.join("codebases.yaml"), "codebases: [unclosed\n").unwrap();
+    basecamp()
+        .args(["config", "restore", "codebases-2", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored codebases.yaml from backup codebases-2"));
+    let restored = std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap();
+    assert!(restored.starts_with("# Team repositories") && restored.contains("cache"));
+    assert!(!restored.contains("worker"));
+    assert_eq!(
+        std::fs::read_to_string(backups_dir.join("codebases-4.yaml")).unwrap(),
+        "codebases: [unclosed\n"
+    );
+    basecamp()
+        .args(["config", "restore", "codebases-1", "--yes"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No backup named 'codebases-1'"));
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_pinned_revisions() {
+    // Setup - a local "organization" with one repository and a few releases
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let org = temp_path.join("org");
+    let origin = git2::Repository::init(org.join("api-server.git")).unwrap();
+    let tag = |name: &str| {
+        let head = origin.head().unwrap().peel_to_commit().unwrap();
+        origin.tag_lightweight(name, head.as_object(), false).unwrap();
+        head.id()
+    };
+    commit_empty(&origin, "Release 1.0.0");
+    let pinned = tag("v1.0.0");
+    commit_empty(&origin, "Release 1.9.0");
+    tag("v1.9.0");
+    commit_empty(&origin, "Release 1.10.0");
+    let latest = tag("v1.10.0");
+    commit_empty(&origin, "Release candidate");
+    tag("v2.0.0-rc1");
+
+    let workspace = temp_path.join("workspace");
+    let basecamp_dir = common::create_test_config(&workspace);
+    std::fs::write(basecamp_dir.join("config.yaml"), format!("github_url: {}", org.display())).unwrap();
+    std::fs::write(
+        basecamp_dir.join("codebases.yaml"),
+        "codebases:\n  backend: [api-server]\nrepositories:\n  api-server:\n    rev: v1.0.0\n",
+    )
+    .unwrap();
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install", "backend"]).assert().success();
+    let clone = git2::Repository::open(workspace.join("backend/api-server")).unwrap();
+    assert!(clone.head_detached().unwrap());
+    assert_eq!(clone.head().unwrap().target(), Some(pinned));
+
+    // Moving off the pin shows in status, and install --update goes back to it
+    let tip = clone.head().unwrap().peel_to_commit().unwrap();
+    clone.branch("work", &tip, false).unwrap();
+    clone.set_head("refs/heads/work").unwrap();
+    commit_empty(&clone, "Local experiment");
+    basecamp()
+        .args(["status", "--no-cache"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("v1.0.0 (drifted)").and(predicate::str::contains("off their pinned revision")));
+    basecamp()
+        .args(["install", "backend", "--update"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("checked out v1.0.0"));
+    assert_eq!(clone.head().unwrap().target(), Some(pinned));
+    basecamp()
+        .args(["status", "--no-cache"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("drifted").not());
+
+    // Pins move to the highest release of their series
+    basecamp()
+        .args(["update-pins", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("v1.10.0").and(predicate::str::contains("Dry run")));
+    let codebases = std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap();
+    assert!(codebases.contains("rev: v1.0.0"));
+    basecamp().arg("update-pins").assert().success();
+    let codebases = std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap();
+    assert!(codebases.contains("rev: v1.10.0"));
+    basecamp().args(["install", "--update"]).assert().success();
+    assert_eq!(clone.head().unwrap().target(), Some(latest));
+    basecamp()
+        .arg("update-pins")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Every pin is current"));
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_sparse_checkout() {
+    // Setup - a local "organization" with a monorepo
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let org = temp_path.join("org");
+    let origin_path = org.join("platform.git");
+    let origin = git2::Repository::init(&origin_path).unwrap();
+    let files = [
+        "README.md",
+        "services/README.md",
+        "services/payments/main.rs",
+        "services/orders/main.rs",
+        "libs/shared/lib.rs",
+        "docs/guide.md",
+    ];
+    let mut index = origin.index().unwrap();
+    for file in files {
+        let path = origin_path.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, file).unwrap();
+        index.add_path(std::path::Path::new(file)).unwrap();
+    }
+    index.write().unwrap();
+    commit_empty(&origin, "Monorepo");
+
+    let workspace = temp_path.join("workspace");
+    let basecamp_dir = common::create_test_config(&workspace);
+    std::fs::write(basecamp_dir.join("config.yaml"), format!("github_url: {}", org.display())).unwrap();
+    let codebases = |sparse: &str| {
+        std::fs::write(
+            basecamp_dir.join("codebases.yaml"),
+            format!("codebases:\n  payments: [platform]\nrepositories:\n  platform:\n    sparse: {}\n", sparse),
+        )
+        .unwrap();
+    };
+    codebases("[services/payments]");
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install", "payments"]).assert().success();
+    let clone_path = workspace.join("payments/platform");
+    for kept in ["README.md", "services/README.md", "services/payments/main.rs"] {
+        assert!(clone_path.join(kept).exists(), "{} should be checked out", kept);
+    }
+    for left_out in ["services/orders", "libs", "docs"] {
+        assert!(!clone_path.join(left_out).exists(), "{} should be left out", left_out);
+    }
+    let clone = git2::Repository::open(&clone_path).unwrap();
+    assert!(clone.config().unwrap().get_bool("core.sparseCheckout").unwrap());
+    assert_eq!(
+        std::fs::read_to_string(clone_path.join(".git/info/sparse-checkout")).unwrap(),
+        "/*\n!/*/\n/services/\n!/services/*/\n/services/payments/\n"
+    );
+    basecamp()
+        .args(["status", "--no-cache"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("clean"));
+
+    // Widening the paths brings their files back
+    codebases("[services/payments, libs/shared]");
+    basecamp().args(["install", "payments", "--update"]).assert().success();
+    assert_eq!(
+        std::fs::read_to_string(clone_path.join("libs/shared/lib.rs")).unwrap(),
+        "libs/shared/lib.rs"
+    );
+    assert!(!clone_path.join("docs").exists());
+
+    // Narrowing them again is refused while a file that would be left out has changes
+    std::fs::write(clone_path.join("libs/shared/lib.rs"), "work in progress").unwrap();
+    codebases("[services/payments]");
+    basecamp()
+        .args(["install", "payments", "--update"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("has uncommitted changes"));
+    assert_eq!(
+        std::fs::read_to_string(clone_path.join("libs/shared/lib.rs")).unwrap(),
+        "work in progress"
+    );
+    let entry = clone.index().unwrap().get_path(std::path::Path::new("libs/shared/lib.rs"), 0).unwrap();
+    assert_eq!(entry.flags_extended & git2::IndexEntryExtendedFlag::SKIP_WORKTREE.bits(), 0);
+
+    codebases("[services/../secrets]");
+    basecamp()
+        .arg("validate")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'services/../secrets' isn't a usable sparse path of 'platform'"));
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_repository_dir_override() {
+    // Setup - a local "organization" with one repository
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(&temp_path, &["api"], "", "");
+    let basecamp_dir = workspace.join(".basecamp");
+    let codebases = |settings: &str| {
+        std::fs::write(
+            basecamp_dir.join("codebases.yaml"),
+            format!("codebases:\n  backend: [api, legacy-api]\nrepositories:\n{}", settings),
+        )
+        .unwrap();
+    };
+    codebases("  api:\n    dir: legacy-api\n");
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+
+    // Two repositories can't share a directory
+    basecamp()
+        .arg("validate")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'api' and 'legacy-api' of codebase 'backend' are both cloned into 'legacy-api'"));
+
+    codebases("  api:\n    dir: old-api\n  legacy-api:\n    dir: ../elsewhere\n");
+    basecamp()
+        .arg("validate")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'../elsewhere' isn't a usable directory name of 'legacy-api'"));
+
+    // The clone lands in its directory, and commands find it there
+    std::fs::write(
+        basecamp_dir.join("codebases.yaml"),
+        "codebases:\n  backend: [api]\nrepositories:\n  api:\n    dir: old-api\n",
+    )
+    .unwrap();
+    basecamp().args(["install", "backend"]).assert().success();
+    assert!(workspace.join("backend/old-api/.git").exists());
+    assert!(!workspace.join("backend/api").exists());
+    basecamp()
+        .args(["status", "--no-cache"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("clean"));
+    basecamp()
+        .args(["path", "api"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("backend/old-api"));
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_codebase_environment() {
+    // Setup - a local "organization" with one repository
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api"],
+        "env_files: [dotenv, direnv]\n",
+        "\
+codebases:
+  backend: [api]
+env:
+  backend:
+    DATABASE_URL: postgres://localhost/{codebase}_{repo}
+    PORT: 8080
+    TOKEN: {command: echo s3cret}
+",
+    );
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+
+    // Installing writes the environment files, kept out of git status
+    basecamp().args(["install", "backend"]).assert().success();
+    let clone_path = workspace.join("backend/api");
+    let dotenv = std::fs::read_to_string(clone_path.join(".env")).unwrap();
+    assert!(dotenv.contains("DATABASE_URL=\"postgres://localhost/backend_api\"\n"), "{}", dotenv);
+    assert!(dotenv.contains("PORT=\"8080\"\n"), "{}", dotenv);
+    assert!(dotenv.contains("TOKEN=\"s3cret\"\n"), "{}", dotenv);
+    let envrc = std::fs::read_to_string(clone_path.join(".envrc")).unwrap();
+    assert!(envrc.contains("export TOKEN='s3cret'\n"), "{}", envrc);
+    basecamp()
+        .args(["status", "--no-cache"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("clean"));
+
+    // The environment for eval, with messages kept off stdout
+    basecamp()
+        .args(["env", "backend", "api"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("export DATABASE_URL='postgres://localhost/backend_api'\n"))
+        .stdout(predicate::str::contains("export PORT='8080'\n"));
+    basecamp()
+        .args(["env", "backend"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("export DATABASE_URL='postgres://localhost/backend_{repo}'\n"));
+
+    // Files the repository owns are left alone
+    std::fs::write(clone_path.join(".env"), "PORT=3000\n").unwrap();
+    basecamp()
+        .args(["install", "backend"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("weren't written by basecamp"));
+    assert_eq!(std::fs::read_to_string(clone_path.join(".env")).unwrap(), "PORT=3000\n");
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_shared_hooks() {
+    // Setup - a local "organization" with one repository
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (org, workspace) =
+        common::local_org_workspace(&temp_path, &["api"], "hooks_dir: hooks\n", "codebases:\n  backend: [api]\n");
+    let basecamp_dir = workspace.join(".basecamp");
+    let config = |mode: &str| {
+        std::fs::write(
+            basecamp_dir.join("config.yaml"),
+            format!("github_url: {}\nhooks_dir: hooks\n{}", org.display(), mode),
+        )
+        .unwrap();
+    };
+    let shared = workspace.join("hooks");
+    std::fs::create_dir_all(&shared).unwrap();
+    std::fs::write(shared.join("pre-commit"), "#!/bin/sh\nexit 0\n").unwrap();
+    std::fs::write(shared.join("pre-push.sample"), "#!/bin/sh\n").unwrap();
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+
+    // Installing deploys the hooks
+    basecamp().args(["install", "backend"]).assert().success();
+    let hooks = workspace.join("backend/api/.git/hooks");
+    assert_eq!(std::fs::read_to_string(hooks.join("pre-commit")).unwrap(), "#!/bin/sh\nexit 0\n");
+    assert!(!hooks.join("pre-push").exists());
+
+    // Syncing removes dropped hooks and leaves the repository's own alone
+    std::fs::remove_file(shared.join("pre-commit")).unwrap();
+    std::fs::write(shared.join("commit-msg"), "#!/bin/sh\nexit 0\n").unwrap();
+    std::fs::write(hooks.join("commit-msg"), "#!/bin/sh\n# own\n").unwrap();
+    basecamp()
+        .args(["hooks", "sync"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pre-commit"))
+        .stdout(predicate::str::contains("commit-msg"));
+    assert!(!hooks.join("pre-commit").exists());
+    assert_eq!(std::fs::read_to_string(hooks.join("commit-msg")).unwrap(), "#!/bin/sh\n# own\n");
+
+    // Or point core.hooksPath at the directory
+    config("hooks_mode: hooks_path\n");
+    basecamp().args(["hooks", "sync", "backend"]).assert().success();
+    let clone = git2::Repository::open(workspace.join("backend/api")).unwrap();
+    assert_eq!(
+        clone.config().unwrap().get_path("core.hooksPath").unwrap(),
+        shared.canonicalize().unwrap_or(shared.clone())
+    );
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_git_settings() {
+    // Setup - a local "organization" with two repositories
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api", "blog"],
+        "git:\n  user:\n    name: Jane Doe\n    email: jane@work.example\n  pull.rebase: true\n",
+        "codebases:\n  backend: [api, blog]\nrepositories:\n  blog:\n    git:\n      user.email: jane@personal.example\n",
+    );
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    let local = |repo: &str, key: &str| {
+        let clone = git2::Repository::open(workspace.join("backend").join(repo)).unwrap();
+        let config = clone.config().unwrap().open_level(git2::ConfigLevel::Local).unwrap();
+        config.get_string(key).unwrap()
+    };
+
+    // Installing sets the identity, overridden per repository
+    basecamp().args(["install", "backend"]).assert().success();
+    assert_eq!(local("api", "user.name"), "Jane Doe");
+    assert_eq!(local("api", "user.email"), "jane@work.example");
+    assert_eq!(local("api", "pull.rebase"), "true");
+    assert_eq!(local("blog", "user.email"), "jane@personal.example");
+    basecamp().arg("verify").assert().success();
+
+    // A changed email is caught and fixed by verify
+    let clone = git2::Repository::open(workspace.join("backend/api")).unwrap();
+    let mut config = clone.config().unwrap().open_level(git2::ConfigLevel::Local).unwrap();
+    config.set_str("user.email", "jane@personal.example").unwrap();
+    basecamp()
+        .arg("verify")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("user.email"))
+        .stderr(predicate::str::contains("1 git config values of 2 cloned repositories don't match"));
+    basecamp().args(["verify", "--fix"]).assert().success();
+    assert_eq!(local("api", "user.email"), "jane@work.example");
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_commit_signing() {
+    // Setup - a local "organization" with one repository
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let home = temp_path.join("home");
+    std::fs::create_dir_all(home.join(".ssh")).unwrap();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api"],
+        "git:\n  user.email: jane@work.example\nsigning:\n  format: ssh\n  key: ~/.ssh/signing\n  tags: true\n",
+        "codebases:\n  backend: [api]\n",
+    );
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace).env("HOME", &home);
+        cmd
+    };
+
+    // Installing turns signing on, with the key path expanded
+    basecamp().args(["install", "backend"]).assert().success();
+    let clone = git2::Repository::open(workspace.join("backend/api")).unwrap();
+    let config = clone.config().unwrap().open_level(git2::ConfigLevel::Local).unwrap();
+    assert_eq!(config.get_string("commit.gpgsign").unwrap(), "true");
+    assert_eq!(config.get_string("tag.gpgsign").unwrap(), "true");
+    assert_eq!(config.get_string("gpg.format").unwrap(), "ssh");
+    assert_eq!(
+        config.get_string("user.signingkey").unwrap(),
+        home.join(".ssh/signing").to_string_lossy()
+    );
+
+    // Without the key, status and verify report it
+    basecamp()
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Commits wouldn't be signed in 1 repositories"));
+    basecamp()
+        .arg("verify")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("backend/api"))
+        .stderr(predicate::str::contains("Commits wouldn't be signed in 1 repositories"));
+
+    // With it, verify passes
+    let generated = std::process::Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+        .arg(home.join(".ssh/signing"))
+        .status()
+        .is_ok_and(|status| status.success());
+    if generated {
+        basecamp().arg("verify").assert().success();
+    }
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_run_task_filters() {
+    // Setup - a local "organization" with two repositories
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api", "web"],
+        "",
+        "codebases:\n  backend: [api, web]\ntasks:\n  mark:\n    command: git rev-parse HEAD > ../$(basename $PWD).ran\n",
+    );
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    let ran = |repo: &str| {
+        let path = workspace.join(format!("backend/{}.ran", repo));
+        let ran = path.exists();
+        let _ = std::fs::remove_file(path);
+        ran
+    };
+    basecamp().args(["install", "backend"]).assert().success();
+
+    // Only the repository with changes
+    std::fs::write(workspace.join("backend/api/notes.txt"), "wip").unwrap();
+    basecamp().args(["run", "mark", "--filter", "dirty"]).assert().success();
+    assert!(ran("api"));
+    assert!(!ran("web"));
+
+    // Only the tagged one, and both conditions must hold
+    let web = git2::Repository::open(workspace.join("backend/web")).unwrap();
+    let head = web.head().unwrap().peel_to_commit().unwrap();
+    web.tag_lightweight("v1.0", head.as_object(), false).unwrap();
+    basecamp().args(["run", "mark", "--filter", "tag=v1.0"]).assert().success();
+    assert!(!ran("api"));
+    assert!(ran("web"));
+    basecamp()
+        .args(["run", "mark", "--filter", "tag", "--filter", "dirty"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No installed repositories match tag and dirty"));
+    assert!(!ran("api") && !ran("web"));
+
+    // Unknown predicates are rejected
+    basecamp()
+        .args(["run", "mark", "--filter", "stale"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid filter: stale"));
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_paths() {
+    // Setup - a local "organization" with three repositories, one not installed
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api", "web", "docs"],
+        "",
+        "codebases:\n  backend: [web, api]\n  site: [docs]\ndepends_on:\n  web: [api]\n",
+    );
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install", "backend"]).assert().success();
+    let root = workspace.canonicalize().unwrap();
+    let path = |repo: &str| root.join("backend").join(repo).display().to_string();
+
+    // Installed repositories only, in codebases.yaml order or by dependencies
+    basecamp()
+        .arg("paths")
+        .assert()
+        .success()
+        .stdout(format!("{}\n{}\n", path("web"), path("api")));
+    basecamp()
+        .args(["paths", "backend", "--deps", "-0"])
+        .assert()
+        .success()
+        .stdout(format!("{}\0{}\0", path("api"), path("web")));
+
+    // Filtered by state
+    std::fs::write(workspace.join("backend/web/notes.txt"), "wip").unwrap();
+    basecamp()
+        .args(["paths", "--filter", "dirty"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", path("web")));
+    basecamp().args(["paths", "site"]).assert().success().stdout("");
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_run_task_output_and_timeout() {
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let basecamp_dir = common::create_test_config(&temp_path);
+
+    let mut codebases = std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap();
+    codebases.push_str(
+        "\ntasks:\n  greet:\n    command: echo hello\n  hang:\n    command: sleep 30 & sleep 30\n  \
+         flood:\n    command: yes | head -c 100000; exit 1\n",
+    );
+    std::fs::write(basecamp_dir.join("codebases.yaml"), codebases).unwrap();
+    std::fs::create_dir_all(temp_path.join("frontend/ui-component")).unwrap();
+    std::fs::create_dir_all(temp_path.join("frontend/web-client")).unwrap();
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&temp_path);
+        cmd
+    };
+
+    // Live output is prefixed with the repository, captured output comes in blocks
+    basecamp()
+        .args(["run", "greet", "frontend", "--stream"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("frontend/web-client | hello"));
+    basecamp()
+        .args(["run", "greet", "frontend", "--capture"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("frontend/ui-component:"))
+        .stdout(predicate::str::contains("hello"));
+
+    // Hanging tasks are killed along with what they started
+    let started = std::time::Instant::now();
+    basecamp()
+        .args(["run", "hang", "frontend", "--timeout", "1"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("TIMED OUT"))
+        .stdout(predicate::str::contains("timed out after 1s"));
+    assert!(started.elapsed() < std::time::Duration::from_secs(20));
+
+    // Output beyond the limit is dropped
+    basecamp()
+        .args(["run", "flood", "frontend", "--max-output", "100"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("[output cut after 100 bytes]"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_config_diff_preview() {
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let basecamp_dir = common::create_test_config(&temp_path);
+    let codebases = std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap();
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&temp_path).arg("--offline");
+        cmd
+    };
+
+    // Without a confirmation the change is shown but not saved
+    basecamp()
+        .args(["--diff", "add", "backend", "cache"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("+++ b/codebases.yaml"))
+        .stdout(predicate::str::contains("+    - cache"))
+        .stderr(predicate::str::contains("Changes to the configuration were not saved"));
+    assert_eq!(std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap(), codebases);
+
+    // Confirmed, it is
+    basecamp()
+        .args(["--diff", "--yes", "add", "backend", "cache"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+    - cache"));
+    assert!(std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap().contains("- cache"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_local_work_blocks_every_repository() {
+    // Setup - a local "organization" with three installed repositories, two of them with local work
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server", "cache", "queue"],
+        "",
+        "codebases:\n  backend: [api-server, cache, queue]\n",
+    );
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install", "backend"]).assert().success();
+    std::fs::write(workspace.join("backend/api-server/notes.txt"), "local notes\n").unwrap();
+    let cache = git2::Repository::open(workspace.join("backend/cache")).unwrap();
+    commit_empty(&cache, "Local commit");
+
+    // Both are named at once, and nothing is removed
+    basecamp()
+        .args(["remove", "backend", "--yes"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("2 repositories have local work"))
+        .stderr(predicate::str::contains("api-server' has uncommitted changes"))
+        .stderr(predicate::str::contains("cache' has unpushed commits"));
+    assert!(workspace.join("backend/queue").exists());
+
+    // So are they before a re-clone
+    basecamp()
+        .args(["install", "backend", "--force-reclone", "api-server", "--force-reclone", "cache"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("2 repositories have local work"));
+    assert!(workspace.join("backend/api-server/notes.txt").exists());
+
+    basecamp().args(["remove", "backend", "queue", "--yes"]).assert().success();
+    assert!(!workspace.join("backend/queue").exists());
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_force_reclone() {
+    // Setup - a local "organization" with two installed repositories
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (org, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server", "cache", "shared-ui"],
+        "",
+        "codebases:\n  backend: [api-server, cache]\n  frontend: [shared-ui]\nrepositories:\n  shared-ui:\n    shared: true\n",
+    );
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install"]).assert().success();
+    std::fs::write(workspace.join("backend/api-server/notes.txt"), "local notes\n").unwrap();
+
+    // Local work is never deleted without --force
+    basecamp()
+        .args(["install", "backend", "--force-reclone", "api-server"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("api-server' has uncommitted changes"));
+    assert!(workspace.join("backend/api-server/notes.txt").exists());
+
+    // Only configured repositories can be re-cloned
+    basecamp()
+        .args(["install", "backend", "--force-reclone", "unknown"])
+        .assert()
+        .failure();
+
+    // A clean clone is deleted and cloned again
+    let upstream_head = |name: &str| {
+        git2::Repository::open(org.join(format!("{}.git", name))).unwrap().head().unwrap().target().unwrap()
+    };
+    commit_empty(&git2::Repository::open(org.join("cache.git")).unwrap(), "Upstream commit");
+    basecamp()
+        .args(["install", "backend", "--force-reclone", "cache"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("for a fresh clone"))
+        .stdout(predicate::str::contains("Cloned 'cache' successfully"));
+    let cache = git2::Repository::open(workspace.join("backend/cache")).unwrap();
+    assert_eq!(cache.head().unwrap().target(), Some(upstream_head("cache")));
+
+    // With --force, local work goes too, into the trash
+    basecamp()
+        .args(["install", "backend", "--force-reclone", "api-server", "--force"])
+        .assert()
+        .success();
+    assert!(workspace.join("backend/api-server/.git").exists());
+    assert!(!workspace.join("backend/api-server/notes.txt").exists());
+    let trashed = std::fs::read_dir(workspace.join(".basecamp/trash"))
+        .unwrap()
+        .flatten()
+        .any(|run| run.path().join("0-api-server/notes.txt").exists());
+    assert!(trashed);
+
+    // A shared repository is cloned again, not only linked to the same clone
+    commit_empty(&git2::Repository::open(org.join("shared-ui.git")).unwrap(), "Upstream commit");
+    basecamp()
+        .args(["install", "frontend", "--force-reclone", "shared-ui"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Cloned 'shared-ui' successfully"));
+    let shared = git2::Repository::open(workspace.join(".basecamp/shared/shared-ui")).unwrap();
+    assert_eq!(shared.head().unwrap().target(), Some(upstream_head("shared-ui")));
+    assert!(workspace.join("frontend/shared-ui/.git").exists());
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_remove_dry_run() {
+    // Setup - a local "organization" with two installed repositories, one of them with local work
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let codebases = "codebases:\n  backend: [api-server, cache]\n";
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server", "cache"],
+        "",
+        codebases,
+    );
+    let basecamp_dir = workspace.join(".basecamp");
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install", "backend"]).assert().success();
+    std::fs::write(workspace.join("backend/api-server/notes.txt"), "local notes\n").unwrap();
+
+    // Entries, directories with their sizes and local work are listed, without asking
+    basecamp()
+        .args(["remove", "backend", "api-server", "cache", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Would remove [api-server, cache] from codebase 'backend'"))
+        .stdout(predicate::str::contains("api-server ("))
+        .stdout(predicate::str::contains("Total: "))
+        .stdout(predicate::str::contains("api-server' has uncommitted changes"))
+        .stdout(predicate::str::contains("Dry run: nothing was changed"));
+    basecamp()
+        .args(["remove", "backend", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Would remove codebase 'backend' from codebases.yaml"));
+
+    // Nothing was changed
+    assert_eq!(std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap(), codebases);
+    assert!(workspace.join("backend/cache").exists());
+    assert!(workspace.join("backend/api-server/notes.txt").exists());
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_remove_codebase_deletes_in_parallel() {
+    // Setup - a local "organization" with three installed repositories
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server", "cache", "queue"],
+        "parallel: 3",
+        "codebases:\n  backend: [api-server, cache, queue]\n",
+    );
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install", "backend"]).assert().success();
+
+    // Every repository is reported, then the codebase directory
+    basecamp()
+        .args(["remove", "backend", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Deleted 'backend/api-server'"))
+        .stdout(predicate::str::contains("Deleted 'backend/queue'"))
+        .stdout(predicate::str::contains("Successfully deleted local directory 'backend'"));
+    assert!(!workspace.join("backend").exists());
+
+    // And all of them come back
+    basecamp().args(["undo", "--yes"]).assert().success();
+    for name in ["api-server", "cache", "queue"] {
+        assert!(workspace.join("backend").join(name).join(".git").exists());
+    }
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_remove_group() {
+    // Setup - a group of two installed codebases next to another codebase
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["web-client", "api-server", "cli"],
+        "",
+        "codebases:\n  platform:\n    frontend: [web-client]\n    backend: [api-server]\n  tools: [cli]\n",
+    );
+    let basecamp_dir = workspace.join(".basecamp");
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install", "platform"]).assert().success();
+    basecamp().args(["install", "tools"]).assert().success();
+
+    // The dry run covers every codebase of the group
+    basecamp()
+        .args(["remove", "platform", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Would remove codebase 'platform/frontend'"))
+        .stdout(predicate::str::contains("Would remove codebase 'platform/backend'"));
+    assert!(workspace.join("platform/frontend/web-client").exists());
+
+    // Local work anywhere in the group refuses the whole removal
+    std::fs::write(workspace.join("platform/backend/api-server/notes.txt"), "local notes\n").unwrap();
+    basecamp()
+        .args(["remove", "platform", "--yes"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("api-server"));
+    assert!(workspace.join("platform/frontend/web-client").exists());
+    std::fs::remove_file(workspace.join("platform/backend/api-server/notes.txt")).unwrap();
+
+    // Both codebases go, the rest stays
+    basecamp()
+        .args(["remove", "platform", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed group 'platform' (platform/backend, platform/frontend)"));
+    assert!(!workspace.join("platform").exists());
+    assert!(workspace.join("tools/cli").exists());
+    let codebases = std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap();
+    assert!(!codebases.contains("platform"));
+    assert!(codebases.contains("cli"));
+
+    // And a single undo brings the group back
+    basecamp().args(["undo", "--yes"]).assert().success();
+    assert!(workspace.join("platform/frontend/web-client/.git").exists());
+    assert!(workspace.join("platform/backend/api-server/.git").exists());
+    let codebases = std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap();
+    assert!(codebases.contains("web-client"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_archive_and_unarchive() {
+    // Setup - a local "organization" with two installed repositories
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server", "cache"],
+        "",
+        "codebases:\n  backend: [api-server, cache]\n",
+    );
+    let basecamp_dir = workspace.join(".basecamp");
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install", "backend"]).assert().success();
+    std::fs::write(workspace.join("backend/cache/notes.txt"), "local notes\n").unwrap();
+
+    // Archived with its local work, and out of the configuration
+    basecamp()
+        .args(["archive", "backend", "cache", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Archived cache of codebase 'backend'"));
+    assert!(!workspace.join("backend/cache").exists());
+    assert!(basecamp_dir.join("archive/backend/cache/notes.txt").exists());
+    let codebases = std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap();
+    assert!(!codebases.contains("cache"));
+
+    // Brought back as it was
+    basecamp()
+        .args(["unarchive", "backend"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Unarchived cache into codebase 'backend'"));
+    assert!(workspace.join("backend/cache/notes.txt").exists());
+    assert!(!basecamp_dir.join("archive/backend").exists());
+    let codebases = std::fs::read_to_string(basecamp_dir.join("codebases.yaml")).unwrap();
+    assert!(codebases.contains("cache"));
+    basecamp()
+        .args(["unarchive", "backend"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Codebase 'backend' has no archived repositories"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_remove_large_deletion_needs_confirmation() {
+    // Setup - an installed repository holding 2 MiB, above a 1 MiB threshold
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server"],
+        "confirm_delete_mib: 1",
+        "codebases:\n  backend: [api-server]\n",
+    );
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install", "backend"]).assert().success();
+    std::fs::write(workspace.join("backend/api-server/data.bin"), vec![0u8; 2 * 1024 * 1024]).unwrap();
+
+    // Without a terminal to type the name in, nothing is deleted
+    basecamp()
+        .args(["remove", "backend", "api-server", "--force"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(2.0 MiB)"))
+        .stdout(predicate::str::contains("pass --yes to confirm"))
+        .stdout(predicate::str::contains("Remove cancelled"));
+    assert!(workspace.join("backend/api-server/data.bin").exists());
+
+    basecamp().args(["remove", "backend", "api-server", "--force", "--yes"]).assert().success();
+    assert!(!workspace.join("backend/api-server").exists());
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_list_installed() {
+    // Setup - a local "organization" with two repositories, one of them installed
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (org, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server"],
+        "",
+        "codebases:\n  backend: [api-server, cache]\n  frontend: [web-client]\n",
+    );
+    let basecamp_dir = workspace.join(".basecamp");
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install", "backend"]).assert().code(2);
+
+    basecamp()
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1/2 cloned"))
+        .stdout(predicate::str::contains("0/1 cloned"));
+    basecamp()
+        .args(["list", "backend"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Installed"))
+        .stdout(predicate::str::contains("1/2 cloned"));
+
+    // Only what `install` would still clone
+    basecamp()
+        .args(["list", "backend", "--missing-only"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("cache"))
+        .stdout(predicate::str::contains("api-server").not());
+    basecamp()
+        .args(["list", "--missing-only"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("web-client"))
+        .stdout(predicate::str::contains("api-server").not());
+
+    // As a tree, one repository per line
+    basecamp()
+        .args(["list", "--tree"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("backend (1/2 cloned)\n├── ✓ api-server\n└── ○ cache"))
+        .stdout(predicate::str::contains("frontend (0/1 cloned)\n└── ○ web-client"));
+    basecamp()
+        .args(["list", "backend", "--tree", "--status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match("✓ api-server  \\S+ clean").unwrap())
+        .stdout(predicate::str::contains("○ cache       not installed"));
+
+    // Plain ASCII symbols for terminals without Unicode glyphs
+    std::fs::write(
+        basecamp_dir.join("config.yaml"),
+        format!("github_url: {}\nui:\n  ascii: true\n", org.display()),
+    )
+    .unwrap();
+    basecamp()
+        .args(["list", "--tree"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("backend (1/2 cloned)\n|-- + api-server\n`-- - cache"));
+
+    // Theme colors are checked when the configuration is loaded
+    std::fs::write(
+        basecamp_dir.join("config.yaml"),
+        format!("github_url: {}\nui:\n  theme:\n    success: lime\n", org.display()),
+    )
+    .unwrap();
+    basecamp()
+        .arg("list")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("ui.theme.success: unknown color 'lime'"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_info() {
+    // Setup - a local "organization" with one repository, installed
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (org, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server"],
+        "",
+        "codebases:\n  backend: [api-server]\n  platform: [api-server]\n",
+    );
+    let origin = git2::Repository::open(org.join("api-server.git")).unwrap();
+    commit_empty(&origin, "Add the first endpoint");
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&workspace);
+        cmd
+    };
+    basecamp().args(["install", "backend"]).assert().success();
+    std::fs::write(workspace.join("backend/api-server/notes.txt"), "local notes\n").unwrap();
+
+    basecamp()
+        .args(["info", "api-server"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Codebases:   backend, platform"))
+        .stdout(predicate::str::contains("api-server.git"))
+        .stdout(predicate::str::contains("platform/api-server (not installed)"))
+        .stdout(predicate::str::contains("Add the first endpoint (Test, "))
+        .stdout(predicate::str::contains("Remote:      origin "))
+        .stdout(predicate::str::contains("State:       dirty"))
+        .stdout(predicate::str::contains("Size:"));
+    basecamp()
+        .args(["info", "api-servr"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("api-servr"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_translated_messages() {
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let basecamp_dir = common::create_test_config(&temp_path);
+
+    let basecamp = || {
+        let mut cmd = Command::cargo_bin("basecamp").unwrap();
+        cmd.current_dir(&temp_path);
+        cmd
+    };
+
+    // The catalog is a translation file to start from
+    let output = basecamp().args(["config", "messages"]).assert().success().get_output().stdout.clone();
+    let catalog: std::collections::BTreeMap<String, String> = serde_yaml::from_slice(&output).unwrap();
+    assert_eq!(catalog["error.task_not_found"], "Task '{task}' not found");
+
+    // Translations replace the English texts, placeholders in any order
+    std::fs::write(
+        temp_path.join("messages-fr.yaml"),
+        "report.task_not_found: \"Tâche '{task}' introuvable\"\nerror.retired: old\n",
+    )
+    .unwrap();
+    let mut config = std::fs::read_to_string(basecamp_dir.join("config.yaml")).unwrap();
+    config.push_str("\nui:\n  messages: messages-fr.yaml\n");
+    std::fs::write(basecamp_dir.join("config.yaml"), config).unwrap();
+    basecamp()
+        .args(["run", "deploy"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Unknown message IDs"))
+        .stderr(predicate::str::contains("Tâche 'deploy' introuvable"));
+
+    // Messages without a translation keep their English text
+    basecamp()
+        .args(["remove", "mobile"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Codebase 'mobile' not found"));
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_install_priority() {
+    // Setup - a local "organization" with three repositories
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server", "cache", "docs"],
+        "",
+        "codebases:\n  backend: [api-server, cache, docs]\nrepositories:\n  docs:\n    priority: low\n  cache:\n    priority: high\n",
+    );
+    let basecamp_dir = workspace.join(".basecamp");
+
+    // High priority first and low priority last, whatever the configuration order
+    let output = Command::cargo_bin("basecamp")
+        .unwrap()
+        .args(["install", "-p", "1"])
+        .current_dir(&workspace)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8_lossy(&output);
+    let position = |repo: &str| output.find(&format!("Cloned '{}'", repo)).unwrap();
+    assert!(position("cache") < position("api-server"), "{}", output);
+    assert!(position("api-server") < position("docs"), "{}", output);
+
+    // Sizes are kept to clone smaller repositories first next time
+    let state = std::fs::read_to_string(basecamp_dir.join("state.json")).unwrap();
+    assert!(state.contains("\"size\""), "{}", state);
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_install_all_codebases_together() {
+    // Setup - two codebases, with the most urgent repository in the last one
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server", "cache", "web-client"],
+        "",
+        "codebases:\n  backend: [api-server, cache]\n  frontend: [web-client]\nrepositories:\n  api-server:\n    priority: low\n  web-client:\n    priority: high\n",
+    );
+
+    // One queue for every repository, reported per codebase
+    let output = Command::cargo_bin("basecamp")
+        .unwrap()
+        .args(["install", "-p", "1"])
+        .current_dir(&workspace)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8_lossy(&output);
+    assert!(output.contains("Installing 3 repositories across 2 codebases"), "{}", output);
+    let position = |line: &str| output.find(line).unwrap_or_else(|| panic!("{} missing from {}", line, output));
+    assert!(position("Cloned 'frontend/web-client'") < position("Cloned 'backend/cache'"), "{}", output);
+    assert!(position("Cloned 'backend/cache'") < position("Cloned 'backend/api-server'"), "{}", output);
+    assert!(position("'frontend' finished (1/1)") < position("'backend' finished (2/2)"), "{}", output);
+    assert!(workspace.join("frontend/web-client/.git").exists());
+    assert!(workspace.join("backend/api-server/.git").exists());
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_parallel_progress_without_terminal() {
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server", "cache"],
+        "",
+        "codebases:\n  backend: [api-server]\n",
+    );
+    let basecamp = |args: &[&str]| {
+        let output = Command::cargo_bin("basecamp")
+            .unwrap()
+            .args(args)
+            .current_dir(&workspace)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        String::from_utf8_lossy(&output).into_owned()
+    };
+
+    // Spinners and bars become one line per finished repository
+    let output = basecamp(&["install"]);
+    assert!(output.contains("Cloned 'api-server' successfully ✓\n"), "{}", output);
+    assert!(output.contains("Installed 1 new repositories\n"), "{}", output);
+    assert!(!output.contains("Cloning 'api-server'...") && !output.contains("(100%)"), "{}", output);
+
+    let output = basecamp(&["add", "backend", "cache"]);
+    assert!(output.contains("Cloned 'cache' successfully ✓\n"), "{}", output);
+    assert!(!output.contains("Cloning 'cache'...") && !output.contains("(100%)"), "{}", output);
+
+    // On a terminal the spinners and bars are drawn instead
+    #[cfg(target_os = "linux")]
+    if std::process::Command::new("script").arg("--version").output().is_ok() {
+        std::fs::remove_dir_all(workspace.join("backend")).unwrap();
+        let output = std::process::Command::new("script")
+            .arg("-qec")
+            .arg(format!("'{}' install", assert_cmd::cargo::cargo_bin("basecamp").display()))
+            .arg("/dev/null")
+            .env_remove("NO_COLOR")
+            .current_dir(&workspace)
+            .output()
+            .unwrap();
+        let output = String::from_utf8_lossy(&output.stdout);
+        assert!(output.contains("Cloning 'api-server'..."), "{}", output);
+        assert!(output.contains("2/2 (100%)"), "{}", output);
+    }
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_install_resume() {
+    // Setup - a local "organization" missing one of the configured repositories
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (org, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server", "cache"],
+        "",
+        "codebases:\n  backend: [api-server, cache, docs]\n",
+    );
+    let basecamp_dir = workspace.join(".basecamp");
+    let basecamp = |args: &[&str]| {
+        let mut command = Command::cargo_bin("basecamp").unwrap();
+        command.args(args).current_dir(&workspace);
+        command
+    };
+
+    // Nothing to resume yet
+    basecamp(&["install", "--resume"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No interrupted install to resume"));
+
+    // The failed repository is kept in a checkpoint
+    basecamp(&["install"])
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains("Run 'basecamp install --resume' to retry them"));
+    let state_dir = basecamp_dir.join("state");
+    let checkpoints: Vec<_> = std::fs::read_dir(&state_dir).unwrap().collect();
+    assert_eq!(checkpoints.len(), 1);
+    let checkpoint = std::fs::read_to_string(checkpoints[0].as_ref().unwrap().path()).unwrap();
+    assert!(checkpoint.contains("\"failed\""), "{}", checkpoint);
+
+    // The repository appears, with a partial clone left behind
+    let origin = git2::Repository::init(org.join("docs.git")).unwrap();
+    commit_empty(&origin, "Initial commit");
+    std::fs::create_dir_all(workspace.join("backend/docs/.git")).unwrap();
+
+    // Only the failed repository is cloned again, after trashing its partial clone
+    basecamp(&["install", "--resume"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 of 3 repositories left"))
+        .stdout(predicate::str::contains("Moving partial clone"))
+        .stdout(predicate::str::contains("Cloned 'docs' successfully"))
+        .stdout(predicate::str::contains("api-server").not());
+    assert!(workspace.join("backend/docs/.git/HEAD").exists());
+    assert!(!state_dir.exists());
+
+    // The finished run can't be resumed
+    basecamp(&["install", "--resume"]).assert().failure();
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_install_resume_keeps_shared_clones() {
+    // Setup - a shared repository cloned for one codebase
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let codebases = "codebases:\n  frontend: [shared-ui]\n  mobile: [shared-ui]\nrepositories:\n  shared-ui:\n    shared: true\n";
+    let (_, workspace) = common::local_org_workspace(&temp_path, &["shared-ui"], "", codebases);
+    let basecamp_dir = workspace.join(".basecamp");
+    let basecamp = |args: &[&str]| {
+        let mut command = Command::cargo_bin("basecamp").unwrap();
+        command.args(args).current_dir(&workspace);
+        command
+    };
+    basecamp(&["install", "frontend"]).assert().success();
+    let shared_clone = basecamp_dir.join("shared/shared-ui/.git");
+    assert!(shared_clone.is_dir());
+
+    // Linking it into another codebase fails after the link, on a revision that doesn't exist
+    std::fs::write(basecamp_dir.join("codebases.yaml"), format!("{}    rev: v9.9.9\n", codebases)).unwrap();
+    basecamp(&["install", "mobile"]).assert().code(2);
+
+    // A path leading out of the workspace is ignored too
+    let outside = temp_path.join("outside");
+    std::fs::create_dir_all(&outside).unwrap();
+    let state_dir = basecamp_dir.join("state");
+    let checkpoint_path = std::fs::read_dir(&state_dir).unwrap().next().unwrap().unwrap().path();
+    let mut checkpoint: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&checkpoint_path).unwrap()).unwrap();
+    assert!(checkpoint["repositories"][0].get("path").is_none(), "{}", checkpoint);
+    checkpoint["repositories"][0]["path"] = serde_json::json!("../../outside");
+    std::fs::write(&checkpoint_path, checkpoint.to_string()).unwrap();
+
+    // The shared clone existed before the failed run, so resuming leaves it alone
+    basecamp(&["install", "--resume"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("partial clone").not());
+    assert!(shared_clone.is_dir());
+    assert!(outside.exists());
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_install_non_git_conflicts() {
+    // Setup - a local "organization" and directories that aren't clones where they'd go
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let (_, workspace) = common::local_org_workspace(
+        &temp_path,
+        &["api-server", "cache"],
+        "",
+        "codebases:\n  backend: [api-server, cache]\n",
+    );
+    let basecamp_dir = workspace.join(".basecamp");
+    for name in ["api-server", "cache"] {
+        std::fs::create_dir_all(workspace.join("backend").join(name)).unwrap();
+        std::fs::write(workspace.join("backend").join(name).join("notes.txt"), "notes").unwrap();
+    }
+    let basecamp = |args: &[&str]| {
+        let mut command = Command::cargo_bin("basecamp").unwrap();
+        command.args(args).current_dir(&workspace);
+        command
+    };
+
+    // Reported as conflicts and left alone, instead of passing for installed repositories
+    basecamp(&["install"])
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains("2 repositories weren't cloned because a directory is in the way"))
+        .stdout(predicate::str::contains("--backup-conflicts"))
+        .stdout(predicate::str::contains("✗ 2 conflicts · "))
+        .stderr(predicate::str::contains("exists but isn't a git repository"));
+    assert!(workspace.join("backend/api-server/notes.txt").exists());
+    assert!(!workspace.join("backend/api-server/.git").exists());
+
+    // Both options can't be combined
+    basecamp(&["install", "--overwrite-non-git", "--backup-conflicts"]).assert().failure();
+
+    // Moved aside, then cloned
+    std::fs::remove_dir_all(workspace.join("backend/cache")).unwrap();
+    basecamp(&["install", "--backup-conflicts"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Cloned 'api-server' successfully"));
+    assert!(workspace.join("backend/api-server/.git").exists());
+    let backups: Vec<_> = std::fs::read_dir(basecamp_dir.join("conflicts/backend")).unwrap().collect();
+    assert_eq!(backups.len(), 1);
+    assert!(backups[0].as_ref().unwrap().path().join("notes.txt").exists());
+
+    // Deleted, then cloned; an empty directory is no conflict
+    std::fs::remove_dir_all(workspace.join("backend/api-server")).unwrap();
+    std::fs::create_dir_all(workspace.join("backend/api-server")).unwrap();
+    std::fs::remove_dir_all(workspace.join("backend/cache")).unwrap();
+    std::fs::create_dir_all(workspace.join("backend/cache")).unwrap();
+    std::fs::write(workspace.join("backend/cache/notes.txt"), "notes").unwrap();
+    basecamp(&["install", "--overwrite-non-git"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Cloned 'api-server' successfully"))
+        .stdout(predicate::str::contains("Cloned 'cache' successfully"));
+    assert!(workspace.join("backend/cache/.git").exists());
+    assert!(!workspace.join("backend/cache/notes.txt").exists());
+
+    // Overwritten directories went to the trash, and undo swaps them back for the clones
+    basecamp(&["undo", "--yes"]).assert().success();
+    assert!(workspace.join("backend/cache/notes.txt").exists());
+    assert!(!workspace.join("backend/cache/.git").exists());
+    assert!(workspace.join("backend/api-server/.git").exists());
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_unsafe_names_rejected() {
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let workspace = temp_path.join("workspace");
+    let basecamp_dir = common::create_test_config(&workspace);
+    let basecamp = |args: &[&str]| {
+        let mut command = Command::cargo_bin("basecamp").unwrap();
+        command.args(args).current_dir(&workspace);
+        command
+    };
+
+    // Names that would lead out of the workspace can't be added
+    basecamp(&["add", "../outside", "web-client"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'../outside' is not a valid codebase name"));
+    basecamp(&["add", "frontend", "../../etc"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'../../etc' is not a valid repository name"));
+
+    // Nor loaded from a hand-edited codebases.yaml, before anything is cloned or deleted
+    for codebases in [
+        "codebases:\n  ../outside: [web-client]\n",
+        "codebases:\n  frontend: [\"../../etc\"]\n",
+        "codebases:\n  frontend: [web-client]\nrepositories:\n  web-client:\n    dir: ../web\n",
+    ] {
+        std::fs::write(basecamp_dir.join("codebases.yaml"), codebases).unwrap();
+        basecamp(&["remove", "frontend", "--yes"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Invalid configuration"));
+        basecamp(&["validate"]).assert().failure();
+    }
+    assert!(!temp_path.join("outside").exists());
+
+    // Cleanup
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_compose_namespaces_references_and_paths() {
+    // Setup - two repositories with compose files referring to their own services
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let workspace = temp_path.join("workspace");
+    let basecamp_dir = common::create_test_config(&workspace);
+    std::fs::write(basecamp_dir.join("codebases.yaml"), "codebases:\n  backend: [api-server, cache]\n").unwrap();
+
+    let api = workspace.join("backend/api-server");
+    std::fs::create_dir_all(&api).unwrap();
+    std::fs::write(
+        api.join("compose.yaml"),
+        "services:\n  api:\n    build: docker\n    env_file: [config/dev.env, /etc/shared.env]\n    \
+         volumes: [\"./data:/data\", \"logs:/logs\"]\n  sidecar:\n    build: https://github.com/org/sidecar.git\n    \
+         network_mode: service:api\n    volumes_from: [\"api:ro\", \"container:external\"]\n",
+    )
+    .unwrap();
+    let cache = workspace.join("backend/cache");
+    std::fs::create_dir_all(&cache).unwrap();
+    std::fs::write(cache.join("compose.yaml"), "services:\n  redis:\n    network_mode: host\n").unwrap();
+
+    Command::cargo_bin("basecamp")
+        .unwrap()
+        .args(["compose", "backend"])
+        .current_dir(&workspace)
+        .assert()
+        .success();
+    let stack: serde_yaml::Value =
+        serde_yaml::from_str(&std::fs::read_to_string(workspace.join("backend/compose.yaml")).unwrap()).unwrap();
+    let services = &stack["services"];
+
+    // Relative paths, with or without a leading '.', point into the repository
+    let api = &services["api-server-api"];
+    assert_eq!(api["build"], "./api-server/docker");
+    assert_eq!(api["env_file"][0], "./api-server/config/dev.env");
+    assert_eq!(api["env_file"][1], "/etc/shared.env");
+    assert_eq!(api["volumes"][0], "./api-server/data:/data");
+    assert_eq!(api["volumes"][1], "logs:/logs");
+
+    // Service references follow the renamed services, other targets are kept
+    let sidecar = &services["api-server-sidecar"];
+    assert_eq!(sidecar["build"], "https://github.com/org/sidecar.git");
+    assert_eq!(sidecar["network_mode"], "service:api-server-api");
+    assert_eq!(sidecar["volumes_from"][0], "api-server-api:ro");
+    assert_eq!(sidecar["volumes_from"][1], "container:external");
+    assert_eq!(services["cache-redis"]["network_mode"], "host");
+
     // Cleanup
     common::teardown(temp_dir);
 }
@@ -1,7 +1,28 @@
+// Each test crate uses its own subset of these helpers
+#![allow(dead_code)]
+
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use tempfile::TempDir;
 
+/// Point HOME and XDG_CONFIG_HOME at an empty directory for the whole test run
+///
+/// Keeps the user-level config.yaml of whoever runs the tests out of loaded configs.
+/// Call it first in every test that loads a config or reads the home directory.
+pub fn isolate_home() {
+    static HOME: OnceLock<TempDir> = OnceLock::new();
+    HOME.get_or_init(|| {
+        let home = TempDir::new().expect("Failed to create temporary home directory");
+        // Set once, before any test of the run reads them
+        unsafe {
+            std::env::set_var("HOME", home.path());
+            std::env::set_var("XDG_CONFIG_HOME", home.path().join(".config"));
+        }
+        home
+    });
+}
+
 /// Create a temporary directory and return its path
 pub fn setup_temp_dir() -> (TempDir, PathBuf) {
     let temp_dir = TempDir::new().expect("Failed to create temporary directory");
@@ -41,3 +62,35 @@ pub fn teardown(temp_dir: TempDir) {
         .close()
         .expect("Failed to remove temporary directory");
 }
+
+/// Commit an empty tree on top of HEAD
+pub fn commit_empty(repo: &git2::Repository, message: &str) {
+    let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+    let tree_id = repo.index().unwrap().write_tree().unwrap();
+    let tree = repo.find_tree(tree_id).unwrap();
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)
+        .unwrap();
+}
+
+/// A workspace cloning from a local "organization" of repositories with one commit each
+///
+/// config.yaml points `github_url` at the organization, followed by `settings`,
+/// and codebases.yaml holds `codebases`. Returns the organization and the
+/// workspace directories.
+pub fn local_org_workspace(temp_path: &Path, repos: &[&str], settings: &str, codebases: &str) -> (PathBuf, PathBuf) {
+    let org = temp_path.join("org");
+    for name in repos {
+        let origin = git2::Repository::init(org.join(format!("{}.git", name))).unwrap();
+        commit_empty(&origin, "Initial commit");
+    }
+
+    let workspace = temp_path.join("workspace");
+    let basecamp_dir = workspace.join(".basecamp");
+    fs::create_dir_all(&basecamp_dir).expect("Failed to create .basecamp directory");
+    fs::write(basecamp_dir.join("config.yaml"), format!("github_url: {}\n{}", org.display(), settings))
+        .expect("Failed to write config.yaml file");
+    fs::write(basecamp_dir.join("codebases.yaml"), codebases).expect("Failed to write codebases.yaml file");
+    (org, workspace)
+}
@@ -1,256 +1,125 @@
 mod common;
 
-use basecamp::config::{Config, CodebasesConfig};
-use basecamp::error::{BasecampError, BasecampResult};
-use std::path::PathBuf;
-use std::fs::File;
-use std::io::Write;
+use basecamp::config::{Config, CodebasesConfig, GitConfig, UpdateStrategy};
+use basecamp::error::BasecampError;
+use basecamp::template::{self, Vars};
+use basecamp::validate::{self, Severity};
+use basecamp::yaml_edit;
+use std::path::{Path, PathBuf};
 
 #[test]
 fn test_config_load() {
-    // Save the original directory to ensure we always go back
-    let original_dir = std::env::current_dir().unwrap();
-    
-    // Use a block with a finally-like pattern to ensure cleanup
-    let result = std::panic::catch_unwind(|| {
-        // Setup
-        let (temp_dir, temp_path) = common::setup_temp_dir();
-        println!("Test config_load using temp directory: {:?}", temp_path);
-        
-        // Create test config files
-        let basecamp_dir = common::create_test_config(&temp_path);
-        println!("Created test config in: {:?}", basecamp_dir);
-        
-        // Verify test files were created correctly
-        assert!(basecamp_dir.exists(), "Basecamp directory not created: {:?}", basecamp_dir);
-        assert!(basecamp_dir.join("config.yaml").exists(), "config.yaml not created");
-        assert!(basecamp_dir.join("codebases.yaml").exists(), "codebases.yaml not created");
-
-        // Set working directory to temp_path
-        println!("Changing working directory from {:?} to {:?}", original_dir, temp_path);
-        std::env::set_current_dir(&temp_path).unwrap();
-
-        // Test loading the config
-        let config = Config::load(&PathBuf::new()).expect("Failed to load config");
-
-        // Verify
-        assert_eq!(config.git_config.github_url, "https://github.com/test-org");
-        
-        // Print codebases for debugging
-        println!("Loaded codebases: {:?}", config.codebases_config.codebases);
-        
-        // Expected repositories based on common::create_test_config
-        assert_eq!(config.codebases_config.codebases.len(), 2, 
-                   "Expected 2 codebases (frontend and backend), found: {:?}", 
-                   config.codebases_config.codebases);
-        assert!(config.codebases_config.codebases.contains_key("frontend"), 
-                "Frontend codebase not found in: {:?}", config.codebases_config.codebases);
-        assert!(config.codebases_config.codebases.contains_key("backend"), 
-                "Backend codebase not found in: {:?}", config.codebases_config.codebases);
-        
-        // Print repository counts for debugging
-        let frontend_repos = config.codebases_config.codebases.get("frontend").unwrap();
-        println!("Frontend repositories: {:?}", frontend_repos);
-        
-        let backend_repos = config.codebases_config.codebases.get("backend").unwrap();
-        println!("Backend repositories: {:?}", backend_repos);
-        
-        assert_eq!(frontend_repos.len(), 2,
-                  "Expected 2 frontend repos, found: {:?}", frontend_repos);
-        assert_eq!(backend_repos.len(), 2,
-                  "Expected 2 backend repos, found: {:?}", backend_repos);
-
-        // Cleanup
-        println!("Test cleanup: removing temp directory");
-        common::teardown(temp_dir);
-    });
-    
-    // Always return to the original directory
-    println!("Returning to original directory: {:?}", original_dir);
-    if let Err(e) = std::env::set_current_dir(&original_dir) {
-        eprintln!("Failed to return to original directory: {}", e);
-    }
-    
-    // Re-throw any panic that occurred in the test
-    if let Err(e) = result {
-        std::panic::resume_unwind(e);
-    }
-    
-    println!("Test config_load completed");
+    common::isolate_home();
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+
+    // Create test config files
+    let basecamp_dir = common::create_test_config(&temp_path);
+
+    // Verify test files were created correctly
+    assert!(basecamp_dir.exists(), "Basecamp directory not created: {:?}", basecamp_dir);
+    assert!(basecamp_dir.join("config.yaml").exists(), "config.yaml not created");
+    assert!(basecamp_dir.join("codebases.yaml").exists(), "codebases.yaml not created");
+
+    // Load the config from the workspace root, without changing the working directory
+    let config = Config::load(&temp_path).expect("Failed to load config");
+
+    // Verify
+    assert_eq!(config.workspace.root(), temp_path);
+    assert_eq!(config.git_config.github_url, "https://github.com/test-org");
+
+    // Expected repositories based on common::create_test_config
+    assert_eq!(config.codebases_config.codebases.len(), 2,
+               "Expected 2 codebases (frontend and backend), found: {:?}",
+               config.codebases_config.codebases);
+    assert!(config.codebases_config.codebases.contains_key("frontend"),
+            "Frontend codebase not found in: {:?}", config.codebases_config.codebases);
+    assert!(config.codebases_config.codebases.contains_key("backend"),
+            "Backend codebase not found in: {:?}", config.codebases_config.codebases);
+
+    let frontend_repos = config.codebases_config.codebases.get("frontend").unwrap();
+    let backend_repos = config.codebases_config.codebases.get("backend").unwrap();
+
+    assert_eq!(frontend_repos.len(), 2,
+              "Expected 2 frontend repos, found: {:?}", frontend_repos);
+    assert_eq!(backend_repos.len(), 2,
+              "Expected 2 backend repos, found: {:?}", backend_repos);
+
+    // Repository paths are derived from the workspace root
+    assert_eq!(
+        config.workspace.repo_path("frontend", "react-app"),
+        temp_path.join("frontend").join("react-app")
+    );
+
+    // Loading through the .basecamp directory selects the same workspace
+    let config = Config::load(&basecamp_dir).expect("Failed to load config");
+    assert_eq!(config.workspace.root(), temp_path);
+
+    // Cleanup
+    common::teardown(temp_dir);
 }
 
 #[test]
 fn test_config_save() {
-    // Save the original directory to ensure we always go back
-    let original_dir = std::env::current_dir().unwrap();
-    
-    // Use a block with a finally-like pattern to ensure cleanup
-    let result = std::panic::catch_unwind(|| {
-        // Setup
-        let (temp_dir, temp_path) = common::setup_temp_dir();
-        println!("Test config_save using temp directory: {:?}", temp_path);
-
-        // Create a config to save
-        let mut config = Config::new();
-        config
-            .set_github_url("https://github.com/test-org".to_string())
-            .unwrap();
-
-        // Add some repositories
-        let repos_to_add = ["repo1".to_string(), "repo2".to_string()];
-        config
-            .add_repositories("test-codebase", &repos_to_add)
-            .unwrap();
-
-        // Print config state before saving
-        println!("Config before saving: {{");
-        println!("  git_config: {{ github_url: {} }}", config.git_config.github_url);
-        println!("  codebases_config: {{ codebases: {:?} }}", config.codebases_config.codebases);
-        println!("}}");
-
-        // Ensure .basecamp directory exists in temp_path
-        let basecamp_dir = temp_path.join(".basecamp");
-        println!("Creating .basecamp directory at: {:?}", basecamp_dir);
-        std::fs::create_dir_all(&basecamp_dir).expect("Failed to create .basecamp directory");
-        assert!(basecamp_dir.exists(), "Failed to create basecamp directory");
-        
-        // Create a custom config with absolute paths for testing
-        struct TestConfig {
-            config: Config,
-            base_path: PathBuf,
-        }
-        
-        impl TestConfig {
-            fn get_config_path(&self) -> PathBuf {
-                self.base_path.join(".basecamp/config.yaml")
-            }
-            
-            fn get_codebases_path(&self) -> PathBuf {
-                self.base_path.join(".basecamp/codebases.yaml")
-            }
-            
-            fn save(&self) -> BasecampResult<()> {
-                // Save git config
-                let yaml = serde_yaml::to_string(&self.config.git_config)?;
-                let mut file = File::create(self.get_config_path())?;
-                file.write_all(yaml.as_bytes())?;
-                
-                // Save codebases config
-                let yaml = serde_yaml::to_string(&self.config.codebases_config)?;
-                let mut file = File::create(self.get_codebases_path())?;
-                file.write_all(yaml.as_bytes())?;
-                
-                Ok(())
-            }
-            
-            fn load(&self) -> BasecampResult<Config> {
-                // Load git config
-                let git_config = if self.get_config_path().exists() {
-                    let content = std::fs::read_to_string(self.get_config_path())?;
-                    serde_yaml::from_str(&content)?
-                } else {
-                    return Err(BasecampError::FileNotFound(self.get_config_path()));
-                };
-                
-                // Load codebases config
-                let codebases_config = if self.get_codebases_path().exists() {
-                    let content = std::fs::read_to_string(self.get_codebases_path())?;
-                    serde_yaml::from_str(&content)?
-                } else {
-                    CodebasesConfig::default()
-                };
-                
-                Ok(Config {
-                    git_config,
-                    codebases_config,
-                })
-            }
-        }
-        
-        // Create a test config with the absolute path
-        let test_config = TestConfig {
-            config,
-            base_path: temp_path.clone(),
-        };
-
-        // Save the configuration using absolute paths
-        println!("Saving configuration using direct file operations...");
-        test_config.save().expect("Failed to save config");
-        
-        // Verify the config files exist
-        let config_path = test_config.get_config_path();
-        let codebases_path = test_config.get_codebases_path();
-        
-        // List directory contents after saving
-        println!("Directory contents after saving:");
-        if let Ok(entries) = std::fs::read_dir(&basecamp_dir) {
-            for entry in entries {
-                if let Ok(entry) = entry {
-                    println!("  {:?}", entry.path());
-                }
-            }
-        } else {
-            println!("  Failed to read directory contents");
-        }
-        
-        assert!(config_path.exists(), 
-                "Config file not created at: {:?}", config_path);
-        
-        assert!(codebases_path.exists(), 
-                "Codebases file not created at: {:?}", codebases_path);
-
-        // If codebases file exists, read and print its content
-        if codebases_path.exists() {
-            let codebases_content = std::fs::read_to_string(&codebases_path)
-                .expect("Failed to read codebases.yaml");
-            println!("Raw codebases.yaml content:\n{}", codebases_content);
-        } else {
-            println!("Codebases file does not exist, cannot read content");
-        }
-
-        // Load the config back and verify contents
-        println!("Loading saved configuration...");
-        let loaded_config = test_config.load().expect("Failed to load saved config");
-        assert_eq!(loaded_config.git_config.github_url, "https://github.com/test-org");
-        assert_eq!(loaded_config.codebases_config.codebases.len(), 1);
-        assert!(loaded_config.codebases_config.codebases.contains_key("test-codebase"), 
-                "test-codebase not found in codebases: {:?}", 
-                loaded_config.codebases_config.codebases);
-        
-        // Get the repositories and verify each expected one exists
-        let loaded_repos = loaded_config.codebases_config.codebases.get("test-codebase")
-            .expect("test-codebase not found in loaded config");
-        println!("Loaded repositories: {:?}", loaded_repos);
-        
-        // Check if each repository was saved and loaded correctly
-        for repo in &repos_to_add {
-            assert!(loaded_repos.contains(repo), 
-                    "Repository {} not found in loaded repositories: {:?}", 
-                    repo, loaded_repos);
-        }
-        
-        // Check the total count matches
-        assert_eq!(loaded_repos.len(), repos_to_add.len(), 
-                  "Expected {} repositories, found {}", 
-                  repos_to_add.len(), loaded_repos.len());
-
-        // Cleanup
-        println!("Test cleanup: removing temp directory");
-        common::teardown(temp_dir);
-    });
-    
-    // Always return to the original directory
-    println!("Returning to original directory: {:?}", original_dir);
-    if let Err(e) = std::env::set_current_dir(&original_dir) {
-        eprintln!("Failed to return to original directory: {}", e);
-    }
-    
-    // Re-throw any panic that occurred in the test
-    if let Err(e) = result {
-        std::panic::resume_unwind(e);
+    common::isolate_home();
+    // Setup
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+
+    // Create a config to save
+    let mut config = Config::new();
+    config
+        .set_github_url("https://github.com/test-org".to_string())
+        .unwrap();
+
+    // Add some repositories
+    let repos_to_add = ["repo1".to_string(), "repo2".to_string()];
+    config
+        .add_repositories("test-codebase", &repos_to_add)
+        .unwrap();
+
+    // Save to the workspace root; the .basecamp directory is created as needed
+    config.save(&temp_path).expect("Failed to save config");
+
+    let config_path = temp_path.join(".basecamp/config.yaml");
+    let codebases_path = temp_path.join(".basecamp/codebases.yaml");
+    assert!(config_path.exists(),
+            "Config file not created at: {:?}", config_path);
+    assert!(codebases_path.exists(),
+            "Codebases file not created at: {:?}", codebases_path);
+
+    // Load the config back and verify contents
+    let loaded_config = Config::load(&temp_path).expect("Failed to load saved config");
+    assert_eq!(loaded_config.git_config.github_url, "https://github.com/test-org");
+    assert_eq!(loaded_config.codebases_config.codebases.len(), 1);
+    assert!(loaded_config.codebases_config.codebases.contains_key("test-codebase"),
+            "test-codebase not found in codebases: {:?}",
+            loaded_config.codebases_config.codebases);
+
+    // Get the repositories and verify each expected one exists
+    let loaded_repos = loaded_config.codebases_config.codebases.get("test-codebase")
+        .expect("test-codebase not found in loaded config");
+
+    // Check if each repository was saved and loaded correctly
+    for repo in &repos_to_add {
+        assert!(loaded_repos.contains(repo),
+                "Repository {} not found in loaded repositories: {:?}",
+                repo, loaded_repos);
     }
-    
-    println!("Test config_save completed");
+
+    // Check the total count matches
+    assert_eq!(loaded_repos.len(), repos_to_add.len(),
+              "Expected {} repositories, found {}",
+              repos_to_add.len(), loaded_repos.len());
+
+    // Saving without a root writes back to the workspace the config was loaded from
+    let mut reloaded = loaded_config.clone();
+    reloaded.add_repositories("test-codebase", &["repo3".to_string()]).unwrap();
+    reloaded.save(&PathBuf::new()).expect("Failed to save config");
+    let loaded_config = Config::load(&temp_path).expect("Failed to load saved config");
+    assert_eq!(loaded_config.codebases_config.codebases["test-codebase"].len(), 3);
+
+    // Cleanup
+    common::teardown(temp_dir);
 }
 
 #[test]
@@ -311,3 +180,409 @@ fn test_remove_repositories() {
     assert!(!repos.contains(&"repo2".to_string()));
     assert!(repos.contains(&"repo3".to_string()));
 }
+
+#[test]
+fn test_dependency_levels() {
+    // Setup
+    let mut config = Config::new();
+    let deps = &mut config.codebases_config.depends_on;
+    deps.insert("web-client".to_string(), vec!["api-server".to_string()]);
+    deps.insert("api-server".to_string(), vec!["database".to_string(), "unknown".to_string()]);
+
+    // Test
+    let repos: Vec<String> = ["web-client", "ui-component", "api-server", "database"]
+        .iter()
+        .map(|r| r.to_string())
+        .collect();
+    let levels = config.dependency_levels(&repos).unwrap();
+
+    // Verify
+    assert_eq!(levels.len(), 3);
+    assert_eq!(levels[0], vec!["ui-component".to_string(), "database".to_string()]);
+    assert_eq!(levels[1], vec!["api-server".to_string()]);
+    assert_eq!(levels[2], vec!["web-client".to_string()]);
+
+    // Cycles are reported instead of looping forever
+    config
+        .codebases_config
+        .depends_on
+        .insert("database".to_string(), vec!["web-client".to_string()]);
+    assert!(matches!(
+        config.dependency_levels(&repos),
+        Err(BasecampError::DependencyCycle(_))
+    ));
+}
+
+#[test]
+fn test_codebase_groups() {
+    // Nested groups are flattened into `group/codebase` names
+    let codebases: CodebasesConfig = serde_yaml::from_str(
+        "codebases:\n  platform:\n    frontend: [web-client]\n    backend: [api]\n  tools: [cli]\n",
+    )
+    .unwrap();
+    let mut config = Config::new();
+    config.codebases_config = codebases;
+    assert_eq!(
+        config.list_codebases(),
+        vec!["platform/backend", "platform/frontend", "tools"]
+    );
+
+    // A group selects its codebases, a codebase only itself
+    assert!(config.is_group("platform"));
+    assert_eq!(
+        config.select_codebases(Some("platform")).unwrap(),
+        vec!["platform/backend".to_string(), "platform/frontend".to_string()]
+    );
+    assert_eq!(
+        config.select_codebases(Some("platform/frontend")).unwrap(),
+        vec!["platform/frontend".to_string()]
+    );
+    assert!(matches!(
+        config.select_codebases(Some("plat")),
+        Err(BasecampError::CodebaseNotFound(_))
+    ));
+
+    // Codebases added to a group are written back nested
+    config
+        .add_repositories("platform/data", &["etl".to_string()])
+        .unwrap();
+    let yaml = serde_yaml::to_string(&config.codebases_config).unwrap();
+    assert_eq!(
+        yaml,
+        "codebases:\n  platform:\n    backend:\n    - api\n    data:\n    - etl\n    frontend:\n    - web-client\n  tools:\n  - cli\n"
+    );
+}
+
+#[test]
+fn test_codebase_aliases() {
+    let mut config = Config::new();
+    config
+        .add_repositories("frontend", &["web-client".to_string()])
+        .unwrap();
+    config
+        .add_repositories("fe", &["legacy".to_string()])
+        .unwrap();
+    config.git_config.aliases.insert("front".to_string(), "frontend".to_string());
+    config.git_config.aliases.insert("fe".to_string(), "frontend".to_string());
+
+    // Aliases resolve to their codebase, other names are kept as given
+    assert_eq!(config.resolve_codebase("front"), "frontend");
+    assert_eq!(config.resolve_codebase("backend"), "backend");
+
+    // A codebase named like an alias wins
+    assert_eq!(config.resolve_codebase("fe"), "fe");
+}
+
+#[test]
+fn test_duplicate_repositories() {
+    let mut config = Config::new();
+    config
+        .add_repositories("frontend", &["web-client".to_string(), "design".to_string()])
+        .unwrap();
+    config
+        .add_repositories("mobile", &["app".to_string(), "design".to_string()])
+        .unwrap();
+
+    // Repositories listed in several codebases are reported with those codebases
+    assert_eq!(
+        config.duplicate_repositories(),
+        vec![(
+            "design".to_string(),
+            vec!["frontend".to_string(), "mobile".to_string()]
+        )]
+    );
+
+    // Intentional duplicates are not
+    config.update_repo_config("design", |settings| settings.allow_duplicate = true);
+    assert!(config.duplicate_repositories().is_empty());
+
+    // Settings back at their defaults are dropped
+    config.update_repo_config("design", |settings| settings.allow_duplicate = false);
+    assert!(config.codebases_config.repositories.is_empty());
+}
+
+#[test]
+fn test_cache_config_defaults() {
+    // Missing cache section falls back to the default TTL and isn't written back
+    let git_config: GitConfig = serde_yaml::from_str("github_url: https://github.com/test-org").unwrap();
+    assert_eq!(git_config.cache.ttl, 60);
+    assert!(!serde_yaml::to_string(&git_config).unwrap().contains("cache"));
+
+    let git_config: GitConfig =
+        serde_yaml::from_str("github_url: https://github.com/test-org\ncache:\n  ttl: 0").unwrap();
+    assert_eq!(git_config.cache.ttl, 0);
+}
+
+#[test]
+fn test_apply_profile() {
+    let mut git_config: GitConfig = serde_yaml::from_str(
+        "github_url: https://github.com/test-org\nprofiles:\n  oss:\n    github_url: git@github.com:oss-org\n    update: rebase",
+    )
+    .unwrap();
+    assert_eq!(git_config.active_profile(), None);
+
+    // Only the settings defined by the profile are overridden
+    git_config.apply_profile("oss").unwrap();
+    assert_eq!(git_config.github_url, "git@github.com:oss-org");
+    assert_eq!(git_config.update, UpdateStrategy::Rebase);
+    assert_eq!(git_config.cache.ttl, 60);
+    assert_eq!(git_config.active_profile(), Some("oss"));
+
+    assert!(matches!(
+        git_config.apply_profile("work"),
+        Err(BasecampError::ProfileNotFound(_))
+    ));
+}
+
+#[test]
+fn test_validate_codebases_file() {
+    let content = "codebases:\n  frontend:\n    - web-client\n    - web-client\n    - bad name\n  empty: []\ntaks: {}\n";
+    let diagnostics = validate::validate_codebases_file(Path::new("codebases.yaml"), content);
+
+    // Every problem is reported with its line
+    let found: Vec<(Option<usize>, Severity)> =
+        diagnostics.iter().map(|d| (d.line, d.severity)).collect();
+    assert_eq!(
+        found,
+        vec![
+            (Some(4), Severity::Error),
+            (Some(5), Severity::Error),
+            (Some(6), Severity::Warning),
+            (Some(7), Severity::Warning),
+        ]
+    );
+    assert_eq!(diagnostics[3].help.as_deref(), Some("Did you mean 'tasks'?"));
+
+    assert!(validate::is_valid_repo_name("web-client.v2"));
+    assert!(!validate::is_valid_repo_name(".."));
+    assert!(!validate::is_valid_repo_name("../etc"));
+    assert!(!validate::is_valid_repo_name("web\nclient"));
+
+    assert!(validate::is_valid_codebase_name("platform/frontend"));
+    for name in ["", "..", "../outside", "/etc", "a//b", "platform/../..", "c:\\temp", ".basecamp/shared", "a\tb"] {
+        assert!(!validate::is_valid_codebase_name(name), "{:?}", name);
+    }
+    assert!(validate::is_valid_dir_name("legacy-api"));
+    assert!(!validate::is_valid_dir_name("../legacy-api"));
+}
+
+#[test]
+fn test_validate_tls_settings() {
+    let content = "github_url: https://git.corp.example/team\ntls:\n  ca_bundle: corp-ca.pem\n  insecure_skip_verify: true\n  verify: false\n";
+    let diagnostics = validate::validate_config_file(Path::new("config.yaml"), content);
+
+    let found: Vec<(Option<usize>, Severity)> =
+        diagnostics.iter().map(|d| (d.line, d.severity)).collect();
+    assert_eq!(
+        found,
+        vec![(Some(4), Severity::Warning), (Some(5), Severity::Warning)]
+    );
+    assert!(diagnostics[0].message.contains("verification is disabled"));
+}
+
+/// Self-signed certificate authority used as a `tls.ca_bundle`
+const TEST_CA: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIBjTCCATOgAwIBAgIUS4gY+5UARXDHOx7iaPOxbTe2YagwCgYIKoZIzj0EAwIw
+GzEZMBcGA1UEAwwQQmFzZUNhbXAgVGVzdCBDQTAgFw0yNjEwMTgwNjQyMjhaGA8y
+MTI2MDkyNDA2NDIyOFowGzEZMBcGA1UEAwwQQmFzZUNhbXAgVGVzdCBDQTBZMBMG
+ByqGSM49AgEGCCqGSM49AwEHA0IABAV82I3IEM7hxMcPUDTYGD2kYlPuTf/8MNoe
+Q5n4pyN3L7H8UvrnZRHyjk5w4vFBKwYsyhYWMrhtMi/q01QxpqmjUzBRMB0GA1Ud
+DgQWBBT6kLUrvoFFzR8OSy+/sSvrD9NvAzAfBgNVHSMEGDAWgBT6kLUrvoFFzR8O
+Sy+/sSvrD9NvAzAPBgNVHRMBAf8EBTADAQH/MAoGCCqGSM49BAMCA0gAMEUCIBXG
+bdjBm/OFfRM4g8WCdLDA1q3kBxq7l+XV5MbwvUK0AiEA9CmB1KZQPk7tdkQW+Y4r
+XHpMyeO5lWg6D5/eLajMk/s=
+-----END CERTIFICATE-----
+";
+
+#[test]
+fn test_tls_ca_bundle() {
+    common::isolate_home();
+    let (_temp_dir, temp_path) = common::setup_temp_dir();
+    let basecamp_dir = common::create_test_config(&temp_path);
+    std::fs::write(
+        basecamp_dir.join("config.yaml"),
+        "github_url: https://git.corp.example/team\ntls:\n  ca_bundle: corp-ca.pem\n",
+    )
+    .unwrap();
+
+    // A missing bundle is reported instead of silently trusting the defaults
+    match Config::load(&temp_path) {
+        Err(BasecampError::InvalidConfig(message)) => assert!(message.contains("corp-ca.pem"), "{}", message),
+        other => panic!("expected a missing bundle error, got {:?}", other.map(|_| ())),
+    }
+
+    // Resolved against the workspace root, and trusted once for the process
+    std::fs::write(temp_path.join("corp-ca.pem"), TEST_CA).unwrap();
+    Config::load(&temp_path).expect("Failed to load config");
+    Config::load(&temp_path).expect("Failed to load config again");
+
+    // Another bundle can't replace it during the same run
+    std::fs::write(temp_path.join("other-ca.pem"), TEST_CA).unwrap();
+    std::fs::write(
+        basecamp_dir.join("config.yaml"),
+        "github_url: https://git.corp.example/team\ntls:\n  ca_bundle: other-ca.pem\n",
+    )
+    .unwrap();
+    assert!(matches!(Config::load(&temp_path), Err(BasecampError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_validate_ui_settings() {
+    let content = "github_url: https://github.com/org\nui:\n  ascii: true\n  theme:\n    success: cyan\n    spinner: \"|/-\\\\\"\n    info: blue\n";
+    let diagnostics = validate::validate_config_file(Path::new("config.yaml"), content);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].line, Some(7));
+    assert!(diagnostics[0].message.contains("info"));
+}
+
+#[test]
+fn test_expand_home() {
+    common::isolate_home();
+    let home = Config::home_dir().expect("home directory");
+
+    assert_eq!(Config::expand_home(Path::new("~/.ssh/id_ed25519")), home.join(".ssh/id_ed25519"));
+    assert_eq!(Config::expand_home(Path::new("~\\.ssh\\id_ed25519")), home.join(".ssh\\id_ed25519"));
+    assert_eq!(Config::expand_home(Path::new("~")), home);
+
+    // Only a leading home reference is expanded
+    assert_eq!(Config::expand_home(Path::new("/keys/~/id")), Path::new("/keys/~/id"));
+    assert_eq!(Config::expand_home(Path::new("~other/id")), Path::new("~other/id"));
+}
+
+#[test]
+fn test_template_placeholders() {
+    common::isolate_home();
+    let home = Config::home_dir().expect("home directory");
+    let vars = Vars {
+        org: Some("acme".to_string()),
+        ..Vars::default()
+    }
+    .with_repo("backend", "api");
+
+    assert_eq!(
+        template::expand("{home}/keys/{org}-{codebase}-{repo}", &vars),
+        format!("{}/keys/acme-backend-api", home.display())
+    );
+    if let Ok(path) = std::env::var("PATH") {
+        assert_eq!(template::expand("${PATH}", &vars), path);
+    }
+
+    // Unknown placeholders, unset variables and shell braces are kept
+    for kept in ["{user}", "${BASECAMP_UNSET_TEST_VARIABLE}", "awk '{print $1}'", "{ }", "{org"] {
+        assert_eq!(template::expand(kept, &vars), kept);
+    }
+    assert_eq!(template::expand("{org}", &Vars::default()), "{org}");
+}
+
+#[test]
+fn test_config_placeholders_survive_saving() {
+    common::isolate_home();
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let basecamp_dir = common::create_test_config(&temp_path);
+    std::fs::write(
+        basecamp_dir.join("config.yaml"),
+        "github_url: https://github.com/test-org\nmirrors:\n  backup:\n    url: git@gitlab.com:{org}-backup\n",
+    )
+    .unwrap();
+    std::fs::write(
+        basecamp_dir.join("codebases.yaml"),
+        "codebases:\n  backend: [api]\nrepositories:\n  api:\n    upstream: upstream-{org}/{repo}\n",
+    )
+    .unwrap();
+
+    let mut config = Config::load(&temp_path).expect("Failed to load config");
+    assert_eq!(config.git_config.mirrors["backup"].url, "git@gitlab.com:test-org-backup");
+    assert_eq!(
+        config.extra_remotes("api"),
+        vec![("upstream".to_string(), "https://github.com/upstream-test-org/api.git".to_string())]
+    );
+
+    // Saving writes the placeholders back, not their values
+    config.git_config.parallel = Some(4);
+    config.save_config().expect("Failed to save config");
+    let saved = std::fs::read_to_string(basecamp_dir.join("config.yaml")).unwrap();
+    assert!(saved.contains("url: git@gitlab.com:{org}-backup"), "{}", saved);
+    assert!(saved.contains("parallel: 4"), "{}", saved);
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_yaml_edit_preserves_comments() {
+    let content = "\
+# Team repositories
+codebases:
+  # Customer facing
+  frontend:
+    - web-client # main app
+    - legacy-ui
+
+  backend:
+    - api
+";
+    let old: serde_yaml::Value = serde_yaml::from_str(content).unwrap();
+    let new: serde_yaml::Value = serde_yaml::from_str(
+        "codebases:\n  frontend: [web-client, dashboard]\n  backend: [api]\n  data: [etl]\n",
+    )
+    .unwrap();
+
+    // Only the changed lines are touched
+    let edited = yaml_edit::edit(content, &old, &new).unwrap();
+    assert_eq!(
+        edited,
+        "\
+# Team repositories
+codebases:
+  # Customer facing
+  frontend:
+    - web-client # main app
+    - dashboard
+
+  backend:
+    - api
+  data:
+  - etl
+"
+    );
+}
+
+#[test]
+fn test_yaml_edit_sort_keeps_comments() {
+    let content = "\
+# Team repositories
+codebases:
+  # Customer facing
+  frontend:
+    - web-client # main app
+    # Being replaced
+    - legacy-ui
+
+  backend: [cache, api]
+depends_on:
+  web-client: [api]
+";
+
+    // Entries move with the comments right above them, blank lines stay in place
+    let sorted = yaml_edit::sort(content, &["codebases", "depends_on"]).unwrap();
+    assert_eq!(
+        sorted,
+        "\
+# Team repositories
+codebases:
+  backend:
+  - api
+  - cache
+
+  # Customer facing
+  frontend:
+    # Being replaced
+    - legacy-ui
+    - web-client # main app
+depends_on:
+  web-client: [api]
+"
+    );
+    assert_eq!(yaml_edit::sort(&sorted, &["codebases", "depends_on"]).unwrap(), sorted);
+}
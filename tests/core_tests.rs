@@ -0,0 +1,187 @@
+mod common;
+
+use std::fs;
+use std::sync::{Arc, Barrier, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use basecamp::config::Config;
+use basecamp::core::events::{self, Event};
+use basecamp::core::install::{self, ConflictPolicy, InstallSettings, RepoResult, SkipReason};
+use basecamp::core::{ItemStatus, ProgressSink};
+use basecamp::oplog::OperationLog;
+use basecamp::ui::TransferRate;
+use basecamp::workspace::Workspace;
+
+/// Sink remembering how each item finished
+#[derive(Default)]
+struct Recorder {
+    finished: Mutex<Vec<(String, ItemStatus)>>,
+}
+
+impl ProgressSink for Recorder {
+    fn item_finished(&self, item: &str, status: ItemStatus, _message: &str) {
+        self.finished.lock().unwrap().push((item.to_string(), status));
+    }
+}
+
+impl Recorder {
+    fn take(&self) -> Vec<(String, ItemStatus)> {
+        let mut finished = std::mem::take(&mut *self.finished.lock().unwrap());
+        finished.sort_by(|a, b| a.0.cmp(&b.0));
+        finished
+    }
+}
+
+#[test]
+fn test_transfer_rate() {
+    let started = Instant::now();
+    let mut rate = TransferRate::new(started);
+
+    // Unknown until a second has passed
+    rate.update(4096, started + Duration::from_millis(500));
+    assert_eq!(rate.bytes_per_sec(), 0);
+
+    rate.update(8192, started + Duration::from_secs(2));
+    assert_eq!(rate.bytes_per_sec(), 4096);
+
+    // Measured from the last sample, not the start
+    rate.update(9216, started + Duration::from_millis(2500));
+    assert_eq!(rate.bytes_per_sec(), 4096);
+    rate.update(10240, started + Duration::from_secs(4));
+    assert_eq!(rate.bytes_per_sec(), 1024);
+
+    // A stalled transfer drops to nothing
+    rate.update(10240, started + Duration::from_secs(5));
+    assert_eq!(rate.bytes_per_sec(), 0);
+}
+
+#[test]
+fn test_install_without_terminal() {
+    common::isolate_home();
+    // Setup - a local "organization" with one of the two configured repositories
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let org = temp_path.join("org");
+    let origin = git2::Repository::init(org.join("api-server.git")).unwrap();
+    let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+    let tree = origin.find_tree(origin.index().unwrap().write_tree().unwrap()).unwrap();
+    origin.commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[]).unwrap();
+
+    let workspace = temp_path.join("workspace");
+    common::create_test_config(&workspace);
+    fs::write(
+        workspace.join(".basecamp/config.yaml"),
+        format!("github_url: {}\n", org.display()),
+    )
+    .unwrap();
+    let config = Config::load(&workspace).unwrap();
+    let jobs = vec![
+        ("backend".to_string(), "api-server".to_string()),
+        ("backend".to_string(), "database".to_string()),
+    ];
+    let settings = InstallSettings {
+        parallel: 2,
+        fail_fast: false,
+        update: None,
+        conflicts: ConflictPolicy::default(),
+    };
+
+    // Results come back as data, progress goes to the sink and typed events to subscribers
+    let (subscription, events) = events::channel();
+    let recorder = Recorder::default();
+    let report = install::install(&config, &jobs, settings, &OperationLog::new(&config.workspace, "install"), &recorder);
+
+    assert_eq!((report.cloned, report.failed), (1, 1));
+    assert_eq!(report.repositories[0].result, RepoResult::Cloned);
+    assert!(report.repositories[1].error.is_some());
+    assert_eq!(report.exit_code(), install::EXIT_REPOSITORIES_FAILED);
+    assert!(workspace.join("backend/api-server/.git").exists());
+    assert_eq!(
+        recorder.take(),
+        vec![
+            ("api-server".to_string(), ItemStatus::Succeeded),
+            ("database".to_string(), ItemStatus::Failed),
+        ]
+    );
+
+    let received: Vec<Event> = events.try_iter().collect();
+    assert!(received.iter().any(|event| matches!(
+        event,
+        Event::CloneStarted { repo, path, .. } if repo == "api-server" && path.ends_with("backend/api-server")
+    )));
+    assert!(received.iter().any(|event| matches!(
+        event,
+        Event::CloneFinished { repo, error: None, .. } if repo == "api-server"
+    )));
+    assert!(received.iter().any(|event| matches!(
+        event,
+        Event::CloneFinished { repo, error: Some(_), .. } if repo == "database"
+    )));
+
+    // Installed repositories are skipped on the next run
+    let report = install::install(&config, &jobs[..1], settings, &OperationLog::new(&config.workspace, "install"), &recorder);
+    assert_eq!(report.repositories[0].reason, Some(SkipReason::AlreadyInstalled));
+    assert!(report.is_success());
+    assert_eq!(recorder.take(), vec![("api-server".to_string(), ItemStatus::Skipped)]);
+    assert_eq!(
+        events.try_iter().collect::<Vec<_>>(),
+        vec![Event::RepoSkipped {
+            codebase: "backend".to_string(),
+            repo: "api-server".to_string(),
+            reason: SkipReason::AlreadyInstalled,
+        }]
+    );
+
+    config.save_codebases().unwrap();
+    assert_eq!(
+        events.try_iter().collect::<Vec<_>>(),
+        vec![Event::ConfigSaved { path: workspace.join(".basecamp/codebases.yaml") }]
+    );
+
+    // Nothing is received once the subscription is dropped
+    drop(subscription);
+    config.save_codebases().unwrap();
+    assert!(events.try_iter().next().is_none());
+
+    // Listeners of a run only hear its own events, like the requests of `serve`
+    let subscribed = Barrier::new(2);
+    let saved = Mutex::new(());
+    let heard: Vec<usize> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..2)
+            .map(|_| {
+                scope.spawn(|| {
+                    let run = events::run();
+                    let received = Arc::new(Mutex::new(Vec::new()));
+                    let sink = Arc::clone(&received);
+                    let _subscription = events::subscribe_run(run.id(), move |event| sink.lock().unwrap().push(event.clone()));
+                    subscribed.wait();
+                    let saving = saved.lock().unwrap();
+                    config.save_codebases().unwrap();
+                    drop(saving);
+                    subscribed.wait();
+                    received.lock().unwrap().len()
+                })
+            })
+            .collect();
+        workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+    });
+    assert_eq!(heard, vec![1, 1]);
+
+    common::teardown(temp_dir);
+}
+
+#[test]
+fn test_operation_logs_started_in_the_same_second() {
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let workspace = Workspace::new(&temp_path);
+
+    // Log names have second resolution, so a log written in the same second gets a suffix
+    let log = OperationLog::new(&workspace, "install");
+    let first = log.write(None).unwrap();
+    let second = log.write(None).unwrap();
+    assert_ne!(first, second);
+    assert!(second.to_string_lossy().ends_with("-install-2.log"));
+    assert!(first.exists() && second.exists());
+
+    common::teardown(temp_dir);
+}
@@ -0,0 +1,541 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use basecamp::error::BasecampError;
+use basecamp::git::auth::{self, CredentialChain, CredentialProvider, CredentialRequest, TokenScope};
+use basecamp::git::backend::{self, Backend, BackendKind, Git2Backend};
+use basecamp::git::provider::{CiState, HealthAnswers, HostProvider, RemoteHealth};
+use basecamp::git::{DEFAULT_REMOTE, GitRepo, NetworkTimeouts, RepoStatus, UpdateResult};
+use git2::{Repository, Signature};
+
+#[test]
+fn test_build_repo_url() {
+    assert_eq!(
+        GitRepo::build_repo_url("https://github.com/test-org", "api"),
+        "https://github.com/test-org/api.git"
+    );
+    assert_eq!(
+        GitRepo::build_repo_url("git@github.com:test-org", "api"),
+        "git@github.com:test-org/api.git"
+    );
+    assert_eq!(
+        GitRepo::build_repo_url("http://git.internal:8080/test-org", "api"),
+        "http://git.internal:8080/test-org/api.git"
+    );
+}
+
+#[test]
+fn test_build_web_url() {
+    let cases = [
+        ("https://github.com/test-org", "https://github.com/test-org/api"),
+        ("https://github.com/test-org/", "https://github.com/test-org/api"),
+        ("git@github.com:test-org", "https://github.com/test-org/api"),
+        ("git@git.corp.example:platform/team", "https://git.corp.example/platform/team/api"),
+        ("ssh://git@git.corp.example:2222/test-org", "https://git.corp.example/test-org/api"),
+        ("https://user@gitlab.example/test-org", "https://gitlab.example/test-org/api"),
+        ("http://git.internal:8080/test-org", "http://git.internal:8080/test-org/api"),
+    ];
+
+    for (base, expected) in cases {
+        assert_eq!(GitRepo::build_web_url(base, "api"), expected, "base URL: {}", base);
+    }
+}
+
+/// Commit a file change on the current branch of a repository
+fn commit_file(repo: &Repository, name: &str, content: &str) {
+    let workdir = repo.workdir().unwrap();
+    fs::write(workdir.join(name), content).unwrap();
+
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(name)).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+
+    let signature = Signature::now("Test", "test@example.com").unwrap();
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &signature, &signature, name, &tree, &parents)
+        .unwrap();
+}
+
+#[test]
+fn test_update_fast_forward() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let origin_path = temp_dir.path().join("origin.git");
+    Repository::init_bare(&origin_path).unwrap();
+    let origin_url = origin_path.to_string_lossy().to_string();
+
+    // Publish an initial commit from a first clone
+    let upstream = Repository::clone(&origin_url, temp_dir.path().join("upstream")).unwrap();
+    commit_file(&upstream, "README.md", "one");
+    let mut remote = upstream.find_remote("origin").unwrap();
+    let head = upstream.head().unwrap().name().unwrap().to_string();
+    remote.push(&[format!("{0}:{0}", head)], None).unwrap();
+
+    let local_path = temp_dir.path().join("local");
+    GitRepo::clone_with_stats(&origin_url, &local_path, DEFAULT_REMOTE).unwrap();
+    assert_eq!(GitRepo::update(&local_path, DEFAULT_REMOTE, false).unwrap(), UpdateResult::UpToDate);
+
+    // A new upstream commit is fast-forwarded into the working tree
+    commit_file(&upstream, "CHANGELOG.md", "two");
+    remote.push(&[format!("{0}:{0}", head)], None).unwrap();
+
+    assert_eq!(
+        GitRepo::update(&local_path, DEFAULT_REMOTE, false).unwrap(),
+        UpdateResult::FastForwarded(1)
+    );
+    assert!(local_path.join("CHANGELOG.md").exists());
+    assert_eq!(GitRepo::status(&local_path, DEFAULT_REMOTE).unwrap().behind, 0);
+}
+
+#[test]
+fn test_backend_status() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let origin_path = temp_dir.path().join("origin.git");
+    Repository::init_bare(&origin_path).unwrap();
+    let origin_url = origin_path.to_string_lossy().to_string();
+
+    let upstream = Repository::clone(&origin_url, temp_dir.path().join("upstream")).unwrap();
+    commit_file(&upstream, "README.md", "one");
+    let mut remote = upstream.find_remote("origin").unwrap();
+    let head = upstream.head().unwrap().name().unwrap().to_string();
+    remote.push(&[format!("{0}:{0}", head)], None).unwrap();
+
+    // One local commit, one upstream commit and an untracked file
+    let local_path = temp_dir.path().join("local");
+    let (local, _) = GitRepo::clone_with_stats(&origin_url, &local_path, DEFAULT_REMOTE).unwrap();
+    commit_file(&local, "local.txt", "mine");
+    commit_file(&upstream, "CHANGELOG.md", "two");
+    remote.push(&[format!("{0}:{0}", head)], None).unwrap();
+    Git2Backend.fetch(&local_path, "origin", None).unwrap();
+    fs::write(local_path.join("notes.txt"), "draft").unwrap();
+
+    let expected = RepoStatus {
+        branch: Some(head.trim_start_matches("refs/heads/").to_string()),
+        ahead: 1,
+        behind: 1,
+        dirty: true,
+    };
+    assert_eq!(Git2Backend.status(&local_path, DEFAULT_REMOTE).unwrap(), expected);
+    #[cfg(feature = "gix")]
+    assert_eq!(backend::GixBackend.status(&local_path, DEFAULT_REMOTE).unwrap(), expected);
+
+    // gix can only be selected when it was compiled in
+    let _backend = ScopedBackend::current();
+    assert_eq!(backend::set_backend(BackendKind::Gix).is_ok(), cfg!(feature = "gix"));
+}
+
+/// Restores the selected backend when dropped
+struct ScopedBackend(BackendKind);
+
+impl ScopedBackend {
+    fn current() -> Self {
+        Self(backend::backend())
+    }
+}
+
+impl Drop for ScopedBackend {
+    fn drop(&mut self) {
+        backend::set_backend(self.0).unwrap();
+    }
+}
+
+#[test]
+fn test_same_remote() {
+    assert!(GitRepo::same_remote(
+        "https://github.com/test-org/api.git",
+        "https://github.com/test-org/api"
+    ));
+    assert!(GitRepo::same_remote(
+        "git@github.com:test-org/api.git",
+        "git@github.com:test-org/api/"
+    ));
+    assert!(!GitRepo::same_remote(
+        "git@github.com:test-org/api.git",
+        "https://github.com/test-org/api.git"
+    ));
+}
+
+#[test]
+fn test_convert_url() {
+    assert_eq!(
+        GitRepo::convert_url("https://github.com/test-org", true).as_deref(),
+        Some("git@github.com:test-org")
+    );
+    assert_eq!(
+        GitRepo::convert_url("git@github.com:test-org/", false).as_deref(),
+        Some("https://github.com/test-org")
+    );
+    assert_eq!(
+        GitRepo::convert_url("https://token@github.example.com/team", true).as_deref(),
+        Some("git@github.example.com:team")
+    );
+    assert_eq!(
+        GitRepo::convert_url("ssh://git@git.corp.example:2222/team", true).as_deref(),
+        Some("ssh://git@git.corp.example:2222/team")
+    );
+    assert_eq!(
+        GitRepo::convert_url("ssh://git@git.corp.example:2222/team", false).as_deref(),
+        Some("https://git.corp.example/team")
+    );
+    assert_eq!(
+        GitRepo::convert_url("https://git.corp.example:8443/team", true).as_deref(),
+        Some("git@git.corp.example:team")
+    );
+    assert_eq!(GitRepo::convert_url("not-a-url", true), None);
+}
+
+#[test]
+fn test_rename_org() {
+    assert_eq!(
+        GitRepo::rename_org("git@github.com:old-org", "old-org", "new-org").as_deref(),
+        Some("git@github.com:new-org")
+    );
+    assert_eq!(
+        GitRepo::rename_org(
+            "https://github.com/old-org/",
+            "github.com/old-org",
+            "ghe.example.com/new-org"
+        )
+        .as_deref(),
+        Some("https://ghe.example.com/new-org")
+    );
+    assert_eq!(
+        GitRepo::rename_org("ssh://git@git.corp.example:2222/old-org", "old-org", "new-org").as_deref(),
+        Some("ssh://git@git.corp.example:2222/new-org")
+    );
+    assert_eq!(
+        GitRepo::build_repo_url("ssh://git@git.corp.example:2222/new-org", "api"),
+        "ssh://git@git.corp.example:2222/new-org/api.git"
+    );
+    assert_eq!(GitRepo::rename_org("https://github.com/other", "old-org", "new-org"), None);
+    assert_eq!(
+        GitRepo::split_repo_url("git@github.com:old-org/api.git"),
+        Some(("git@github.com:old-org".to_string(), "api".to_string()))
+    );
+}
+
+#[test]
+fn test_detect_provider_from_origin() {
+    let owner = |url: &str| GitRepo::remote_owner(url);
+    assert_eq!(owner("git@github.com:acme/web.git"), Some(("github.com".to_string(), "acme".to_string())));
+    assert_eq!(
+        owner("https://token@gitlab.example.com/platform/tools/cli.git"),
+        Some(("gitlab.example.com".to_string(), "platform/tools".to_string()))
+    );
+    assert_eq!(owner("/srv/git/web.git"), None);
+
+    assert_eq!(HostProvider::detect("github.com"), HostProvider::Github);
+    assert_eq!(HostProvider::detect("github.acme.com"), HostProvider::GithubEnterprise);
+    assert_eq!(HostProvider::detect("gitlab.example.com"), HostProvider::Gitlab);
+    assert_eq!(HostProvider::detect("bitbucket.org"), HostProvider::Bitbucket);
+    assert_eq!(HostProvider::detect("git.example.com"), HostProvider::Custom);
+    assert_eq!(
+        HostProvider::Gitlab.base_url("https://gitlab.example.com/", "platform/tools", true),
+        "git@gitlab.example.com:platform/tools"
+    );
+}
+
+/// Network limits set for a test, restored when it ends (even by a panic)
+struct ScopedTimeouts(NetworkTimeouts);
+
+impl ScopedTimeouts {
+    fn set(timeouts: NetworkTimeouts) -> Self {
+        let previous = GitRepo::timeouts();
+        GitRepo::set_timeouts(timeouts);
+        Self(previous)
+    }
+}
+
+impl Drop for ScopedTimeouts {
+    fn drop(&mut self) {
+        GitRepo::set_timeouts(self.0);
+    }
+}
+
+#[test]
+fn test_certificate_check() {
+    let previous = GitRepo::is_insecure_skip_verify();
+
+    // Verified by libgit2 unless tls.insecure_skip_verify is set
+    GitRepo::set_insecure_skip_verify(false);
+    assert!(!GitRepo::skips_certificate_check(true));
+
+    // Even then, SSH host keys are still checked
+    GitRepo::set_insecure_skip_verify(true);
+    assert!(GitRepo::skips_certificate_check(true));
+    assert!(!GitRepo::skips_certificate_check(false));
+
+    GitRepo::set_insecure_skip_verify(previous);
+}
+
+#[test]
+fn test_clone_connect_timeout() {
+    // A server that accepts connections and only hangs up after a while
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        for connection in listener.incoming() {
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_secs(2));
+                drop(connection);
+            });
+        }
+    });
+
+    let _timeouts = ScopedTimeouts::set(NetworkTimeouts {
+        connect: Some(Duration::from_secs(1)),
+        total: None,
+    });
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("hung");
+    let started = Instant::now();
+    let result = GitRepo::clone_with_stats(&format!("http://127.0.0.1:{}/org/hung.git", port), &path, DEFAULT_REMOTE);
+
+    // The clone is given up after the connect limit, then waited for until it stops
+    assert!(matches!(result, Err(BasecampError::Timeout(ref repo, _)) if repo == "hung"));
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_resolve_proxy() {
+    let env = |vars: &'static [(&'static str, &'static str)]| {
+        move |name: &str| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    };
+    let url = "https://github.com/test-org/api.git";
+
+    // Environment variables, by scheme
+    let vars = &[("https_proxy", "http://secure:3128"), ("HTTP_PROXY", "http://plain:3128")];
+    assert_eq!(GitRepo::resolve_proxy(url, None, env(vars)).as_deref(), Some("http://secure:3128"));
+    assert_eq!(
+        GitRepo::resolve_proxy("http://git.corp/api.git", None, env(vars)).as_deref(),
+        Some("http://plain:3128")
+    );
+
+    // The configured proxy wins, SSH remotes never use one
+    assert_eq!(
+        GitRepo::resolve_proxy(url, Some("http://configured:8080"), env(vars)).as_deref(),
+        Some("http://configured:8080")
+    );
+    assert_eq!(GitRepo::resolve_proxy("git@github.com:test-org/api.git", None, env(vars)), None);
+
+    // no_proxy hosts and domains are reached directly
+    let vars = &[("HTTPS_PROXY", "http://secure:3128"), ("no_proxy", "localhost, .github.com")];
+    assert_eq!(GitRepo::resolve_proxy(url, Some("http://configured:8080"), env(vars)), None);
+    assert_eq!(
+        GitRepo::resolve_proxy("https://gitlab.com/test-org/api.git", None, env(vars)).as_deref(),
+        Some("http://secure:3128")
+    );
+    assert_eq!(GitRepo::resolve_proxy(url, None, env(&[])), None);
+}
+
+#[test]
+fn test_apply_url_rewrites() {
+    let rewrites = vec![
+        ("git@github.com:".to_string(), "https://github.com/".to_string()),
+        ("git@work:".to_string(), "https://github.com/work-org/".to_string()),
+    ];
+
+    // The longest matching prefix wins
+    assert_eq!(
+        GitRepo::apply_url_rewrites("https://github.com/work-org/api.git", &rewrites),
+        "git@work:api.git"
+    );
+    assert_eq!(
+        GitRepo::apply_url_rewrites("https://github.com/test-org/api.git", &rewrites),
+        "git@github.com:test-org/api.git"
+    );
+    assert_eq!(
+        GitRepo::apply_url_rewrites("https://gitlab.com/test-org/api.git", &rewrites),
+        "https://gitlab.com/test-org/api.git"
+    );
+}
+
+/// Test provider handing out a fixed number of user name credentials
+struct Counted {
+    name: &'static str,
+    left: usize,
+}
+
+impl CredentialProvider for Counted {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn next(&mut self, _request: &CredentialRequest<'_>) -> Option<git2::Cred> {
+        self.left = self.left.checked_sub(1)?;
+        git2::Cred::userpass_plaintext(self.name, "secret").ok()
+    }
+}
+
+#[test]
+fn test_credential_chain() {
+    let request = CredentialRequest {
+        url: "https://github.com/test-org/api.git",
+        username: "git",
+        allowed: git2::CredentialType::USER_PASS_PLAINTEXT,
+    };
+
+    // Providers are asked in order until each runs out
+    let mut chain = CredentialChain::new(vec![
+        Box::new(Counted { name: "first", left: 1 }),
+        Box::new(Counted { name: "empty", left: 0 }),
+        Box::new(Counted { name: "second", left: 2 }),
+    ]);
+    for _ in 0..3 {
+        assert!(chain.next(&request).is_ok());
+    }
+    assert!(chain.next(&request).is_err());
+
+    // Attempts are capped whatever the providers still offer
+    let mut chain = CredentialChain::new(vec![Box::new(Counted { name: "many", left: 100 })]);
+    let accepted = (0..100).filter(|_| chain.next(&request).is_ok()).count();
+    assert_eq!(accepted, 6);
+}
+
+#[test]
+fn test_token_scoped_to_host() {
+    let env = |name: &str| {
+        ["BASECAMP_TOKEN", "GITHUB_TOKEN", "GITLAB_TOKEN"]
+            .contains(&name)
+            .then(|| format!("{}-value", name))
+    };
+    let enterprise = TokenScope {
+        host: "github.corp.example".to_string(),
+        provider: HostProvider::GithubEnterprise,
+    };
+    let gitlab = TokenScope {
+        host: "git.corp.example".to_string(),
+        provider: HostProvider::Gitlab,
+    };
+    let token = |url: &str, scope: Option<&TokenScope>| auth::token_for_url(url, scope, env);
+
+    // BASECAMP_TOKEN goes to the configured host, the others to their own services
+    assert_eq!(
+        token("https://github.corp.example/team/api.git", Some(&enterprise)).as_deref(),
+        Some("BASECAMP_TOKEN-value")
+    );
+    assert_eq!(token("https://github.com/org/api.git", Some(&gitlab)).as_deref(), Some("GITHUB_TOKEN-value"));
+    assert_eq!(token("https://gitlab.com/org/api.git", None).as_deref(), Some("GITLAB_TOKEN-value"));
+    assert_eq!(
+        auth::token_variables("git.corp.example:8443", Some(&gitlab)),
+        ["BASECAMP_TOKEN", "GITLAB_TOKEN"]
+    );
+    assert_eq!(auth::token_variables("github.corp.example", Some(&enterprise)), ["BASECAMP_TOKEN", "GITHUB_TOKEN", "GH_TOKEN"]);
+
+    // Hosts a token doesn't belong to get none
+    assert_eq!(token("https://git.other.example/org/api.git", Some(&enterprise)), None);
+    assert_eq!(token("https://github.corp.example.evil.io/org/api.git", Some(&enterprise)), None);
+    assert!(auth::token_variables("git.corp.example", Some(&enterprise)).is_empty());
+    assert!(!auth::token_variables("github.com", Some(&gitlab)).contains(&"GITLAB_TOKEN"));
+    assert!(!auth::token_variables("git.corp.example", Some(&gitlab)).contains(&"GITHUB_TOKEN"));
+
+    // Never over plain HTTP or SSH
+    assert_eq!(token("http://github.corp.example/team/api.git", Some(&enterprise)), None);
+    assert_eq!(token("git@github.com:org/api.git", None), None);
+}
+
+#[test]
+fn test_health_from_answers() {
+    use serde_json::json;
+
+    // GitHub: the worst of check runs and the combined status; renamed within the owner
+    let answers = HealthAnswers {
+        repository: json!({"default_branch": "main", "full_name": "test-org/api-v2", "archived": false}),
+        runs: vec![
+            json!({"status": "completed", "conclusion": "success"}),
+            json!({"status": "in_progress", "conclusion": null}),
+        ],
+        combined_status: Some(json!({"total_count": 1, "state": "success"})),
+        open_pull_requests: 3,
+    };
+    let health = HostProvider::Github.health_from("test-org", "api", &answers);
+    assert_eq!(health.default_branch.as_deref(), Some("main"));
+    assert_eq!(health.ci, Some(CiState::Pending));
+    assert_eq!(health.open_pull_requests, 3);
+    assert_eq!(health.renamed_within("test-org"), Some("api-v2"));
+    assert!(!health.archived);
+
+    // An empty combined status is no CI; a failed check run is a failure
+    let answers = HealthAnswers {
+        repository: json!({"default_branch": "main", "full_name": "test-org/api"}),
+        runs: vec![json!({"status": "completed", "conclusion": "timed_out"})],
+        combined_status: Some(json!({"total_count": 0, "state": "pending"})),
+        ..HealthAnswers::default()
+    };
+    let health = HostProvider::GithubEnterprise.health_from("test-org", "api", &answers);
+    assert_eq!(health.ci, Some(CiState::Failure));
+    assert_eq!(health.moved_to, None);
+
+    // GitLab: the latest pipeline only, skipped pipelines are no CI
+    let project = json!({"default_branch": "develop", "path_with_namespace": "team/sub/api", "archived": true});
+    let gitlab = |status: &str| HealthAnswers {
+        repository: project.clone(),
+        runs: vec![json!({ "status": status }), json!({"status": "failed"})],
+        ..HealthAnswers::default()
+    };
+    let health = HostProvider::Gitlab.health_from("team/sub", "api", &gitlab("success"));
+    assert_eq!(health.ci, Some(CiState::Success));
+    assert_eq!(health.default_branch.as_deref(), Some("develop"));
+    assert!(health.archived);
+    assert_eq!(HostProvider::Gitlab.health_from("team/sub", "api", &gitlab("skipped")).ci, None);
+    assert_eq!(HostProvider::Gitlab.health_from("team/sub", "api", &gitlab("running")).ci, Some(CiState::Pending));
+
+    // Bitbucket: the worst commit status, moved to another workspace
+    let answers = HealthAnswers {
+        repository: json!({"mainbranch": {"name": "master"}, "full_name": "elsewhere/api"}),
+        runs: vec![json!({"state": "SUCCESSFUL"}), json!({"state": "STOPPED"})],
+        ..HealthAnswers::default()
+    };
+    let health = HostProvider::Bitbucket.health_from("team", "api", &answers);
+    assert_eq!(health.default_branch.as_deref(), Some("master"));
+    assert_eq!(health.ci, Some(CiState::Failure));
+    assert_eq!(health.moved_to.as_deref(), Some("elsewhere/api"));
+    assert_eq!(health.renamed_within("team"), None);
+
+    // Without a default branch there is no CI to read
+    let answers = HealthAnswers {
+        repository: json!({"full_name": "test-org/empty"}),
+        runs: vec![json!({"status": "completed", "conclusion": "failure"})],
+        ..HealthAnswers::default()
+    };
+    assert_eq!(HostProvider::Github.health_from("test-org", "empty", &answers), RemoteHealth::default());
+}
+
+#[test]
+fn test_key_candidates() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let ssh_dir = temp_dir.path();
+    for name in ["id_rsa", "id_rsa.pub", "work", "work.pub", "orphan", "known_hosts"] {
+        fs::write(ssh_dir.join(name), "").unwrap();
+    }
+    let corp_key = temp_dir.path().join("keys/corp");
+    fs::write(ssh_dir.join("config"), format!("Host corp\n  IdentityFile {}\n", corp_key.display())).unwrap();
+
+    let configured = ssh_dir.join("configured");
+    let keys = auth::key_candidates(Some(&configured), ssh_dir);
+
+    // The configured key, standard names, IdentityFiles, then other keys with a .pub
+    assert_eq!(keys[0], configured);
+    assert_eq!(keys[1], ssh_dir.join("id_ed25519"));
+    assert!(keys.contains(&corp_key));
+    assert_eq!(keys.last(), Some(&ssh_dir.join("work")));
+    assert!(!keys.contains(&ssh_dir.join("orphan")));
+    assert_eq!(keys.iter().filter(|key| **key == ssh_dir.join("id_rsa")).count(), 1);
+}
+
+#[test]
+fn test_parse_helper_output() {
+    assert_eq!(
+        auth::parse_helper_output("protocol=https\nhost=github.com\nusername=me\npassword=s3cr=t\n"),
+        Some(("me".to_string(), "s3cr=t".to_string()))
+    );
+    assert_eq!(auth::parse_helper_output("protocol=https\nhost=github.com\n"), None);
+}
@@ -0,0 +1,42 @@
+// Kept apart from the other tests: loading a configuration sets process-wide
+// settings, which other tests of the same binary would change concurrently.
+mod common;
+
+use basecamp::config::Config;
+use basecamp::git::GitRepo;
+use basecamp::messages;
+
+#[test]
+fn test_workspace_settings_dont_leak() {
+    common::isolate_home();
+    // Setup - an offline workspace with translated messages, and a default one
+    let (temp_dir, temp_path) = common::setup_temp_dir();
+    let offline = temp_path.join("offline");
+    let basecamp_dir = common::create_test_config(&offline);
+    std::fs::write(basecamp_dir.join("messages.yaml"), "hint.offline: Hors ligne\n").unwrap();
+    std::fs::write(
+        basecamp_dir.join("config.yaml"),
+        "github_url: https://github.com/test-org\noffline: true\ncolor: false\nui:\n  messages: .basecamp/messages.yaml\n",
+    )
+    .unwrap();
+    let online = temp_path.join("online");
+    common::create_test_config(&online);
+    let english = messages::text("hint.offline");
+
+    Config::load(&offline).unwrap();
+    assert!(GitRepo::is_offline());
+    assert_eq!(messages::text("hint.offline"), "Hors ligne");
+
+    // Loading another workspace replaces its settings instead of keeping them
+    Config::load(&online).unwrap();
+    assert!(!GitRepo::is_offline());
+    assert_eq!(messages::text("hint.offline"), english);
+
+    // --offline still applies to every workspace
+    GitRepo::set_offline(true);
+    Config::load(&online).unwrap();
+    assert!(GitRepo::is_offline());
+    GitRepo::set_offline(false);
+
+    common::teardown(temp_dir);
+}